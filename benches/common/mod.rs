@@ -0,0 +1,196 @@
+//! Generators for synthetic OCFL objects that are large enough to be worth benchmarking. The
+//! objects are written directly to disk, rather than committed through `OcflRepo`, so that
+//! generating deep version chains does not dominate the time it takes to run the benchmarks.
+
+#![allow(dead_code)]
+
+use std::fs;
+use std::path::Path;
+
+use rocfl::ocfl::{DigestAlgorithm, OcflRepo, RepoInitOptions, SpecVersion};
+use serde_json::{json, Map, Value};
+
+const INVENTORY_TYPE: &str = "https://ocfl.io/1.0/spec/#inventory";
+const OBJECT_NAMASTE: &str = "0=ocfl_object_1.0";
+const CREATED: &str = "2022-01-01T00:00:00Z";
+
+/// An object in which every version adds `files_per_version` files and updates the first file
+/// of the previous version, so that each version's state grows and its files were last updated
+/// in a spread of different versions.
+pub struct SyntheticObject {
+    pub id: String,
+    pub versions: usize,
+    pub files_per_version: usize,
+    /// The number of directories each logical path is nested within
+    pub depth: usize,
+    /// When true, only the first version adds files, and every later version updates one of
+    /// them in turn, so that the size of each version's state does not grow with the number of
+    /// versions
+    pub fixed_state: bool,
+}
+
+impl SyntheticObject {
+    pub fn new(id: &str, versions: usize, files_per_version: usize) -> Self {
+        Self {
+            id: id.to_string(),
+            versions,
+            files_per_version,
+            depth: 3,
+            fixed_state: false,
+        }
+    }
+
+    pub fn with_depth(mut self, depth: usize) -> Self {
+        self.depth = depth;
+        self
+    }
+
+    pub fn with_fixed_state(mut self) -> Self {
+        self.fixed_state = true;
+        self
+    }
+
+    /// Returns the logical path of the nth file that was added in the version
+    pub fn logical_path(&self, version: usize, n: usize) -> String {
+        let mut path = String::new();
+        for level in 0..self.depth {
+            path.push_str(&format!("dir-{}/", (n >> level) % 4));
+        }
+        path.push_str(&format!("v{}-file-{}.txt", version, n));
+        path
+    }
+
+    /// Returns every logical path in the head version
+    pub fn head_logical_paths(&self) -> Vec<String> {
+        let versions = if self.fixed_state { 1 } else { self.versions };
+        (1..=versions)
+            .flat_map(|version| {
+                (0..self.files_per_version).map(move |n| self.logical_path(version, n))
+            })
+            .collect()
+    }
+
+    /// Returns the inventory of the object's head version
+    pub fn inventory(&self) -> Value {
+        self.build(|_, _| ())
+    }
+
+    pub fn inventory_bytes(&self) -> Vec<u8> {
+        serde_json::to_vec(&self.inventory()).unwrap()
+    }
+
+    /// Writes the object, including its content files, to `object_root`
+    pub fn write(&self, object_root: &Path) {
+        let inventory = self.build(|content_path, content| {
+            let path = object_root.join(content_path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        });
+
+        let bytes = serde_json::to_vec(&inventory).unwrap();
+        let digest = DigestAlgorithm::Sha512
+            .hash_hex(&mut bytes.as_slice())
+            .unwrap();
+        let sidecar = format!("{}  inventory.json\n", digest);
+
+        let head_dir = object_root.join(format!("v{}", self.versions));
+
+        for dir in [object_root, head_dir.as_path()] {
+            fs::create_dir_all(dir).unwrap();
+            fs::write(dir.join("inventory.json"), &bytes).unwrap();
+            fs::write(dir.join("inventory.json.sha512"), &sidecar).unwrap();
+        }
+
+        fs::write(object_root.join(OBJECT_NAMASTE), "ocfl_object_1.0\n").unwrap();
+    }
+
+    /// Builds the inventory, calling `add_content` with the content path and content of every
+    /// file in the manifest
+    fn build(&self, mut add_content: impl FnMut(&str, &str)) -> Value {
+        let mut manifest = Map::new();
+        let mut versions = Map::new();
+        let mut state = Map::new();
+
+        for version in 1..=self.versions {
+            if version > 1 {
+                let updated = if self.fixed_state {
+                    self.logical_path(1, (version - 2) % self.files_per_version)
+                } else {
+                    self.logical_path(version - 1, 0)
+                };
+                state.retain(|_, paths| paths[0] != updated.as_str());
+                let digest = self.add_file(version, &updated, &mut manifest, &mut add_content);
+                state.insert(digest, json!([updated]));
+            }
+
+            let added = if self.fixed_state && version > 1 {
+                0
+            } else {
+                self.files_per_version
+            };
+
+            for n in 0..added {
+                let logical_path = self.logical_path(version, n);
+                let digest =
+                    self.add_file(version, &logical_path, &mut manifest, &mut add_content);
+                state.insert(digest, json!([logical_path]));
+            }
+
+            versions.insert(
+                format!("v{}", version),
+                json!({
+                    "created": CREATED,
+                    "message": format!("version {}", version),
+                    "user": {
+                        "name": "Benchmark",
+                        "address": "mailto:benchmark@example.com"
+                    },
+                    "state": state.clone(),
+                }),
+            );
+        }
+
+        json!({
+            "id": self.id,
+            "type": INVENTORY_TYPE,
+            "digestAlgorithm": "sha512",
+            "head": format!("v{}", self.versions),
+            "manifest": manifest,
+            "versions": versions,
+        })
+    }
+
+    fn add_file(
+        &self,
+        version: usize,
+        logical_path: &str,
+        manifest: &mut Map<String, Value>,
+        add_content: &mut impl FnMut(&str, &str),
+    ) -> String {
+        let content = format!("{} {}", version, logical_path);
+        let digest = DigestAlgorithm::Sha512
+            .hash_hex(&mut content.as_bytes())
+            .unwrap()
+            .to_string();
+        let content_path = format!("v{}/content/{}", version, logical_path);
+
+        add_content(&content_path, &content);
+        manifest.insert(digest.clone(), json!([content_path]));
+
+        digest
+    }
+}
+
+/// Creates a repository without a storage layout at `root` that contains the objects. Each
+/// object's root is a directory named after its ID.
+pub fn synthetic_repo(root: &Path, objects: &[SyntheticObject]) -> OcflRepo {
+    let options = RepoInitOptions { spec_copy: false };
+    let repo =
+        OcflRepo::init_fs_repo_with_options(root, None, SpecVersion::Ocfl1_0, None, options).unwrap();
+
+    for object in objects {
+        object.write(&root.join(&object.id));
+    }
+
+    repo
+}
@@ -0,0 +1,148 @@
+//! Benchmarks of the operations whose performance is dominated by inventories and the path
+//! types, run with `cargo bench --features bench`.
+
+use std::convert::TryFrom;
+use std::rc::Rc;
+
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use rocfl::ocfl::{
+    parse_inventory, ContentPath, Inventory, LogicalPath, ObjectVersion, ValidationResult,
+    VersionNum, VersionRef,
+};
+use tempfile::TempDir;
+
+use common::{synthetic_repo, SyntheticObject};
+
+mod common;
+
+fn inventory_parsing(c: &mut Criterion) {
+    let mut group = c.benchmark_group("inventory_parsing");
+
+    for (versions, files) in [(10, 100), (100, 100)] {
+        let bytes = SyntheticObject::new("parse", versions, files).inventory_bytes();
+        let id = format!("{}x{}", versions, files);
+
+        group.bench_with_input(BenchmarkId::new("deserialize", &id), &bytes, |b, bytes| {
+            b.iter(|| serde_json::from_slice::<Inventory>(bytes).unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new("validate", &id), &bytes, |b, bytes| {
+            b.iter(|| parse_inventory(bytes).0.unwrap())
+        });
+    }
+
+    group.finish();
+}
+
+fn state_reconstruction(c: &mut Criterion) {
+    let root = TempDir::new().unwrap();
+    let repo = synthetic_repo(root.path(), &[SyntheticObject::new("deep", 500, 2)]);
+
+    let mut group = c.benchmark_group("state_reconstruction");
+    group.sample_size(20);
+
+    group.bench_function("head", |b| {
+        b.iter(|| repo.get_object("deep", VersionRef::Head).unwrap())
+    });
+    group.bench_function("v1", |b| {
+        b.iter(|| repo.get_object("deep", VersionNum::v1().into()).unwrap())
+    });
+
+    // Excludes parsing so that only the cost of computing each file's last update is measured.
+    // Each version of the first object adds a file, so the size of its inventory grows with the
+    // square of the number of versions, and each version of the second object updates one of
+    // 100 files, so the size of its inventory grows linearly.
+    let objects = [500, 1000].into_iter().map(|versions| {
+        let object = SyntheticObject::new("chain", versions, 1);
+        (BenchmarkId::new("construct_state", versions), object)
+    });
+    let fixed_objects = [1000, 2000, 4000].into_iter().map(|versions| {
+        let object = SyntheticObject::new("chain", versions, 100).with_fixed_state();
+        (BenchmarkId::new("construct_state_fixed", versions), object)
+    });
+
+    for (id, object) in objects.chain(fixed_objects) {
+        let bytes = object.inventory_bytes();
+
+        group.bench_with_input(id, &bytes, |b, bytes| {
+            b.iter_batched(
+                || serde_json::from_slice::<Inventory>(bytes).unwrap(),
+                |inventory| {
+                    ObjectVersion::from_inventory(inventory, VersionRef::Head, "chain", None, false)
+                        .unwrap()
+                },
+                BatchSize::LargeInput,
+            )
+        });
+    }
+
+    group.finish();
+}
+
+fn manifest_lookups(c: &mut Criterion) {
+    let object = SyntheticObject::new("lookup", 20, 500).with_depth(6);
+    let inventory: Inventory = serde_json::from_slice(&object.inventory_bytes()).unwrap();
+
+    let logical_paths: Vec<LogicalPath> = object
+        .head_logical_paths()
+        .iter()
+        .map(|path| LogicalPath::try_from(path.as_str()).unwrap())
+        .collect();
+    let content_paths: Vec<Rc<ContentPath>> = inventory.manifest_paths().into_iter().collect();
+
+    let mut group = c.benchmark_group("manifest_lookups");
+
+    group.bench_function("content_path_for_logical_path", |b| {
+        b.iter(|| {
+            for path in &logical_paths {
+                inventory
+                    .content_path_for_logical_path(path, VersionRef::Head)
+                    .unwrap();
+            }
+        })
+    });
+    group.bench_function("digest_for_content_path", |b| {
+        b.iter(|| {
+            for path in &content_paths {
+                inventory.digest_for_content_path(path).unwrap();
+            }
+        })
+    });
+    group.bench_function("resolve_glob", |b| {
+        b.iter(|| {
+            inventory
+                .head_version()
+                .resolve_glob("dir-1/*/dir-3/**", true)
+                .unwrap()
+        })
+    });
+
+    group.finish();
+}
+
+fn validation(c: &mut Criterion) {
+    let root = TempDir::new().unwrap();
+    let repo = synthetic_repo(root.path(), &[SyntheticObject::new("large", 10, 1000)]);
+
+    assert!(!repo.validate_object("large", false).unwrap().has_errors());
+
+    let mut group = c.benchmark_group("validation");
+    group.sample_size(10);
+
+    group.bench_function("structure", |b| {
+        b.iter(|| repo.validate_object("large", false).unwrap())
+    });
+    group.bench_function("fixity", |b| {
+        b.iter(|| repo.validate_object("large", true).unwrap())
+    });
+
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    inventory_parsing,
+    state_reconstruction,
+    manifest_lookups,
+    validation
+);
+criterion_main!(benches);
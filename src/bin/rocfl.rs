@@ -1,7 +1,7 @@
 use std::process;
 
 use clap::Parser;
-use log::{error, LevelFilter};
+use log::error;
 use rocfl::cmd::opts::*;
 use rocfl::config::Config;
 use rocfl::ocfl::RocflError;
@@ -10,20 +10,10 @@ use rocfl::{cmd, config};
 fn main() {
     let mut args = RocflArgs::parse();
 
-    let log_level = if args.quiet {
-        LevelFilter::Off
-    } else if args.verbose {
-        LevelFilter::Info
-    } else {
-        LevelFilter::Warn
-    };
-
-    env_logger::builder()
-        .filter_level(log_level)
-        .format_timestamp(None)
-        .format_module_path(false)
-        .format_target(false)
-        .init();
+    if let Err(e) = cmd::init_logging(&args) {
+        eprintln!("{:#}", e);
+        process::exit(1);
+    }
 
     let config = match config::load_config(&args.name) {
         Ok(config) => config,
@@ -48,6 +38,11 @@ fn main() {
             }
             _ => error!("{:#}", e),
         }
-        process::exit(1);
+        process::exit(cmd::exit_status().max(1));
+    }
+
+    let status = cmd::exit_status();
+    if status != 0 {
+        process::exit(status);
     }
 }
@@ -1,18 +1,36 @@
+use std::collections::BTreeMap;
 use std::convert::TryInto;
+use std::fs::{self, File, OpenOptions};
 use std::io;
-use std::io::{BufWriter, Write};
-use std::sync::atomic::AtomicBool;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 
-use log::info;
+use log::{error, info, warn};
 
+use crate::cmd::list::print_skipped_summary;
 use crate::cmd::opts::{
-    CatCmd, CommitCmd, ConfigCmd, CopyCmd, DigestAlgorithm as OptAlgorithm, Field, InfoCmd,
-    InitCmd, ListCmd, MoveCmd, NewCmd, PurgeCmd, RemoveCmd, ResetCmd, ShowCmd, StatusCmd,
-    UpgradeCmd,
+    AdoptCmd, AliasCmd, AliasCommand, ApplyCmd, CatCmd, CommitCmd, ConfigCmd, CopyCmd,
+    DigestAlgorithm as OptAlgorithm, DoctorCmd, ExportCmd, ExtensionsCmd, Field, FindCmd,
+    FreezeCmd, GcCmd, GrepCmd, InfoCmd, InitCmd, InspectInventoryCmd, LayoutCmd, LayoutCommand,
+    ListCmd, ListFormat, LogsCmd, MetaCmd, MetaCommand, MoveCmd, NewCmd, PurgeCmd, RebaseCmd,
+    RemoveCmd, RenameCmd, ResetCmd, ShowCmd, SquashCmd, StageCmd, StageCommand, StatusCmd,
+    Symlinks, Timestamps, UnfreezeCmd, UpgradeCmd, VerifySignaturesCmd, ZipCmd, ZipCommand,
+    ZipExportArgs, ZipImportArgs,
+};
+use crate::cmd::table::{Alignment, AsRow, Column, ColumnId, Row, Separator, TableView, TextCell};
+use crate::cmd::validate::print_staged_result;
+use crate::cmd::{
+    map_spec_version, paint, println, raise_exit_status, style, Cmd, GlobalArgs, DATE_FORMAT,
 };
-use crate::cmd::{map_spec_version, println, style, Cmd, GlobalArgs};
 use crate::config::Config;
-use crate::ocfl::{CommitMeta, DigestAlgorithm, OcflRepo, Result};
+use crate::ocfl::{
+    CommitMeta, DigestAlgorithm, InventoryPath, ObjectAlias, ObjectPatch, ObjectTemplate, OcflRepo,
+    Result, RocflError, SignatureStatus, SigningKey, SymlinkPolicy, ValidationResult, VerifyingKey,
+};
+
+/// The number of times 'commit --retry' rebases the staged changes before giving up
+const MAX_COMMIT_RETRIES: usize = 3;
 
 impl Cmd for CatCmd {
     fn exec(
@@ -22,21 +40,336 @@ impl Cmd for CatCmd {
         _config: &Config,
         _terminate: &AtomicBool,
     ) -> Result<()> {
-        if self.staged {
-            repo.get_staged_object_file(
+        if self.path.version.is_some() && (self.staged || self.version.is_some()) {
+            return Err(RocflError::InvalidValue(format!(
+                "{} specifies a version, which cannot be combined with '--staged' or '--version'",
+                self.path
+            )));
+        }
+
+        let version_num = self.version.or(self.path.version).into();
+
+        match (self.staged, self.verify) {
+            (true, false) => {
+                repo.get_staged_object_file(&self.object_id, &self.path.path, &mut io::stdout())
+            }
+            (true, true) => repo.get_staged_object_file_verified(
                 &self.object_id,
-                &self.path.as_str().try_into()?,
+                &self.path.path,
                 &mut io::stdout(),
-            )
-        } else {
-            repo.get_object_file(
+            ),
+            (false, false) => repo.get_object_file(
                 &self.object_id,
-                &self.path.as_str().try_into()?,
-                self.version.into(),
+                &self.path.path,
+                version_num,
                 &mut io::stdout(),
-            )
+            ),
+            (false, true) => repo.get_object_file_verified(
+                &self.object_id,
+                &self.path.path,
+                version_num,
+                &mut io::stdout(),
+            ),
+        }
+    }
+}
+
+impl Cmd for LogsCmd {
+    fn exec(
+        &self,
+        repo: &OcflRepo,
+        _args: GlobalArgs,
+        _config: &Config,
+        _terminate: &AtomicBool,
+    ) -> Result<()> {
+        match &self.file {
+            Some(file) => repo.get_object_log_file(&self.object_id, file, &mut io::stdout()),
+            None => {
+                let logs = repo.list_object_logs(&self.object_id)?;
+
+                let out = io::stdout();
+                let mut writer = BufWriter::new(out.lock());
+                for log in logs {
+                    let _ = writeln!(writer, "{}", log);
+                }
+                let _ = writer.flush();
+
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Cmd for ExtensionsCmd {
+    fn exec(
+        &self,
+        repo: &OcflRepo,
+        _args: GlobalArgs,
+        _config: &Config,
+        _terminate: &AtomicBool,
+    ) -> Result<()> {
+        match &self.cat {
+            Some(file) => repo.get_object_extension_file(&self.object_id, file, &mut io::stdout()),
+            None => {
+                let files = repo.list_object_extension_files(&self.object_id)?;
+
+                let out = io::stdout();
+                let mut writer = BufWriter::new(out.lock());
+                for file in files {
+                    let _ = writeln!(writer, "{}", file);
+                }
+                let _ = writer.flush();
+
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Cmd for GrepCmd {
+    fn exec(
+        &self,
+        repo: &OcflRepo,
+        args: GlobalArgs,
+        _config: &Config,
+        _terminate: &AtomicBool,
+    ) -> Result<()> {
+        let mut out = BufWriter::new(io::stdout());
+
+        repo.search_object_content(
+            &self.object_id,
+            self.version.into(),
+            &self.pattern,
+            self.path.as_deref(),
+            self.max_size,
+            |found| {
+                let path = paint(args.no_styles, *style::CYAN, found.logical_path.as_str());
+
+                if self.files_with_matches {
+                    let _ = writeln!(out, "{}", path);
+                    return false;
+                }
+
+                let _ = writeln!(
+                    out,
+                    "{}:{}:{}",
+                    path,
+                    paint(args.no_styles, *style::GREEN, found.line_number.to_string()),
+                    found.line
+                );
+                true
+            },
+        )?;
+
+        out.flush()?;
+
+        Ok(())
+    }
+}
+
+impl Cmd for FindCmd {
+    fn exec(
+        &self,
+        repo: &OcflRepo,
+        args: GlobalArgs,
+        _config: &Config,
+        terminate: &AtomicBool,
+    ) -> Result<()> {
+        let iter = repo.find_paths(&self.path, self.object_id.as_deref(), self.head)?;
+
+        let mut out = BufWriter::new(io::stdout());
+        let isatty = atty::is(atty::Stream::Stdout);
+        let mut skipped = Vec::new();
+
+        for matches in iter {
+            if terminate.load(Ordering::Acquire) {
+                break;
+            }
+
+            let matches = match matches {
+                Ok(matches) => matches,
+                Err(e) => {
+                    skipped.push(e);
+                    continue;
+                }
+            };
+
+            for path_match in matches {
+                let _ = write!(
+                    out,
+                    "{}\t{}\t{}",
+                    path_match.object_id,
+                    paint(
+                        args.no_styles,
+                        *style::GREEN,
+                        path_match.version_num.to_string()
+                    ),
+                    paint(
+                        args.no_styles,
+                        *style::CYAN,
+                        path_match.logical_path.as_str()
+                    ),
+                );
+                if self.digest {
+                    let _ = write!(out, "\t{}", path_match.digest);
+                }
+                let _ = writeln!(out);
+            }
+
+            if isatty {
+                let _ = out.flush();
+            }
+        }
+
+        out.flush()?;
+
+        if !skipped.is_empty() {
+            if !args.quiet {
+                print_skipped_summary(&skipped);
+            }
+            if !self.skip_errors {
+                raise_exit_status(1);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Cmd for ExportCmd {
+    fn exec(
+        &self,
+        repo: &OcflRepo,
+        args: GlobalArgs,
+        _config: &Config,
+        terminate: &AtomicBool,
+    ) -> Result<()> {
+        let object_ids = if self.object_ids.is_empty() {
+            repo.list_objects(None)?
+                .filter_map(|object| match object {
+                    Ok(object) => Some(object.id),
+                    Err(e) => {
+                        error!("Failed to export object: {:#}", e);
+                        raise_exit_status(1);
+                        None
+                    }
+                })
+                .collect()
+        } else {
+            self.object_ids.clone()
+        };
+
+        let mut files_written = 0;
+        let mut files_skipped = 0;
+        let mut bytes_written = 0;
+
+        for object_id in &object_ids {
+            if terminate.load(Ordering::Acquire) {
+                break;
+            }
+
+            match repo.export_cas(object_id, self.version.into(), &self.destination) {
+                Ok(export) => {
+                    if !args.quiet {
+                        println(format!(
+                            "Exported object {} version {}: {} files written, {} skipped",
+                            export.object_id,
+                            export.version_num,
+                            export.files_written,
+                            export.files_skipped
+                        ));
+                    }
+                    files_written += export.files_written;
+                    files_skipped += export.files_skipped;
+                    bytes_written += export.bytes_written;
+                }
+                Err(e) => {
+                    error!("Failed to export object {}: {:#}", object_id, e);
+                    raise_exit_status(1);
+                }
+            }
+        }
+
+        if !args.quiet {
+            println(format!(
+                "Wrote {} files ({} bytes) and skipped {} files already in {}",
+                files_written,
+                bytes_written,
+                files_skipped,
+                self.destination.to_string_lossy()
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+impl Cmd for ZipCmd {
+    fn exec(
+        &self,
+        repo: &OcflRepo,
+        args: GlobalArgs,
+        _config: &Config,
+        _terminate: &AtomicBool,
+    ) -> Result<()> {
+        let result = match &self.command {
+            ZipCommand::Export(export) => export_zip(repo, export, args),
+            ZipCommand::Import(import) => import_zip(repo, import, args),
+        };
+
+        if let Err(RocflError::InvalidObject { errors, .. }) = &result {
+            errors
+                .iter()
+                .for_each(|e| error!("[{}] {}", e.code, e.text));
+        }
+
+        result
+    }
+}
+
+fn export_zip(repo: &OcflRepo, args: &ZipExportArgs, global_args: GlobalArgs) -> Result<()> {
+    let file = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&args.destination)
+        .map_err(|e| RocflError::io_path("create zip archive", &args.destination, e))?;
+
+    let export = match repo.export_object_zip(&args.object_id, BufWriter::new(file)) {
+        Ok(export) => export,
+        Err(e) => {
+            // Do not leave a partial archive behind
+            let _ = fs::remove_file(&args.destination);
+            return Err(e);
         }
+    };
+
+    if !global_args.quiet {
+        println(format!(
+            "Exported object {} version {} to {}: {} files",
+            export.object_id,
+            export.head,
+            args.destination.to_string_lossy(),
+            export.files
+        ));
+    }
+
+    Ok(())
+}
+
+fn import_zip(repo: &OcflRepo, args: &ZipImportArgs, global_args: GlobalArgs) -> Result<()> {
+    let file = File::open(&args.source)
+        .map_err(|e| RocflError::io_path("open zip archive", &args.source, e))?;
+
+    let import = repo.import_object_zip(BufReader::new(file))?;
+
+    if !global_args.quiet {
+        println(format!(
+            "Imported object {} version {}: {} files",
+            import.object_id, import.head, import.files
+        ));
     }
+
+    Ok(())
 }
 
 /// This is needed to keep enum_dispatch happy
@@ -65,21 +398,79 @@ impl Cmd for ConfigCmd {
     }
 }
 
+/// This is needed to keep enum_dispatch happy
+impl Cmd for InspectInventoryCmd {
+    fn exec(
+        &self,
+        _repo: &OcflRepo,
+        _args: GlobalArgs,
+        _config: &Config,
+        _terminate: &AtomicBool,
+    ) -> Result<()> {
+        unimplemented!()
+    }
+}
+
 impl Cmd for NewCmd {
     fn exec(
         &self,
         repo: &OcflRepo,
         _args: GlobalArgs,
-        _config: &Config,
+        config: &Config,
         _terminate: &AtomicBool,
     ) -> Result<()> {
-        repo.create_object(
-            &self.object_id,
-            self.spec_version.map(map_spec_version),
-            algorithm(self.digest_algorithm),
-            &self.content_directory,
-            self.zero_padding,
-        )?;
+        let template = match &self.from_template {
+            Some(path) => Some(ObjectTemplate::read(path)?),
+            None => None,
+        };
+
+        // Command line arguments are already merged into the config, so they must be checked
+        // separately to take precedence over the template
+        let digest_algorithm = match self
+            .digest_algorithm
+            .map(algorithm)
+            .or_else(|| template.as_ref().and_then(|t| t.digest_algorithm))
+            .or(config.digest_algorithm)
+        {
+            Some(digest_algorithm) => digest_algorithm,
+            None => repo
+                .default_digest_algorithm()?
+                .unwrap_or(DigestAlgorithm::Sha512),
+        };
+        warn_digest_algorithm(digest_algorithm);
+
+        let zero_padding = self
+            .zero_padding
+            .or_else(|| template.as_ref().and_then(|t| t.zero_padding))
+            .or(config.zero_padding)
+            .unwrap_or(0);
+        warn_zero_padding(zero_padding);
+
+        match template {
+            Some(mut template) => {
+                if self.content_directory.is_some() || template.content_directory.is_none() {
+                    template.content_directory = Some(config.content_directory().to_string());
+                }
+                template.digest_algorithm = Some(digest_algorithm);
+                template.zero_padding = Some(zero_padding);
+
+                repo.create_object_from_template(
+                    &self.object_id,
+                    self.spec_version.map(map_spec_version),
+                    &template,
+                    &config.metadata_path().try_into()?,
+                )?;
+            }
+            None => {
+                repo.create_object(
+                    &self.object_id,
+                    self.spec_version.map(map_spec_version),
+                    digest_algorithm,
+                    config.content_directory(),
+                    zero_padding,
+                )?;
+            }
+        }
 
         info!("Staged new OCFL object {}", self.object_id);
 
@@ -95,7 +486,11 @@ impl Cmd for CopyCmd {
         _config: &Config,
         _terminate: &AtomicBool,
     ) -> Result<()> {
-        if self.internal {
+        if let Some(archive) = &self.from_archive {
+            let file =
+                File::open(archive).map_err(|e| RocflError::io_path("open archive", archive, e))?;
+            repo.copy_archive_external(&self.object_id, file, &self.destination)
+        } else if self.internal {
             repo.copy_files_internal(
                 &self.object_id,
                 self.version.into(),
@@ -104,9 +499,11 @@ impl Cmd for CopyCmd {
                 self.recursive,
             )
         } else {
-            repo.copy_files_external(
+            repo.set_symlink_policy(symlink_policy(self.symlinks));
+            repo.set_preserve_timestamps(self.preserve_timestamps);
+            repo.copy_files_external_iter(
                 &self.object_id,
-                &self.source,
+                external_sources(&self.source, self.files_from.as_deref())?,
                 &self.destination,
                 self.recursive,
             )
@@ -125,7 +522,13 @@ impl Cmd for MoveCmd {
         if self.internal {
             repo.move_files_internal(&self.object_id, &self.source, &self.destination)
         } else {
-            repo.move_files_external(&self.object_id, &self.source, &self.destination)
+            repo.set_symlink_policy(symlink_policy(self.symlinks));
+            repo.set_preserve_timestamps(self.preserve_timestamps);
+            repo.move_files_external_iter(
+                &self.object_id,
+                external_sources(&self.source, self.files_from.as_deref())?,
+                &self.destination,
+            )
         }
     }
 }
@@ -142,6 +545,60 @@ impl Cmd for RemoveCmd {
     }
 }
 
+impl Cmd for ApplyCmd {
+    fn exec(
+        &self,
+        repo: &OcflRepo,
+        _args: GlobalArgs,
+        _config: &Config,
+        _terminate: &AtomicBool,
+    ) -> Result<()> {
+        let patch = if self.patch.as_os_str() == "-" {
+            ObjectPatch::from_reader(io::stdin().lock())?
+        } else {
+            let file = File::open(&self.patch)
+                .map_err(|e| RocflError::io_path("open patch", &self.patch, e))?;
+            ObjectPatch::from_reader(BufReader::new(file))?
+        };
+
+        repo.apply_patch(&self.object_id, &patch)
+    }
+}
+
+impl Cmd for StageCmd {
+    fn exec(
+        &self,
+        repo: &OcflRepo,
+        args: GlobalArgs,
+        _config: &Config,
+        _terminate: &AtomicBool,
+    ) -> Result<()> {
+        match &self.command {
+            StageCommand::From(from) => {
+                repo.stage_from_version(
+                    &from.object_id,
+                    from.version.into(),
+                    &from.paths,
+                    from.recursive,
+                )?;
+
+                if !args.quiet {
+                    let version = match from.version {
+                        Some(version) => version.to_string(),
+                        None => "HEAD".to_string(),
+                    };
+                    println(format!(
+                        "Staged object {} from version {}",
+                        from.object_id, version
+                    ));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
 impl Cmd for ResetCmd {
     fn exec(
         &self,
@@ -158,26 +615,91 @@ impl Cmd for ResetCmd {
     }
 }
 
+impl Cmd for RebaseCmd {
+    fn exec(
+        &self,
+        repo: &OcflRepo,
+        args: GlobalArgs,
+        _config: &Config,
+        _terminate: &AtomicBool,
+    ) -> Result<()> {
+        let staged = repo
+            .get_staged_object_details(&self.object_id)?
+            .version_details
+            .version_num;
+        let version_num = repo.rebase_staged(&self.object_id)?;
+
+        if !args.quiet {
+            if version_num == staged {
+                println(format!(
+                    "The staged changes to {} already follow its current version",
+                    self.object_id
+                ));
+            } else {
+                println(format!(
+                    "Rebased the staged changes to {} from version {} to version {}",
+                    self.object_id, staged, version_num
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
 impl Cmd for CommitCmd {
     fn exec(
         &self,
         repo: &OcflRepo,
-        _args: GlobalArgs,
+        args: GlobalArgs,
         config: &Config,
         _terminate: &AtomicBool,
     ) -> Result<()> {
-        let meta = CommitMeta::new()
-            .with_user(config.author_name.clone(), config.author_address.clone())?
-            .with_message(self.message.clone())
-            .with_created(self.created);
-        repo.commit(
-            &self.object_id,
-            meta,
-            self.object_root.as_ref().map(|r| r.as_ref()),
-            self.pretty_print,
-        )?;
+        if self.validate {
+            let result = repo.validate_staged(&self.object_id)?;
 
-        Ok(())
+            if result.has_errors() {
+                print_staged_result(&result, args.no_styles);
+                return Err(RocflError::InvalidObject {
+                    object_id: self.object_id.clone(),
+                    errors: result.errors().to_vec(),
+                });
+            }
+        }
+
+        repo.set_override_frozen(self.force);
+        repo.set_override_quotas(self.force);
+
+        let mut attempts = 0;
+
+        loop {
+            let meta = CommitMeta::builder()
+                .defaults_from_config(config)
+                .message(self.message.clone())
+                .created(self.created)
+                .build()?;
+
+            match repo.commit(
+                &self.object_id,
+                meta,
+                self.object_root.as_ref().map(|r| r.as_ref()),
+                config.pretty_print.unwrap_or(false),
+            ) {
+                Err(RocflError::VersionConflict { current, .. })
+                    if self.retry && attempts < MAX_COMMIT_RETRIES =>
+                {
+                    attempts += 1;
+                    let version_num = repo.rebase_staged(&self.object_id)?;
+                    if !args.quiet {
+                        println(format!(
+                            "Rebased the staged changes onto version {} as version {}",
+                            current, version_num
+                        ));
+                    }
+                }
+                result => return result,
+            }
+        }
     }
 }
 
@@ -190,20 +712,23 @@ impl Cmd for UpgradeCmd {
         _terminate: &AtomicBool,
     ) -> Result<()> {
         if let Some(object_id) = &self.object_id {
-            let meta = CommitMeta::new()
-                .with_user(config.author_name.clone(), config.author_address.clone())?
-                .with_message(self.message.clone())
-                .with_created(self.created);
+            repo.set_override_frozen(self.force);
+
+            let meta = CommitMeta::builder()
+                .defaults_from_config(config)
+                .message(self.message.clone())
+                .created(self.created)
+                .build()?;
             repo.upgrade_object(
                 object_id,
                 map_spec_version(self.spec_version),
                 meta,
-                self.pretty_print,
+                config.pretty_print.unwrap_or(false),
             )?;
             Ok(())
         } else {
             repo.upgrade_repo(map_spec_version(self.spec_version))?;
-            if !args.quiet {
+            if !args.quiet && !args.dry_run {
                 println(format!("Upgraded OCFL repository to {}", self.spec_version));
             }
             Ok(())
@@ -234,13 +759,26 @@ impl Cmd for StatusCmd {
                 path: None,
                 staged: true,
                 logical_dirs: false,
+                content_path: false,
+                origin: false,
                 digest: false,
+                digest_length: None,
                 objects: false,
+                skip_errors: false,
+                diff_markers: false,
+                before: None,
+                after: None,
+                min_size: None,
+                max_size: None,
+                frozen: false,
+                fingerprint: false,
+                timestamps: Timestamps::Updated,
                 header: true,
                 long: true,
                 reverse: false,
                 physical: false,
                 tsv: false,
+                format: ListFormat::Table,
                 sort: Field::Name,
             };
 
@@ -253,18 +791,27 @@ impl Cmd for PurgeCmd {
     fn exec(
         &self,
         repo: &OcflRepo,
-        _args: GlobalArgs,
+        args: GlobalArgs,
         _config: &Config,
         _terminate: &AtomicBool,
     ) -> Result<()> {
         let mut out = BufWriter::new(io::stdout());
 
-        if !self.force {
-            let _ = write!(
-                out,
-                "Permanently delete '{}'? This cannot be undone. [y/N]: ",
-                self.object_id
-            );
+        repo.set_override_frozen(self.force);
+
+        if !self.force && !args.dry_run {
+            let _ = match &self.resume_at {
+                Some(object_root) => write!(
+                    out,
+                    "Permanently delete the partially purged object at '{}'? This cannot be undone. [y/N]: ",
+                    object_root
+                ),
+                None => write!(
+                    out,
+                    "Permanently delete '{}'? This cannot be undone. [y/N]: ",
+                    self.object_id.as_deref().unwrap_or_default()
+                ),
+            };
             let _ = out.flush();
             let mut response = String::new();
             io::stdin().read_line(&mut response)?;
@@ -274,19 +821,400 @@ impl Cmd for PurgeCmd {
             }
         }
 
-        repo.purge_object(&self.object_id)
+        match &self.resume_at {
+            Some(object_root) => {
+                let deleted = repo.purge_object_root(object_root)?;
+
+                if !args.quiet {
+                    if args.verbose {
+                        for file in &deleted {
+                            let _ = writeln!(out, "{}", file);
+                        }
+                    }
+                    let _ = writeln!(
+                        out,
+                        "Deleted {} remaining file(s) from '{}'",
+                        deleted.len(),
+                        object_root
+                    );
+                }
+
+                Ok(())
+            }
+            None => repo.purge_object(self.object_id.as_deref().unwrap_or_default()),
+        }
     }
 }
 
-impl Cmd for InfoCmd {
+impl Cmd for RenameCmd {
     fn exec(
         &self,
         repo: &OcflRepo,
         args: GlobalArgs,
-        _config: &Config,
+        config: &Config,
         _terminate: &AtomicBool,
     ) -> Result<()> {
-        let style = if args.no_styles {
+        repo.set_override_frozen(self.force);
+
+        repo.rename_object(
+            &self.object_id,
+            &self.new_object_id,
+            self.object_root.as_deref(),
+            config.pretty_print.unwrap_or(false),
+        )?;
+
+        if !args.quiet {
+            println(format!(
+                "Renamed object {} to {}",
+                self.object_id, self.new_object_id
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+impl Cmd for SquashCmd {
+    fn exec(
+        &self,
+        repo: &OcflRepo,
+        args: GlobalArgs,
+        config: &Config,
+        _terminate: &AtomicBool,
+    ) -> Result<()> {
+        let mut out = BufWriter::new(io::stdout());
+
+        repo.set_override_frozen(self.force);
+
+        if !self.force && !args.dry_run {
+            let _ = write!(
+                out,
+                "Permanently collapse versions v1 through {} of '{}'? Content that is only in these versions is deleted. This cannot be undone. [y/N]: ",
+                self.through, self.object_id
+            );
+            let _ = out.flush();
+            let mut response = String::new();
+            io::stdin().read_line(&mut response)?;
+            if !response.trim().eq_ignore_ascii_case("y") {
+                let _ = writeln!(out, "Aborted");
+                return Ok(());
+            }
+        }
+
+        let report = repo.squash_object(
+            &self.object_id,
+            self.through,
+            config.pretty_print.unwrap_or(false),
+        )?;
+
+        if !args.quiet && !args.dry_run {
+            for (original, squashed) in &report.versions {
+                let _ = writeln!(out, "{} -> {}", original, squashed);
+            }
+            if args.verbose {
+                for file in &report.removed_files {
+                    let _ = writeln!(out, "Deleted {}", file);
+                }
+            }
+            let _ = writeln!(
+                out,
+                "Squashed object {} through {}, deleting {} content file(s)",
+                report.object_id,
+                self.through,
+                report.removed_files.len()
+            );
+        }
+
+        out.flush()?;
+
+        Ok(())
+    }
+}
+
+impl Cmd for GcCmd {
+    fn exec(
+        &self,
+        repo: &OcflRepo,
+        args: GlobalArgs,
+        _config: &Config,
+        _terminate: &AtomicBool,
+    ) -> Result<()> {
+        let dry_run = self.dry_run || args.dry_run;
+
+        let object_id = match &self.object_id {
+            Some(object_id) if !self.staging => object_id,
+            _ => return self.gc_staging(repo, dry_run),
+        };
+
+        let orphans = repo.list_orphaned_content(object_id)?;
+
+        let mut out = BufWriter::new(io::stdout());
+
+        if orphans.is_empty() {
+            let _ = writeln!(out, "No orphaned files found in '{}'", object_id);
+            return Ok(());
+        }
+
+        for orphan in &orphans {
+            let _ = writeln!(out, "{}", orphan);
+        }
+
+        if dry_run {
+            return Ok(());
+        }
+
+        if !self.force {
+            let _ = write!(
+                out,
+                "Permanently delete {} orphaned file(s) from '{}'? This cannot be undone. [y/N]: ",
+                orphans.len(),
+                object_id
+            );
+            let _ = out.flush();
+            let mut response = String::new();
+            io::stdin().read_line(&mut response)?;
+            if !response.trim().eq_ignore_ascii_case("y") {
+                let _ = writeln!(out, "Aborted");
+                return Ok(());
+            }
+        }
+
+        let removed = repo.rm_orphaned_content(object_id)?;
+        let _ = writeln!(out, "Deleted {} orphaned file(s)", removed.len());
+
+        Ok(())
+    }
+}
+
+impl GcCmd {
+    fn gc_staging(&self, repo: &OcflRepo, dry_run: bool) -> Result<()> {
+        let abandoned = repo.list_abandoned_staging(self.older_than.0)?;
+
+        let mut out = BufWriter::new(io::stdout());
+
+        if abandoned.is_empty() {
+            let _ = writeln!(out, "No abandoned staged content found");
+            return Ok(());
+        }
+
+        for object in &abandoned {
+            let modified = object
+                .last_modified
+                .map(|modified| modified.format(DATE_FORMAT).to_string())
+                .unwrap_or_default();
+            let _ = writeln!(
+                out,
+                "{}\t{} file(s)\t{} bytes\t{}",
+                object.object_root, object.files, object.bytes, modified
+            );
+        }
+
+        if dry_run {
+            return Ok(());
+        }
+
+        if !self.force {
+            let _ = write!(
+                out,
+                "Permanently delete the staged content of {} object(s)? This cannot be undone. [y/N]: ",
+                abandoned.len()
+            );
+            let _ = out.flush();
+            let mut response = String::new();
+            io::stdin().read_line(&mut response)?;
+            if !response.trim().eq_ignore_ascii_case("y") {
+                let _ = writeln!(out, "Aborted");
+                return Ok(());
+            }
+        }
+
+        let removed = repo.rm_abandoned_staging(self.older_than.0)?;
+        let _ = writeln!(
+            out,
+            "Deleted the staged content of {} object(s)",
+            removed.len()
+        );
+
+        Ok(())
+    }
+}
+
+impl Cmd for DoctorCmd {
+    fn exec(
+        &self,
+        repo: &OcflRepo,
+        args: GlobalArgs,
+        _config: &Config,
+        _terminate: &AtomicBool,
+    ) -> Result<()> {
+        let report = repo.check_health(self.lock_age.0)?;
+        let mut out = BufWriter::new(io::stdout());
+
+        for problem in &report.problems {
+            let location = match (&problem.object_id, &problem.path) {
+                (Some(id), Some(path)) => format!("Object {} at {}: ", id, path),
+                (Some(id), None) => format!("Object {}: ", id),
+                (None, Some(path)) => format!("{}: ", path),
+                (None, None) => String::new(),
+            };
+
+            let _ = writeln!(
+                out,
+                "{} {}{}",
+                paint(args.no_styles, *style::RED, format!("[{}]", problem.check)),
+                location,
+                problem.message
+            );
+            let _ = writeln!(out, "  Fix: {}", problem.remedy);
+        }
+
+        if !args.quiet {
+            let summary = format!(
+                "Checked {} object(s) and {} staged object(s)",
+                report.objects_checked, report.staged_objects_checked
+            );
+            if report.problems.is_empty() {
+                let _ = writeln!(out, "{}. No problems found", summary);
+            } else {
+                let _ = writeln!(
+                    out,
+                    "{}. Found {} problem(s)",
+                    summary,
+                    report.problems.len()
+                );
+            }
+        }
+
+        out.flush()?;
+
+        if !report.problems.is_empty() {
+            raise_exit_status(1);
+        }
+
+        Ok(())
+    }
+}
+
+impl Cmd for VerifySignaturesCmd {
+    fn exec(
+        &self,
+        repo: &OcflRepo,
+        args: GlobalArgs,
+        config: &Config,
+        terminate: &AtomicBool,
+    ) -> Result<()> {
+        let trusted_keys = if !self.keys.is_empty() {
+            self.keys
+                .iter()
+                .map(VerifyingKey::from_pem_file)
+                .collect::<Result<Vec<_>>>()?
+        } else if let Some(signing_key) = &config.signing_key {
+            vec![SigningKey::from_pem_file(signing_key)?.verifying_key()]
+        } else {
+            return Err(RocflError::InvalidValue(
+                "A trusted public key must be specified with '--key' or the 'signing_key' \
+                 config property"
+                    .to_string(),
+            ));
+        };
+
+        let object_ids = if self.object_ids.is_empty() {
+            repo.list_objects(None)?
+                .filter_map(|object| match object {
+                    Ok(object) => Some(object.id),
+                    Err(e) => {
+                        error!("Failed to verify object signatures: {:#}", e);
+                        raise_exit_status(1);
+                        None
+                    }
+                })
+                .collect()
+        } else {
+            self.object_ids.clone()
+        };
+
+        let mut out = BufWriter::new(io::stdout());
+        let mut verified = 0;
+        let mut unsigned = 0;
+        let mut failed = 0;
+
+        for object_id in &object_ids {
+            if terminate.load(Ordering::Acquire) {
+                break;
+            }
+
+            let report = match repo.verify_signatures(object_id, &trusted_keys) {
+                Ok(report) => report,
+                Err(e) => {
+                    let _ = out.flush();
+                    error!("Failed to verify object {}: {:#}", object_id, e);
+                    raise_exit_status(1);
+                    continue;
+                }
+            };
+
+            for version in &report.versions {
+                let (style, status) = match &version.status {
+                    SignatureStatus::Valid { public_key } => {
+                        verified += 1;
+                        (*style::GREEN, format!("valid, signed by {}", public_key))
+                    }
+                    SignatureStatus::Unsigned => {
+                        unsigned += 1;
+                        (*style::YELLOW, "unsigned".to_string())
+                    }
+                    SignatureStatus::Untrusted { public_key } => {
+                        failed += 1;
+                        (*style::RED, format!("untrusted, signed by {}", public_key))
+                    }
+                    SignatureStatus::Invalid(reason) => {
+                        failed += 1;
+                        (*style::RED, format!("invalid, {}", reason))
+                    }
+                };
+
+                if !args.quiet || version.status.is_failure() {
+                    let _ = writeln!(
+                        out,
+                        "Object {} version {}: {}",
+                        report.object_id,
+                        version.version,
+                        paint(args.no_styles, style, status)
+                    );
+                }
+            }
+        }
+
+        if !args.quiet {
+            let _ = writeln!(
+                out,
+                "Verified {} version(s): {} valid, {} unsigned, {} failed",
+                verified + unsigned + failed,
+                verified,
+                unsigned,
+                failed
+            );
+        }
+
+        out.flush()?;
+
+        if failed > 0 {
+            raise_exit_status(1);
+        }
+
+        Ok(())
+    }
+}
+
+impl Cmd for InfoCmd {
+    fn exec(
+        &self,
+        repo: &OcflRepo,
+        args: GlobalArgs,
+        _config: &Config,
+        _terminate: &AtomicBool,
+    ) -> Result<()> {
+        let style = if args.no_styles {
             &*style::DEFAULT
         } else {
             &*style::BOLD
@@ -303,20 +1231,56 @@ impl Cmd for InfoCmd {
 
             let _ = writeln!(
                 out,
-                "{}     {}",
+                "{}      {}",
                 style.paint("Spec Version:"),
                 info.spec_version
             );
             let _ = writeln!(
                 out,
-                "{} {}",
+                "{}  {}",
                 style.paint("Digest Algorithm:"),
                 info.digest_algorithm
                     .unwrap_or_else(|| "unknown".to_string())
             );
+            let _ = writeln!(
+                out,
+                "{} {}",
+                style.paint("Content Directory:"),
+                info.content_directory
+                    .unwrap_or_else(|| "unknown".to_string())
+            );
+            for anomaly in &info.content_dir_anomalies {
+                let _ = writeln!(
+                    out,
+                    "  {} {}",
+                    paint(args.no_styles, *style::YELLOW, "[Warning]"),
+                    anomaly
+                );
+            }
+
+            if !info.fixity_algorithms.is_empty() {
+                let _ = writeln!(
+                    out,
+                    "{} {}",
+                    style.paint("Fixity Algorithms:"),
+                    info.fixity_algorithms.join(", ")
+                );
+            }
+
+            if let Some(frozen) = &info.frozen {
+                let _ = writeln!(
+                    out,
+                    "{}            {}",
+                    style.paint("Frozen:"),
+                    frozen.timestamp.to_rfc3339()
+                );
+                if let Some(reason) = &frozen.reason {
+                    let _ = writeln!(out, "  {}", reason);
+                }
+            }
 
             if info.extensions.is_empty() {
-                let _ = writeln!(out, "{}       none", style.paint("Extensions:"));
+                let _ = writeln!(out, "{}        none", style.paint("Extensions:"));
             } else {
                 info.extensions.sort();
                 let _ = writeln!(out, "{}", style.paint("Extensions:"));
@@ -360,9 +1324,442 @@ impl Cmd for InfoCmd {
     }
 }
 
-fn algorithm(algorithm: OptAlgorithm) -> DigestAlgorithm {
+impl Cmd for AdoptCmd {
+    fn exec(
+        &self,
+        repo: &OcflRepo,
+        args: GlobalArgs,
+        _config: &Config,
+        _terminate: &AtomicBool,
+    ) -> Result<()> {
+        let object_id = repo.adopt_object(&self.object_root)?;
+
+        if !args.quiet {
+            println(format!(
+                "Adopted object {} at {}",
+                object_id, self.object_root
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+impl Cmd for FreezeCmd {
+    fn exec(
+        &self,
+        repo: &OcflRepo,
+        args: GlobalArgs,
+        _config: &Config,
+        _terminate: &AtomicBool,
+    ) -> Result<()> {
+        repo.freeze_object(&self.object_id, self.reason.as_deref())?;
+
+        if !args.quiet {
+            println(format!("Froze object {}", self.object_id));
+        }
+
+        Ok(())
+    }
+}
+
+impl Cmd for UnfreezeCmd {
+    fn exec(
+        &self,
+        repo: &OcflRepo,
+        args: GlobalArgs,
+        _config: &Config,
+        _terminate: &AtomicBool,
+    ) -> Result<()> {
+        repo.unfreeze_object(&self.object_id)?;
+
+        if !args.quiet {
+            println(format!("Unfroze object {}", self.object_id));
+        }
+
+        Ok(())
+    }
+}
+
+impl Cmd for AliasCmd {
+    fn exec(
+        &self,
+        repo: &OcflRepo,
+        args: GlobalArgs,
+        _config: &Config,
+        _terminate: &AtomicBool,
+    ) -> Result<()> {
+        match &self.command {
+            AliasCommand::Add(add) => {
+                repo.add_object_alias(&add.object_id, &add.alias)?;
+
+                if !args.quiet {
+                    println(format!(
+                        "Added alias {} for object {}",
+                        add.alias, add.object_id
+                    ));
+                }
+            }
+            AliasCommand::Remove(remove) => {
+                repo.remove_object_alias(&remove.alias)?;
+
+                if !args.quiet {
+                    println(format!("Removed alias {}", remove.alias));
+                }
+            }
+            AliasCommand::List(list) => {
+                let aliases = repo.list_object_aliases(list.object_id.as_deref())?;
+
+                let separator = if list.tsv {
+                    Separator::Tab
+                } else {
+                    Separator::Space
+                };
+
+                let mut table = TableView::new(
+                    vec![
+                        Column::new(ColumnId::Alias, "Alias", Alignment::Left),
+                        Column::new(ColumnId::ObjectId, "Object ID", Alignment::Left),
+                    ],
+                    separator,
+                    list.header,
+                    !args.no_styles,
+                );
+
+                aliases.iter().for_each(|alias| table.add_row(alias));
+
+                let out = io::stdout();
+                let mut writer = BufWriter::new(out.lock());
+                let _ = table.write(&mut writer);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Cmd for LayoutCmd {
+    fn exec(
+        &self,
+        repo: &OcflRepo,
+        args: GlobalArgs,
+        _config: &Config,
+        _terminate: &AtomicBool,
+    ) -> Result<()> {
+        match &self.command {
+            LayoutCommand::Show => {
+                let style = if args.no_styles {
+                    &*style::DEFAULT
+                } else {
+                    &*style::BOLD
+                };
+
+                let info = repo.verify_layout()?;
+                let mut out = BufWriter::new(io::stdout());
+
+                let _ = writeln!(
+                    out,
+                    "{}   {}",
+                    style.paint("Extension:"),
+                    info.extension.as_deref().unwrap_or("none")
+                );
+                let _ = writeln!(
+                    out,
+                    "{} {}",
+                    style.paint("Description:"),
+                    info.description.as_deref().unwrap_or("none")
+                );
+
+                match &info.config {
+                    Some(config) => {
+                        let source = if info.has_config_file {
+                            "Config:"
+                        } else {
+                            "Config (defaults):"
+                        };
+                        let _ = writeln!(out, "{}", style.paint(source));
+                        for line in config.lines() {
+                            let _ = writeln!(out, "  {}", line);
+                        }
+                    }
+                    None => {
+                        let _ = writeln!(out, "{}      none", style.paint("Config:"));
+                    }
+                }
+
+                let _ = writeln!(
+                    out,
+                    "{}    {}",
+                    style.paint("Registry:"),
+                    if repo.is_object_registry() {
+                        "enabled"
+                    } else {
+                        "disabled"
+                    }
+                );
+
+                if !info.problems.is_empty() {
+                    let _ = writeln!(
+                        out,
+                        "{} run 'rocfl layout verify' for details",
+                        paint(args.no_styles, *style::RED, "Layout has problems:")
+                    );
+                }
+
+                out.flush()?;
+            }
+            LayoutCommand::Verify => {
+                let info = repo.verify_layout()?;
+                let mut out = BufWriter::new(io::stdout());
+
+                if info.problems.is_empty() {
+                    if !args.quiet {
+                        let _ = writeln!(out, "Storage layout is consistent");
+                    }
+                    out.flush()?;
+                } else {
+                    for problem in &info.problems {
+                        let _ = writeln!(
+                            out,
+                            "{} {}",
+                            paint(args.no_styles, *style::RED, "[Problem]"),
+                            problem
+                        );
+                    }
+                    out.flush()?;
+                    raise_exit_status(1);
+                }
+            }
+            LayoutCommand::SetDescription(set) => {
+                repo.set_layout_description(&set.description)?;
+
+                if !args.quiet {
+                    println("Updated storage layout description");
+                }
+            }
+            LayoutCommand::Registry(registry) => {
+                let count = repo.set_object_registry(!registry.disable)?;
+
+                if !args.quiet {
+                    if registry.disable {
+                        println("Disabled the object registry");
+                    } else {
+                        println(format!(
+                            "Enabled the object registry with {} objects",
+                            count
+                        ));
+                    }
+                }
+            }
+            LayoutCommand::Map(_) => {
+                unreachable!("layout map cmd is handled before repo creation")
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Cmd for MetaCmd {
+    fn exec(
+        &self,
+        repo: &OcflRepo,
+        args: GlobalArgs,
+        config: &Config,
+        _terminate: &AtomicBool,
+    ) -> Result<()> {
+        let metadata_path = config.metadata_path().try_into()?;
+
+        match &self.command {
+            MetaCommand::Get(get) => {
+                let metadata = if get.staged {
+                    repo.get_staged_object_metadata(&get.object_id, &metadata_path)?
+                } else {
+                    repo.get_object_metadata(&get.object_id, get.version.into(), &metadata_path)?
+                };
+
+                let out = io::stdout();
+                let mut writer = BufWriter::new(out.lock());
+
+                match &get.key {
+                    Some(key) => match metadata.get(key) {
+                        Some(value) => {
+                            let _ = writeln!(writer, "{}", value);
+                        }
+                        None => {
+                            return Err(RocflError::NotFound(format!(
+                                "Object {} does not have metadata entry {}",
+                                get.object_id, key
+                            )));
+                        }
+                    },
+                    None => {
+                        for (key, value) in metadata {
+                            let _ = writeln!(writer, "{}={}", key, value);
+                        }
+                    }
+                }
+            }
+            MetaCommand::Set(set) => {
+                if set.entries.is_empty() && set.unset.is_empty() {
+                    return Err(RocflError::InvalidValue(
+                        "At least one entry must be set or unset".to_string(),
+                    ));
+                }
+
+                let mut changes = BTreeMap::new();
+
+                for key in &set.unset {
+                    changes.insert(key.clone(), None);
+                }
+
+                for entry in &set.entries {
+                    match entry.split_once('=') {
+                        Some((key, value)) if !key.is_empty() => {
+                            changes.insert(key.to_string(), Some(value.to_string()));
+                        }
+                        _ => {
+                            return Err(RocflError::InvalidValue(format!(
+                                "Metadata entries must be formatted as KEY=VALUE. Found: {}",
+                                entry
+                            )));
+                        }
+                    }
+                }
+
+                repo.stage_object_metadata(&set.object_id, &metadata_path, &changes)?;
+
+                if set.no_commit {
+                    if !args.quiet {
+                        println(format!(
+                            "Staged metadata changes to {} in object {}",
+                            metadata_path, set.object_id
+                        ));
+                    }
+                } else {
+                    let meta = CommitMeta::builder()
+                        .defaults_from_config(config)
+                        .message(set.message.clone())
+                        .build()?;
+                    repo.commit(
+                        &set.object_id,
+                        meta,
+                        None,
+                        config.pretty_print.unwrap_or(false),
+                    )?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+pub(super) fn algorithm(algorithm: OptAlgorithm) -> DigestAlgorithm {
     match algorithm {
         OptAlgorithm::Sha256 => DigestAlgorithm::Sha256,
         OptAlgorithm::Sha512 => DigestAlgorithm::Sha512,
     }
 }
+
+/// Returns the source paths passed as arguments followed by those listed in the `--files-from`
+/// file, if one was specified.
+fn external_sources(source: &[String], files_from: Option<&Path>) -> Result<Vec<PathBuf>> {
+    let mut paths: Vec<PathBuf> = source.iter().map(PathBuf::from).collect();
+
+    if let Some(file) = files_from {
+        let mut bytes = Vec::new();
+
+        if file.as_os_str() == "-" {
+            io::stdin().read_to_end(&mut bytes)?;
+        } else {
+            File::open(file)
+                .map_err(|e| RocflError::io_path("open file list", file, e))?
+                .read_to_end(&mut bytes)?;
+        }
+
+        paths.extend(parse_file_list(bytes)?);
+    }
+
+    Ok(paths)
+}
+
+/// Splits a file list on NUL characters, if it contains any, or on newlines otherwise. Blank
+/// entries are ignored.
+fn parse_file_list(bytes: Vec<u8>) -> Result<Vec<PathBuf>> {
+    let delimiter = if bytes.contains(&0) { b'\0' } else { b'\n' };
+
+    bytes
+        .split(|b| *b == delimiter)
+        .map(|entry| match delimiter {
+            b'\n' => entry.strip_suffix(b"\r").unwrap_or(entry),
+            _ => entry,
+        })
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| bytes_to_path(entry.to_vec()))
+        .collect()
+}
+
+#[cfg(unix)]
+fn bytes_to_path(bytes: Vec<u8>) -> Result<PathBuf> {
+    use std::os::unix::ffi::OsStringExt;
+    Ok(PathBuf::from(std::ffi::OsString::from_vec(bytes)))
+}
+
+#[cfg(not(unix))]
+fn bytes_to_path(bytes: Vec<u8>) -> Result<PathBuf> {
+    String::from_utf8(bytes).map(PathBuf::from).map_err(|e| {
+        RocflError::InvalidValue(format!(
+            "File list contains a path that is not UTF-8: {}",
+            e
+        ))
+    })
+}
+
+fn symlink_policy(symlinks: Symlinks) -> SymlinkPolicy {
+    match symlinks {
+        Symlinks::Follow => SymlinkPolicy::Follow,
+        Symlinks::Skip => SymlinkPolicy::Skip,
+        Symlinks::Error => SymlinkPolicy::Error,
+    }
+}
+
+/// Logs a warning when a digest algorithm other than sha512 is chosen. The spec recommends
+/// sha512 (W004), and `validate` warns about objects that do not use it.
+pub(super) fn warn_digest_algorithm(algorithm: DigestAlgorithm) {
+    if algorithm != DigestAlgorithm::Sha512 {
+        warn!(
+            "Using {} as the digest algorithm. The OCFL spec recommends sha512 (W004).",
+            algorithm
+        );
+    }
+}
+
+/// Logs a warning when versions are zero-padded. The spec discourages zero-padding (W001), and
+/// `validate` warns about objects that use it.
+fn warn_zero_padding(width: u32) {
+    if width > 0 {
+        warn!(
+            "Using zero-padded version numbers with a width of {}. The OCFL spec recommends against zero-padding (W001).",
+            width
+        );
+    }
+}
+
+impl<'a> AsRow<'a> for ObjectAlias {
+    fn as_row(&'a self, columns: &[Column]) -> Row<'a> {
+        let mut cells = Vec::new();
+
+        for column in columns {
+            let cell = match column.id {
+                ColumnId::Alias => TextCell::new(&self.alias),
+                ColumnId::ObjectId => TextCell::new(&self.object_id).with_style(&style::BOLD),
+                _ => TextCell::blank(),
+            };
+
+            cells.push(cell);
+        }
+
+        Row::new(cells)
+    }
+}
@@ -0,0 +1,189 @@
+//! Shell completion scripts. The scripts are generated by clap, and then extended with a hook
+//! that completes object IDs by calling `rocfl completions --object-ids PREFIX`.
+
+use std::io::{self, Write};
+use std::sync::atomic::AtomicBool;
+
+use clap::CommandFactory;
+use clap_complete::Shell;
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::cmd::opts::{CompletionShell, CompletionsCmd, RocflArgs};
+use crate::cmd::{Cmd, GlobalArgs};
+use crate::config::Config;
+use crate::ocfl::{OcflRepo, Result};
+
+const BIN_NAME: &str = "rocfl";
+const OBJECT_ID_VALUE_NAME: &str = "OBJ_ID";
+
+/// Matches the zsh argument specs of object ID positionals, which clap completes as files
+static ZSH_OBJECT_ID_SPEC: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(':+object_ids?(?: -- [^']*?)?):_default'").unwrap());
+
+impl Cmd for CompletionsCmd {
+    fn exec(
+        &self,
+        repo: &OcflRepo,
+        _args: GlobalArgs,
+        _config: &Config,
+        _terminate: &AtomicBool,
+    ) -> Result<()> {
+        let prefix = self.object_ids.as_deref().unwrap_or_default();
+        let ids = repo.list_object_ids(prefix, self.limit)?;
+
+        let mut out = io::stdout().lock();
+        for id in ids {
+            writeln!(out, "{}", id)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Writes the completion script for the shell to the writer. This does not require a repository.
+pub fn write_script(shell: CompletionShell, out: &mut dyn Write) -> Result<()> {
+    let mut command = RocflArgs::command();
+    let commands = object_id_commands(&command);
+
+    let mut script = Vec::new();
+    clap_complete::generate(clap_shell(shell), &mut command, BIN_NAME, &mut script);
+    let script = String::from_utf8_lossy(&script);
+
+    match shell {
+        CompletionShell::Bash => {
+            out.write_all(script.as_bytes())?;
+            write_bash_hook(&commands, out)?;
+        }
+        CompletionShell::Zsh => {
+            let script = ZSH_OBJECT_ID_SPEC.replace_all(&script, "$1:_rocfl_object_ids'");
+            // The hook must follow the #compdef line, which must be the first line of the script
+            let (compdef, rest) = script.split_once('\n').unwrap_or((&script, ""));
+            writeln!(out, "{}", compdef)?;
+            write_zsh_hook(out)?;
+            out.write_all(rest.as_bytes())?;
+        }
+        CompletionShell::Fish => {
+            out.write_all(script.as_bytes())?;
+            write_fish_hook(&commands, out)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn clap_shell(shell: CompletionShell) -> Shell {
+    match shell {
+        CompletionShell::Bash => Shell::Bash,
+        CompletionShell::Zsh => Shell::Zsh,
+        CompletionShell::Fish => Shell::Fish,
+    }
+}
+
+/// Returns the names of the subcommands whose first positional argument is an object ID
+fn object_id_commands(command: &clap::Command) -> Vec<String> {
+    command
+        .get_subcommands()
+        .filter(|sub| {
+            sub.get_positionals()
+                .next()
+                .and_then(|arg| arg.get_value_names())
+                .is_some_and(|names| {
+                    names
+                        .iter()
+                        .any(|name| name.as_str().starts_with(OBJECT_ID_VALUE_NAME))
+                })
+        })
+        .map(|sub| sub.get_name().to_string())
+        .collect()
+}
+
+/// Wraps the generated `_rocfl` function so that object IDs are completed when the current word
+/// is the first positional argument of a command that accepts an object ID. The generated
+/// function only completes options in this position, so its completions are replaced.
+///
+/// Object IDs frequently contain colons, which bash splits words on, so the command line is
+/// re-split on whitespace and the completions are trimmed the same way bash-completion's
+/// `__ltrim_colon_completions` does.
+fn write_bash_hook(commands: &[String], out: &mut dyn Write) -> Result<()> {
+    write!(
+        out,
+        r#"
+_rocfl_object_ids() {{
+    local line="${{COMP_LINE:0:COMP_POINT}}" cur i j
+    local -a words
+    _rocfl "$@"
+
+    read -ra words <<< "${{line}}"
+    if [[ -z ${{line}} || ${{line}} == *[[:space:]] ]]; then
+        cur=""
+    else
+        cur="${{words[${{#words[@]}}-1]}}"
+        unset 'words[${{#words[@]}}-1]'
+    fi
+
+    if [[ ${{cur}} == -* ]]; then
+        return 0
+    fi
+
+    for (( i = 1; i < ${{#words[@]}}; i++ )); do
+        case "${{words[i]}}" in
+            {commands})
+                for (( j = i + 1; j < ${{#words[@]}}; j++ )); do
+                    if [[ ${{words[j]}} != -* ]]; then
+                        return 0
+                    fi
+                done
+                COMPREPLY=( $({bin} completions --object-ids "${{cur}}" 2>/dev/null) )
+                if [[ ${{cur}} == *:* && ${{COMP_WORDBREAKS}} == *:* ]]; then
+                    local colon_prefix="${{cur%"${{cur##*:}}"}}"
+                    for (( j = 0; j < ${{#COMPREPLY[@]}}; j++ )); do
+                        COMPREPLY[j]="${{COMPREPLY[j]#"${{colon_prefix}}"}}"
+                    done
+                fi
+                return 0
+                ;;
+        esac
+    done
+}}
+
+if [[ "${{BASH_VERSINFO[0]}}" -eq 4 && "${{BASH_VERSINFO[1]}}" -ge 4 || "${{BASH_VERSINFO[0]}}" -gt 4 ]]; then
+    complete -F _rocfl_object_ids -o nosort -o bashdefault -o default {bin}
+else
+    complete -F _rocfl_object_ids -o bashdefault -o default {bin}
+fi
+"#,
+        commands = commands.join("|"),
+        bin = BIN_NAME,
+    )?;
+    Ok(())
+}
+
+/// Defines the function that the generated zsh script's object ID arguments are completed with
+fn write_zsh_hook(out: &mut dyn Write) -> Result<()> {
+    write!(
+        out,
+        r#"
+_rocfl_object_ids() {{
+    local -a ids
+    ids=(${{(f)"$({bin} completions --object-ids "${{PREFIX}}" 2>/dev/null)"}})
+    compadd -a ids
+}}
+"#,
+        bin = BIN_NAME,
+    )?;
+    Ok(())
+}
+
+/// Adds object IDs to the completions of the commands that accept an object ID
+fn write_fish_hook(commands: &[String], out: &mut dyn Write) -> Result<()> {
+    write!(
+        out,
+        r#"
+complete -c {bin} -n "__fish_seen_subcommand_from {commands}" -a "({bin} completions --object-ids (commandline -ct) 2>/dev/null)"
+"#,
+        commands = commands.join(" "),
+        bin = BIN_NAME,
+    )?;
+    Ok(())
+}
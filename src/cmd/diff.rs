@@ -1,16 +1,19 @@
 use core::fmt;
 use std::borrow::Cow;
 use std::cmp::Ordering;
-use std::convert::TryInto;
+use std::collections::{HashMap, HashSet};
 use std::fmt::Formatter;
 use std::io::{self, BufWriter, Write};
-use std::sync::atomic::AtomicBool;
+use std::sync::atomic::{AtomicBool, Ordering as AOrdering};
 
-use crate::cmd::opts::{DiffCmd, LogCmd, ShowCmd};
+use crate::cmd::opts::{CmpCmd, DiffCmd, LogCmd, Renames, ShowCmd, VersionOrPath};
 use crate::cmd::table::{Alignment, AsRow, Column, ColumnId, Row, Separator, TableView, TextCell};
-use crate::cmd::{style, Cmd, GlobalArgs, DATE_FORMAT};
+use crate::cmd::{raise_exit_status, style, Cmd, GlobalArgs, DATE_FORMAT};
 use crate::config::Config;
-use crate::ocfl::{Diff, InventoryPath, OcflRepo, Result, VersionDetails};
+use crate::ocfl::{
+    Diff, InventoryPath, LogicalPath, ObjectDiff, OcflRepo, RenameDetection, Result,
+    VersionContentSize, VersionDetails, VersionNum, VersionPage, VersionRange,
+};
 
 const DEFAULT_USER: &str = "NA";
 
@@ -19,6 +22,13 @@ const MODIFIED: &str = "Modified";
 const DELETED: &str = "Deleted";
 const RENAMED: &str = "Renamed";
 
+const ONLY_IN_A: &str = "Only in A";
+const ONLY_IN_B: &str = "Only in B";
+const DIFFERS: &str = "Differs";
+
+/// Marks versions that were created before the version that precedes them
+const OUT_OF_ORDER_MARKER: &str = "(!)";
+
 impl Cmd for LogCmd {
     fn exec(
         &self,
@@ -27,16 +37,82 @@ impl Cmd for LogCmd {
         _config: &Config,
         _terminate: &AtomicBool,
     ) -> Result<()> {
-        let mut versions = match &self.path {
-            Some(path) => repo.list_file_versions(&self.object_id, &path.try_into()?)?,
-            None => repo.list_object_versions(&self.object_id)?,
+        let (versions, out_of_order) = match &self.path {
+            Some(path_ref) => {
+                let out_of_order =
+                    out_of_order_versions(&repo.list_object_versions(&self.object_id)?);
+
+                let mut versions = repo.list_file_versions(&self.object_id, &path_ref.path)?;
+                if let Some(last) = path_ref.version {
+                    versions.retain(|version| version.version_num <= last);
+                }
+                if self.reverse {
+                    versions.reverse();
+                }
+
+                let versions = versions
+                    .into_iter()
+                    .skip(self.skip)
+                    .take(self.num.0)
+                    .collect();
+                (versions, out_of_order)
+            }
+            None => {
+                // The version that precedes the oldest displayed version is also listed, so that
+                // it can be determined if the oldest displayed version is out of order
+                let (offset, preceding) = match self.reverse {
+                    true => (self.skip, 0),
+                    false => (self.skip.saturating_sub(1), self.skip.min(1)),
+                };
+
+                let page = VersionPage::new()
+                    .with_reverse(self.reverse)
+                    .with_offset(offset)
+                    .with_limit(self.num.0.saturating_add(1));
+
+                let mut versions = repo.list_object_versions_page(&self.object_id, page)?;
+                let out_of_order = out_of_order_versions(&versions);
+
+                versions.drain(..preceding);
+                versions.truncate(self.num.0);
+                (versions, out_of_order)
+            }
         };
 
-        if self.reverse {
-            versions.reverse();
-        }
+        let sizes = if self.sizes {
+            repo.list_object_version_sizes(&self.object_id)?
+                .into_iter()
+                .map(|size| (size.version_num, size))
+                .collect()
+        } else {
+            HashMap::new()
+        };
+
+        let stats: HashMap<VersionNum, StatLine> = if self.diff {
+            let version_nums: Vec<VersionNum> =
+                versions.iter().map(|version| version.version_num).collect();
+            repo.diff_versions(&self.object_id, &version_nums, RenameDetection::All)?
+                .into_iter()
+                .map(|step| {
+                    let line = StatLine::new(step.version_num.to_string(), &step.diffs);
+                    (step.version_num, line)
+                })
+                .collect()
+        } else {
+            HashMap::new()
+        };
 
-        versions.truncate(self.num.0);
+        let versions: Vec<VersionLine> = versions
+            .iter()
+            .map(|version| {
+                VersionLine::new(
+                    version,
+                    sizes.get(&version.version_num),
+                    stats.get(&version.version_num),
+                    out_of_order.contains(&version.version_num),
+                )
+            })
+            .collect();
 
         self.print_versions(&versions, args);
         Ok(())
@@ -44,7 +120,7 @@ impl Cmd for LogCmd {
 }
 
 impl LogCmd {
-    fn print_versions(&self, versions: &[VersionDetails], args: GlobalArgs) {
+    fn print_versions(&self, versions: &[VersionLine], args: GlobalArgs) {
         let out = io::stdout();
 
         if self.compact {
@@ -55,20 +131,40 @@ impl LogCmd {
         } else {
             let mut writer = BufWriter::new(out.lock());
             for version in versions {
-                let _ = writeln!(writer, "{}", FormatVersion::new(version, !args.no_styles));
+                let _ = writeln!(
+                    writer,
+                    "{}",
+                    FormatVersion::new(version.details, !args.no_styles)
+                        .with_size(version.size)
+                        .with_stats(version.stats)
+                        .with_out_of_order(version.out_of_order)
+                );
             }
         }
     }
 
-    fn version_table(&self, args: GlobalArgs) -> TableView {
-        let columns = vec![
+    fn version_table(&self, args: GlobalArgs) -> TableView<'_> {
+        let mut columns = vec![
             Column::new(ColumnId::Version, "Version", Alignment::Right),
             Column::new(ColumnId::Author, "Author", Alignment::Left),
             Column::new(ColumnId::Address, "Address", Alignment::Left),
             Column::new(ColumnId::Created, "Created", Alignment::Left),
-            Column::new(ColumnId::Message, "Message", Alignment::Left),
         ];
 
+        if self.sizes {
+            columns.push(Column::new(ColumnId::Files, "Files", Alignment::Right));
+            columns.push(Column::new(ColumnId::Bytes, "Bytes", Alignment::Right));
+        }
+
+        if self.diff {
+            columns.push(Column::new(ColumnId::Added, ADDED, Alignment::Right));
+            columns.push(Column::new(ColumnId::Modified, MODIFIED, Alignment::Right));
+            columns.push(Column::new(ColumnId::Deleted, DELETED, Alignment::Right));
+            columns.push(Column::new(ColumnId::Renamed, RENAMED, Alignment::Right));
+        }
+
+        columns.push(Column::new(ColumnId::Message, "Message", Alignment::Left));
+
         TableView::new(columns, self.separator(), self.header, !args.no_styles)
     }
 
@@ -111,7 +207,13 @@ impl Cmd for ShowCmd {
                 display_diffs(diffs, &args)
             }
         } else {
-            let object = repo.get_object_details(&self.object_id, self.version.into())?;
+            let (version, path) = match &self.version {
+                Some(VersionOrPath::Version(version)) => (Some(*version), None),
+                Some(VersionOrPath::Path(path_ref)) => (path_ref.version, Some(&path_ref.path)),
+                None => (None, None),
+            };
+
+            let object = repo.get_object_details(&self.object_id, version.into())?;
 
             if !self.minimal {
                 let _ = writeln!(
@@ -124,7 +226,11 @@ impl Cmd for ShowCmd {
 
             let right = object.version_details.version_num;
 
-            let diffs = repo.diff(&self.object_id, None, right)?;
+            let mut diffs = repo.diff(&self.object_id, None, right)?;
+
+            if let Some(path) = path.filter(|path| !path.is_empty()) {
+                diffs.retain(|diff| diff_includes_path(diff, path));
+            }
 
             display_diffs(diffs, &args)
         }
@@ -132,6 +238,27 @@ impl Cmd for ShowCmd {
 }
 
 impl Cmd for DiffCmd {
+    fn exec(
+        &self,
+        repo: &OcflRepo,
+        args: GlobalArgs,
+        _config: &Config,
+        terminate: &AtomicBool,
+    ) -> Result<()> {
+        match (self.versions, self.left, self.right) {
+            (Some(range), _, _) if self.emit_patch => self.emit_patch(repo, range.start, range.end),
+            (None, Some(left), Some(right)) if self.emit_patch => {
+                self.emit_patch(repo, left, right)
+            }
+            (Some(range), _, _) if self.steps => self.display_steps(repo, range, &args, terminate),
+            (Some(range), _, _) => self.display_net(repo, range.start, range.end, &args),
+            (None, Some(left), Some(right)) => self.display_net(repo, left, right, &args),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Cmd for CmpCmd {
     fn exec(
         &self,
         repo: &OcflRepo,
@@ -139,13 +266,160 @@ impl Cmd for DiffCmd {
         _config: &Config,
         _terminate: &AtomicBool,
     ) -> Result<()> {
-        if self.left == self.right {
+        let diffs = repo.compare_objects(
+            &self.object_id_a,
+            self.version_a.into(),
+            &self.object_id_b,
+            self.version_b.into(),
+        )?;
+
+        if diffs.is_empty() {
             return Ok(());
         }
 
-        let diffs = repo.diff(&self.object_id, Some(self.left), self.right)?;
+        let columns = vec![
+            Column::new(ColumnId::Difference, "Difference", Alignment::Left),
+            Column::new(ColumnId::LogicalPath, "Logical Path", Alignment::Left),
+        ];
+
+        let mut table = TableView::new(columns, Separator::Space, true, !args.no_styles);
+
+        diffs.iter().for_each(|diff| table.add_row(diff));
 
-        display_diffs(diffs, &args)
+        let mut writer = BufWriter::new(io::stdout());
+        let _ = table.write(&mut writer);
+        writer.flush()?;
+
+        raise_exit_status(1);
+        Ok(())
+    }
+}
+
+impl DiffCmd {
+    fn rename_detection(&self) -> RenameDetection {
+        if self.no_renames {
+            return RenameDetection::Off;
+        }
+
+        match self.renames {
+            Renames::All => RenameDetection::All,
+            Renames::OneToOne => RenameDetection::OneToOne,
+            Renames::Off => RenameDetection::Off,
+        }
+    }
+
+    /// Prints a patch of the changes between the two versions to stdout
+    fn emit_patch(&self, repo: &OcflRepo, left: VersionNum, right: VersionNum) -> Result<()> {
+        let patch = repo.create_patch(
+            &self.object_id,
+            Some(left),
+            right,
+            self.rename_detection(),
+            self.inline,
+        )?;
+
+        let mut writer = BufWriter::new(io::stdout());
+        patch.to_writer(&mut writer)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Displays the net changes between the two versions
+    fn display_net(
+        &self,
+        repo: &OcflRepo,
+        left: VersionNum,
+        right: VersionNum,
+        args: &GlobalArgs,
+    ) -> Result<()> {
+        if left == right {
+            return Ok(());
+        }
+
+        let diffs =
+            repo.diff_with_renames(&self.object_id, Some(left), right, self.rename_detection())?;
+
+        if self.stat {
+            let label = format!("{}..{}", left, right);
+            display_stats(&[StatLine::new(label, &diffs)], args);
+            Ok(())
+        } else {
+            display_diffs(diffs, args)
+        }
+    }
+
+    /// Displays the changes made in each version in the range. Each version's changes are written
+    /// as soon as they are computed, unless only the stats are displayed.
+    fn display_steps(
+        &self,
+        repo: &OcflRepo,
+        range: VersionRange,
+        args: &GlobalArgs,
+        terminate: &AtomicBool,
+    ) -> Result<()> {
+        let steps = repo.diff_steps(&self.object_id, range, self.rename_detection())?;
+
+        if self.stat {
+            let mut lines = Vec::new();
+
+            for step in steps {
+                if terminate.load(AOrdering::Acquire) {
+                    break;
+                }
+                let step = step?;
+                lines.push(StatLine::new(step.version_num.to_string(), &step.diffs));
+            }
+
+            display_stats(&lines, args);
+        } else {
+            let style = if args.no_styles {
+                &*style::DEFAULT
+            } else {
+                &*style::YELLOW
+            };
+
+            for (i, step) in steps.enumerate() {
+                if terminate.load(AOrdering::Acquire) {
+                    break;
+                }
+                let step = step?;
+
+                {
+                    let mut out = io::stdout().lock();
+                    if i > 0 {
+                        writeln!(out)?;
+                    }
+                    writeln!(
+                        out,
+                        "{}",
+                        style.paint(format!("Version {}", step.version_num.number))
+                    )?;
+                }
+
+                display_diffs(step.diffs, args)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns true if any of the paths in the diff are the path, or are under it
+fn diff_includes_path(diff: &Diff, path: &LogicalPath) -> bool {
+    let includes = |other: &LogicalPath| {
+        other == path
+            || other
+                .as_str()
+                .strip_prefix(path.as_str())
+                .is_some_and(|rest| rest.starts_with('/'))
+    };
+
+    match diff {
+        Diff::Added(other) | Diff::Modified(other) | Diff::Deleted(other) => includes(other),
+        Diff::Renamed { original, renamed } => original
+            .iter()
+            .chain(renamed.iter())
+            .any(|other| includes(other)),
     }
 }
 
@@ -170,46 +444,266 @@ fn display_diffs(diffs: Vec<Diff>, args: &GlobalArgs) -> Result<()> {
     Ok(())
 }
 
+fn display_stats(lines: &[StatLine], args: &GlobalArgs) {
+    let columns = vec![
+        Column::new(ColumnId::Version, "Version", Alignment::Left),
+        Column::new(ColumnId::Added, ADDED, Alignment::Right),
+        Column::new(ColumnId::Modified, MODIFIED, Alignment::Right),
+        Column::new(ColumnId::Deleted, DELETED, Alignment::Right),
+        Column::new(ColumnId::Renamed, RENAMED, Alignment::Right),
+    ];
+
+    let mut table = TableView::new(columns, Separator::Space, true, !args.no_styles);
+
+    lines.iter().for_each(|line| table.add_row(line));
+
+    let out = io::stdout();
+    let mut writer = BufWriter::new(out.lock());
+    let _ = table.write(&mut writer);
+}
+
 struct FormatVersion<'a> {
     details: &'a VersionDetails,
+    size: Option<&'a VersionContentSize>,
+    stats: Option<&'a StatLine>,
+    out_of_order: bool,
     enable_styling: bool,
 }
 
+struct VersionLine<'a> {
+    details: &'a VersionDetails,
+    size: Option<&'a VersionContentSize>,
+    /// The changes made in the version, relative the version before it
+    stats: Option<&'a StatLine>,
+    /// True if the version was created before the version that precedes it
+    out_of_order: bool,
+}
+
 struct DiffLine {
     diff: Diff,
 }
 
+/// The number of each kind of change made in a version, or range of versions
+struct StatLine {
+    label: String,
+    added: usize,
+    modified: usize,
+    deleted: usize,
+    renamed: usize,
+}
+
 impl<'a> FormatVersion<'a> {
     fn new(details: &'a VersionDetails, enable_styling: bool) -> Self {
         Self {
             details,
+            size: None,
+            stats: None,
+            out_of_order: false,
             enable_styling,
         }
     }
+
+    fn with_size(mut self, size: Option<&'a VersionContentSize>) -> Self {
+        self.size = size;
+        self
+    }
+
+    fn with_stats(mut self, stats: Option<&'a StatLine>) -> Self {
+        self.stats = stats;
+        self
+    }
+
+    fn with_out_of_order(mut self, out_of_order: bool) -> Self {
+        self.out_of_order = out_of_order;
+        self
+    }
 }
 
 impl fmt::Display for FormatVersion<'_> {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         let version = format!("Version {}", self.details.version_num.number);
-        let style = if self.enable_styling {
-            &*style::YELLOW
+        let (style, marker_style) = if self.enable_styling {
+            (&*style::YELLOW, &*style::RED)
+        } else {
+            (&*style::DEFAULT, &*style::DEFAULT)
+        };
+
+        let marker = if self.out_of_order {
+            marker_style
+                .paint(format!(
+                    " {} Created before the previous version",
+                    OUT_OF_ORDER_MARKER
+                ))
+                .to_string()
         } else {
-            &*style::DEFAULT
+            String::new()
         };
 
         write!(
             f,
-            "{}\n{:width$} {} <{}>\n{:width$} {}\n{:width$} {}\n",
+            "{}\n{:width$} {} <{}>\n{:width$} {}{}\n{:width$} {}\n",
             style.paint(version),
             "Author:",
             defaulted_str(&self.details.user_name, DEFAULT_USER),
             defaulted_str(&self.details.user_address, DEFAULT_USER),
             "Date:",
             self.details.created.to_rfc2822(),
+            marker,
             "Message:",
             self.details.message.as_ref().unwrap_or(&"".to_owned()),
             width = 8
-        )
+        )?;
+
+        if let Some(size) = self.size {
+            writeln!(
+                f,
+                "{:width$} {} files, {} bytes",
+                "Added:",
+                size.file_count,
+                size.byte_count,
+                width = 8
+            )?;
+        }
+
+        if let Some(stats) = self.stats {
+            writeln!(
+                f,
+                "{:width$} {} added, {} modified, {} deleted, {} renamed",
+                "Changes:",
+                stats.added,
+                stats.modified,
+                stats.deleted,
+                stats.renamed,
+                width = 8
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a> VersionLine<'a> {
+    fn new(
+        details: &'a VersionDetails,
+        size: Option<&'a VersionContentSize>,
+        stats: Option<&'a StatLine>,
+        out_of_order: bool,
+    ) -> Self {
+        Self {
+            details,
+            size,
+            stats,
+            out_of_order,
+        }
+    }
+}
+
+impl<'a> AsRow<'a> for VersionLine<'a> {
+    fn as_row(&'a self, columns: &[Column]) -> Row<'a> {
+        let mut cells = Vec::new();
+
+        for column in columns {
+            let cell = match (column.id, self.size) {
+                (ColumnId::Files, Some(size)) => TextCell::new(size.file_count.to_string()),
+                (ColumnId::Bytes, Some(size)) => TextCell::new(size.byte_count.to_string()),
+                (ColumnId::Files, None) | (ColumnId::Bytes, None) => TextCell::blank(),
+                (ColumnId::Added, _)
+                | (ColumnId::Modified, _)
+                | (ColumnId::Deleted, _)
+                | (ColumnId::Renamed, _) => match self.stats {
+                    Some(stats) => stats.count_cell(column),
+                    None => TextCell::blank(),
+                },
+                (ColumnId::Created, _) if self.out_of_order => TextCell::new(format!(
+                    "{} {}",
+                    self.details.created.format(DATE_FORMAT),
+                    OUT_OF_ORDER_MARKER
+                ))
+                .with_style(&style::RED),
+                _ => version_cell(self.details, column),
+            };
+
+            cells.push(cell);
+        }
+
+        Row::new(cells)
+    }
+}
+
+impl StatLine {
+    fn new(label: String, diffs: &[Diff]) -> Self {
+        let mut line = Self {
+            label,
+            added: 0,
+            modified: 0,
+            deleted: 0,
+            renamed: 0,
+        };
+
+        for diff in diffs {
+            match diff {
+                Diff::Added(_) => line.added += 1,
+                Diff::Modified(_) => line.modified += 1,
+                Diff::Deleted(_) => line.deleted += 1,
+                Diff::Renamed { .. } => line.renamed += 1,
+            }
+        }
+
+        line
+    }
+}
+
+impl StatLine {
+    /// Returns the cell that contains the count of the kind of change the column displays
+    fn count_cell<'a>(&self, column: &Column) -> TextCell<'a> {
+        match column.id {
+            ColumnId::Added => TextCell::new(self.added.to_string()),
+            ColumnId::Modified => TextCell::new(self.modified.to_string()),
+            ColumnId::Deleted => TextCell::new(self.deleted.to_string()),
+            ColumnId::Renamed => TextCell::new(self.renamed.to_string()),
+            _ => TextCell::blank(),
+        }
+    }
+}
+
+impl<'a> AsRow<'a> for StatLine {
+    fn as_row(&'a self, columns: &[Column]) -> Row<'a> {
+        let mut cells = Vec::new();
+
+        for column in columns {
+            let cell = match column.id {
+                ColumnId::Version => TextCell::new(&self.label).with_style(&style::GREEN),
+                _ => self.count_cell(column),
+            };
+
+            cells.push(cell);
+        }
+
+        Row::new(cells)
+    }
+}
+
+impl<'a> AsRow<'a> for ObjectDiff {
+    fn as_row(&'a self, columns: &[Column]) -> Row<'a> {
+        let mut cells = Vec::new();
+
+        for column in columns {
+            let cell = match column.id {
+                ColumnId::Difference => match self {
+                    ObjectDiff::OnlyInA(_) => TextCell::new(ONLY_IN_A).with_style(&style::RED),
+                    ObjectDiff::OnlyInB(_) => TextCell::new(ONLY_IN_B).with_style(&style::GREEN),
+                    ObjectDiff::ContentDiffers(_) => {
+                        TextCell::new(DIFFERS).with_style(&style::CYAN)
+                    }
+                },
+                ColumnId::LogicalPath => TextCell::new(self.path().as_str()),
+                _ => TextCell::blank(),
+            };
+
+            cells.push(cell);
+        }
+
+        Row::new(cells)
     }
 }
 
@@ -243,7 +737,7 @@ impl<'a> AsRow<'a> for DiffLine {
 }
 
 impl DiffLine {
-    fn path_display(&self) -> Cow<str> {
+    fn path_display(&self) -> Cow<'_, str> {
         match &self.diff {
             Diff::Renamed { original, renamed } => Cow::Owned(format!(
                 "{} -> {}",
@@ -285,34 +779,38 @@ impl Ord for DiffLine {
     }
 }
 
-impl<'a> AsRow<'a> for VersionDetails {
-    fn as_row(&'a self, columns: &[Column]) -> Row<'a> {
-        let mut cells = Vec::new();
-
-        for column in columns {
-            let cell = match column.id {
-                ColumnId::Version => {
-                    TextCell::new(self.version_num.to_string()).with_style(&style::GREEN)
-                }
-                ColumnId::Author => TextCell::new(defaulted_str(&self.user_name, DEFAULT_USER))
-                    .with_style(&style::BOLD),
-                ColumnId::Address => TextCell::new(defaulted_str(&self.user_address, DEFAULT_USER)),
-                ColumnId::Created => TextCell::new(self.created.format(DATE_FORMAT).to_string())
-                    .with_style(&style::YELLOW),
-                ColumnId::Message => match &self.message {
-                    Some(message) => TextCell::new(message),
-                    None => TextCell::blank(),
-                },
-                _ => TextCell::blank(),
-            };
-
-            cells.push(cell);
+fn version_cell<'a>(details: &'a VersionDetails, column: &Column) -> TextCell<'a> {
+    match column.id {
+        ColumnId::Version => {
+            TextCell::new(details.version_num.to_string()).with_style(&style::GREEN)
         }
-
-        Row::new(cells)
+        ColumnId::Author => {
+            TextCell::new(defaulted_str(&details.user_name, DEFAULT_USER)).with_style(&style::BOLD)
+        }
+        ColumnId::Address => TextCell::new(defaulted_str(&details.user_address, DEFAULT_USER)),
+        ColumnId::Created => TextCell::new(details.created.format(DATE_FORMAT).to_string())
+            .with_style(&style::YELLOW),
+        ColumnId::Message => match &details.message {
+            Some(message) => TextCell::new(message),
+            None => TextCell::blank(),
+        },
+        _ => TextCell::blank(),
     }
 }
 
+/// Returns the numbers of the versions whose created timestamps are earlier than the timestamps
+/// of the versions that precede them
+fn out_of_order_versions(versions: &[VersionDetails]) -> HashSet<VersionNum> {
+    let mut sorted: Vec<&VersionDetails> = versions.iter().collect();
+    sorted.sort_unstable_by_key(|version| version.version_num);
+
+    sorted
+        .windows(2)
+        .filter(|pair| pair[1].created < pair[0].created)
+        .map(|pair| pair[1].version_num)
+        .collect()
+}
+
 fn defaulted_str<'a>(value: &'a Option<String>, default: &'a str) -> &'a str {
     match value {
         Some(value) => value.as_ref(),
@@ -1,27 +1,41 @@
 use std::cmp::Ordering;
 use std::collections::{HashMap, HashSet};
 use std::convert::TryInto;
+use std::io;
 use std::io::{BufWriter, Write};
+use std::rc::Rc;
 use std::sync::atomic::{AtomicBool, Ordering as AOrdering};
-use std::{io, process};
 
+use chrono::{DateTime, Local};
 use globset::GlobBuilder;
-use log::error;
+use serde_json::{json, Value};
 
 use crate::cmd::opts::{ListCmd, *};
 use crate::cmd::table::{Alignment, AsRow, Column, ColumnId, Row, Separator, TableView, TextCell};
-use crate::cmd::{paint, style, Cmd, GlobalArgs, DATE_FORMAT};
+use crate::cmd::{paint, raise_exit_status, style, Cmd, GlobalArgs, DATE_FORMAT};
 use crate::config::Config;
 use crate::ocfl::{
-    FileDetails, InventoryPath, LogicalPath, ObjectVersion, ObjectVersionDetails, OcflRepo, Result,
+    ContentPathVersion, Diff, DigestAlgorithm, FileDetails, InventoryPath, ListFilter, LogicalPath,
+    ObjectError, ObjectFingerprint, ObjectVersion, ObjectVersionDetails, OcflRepo, Result,
+    VersionNum, VersionRef,
 };
 
 const VERSION: &str = "Version";
 const UPDATED: &str = "Updated";
+const ORIGINAL: &str = "Original";
 const OBJECT_ID: &str = "Object ID";
 const PHYSICAL_PATH: &str = "Physical Path";
+const CONTENT_PATH: &str = "Content Path";
+const ORIGIN: &str = "Origin";
 const LOGICAL_PATH: &str = "Logical Path";
 const DIGEST: &str = "Digest";
+const CHANGE: &str = "Change";
+const FINGERPRINT: &str = "Fingerprint";
+
+const UNCHANGED: &str = "Unchanged";
+const ADDED: &str = "Added";
+const MODIFIED: &str = "Modified";
+const DELETED: &str = "Deleted";
 
 impl Cmd for ListCmd {
     fn exec(
@@ -49,38 +63,85 @@ impl ListCmd {
         let iter = if self.staged {
             repo.list_staged_objects(self.object_id.as_deref())?
         } else {
-            repo.list_objects(self.object_id.as_deref())?
+            repo.list_objects_filtered(self.object_id.as_deref(), self.list_filter())?
         };
 
-        if (self.sort == Field::None || self.sort == Field::Default)
-            && ((!self.long && !self.physical) || self.tsv)
-        {
+        #[allow(clippy::result_large_err)]
+        let iter = Box::new(iter.map(|object| self.object_listing(repo, object)));
+
+        let quiet = args.quiet;
+
+        let skipped = if self.format == ListFormat::Ndjson {
+            self.write_objects_as_json(terminate, iter)
+        } else if self.is_unsorted() && ((!self.long && !self.physical) || self.is_tsv()) {
             // It's safe to stream the results so long as they are not sorted and do not need
             // to be displayed in a table
-            self.stream_objects(args, iter);
+            self.stream_objects(args, iter)
         } else {
-            self.write_objects_to_table(args, terminate, iter);
+            self.write_objects_to_table(args, terminate, iter)
+        };
+
+        if !skipped.is_empty() {
+            if !quiet {
+                print_skipped_summary(&skipped);
+            }
+            if !self.skip_errors {
+                raise_exit_status(1);
+            }
         }
 
         Ok(())
     }
 
+    /// Pairs an object with its fingerprint, which is only read when '--fingerprint' is specified
+    #[allow(clippy::result_large_err)]
+    fn object_listing(
+        &self,
+        repo: &OcflRepo,
+        object: Result<ObjectVersionDetails, ObjectError>,
+    ) -> Result<ObjectListing, ObjectError> {
+        let details = object?;
+
+        let fingerprint = if self.fingerprint {
+            match repo.object_fingerprint(&details.id) {
+                Ok(fingerprint) => Some(fingerprint),
+                Err(e) => {
+                    return Err(ObjectError::new(
+                        Some(details.id),
+                        Some(details.object_root),
+                        e,
+                    ))
+                }
+            }
+        } else {
+            None
+        };
+
+        Ok(ObjectListing {
+            details,
+            fingerprint,
+        })
+    }
+
+    /// Writes objects to stdout as they are read, and returns the objects that could not be read
     fn stream_objects<'a>(
         &self,
         args: GlobalArgs,
-        iter: Box<dyn Iterator<Item = Result<ObjectVersionDetails>> + 'a>,
-    ) {
+        iter: Box<dyn Iterator<Item = Result<ObjectListing, ObjectError>> + 'a>,
+    ) -> Vec<ObjectError> {
         let mut out = BufWriter::new(io::stdout());
         let isatty = atty::is(atty::Stream::Stdout);
-        let mut has_errors = false;
+        let mut skipped = Vec::new();
         let mut header_printed = false;
 
         for object in iter {
-            if let Err(e) = object {
-                has_errors = true;
-                error!("{:#}", e);
-                continue;
-            }
+            let object = match object {
+                Ok(object) => object,
+                Err(e) => {
+                    skipped.push(e);
+                    continue;
+                }
+            };
 
             if !header_printed && self.header {
                 header_printed = true;
@@ -100,23 +161,28 @@ impl ListCmd {
                     header_line.push_str(&paint(args.no_styles, *style::UNDERLINE, VERSION));
                 }
 
+                if self.fingerprint {
+                    header_line.push('\t');
+                    header_line.push_str(&paint(args.no_styles, *style::UNDERLINE, FINGERPRINT));
+                }
+
                 let _ = writeln!(out, "{}", header_line);
             }
 
-            let object = object.unwrap();
             let mut line = "".to_string();
 
             if self.long {
                 line.push_str(&paint(
                     args.no_styles,
                     *style::GREEN,
-                    object.version_details.version_num.to_string(),
+                    object.details.version_details.version_num.to_string(),
                 ));
                 line.push('\t');
                 line.push_str(&paint(
                     args.no_styles,
                     *style::YELLOW,
                     object
+                        .details
                         .version_details
                         .created
                         .format(DATE_FORMAT)
@@ -125,11 +191,16 @@ impl ListCmd {
                 line.push('\t');
             }
 
-            line.push_str(&paint(args.no_styles, *style::BOLD, &object.id));
+            line.push_str(&paint(args.no_styles, *style::BOLD, &object.details.id));
 
             if self.physical {
                 line.push('\t');
-                line.push_str(&object.object_root);
+                line.push_str(&object.details.object_root);
+            }
+
+            if let Some(fingerprint) = &object.fingerprint {
+                line.push('\t');
+                line.push_str(&fingerprint_text(fingerprint));
             }
 
             let _ = writeln!(out, "{}", line);
@@ -140,50 +211,93 @@ impl ListCmd {
 
         let _ = out.flush();
 
-        if has_errors {
-            process::exit(1);
+        skipped
+    }
+
+    /// Writes each object to stdout as a JSON object on its own line, and returns the objects that
+    /// could not be read. Objects are written as they are read, unless they must be sorted.
+    fn write_objects_as_json<'a>(
+        &self,
+        terminate: &AtomicBool,
+        iter: Box<dyn Iterator<Item = Result<ObjectListing, ObjectError>> + 'a>,
+    ) -> Vec<ObjectError> {
+        let mut out = BufWriter::new(io::stdout());
+        let isatty = atty::is(atty::Stream::Stdout);
+        let mut skipped = Vec::new();
+        let mut objects = Vec::new();
+
+        for object in iter {
+            if terminate.load(AOrdering::Acquire) {
+                return skipped;
+            }
+
+            match object {
+                Ok(object) if self.is_unsorted() => {
+                    let _ = writeln!(out, "{}", object_json(&object));
+                    if isatty {
+                        let _ = out.flush();
+                    }
+                }
+                Ok(object) => objects.push(object),
+                Err(e) => skipped.push(e),
+            }
         }
+
+        objects.sort_unstable_by(|a, b| {
+            if self.reverse {
+                cmp_objects(&self.sort, &b.details, &a.details)
+            } else {
+                cmp_objects(&self.sort, &a.details, &b.details)
+            }
+        });
+
+        for object in &objects {
+            let _ = writeln!(out, "{}", object_json(object));
+        }
+
+        let _ = out.flush();
+
+        skipped
     }
 
+    /// Writes objects to stdout in a table, and returns the objects that could not be read
     fn write_objects_to_table<'a>(
         &self,
         args: GlobalArgs,
         terminate: &AtomicBool,
-        iter: Box<dyn Iterator<Item = Result<ObjectVersionDetails>> + 'a>,
-    ) {
-        let mut has_errors = false;
+        iter: Box<dyn Iterator<Item = Result<ObjectListing, ObjectError>> + 'a>,
+    ) -> Vec<ObjectError> {
+        let mut skipped = Vec::new();
         let mut objects = Vec::new();
 
         for object in iter {
-            if let Err(e) = object {
-                has_errors = true;
-                error!("{:#}", e);
-                continue;
+            if terminate.load(AOrdering::Acquire) {
+                return skipped;
             }
 
-            if terminate.load(AOrdering::Acquire) {
-                return;
+            match object {
+                Ok(object) => objects.push(object),
+                Err(e) => skipped.push(e),
             }
-            objects.push(object.unwrap());
         }
 
         objects.sort_unstable_by(|a, b| {
             if self.reverse {
-                cmp_objects(&self.sort, b, a)
+                cmp_objects(&self.sort, &b.details, &a.details)
             } else {
-                cmp_objects(&self.sort, a, b)
+                cmp_objects(&self.sort, &a.details, &b.details)
             }
         });
 
         if terminate.load(AOrdering::Acquire) {
-            return;
+            return skipped;
         }
 
         let mut table = self.object_table(args);
 
         for object in &objects {
             if terminate.load(AOrdering::Acquire) {
-                return;
+                return skipped;
             }
 
             table.add_row(object);
@@ -194,9 +308,7 @@ impl ListCmd {
         let _ = table.write(&mut writer);
         let _ = writer.flush();
 
-        if has_errors {
-            process::exit(1);
-        }
+        skipped
     }
 
     fn list_object_contents(
@@ -206,13 +318,32 @@ impl ListCmd {
         _terminate: &AtomicBool,
     ) -> Result<()> {
         let object_id = self.object_id.as_ref().unwrap();
-        let object = if self.staged {
-            repo.get_staged_object(object_id)?
+
+        let mut listings = if self.diff_markers {
+            self.staged_listings_with_markers(repo, object_id)?
         } else {
-            repo.get_object(object_id, self.version.into())?
+            let object = if self.staged {
+                repo.get_staged_object(object_id)?
+            } else {
+                repo.get_object_filtered(object_id, self.version.into(), self.list_filter())?
+            };
+
+            self.filter_paths_to_listings(object)?
         };
 
-        let mut listings = self.filter_paths_to_listings(object)?;
+        if self.timestamps == Timestamps::Original {
+            let timestamps = if self.staged {
+                repo.get_staged_original_timestamps(object_id)?
+            } else {
+                repo.get_original_timestamps(object_id, self.version.into())?
+            };
+
+            for listing in &mut listings {
+                if let Listing::File(file) = listing {
+                    file.original = timestamps.get(&file.logical_path).copied();
+                }
+            }
+        }
 
         listings.sort_unstable_by(|a, b| {
             if self.reverse {
@@ -222,17 +353,29 @@ impl ListCmd {
             }
         });
 
-        let mut table = self.object_content_table(args);
-        listings.iter().for_each(|listing| table.add_row(listing));
-
         let out = io::stdout();
         let mut writer = BufWriter::new(out.lock());
-        let _ = table.write(&mut writer);
+
+        if self.format == ListFormat::Ndjson {
+            for listing in &listings {
+                let _ = writeln!(writer, "{}", listing_json(listing));
+            }
+        } else {
+            let digest_algorithm = listings.iter().find_map(|listing| match listing {
+                Listing::File(file) => Some(file.details.digest_algorithm),
+                Listing::Dir(_) => None,
+            });
+            let mut table = self.object_content_table(args, digest_algorithm);
+            listings.iter().for_each(|listing| table.add_row(listing));
+            let _ = table.write(&mut writer);
+        }
+
+        let _ = writer.flush();
 
         Ok(())
     }
 
-    fn object_table(&self, args: GlobalArgs) -> TableView {
+    fn object_table(&self, args: GlobalArgs) -> TableView<'_> {
         let mut columns = Vec::new();
 
         if self.long {
@@ -250,15 +393,38 @@ impl ListCmd {
             ));
         }
 
+        if self.fingerprint {
+            columns.push(Column::new(ColumnId::Digest, FINGERPRINT, Alignment::Left));
+        }
+
         TableView::new(columns, self.separator(), self.header, !args.no_styles)
     }
 
-    fn object_content_table(&self, args: GlobalArgs) -> TableView {
+    fn object_content_table(
+        &self,
+        args: GlobalArgs,
+        digest_algorithm: Option<DigestAlgorithm>,
+    ) -> TableView<'_> {
         let mut columns = Vec::new();
 
+        if self.diff_markers {
+            columns.push(Column::new(ColumnId::Operation, CHANGE, Alignment::Left));
+        }
+
         if self.long {
             columns.push(Column::new(ColumnId::Version, VERSION, Alignment::Right));
-            columns.push(Column::new(ColumnId::Created, UPDATED, Alignment::Left));
+            match self.timestamps {
+                Timestamps::Updated => {
+                    columns.push(Column::new(ColumnId::Created, UPDATED, Alignment::Left))
+                }
+                Timestamps::Original => {
+                    columns.push(Column::new(ColumnId::Original, ORIGINAL, Alignment::Left))
+                }
+            }
+        }
+
+        if self.origin {
+            columns.push(Column::new(ColumnId::Origin, ORIGIN, Alignment::Right));
         }
 
         columns.push(Column::new(
@@ -267,6 +433,14 @@ impl ListCmd {
             Alignment::Left,
         ));
 
+        if self.content_path {
+            columns.push(Column::new(
+                ColumnId::ContentPath,
+                CONTENT_PATH,
+                Alignment::Left,
+            ));
+        }
+
         if self.physical {
             columns.push(Column::new(
                 ColumnId::PhysicalPath,
@@ -276,16 +450,115 @@ impl ListCmd {
         }
 
         if self.digest {
-            columns.push(Column::new(ColumnId::Digest, DIGEST, Alignment::Left));
+            let mut column = Column::new(ColumnId::Digest, DIGEST, Alignment::Left);
+            if let (Some(length), Some(algorithm)) = (self.digest_length, digest_algorithm) {
+                // The length does not include the 'algorithm:' prefix
+                column = column.with_max_width(algorithm.to_string().len() + 1 + length);
+            }
+            columns.push(column);
         }
 
         TableView::new(columns, self.separator(), self.header, !args.no_styles)
     }
 
-    fn filter_paths_to_listings(&self, object: ObjectVersion) -> Result<Vec<Listing>> {
+    /// Merges the object's HEAD state with its staged changes, marking every matching path
+    /// with how it differs from HEAD. Paths that are deleted in the staged version are listed
+    /// using their HEAD details.
+    fn staged_listings_with_markers(
+        &self,
+        repo: &OcflRepo,
+        object_id: &str,
+    ) -> Result<Vec<Listing>> {
+        let staged = repo.get_staged_object(object_id)?;
+
+        // Objects that have only been staged do not have a HEAD version yet
+        let head = if repo.object_exists(object_id)? {
+            Some(repo.get_object(object_id, VersionRef::Head)?)
+        } else {
+            None
+        };
+
+        let mut changes: HashMap<Rc<LogicalPath>, Change> = HashMap::new();
+
+        for diff in repo.diff_staged(object_id)? {
+            match diff {
+                Diff::Added(path) => {
+                    changes.insert(path, Change::Added);
+                }
+                Diff::Modified(path) => {
+                    changes.insert(path, Change::Modified);
+                }
+                Diff::Deleted(path) => {
+                    changes.insert(path, Change::Deleted);
+                }
+                Diff::Renamed { original, renamed } => {
+                    original.into_iter().for_each(|path| {
+                        changes.insert(path, Change::Deleted);
+                    });
+                    renamed.into_iter().for_each(|path| {
+                        changes.insert(path, Change::Added);
+                    });
+                }
+            }
+        }
+
+        let matcher = GlobBuilder::new(&self.path_glob())
+            .backslash_escape(true)
+            .build()?
+            .compile_matcher();
+
         let mut listings = Vec::new();
 
-        let glob = match &self.path {
+        for (path, details) in staged.state {
+            if matcher.is_match(path.as_str()) {
+                let change = changes.get(&path).copied().unwrap_or(Change::Unchanged);
+                listings.push(Listing::File(ContentListing {
+                    logical_path: path.to_string(),
+                    details,
+                    change: Some(change),
+                    original: None,
+                }));
+            }
+        }
+
+        if let Some(head) = head {
+            for (path, details) in head.state {
+                if changes.get(&path) == Some(&Change::Deleted) && matcher.is_match(path.as_str()) {
+                    listings.push(Listing::File(ContentListing {
+                        logical_path: path.to_string(),
+                        details,
+                        change: Some(Change::Deleted),
+                        original: None,
+                    }));
+                }
+            }
+        }
+
+        Ok(listings)
+    }
+
+    fn list_filter(&self) -> ListFilter {
+        let mut filter = ListFilter::new();
+        if let Some(date) = self.before {
+            filter = filter.with_created_before(date);
+        }
+        if let Some(date) = self.after {
+            filter = filter.with_created_after(date);
+        }
+        if let Some(bytes) = self.min_size {
+            filter = filter.with_min_size(bytes);
+        }
+        if let Some(bytes) = self.max_size {
+            filter = filter.with_max_size(bytes);
+        }
+        if self.frozen {
+            filter = filter.with_frozen();
+        }
+        filter
+    }
+
+    fn path_glob(&self) -> String {
+        match &self.path {
             Some(path) => {
                 let trimmed = path.trim_start_matches('/');
                 if trimmed.is_empty() {
@@ -295,7 +568,13 @@ impl ListCmd {
                 }
             }
             None => "*".to_string(),
-        };
+        }
+    }
+
+    fn filter_paths_to_listings(&self, object: ObjectVersion) -> Result<Vec<Listing>> {
+        let mut listings = Vec::new();
+
+        let glob = self.path_glob();
 
         let glob_trailing_slash = glob.ends_with('/');
 
@@ -318,6 +597,8 @@ impl ListCmd {
                 listings.push(Listing::File(ContentListing {
                     logical_path: path.to_string(),
                     details,
+                    change: None,
+                    original: None,
                 }));
             } else {
                 not_matched.insert(path, details);
@@ -357,6 +638,8 @@ impl ListCmd {
                         listings.push(Listing::File(ContentListing {
                             logical_path: path.to_string(),
                             details,
+                            change: None,
+                            original: None,
                         }));
                     }
                 }
@@ -378,8 +661,16 @@ impl ListCmd {
         Ok(listings)
     }
 
+    fn is_unsorted(&self) -> bool {
+        self.sort == Field::None || self.sort == Field::Default
+    }
+
+    fn is_tsv(&self) -> bool {
+        self.tsv || self.format == ListFormat::Tsv
+    }
+
     fn separator(&self) -> Separator {
-        if self.tsv {
+        if self.is_tsv() {
             Separator::Tab
         } else {
             Separator::Space
@@ -387,6 +678,79 @@ impl ListCmd {
     }
 }
 
+/// Prints a summary of the objects that were skipped because they could not be read to stderr
+pub(crate) fn print_skipped_summary(skipped: &[ObjectError]) {
+    let stderr = io::stderr();
+    let mut err = stderr.lock();
+
+    let _ = writeln!(
+        err,
+        "Skipped {} object{} that could not be read:",
+        skipped.len(),
+        if skipped.len() == 1 { "" } else { "s" }
+    );
+
+    for error in skipped {
+        let _ = writeln!(err, "  {}", error);
+    }
+}
+
+fn object_json(object: &ObjectListing) -> Value {
+    let details = &object.details;
+    let mut value = json!({
+        "object_id": details.id,
+        "version": details.version_details.version_num.to_string(),
+        "created": details.version_details.created.to_rfc3339(),
+        "digest_algorithm": details.digest_algorithm.to_string(),
+        "object_root": details.object_root,
+    });
+    if let Some(fingerprint) = &object.fingerprint {
+        value["fingerprint"] = json!(fingerprint_text(fingerprint));
+        value["e_tag"] = json!(fingerprint.e_tag);
+    }
+    value
+}
+
+/// Formats a fingerprint as 'algorithm:digest'
+fn fingerprint_text(fingerprint: &ObjectFingerprint) -> String {
+    format!(
+        "{}:{}",
+        fingerprint.digest_algorithm, fingerprint.inventory_digest
+    )
+}
+
+fn listing_json(listing: &Listing) -> Value {
+    match listing {
+        Listing::File(file) => {
+            let mut value = json!({
+            "type": "file",
+            "logical_path": file.logical_path,
+            "version": file.details.last_update.version_num.to_string(),
+            "created": file.details.last_update.created.to_rfc3339(),
+            "digest_algorithm": file.details.digest_algorithm.to_string(),
+            "digest": file.details.digest.to_string(),
+            "physical_path": file.details.storage_path,
+            "content_path": file.details.content_path.as_str(),
+            "origin": origin(&file.details).to_string(),
+            "change": file.change.map(|change| match change {
+                Change::Unchanged => "unchanged",
+                Change::Added => "added",
+                Change::Modified => "modified",
+                Change::Deleted => "deleted",
+            }),
+            });
+            if let Some(original) = file.original {
+                value["original"] = json!(original.to_rfc3339());
+            }
+            value
+        }
+        Listing::Dir(dir) => json!({
+            "type": "dir",
+            "logical_path": dir,
+        }),
+    }
+}
+
 fn cmp_objects(field: &Field, a: &ObjectVersionDetails, b: &ObjectVersionDetails) -> Ordering {
     match field {
         Field::Name => natord::compare(&a.id, &b.id),
@@ -410,11 +774,14 @@ fn cmp_listings(field: &Field, a: &Listing, b: &Listing) -> Ordering {
                 .last_update
                 .version_num
                 .cmp(&b.details.last_update.version_num),
-            Field::Updated => a
-                .details
-                .last_update
-                .created
-                .cmp(&b.details.last_update.created),
+            Field::Updated => match (a.original, b.original) {
+                (None, None) => a
+                    .details
+                    .last_update
+                    .created
+                    .cmp(&b.details.last_update.created),
+                (a, b) => a.cmp(&b),
+            },
             Field::Physical => natord::compare(&a.details.storage_path, &b.details.storage_path),
             Field::Digest => a.details.digest.cmp(&b.details.digest),
             Field::None => Ordering::Equal,
@@ -436,6 +803,15 @@ fn cmp_listings(field: &Field, a: &Listing, b: &Listing) -> Ordering {
     }
 }
 
+/// The version the file's content was added to the object in. Content that is staged is added
+/// in the version that is being staged.
+fn origin(details: &FileDetails) -> VersionNum {
+    match details.content_path.version {
+        ContentPathVersion::VersionNum(version_num) => version_num,
+        ContentPathVersion::MutableHead => details.last_update.version_num,
+    }
+}
+
 fn create_logical_dirs(object: &ObjectVersion) -> HashSet<LogicalPath> {
     let mut dirs = HashSet::with_capacity(object.state.len());
 
@@ -453,6 +829,12 @@ fn create_logical_dirs(object: &ObjectVersion) -> HashSet<LogicalPath> {
     dirs
 }
 
+/// An object in a listing, and its fingerprint when '--fingerprint' is specified
+struct ObjectListing {
+    details: ObjectVersionDetails,
+    fingerprint: Option<ObjectFingerprint>,
+}
+
 enum Listing {
     File(ContentListing),
     Dir(String),
@@ -461,6 +843,18 @@ enum Listing {
 struct ContentListing {
     logical_path: String,
     details: FileDetails,
+    change: Option<Change>,
+    /// The file's original modification time, only populated with `--timestamps original`
+    original: Option<DateTime<Local>>,
+}
+
+/// How a staged file differs from the object's HEAD version
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Change {
+    Unchanged,
+    Added,
+    Modified,
+    Deleted,
 }
 
 impl<'a> AsRow<'a> for Listing {
@@ -493,6 +887,13 @@ impl<'a> AsRow<'a> for ContentListing {
 
         for column in columns {
             let cell = match column.id {
+                ColumnId::Operation => match self.change {
+                    Some(Change::Unchanged) => TextCell::new(UNCHANGED),
+                    Some(Change::Added) => TextCell::new(ADDED).with_style(&style::GREEN),
+                    Some(Change::Modified) => TextCell::new(MODIFIED).with_style(&style::CYAN),
+                    Some(Change::Deleted) => TextCell::new(DELETED).with_style(&style::RED),
+                    None => TextCell::blank(),
+                },
                 ColumnId::Version => {
                     TextCell::new(self.details.last_update.version_num.to_string())
                         .with_style(&style::GREEN)
@@ -505,7 +906,16 @@ impl<'a> AsRow<'a> for ContentListing {
                         .to_string(),
                 )
                 .with_style(&style::YELLOW),
+                ColumnId::Original => match self.original {
+                    Some(original) => TextCell::new(original.format(DATE_FORMAT).to_string())
+                        .with_style(&style::YELLOW),
+                    None => TextCell::blank(),
+                },
+                ColumnId::Origin => {
+                    TextCell::new(origin(&self.details).to_string()).with_style(&style::GREEN)
+                }
                 ColumnId::LogicalPath => TextCell::new(&self.logical_path).with_style(&style::BOLD),
+                ColumnId::ContentPath => TextCell::new(self.details.content_path.as_str()),
                 ColumnId::PhysicalPath => TextCell::new(&self.details.storage_path),
                 ColumnId::Digest => TextCell::new(format!(
                     "{}:{}",
@@ -521,20 +931,29 @@ impl<'a> AsRow<'a> for ContentListing {
     }
 }
 
-impl<'a> AsRow<'a> for ObjectVersionDetails {
+impl<'a> AsRow<'a> for ObjectListing {
     fn as_row(&'a self, columns: &[Column]) -> Row<'a> {
+        let details = &self.details;
         let mut cells = Vec::new();
 
         for column in columns {
             let cell = match column.id {
-                ColumnId::Version => TextCell::new(self.version_details.version_num.to_string())
+                ColumnId::Version => TextCell::new(details.version_details.version_num.to_string())
                     .with_style(&style::GREEN),
-                ColumnId::Created => {
-                    TextCell::new(self.version_details.created.format(DATE_FORMAT).to_string())
-                        .with_style(&style::YELLOW)
-                }
-                ColumnId::ObjectId => TextCell::new(&self.id).with_style(&style::BOLD),
-                ColumnId::PhysicalPath => TextCell::new(&self.object_root),
+                ColumnId::Created => TextCell::new(
+                    details
+                        .version_details
+                        .created
+                        .format(DATE_FORMAT)
+                        .to_string(),
+                )
+                .with_style(&style::YELLOW),
+                ColumnId::ObjectId => TextCell::new(&details.id).with_style(&style::BOLD),
+                ColumnId::PhysicalPath => TextCell::new(&details.object_root),
+                ColumnId::Digest => match &self.fingerprint {
+                    Some(fingerprint) => TextCell::new(fingerprint_text(fingerprint)),
+                    None => TextCell::blank(),
+                },
                 _ => TextCell::blank(),
             };
 
@@ -0,0 +1,146 @@
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::path::Path;
+
+use chrono::{Local, SecondsFormat};
+use env_logger::fmt::Formatter;
+use env_logger::{Builder, Logger, Target, WriteStyle};
+use log::{LevelFilter, Log, Metadata, Record};
+use serde_json::{json, Value};
+
+use crate::cmd::opts::{LogFormat, RocflArgs};
+use crate::ocfl::{Result, RocflError, AUDIT_LOG_TARGET};
+
+/// Dispatches log records to the console and, optionally, a log file. Each destination has
+/// its own level filters.
+struct RocflLogger {
+    console: Logger,
+    file: Option<Logger>,
+}
+
+impl Log for RocflLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.console.enabled(metadata)
+            || self
+                .file
+                .as_ref()
+                .map(|file| file.enabled(metadata))
+                .unwrap_or(false)
+    }
+
+    fn log(&self, record: &Record) {
+        self.console.log(record);
+        if let Some(file) = &self.file {
+            file.log(record);
+        }
+    }
+
+    fn flush(&self) {
+        self.console.flush();
+        if let Some(file) = &self.file {
+            file.flush();
+        }
+    }
+}
+
+/// Initializes the global logger based on the logging options in `args`. This must only be
+/// called once.
+pub fn init_logging(args: &RocflArgs) -> Result<()> {
+    let console = console_logger(args);
+    let file = match &args.log_file {
+        Some(path) => Some(file_logger(path, args)?),
+        None => None,
+    };
+
+    let max_level = file
+        .as_ref()
+        .map(|file| file.filter().max(console.filter()))
+        .unwrap_or_else(|| console.filter());
+
+    log::set_boxed_logger(Box::new(RocflLogger { console, file }))
+        .map_err(|e| RocflError::General(format!("Failed to initialize logging: {}", e)))?;
+    log::set_max_level(max_level);
+
+    Ok(())
+}
+
+fn console_logger(args: &RocflArgs) -> Logger {
+    let log_level = if args.quiet {
+        LevelFilter::Off
+    } else if args.verbose {
+        LevelFilter::Info
+    } else {
+        LevelFilter::Warn
+    };
+
+    let mut builder = Builder::from_default_env();
+    builder.filter_level(log_level);
+
+    if !args.quiet {
+        if let Some(filter) = &args.log_filter {
+            builder.parse_filters(filter);
+        }
+    }
+
+    match args.log_format {
+        LogFormat::Text => {
+            builder
+                .format_timestamp(None)
+                .format_module_path(false)
+                .format_target(false);
+        }
+        LogFormat::Json => {
+            builder.format(format_json);
+        }
+    }
+
+    builder.build()
+}
+
+fn file_logger(path: &Path, args: &RocflArgs) -> Result<Logger> {
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| RocflError::io_path("open log file", path, e))?;
+
+    let mut builder = Builder::new();
+    builder
+        .filter_level(LevelFilter::Info)
+        .write_style(WriteStyle::Never)
+        .target(Target::Pipe(Box::new(file)));
+
+    if let Some(filter) = &args.log_filter {
+        builder.parse_filters(filter);
+    }
+
+    match args.log_format {
+        LogFormat::Text => {
+            builder.format_timestamp_millis().format_module_path(false);
+        }
+        LogFormat::Json => {
+            builder.format(format_json);
+        }
+    }
+
+    Ok(builder.build())
+}
+
+/// Writes a record as a single line JSON object. The messages of audit records are JSON
+/// objects themselves, and are embedded as `event` rather than `message`.
+fn format_json(buf: &mut Formatter, record: &Record) -> io::Result<()> {
+    let message = record.args().to_string();
+
+    let mut entry = json!({
+        "timestamp": Local::now().to_rfc3339_opts(SecondsFormat::Millis, false),
+        "level": record.level().as_str(),
+        "target": record.target(),
+    });
+
+    match serde_json::from_str::<Value>(&message) {
+        Ok(event) if record.target() == AUDIT_LOG_TARGET => entry["event"] = event,
+        _ => entry["message"] = Value::String(message),
+    }
+
+    writeln!(buf, "{}", entry)
+}
@@ -0,0 +1,353 @@
+//! Checksum manifests of object versions. Checksum manifests use the format of tools like
+//! `sha512sum`, including its escaping of paths that contain backslashes or newlines, and CSV
+//! manifests have the columns `digest,algorithm,logical_path,version`.
+
+use std::collections::{BTreeMap, HashSet};
+use std::fs::File;
+use std::io::{self, BufWriter, Read, Write};
+use std::rc::Rc;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::cmd::opts::{
+    ManifestCmd, ManifestCommand, ManifestExportArgs, ManifestFormat, ManifestImportArgs,
+};
+use crate::cmd::{paint, raise_exit_status, style, Cmd, GlobalArgs};
+use crate::config::Config;
+use crate::ocfl::{
+    DigestAlgorithm, FileDetails, LogicalPath, ObjectVersion, OcflRepo, Result, RocflError,
+};
+
+const CSV_HEADER: [&str; 4] = ["digest", "algorithm", "logical_path", "version"];
+
+impl Cmd for ManifestCmd {
+    fn exec(
+        &self,
+        repo: &OcflRepo,
+        args: GlobalArgs,
+        _config: &Config,
+        terminate: &AtomicBool,
+    ) -> Result<()> {
+        match &self.command {
+            ManifestCommand::Export(export) => export_manifest(repo, export),
+            ManifestCommand::Import(import) => verify_manifest(repo, import, args, terminate),
+        }
+    }
+}
+
+/// An entry in a manifest that was read from a file
+struct ManifestEntry {
+    digest: String,
+    algorithm: DigestAlgorithm,
+    path: String,
+}
+
+fn export_manifest(repo: &OcflRepo, args: &ManifestExportArgs) -> Result<()> {
+    let object = repo.get_object(&args.object_id, args.version.into())?;
+    let files = sorted_files(&object);
+
+    let mut out = BufWriter::new(io::stdout());
+
+    match args.format {
+        ManifestFormat::Checksum => {
+            for (path, (_, details)) in files {
+                let _ = writeln!(out, "{}", checksum_line(&details.digest.to_string(), path));
+            }
+        }
+        ManifestFormat::Csv => {
+            let _ = writeln!(out, "{}", CSV_HEADER.join(","));
+            for (path, (_, details)) in files {
+                let _ = writeln!(
+                    out,
+                    "{},{},{},{}",
+                    details.digest,
+                    details.digest_algorithm,
+                    csv_field(path),
+                    details.last_update.version_num
+                );
+            }
+        }
+    }
+
+    out.flush()?;
+
+    Ok(())
+}
+
+fn verify_manifest(
+    repo: &OcflRepo,
+    args: &ManifestImportArgs,
+    global: GlobalArgs,
+    terminate: &AtomicBool,
+) -> Result<()> {
+    let object = repo.get_object(&args.object_id, args.version.into())?;
+    let version_num = object.version_details.version_num;
+
+    let contents = read_manifest(args)?;
+    let entries = match args.format {
+        ManifestFormat::Checksum => parse_checksum(
+            &contents,
+            args.digest_algorithm.unwrap_or(object.digest_algorithm),
+        )?,
+        ManifestFormat::Csv => parse_csv(&contents)?,
+    };
+
+    let files = sorted_files(&object);
+    let mut problems = Vec::new();
+    let mut seen = HashSet::new();
+
+    for entry in &entries {
+        if terminate.load(Ordering::Acquire) {
+            break;
+        }
+
+        let (path, details) = match files.get(entry.path.as_str()) {
+            Some(file) => *file,
+            None => {
+                problems.push(format!("{} is not in the object", entry.path));
+                continue;
+            }
+        };
+
+        if !seen.insert(path) {
+            continue;
+        }
+
+        let actual = if entry.algorithm == details.digest_algorithm {
+            details.digest.to_string()
+        } else {
+            let mut writer = entry.algorithm.writer(io::sink());
+            repo.get_object_file(&args.object_id, path, Some(version_num).into(), &mut writer)?;
+            writer.finalize_hex().to_string()
+        };
+
+        if !actual.eq_ignore_ascii_case(&entry.digest) {
+            problems.push(format!(
+                "{} has {} digest {} but the manifest has {}",
+                entry.path, entry.algorithm, actual, entry.digest
+            ));
+        }
+    }
+
+    if !terminate.load(Ordering::Acquire) {
+        for (path, (logical_path, _)) in &files {
+            if !seen.contains(logical_path) {
+                problems.push(format!("{} is not in the manifest", path));
+            }
+        }
+    }
+
+    let mut out = BufWriter::new(io::stdout());
+
+    if problems.is_empty() {
+        if !global.quiet {
+            let _ = writeln!(
+                out,
+                "Object {} version {} matches the manifest",
+                object.id, version_num
+            );
+        }
+        out.flush()?;
+    } else {
+        for problem in &problems {
+            let _ = writeln!(
+                out,
+                "{} {}",
+                paint(global.no_styles, *style::RED, "[Problem]"),
+                problem
+            );
+        }
+        out.flush()?;
+        raise_exit_status(1);
+    }
+
+    Ok(())
+}
+
+/// Returns the files in the object version keyed and sorted by logical path
+fn sorted_files(object: &ObjectVersion) -> BTreeMap<&str, (&Rc<LogicalPath>, &FileDetails)> {
+    object
+        .state
+        .iter()
+        .map(|(path, details)| (path.as_ref().as_ref(), (path, details)))
+        .collect()
+}
+
+fn read_manifest(args: &ManifestImportArgs) -> Result<String> {
+    let mut contents = String::new();
+
+    if args.manifest.as_os_str() == "-" {
+        io::stdin().read_to_string(&mut contents)?;
+    } else {
+        File::open(&args.manifest)?.read_to_string(&mut contents)?;
+    }
+
+    Ok(contents)
+}
+
+/// Formats a line in the format of `sha512sum`. Paths that contain a backslash or newline are
+/// escaped, and the line is prefixed with a backslash.
+fn checksum_line(digest: &str, path: &str) -> String {
+    if path.contains(['\\', '\n']) {
+        let escaped = path.replace('\\', "\\\\").replace('\n', "\\n");
+        format!("\\{}  {}", digest, escaped)
+    } else {
+        format!("{}  {}", digest, path)
+    }
+}
+
+fn parse_checksum(contents: &str, algorithm: DigestAlgorithm) -> Result<Vec<ManifestEntry>> {
+    let mut entries = Vec::new();
+
+    for (i, line) in contents.lines().enumerate() {
+        if line.is_empty() {
+            continue;
+        }
+
+        let (escaped, line) = match line.strip_prefix('\\') {
+            Some(line) => (true, line),
+            None => (false, line),
+        };
+
+        // The path is separated from the digest by a space and either a space or, for files that
+        // were read in binary mode, an asterisk
+        let (digest, path) = line
+            .split_once(' ')
+            .and_then(|(digest, rest)| {
+                rest.strip_prefix(' ')
+                    .or_else(|| rest.strip_prefix('*'))
+                    .map(|path| (digest, path))
+            })
+            .ok_or_else(|| {
+                RocflError::InvalidValue(format!(
+                    "Invalid checksum manifest line {}: {}",
+                    i + 1,
+                    line
+                ))
+            })?;
+
+        entries.push(ManifestEntry {
+            digest: digest.to_string(),
+            algorithm,
+            path: if escaped {
+                unescape_checksum_path(path)
+            } else {
+                path.to_string()
+            },
+        });
+    }
+
+    Ok(entries)
+}
+
+fn unescape_checksum_path(path: &str) -> String {
+    let mut unescaped = String::with_capacity(path.len());
+    let mut chars = path.chars();
+
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('n') => unescaped.push('\n'),
+                Some(next) => unescaped.push(next),
+                None => unescaped.push(c),
+            }
+        } else {
+            unescaped.push(c);
+        }
+    }
+
+    unescaped
+}
+
+/// Quotes a CSV field if it contains a comma, quote, or line break
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn parse_csv(contents: &str) -> Result<Vec<ManifestEntry>> {
+    let mut entries = Vec::new();
+
+    for (i, record) in csv_records(contents)?.into_iter().enumerate() {
+        if i == 0 && record == CSV_HEADER {
+            continue;
+        }
+
+        if record.len() != CSV_HEADER.len() {
+            return Err(RocflError::InvalidValue(format!(
+                "Invalid CSV manifest record {}: expected {} fields but found {}",
+                i + 1,
+                CSV_HEADER.len(),
+                record.len()
+            )));
+        }
+
+        let mut fields = record.into_iter();
+        let digest = fields.next().unwrap();
+        let algorithm = DigestAlgorithm::from_str(&fields.next().unwrap()).map_err(|_| {
+            RocflError::InvalidValue(format!(
+                "Invalid CSV manifest record {}: unknown digest algorithm",
+                i + 1
+            ))
+        })?;
+
+        entries.push(ManifestEntry {
+            digest,
+            algorithm,
+            path: fields.next().unwrap(),
+        });
+    }
+
+    Ok(entries)
+}
+
+/// Splits CSV contents into records of unquoted fields. Blank lines are skipped.
+fn csv_records(contents: &str) -> Result<Vec<Vec<String>>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = contents.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if quoted {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => quoted = false,
+                _ => field.push(c),
+            }
+        } else {
+            match c {
+                '"' if field.is_empty() => quoted = true,
+                ',' => record.push(std::mem::take(&mut field)),
+                '\r' if chars.peek() == Some(&'\n') => (),
+                '\n' => {
+                    if !record.is_empty() || !field.is_empty() {
+                        record.push(std::mem::take(&mut field));
+                        records.push(std::mem::take(&mut record));
+                    }
+                }
+                _ => field.push(c),
+            }
+        }
+    }
+
+    if quoted {
+        return Err(RocflError::InvalidValue(
+            "Invalid CSV manifest: unterminated quoted field".to_string(),
+        ));
+    }
+
+    if !record.is_empty() || !field.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+
+    Ok(records)
+}
@@ -1,40 +1,199 @@
 use std::borrow::Cow;
-use std::fmt::Display;
+use std::collections::BTreeMap;
+use std::fmt::{Debug, Display};
 use std::io::{self, Read, Write};
 use std::path::Path;
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicI32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use std::{fs, process};
 
 use ansi_term::{ANSIGenericString, Style};
 use enum_dispatch::enum_dispatch;
-use log::{error, info};
+use log::{error, info, warn};
+use once_cell::sync::OnceCell;
 #[cfg(feature = "s3")]
 use rusoto_core::Region;
 
 use crate::cmd::opts::*;
 use crate::config::{self, Config};
+#[cfg(feature = "s3")]
+use crate::ocfl::CloudStorage;
 use crate::ocfl::{
-    LayoutExtensionName, OcflRepo, Result, RocflError, SpecVersion as OcflSpecVersion,
-    StorageLayout,
+    ChangePlan, CommandCallout, EncryptionKey, LayoutExtensionName, OcflRepo, OrphanKind,
+    RepoInitOptions, Result, RocflError, S3RequestMetrics, SigningKey,
+    SpecVersion as OcflSpecVersion, StorageLayout,
 };
 
 mod cmds;
+mod completions;
 mod diff;
 mod list;
+mod logging;
+mod manifest;
 pub mod opts;
 mod style;
 mod table;
+mod tree;
 mod validate;
+mod watch;
+
+pub use self::logging::init_logging;
 
 const DATE_FORMAT: &str = "%Y-%m-%d %H:%M";
 
+static EXIT_STATUS: AtomicI32 = AtomicI32::new(0);
+
+/// Lock files older than this are considered orphaned when it cannot be determined whether the
+/// process that acquired them is still running
+const ORPHAN_AGE: Duration = Duration::from_secs(60 * 60);
+
 /// Executes a `rocfl` command
 pub fn exec_command(args: &RocflArgs, config: Config) -> Result<()> {
+    // config cmd is handled before the config is validated so that it can fix invalid config
+    if let Command::Config(command) = &args.command {
+        return exec_config(command);
+    }
+
+    // completion scripts are generated without a repository so that they can be installed first
+    if let Command::Completions(CompletionsCmd {
+        shell: Some(shell), ..
+    }) = &args.command
+    {
+        return completions::write_script(*shell, &mut io::stdout());
+    }
+
+    // inventories are inspected without a repository so that any inventory file may be checked
+    if let Command::InspectInventory(command) = &args.command {
+        return validate::inspect_inventory(
+            command,
+            GlobalArgs::new(args.quiet, args.verbose, args.no_styles, args.dry_run),
+        );
+    }
+
+    if let Command::Validate(command) = &args.command {
+        if command.inventory.is_some() {
+            return validate::validate_inventory_file(
+                command,
+                GlobalArgs::new(args.quiet, args.verbose, args.no_styles, args.dry_run),
+            );
+        }
+    }
+
+    // layouts are mapped without a repository so that IDs can be checked before migrating
+    if let Command::Layout(LayoutCmd {
+        command: LayoutCommand::Map(command),
+    }) = &args.command
+    {
+        return exec_layout_map(command, args.no_styles);
+    }
+
+    let names = repo_names(args);
+
+    if names.len() > 1 {
+        return exec_multi_repo(args, &names);
+    }
+
+    exec_repo_command(args, config, &Interrupt::default())
+}
+
+/// Returns the status the process should exit with. Commands that find problems, such as invalid
+/// objects, raise the status rather than exiting immediately, so that the remaining repositories
+/// are still processed when a command is run against more than one.
+pub fn exit_status() -> i32 {
+    EXIT_STATUS.load(Ordering::Acquire)
+}
+
+/// Raises the status the process exits with to `status`, if it is greater than the current status
+fn raise_exit_status(status: i32) {
+    EXIT_STATUS.fetch_max(status, Ordering::AcqRel);
+}
+
+/// The names of the repositories to run the command against, from the comma separated '--name'
+fn repo_names(args: &RocflArgs) -> Vec<&str> {
+    args.name
+        .as_deref()
+        .map(|names| {
+            names
+                .split(',')
+                .map(str::trim)
+                .filter(|name| !name.is_empty())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Runs the command against each of the named repositories in turn. Each repository's output is
+/// preceded by a header that contains its name, and errors are prefixed with its name. A failure
+/// in one repository does not prevent the command from running against the others.
+fn exec_multi_repo(args: &RocflArgs, names: &[&str]) -> Result<()> {
+    if args.root.is_some() || args.staging_root.is_some() || args.bucket.is_some() {
+        return Err(RocflError::InvalidValue(
+            "--root, --staging-root, and --bucket may not be used with multiple repository names"
+                .to_string(),
+        ));
+    }
+
+    let interrupt = Interrupt::default();
+    let mut failed = Vec::new();
+
+    for (i, name) in names.iter().enumerate() {
+        if interrupt.is_terminated() {
+            break;
+        }
+
+        if i > 0 {
+            println("");
+        }
+        println(paint(
+            args.no_styles,
+            *style::BOLD,
+            format!("==> {} <==", name),
+        ));
+
+        let result = config::load_config(&Some(name.to_string())).and_then(|config| {
+            if config.root.is_none() && config.bucket.is_none() {
+                return Err(RocflError::InvalidValue(format!(
+                    "Repository {} does not have a root or bucket configured",
+                    name
+                )));
+            }
+            exec_repo_command(args, config, &interrupt)
+        });
+
+        if let Err(e) = result {
+            match e {
+                RocflError::CopyMoveError(errors) => errors
+                    .0
+                    .iter()
+                    .for_each(|error| error!("[{}] {}", name, error)),
+                _ => error!("[{}] {:#}", name, e),
+            }
+            failed.push(*name);
+        }
+    }
+
+    if failed.is_empty() {
+        Ok(())
+    } else {
+        Err(RocflError::General(format!(
+            "Command failed in {} of {} repositories: {}",
+            failed.len(),
+            names.len(),
+            failed.join(", ")
+        )))
+    }
+}
+
+/// Executes the command against the repository described by the config
+fn exec_repo_command(args: &RocflArgs, config: Config, interrupt: &Interrupt) -> Result<()> {
     let config = resolve_config(args, config);
     let config = default_values(config)?;
 
     info!("Resolved configuration: {:?}", config);
+    for (property, source) in &config.sources {
+        info!("Configured {} from {}", property, source);
+    }
 
     config.validate()?;
 
@@ -43,33 +202,182 @@ pub fn exec_command(args: &RocflArgs, config: Config) -> Result<()> {
             // init cmd needs to be handled differently because the repo does not exist yet
             init_repo(command, args, &config)
         }
-        Command::Config(_command) => edit_config()
-            .map_err(|e| RocflError::General(format!("Failed to edit config file: {}", e))),
+        Command::Config(_command) => unreachable!("config cmd is handled before repo creation"),
         _ => {
             let repo = Arc::new(create_repo(&config)?);
-            let terminate = Arc::new(AtomicBool::new(false));
 
-            let repo_ref = repo.clone();
-            let terminate_ref = terminate.clone();
+            if let Some(timeout) = config.timeout {
+                repo.set_timeout(Duration::from_secs(timeout));
+            }
+
+            repo.set_dry_run(args.dry_run);
+
+            if args.auto_clean || modifies_repo(&args.command) {
+                handle_orphans(&repo, args.auto_clean && !args.dry_run);
+            }
+
+            interrupt.watch(&repo)?;
+
+            let result = args.command.exec(
+                &repo,
+                GlobalArgs::new(args.quiet, args.verbose, args.no_styles, args.dry_run),
+                &config,
+                &interrupt.terminate,
+            );
+
+            if result.is_ok() {
+                print_change_plans(&repo.take_change_plans());
+            }
+
+            if let Some(metrics) = repo.s3_request_metrics() {
+                print_s3_request_metrics(&metrics);
+            }
+
+            // A timed out command may fail with any error, or stop early without one
+            match config.timeout {
+                Some(timeout) if repo.is_timed_out() => {
+                    Err(RocflError::TimedOut(Duration::from_secs(timeout)))
+                }
+                _ => result,
+            }
+        }
+    }
+}
+
+/// Returns `true` if the command may modify the repository. Orphaned locks and staging
+/// directories are only looked for before these commands, unless `--auto-clean` is specified,
+/// so that commands that only read the repository do not need to scan staging.
+fn modifies_repo(command: &Command) -> bool {
+    match command {
+        Command::New(_)
+        | Command::Copy(_)
+        | Command::Move(_)
+        | Command::Remove(_)
+        | Command::Apply(_)
+        | Command::Stage(_)
+        | Command::Reset(_)
+        | Command::Commit(_)
+        | Command::Watch(_)
+        | Command::Rebase(_)
+        | Command::Purge(_)
+        | Command::Squash(_)
+        | Command::Rename(_)
+        | Command::Gc(_)
+        | Command::Upgrade(_)
+        | Command::Adopt(_)
+        | Command::Freeze(_)
+        | Command::Unfreeze(_) => true,
+        Command::Zip(ZipCmd { command }) => matches!(command, ZipCommand::Import(_)),
+        Command::Alias(AliasCmd { command }) => {
+            matches!(command, AliasCommand::Add(_) | AliasCommand::Remove(_))
+        }
+        Command::Meta(MetaCmd { command, .. }) => matches!(command, MetaCommand::Set(_)),
+        Command::Layout(LayoutCmd { command }) => matches!(
+            command,
+            LayoutCommand::SetDescription(_) | LayoutCommand::Registry(_)
+        ),
+        _ => false,
+    }
+}
+
+/// Reports the lock files and staging directories that were left behind by processes that
+/// stopped unexpectedly, and removes them if `clean` is set. Failures are logged rather than
+/// returned so that they do not prevent the command from running.
+fn handle_orphans(repo: &OcflRepo, clean: bool) {
+    let orphans = match repo.find_orphans(ORPHAN_AGE) {
+        Ok(orphans) => orphans,
+        Err(e) => {
+            warn!(
+                "Failed to check for orphaned locks and staging directories: {:#}",
+                e
+            );
+            return;
+        }
+    };
+
+    if orphans.is_empty() {
+        return;
+    }
+
+    for orphan in &orphans {
+        let kind = match orphan.kind {
+            OrphanKind::StaleLock => "lock file",
+            OrphanKind::StagingDir => "staging directory",
+        };
+        match &orphan.object_id {
+            Some(object_id) => warn!(
+                "Found orphaned {} for object {} at {}: {}",
+                kind,
+                object_id,
+                orphan.path.to_string_lossy(),
+                orphan.reason
+            ),
+            None => warn!(
+                "Found orphaned {} at {}: {}",
+                kind,
+                orphan.path.to_string_lossy(),
+                orphan.reason
+            ),
+        }
+    }
+
+    if !clean {
+        warn!("Run with --auto-clean to remove orphaned locks and staging directories");
+        return;
+    }
+
+    match repo.clean_orphans(&orphans) {
+        Ok(()) => warn!(
+            "Removed {} orphaned locks and staging directories",
+            orphans.len()
+        ),
+        Err(e) => error!(
+            "Failed to remove orphaned locks and staging directories: {:#}",
+            e
+        ),
+    }
+}
+
+/// Stops the running command when the user presses ctrl-c, and force quits if it is pressed
+/// again. The handler is installed when the first repository is watched.
+#[derive(Default)]
+struct Interrupt {
+    terminate: Arc<AtomicBool>,
+    /// The repository the command is currently running against
+    repo: Arc<Mutex<Option<Arc<OcflRepo>>>>,
+    installed: OnceCell<()>,
+}
+
+impl Interrupt {
+    /// Closes the repository when the user presses ctrl-c
+    fn watch(&self, repo: &Arc<OcflRepo>) -> Result<()> {
+        *self.repo.lock().unwrap() = Some(repo.clone());
+
+        self.installed.get_or_try_init(|| -> Result<()> {
+            let terminate = self.terminate.clone();
+            let repo = self.repo.clone();
 
             ctrlc::set_handler(move || {
-                if terminate_ref.load(Ordering::Acquire) {
+                if terminate.load(Ordering::Acquire) {
                     error!("Force quitting. If a write operation was in progress, it is possible the resource was left in an inconsistent state.");
                     process::exit(1);
                 } else {
                     println("Stopping rocfl. If in the middle of a write operation, please wait for it to gracefully complete.");
-                    terminate_ref.store(true, Ordering::Release);
-                    repo_ref.close();
+                    terminate.store(true, Ordering::Release);
+                    if let Some(repo) = repo.lock().unwrap().as_ref() {
+                        repo.close();
+                    }
                 }
             })?;
 
-            args.command.exec(
-                &repo,
-                GlobalArgs::new(args.quiet, args.verbose, args.no_styles),
-                &config,
-                &terminate,
-            )
-        }
+            Ok(())
+        })?;
+
+        Ok(())
+    }
+
+    fn is_terminated(&self) -> bool {
+        self.terminate.load(Ordering::Acquire)
     }
 }
 
@@ -88,20 +396,64 @@ trait Cmd {
 
 struct GlobalArgs {
     quiet: bool,
-    _verbose: bool,
+    verbose: bool,
     no_styles: bool,
+    dry_run: bool,
 }
 
 impl GlobalArgs {
-    fn new(quiet: bool, verbose: bool, no_styles: bool) -> Self {
+    fn new(quiet: bool, verbose: bool, no_styles: bool, dry_run: bool) -> Self {
         Self {
             quiet,
-            _verbose: verbose,
+            verbose,
             no_styles,
+            dry_run,
         }
     }
 }
 
+/// Prints the changes that were planned by a command that was run in dry-run mode
+fn print_change_plans(plans: &[ChangePlan]) {
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+
+    for plan in plans {
+        let _ = match &plan.object_id {
+            Some(object_id) => writeln!(out, "Dry run: {} {}", plan.operation, object_id),
+            None => writeln!(out, "Dry run: {}", plan.operation),
+        };
+
+        if plan.changes.is_empty() {
+            let _ = writeln!(out, "  no changes");
+        }
+
+        for change in &plan.changes {
+            let _ = writeln!(out, "  {}", change);
+        }
+    }
+}
+
+/// Prints the number, failures, latency, and size of the S3 requests a command sent to stderr
+fn print_s3_request_metrics(metrics: &S3RequestMetrics) {
+    let stderr = io::stderr();
+    let mut err = stderr.lock();
+
+    let _ = writeln!(err, "S3 requests:");
+
+    for (operation, metrics) in &metrics.operations {
+        let _ = writeln!(
+            err,
+            "  {}: requests={} failures={} latency={}ms sent={}B received={}B",
+            operation,
+            metrics.requests,
+            metrics.failures,
+            metrics.latency.as_millis(),
+            metrics.bytes_sent,
+            metrics.bytes_received
+        );
+    }
+}
+
 fn println(value: impl Display) {
     let _ = writeln!(io::stdout(), "{}", value);
 }
@@ -123,28 +475,46 @@ where
 }
 
 pub fn init_repo(cmd: &InitCmd, args: &RocflArgs, config: &Config) -> Result<()> {
+    if cmd.from_existing {
+        return init_repo_from_existing(cmd, args, config);
+    }
+
     let spec_version = map_spec_version(cmd.spec_version);
 
-    if is_s3(config) {
+    if args.dry_run {
+        let layout = create_layout(cmd.layout, cmd.config_file.as_deref())?;
+        print_change_plans(&[OcflRepo::plan_init(
+            spec_version,
+            layout.as_ref(),
+            init_options(cmd),
+        )]);
+        return Ok(());
+    }
+
+    let repo = if is_s3(config) {
         #[cfg(not(feature = "s3"))]
         return Err(RocflError::General(
             "This binary was not compiled with S3 support.".to_string(),
         ));
 
         #[cfg(feature = "s3")]
-        let _ = init_s3_repo(
+        init_s3_repo(
             config,
             spec_version,
             create_layout(cmd.layout, cmd.config_file.as_deref())?,
-        )?;
+            init_options(cmd),
+        )?
     } else {
-        let _ = OcflRepo::init_fs_repo(
+        OcflRepo::init_fs_repo_with_options(
             config.root.as_ref().unwrap(),
             config.staging_root.as_ref().map(Path::new),
             spec_version,
             create_layout(cmd.layout, cmd.config_file.as_deref())?,
-        )?;
-    }
+            init_options(cmd),
+        )?
+    };
+
+    set_default_digest_algorithm(&repo, cmd)?;
 
     if !args.quiet {
         println(format!(
@@ -156,6 +526,61 @@ pub fn init_repo(cmd: &InitCmd, args: &RocflArgs, config: &Config) -> Result<()>
     Ok(())
 }
 
+fn init_repo_from_existing(cmd: &InitCmd, args: &RocflArgs, config: &Config) -> Result<()> {
+    let repo = if is_s3(config) {
+        #[cfg(not(feature = "s3"))]
+        return Err(RocflError::General(
+            "This binary was not compiled with S3 support.".to_string(),
+        ));
+
+        #[cfg(feature = "s3")]
+        match cloud_storage(config)? {
+            #[cfg(any(feature = "azure", feature = "gcs"))]
+            Some(storage) => OcflRepo::init_cloud_repo_from_existing(
+                &storage,
+                config.bucket.as_ref().unwrap(),
+                config.root.as_deref(),
+                config.staging_root.as_ref().unwrap(),
+            )?,
+            _ => OcflRepo::init_s3_repo_from_existing(
+                resolve_region(config)?,
+                config.bucket.as_ref().unwrap(),
+                config.root.as_deref(),
+                config.profile.as_deref(),
+                config.staging_root.as_ref().unwrap(),
+                &config.s3_client_options(),
+            )?,
+        }
+    } else {
+        OcflRepo::init_fs_repo_from_existing(
+            config.root.as_ref().unwrap(),
+            config.staging_root.as_ref().map(Path::new),
+        )?
+    };
+
+    set_default_digest_algorithm(&repo, cmd)?;
+
+    if !args.quiet {
+        let info = repo.describe_repo()?;
+        println(format!(
+            "Initialized existing OCFL {} repository with layout {}",
+            info.spec_version,
+            info.layout.as_deref().unwrap_or("None")
+        ));
+    }
+
+    Ok(())
+}
+
+/// Persists the digest algorithm specified on `init` as the repository's default
+fn set_default_digest_algorithm(repo: &OcflRepo, cmd: &InitCmd) -> Result<()> {
+    if let Some(algorithm) = cmd.digest_algorithm.map(cmds::algorithm) {
+        cmds::warn_digest_algorithm(algorithm);
+        repo.set_default_digest_algorithm(algorithm)?;
+    }
+    Ok(())
+}
+
 fn create_repo(config: &Config) -> Result<OcflRepo> {
     if is_s3(config) {
         #[cfg(not(feature = "s3"))]
@@ -166,13 +591,58 @@ fn create_repo(config: &Config) -> Result<OcflRepo> {
         #[cfg(feature = "s3")]
         create_s3_repo(config)
     } else {
-        OcflRepo::fs_repo(
+        let repo = OcflRepo::fs_repo(
             config.root.as_ref().unwrap(),
             config.staging_root.as_ref().map(Path::new),
+        )?
+        .with_hard_links(config.hard_links.unwrap_or(false))
+        .with_audit_trail(config.audit_trail.unwrap_or(false))
+        .with_validation_history(config.validation_history.unwrap_or(false))
+        .with_read_only(config.read_only.unwrap_or(false))
+        .with_commit_quotas(config.commit_quotas());
+
+        with_commit_callout(
+            with_encryption_key(
+                with_signing_key(with_threads(repo, config), config)?,
+                config,
+            )?,
+            config,
         )
     }
 }
 
+/// Applies the configured commit callout command to the repository, if one was configured
+fn with_commit_callout(repo: OcflRepo, config: &Config) -> Result<OcflRepo> {
+    match &config.commit_callout {
+        Some(command) => Ok(repo.with_commit_callout(CommandCallout::new(command)?)),
+        None => Ok(repo),
+    }
+}
+
+/// Applies the configured encryption key to the repository, if one was configured
+fn with_encryption_key(repo: OcflRepo, config: &Config) -> Result<OcflRepo> {
+    match &config.encryption_key {
+        Some(path) => Ok(repo.with_encryption_key(EncryptionKey::from_file(path)?)),
+        None => Ok(repo),
+    }
+}
+
+/// Applies the configured signing key to the repository, if one was configured
+fn with_signing_key(repo: OcflRepo, config: &Config) -> Result<OcflRepo> {
+    match &config.signing_key {
+        Some(path) => Ok(repo.with_signing_key(SigningKey::from_pem_file(path)?)),
+        None => Ok(repo),
+    }
+}
+
+/// Applies the configured number of threads to the repository, if one was configured
+fn with_threads(repo: OcflRepo, config: &Config) -> OcflRepo {
+    match config.threads {
+        Some(threads) => repo.with_threads(threads),
+        None => repo,
+    }
+}
+
 fn create_layout(layout_name: Layout, config_file: Option<&Path>) -> Result<Option<StorageLayout>> {
     let config_bytes = match read_layout_config(config_file) {
         Ok(bytes) => bytes,
@@ -224,14 +694,41 @@ fn read_layout_config(config_file: Option<&Path>) -> Result<Option<Vec<u8>>> {
 
 #[cfg(feature = "s3")]
 fn create_s3_repo(config: &Config) -> Result<OcflRepo> {
-    let region = resolve_region(config)?;
-
-    OcflRepo::s3_repo(
-        region,
-        config.bucket.as_ref().unwrap(),
-        config.root.as_deref(),
-        config.staging_root.as_ref().unwrap(),
-        config.profile.as_deref(),
+    let repo = match cloud_storage(config)? {
+        #[cfg(any(feature = "azure", feature = "gcs"))]
+        Some(storage) => OcflRepo::cloud_repo(
+            &storage,
+            config.bucket.as_ref().unwrap(),
+            config.root.as_deref(),
+            config.staging_root.as_ref().unwrap(),
+        )?,
+        _ => OcflRepo::s3_repo(
+            resolve_region(config)?,
+            config.bucket.as_ref().unwrap(),
+            config.root.as_deref(),
+            config.staging_root.as_ref().unwrap(),
+            config.profile.as_deref(),
+            &config.s3_client_options(),
+        )?,
+    };
+
+    let repo = repo
+        .with_s3_write_options(
+            config.content_write_options()?,
+            config.inventory_write_options()?,
+        )
+        .with_audit_trail(config.audit_trail.unwrap_or(false))
+        .with_validation_history(config.validation_history.unwrap_or(false))
+        .with_read_only(config.read_only.unwrap_or(false))
+        .with_commit_quotas(config.commit_quotas())
+        .with_bucket_staging(config.bucket_staging.unwrap_or(false));
+
+    with_commit_callout(
+        with_encryption_key(
+            with_signing_key(with_threads(repo, config), config)?,
+            config,
+        )?,
+        config,
     )
 }
 
@@ -240,18 +737,53 @@ fn init_s3_repo(
     config: &Config,
     spec_version: OcflSpecVersion,
     layout: Option<StorageLayout>,
+    options: RepoInitOptions,
 ) -> Result<OcflRepo> {
-    let region = resolve_region(config)?;
-
-    OcflRepo::init_s3_repo(
-        region,
-        config.bucket.as_ref().unwrap(),
-        config.root.as_deref(),
-        config.profile.as_deref(),
-        config.staging_root.as_ref().unwrap(),
-        spec_version,
-        layout,
-    )
+    match cloud_storage(config)? {
+        #[cfg(any(feature = "azure", feature = "gcs"))]
+        Some(storage) => OcflRepo::init_cloud_repo(
+            &storage,
+            config.bucket.as_ref().unwrap(),
+            config.root.as_deref(),
+            config.staging_root.as_ref().unwrap(),
+            spec_version,
+            layout,
+            options,
+        ),
+        _ => OcflRepo::init_s3_repo_with_options(
+            resolve_region(config)?,
+            config.bucket.as_ref().unwrap(),
+            config.root.as_deref(),
+            config.profile.as_deref(),
+            config.staging_root.as_ref().unwrap(),
+            spec_version,
+            layout,
+            &config.s3_client_options(),
+            options,
+        ),
+    }
+}
+
+/// Returns the cloud storage service, other than S3, that the repository is in. An error is
+/// returned if the binary was not compiled with support for the service.
+#[cfg(feature = "s3")]
+fn cloud_storage(config: &Config) -> Result<Option<CloudStorage>> {
+    let storage = config.cloud_storage();
+
+    let supported = match &storage {
+        Some(CloudStorage::Azure { .. }) => cfg!(feature = "azure"),
+        Some(CloudStorage::Gcs { .. }) => cfg!(feature = "gcs"),
+        None => true,
+    };
+
+    if supported {
+        Ok(storage)
+    } else {
+        Err(RocflError::General(format!(
+            "This binary was not compiled with {} support.",
+            config.provider()
+        )))
+    }
 }
 
 #[cfg(feature = "s3")]
@@ -266,46 +798,171 @@ fn resolve_region(config: &Config) -> Result<Region> {
 }
 
 fn resolve_config(args: &RocflArgs, mut config: Config) -> Config {
-    if args.root.is_some() {
-        config.root = args.root.clone();
-    }
-    if args.staging_root.is_some() {
-        config.staging_root = args.staging_root.clone();
-    }
-    if args.bucket.is_some() {
-        config.bucket = args.bucket.clone();
-    }
-    if args.region.is_some() {
-        config.region = args.region.clone();
-    }
-    if args.endpoint.is_some() {
-        config.endpoint = args.endpoint.clone();
-    }
-    if args.profile.is_some() {
-        config.profile = args.profile.clone()
-    }
+    let sources = &mut config.sources;
+
+    override_field(&mut config.root, sources, "root", &args.root);
+    override_field(
+        &mut config.staging_root,
+        sources,
+        "staging_root",
+        &args.staging_root,
+    );
+    override_field(&mut config.bucket, sources, "bucket", &args.bucket);
+    override_field(&mut config.region, sources, "region", &args.region);
+    override_field(&mut config.endpoint, sources, "endpoint", &args.endpoint);
+    override_field(&mut config.profile, sources, "profile", &args.profile);
+    override_field(&mut config.timeout, sources, "timeout", &args.timeout);
+    override_field(&mut config.threads, sources, "threads", &args.threads);
+    override_field(
+        &mut config.read_only,
+        sources,
+        "read_only",
+        &args.read_only.then_some(true),
+    );
 
-    if let Command::Commit(commit) = &args.command {
-        if commit.user_name.is_some() {
-            config.author_name = commit.user_name.clone();
+    match &args.command {
+        Command::Commit(commit) => {
+            override_field(
+                &mut config.author_name,
+                sources,
+                "author_name",
+                &commit.user_name,
+            );
+            override_field(
+                &mut config.author_address,
+                sources,
+                "author_address",
+                &commit.user_address,
+            );
+            override_field(
+                &mut config.pretty_print,
+                sources,
+                "pretty_print",
+                &negatable(commit.pretty_print, commit.no_pretty_print),
+            );
+            override_field(
+                &mut config.hard_links,
+                sources,
+                "hard_links",
+                &negatable(commit.hard_links, commit.no_hard_links),
+            );
+            override_field(
+                &mut config.content_storage_class,
+                sources,
+                "content_storage_class",
+                &commit.storage_class,
+            );
+            override_field(
+                &mut config.content_tags,
+                sources,
+                "content_tags",
+                &commit.tags,
+            );
+            override_field(
+                &mut config.inventory_storage_class,
+                sources,
+                "inventory_storage_class",
+                &commit.inventory_storage_class,
+            );
+            override_field(
+                &mut config.inventory_tags,
+                sources,
+                "inventory_tags",
+                &commit.inventory_tags,
+            );
         }
-        if commit.user_address.is_some() {
-            config.author_address = commit.user_address.clone();
+        Command::Meta(meta) => {
+            override_field(
+                &mut config.metadata_path,
+                sources,
+                "metadata_path",
+                &meta.path,
+            );
         }
-    }
-
-    if let Command::Upgrade(commit) = &args.command {
-        if commit.user_name.is_some() {
-            config.author_name = commit.user_name.clone();
+        Command::Upgrade(upgrade) => {
+            override_field(
+                &mut config.author_name,
+                sources,
+                "author_name",
+                &upgrade.user_name,
+            );
+            override_field(
+                &mut config.author_address,
+                sources,
+                "author_address",
+                &upgrade.user_address,
+            );
+            override_field(
+                &mut config.pretty_print,
+                sources,
+                "pretty_print",
+                &negatable(upgrade.pretty_print, upgrade.no_pretty_print),
+            );
         }
-        if commit.user_address.is_some() {
-            config.author_address = commit.user_address.clone();
+        Command::New(new) => {
+            override_field(
+                &mut config.digest_algorithm,
+                sources,
+                "digest_algorithm",
+                &new.digest_algorithm.map(cmds::algorithm),
+            );
+            override_field(
+                &mut config.content_directory,
+                sources,
+                "content_directory",
+                &new.content_directory,
+            );
+            override_field(
+                &mut config.zero_padding,
+                sources,
+                "zero_padding",
+                &new.zero_padding,
+            );
         }
+        _ => (),
     }
 
     config
 }
 
+/// Maps a flag and its negating `--no-` flag to the value they specify, if either was specified
+fn negatable(flag: bool, no_flag: bool) -> Option<bool> {
+    if flag {
+        Some(true)
+    } else if no_flag {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+/// Replaces the configured value with the value specified on the command line, if one was
+/// specified, and records that the value was sourced from the command line.
+fn override_field<T: Clone + PartialEq + Debug>(
+    field: &mut Option<T>,
+    sources: &mut BTreeMap<&'static str, String>,
+    property: &'static str,
+    value: &Option<T>,
+) {
+    if let Some(value) = value {
+        if let Some(original) = field.as_ref().filter(|original| *original != value) {
+            info!(
+                "Command line value {:?} for {} overrides {:?} from {}",
+                value,
+                property,
+                original,
+                sources
+                    .get(property)
+                    .map(|source| source.as_str())
+                    .unwrap_or("config")
+            );
+        }
+
+        *field = Some(value.clone());
+        sources.insert(property, "command line".to_string());
+    }
+}
+
 fn default_values(mut config: Config) -> Result<Config> {
     if is_s3(&config) {
         if config.staging_root.is_none() {
@@ -322,25 +979,107 @@ fn is_s3(config: &Config) -> bool {
     config.bucket.is_some()
 }
 
-fn edit_config() -> Result<()> {
-    match config::config_path() {
-        Some(config_path) => {
-            if !config_path.exists() {
-                fs::create_dir_all(config_path.parent().unwrap())?;
-                let mut file = fs::File::create(&config_path)?;
-                write!(
-                    file,
-                    "{}",
-                    include_str!("../../resources/main/files/config.toml")
-                )?;
-            }
+fn exec_config(command: &ConfigCmd) -> Result<()> {
+    let config_path = config::config_path()
+        .ok_or_else(|| RocflError::General("Failed to find rocfl config".to_string()))?;
 
-            edit::edit_file(&config_path)?;
+    match &command.command {
+        None => edit_config(&config_path)
+            .map_err(|e| RocflError::General(format!("Failed to edit config file: {}", e))),
+        Some(ConfigCommand::List(args)) => {
+            let mut out = io::stdout();
+            for (key, value) in config::list_config_values(&config_path, args.section.as_deref())? {
+                let _ = writeln!(out, "{}={}", key, value);
+            }
             Ok(())
         }
-        None => Err(RocflError::General(
-            "Failed to find rocfl config".to_string(),
-        )),
+        Some(ConfigCommand::Get(args)) => {
+            match config::get_config_value(&config_path, &args.key)? {
+                Some(value) => {
+                    let _ = writeln!(io::stdout(), "{}", value);
+                    Ok(())
+                }
+                None => Err(RocflError::NotFound(format!(
+                    "Config property {}",
+                    args.key
+                ))),
+            }
+        }
+        Some(ConfigCommand::Set(args)) => {
+            config::set_config_value(&config_path, &args.key, &args.value)
+        }
+        Some(ConfigCommand::Unset(args)) => config::unset_config_value(&config_path, &args.key),
+    }
+}
+
+fn exec_layout_map(command: &LayoutMapArgs, no_styles: bool) -> Result<()> {
+    let delimiter_config = |name: LayoutExtensionName| {
+        command
+            .delimiter
+            .as_ref()
+            .map(|delimiter| {
+                serde_json::to_vec(&serde_json::json!({
+                    "extensionName": name.to_string(),
+                    "delimiter": delimiter,
+                }))
+            })
+            .transpose()
+    };
+
+    let layouts = [
+        (LayoutExtensionName::FlatDirectLayout, None),
+        (LayoutExtensionName::HashedNTupleObjectIdLayout, None),
+        (LayoutExtensionName::HashedNTupleLayout, None),
+        (
+            LayoutExtensionName::FlatOmitPrefixLayout,
+            delimiter_config(LayoutExtensionName::FlatOmitPrefixLayout)?,
+        ),
+        (
+            LayoutExtensionName::NTupleOmitPrefixLayout,
+            delimiter_config(LayoutExtensionName::NTupleOmitPrefixLayout)?,
+        ),
+    ];
+
+    let mut out = io::stdout();
+
+    for (name, config) in layouts {
+        let requires_delimiter = matches!(
+            name,
+            LayoutExtensionName::FlatOmitPrefixLayout | LayoutExtensionName::NTupleOmitPrefixLayout
+        );
+
+        let mapped = if requires_delimiter && config.is_none() {
+            Err("requires --delimiter".to_string())
+        } else {
+            StorageLayout::new(name, config.as_deref())
+                .and_then(|layout| layout.try_map_object_id(&command.object_id))
+                .map_err(|e| e.to_string())
+        };
+
+        let name = format!("{:<41}", name.to_string());
+        let _ = match mapped {
+            Ok(path) => writeln!(out, "{}{}", paint(no_styles, *style::BOLD, name), path),
+            Err(e) => writeln!(
+                out,
+                "{}{}",
+                paint(no_styles, *style::BOLD, name),
+                paint(no_styles, *style::RED, e)
+            ),
+        };
+    }
+
+    Ok(())
+}
+
+fn edit_config(config_path: &Path) -> Result<()> {
+    config::init_config_file(config_path)?;
+    edit::edit_file(config_path)?;
+    Ok(())
+}
+
+fn init_options(cmd: &InitCmd) -> RepoInitOptions {
+    RepoInitOptions {
+        spec_copy: !cmd.no_spec_copy,
     }
 }
 
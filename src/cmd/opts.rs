@@ -2,13 +2,17 @@ use std::fmt::{self, Display, Formatter};
 use std::num::ParseIntError;
 use std::path::PathBuf;
 use std::str::FromStr;
+use std::time::Duration;
 
-use chrono::{DateTime, Local};
+use chrono::{DateTime, Local, NaiveDate, TimeZone};
 use clap::{ArgAction, Args, Parser, Subcommand, ValueEnum};
 use enum_dispatch::enum_dispatch;
 use strum_macros::{Display as EnumDisplay, EnumString};
 
-use crate::ocfl::{ErrorCode, VersionNum, WarnCode};
+use crate::ocfl::{
+    DigestAlgorithm as OcflDigestAlgorithm, ErrorCode, HexDigest, PathRef, RocflError, VersionNum,
+    VersionRange, WarnCode,
+};
 
 /// A CLI for OCFL repositories
 ///
@@ -33,6 +37,9 @@ pub struct RocflArgs {
     /// Repository names are used to load repository specific configuration in the rocfl config
     /// file. For example, a repository's root could be defined in the config and referenced
     /// here by name so that the root does not need to be specified with every command.
+    ///
+    /// Multiple comma separated names, such as 'repo1,repo2', run the command against each of
+    /// the named repositories in turn.
     #[arg(short, long, value_name = "NAME")]
     pub name: Option<String>,
 
@@ -79,6 +86,68 @@ pub struct RocflArgs {
     #[arg(short = 'S', long)]
     pub no_styles: bool,
 
+    /// Abort the command if it has not completed after the specified number of seconds
+    ///
+    /// In-flight work is stopped the same way it is when the command is interrupted, and the
+    /// command fails with a timeout error. This does not apply to 'init'.
+    #[arg(long, value_name = "SECONDS")]
+    pub timeout: Option<u64>,
+
+    /// Number of threads to distribute work such as fixity checking across
+    ///
+    /// By default, a thread is used for each available core.
+    #[arg(long, value_name = "N")]
+    pub threads: Option<usize>,
+
+    /// Reject every command that would modify the repository or its staged objects
+    ///
+    /// This may also be enabled with the 'read_only' config property.
+    #[arg(long)]
+    pub read_only: bool,
+
+    /// Report the changes a command would make without making them
+    ///
+    /// This is honored by 'commit', 'cp', 'mv', 'rm', 'purge', 'upgrade', 'init', and 'gc'.
+    /// The paths that would be staged or removed, the versions that would be created, and the
+    /// files that would be written or deleted are printed. Other commands that modify the
+    /// repository fail.
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Remove lock files and staging directories that were left behind by crashed processes
+    ///
+    /// Before every command that modifies the repository, rocfl looks for lock files whose process
+    /// is no longer running, or that are more than an hour old when this cannot be determined, and
+    /// staged objects that were interrupted before their inventory was written. They are reported
+    /// as warnings, and are only removed when this is set. When this is set, they are looked for
+    /// before every command. Nothing is removed with --dry-run.
+    #[arg(long)]
+    pub auto_clean: bool,
+
+    /// Also write log records to the specified file
+    ///
+    /// Records are appended to the file. Unless overridden by --log-filter, the file receives
+    /// all info level records, regardless of --quiet or --verbose. This includes a record for
+    /// every change made to the repository, logged to the 'rocfl::audit' target, that describes
+    /// the operation, object ID, version, paths, duration, and outcome of the change.
+    #[arg(long, value_name = "LOG_PATH")]
+    pub log_file: Option<PathBuf>,
+
+    /// Format of log records
+    ///
+    /// 'json' emits one JSON object per line, which is suitable for shipping to log aggregators.
+    /// The details of repository changes are included as an 'event' object.
+    #[arg(long, value_name = "FORMAT", default_value_t = LogFormat::Text)]
+    pub log_format: LogFormat,
+
+    /// Per-module log levels, using env_logger syntax
+    ///
+    /// A comma separated list of directives in the form 'MODULE=LEVEL', or just 'LEVEL'
+    /// to set the default level. For example: 'warn,rocfl::ocfl::store=debug'. The filter
+    /// applies to both the console and the log file.
+    #[arg(long, value_name = "FILTER")]
+    pub log_filter: Option<String>,
+
     /// Subcommand to execute
     #[command(subcommand)]
     pub command: Command,
@@ -91,14 +160,32 @@ pub enum Command {
     Config(ConfigCmd),
     #[command(name = "ls")]
     List(ListCmd),
+    #[command(name = "tree")]
+    Tree(TreeCmd),
     #[command(name = "log")]
     Log(LogCmd),
     #[command(name = "show")]
     Show(ShowCmd),
     #[command(name = "diff")]
     Diff(DiffCmd),
+    #[command(name = "cmp")]
+    Cmp(CmpCmd),
     #[command(name = "cat")]
     Cat(CatCmd),
+    #[command(name = "logs")]
+    Logs(LogsCmd),
+    #[command(name = "extensions")]
+    Extensions(ExtensionsCmd),
+    #[command(name = "manifest")]
+    Manifest(ManifestCmd),
+    #[command(name = "export")]
+    Export(ExportCmd),
+    #[command(name = "zip")]
+    Zip(ZipCmd),
+    #[command(name = "grep")]
+    Grep(GrepCmd),
+    #[command(name = "find")]
+    Find(FindCmd),
     #[command(name = "init")]
     Init(InitCmd),
     #[command(name = "new")]
@@ -109,41 +196,173 @@ pub enum Command {
     Move(MoveCmd),
     #[command(name = "rm")]
     Remove(RemoveCmd),
+    #[command(name = "apply")]
+    Apply(ApplyCmd),
+    #[command(name = "stage")]
+    Stage(StageCmd),
     #[command(name = "reset")]
     Reset(ResetCmd),
     #[command(name = "commit")]
     Commit(CommitCmd),
+    #[command(name = "watch")]
+    Watch(WatchCmd),
+    #[command(name = "rebase")]
+    Rebase(RebaseCmd),
     #[command(name = "status")]
     Status(StatusCmd),
     #[command(name = "purge")]
     Purge(PurgeCmd),
+    #[command(name = "squash")]
+    Squash(SquashCmd),
+    #[command(name = "rename")]
+    Rename(RenameCmd),
+    #[command(name = "gc")]
+    Gc(GcCmd),
     #[command(name = "validate")]
     Validate(ValidateCmd),
+    #[command(name = "doctor")]
+    Doctor(DoctorCmd),
+    #[command(name = "verify-signatures")]
+    VerifySignatures(VerifySignaturesCmd),
+    #[command(name = "inspect-inventory")]
+    InspectInventory(InspectInventoryCmd),
     #[command(name = "info")]
     Info(InfoCmd),
     #[command(name = "upgrade")]
     Upgrade(UpgradeCmd),
+    #[command(name = "adopt")]
+    Adopt(AdoptCmd),
+    #[command(name = "freeze")]
+    Freeze(FreezeCmd),
+    #[command(name = "unfreeze")]
+    Unfreeze(UnfreezeCmd),
+    #[command(name = "alias")]
+    Alias(AliasCmd),
+    #[command(name = "meta")]
+    Meta(MetaCmd),
+    #[command(name = "layout")]
+    Layout(LayoutCmd),
+    #[command(name = "completions")]
+    Completions(CompletionsCmd),
 }
 
 /// Edit rocfl configuration
 ///
+/// Without a subcommand, the config file is opened in your editor. Use the 'list', 'get',
+/// 'set', and 'unset' subcommands to manage the configuration non-interactively. These
+/// subcommands refer to properties as SECTION.PROPERTY, for example: 'global.author_name' or
+/// 'my-repo.root'.
+///
 /// The config file can have one global section, [global], that defines defaults across all
 /// configurations, and any number of named sections, [NAME]. Each section can define any
 /// of the following properties: author_name, author_address, root, staging_root, region,
-/// bucket, and endpoint.
+/// bucket, endpoint, and profile.
+///
+/// Repositories in Azure Blob Storage or Google Cloud Storage set provider (s3, azure, or gcs)
+/// along with the bucket, or container. Azure repositories also set account, and credentials
+/// may be set to an Azure access key or the path to a GCS service account key file.
+///
+/// Sections may also define defaults for new objects and versions: pretty_print (true or
+/// false), digest_algorithm (sha512 or sha256), content_directory, zero_padding, and hard_links
+/// (true or false). These values are used by 'new' and 'commit' unless they are overridden on
+/// the command line.
+///
+/// S3 repositories may also define the storage class and tags to write files with:
+/// content_storage_class, content_tags, inventory_storage_class, and inventory_tags. Tags are
+/// comma separated KEY=VALUE pairs. user_agent is prepended to the user agent of S3 requests,
+/// request_timeout is the number of seconds to wait for an S3 request, and request_metrics (true
+/// or false) prints per-operation S3 request metrics to stderr after each command. bucket_staging
+/// (true or false) stages content in the bucket rather than on local disk, so that 'commit'
+/// copies it into place server-side.
+///
+/// metadata_path sets the logical path of the file that 'meta' stores object metadata in.
+///
+/// audit_trail (true or false) records every commit and upgrade in the object's logs directory.
+///
+/// validation_history (true or false) records the result of each object's most recent validation
+/// in the repository, which 'validate --stale-than' uses to skip recently validated objects.
+///
+/// threads sets the number of threads that work such as fixity checking is distributed across.
+///
+/// read_only (true or false) rejects every command that would modify the repository.
+///
+/// max_object_size, max_version_files, and max_staged_bytes are quotas that 'commit' enforces
+/// unless '--force' is specified. They limit the total size of an object's content in bytes, the
+/// number of files in a version, and the number of new bytes a commit adds.
+///
+/// commit_callout is a command that 'commit' runs on every file it adds, with the path to the
+/// file appended to its arguments. The commit is rejected if the command exits with a non-zero
+/// status.
+///
+/// encryption_key is the path to a file containing a base64 encoded 256-bit key, such as one
+/// created with 'openssl rand -base64 32'. The content of objects that are created while it is set
+/// is encrypted before it is written, and is decrypted when it is read.
 ///
 /// Global configuration is always active, and named configuration is activated by invoking
 /// rocfl with '-n NAME'. When resolving configuration, command line arguments have highest
 /// precedence, followed by named configuration, and finally global configuration.
 #[derive(Args, Debug)]
-pub struct ConfigCmd {}
+pub struct ConfigCmd {
+    /// Config operation to execute
+    #[command(subcommand)]
+    pub command: Option<ConfigCommand>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ConfigCommand {
+    /// List the properties that are set, as SECTION.PROPERTY=VALUE
+    #[command(name = "list")]
+    List(ConfigListArgs),
+    /// Print the value of a property
+    #[command(name = "get")]
+    Get(ConfigGetArgs),
+    /// Set the value of a property
+    #[command(name = "set")]
+    Set(ConfigSetArgs),
+    /// Remove a property
+    #[command(name = "unset")]
+    Unset(ConfigUnsetArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct ConfigListArgs {
+    /// Name of the section to list. By default, all sections are listed.
+    #[arg(value_name = "SECTION")]
+    pub section: Option<String>,
+}
+
+#[derive(Args, Debug)]
+pub struct ConfigGetArgs {
+    /// Property to get, as SECTION.PROPERTY
+    #[arg(value_name = "KEY")]
+    pub key: String,
+}
+
+#[derive(Args, Debug)]
+pub struct ConfigSetArgs {
+    /// Property to set, as SECTION.PROPERTY
+    #[arg(value_name = "KEY")]
+    pub key: String,
+
+    /// Value to set the property to
+    #[arg(value_name = "VALUE")]
+    pub value: String,
+}
+
+#[derive(Args, Debug)]
+pub struct ConfigUnsetArgs {
+    /// Property to remove, as SECTION.PROPERTY
+    #[arg(value_name = "KEY")]
+    pub key: String,
+}
 
 /// List objects or files within objects
 ///
 /// When listing objects, rocfl must scan the entire repository, and can therefore be very slow
 /// when operating on large repositories or repositories in S3. Results will be printed as soon
 /// as they're found so long as the results do not need to be sorted or displayed in a formatted
-/// table. Use '-t' to disable the table formatting.
+/// table. Use '-t' to disable the table formatting, or '--format ndjson' to write each result as
+/// a JSON object on its own line.
 ///
 /// This command supports glob expressions. When you use globs, it is usually a good idea to
 /// quote them so that your shell does not attempt to expand them.
@@ -159,7 +378,8 @@ pub struct ListCmd {
 
     /// Enable long output
     ///
-    /// Format: Version, Updated, Name (Object ID or Logical Path)
+    /// Format: Version, Updated, Name (Object ID or Logical Path). Additional columns are
+    /// enabled with '--origin', '-c', '-p', and '-d'.
     #[arg(short, long)]
     pub long: bool,
 
@@ -167,18 +387,52 @@ pub struct ListCmd {
     #[arg(short, long)]
     pub physical: bool,
 
+    /// Display the path to the file's content relative the object root
+    ///
+    /// This is the path in the object's inventory manifest, such as 'v1/content/file.txt'. It
+    /// is only displayed when listing the contents of an object.
+    #[arg(short, long)]
+    pub content_path: bool,
+
+    /// Display the version the file's content was added to the object in
+    ///
+    /// The Version column is the version the file was last updated in, and differs from the
+    /// origin when a file is renamed or its content duplicates content from an earlier version.
+    /// It is only displayed when listing the contents of an object.
+    #[arg(long)]
+    pub origin: bool,
+
     /// Display the digest of the item in the format 'algorithm:digest'
     #[arg(short, long)]
     pub digest: bool,
 
+    /// Only display the first LENGTH characters of digests
+    #[arg(long, value_name = "LENGTH", requires = "digest")]
+    pub digest_length: Option<usize>,
+
     /// Display a header row
     #[arg(short = 'H', long)]
     pub header: bool,
 
-    /// Tab separate the output
+    /// Tab separate the output. Equivalent to '--format tsv'
     #[arg(short, long)]
     pub tsv: bool,
 
+    /// Format of the output
+    ///
+    /// 'ndjson' writes each result as a JSON object on its own line, and includes every field
+    /// regardless of which display options are set. Unsorted objects are written as soon as they
+    /// are read, so this is the most efficient way to list very large repositories.
+    #[arg(
+        value_enum,
+        short,
+        long,
+        value_name = "FORMAT",
+        default_value = "table",
+        ignore_case = true
+    )]
+    pub format: ListFormat,
+
     /// List staged objects or the contents of a specific staged object
     #[arg(short = 'S', long, conflicts_with = "version")]
     pub staged: bool,
@@ -206,6 +460,95 @@ pub struct ListCmd {
     #[arg(short, long)]
     pub objects: bool,
 
+    /// Exit with a zero status even if some objects could not be read
+    ///
+    /// Objects that cannot be read are always skipped and summarized on stderr. By default,
+    /// the command then exits with a non-zero status.
+    #[arg(long)]
+    pub skip_errors: bool,
+
+    /// Merge the object's HEAD state with its staged changes and mark each path
+    ///
+    /// Each file is marked as unchanged, added, modified, or deleted relative the object's
+    /// HEAD version, so that the exact contents of the next version can be reviewed before
+    /// committing. Requires '-S' and an object ID.
+    #[arg(
+        long,
+        requires = "staged",
+        requires = "object_id",
+        conflicts_with = "logical_dirs",
+        conflicts_with = "objects"
+    )]
+    pub diff_markers: bool,
+
+    /// Only list objects, or files, that were created before DATE
+    ///
+    /// Objects are matched on the date their most recent version was created, and files on the
+    /// date of the version they were last updated in. DATE is either a date, such as
+    /// '2020-12-23', which is midnight local time, or an RFC 3339 timestamp.
+    #[arg(
+        long,
+        value_name = "DATE",
+        value_parser = parse_date,
+        conflicts_with = "staged"
+    )]
+    pub before: Option<DateTime<Local>>,
+
+    /// Only list objects, or files, that were created after DATE
+    ///
+    /// DATE has the same format as '--before'.
+    #[arg(
+        long,
+        value_name = "DATE",
+        value_parser = parse_date,
+        conflicts_with = "staged"
+    )]
+    pub after: Option<DateTime<Local>>,
+
+    /// Only list objects, or files, that are at least BYTES in size
+    ///
+    /// Objects are matched on the total size of their content files. Sizes must be read from
+    /// storage, which may be slow when listing objects.
+    #[arg(long, value_name = "BYTES", conflicts_with = "staged")]
+    pub min_size: Option<u64>,
+
+    /// Only list objects, or files, that are at most BYTES in size
+    #[arg(long, value_name = "BYTES", conflicts_with = "staged")]
+    pub max_size: Option<u64>,
+
+    /// Only list frozen objects
+    ///
+    /// Each object's freeze marker must be read from storage, which may be slow in S3. This
+    /// does not apply when listing the contents of an object.
+    #[arg(long, conflicts_with = "staged")]
+    pub frozen: bool,
+
+    /// Display the fingerprint of each object's root inventory
+    ///
+    /// The fingerprint is the inventory's digest, as recorded in its sidecar, and changes
+    /// whenever a version is committed to the object. '--format ndjson' also includes the
+    /// inventory's ETag when the repository is in S3. Each object's sidecar must be read from
+    /// storage, which may be slow in S3. This does not apply when listing the contents of an
+    /// object.
+    #[arg(long, conflicts_with = "staged")]
+    pub fingerprint: bool,
+
+    /// Which timestamp to display for files in long output
+    ///
+    /// 'updated' is when the version the file was last updated in was created. 'original' is
+    /// the modification time the file had when it was copied or moved into the object with
+    /// '--preserve-timestamps', and is blank for files without one. Sorting on 'updated' uses
+    /// the displayed timestamp.
+    #[arg(
+        value_enum,
+        long,
+        value_name = "TIMESTAMP",
+        default_value = "updated",
+        ignore_case = true,
+        requires = "long"
+    )]
+    pub timestamps: Timestamps,
+
     /// ID of the object to list. May be a glob when used with '-o'.
     #[arg(value_name = "OBJ_ID")]
     pub object_id: Option<String>,
@@ -216,7 +559,10 @@ pub struct ListCmd {
 }
 
 /// Display version history of an object or file.
+///
+/// '-h' is the short flag of '--header', so help is only available with '--help'.
 #[derive(Args, Debug)]
+#[command(disable_help_flag = true)]
 pub struct LogCmd {
     /// Compact format
     #[arg(short, long)]
@@ -238,13 +584,34 @@ pub struct LogCmd {
     #[arg(short, long, value_name = "NUM", default_value_t)]
     pub num: Num,
 
+    /// Skip the first NUM versions that would otherwise be displayed
+    #[arg(long, value_name = "NUM", default_value_t = 0)]
+    pub skip: usize,
+
+    /// Display the number of content files, and their total size in bytes, that were added
+    /// in each version
+    #[arg(short, long)]
+    pub sizes: bool,
+
+    /// Display the number of files that were added, modified, deleted, and renamed in each
+    /// version, relative the version before it
+    #[arg(short, long)]
+    pub diff: bool,
+
+    /// Print help
+    #[arg(long, action = ArgAction::Help)]
+    pub help: Option<bool>,
+
     /// ID of the object
     #[arg(value_name = "OBJ_ID")]
     pub object_id: String,
 
     /// Optional path to a file
+    ///
+    /// The path may be qualified with a version, as 'PATH@VERSION', to only display the versions
+    /// up to and including that version.
     #[arg(value_name = "PATH")]
-    pub path: Option<String>,
+    pub path: Option<PathRef>,
 }
 
 /// Show a summary of changes in a version
@@ -263,24 +630,97 @@ pub struct ShowCmd {
     pub object_id: String,
 
     /// The version to show. The most recent version is shown by default
+    ///
+    /// A logical path, optionally qualified with a version as 'PATH@VERSION', may be specified
+    /// instead to only show the changes to the path, or the files under it.
     #[arg(value_name = "VERSION")]
-    pub version: Option<VersionNum>,
+    pub version: Option<VersionOrPath>,
 }
 
 /// Show the files that changed between two versions
 #[derive(Args, Debug)]
 pub struct DiffCmd {
+    /// Diff a range of versions, formatted as 'START..END', instead of two versions
+    ///
+    /// The changes made after START, up to and including END, are squashed into their net
+    /// changes, unless '--steps' is specified.
+    #[arg(
+        long,
+        value_name = "RANGE",
+        conflicts_with_all = ["left", "right"],
+        required_unless_present_all = ["left", "right"]
+    )]
+    pub versions: Option<VersionRange>,
+
+    /// Show the changes made in each version in the range, rather than the net changes
+    #[arg(long, requires = "versions")]
+    pub steps: bool,
+
+    /// Only show the number of files that were added, modified, deleted, and renamed
+    #[arg(long)]
+    pub stat: bool,
+
+    /// How files that were moved without changing their content are reported
+    ///
+    /// 'all' reports all of the deleted and added paths that share content as a single rename.
+    /// 'one-to-one' only reports a rename when a single path was moved to a single other path,
+    /// and reports the rest as adds and deletes. 'off' reports every change as an add or delete.
+    #[arg(long, value_name = "MODE", default_value = "all")]
+    pub renames: Renames,
+
+    /// Report renamed files as deleted and added files. This is the same as '--renames off'
+    #[arg(long)]
+    pub no_renames: bool,
+
+    /// Print the changes as a JSON patch that can be replayed onto another object with 'apply'
+    ///
+    /// The content of added and modified files is referenced by digest, unless '--inline' is
+    /// specified.
+    #[arg(long, conflicts_with_all = ["steps", "stat"])]
+    pub emit_patch: bool,
+
+    /// Include the content of added and modified files in the patch
+    #[arg(long, requires = "emit_patch")]
+    pub inline: bool,
+
     /// ID of the object
     #[arg(value_name = "OBJ_ID")]
     pub object_id: String,
 
     /// Left-hand side version
-    #[arg(value_name = "LEFT_VERSION")]
-    pub left: VersionNum,
+    #[arg(value_name = "LEFT_VERSION", requires = "right")]
+    pub left: Option<VersionNum>,
 
     /// Right-hand side version
     #[arg(value_name = "RIGHT_VERSION")]
-    pub right: VersionNum,
+    pub right: Option<VersionNum>,
+}
+
+/// Compare the files in two different objects
+///
+/// The paths that only exist in one of the objects, and the paths whose content differs, are
+/// listed. This is useful for verifying that an object was replicated correctly, or that two
+/// objects are duplicates. The command exits with a non-zero status if the objects differ.
+///
+/// Content is compared by digest. If the objects use different digest algorithms, the content of
+/// the files that are in both objects is read to calculate comparable digests.
+#[derive(Args, Debug)]
+pub struct CmpCmd {
+    /// Version of object A to compare. Default: most recent
+    #[arg(long, value_name = "VERSION")]
+    pub version_a: Option<VersionNum>,
+
+    /// Version of object B to compare. Default: most recent
+    #[arg(long, value_name = "VERSION")]
+    pub version_b: Option<VersionNum>,
+
+    /// ID of object A
+    #[arg(value_name = "OBJ_ID_A")]
+    pub object_id_a: String,
+
+    /// ID of object B
+    #[arg(value_name = "OBJ_ID_B")]
+    pub object_id_b: String,
 }
 
 /// Print the specified file to stdout
@@ -294,118 +734,470 @@ pub struct CatCmd {
     #[arg(short, long, value_name = "VERSION")]
     pub version: Option<VersionNum>,
 
+    /// Verify the file's digest as it is read
+    ///
+    /// The file's content is hashed as it is printed, and the command fails if its digest does
+    /// not match the digest in the object's manifest. Because the content is streamed, it is
+    /// printed before the mismatch is detected.
+    #[arg(long)]
+    pub verify: bool,
+
     /// ID of the object
     #[arg(value_name = "OBJ_ID")]
     pub object_id: String,
 
     /// Logical path of the file
+    ///
+    /// The path may be qualified with a version, as 'PATH@VERSION', instead of using '--version'.
     #[arg(value_name = "PATH")]
-    pub path: String,
+    pub path: PathRef,
 }
 
-/// Create a new OCFL repository
+/// Display the logical paths in an object version as a tree
 ///
-/// The repository is created in the current directory unless the global option '-r PATH'
-/// was specified.
+/// Logical directories are implied by the '/' separated parts of the object's logical paths.
+/// Use '--depth' and '--dirs-only' to get an overview of large objects.
+#[derive(Args, Debug)]
+pub struct TreeCmd {
+    /// Display the size of each file, and the total size of each directory, in bytes
+    #[arg(short, long)]
+    pub size: bool,
+
+    /// Display the digest of each file in the format 'algorithm:digest'
+    #[arg(short, long)]
+    pub digest: bool,
+
+    /// Only display directories
+    #[arg(short = 'D', long)]
+    pub dirs_only: bool,
+
+    /// Only descend the specified number of directories deep
+    #[arg(short = 'L', long, value_name = "DEPTH")]
+    pub depth: Option<usize>,
+
+    /// Version of the object to display
+    #[arg(short, long, value_name = "VERSION")]
+    pub version: Option<VersionNum>,
+
+    /// ID of the object
+    #[arg(value_name = "OBJ_ID")]
+    pub object_id: String,
+
+    /// Logical directory to display the tree of. Default: the object's root
+    #[arg(value_name = "PATH")]
+    pub path: Option<String>,
+}
+
+/// List or print the files in an object's logs directory
 ///
-/// A new repository may only be created in an empty directory. By default, new repositories
-/// are configured to use the storage layout extension 0004-hashed-n-tuple-storage-layout.
-/// You should change this up front if you do not want to use this extension as it is
-/// difficult to change a repository's layout after objects have been created.
+/// Objects may contain a 'logs' directory for recording information, such as preservation
+/// events, that is not part of the object's versioned content. When a file is not specified,
+/// the paths of all of the files in the directory are listed.
 #[derive(Args, Debug)]
-pub struct InitCmd {
-    /// OCFL spec version that the repository adheres to
-    #[arg(
-        value_enum,
-        short = 'v',
-        long,
-        value_name = "SPEC_VERSION",
-        default_value = "1.1",
-        ignore_case = true
-    )]
-    pub spec_version: SpecVersion,
+pub struct LogsCmd {
+    /// ID of the object
+    #[arg(value_name = "OBJ_ID")]
+    pub object_id: String,
 
-    /// Path to a custom storage layout extension config JSON file.
-    #[arg(short, long, value_name = "LAYOUT_CONFIG")]
-    pub config_file: Option<PathBuf>,
+    /// Path of the file to print, relative the logs directory
+    #[arg(value_name = "FILE")]
+    pub file: Option<String>,
+}
 
-    /// OCFL storage layout extension to use
+/// List or print the files in an object's extensions directory
+///
+/// Objects may contain an 'extensions' directory that OCFL extensions store data in that is not
+/// part of the object's versioned content, such as the mutable HEAD of the 0005-mutable-head
+/// extension. When a file is not specified, the paths of all of the files in the directory are
+/// listed. Each path begins with the name of the extension the file belongs to.
+#[derive(Args, Debug)]
+pub struct ExtensionsCmd {
+    /// ID of the object
+    #[arg(value_name = "OBJ_ID")]
+    pub object_id: String,
+
+    /// Path of the file to print, relative the extensions directory
+    #[arg(long, value_name = "FILE")]
+    pub cat: Option<String>,
+}
+
+/// Export or verify a checksum manifest of an object version
+///
+/// A manifest lists the digest of every file in an object version. 'export' writes one in a
+/// format that other tools understand, such as 'sha512sum -c' or a spreadsheet, and
+/// 'import --verify' compares a manifest against the object, reporting the files whose digests
+/// differ, that are missing from the object, or that are missing from the manifest.
+#[derive(Args, Debug)]
+pub struct ManifestCmd {
+    /// Manifest operation to execute
+    #[command(subcommand)]
+    pub command: ManifestCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ManifestCommand {
+    /// Write a manifest of an object version to stdout
+    #[command(name = "export")]
+    Export(ManifestExportArgs),
+    /// Compare a manifest against an object version
+    #[command(name = "import")]
+    Import(ManifestImportArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct ManifestExportArgs {
+    /// Version of the object to export. Default: most recent
+    #[arg(short, long, value_name = "VERSION")]
+    pub version: Option<VersionNum>,
+
+    /// Format of the manifest
     ///
-    /// The default extension configuration for the extension is used. Custom configuration
-    /// may be specified using '--config-file'.
+    /// 'checksum' is the format used by tools like 'sha512sum'. 'csv' includes the digest
+    /// algorithm, and the version each file was last updated in.
     #[arg(
         value_enum,
         short,
         long,
-        value_name = "LAYOUT",
-        default_value = "0004-hashed-n-tuple-storage-layout",
+        value_name = "FORMAT",
+        default_value = "checksum",
         ignore_case = true
     )]
-    pub layout: Layout,
+    pub format: ManifestFormat,
+
+    /// ID of the object
+    #[arg(value_name = "OBJ_ID")]
+    pub object_id: String,
 }
 
-/// Upgrades an existing OCFL repository or object
-///
-/// Upgrades a repository or object to a later spec version. If an object ID is not specified,
-/// then the repository is upgraded. Upgrading a repository does not upgrade existing objects
-/// in the repository, but newly created objects will use the upgraded version by default.
-///
-/// After upgrading a repository, existing objects must be upgraded explicitly if you want them
-/// upgraded. Upgrading an object requires creating a new OCFL object version. As such, upgrading
-/// is treated the same as a commit operation. This means that any staged changes an object has
-/// are committed to the object as part of the upgrade.
 #[derive(Args, Debug)]
-pub struct UpgradeCmd {
-    /// OCFL spec version to upgrade to
+pub struct ManifestImportArgs {
+    /// Compare the manifest against the object, and exit with a non-zero status if they differ.
+    /// Manifests may only be imported to verify them.
+    #[arg(long, required = true)]
+    pub verify: bool,
+
+    /// Version of the object to compare against. Default: most recent
+    #[arg(short, long, value_name = "VERSION")]
+    pub version: Option<VersionNum>,
+
+    /// Format of the manifest
     #[arg(
         value_enum,
-        short = 'v',
+        short,
         long,
-        value_name = "SPEC_VERSION",
+        value_name = "FORMAT",
+        default_value = "checksum",
         ignore_case = true
     )]
-    pub spec_version: SpecVersion,
+    pub format: ManifestFormat,
 
-    /// Pretty print the version's inventory.json file
+    /// Algorithm of the digests in a 'checksum' manifest. Default: the object's algorithm
     ///
-    /// Only applies when upgrading objects
-    #[arg(short, long)]
-    pub pretty_print: bool,
+    /// When the algorithm differs from the object's, each file's digest is calculated by reading
+    /// its content.
+    #[arg(short, long, value_name = "ALGORITHM")]
+    pub digest_algorithm: Option<OcflDigestAlgorithm>,
 
-    /// Name of the user to attribute the changes to
-    ///
-    /// Only applies when upgrading objects
-    #[arg(short = 'n', long, value_name = "NAME")]
-    pub user_name: Option<String>,
+    /// ID of the object
+    #[arg(value_name = "OBJ_ID")]
+    pub object_id: String,
 
-    /// Address URI of the user to attribute the changes to. For example, mailto:test@example.com
-    ///
-    /// Only applies when upgrading objects
-    #[arg(short = 'a', long, value_name = "ADDRESS")]
-    pub user_address: Option<String>,
+    /// Path to the manifest, or '-' to read it from stdin
+    #[arg(value_name = "MANIFEST")]
+    pub manifest: PathBuf,
+}
 
-    /// Message describing the changes
-    ///
-    /// Only applies when upgrading objects
-    #[arg(short, long, value_name = "MESSAGE")]
-    pub message: Option<String>,
+/// Export objects to a content-addressed directory
+///
+/// The content of each object is written to DST/content/ALGORITHM/XX/DIGEST, where XX is the
+/// first two characters of the content's digest, and a JSON manifest that maps the object's
+/// logical paths to digests is written to DST/objects/ENCODED_ID.json. Content that is shared by
+/// many objects is only written once, which makes the export efficient to transfer.
+///
+/// Exports may be resumed by running the same command again. Content that was already exported
+/// is skipped, as are objects whose manifest already describes the exported version. When no
+/// object IDs are specified, every object in the repository is exported.
+#[derive(Args, Debug)]
+pub struct ExportCmd {
+    /// Export the objects in a content-addressed layout. Objects may only be exported in this
+    /// layout.
+    #[arg(long, required = true)]
+    pub cas: bool,
 
-    /// RFC 3339 creation timestamp of the version. Default: now
-    ///
-    /// Only applies when upgrading objects.
-    /// Example timestamp: 2020-12-23T10:11:12-06:00
-    #[arg(short, long, value_name = "TIMESTAMP")]
-    pub created: Option<DateTime<Local>>,
+    /// Version of the objects to export. Default: most recent
+    #[arg(short, long, value_name = "VERSION")]
+    pub version: Option<VersionNum>,
 
-    /// ID of the object to upgrade
+    /// Directory to export the objects to
+    #[arg(value_name = "DST")]
+    pub destination: PathBuf,
+
+    /// IDs of the objects to export. Default: all objects
     #[arg(value_name = "OBJ_ID")]
-    pub object_id: Option<String>,
+    pub object_ids: Vec<String>,
 }
 
-/// Stage a new OCFL object
+/// Transfer whole objects between repositories as zip archives
 ///
-/// New objects are created in staging and must be committed before they are available in the
-/// main repository.
+/// 'export' writes every file in an object's root, including all of its versions, inventories,
+/// sidecars, logs, and extensions, to a single zip64 archive. 'import' creates a new object in
+/// the repository from such an archive. Objects are validated, including the fixity of all of
+/// their content, both before they are exported and before they are imported.
+#[derive(Args, Debug)]
+pub struct ZipCmd {
+    /// Zip operation to execute
+    #[command(subcommand)]
+    pub command: ZipCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ZipCommand {
+    /// Write an object to a zip archive
+    #[command(name = "export")]
+    Export(ZipExportArgs),
+    /// Create an object from a zip archive
+    #[command(name = "import")]
+    Import(ZipImportArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct ZipExportArgs {
+    /// ID of the object
+    #[arg(value_name = "OBJ_ID")]
+    pub object_id: String,
+
+    /// Path of the zip archive to create. It must not already exist.
+    #[arg(value_name = "DST")]
+    pub destination: PathBuf,
+}
+
+#[derive(Args, Debug)]
+pub struct ZipImportArgs {
+    /// Path of the zip archive to import
+    #[arg(value_name = "SRC")]
+    pub source: PathBuf,
+}
+
+/// Search the contents of an object's files
+///
+/// Every file in the object version is searched for lines that match the regular expression, and
+/// matching lines are printed along with the logical path of the file and the line number. The
+/// files that are searched may be restricted using a logical path glob. Files that appear to be
+/// binary, and files that are larger than the maximum size, are skipped.
+#[derive(Args, Debug)]
+pub struct GrepCmd {
+    /// Version of the object to search
+    #[arg(short, long, value_name = "VERSION")]
+    pub version: Option<VersionNum>,
+
+    /// Logical path glob of the files to search
+    #[arg(short, long, value_name = "GLOB")]
+    pub path: Option<String>,
+
+    /// Maximum size, in bytes, of the files to search. Larger files are skipped.
+    #[arg(short, long, value_name = "BYTES", default_value = "10485760")]
+    pub max_size: u64,
+
+    /// Only print the logical paths of the files that contain matches
+    #[arg(short = 'l', long)]
+    pub files_with_matches: bool,
+
+    /// ID of the object
+    #[arg(value_name = "OBJ_ID")]
+    pub object_id: String,
+
+    /// Regular expression to search for
+    #[arg(value_name = "PATTERN")]
+    pub pattern: String,
+}
+
+/// Find files by logical path across all objects
+///
+/// The inventory of every object in the repository is scanned, and each object, version, and
+/// logical path that matches the glob is printed, separated by tabs. By default, every version
+/// of each object is searched, so a file that is unchanged across versions is listed once for
+/// each version it is in. Use '--head' to only search the most recent version of each object.
+///
+/// Results are printed as soon as they're found. This command must read every inventory in the
+/// repository, and can therefore be slow on large repositories or repositories in S3.
+#[derive(Args, Debug)]
+pub struct FindCmd {
+    /// Logical path glob to match, eg. '*.tiff'
+    #[arg(short, long, value_name = "GLOB")]
+    pub path: String,
+
+    /// Only search the most recent version of each object
+    #[arg(long)]
+    pub head: bool,
+
+    /// Also print the digest of each matching file
+    #[arg(short, long)]
+    pub digest: bool,
+
+    /// Exit with a zero status even if some objects could not be read
+    ///
+    /// Objects that cannot be read are always skipped and summarized on stderr. By default,
+    /// the command then exits with a non-zero status.
+    #[arg(long)]
+    pub skip_errors: bool,
+
+    /// Glob of the IDs of the objects to search
+    #[arg(value_name = "OBJ_ID")]
+    pub object_id: Option<String>,
+}
+
+/// Create a new OCFL repository
+///
+/// The repository is created in the current directory unless the global option '-r PATH'
+/// was specified.
+///
+/// A new repository may only be created in an empty directory. By default, new repositories
+/// are configured to use the storage layout extension 0004-hashed-n-tuple-storage-layout.
+/// You should change this up front if you do not want to use this extension as it is
+/// difficult to change a repository's layout after objects have been created.
+///
+/// Use '--from-existing' to set up rocfl over an OCFL repository that was created by another
+/// tool. The repository's existing spec version and layout are used, and any missing spec or
+/// layout documents are written. Existing objects are not modified.
+///
+/// Use '--digest-algorithm' to change the digest algorithm that new objects use by default.
+#[derive(Args, Debug)]
+pub struct InitCmd {
+    /// Initialize over an existing OCFL repository. '--spec-version', '--layout', and
+    /// '--config-file' are ignored.
+    #[arg(short = 'e', long)]
+    pub from_existing: bool,
+
+    /// OCFL spec version that the repository adheres to
+    #[arg(
+        value_enum,
+        short = 'v',
+        long,
+        value_name = "SPEC_VERSION",
+        default_value = "1.1",
+        ignore_case = true
+    )]
+    pub spec_version: SpecVersion,
+
+    /// Path to a custom storage layout extension config JSON file.
+    #[arg(short, long, value_name = "LAYOUT_CONFIG")]
+    pub config_file: Option<PathBuf>,
+
+    /// OCFL storage layout extension to use
+    ///
+    /// The default extension configuration for the extension is used. Custom configuration
+    /// may be specified using '--config-file'.
+    #[arg(
+        value_enum,
+        short,
+        long,
+        value_name = "LAYOUT",
+        default_value = "0004-hashed-n-tuple-storage-layout",
+        ignore_case = true
+    )]
+    pub layout: Layout,
+
+    /// Do not write a copy of the OCFL spec to the storage root
+    ///
+    /// The spec recommends that a copy is included, and 'validate' warns when it is missing.
+    #[arg(long)]
+    pub no_spec_copy: bool,
+
+    /// Default digest algorithm for objects created in the repository [default: sha512]
+    ///
+    /// The algorithm is stored in the repository's 'rocfl-repo-defaults' extension, and is used
+    /// by 'new' when an algorithm is not specified. sha512 is recommended; a warning is logged
+    /// when sha256 is chosen.
+    #[arg(
+        value_enum,
+        short,
+        long,
+        visible_alias = "digest",
+        value_name = "ALGORITHM",
+        ignore_case = true
+    )]
+    pub digest_algorithm: Option<DigestAlgorithm>,
+}
+
+/// Upgrades an existing OCFL repository or object
+///
+/// Upgrades a repository or object to a later spec version. If an object ID is not specified,
+/// then the repository is upgraded. Upgrading a repository does not upgrade existing objects
+/// in the repository, but newly created objects will use the upgraded version by default.
+///
+/// After upgrading a repository, existing objects must be upgraded explicitly if you want them
+/// upgraded. Upgrading an object requires creating a new OCFL object version. As such, upgrading
+/// is treated the same as a commit operation. This means that any staged changes an object has
+/// are committed to the object as part of the upgrade.
+#[derive(Args, Debug)]
+pub struct UpgradeCmd {
+    /// OCFL spec version to upgrade to
+    #[arg(
+        value_enum,
+        short = 'v',
+        long,
+        value_name = "SPEC_VERSION",
+        ignore_case = true
+    )]
+    pub spec_version: SpecVersion,
+
+    /// Pretty print the version's inventory.json file
+    ///
+    /// Only applies when upgrading objects
+    #[arg(short, long, overrides_with = "no_pretty_print")]
+    pub pretty_print: bool,
+
+    /// Do not pretty print the version's inventory.json file, even if the config enables it
+    ///
+    /// Only applies when upgrading objects
+    #[arg(long, overrides_with = "pretty_print")]
+    pub no_pretty_print: bool,
+
+    /// Name of the user to attribute the changes to
+    ///
+    /// Only applies when upgrading objects
+    #[arg(short = 'n', long, value_name = "NAME")]
+    pub user_name: Option<String>,
+
+    /// Address URI of the user to attribute the changes to. For example, mailto:test@example.com
+    ///
+    /// Only applies when upgrading objects
+    #[arg(short = 'a', long, value_name = "ADDRESS")]
+    pub user_address: Option<String>,
+
+    /// Message describing the changes
+    ///
+    /// Only applies when upgrading objects
+    #[arg(short, long, value_name = "MESSAGE")]
+    pub message: Option<String>,
+
+    /// RFC 3339 creation timestamp of the version. Default: now
+    ///
+    /// Only applies when upgrading objects.
+    /// Example timestamp: 2020-12-23T10:11:12-06:00
+    #[arg(short, long, value_name = "TIMESTAMP")]
+    pub created: Option<DateTime<Local>>,
+
+    /// Upgrade the object even if it is frozen
+    #[arg(short, long)]
+    pub force: bool,
+
+    /// ID of the object to upgrade
+    #[arg(value_name = "OBJ_ID")]
+    pub object_id: Option<String>,
+}
+
+/// Stage a new OCFL object
+///
+/// New objects are created in staging and must be committed before they are available in the
+/// main repository.
+///
+/// Objects may be scaffolded from a template with '--from-template'. Templates are TOML, or JSON
+/// when the file has a .json extension, and may define digest_algorithm, content_directory,
+/// zero_padding, a [metadata] table of entries to write to the object's metadata file, and
+/// [[files]] entries. Each file has a logical 'path' and either a 'source' file to copy, which is
+/// resolved relative to the template, or literal 'content'. Command line arguments take
+/// precedence over the template, and the template over the configuration.
 #[derive(Args, Debug)]
 pub struct NewCmd {
     /// OCFL spec version that the object adheres to
@@ -422,24 +1214,35 @@ pub struct NewCmd {
     )]
     pub spec_version: Option<SpecVersion>,
 
-    /// Digest algorithm to use for the inventory digest
+    /// Digest algorithm to use for the inventory digest [default: sha512]
+    ///
+    /// When not specified, the repository's default algorithm is used, which is set with
+    /// 'init --digest-algorithm'. sha512 is recommended; a warning is logged when sha256 is used.
     #[arg(
         value_enum,
         short,
         long,
+        visible_alias = "digest",
         value_name = "ALGORITHM",
-        default_value = "sha512",
         ignore_case = true
     )]
-    pub digest_algorithm: DigestAlgorithm,
+    pub digest_algorithm: Option<DigestAlgorithm>,
 
-    /// Name of the object's content directory
-    #[arg(short, long, value_name = "PATH", default_value = "content")]
-    pub content_directory: String,
+    /// Name of the object's content directory [default: content]
+    #[arg(short, long, value_name = "PATH")]
+    pub content_directory: Option<String>,
 
-    /// Width for zero-padded version numbers, eg. v0001 has a width of 4
-    #[arg(short, long, value_name = "WIDTH", default_value = "0")]
-    pub zero_padding: u32,
+    /// Width for zero-padded version numbers, eg. v0001 has a width of 4 [default: 0]
+    ///
+    /// The width must be 0 or between 2 and 10. Later versions always use the same width as the
+    /// object's first version. Zero-padding is discouraged by the OCFL spec, and a warning is
+    /// logged when it is used.
+    #[arg(short, long, visible_alias = "padding", value_name = "WIDTH")]
+    pub zero_padding: Option<u32>,
+
+    /// Path to a TOML or JSON template that defines the object's initial files and metadata
+    #[arg(short = 't', long, value_name = "TEMPLATE")]
+    pub from_template: Option<PathBuf>,
 
     /// ID of the object to create.
     #[arg(value_name = "OBJ_ID")]
@@ -451,6 +1254,10 @@ pub struct NewCmd {
 /// If the target object does not already have a staged version, a new staged version is created,
 /// and the files are copied to it. The changes must be committed before they are reflected in a
 /// new OCFL version in the object in the main repository.
+///
+/// Files may also be copied out of a tar archive, which may be gzip compressed, using
+/// '--from-archive'. The archive's entries are staged as they are read, without extracting the
+/// archive first, and the archive's internal paths are preserved under the destination.
 #[derive(Args, Debug)]
 pub struct CopyCmd {
     /// Source directories should be copied recursively.
@@ -461,6 +1268,45 @@ pub struct CopyCmd {
     #[arg(short, long)]
     pub internal: bool,
 
+    /// How symbolic links in the source paths are treated
+    ///
+    /// 'follow' adds the files that links point to, 'skip' ignores links and logs a warning,
+    /// and 'error' fails on each link. Only applicable to external files.
+    #[arg(
+        value_enum,
+        long,
+        value_name = "POLICY",
+        default_value = "follow",
+        ignore_case = true
+    )]
+    pub symlinks: Symlinks,
+
+    /// Record the modification times of the source files in the object
+    ///
+    /// The times are stored in the logical file 'metadata/rocfl-timestamps.json', and may be
+    /// listed with 'ls -l --timestamps original'. Only applicable to external files.
+    #[arg(long, conflicts_with_all = ["internal", "from_archive"])]
+    pub preserve_timestamps: bool,
+
+    /// Copy all of the files in the specified tar or tar.gz archive
+    #[arg(
+        long,
+        value_name = "ARCHIVE",
+        conflicts_with_all = ["internal", "recursive", "source"]
+    )]
+    pub from_archive: Option<PathBuf>,
+
+    /// Read additional source paths from FILE, or stdin if '-'
+    ///
+    /// Paths are separated by NUL characters if the list contains any, and newlines otherwise,
+    /// and are not glob expanded. Only applicable to external files.
+    #[arg(
+        long,
+        value_name = "FILE",
+        conflicts_with_all = ["internal", "from_archive"]
+    )]
+    pub files_from: Option<PathBuf>,
+
     /// Version of the object to copy the source paths from. Default: most recent
     ///
     /// Only applicable when copying files internally. For the purposes of this command,
@@ -475,7 +1321,7 @@ pub struct CopyCmd {
     pub object_id: String,
 
     /// Source files to copy. Glob patterns are supported.
-    #[arg(value_name = "SRC", required = true)]
+    #[arg(value_name = "SRC", required_unless_present_any = ["from_archive", "files_from"])]
     pub source: Vec<String>,
 
     /// Destination logical path. Specify '/' to copy into the object's root
@@ -494,12 +1340,39 @@ pub struct MoveCmd {
     #[arg(short, long)]
     pub internal: bool,
 
+    /// How symbolic links in the source paths are treated
+    ///
+    /// 'follow' adds the files that links point to, 'skip' ignores links and logs a warning,
+    /// and 'error' fails on each link. Only applicable to external files.
+    #[arg(
+        value_enum,
+        long,
+        value_name = "POLICY",
+        default_value = "follow",
+        ignore_case = true
+    )]
+    pub symlinks: Symlinks,
+
+    /// Record the modification times of the source files in the object
+    ///
+    /// The times are stored in the logical file 'metadata/rocfl-timestamps.json', and may be
+    /// listed with 'ls -l --timestamps original'. Only applicable to external files.
+    #[arg(long, conflicts_with = "internal")]
+    pub preserve_timestamps: bool,
+
+    /// Read additional source paths from FILE, or stdin if '-'
+    ///
+    /// Paths are separated by NUL characters if the list contains any, and newlines otherwise,
+    /// and are not glob expanded. Only applicable to external files.
+    #[arg(long, value_name = "FILE", conflicts_with = "internal")]
+    pub files_from: Option<PathBuf>,
+
     /// ID of the object to move files into
     #[arg(value_name = "OBJ_ID")]
     pub object_id: String,
 
     /// Source files to move. Glob patterns are supported.
-    #[arg(value_name = "SRC", required = true)]
+    #[arg(value_name = "SRC", required_unless_present = "files_from")]
     pub source: Vec<String>,
 
     /// Destination logical path. Specify '/' to move into the object's root
@@ -530,6 +1403,104 @@ pub struct RemoveCmd {
     pub paths: Vec<String>,
 }
 
+/// Apply a patch created by 'diff --emit-patch' to a staged version of an object
+///
+/// Renames are applied first, then deletes, and then added and modified files. The content of
+/// added and modified files is taken from the patch, if it was inlined. Otherwise, it is resolved
+/// by digest from the object, or read from the version of the object the patch was created from,
+/// if it exists in this repository.
+///
+/// The object must already exist. The changes must be committed before they are reflected in a
+/// new OCFL version.
+#[derive(Args, Debug)]
+pub struct ApplyCmd {
+    /// ID of the object to apply the patch to
+    #[arg(value_name = "OBJ_ID")]
+    pub object_id: String,
+
+    /// Path to the patch file. Use '-' to read the patch from stdin
+    #[arg(value_name = "PATCH")]
+    pub patch: PathBuf,
+}
+
+/// Create a staged version of an object
+///
+/// The staged version can then be modified with 'cp', 'mv', and 'rm' before it is committed.
+#[derive(Args, Debug)]
+pub struct StageCmd {
+    /// Stage operation to execute
+    #[command(subcommand)]
+    pub command: StageCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum StageCommand {
+    /// Stage a version that contains a subset of an existing version's files
+    ///
+    /// The staged version only contains the files in the existing version that match the
+    /// paths, or all of its files if no paths are specified. Files are staged by reference to
+    /// the existing content and are not copied. The object must not have staged changes.
+    #[command(name = "from")]
+    From(StageFromArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct StageFromArgs {
+    /// Logical directories should be staged recursively
+    #[arg(short, long)]
+    pub recursive: bool,
+
+    /// Version of the object to stage files from. By default, the HEAD version is used.
+    #[arg(short, long, value_name = "VERSION")]
+    pub version: Option<VersionNum>,
+
+    /// ID of the object to stage a version of
+    #[arg(value_name = "OBJ_ID")]
+    pub object_id: String,
+
+    /// Logical paths of the files to stage. Glob patterns are supported. If no paths are
+    /// specified, every file in the version is staged.
+    #[arg(value_name = "PATH")]
+    pub paths: Vec<String>,
+}
+
+/// Continuously stage the changes made to a directory into an object
+///
+/// The directory is mirrored into the object's staged version. When rocfl starts, every file in
+/// the directory that is not in the staged version, or differs from it, is staged, and files
+/// that are in the staged version but not in the directory are removed. Afterwards, the directory
+/// is watched, and the files that are added, modified, or deleted are staged as they change. The
+/// object must already exist.
+///
+/// The staged changes are committed when enter is pressed, every '--commit-interval' seconds, or
+/// by running 'rocfl commit' separately. Press ctrl-c to stop watching.
+#[derive(Args, Debug)]
+pub struct WatchCmd {
+    /// Commit the staged changes every SECONDS seconds
+    #[arg(short = 'i', long, value_name = "SECONDS", conflicts_with = "once")]
+    pub commit_interval: Option<u64>,
+
+    /// Message to describe the committed versions
+    #[arg(short, long, value_name = "MSG", conflicts_with = "once")]
+    pub message: Option<String>,
+
+    /// Milliseconds to wait for changes to stop before staging them
+    #[arg(long, value_name = "MILLIS", default_value = "500")]
+    pub debounce: u64,
+
+    /// Stage the current contents of the directory, and then exit without watching it
+    #[arg(long)]
+    pub once: bool,
+
+    /// Directory to watch
+    #[arg(value_name = "DIR")]
+    pub directory: PathBuf,
+
+    /// ID of the object to stage changes in
+    #[arg(value_name = "OBJ_ID")]
+    pub object_id: String,
+}
+
 /// Commit an object's staged changes to a new OCFL version.
 ///
 /// Creates a new OCFL version for all of the changes that were staged for an object, all
@@ -539,13 +1510,19 @@ pub struct RemoveCmd {
 /// time. These values are stamped into the new OCFL version's metadata.
 ///
 /// If the repository is not using a known storage layout, and a new object is being committed,
-/// then the storage root relative path to the object's root must be specified.
+/// then the storage root relative path to the object's root must be specified. Objects that are
+/// written to a location other than the one dictated by the storage layout are recorded in the
+/// repository's object index so that they can be found by subsequent commands.
 #[derive(Args, Debug)]
 pub struct CommitCmd {
     /// Pretty print the version's inventory.json file
-    #[arg(short, long)]
+    #[arg(short, long, overrides_with = "no_pretty_print")]
     pub pretty_print: bool,
 
+    /// Do not pretty print the version's inventory.json file, even if the config enables it
+    #[arg(long, overrides_with = "pretty_print")]
+    pub no_pretty_print: bool,
+
     /// Name of the user to attribute the changes to
     #[arg(short = 'n', long, value_name = "NAME")]
     pub user_name: Option<String>,
@@ -566,16 +1543,90 @@ pub struct CommitCmd {
 
     /// Storage root relative path to the object's root
     ///
-    /// Should only be specified for new objects in repositories without defined storage
-    /// layouts, and is otherwise ignored.
+    /// Only applies to new objects. When specified, the object is written to this location
+    /// instead of the location dictated by the storage layout. Required for new objects in
+    /// repositories without defined storage layouts.
     #[arg(short = 'r', long, value_name = "OBJ_ROOT")]
     pub object_root: Option<String>,
 
+    /// Hard link new files that duplicate content in other objects to the existing files
+    ///
+    /// Only applies to repositories on the local filesystem. Files that cannot be linked, for
+    /// example because they are on different filesystems, are copied. The first commit that
+    /// links files indexes the content of every object in the repository.
+    #[arg(long, overrides_with = "no_hard_links")]
+    pub hard_links: bool,
+
+    /// Copy new files that duplicate existing content, even if the config enables hard links
+    #[arg(long, overrides_with = "hard_links")]
+    pub no_hard_links: bool,
+
+    /// S3 storage class to write the version's content files to, eg. STANDARD_IA or GLACIER_IR
+    ///
+    /// Only applies to repositories in S3. Default: the bucket's default storage class
+    #[arg(long, value_name = "CLASS")]
+    pub storage_class: Option<String>,
+
+    /// Comma separated KEY=VALUE tags to apply to the version's content files
+    ///
+    /// Only applies to repositories in S3. For example: project=archive,owner=library
+    #[arg(long, value_name = "TAGS")]
+    pub tags: Option<String>,
+
+    /// S3 storage class to write the object's inventories to
+    ///
+    /// Only applies to repositories in S3. Inventories are read frequently, and should usually
+    /// be kept in an immediately retrievable storage class.
+    #[arg(long, value_name = "CLASS")]
+    pub inventory_storage_class: Option<String>,
+
+    /// Comma separated KEY=VALUE tags to apply to the object's inventories
+    ///
+    /// Only applies to repositories in S3.
+    #[arg(long, value_name = "TAGS")]
+    pub inventory_tags: Option<String>,
+
+    /// Validate the staged version before committing it
+    ///
+    /// The staged inventory is checked for problems, the version number must be next in
+    /// sequence, and every staged content file must match its digest. The version is not
+    /// committed if any errors are found.
+    #[arg(long)]
+    pub validate: bool,
+
+    /// Commit even if the object is frozen, or the commit exceeds the configured quotas
+    #[arg(short, long)]
+    pub force: bool,
+
+    /// Rebase the staged changes onto the object's current version if another version was
+    /// committed since they were staged
+    ///
+    /// Without this option, the commit fails when the staged version does not immediately follow
+    /// the object's current version. The commit still fails if a path was changed both in the
+    /// staged version and in a version committed since.
+    #[arg(long)]
+    pub retry: bool,
+
     /// ID of the object to commit changes for
     #[arg(value_name = "OBJ_ID")]
     pub object_id: String,
 }
 
+/// Rebase an object's staged changes onto its current version
+///
+/// When another version of the object was committed after the changes were staged, such as by
+/// another user of a shared staging area, the staged version no longer follows the object's
+/// current version and cannot be committed. This replays the staged changes on top of the
+/// current version, so that they can be committed without being staged again. The rebase fails,
+/// and the staged changes are not modified, if a path was changed both in the staged version and
+/// in a version committed since.
+#[derive(Args, Debug)]
+pub struct RebaseCmd {
+    /// ID of the object to rebase the staged changes of
+    #[arg(value_name = "OBJ_ID")]
+    pub object_id: String,
+}
+
 /// Reset an object's staged changes
 ///
 /// Additions are removed, deletions are restored, and modifications are returned to their
@@ -585,41 +1636,158 @@ pub struct CommitCmd {
 pub struct ResetCmd {
     /// Logical directories should be reset recursively
     #[arg(short, long)]
-    pub recursive: bool,
+    pub recursive: bool,
+
+    /// ID of the object to reset
+    #[arg(value_name = "OBJ_ID")]
+    pub object_id: String,
+
+    /// Logical paths of the files to reset. Glob patterns are supported. If no paths are
+    /// specified, the entire object is reset.
+    #[arg(value_name = "PATH")]
+    pub paths: Vec<String>,
+}
+
+/// List objects with staged changes, or a specific object's changes
+///
+/// This command is a simplified version of 'ls --staged' and 'show -staged'. Use the other commands
+/// if you need more options.
+#[derive(Args, Debug)]
+pub struct StatusCmd {
+    /// ID of the object to show staged changes for
+    #[arg(value_name = "OBJ_ID")]
+    pub object_id: Option<String>,
+}
+
+/// Permanently delete an object
+///
+/// Purged objects are permanently deleted from the repository. This operation cannot be undone.
+///
+/// An object's namaste file is deleted last. If a purge is interrupted, the object can usually
+/// be purged again by ID. If it can no longer be found, because its inventory was deleted, use
+/// '--resume-at' with the storage root relative path to the object's root to delete its
+/// remaining files.
+#[derive(Args, Debug)]
+pub struct PurgeCmd {
+    /// Purge without prompting for confirmation, even if the object is frozen
+    #[arg(short, long)]
+    pub force: bool,
+
+    /// Finish purging the partially deleted object at this storage root relative path
+    #[arg(long, value_name = "OBJ_ROOT", conflicts_with = "object_id")]
+    pub resume_at: Option<String>,
+
+    /// ID of the object to purge
+    #[arg(value_name = "OBJ_ID", required_unless_present = "resume_at")]
+    pub object_id: Option<String>,
+}
+
+/// Collapse an object's earliest versions into a single version
+///
+/// The object is rewritten so that its versions up to and including '--through' are replaced by
+/// a single baseline version, v1, that has the state of the '--through' version. Later versions
+/// are renumbered to follow it. Content that is only referenced by the collapsed versions is
+/// permanently deleted. This operation cannot be undone.
+///
+/// The object must not have staged changes. After the object is squashed, each of its original
+/// versions is printed along with the version it is now part of.
+#[derive(Args, Debug)]
+pub struct SquashCmd {
+    /// The last version to collapse into the baseline version
+    #[arg(short, long, value_name = "VERSION")]
+    pub through: VersionNum,
+
+    /// Squash without prompting for confirmation, even if the object is frozen
+    #[arg(short, long)]
+    pub force: bool,
+
+    /// ID of the object to squash
+    #[arg(value_name = "OBJ_ID")]
+    pub object_id: String,
+}
+
+/// Change the ID of an object
+///
+/// Every inventory in the object is rewritten with the new ID, their sidecars are recomputed,
+/// and the object is moved to the location that the storage layout dictates for the new ID. The
+/// renamed object is validated before it replaces the original. Aliases of the object are
+/// updated to refer to the new ID.
+///
+/// The object must not have staged changes. Inventory signatures no longer match the rewritten
+/// inventories after the object is renamed.
+#[derive(Args, Debug)]
+pub struct RenameCmd {
+    /// Storage root relative path to the renamed object's root
+    ///
+    /// When specified, the object is written to this location instead of the location dictated
+    /// by the storage layout. Required in repositories without defined storage layouts.
+    #[arg(short = 'r', long, value_name = "OBJ_ROOT")]
+    pub object_root: Option<String>,
+
+    /// Rename the object even if it is frozen
+    #[arg(short, long)]
+    pub force: bool,
 
-    /// ID of the object to reset
+    /// ID of the object to rename
     #[arg(value_name = "OBJ_ID")]
     pub object_id: String,
 
-    /// Logical paths of the files to reset. Glob patterns are supported. If no paths are
-    /// specified, the entire object is reset.
-    #[arg(value_name = "PATH")]
-    pub paths: Vec<String>,
+    /// New ID of the object
+    #[arg(value_name = "NEW_ID")]
+    pub new_object_id: String,
 }
 
-/// List objects with staged changes, or a specific object's changes
+/// Delete orphaned content files from an object
 ///
-/// This command is a simplified version of 'ls --staged' and 'show -staged'. Use the other commands
-/// if you need more options.
+/// Orphaned content files are files within an object's version content directories that are not
+/// referenced in the object's manifest, and are typically left behind by interrupted commits.
+/// They are reported as E023 errors by 'validate'.
+///
+/// The orphaned files are listed, and then, after confirmation, they are permanently deleted
+/// along with any content directories that are empty as a result. This operation cannot be
+/// undone.
+///
+/// When '--staging' is specified, the content that is staged in an S3 repository's bucket for
+/// objects that no longer have a staged version is deleted instead. This content is left behind
+/// when staging is abandoned, such as when the local staging directory is deleted. Only content
+/// that was staged from the local staging directory is deleted.
 #[derive(Args, Debug)]
-pub struct StatusCmd {
-    /// ID of the object to show staged changes for
-    #[arg(value_name = "OBJ_ID")]
+pub struct GcCmd {
+    /// List the orphaned files without deleting them
+    #[arg(short, long, conflicts_with = "force")]
+    pub dry_run: bool,
+
+    /// Delete without prompting for confirmation
+    #[arg(short, long)]
+    pub force: bool,
+
+    /// Delete abandoned content that is staged in the bucket rather than orphaned object content
+    #[arg(long, conflicts_with = "object_id")]
+    pub staging: bool,
+
+    /// Only delete abandoned staged content that was last modified longer ago than the age, eg. 7d
+    ///
+    /// The age is a number followed by a unit: s, m, h, d, or w.
+    #[arg(long, value_name = "AGE", default_value = "7d")]
+    pub older_than: Age,
+
+    /// ID of the object to clean up
+    #[arg(value_name = "OBJ_ID", required_unless_present = "staging")]
     pub object_id: Option<String>,
 }
 
-/// Permanently delete an object
+/// Parse and validate an inventory file outside of a repository
 ///
-/// Purged objects are permanently deleted from the repository. This operation cannot be undone.
+/// The inventory is validated against the OCFL spec, and a summary of it is displayed along with
+/// any issues that were identified. Only the inventory is validated, not its sidecar or the
+/// files it references. A repository is not required.
+///
+/// Return code 2 is returned if the inventory is invalid.
 #[derive(Args, Debug)]
-pub struct PurgeCmd {
-    /// Purge without prompting for confirmation
-    #[arg(short, long)]
-    pub force: bool,
-
-    /// ID of the object to purge
-    #[arg(value_name = "OBJ_ID")]
-    pub object_id: String,
+pub struct InspectInventoryCmd {
+    /// Path to the inventory file, or '-' to read it from stdin
+    #[arg(value_name = "FILE")]
+    pub file: PathBuf,
 }
 
 /// Validate an object or the entire repository
@@ -634,6 +1802,17 @@ pub struct PurgeCmd {
 ///
 /// If warnings or errors are suppressed and an object has no remaining issues after suppression,
 /// then the object is reported as valid.
+///
+/// When '--verbose' is specified, each error and warning is followed by the section and text of
+/// the OCFL spec that defines its code.
+///
+/// Paths that are likely to break when an object is moved between platforms are reported as
+/// warnings: RW005 for paths with control characters, RW006 for paths that are not in Unicode
+/// Normalization Form C, and RW007 for paths that contain names Windows reserves, such as CON.
+/// '--strict' reports them as errors instead, RE001, RE002, and RE003 respectively.
+///
+/// '--inventory' validates a single inventory file, such as one recovered from a backup, without
+/// a repository or the rest of its object.
 #[derive(Args, Debug)]
 pub struct ValidateCmd {
     /// Interpret positional parameters as paths to object roots relative the repository root
@@ -678,11 +1857,148 @@ pub struct ValidateCmd {
     )]
     pub suppress_error: Vec<ErrorCode>,
 
+    /// Only fixity check a percentage of each object's content files, eg. 10%
+    ///
+    /// The files to check are selected by ordering each object's content files based on a
+    /// seeded hash. The seed is printed in the summary so that the same files can be checked
+    /// again by specifying '--seed'. Use the same seed with an incremented '--round' to check
+    /// the next set of files.
+    ///
+    /// When validation history is enabled, and neither '--seed' nor '--round' is specified, an
+    /// object that was previously sampled with the same percentage is checked from where its
+    /// last sample left off, so that successive validations check different files until every
+    /// file was checked.
+    #[arg(
+        short,
+        long,
+        value_name = "PERCENT",
+        conflicts_with = "no_fixity_check"
+    )]
+    pub sample: Option<Percent>,
+
+    /// Seed used to select the files to check when sampling. Default: random
+    #[arg(long, value_name = "SEED", requires = "sample")]
+    pub seed: Option<u64>,
+
+    /// The set of sampled files to check. Default: 0
+    #[arg(long, value_name = "ROUND", requires = "sample")]
+    pub round: Option<u32>,
+
+    /// Only validate objects with storage paths that start with the prefix
+    ///
+    /// The prefix is relative the repository root. Only the branches of the storage hierarchy
+    /// that may contain matching objects are descended into. The storage root is still
+    /// validated.
+    #[arg(long, value_name = "PREFIX", conflicts_with = "object_ids")]
+    pub prefix: Option<String>,
+
+    /// Only validate objects with IDs that match the glob
+    ///
+    /// The storage root is still validated, but every object's inventory must be read to
+    /// determine its ID. Combine with '--prefix' to limit the objects that are read.
+    #[arg(long, value_name = "GLOB", conflicts_with = "object_ids")]
+    pub id_glob: Option<String>,
+
+    /// Print each result, and the summary, as a JSON object on its own line
+    ///
+    /// Errors and warnings include the section, text, and URL of the OCFL spec that defines
+    /// their code.
+    #[arg(long)]
+    pub json: bool,
+
+    /// Report unportable paths as errors rather than warnings
+    #[arg(long)]
+    pub strict: bool,
+
+    /// Only validate objects that have not been validated within the age, eg. 90d
+    ///
+    /// The age is a number followed by a unit: s, m, h, d, or w. Objects are only considered
+    /// validated when their validation was recorded in the repository's validation history,
+    /// which requires the 'validation_history' config property to be true.
+    #[arg(long, value_name = "AGE", conflicts_with = "paths")]
+    pub stale_than: Option<Age>,
+
+    /// Periodically record the validation's progress in FILE, so that it can be resumed
+    ///
+    /// Progress is recorded every minute and when the validation is interrupted. The file is
+    /// removed when the validation completes.
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["object_ids", "resume"])]
+    pub checkpoint: Option<PathBuf>,
+
+    /// Resume an interrupted validation from the progress recorded in FILE by '--checkpoint'
+    ///
+    /// Objects that were validated before the interruption are skipped, and their results are
+    /// included in the summary. Progress continues to be recorded in FILE. The same options
+    /// that the validation was started with should be used.
+    #[arg(long, value_name = "FILE", conflicts_with = "object_ids")]
+    pub resume: Option<PathBuf>,
+
+    /// Validate the inventory file at FILE, or stdin if '-', instead of objects in a repository
+    ///
+    /// The inventory is parsed and validated against the OCFL spec, but the files it references
+    /// are not. A repository is not required.
+    #[arg(
+        long,
+        value_name = "FILE",
+        conflicts_with_all = [
+            "object_ids", "paths", "prefix", "id_glob", "json", "stale_than", "checkpoint", "resume"
+        ]
+    )]
+    pub inventory: Option<PathBuf>,
+
+    /// The digest the inventory is expected to have, eg. sha512:<hex>, as recorded in its sidecar
+    #[arg(long, value_name = "ALG:DIGEST", requires = "inventory")]
+    pub digest: Option<QualifiedDigest>,
+
     /// IDs of the objects to validate, or paths object roots when used with '--paths'
     #[arg(value_name = "OBJ_ID/PATH")]
     pub object_ids: Vec<String>,
 }
 
+/// Run quick structural health checks over the repository
+///
+/// Unlike 'validate', objects are not validated against the OCFL spec and no content is fixity
+/// checked, so this is much faster. The checks are: the storage root has a valid namaste file
+/// and a consistent storage layout, every object can be read, object roots do not contain
+/// version directories that were left behind by interrupted commits, root inventories have
+/// sidecars, staged versions follow their object's HEAD version, and no lock files have been
+/// left behind.
+///
+/// Each problem is printed with a suggested fix. Return code 1 is returned if any problems were
+/// found.
+#[derive(Args, Debug)]
+pub struct DoctorCmd {
+    /// Report lock files that are older than the age, eg. 30m
+    ///
+    /// The age is a number followed by a unit: s, m, h, d, or w.
+    #[arg(long, value_name = "AGE", default_value = "1h")]
+    pub lock_age: Age,
+}
+
+/// Verify the signatures of object inventories
+///
+/// When the 'signing_key' config property is set, the inventory of every version that rocfl
+/// commits is signed with the key, and the signature is recorded in the object's
+/// logs/rocfl-signatures.jsonl. This command verifies the signature of every version of the
+/// specified objects, or of every object in the repository, against the trusted public keys.
+///
+/// Each version is reported as valid, unsigned, untrusted when it was signed by a key that is
+/// not trusted, or invalid when its inventory does not match its signature. Return code 1 is
+/// returned if any version is untrusted or invalid.
+#[derive(Args, Debug)]
+pub struct VerifySignaturesCmd {
+    /// Path to a PEM encoded ed25519 public key that is trusted
+    ///
+    /// This may be specified multiple times. The public key of the configured 'signing_key' is
+    /// trusted when no keys are specified.
+    #[arg(short, long = "key", value_name = "FILE")]
+    pub keys: Vec<PathBuf>,
+
+    /// IDs of the objects to verify. Every object is verified when none are specified.
+    #[arg(value_name = "OBJ_ID")]
+    pub object_ids: Vec<String>,
+}
+
 /// Display OCFL metadata about a repository or object
 ///
 /// This command displays information, such as OCFL spec version and configured extensions, for
@@ -698,11 +2014,305 @@ pub struct InfoCmd {
     pub object_id: Option<String>,
 }
 
+/// Adopt an existing object into the repository's object index
+///
+/// Objects that do not reside at the location dictated by the repository's storage layout, or
+/// that are in repositories without a storage layout, can be adopted so that they are located by
+/// ID without scanning the repository. Adopted objects may then be updated, purged, and validated
+/// in the same way as any other object.
+#[derive(Args, Debug)]
+pub struct AdoptCmd {
+    /// Storage root relative path to the object's root
+    #[arg(value_name = "OBJ_ROOT")]
+    pub object_root: String,
+}
+
+/// Freeze an object so that it cannot be modified
+///
+/// Frozen objects cannot be committed to, upgraded, or purged unless '--force' is specified.
+/// This is useful for protecting objects that are under legal hold, or that have been
+/// published, from accidental changes. The freeze marker is written to the object's
+/// rocfl-frozen extension, and is displayed by 'info'. Use 'ls --frozen' to list frozen objects.
+#[derive(Args, Debug)]
+pub struct FreezeCmd {
+    /// Why the object is being frozen
+    #[arg(short, long, value_name = "REASON")]
+    pub reason: Option<String>,
+
+    /// ID of the object to freeze
+    #[arg(value_name = "OBJ_ID")]
+    pub object_id: String,
+}
+
+/// Unfreeze a frozen object so that it can be modified again
+#[derive(Args, Debug)]
+pub struct UnfreezeCmd {
+    /// ID of the object to unfreeze
+    #[arg(value_name = "OBJ_ID")]
+    pub object_id: String,
+}
+
+/// Manage object aliases
+///
+/// Aliases are secondary identifiers, such as DOIs, handles, or local IDs, that may be used in
+/// place of an object's ID when reading the object. They are stored in the repository's alias
+/// index, and an alias may only identify a single object.
+#[derive(Args, Debug)]
+pub struct AliasCmd {
+    /// Alias operation to execute
+    #[command(subcommand)]
+    pub command: AliasCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum AliasCommand {
+    /// Add an alias for an object
+    #[command(name = "add")]
+    Add(AliasAddArgs),
+    /// Remove an alias
+    #[command(name = "rm")]
+    Remove(AliasRemoveArgs),
+    /// List aliases
+    #[command(name = "list")]
+    List(AliasListArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct AliasAddArgs {
+    /// ID of the object to add the alias to
+    #[arg(value_name = "OBJ_ID")]
+    pub object_id: String,
+
+    /// Alias to add
+    #[arg(value_name = "ALIAS")]
+    pub alias: String,
+}
+
+#[derive(Args, Debug)]
+pub struct AliasRemoveArgs {
+    /// Alias to remove
+    #[arg(value_name = "ALIAS")]
+    pub alias: String,
+}
+
+#[derive(Args, Debug)]
+pub struct AliasListArgs {
+    /// Display a header row
+    #[arg(short = 'H', long)]
+    pub header: bool,
+
+    /// Tab separate the output
+    #[arg(short, long)]
+    pub tsv: bool,
+
+    /// ID of the object to list aliases for. By default, all aliases are listed.
+    #[arg(value_name = "OBJ_ID")]
+    pub object_id: Option<String>,
+}
+
+/// Inspect and verify the repository's storage layout
+///
+/// The storage layout is declared in the repository's 'ocfl_layout.json' file, and configured
+/// by the matching extension's 'config.json' file. When these files disagree, objects may be
+/// written to unexpected locations, so 'verify' should be run after editing either of them.
+#[derive(Args, Debug)]
+pub struct LayoutCmd {
+    /// Layout operation to execute
+    #[command(subcommand)]
+    pub command: LayoutCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum LayoutCommand {
+    /// Display the declared storage layout and its effective configuration
+    #[command(name = "show")]
+    Show,
+    /// Report discrepancies between 'ocfl_layout.json' and the layout extension configs
+    ///
+    /// The command exits with a non-zero status when any discrepancies are found.
+    #[command(name = "verify")]
+    Verify,
+    /// Rewrite the description in 'ocfl_layout.json'
+    #[command(name = "set-description")]
+    SetDescription(LayoutSetDescriptionArgs),
+    /// Print the storage path an object ID maps to using each supported layout
+    ///
+    /// Every layout is mapped using its default configuration. The omit prefix layouts do not
+    /// have a default delimiter, so they are only mapped when '--delimiter' is specified. This
+    /// is useful for checking where objects created by other OCFL implementations are expected
+    /// to be stored. A repository is not required.
+    #[command(name = "map")]
+    Map(LayoutMapArgs),
+    /// Record the location of every object in the repository's object index
+    ///
+    /// While the registry is enabled, objects are located and listed using the index, which is
+    /// updated when objects are created or purged, rather than by scanning the storage root. This
+    /// is useful for repositories that do not have a storage layout. Enabling the registry scans
+    /// the repository once to index its existing objects.
+    #[command(name = "registry")]
+    Registry(LayoutRegistryArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct LayoutSetDescriptionArgs {
+    /// The new description
+    #[arg(value_name = "DESCRIPTION")]
+    pub description: String,
+}
+
+#[derive(Args, Debug)]
+pub struct LayoutRegistryArgs {
+    /// Disable the registry, so that objects are located by scanning when they are not where the
+    /// storage layout expects them
+    #[arg(short, long)]
+    pub disable: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct LayoutMapArgs {
+    /// The delimiter to use with the omit prefix layouts
+    #[arg(short, long, value_name = "DELIMITER")]
+    pub delimiter: Option<String>,
+
+    /// ID of the object to map
+    #[arg(value_name = "OBJ_ID")]
+    pub object_id: String,
+}
+
+/// Generate a shell completion script
+///
+/// The script is written to stdout, and should be saved wherever your shell loads completions
+/// from. For example, 'rocfl completions bash > ~/.local/share/bash-completion/completions/rocfl'.
+///
+/// In addition to commands and options, the scripts complete object IDs by calling
+/// 'rocfl completions --object-ids PREFIX', which lists the IDs of the objects in the configured
+/// repository that start with PREFIX.
+#[derive(Args, Debug)]
+pub struct CompletionsCmd {
+    /// Shell to generate the completion script for
+    #[arg(
+        value_enum,
+        value_name = "SHELL",
+        required_unless_present = "object_ids"
+    )]
+    pub shell: Option<CompletionShell>,
+
+    /// List the IDs of objects that start with PREFIX instead of generating a script
+    #[arg(
+        long,
+        value_name = "PREFIX",
+        num_args = 0..=1,
+        default_missing_value = "",
+        conflicts_with = "shell"
+    )]
+    pub object_ids: Option<String>,
+
+    /// Maximum number of object IDs to list
+    #[arg(
+        long,
+        value_name = "LIMIT",
+        default_value_t = 100,
+        conflicts_with = "shell"
+    )]
+    pub limit: usize,
+}
+
+/// Manage an object's descriptive metadata
+///
+/// Simple key/value metadata is stored as a JSON object in a file within the object's content,
+/// so that it is versioned alongside the rest of the object. By default, the file is at the
+/// logical path 'metadata/rocfl.json'. A different path may be set with the metadata_path
+/// config property or '--path'.
+#[derive(Args, Debug)]
+pub struct MetaCmd {
+    /// Logical path of the metadata file
+    #[arg(short, long, value_name = "PATH", global = true)]
+    pub path: Option<String>,
+
+    /// Metadata operation to execute
+    #[command(subcommand)]
+    pub command: MetaCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum MetaCommand {
+    /// Print an object's metadata
+    #[command(name = "get")]
+    Get(MetaGetArgs),
+    /// Set or remove metadata entries and commit the change as a new version
+    #[command(name = "set")]
+    Set(MetaSetArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct MetaGetArgs {
+    /// Print the metadata in the object's staged version
+    #[arg(short = 'S', long, conflicts_with = "version")]
+    pub staged: bool,
+
+    /// The version of the object to retrieve the metadata from
+    #[arg(short, long, value_name = "VERSION")]
+    pub version: Option<VersionNum>,
+
+    /// ID of the object
+    #[arg(value_name = "OBJ_ID")]
+    pub object_id: String,
+
+    /// Key of the entry to print. By default, all entries are printed as KEY=VALUE lines.
+    #[arg(value_name = "KEY")]
+    pub key: Option<String>,
+}
+
+#[derive(Args, Debug)]
+pub struct MetaSetArgs {
+    /// Key of an entry to remove. May be specified multiple times.
+    #[arg(short, long, value_name = "KEY")]
+    pub unset: Vec<String>,
+
+    /// Stage the change without committing it. Otherwise, the change is committed along with
+    /// any other changes that are staged for the object.
+    #[arg(long)]
+    pub no_commit: bool,
+
+    /// Message describing the changes
+    #[arg(short, long, value_name = "MESSAGE")]
+    pub message: Option<String>,
+
+    /// ID of the object
+    #[arg(value_name = "OBJ_ID")]
+    pub object_id: String,
+
+    /// Entries to set, formatted as KEY=VALUE
+    #[arg(value_name = "KEY=VALUE")]
+    pub entries: Vec<String>,
+}
+
 // TODO a command for rebasing staging if an object is updated after the staged version was created?
 
 #[derive(Debug, Copy, Clone)]
 pub struct Num(pub usize);
 
+#[derive(Debug, Copy, Clone)]
+pub struct Percent(pub u8);
+
+/// A length of time, such as 90d
+#[derive(Debug, Copy, Clone)]
+pub struct Age(pub Duration);
+
+/// A digest that is qualified with its algorithm, such as sha512:<hex>
+#[derive(Debug, Clone)]
+pub struct QualifiedDigest {
+    pub algorithm: OcflDigestAlgorithm,
+    pub digest: HexDigest,
+}
+
+/// Either a version number, or a logical path that is optionally qualified with a version
+#[derive(Debug, Clone)]
+pub enum VersionOrPath {
+    Version(VersionNum),
+    Path(PathRef),
+}
+
 #[derive(ValueEnum, Debug, Clone, Copy, Eq, PartialEq)]
 pub enum Field {
     Default,
@@ -736,6 +2346,53 @@ pub enum Layout {
     NTupleOmitPrefix,
 }
 
+#[derive(ValueEnum, Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ListFormat {
+    Table,
+    Tsv,
+    Ndjson,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Timestamps {
+    Updated,
+    Original,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ManifestFormat {
+    Checksum,
+    Csv,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Renames {
+    All,
+    OneToOne,
+    Off,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Symlinks {
+    Follow,
+    Skip,
+    Error,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, Eq, PartialEq)]
+pub enum CompletionShell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, Eq, PartialEq, EnumDisplay)]
+#[strum(serialize_all = "lowercase")]
+pub enum LogFormat {
+    Text,
+    Json,
+}
+
 #[derive(ValueEnum, Debug, Clone, Copy)]
 pub enum DigestAlgorithm {
     Sha256,
@@ -773,8 +2430,113 @@ impl FromStr for Num {
     }
 }
 
+impl FromStr for Percent {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let value = s.strip_suffix('%').unwrap_or(s);
+        match u8::from_str(value) {
+            Ok(percent) if (1..=100).contains(&percent) => Ok(Percent(percent)),
+            _ => Err(format!(
+                "Invalid percentage '{}'. Must be between 1% and 100%",
+                s
+            )),
+        }
+    }
+}
+
+impl FromStr for Age {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            format!(
+                "Invalid age '{}'. Must be a number followed by s, m, h, d, or w, eg. 90d",
+                s
+            )
+        };
+
+        let (value, unit) = match s.char_indices().last() {
+            Some((index, _)) => s.split_at(index),
+            None => return Err(invalid()),
+        };
+
+        let seconds = match unit {
+            "s" => 1,
+            "m" => 60,
+            "h" => 60 * 60,
+            "d" => 24 * 60 * 60,
+            "w" => 7 * 24 * 60 * 60,
+            _ => return Err(invalid()),
+        };
+
+        u64::from_str(value)
+            .ok()
+            .and_then(|value| value.checked_mul(seconds))
+            .map(|seconds| Age(Duration::from_secs(seconds)))
+            .ok_or_else(invalid)
+    }
+}
+
+impl FromStr for QualifiedDigest {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            format!(
+                "Invalid digest '{}'. Must be an algorithm followed by a hex digest, eg. sha512:<hex>",
+                s
+            )
+        };
+
+        let (algorithm, digest) = s.split_once(':').ok_or_else(invalid)?;
+
+        if digest.is_empty() || !digest.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(invalid());
+        }
+
+        Ok(QualifiedDigest {
+            algorithm: OcflDigestAlgorithm::from_str(algorithm).map_err(|_| invalid())?,
+            digest: HexDigest::from(digest),
+        })
+    }
+}
+
+impl FromStr for VersionOrPath {
+    type Err = RocflError;
+
+    /// Values that are valid version numbers are interpreted as versions. A path that is also a
+    /// valid version number must be written with a trailing `@`, for example `v1@`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match VersionNum::from_str(s) {
+            Ok(version) => Ok(VersionOrPath::Version(version)),
+            Err(_) => Ok(VersionOrPath::Path(PathRef::from_str(s)?)),
+        }
+    }
+}
+
 impl Display for Num {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.0)
     }
 }
+
+/// Parses a date, such as '2020-12-23', as midnight local time, or an RFC 3339 timestamp
+fn parse_date(value: &str) -> Result<DateTime<Local>, RocflError> {
+    let midnight = NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .and_then(|date| Local.from_local_datetime(&date).earliest());
+
+    match midnight {
+        Some(date) => Ok(date),
+        None => DateTime::parse_from_rfc3339(value)
+            .map(|date| date.with_timezone(&Local))
+            .map_err(|_| {
+                RocflError::InvalidValue(format!(
+                    "Invalid date {}. Expected a date, such as 2020-12-23, or an RFC 3339 timestamp",
+                    value
+                ))
+            }),
+    }
+}
@@ -3,7 +3,7 @@ use std::fmt::{self, Display, Formatter};
 use std::io::{Result, Write};
 
 use ansi_term::Style;
-use unicode_width::UnicodeWidthStr;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
 use crate::cmd::style;
 
@@ -15,14 +15,25 @@ pub trait AsRow<'a> {
 pub enum ColumnId {
     Version,
     Created,
+    Original,
     ObjectId,
     LogicalPath,
     PhysicalPath,
+    ContentPath,
+    Origin,
     Digest,
     Author,
     Address,
     Message,
     Operation,
+    Files,
+    Bytes,
+    Alias,
+    Added,
+    Modified,
+    Deleted,
+    Renamed,
+    Difference,
 }
 
 #[derive(Debug, Clone, Copy, Ord, PartialOrd, Eq, PartialEq)]
@@ -42,6 +53,7 @@ pub struct Column {
     heading: String,
     alignment: Alignment,
     width: usize,
+    max_width: Option<usize>,
 }
 
 pub struct Row<'a> {
@@ -51,6 +63,8 @@ pub struct Row<'a> {
 pub struct TextCell<'a> {
     value: Box<dyn AsRef<str> + 'a>,
     width: usize,
+    /// The byte offset the value is cut off at when it is wider than its column allows
+    end: Option<usize>,
     style: &'static Style,
 }
 
@@ -85,8 +99,11 @@ impl<'a> TableView<'a> {
     }
 
     pub fn add_row(&mut self, row: &'a impl AsRow<'a>) {
-        let row = row.as_row(&self.columns);
-        for (column, cell) in self.columns.iter_mut().zip(&row.cells) {
+        let mut row = row.as_row(&self.columns);
+        for (column, cell) in self.columns.iter_mut().zip(&mut row.cells) {
+            if let Some(max_width) = column.max_width {
+                cell.truncate(max_width);
+            }
             column.update_width(cell.width());
         }
         self.rows.push(row);
@@ -139,14 +156,21 @@ impl Column {
             heading: heading.to_owned(),
             alignment,
             width: 0,
+            max_width: None,
         }
     }
 
+    /// Cuts off every value in the column that is wider than `max_width`
+    pub fn with_max_width(mut self, max_width: usize) -> Self {
+        self.max_width = Some(max_width);
+        self
+    }
+
     fn update_width(&mut self, new_width: usize) {
         self.width = cmp::max(self.width, new_width);
     }
 
-    fn heading_cell(&self) -> TextCell {
+    fn heading_cell(&self) -> TextCell<'_> {
         let mut cell = TextCell::new(&self.heading);
         cell.style = &*style::UNDERLINE;
         cell
@@ -193,6 +217,7 @@ impl<'a> TextCell<'a> {
         Self {
             width: UnicodeWidthStr::width(value.as_ref()),
             value: Box::new(value),
+            end: None,
             style: &*style::DEFAULT,
         }
     }
@@ -210,6 +235,27 @@ impl<'a> TextCell<'a> {
         self.width
     }
 
+    fn truncate(&mut self, max_width: usize) {
+        if self.width <= max_width {
+            return;
+        }
+
+        let mut width = 0;
+        let mut end = 0;
+
+        for (i, c) in (*self.value).as_ref().char_indices() {
+            let char_width = UnicodeWidthChar::width(c).unwrap_or(0);
+            if width + char_width > max_width {
+                break;
+            }
+            width += char_width;
+            end = i + c.len_utf8();
+        }
+
+        self.width = width;
+        self.end = Some(end);
+    }
+
     fn write(
         &self,
         writer: &mut impl Write,
@@ -229,7 +275,10 @@ impl<'a> TextCell<'a> {
             &*style::DEFAULT
         };
 
-        let value = (*self.value).as_ref();
+        let value = match self.end {
+            Some(end) => &(*self.value).as_ref()[..end],
+            None => (*self.value).as_ref(),
+        };
 
         match alignment {
             Alignment::Left => write!(writer, "{}{}", style.paint(value), spaces),
@@ -0,0 +1,160 @@
+use std::io::{self, BufWriter, Write};
+use std::sync::atomic::AtomicBool;
+
+use crate::cmd::opts::TreeCmd;
+use crate::cmd::{paint, style, Cmd, GlobalArgs};
+use crate::config::Config;
+use crate::ocfl::{OcflRepo, Result, RocflError, StateTree};
+
+const BRANCH: &str = "├── ";
+const LAST_BRANCH: &str = "└── ";
+const INDENT: &str = "│   ";
+const LAST_INDENT: &str = "    ";
+
+impl Cmd for TreeCmd {
+    fn exec(
+        &self,
+        repo: &OcflRepo,
+        args: GlobalArgs,
+        _config: &Config,
+        _terminate: &AtomicBool,
+    ) -> Result<()> {
+        let tree = repo.get_object_tree(&self.object_id, self.version.into(), self.size)?;
+
+        let root = match &self.path {
+            Some(path) => tree.find(path).ok_or_else(|| {
+                RocflError::NotFound(format!("Path {} in object {}", path, self.object_id))
+            })?,
+            None => &tree,
+        };
+
+        let mut out = BufWriter::new(io::stdout());
+        let mut counts = Counts::default();
+
+        let root_name = if root.path.is_empty() {
+            self.object_id.as_str()
+        } else {
+            root.path.as_str()
+        };
+
+        let _ = writeln!(
+            out,
+            "{}{}",
+            self.attributes(root),
+            paint(args.no_styles, *style::BOLD, root_name)
+        );
+
+        if root.is_dir() {
+            self.write_children(&mut out, root, "", 1, args.no_styles, &mut counts);
+        }
+
+        let _ = writeln!(out);
+        let _ = write!(out, "{} {}", counts.dirs, plural(counts.dirs, "directory"));
+        if !self.dirs_only {
+            let _ = write!(out, ", {} {}", counts.files, plural(counts.files, "file"));
+        }
+        let _ = writeln!(out);
+
+        let _ = out.flush();
+
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+struct Counts {
+    dirs: usize,
+    files: usize,
+}
+
+impl TreeCmd {
+    fn write_children(
+        &self,
+        out: &mut impl Write,
+        node: &StateTree,
+        prefix: &str,
+        depth: usize,
+        no_styles: bool,
+        counts: &mut Counts,
+    ) {
+        if self.depth.map(|max| depth > max).unwrap_or(false) {
+            return;
+        }
+
+        let mut children: Vec<&StateTree> = node
+            .children
+            .iter()
+            .filter(|child| !self.dirs_only || child.is_dir())
+            .collect();
+        children.sort_by(|a, b| natord::compare(&a.name, &b.name));
+
+        let last_index = children.len().saturating_sub(1);
+
+        for (i, child) in children.into_iter().enumerate() {
+            let (branch, indent) = if i == last_index {
+                (LAST_BRANCH, LAST_INDENT)
+            } else {
+                (BRANCH, INDENT)
+            };
+
+            if child.is_dir() {
+                counts.dirs += 1;
+                let _ = writeln!(
+                    out,
+                    "{}{}{}{}",
+                    prefix,
+                    branch,
+                    self.attributes(child),
+                    paint(no_styles, *style::BOLD, child.name.as_str())
+                );
+                self.write_children(
+                    out,
+                    child,
+                    &format!("{}{}", prefix, indent),
+                    depth + 1,
+                    no_styles,
+                    counts,
+                );
+            } else {
+                counts.files += 1;
+                let _ = writeln!(
+                    out,
+                    "{}{}{}{}",
+                    prefix,
+                    branch,
+                    self.attributes(child),
+                    child.name
+                );
+            }
+        }
+    }
+
+    /// Formats the requested size and digest of a node as `[SIZE DIGEST] `
+    fn attributes(&self, node: &StateTree) -> String {
+        let mut attributes = Vec::new();
+
+        if self.size {
+            attributes.push(format!("{:>10}", node.size.unwrap_or_default()));
+        }
+
+        if self.digest {
+            if let Some(details) = &node.details {
+                attributes.push(format!("{}:{}", details.digest_algorithm, details.digest));
+            }
+        }
+
+        if attributes.is_empty() {
+            String::new()
+        } else {
+            format!("[{}] ", attributes.join(" "))
+        }
+    }
+}
+
+fn plural(count: usize, noun: &str) -> String {
+    match (count, noun.strip_suffix('y')) {
+        (1, _) => noun.to_string(),
+        (_, Some(stem)) => format!("{}ies", stem),
+        (_, None) => format!("{}s", noun),
+    }
+}
@@ -1,22 +1,54 @@
 use std::borrow::Cow;
+use std::collections::HashSet;
 use std::fmt::{Display, Formatter};
-use std::io::{BufWriter, Write};
-use std::sync::atomic::AtomicBool;
-use std::{io, process};
+use std::fs::{self, File};
+use std::io;
+use std::io::{BufWriter, Read, Write};
+use std::mem;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use ansi_term::{ANSIGenericString, Style};
-use log::error;
+use chrono::Local;
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
 
-use crate::cmd::opts::{Level, ValidateCmd};
-use crate::cmd::{paint, style, Cmd, GlobalArgs};
+use crate::cmd::opts::{InspectInventoryCmd, Level, ValidateCmd};
+use crate::cmd::{paint, raise_exit_status, style, Cmd, GlobalArgs};
 use crate::config::Config;
 use crate::ocfl::{
-    ObjectValidationResult, OcflRepo, ProblemLocation, Result, StorageValidationResult,
-    ValidationResult,
+    parse_inventory, validate_inventory_digest, FixityCheck, FixitySample, Inventory,
+    ObjectValidationResult, OcflRepo, ParseDiagnostics, ProblemLocation, Result, RocflError,
+    SpecReference, StorageValidationResult, ValidationCheckpoint, ValidationFilter,
+    ValidationRecord, ValidationResult,
 };
 
 const UNKNOWN_ID: &str = "Unknown";
 
+/// How often the progress of a repository validation is recorded when '--checkpoint' or
+/// '--resume' is specified
+const CHECKPOINT_INTERVAL: Duration = Duration::from_secs(60);
+
+/// The progress of a repository validation, as recorded in the file specified by '--checkpoint'
+#[derive(Deserialize, Serialize, Debug, Default)]
+#[serde(default, rename_all = "camelCase")]
+struct ValidationState {
+    /// The validator's position and the storage hierarchy results accumulated so far
+    checkpoint: ValidationCheckpoint,
+    /// The number of objects that were validated
+    objects: usize,
+    /// The number of objects that were invalid
+    invalid_objects: usize,
+    /// The number of content files that were fixity checked
+    files_checked: usize,
+    /// Whether any objects could not be validated
+    error_validating: bool,
+    /// The seed that was used to sample the files to fixity check
+    seed: Option<u64>,
+}
+
 impl Cmd for ValidateCmd {
     fn exec(
         &self,
@@ -27,10 +59,31 @@ impl Cmd for ValidateCmd {
     ) -> Result<()> {
         // TODO perhaps use something like https://crates.io/crates/console to update the display
 
+        let state = match &self.resume {
+            Some(state_file) => Some(read_state(state_file)?),
+            None => None,
+        };
+
+        let fixity_check = self.fixity_check(state.as_ref().and_then(|state| state.seed));
+        repo.set_strict_paths(self.strict);
+
+        if self.sample.is_some() && self.seed.is_none() && self.round.is_none() {
+            repo.continue_fixity_samples()?;
+        }
+
+        let recently_validated = self.recently_validated(repo)?;
+
         if !self.object_ids.is_empty() {
-            self.validate_objects(repo, args, terminate)?;
+            self.validate_objects(repo, args, fixity_check, &recently_validated, terminate)?;
         } else {
-            self.validate_repo(repo, args, terminate)?;
+            self.validate_repo(
+                repo,
+                args,
+                fixity_check,
+                recently_validated,
+                state,
+                terminate,
+            )?;
         }
 
         Ok(())
@@ -42,7 +95,9 @@ impl ValidateCmd {
         &self,
         repo: &OcflRepo,
         args: GlobalArgs,
-        _terminate: &AtomicBool,
+        fixity_check: FixityCheck,
+        recently_validated: &HashSet<String>,
+        terminate: &AtomicBool,
     ) -> Result<()> {
         let mut out = BufWriter::new(io::stdout());
         let isatty = atty::is(atty::Stream::Stdout);
@@ -50,53 +105,74 @@ impl ValidateCmd {
         let mut has_printed = false;
         let mut obj_count = 0;
         let mut invalid_count = 0;
+        let mut checked_count = 0;
         let mut error_validating = false;
+        let mut records = Vec::new();
 
-        for object_id in &self.object_ids {
-            let mut result = if self.paths {
-                match repo.validate_object_at(object_id, !self.no_fixity_check) {
-                    Ok(result) => result,
-                    Err(e) => {
-                        error_validating = true;
-                        let _ = out.flush();
-                        error!("{:#}", e);
-                        continue;
-                    }
+        let object_ids: Vec<&String> = self
+            .object_ids
+            .iter()
+            .filter(|object_id| {
+                let recent = recently_validated.contains(object_id.as_str());
+                if recent {
+                    info!(
+                        "Skipping object {} because it was validated recently",
+                        object_id
+                    );
                 }
-            } else {
-                match repo.validate_object(object_id, !self.no_fixity_check) {
-                    Ok(result) => result,
-                    Err(e) => {
-                        error_validating = true;
-                        let _ = out.flush();
-                        error!("{:#}", e);
-                        continue;
+                !recent
+            })
+            .collect();
+
+        // Objects are validated in batches, so that results are printed while later objects are
+        // still being validated
+        let pool = repo.thread_pool();
+        let results = object_ids.chunks(pool.threads()).flat_map(|batch| {
+            pool.map(
+                batch,
+                || terminate.load(Ordering::Acquire),
+                |object_id| {
+                    if self.paths {
+                        repo.validate_object_at(object_id, fixity_check)
+                    } else {
+                        repo.validate_object(object_id, fixity_check)
                     }
+                },
+            )
+        });
+
+        for result in results {
+            let mut result = match result {
+                Some(Ok(result)) => result,
+                Some(Err(e)) => {
+                    error_validating = true;
+                    let _ = out.flush();
+                    error!("{:#}", e);
+                    continue;
                 }
+                None => break,
             };
 
             self.suppress_errors_warnings(&mut result);
 
             obj_count += 1;
+            checked_count += result.fixity_checked().len();
             if result.has_errors() {
                 invalid_count += 1;
             }
+            records.extend(ValidationRecord::from_result(&result));
 
             if self.should_print(&result) {
-                if has_printed {
-                    let _ = writeln!(out);
-                } else {
-                    has_printed = true;
-                }
-
-                let _ = write!(
-                    out,
-                    "{}",
+                self.write_result(
+                    &mut out,
+                    &mut has_printed,
                     DisplayObjectValidationResult {
                         result: &result,
                         no_styles: args.no_styles,
+                        verbose: args.verbose,
                         level: self.level,
-                    }
+                    },
+                    || object_result_json(&result, self.level),
                 );
 
                 if isatty {
@@ -105,22 +181,35 @@ impl ValidateCmd {
             }
         }
 
-        if self.object_ids.len() > 1 {
-            if has_printed {
-                let _ = writeln!(out);
-            }
+        if self.object_ids.len() > 1 || self.sample.is_some() {
+            if self.json {
+                let _ = writeln!(
+                    out,
+                    "{}",
+                    summary_json(obj_count, invalid_count, None, fixity_check, checked_count)
+                );
+            } else {
+                if has_printed {
+                    let _ = writeln!(out);
+                }
 
-            let _ = writeln!(out, "{}", paint(args.no_styles, *style::BOLD, "Summary:"));
-            let _ = writeln!(out, "  Total objects:   {}", obj_count);
-            let _ = writeln!(out, "  Invalid objects: {}", invalid_count);
+                let _ = writeln!(out, "{}", paint(args.no_styles, *style::BOLD, "Summary:"));
+                let _ = writeln!(out, "  Total objects:   {}", obj_count);
+                let _ = writeln!(out, "  Invalid objects: {}", invalid_count);
+                write_sample_summary(&mut out, fixity_check, checked_count);
+            }
         }
 
         let _ = out.flush();
 
+        if !record_validations(repo, &records) {
+            error_validating = true;
+        }
+
         if invalid_count > 0 {
-            process::exit(2);
+            raise_exit_status(2);
         } else if error_validating {
-            process::exit(1);
+            raise_exit_status(1);
         }
 
         Ok(())
@@ -130,58 +219,82 @@ impl ValidateCmd {
         &self,
         repo: &OcflRepo,
         args: GlobalArgs,
-        _terminate: &AtomicBool,
+        fixity_check: FixityCheck,
+        recently_validated: HashSet<String>,
+        state: Option<ValidationState>,
+        terminate: &AtomicBool,
     ) -> Result<()> {
-        let mut validator = repo.validate_repo(!self.no_fixity_check)?;
+        let mut filter = ValidationFilter::new().with_excluded_ids(recently_validated);
+        if let Some(prefix) = &self.prefix {
+            filter = filter.with_prefix(prefix);
+        }
+        if let Some(glob) = &self.id_glob {
+            filter = filter.with_id_glob(glob)?;
+        }
+
+        let mut validator = repo.validate_repo_filtered(fixity_check, filter)?;
         let mut out = BufWriter::new(io::stdout());
         let isatty = atty::is(atty::Stream::Stdout);
 
-        let mut obj_count = 0;
-        let mut invalid_count = 0;
         let mut has_printed = false;
-        let mut error_validating = false;
+        let mut records = Vec::new();
+
+        let state_file = self.resume.as_deref().or(self.checkpoint.as_deref());
+        let mut last_checkpoint = Instant::now();
+        let mut progress = match state {
+            Some(mut state) => {
+                validator.resume(mem::take(&mut state.checkpoint));
+                state
+            }
+            None => ValidationState {
+                seed: match fixity_check {
+                    FixityCheck::Sample(sample) => Some(sample.seed),
+                    _ => None,
+                },
+                ..Default::default()
+            },
+        };
 
         self.suppress_errors_warnings(validator.storage_hierarchy_result_mut());
 
         if self.should_print(validator.storage_root_result()) {
-            has_printed = true;
-            let _ = write!(
-                out,
-                "{}",
+            self.write_result(
+                &mut out,
+                &mut has_printed,
                 DisplayStorageValidationResult {
                     result: validator.storage_root_result(),
                     location: "root",
                     no_styles: args.no_styles,
+                    verbose: args.verbose,
                     level: self.level,
-                }
+                },
+                || storage_result_json(validator.storage_root_result(), "root", self.level),
             );
         }
 
-        for result in &mut validator {
+        while let Some(result) = validator.next() {
             match result {
                 Ok(mut result) => {
                     self.suppress_errors_warnings(&mut result);
 
-                    obj_count += 1;
+                    progress.objects += 1;
+                    progress.files_checked += result.fixity_checked().len();
                     if result.has_errors() {
-                        invalid_count += 1;
+                        progress.invalid_objects += 1;
                     }
+                    records.extend(ValidationRecord::from_result(&result));
 
                     if self.should_print(&result) {
-                        if has_printed {
-                            let _ = writeln!(out);
-                        } else {
-                            has_printed = true;
-                        }
-
-                        let _ = write!(
-                            out,
-                            "{}",
+                        self.write_result(
+                            &mut out,
+                            &mut has_printed,
                             DisplayObjectValidationResult {
                                 result: &result,
                                 no_styles: args.no_styles,
+                                verbose: args.verbose,
                                 level: self.level,
-                            }
+                            },
+                            || object_result_json(&result, self.level),
                         );
 
                         if isatty {
@@ -190,58 +303,172 @@ impl ValidateCmd {
                     }
                 }
                 Err(e) => {
-                    error_validating = true;
+                    progress.error_validating = true;
                     let _ = out.flush();
                     error!("{:#}", e);
-                    continue;
+                }
+            }
+
+            if let Some(state_file) = state_file {
+                if last_checkpoint.elapsed() >= CHECKPOINT_INTERVAL {
+                    progress.checkpoint = validator.checkpoint();
+                    write_state(state_file, &progress);
+                    last_checkpoint = Instant::now();
                 }
             }
         }
 
-        self.suppress_errors_warnings(validator.storage_hierarchy_result_mut());
+        if let (Some(state_file), true) = (state_file, terminate.load(Ordering::Acquire)) {
+            let _ = out.flush();
 
-        if self.should_print(validator.storage_hierarchy_result()) {
-            if has_printed {
-                let _ = writeln!(out);
-            } else {
-                has_printed = true;
+            progress.checkpoint = validator.checkpoint();
+            if write_state(state_file, &progress) {
+                warn!(
+                    "Validation interrupted. Resume it with '--resume {}'",
+                    state_file.to_string_lossy()
+                );
             }
 
-            let _ = write!(
-                out,
-                "{}",
+            record_validations(repo, &records);
+            raise_exit_status(1);
+            return Ok(());
+        }
+
+        self.suppress_errors_warnings(validator.storage_hierarchy_result_mut());
+
+        if self.should_print(validator.storage_hierarchy_result()) {
+            self.write_result(
+                &mut out,
+                &mut has_printed,
                 DisplayStorageValidationResult {
                     result: validator.storage_hierarchy_result(),
                     location: "hierarchy",
                     no_styles: args.no_styles,
+                    verbose: args.verbose,
                     level: self.level,
-                }
+                },
+                || {
+                    storage_result_json(
+                        validator.storage_hierarchy_result(),
+                        "hierarchy",
+                        self.level,
+                    )
+                },
             );
         }
 
         let storage_errors = validator.storage_root_result().errors().len()
             + validator.storage_hierarchy_result().errors().len();
 
-        if has_printed {
-            let _ = writeln!(out);
-        }
+        if self.json {
+            let _ = writeln!(
+                out,
+                "{}",
+                summary_json(
+                    progress.objects,
+                    progress.invalid_objects,
+                    Some(storage_errors),
+                    fixity_check,
+                    progress.files_checked
+                )
+            );
+        } else {
+            if has_printed {
+                let _ = writeln!(out);
+            }
 
-        let _ = writeln!(out, "{}", paint(args.no_styles, *style::BOLD, "Summary:"));
-        let _ = writeln!(out, "  Total objects:   {}", obj_count);
-        let _ = writeln!(out, "  Invalid objects: {}", invalid_count);
-        let _ = writeln!(out, "  Storage issues:  {}", storage_errors);
+            let _ = writeln!(out, "{}", paint(args.no_styles, *style::BOLD, "Summary:"));
+            let _ = writeln!(out, "  Total objects:   {}", progress.objects);
+            let _ = writeln!(out, "  Invalid objects: {}", progress.invalid_objects);
+            let _ = writeln!(out, "  Storage issues:  {}", storage_errors);
+            write_sample_summary(&mut out, fixity_check, progress.files_checked);
+        }
 
         let _ = out.flush();
 
-        if invalid_count > 0 || storage_errors > 0 {
-            process::exit(2);
-        } else if error_validating {
-            process::exit(1);
+        if !record_validations(repo, &records) {
+            progress.error_validating = true;
+        }
+
+        if let Some(state_file) = state_file.filter(|file| file.exists()) {
+            if let Err(e) = fs::remove_file(state_file) {
+                error!(
+                    "Failed to remove validation state file {}: {:#}",
+                    state_file.to_string_lossy(),
+                    e
+                );
+            }
+        }
+
+        if progress.invalid_objects > 0 || storage_errors > 0 {
+            raise_exit_status(2);
+        } else if progress.error_validating {
+            raise_exit_status(1);
         }
 
         Ok(())
     }
 
+    /// Returns the IDs of the objects that were validated within the `--stale-than` age, according
+    /// to the repository's validation history
+    fn recently_validated(&self, repo: &OcflRepo) -> Result<HashSet<String>> {
+        let age = match self.stale_than {
+            Some(age) => age,
+            None => return Ok(HashSet::new()),
+        };
+
+        // ages that reach before the earliest representable date include every record
+        let cutoff = chrono::Duration::from_std(age.0)
+            .ok()
+            .and_then(|age| Local::now().checked_sub_signed(age));
+
+        Ok(repo
+            .validation_history()?
+            .into_iter()
+            .filter(|record| cutoff.is_none_or(|cutoff| record.timestamp > cutoff))
+            .map(|record| record.object_id)
+            .collect())
+    }
+
+    /// The fixity check to perform. `resumed_seed` is the sampling seed of the validation that
+    /// is being resumed, which is used if a seed is not specified.
+    fn fixity_check(&self, resumed_seed: Option<u64>) -> FixityCheck {
+        if self.no_fixity_check {
+            FixityCheck::None
+        } else if let Some(percent) = self.sample {
+            FixityCheck::Sample(FixitySample {
+                percent: percent.0,
+                seed: self.seed.or(resumed_seed).unwrap_or_else(random_seed),
+                round: self.round.unwrap_or(0),
+                continue_recorded: self.seed.is_none() && self.round.is_none(),
+            })
+        } else {
+            FixityCheck::All
+        }
+    }
+
+    /// Writes a validation result as text, separated from the previous result by a blank line,
+    /// or as a single line of JSON when `--json` is specified
+    fn write_result(
+        &self,
+        out: &mut impl Write,
+        has_printed: &mut bool,
+        text: impl Display,
+        json: impl FnOnce() -> Value,
+    ) {
+        if self.json {
+            let _ = writeln!(out, "{}", json());
+        } else {
+            if *has_printed {
+                let _ = writeln!(out);
+            } else {
+                *has_printed = true;
+            }
+
+            let _ = write!(out, "{}", text);
+        }
+    }
+
     fn should_print<T: ValidationResult>(&self, result: &T) -> bool {
         result.has_errors()
             || (result.has_warnings() && self.level != Level::Error)
@@ -260,6 +487,60 @@ impl ValidateCmd {
     }
 }
 
+/// Reads the progress of an interrupted validation from the state file
+fn read_state(state_file: &Path) -> Result<ValidationState> {
+    let bytes = fs::read(state_file).map_err(|e| {
+        RocflError::General(format!(
+            "Failed to read validation state file {}: {}",
+            state_file.to_string_lossy(),
+            e
+        ))
+    })?;
+    Ok(serde_json::from_slice(&bytes)?)
+}
+
+/// Writes the progress of the validation to the state file, replacing it atomically so that an
+/// interruption while writing does not lose the previous checkpoint. Returns false if it could
+/// not be written.
+fn write_state(state_file: &Path, state: &ValidationState) -> bool {
+    let part = state_file.with_extension("part");
+
+    let result = serde_json::to_vec(state)
+        .map_err(RocflError::from)
+        .and_then(|bytes| Ok(fs::write(&part, bytes)?))
+        .and_then(|_| Ok(fs::rename(&part, state_file)?));
+
+    match result {
+        Ok(()) => {
+            info!(
+                "Recorded validation progress in {}",
+                state_file.to_string_lossy()
+            );
+            true
+        }
+        Err(e) => {
+            error!(
+                "Failed to record validation progress in {}: {:#}",
+                state_file.to_string_lossy(),
+                e
+            );
+            false
+        }
+    }
+}
+
+/// Records the validations in the repository's validation history, if it is enabled. Returns false
+/// if they could not be recorded.
+fn record_validations(repo: &OcflRepo, records: &[ValidationRecord]) -> bool {
+    match repo.record_validations(records) {
+        Ok(()) => true,
+        Err(e) => {
+            error!("Failed to record validation history: {:#}", e);
+            false
+        }
+    }
+}
+
 trait Painter {
     fn no_styles(&self) -> bool;
 
@@ -284,6 +565,7 @@ struct DisplayStorageValidationResult<'a> {
     result: &'a StorageValidationResult,
     location: &'a str,
     no_styles: bool,
+    verbose: bool,
     level: Level,
 }
 
@@ -329,6 +611,9 @@ impl<'a> Display for DisplayStorageValidationResult<'a> {
                     error.text,
                     width = error_width
                 )?;
+                if self.verbose {
+                    write_spec_reference(f, error.code.spec_reference(), error_width)?;
+                }
             }
 
             if self.level != Level::Error {
@@ -344,6 +629,9 @@ impl<'a> Display for DisplayStorageValidationResult<'a> {
                         warning.text,
                         width = warning_width
                     )?;
+                    if self.verbose {
+                        write_spec_reference(f, warning.code.spec_reference(), warning_width)?;
+                    }
                 }
             }
         } else {
@@ -360,9 +648,128 @@ impl<'a> Display for DisplayStorageValidationResult<'a> {
     }
 }
 
+/// Writes the result of validating an object's staged version to stdout
+pub(crate) fn print_staged_result(result: &ObjectValidationResult, no_styles: bool) {
+    let mut out = BufWriter::new(io::stdout());
+    let _ = write!(
+        out,
+        "{}",
+        DisplayObjectValidationResult {
+            result,
+            no_styles,
+            verbose: false,
+            level: Level::Warn,
+        }
+    );
+    let _ = out.flush();
+}
+
+/// Parses and validates an inventory file, and writes a summary of it and its problems to stdout
+pub(crate) fn inspect_inventory(cmd: &InspectInventoryCmd, args: GlobalArgs) -> Result<()> {
+    let bytes = read_inventory_file(&cmd.file)?;
+    let (inventory, diagnostics) = parse_inventory(&bytes);
+
+    print_inspected_inventory(inventory.as_ref().ok(), &diagnostics, args);
+
+    if diagnostics.has_errors() {
+        raise_exit_status(2);
+    }
+
+    Ok(())
+}
+
+/// Validates the inventory file specified by `validate --inventory`, and optionally verifies that
+/// it matches the digest in its sidecar
+pub(crate) fn validate_inventory_file(cmd: &ValidateCmd, args: GlobalArgs) -> Result<()> {
+    let file = cmd
+        .inventory
+        .as_ref()
+        .expect("validate_inventory_file requires an inventory file");
+
+    let bytes = read_inventory_file(file)?;
+    let (inventory, mut diagnostics) = parse_inventory(&bytes);
+
+    if let Some(expected) = &cmd.digest {
+        validate_inventory_digest(
+            &bytes,
+            expected.algorithm,
+            &expected.digest,
+            &mut diagnostics,
+        )?;
+    }
+
+    cmd.suppress_errors_warnings(&mut diagnostics);
+
+    let display = match cmd.level {
+        Level::Info => true,
+        Level::Warn => diagnostics.has_errors_or_warnings(),
+        Level::Error => diagnostics.has_errors(),
+    };
+
+    if display {
+        print_inspected_inventory(inventory.as_ref().ok(), &diagnostics, args);
+    }
+
+    if diagnostics.has_errors() {
+        raise_exit_status(2);
+    }
+
+    Ok(())
+}
+
+fn read_inventory_file(file: &Path) -> Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+
+    if file.as_os_str() == "-" {
+        io::stdin().read_to_end(&mut bytes)?;
+    } else {
+        File::open(file)?.read_to_end(&mut bytes)?;
+    }
+
+    Ok(bytes)
+}
+
+fn print_inspected_inventory(
+    inventory: Option<&Inventory>,
+    diagnostics: &ParseDiagnostics,
+    args: GlobalArgs,
+) {
+    let mut out = BufWriter::new(io::stdout());
+    let _ = write!(
+        out,
+        "{}",
+        DisplayInspectedInventory {
+            inventory,
+            diagnostics,
+            no_styles: args.no_styles,
+            verbose: args.verbose,
+        }
+    );
+    let _ = out.flush();
+}
+
+fn write_sample_summary(out: &mut impl Write, fixity_check: FixityCheck, checked_count: usize) {
+    if let FixityCheck::Sample(sample) = fixity_check {
+        let _ = writeln!(
+            out,
+            "  Fixity sample:   {}% (seed {}, round {})",
+            sample.percent, sample.seed, sample.round
+        );
+        let _ = writeln!(out, "  Files checked:   {}", checked_count);
+    }
+}
+
+fn random_seed() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_nanos() as u64)
+        .unwrap_or_default()
+}
+
 struct DisplayObjectValidationResult<'a> {
     result: &'a ObjectValidationResult,
     no_styles: bool,
+    verbose: bool,
     level: Level,
 }
 
@@ -420,6 +827,9 @@ impl<'a> Display for DisplayObjectValidationResult<'a> {
                     error.text,
                     width = error_width
                 )?;
+                if self.verbose {
+                    write_spec_reference(f, error.code.spec_reference(), error_width)?;
+                }
             }
 
             if self.level != Level::Error {
@@ -436,6 +846,9 @@ impl<'a> Display for DisplayObjectValidationResult<'a> {
                         warning.text,
                         width = warning_width
                     )?;
+                    if self.verbose {
+                        write_spec_reference(f, warning.code.spec_reference(), warning_width)?;
+                    }
                 }
             }
         } else {
@@ -451,6 +864,208 @@ impl<'a> Display for DisplayObjectValidationResult<'a> {
     }
 }
 
+struct DisplayInspectedInventory<'a> {
+    inventory: Option<&'a Inventory>,
+    diagnostics: &'a ParseDiagnostics,
+    no_styles: bool,
+    verbose: bool,
+}
+
+impl<'a> Painter for DisplayInspectedInventory<'a> {
+    fn no_styles(&self) -> bool {
+        self.no_styles
+    }
+}
+
+impl<'a> Display for DisplayInspectedInventory<'a> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let object_id = self.diagnostics.object_id.as_deref().unwrap_or(UNKNOWN_ID);
+
+        let status = if self.diagnostics.has_errors() {
+            self.paint(*style::RED, "invalid")
+        } else if self.diagnostics.has_warnings() {
+            self.paint(*style::YELLOW, "valid with warnings")
+        } else {
+            self.paint(*style::GREEN, "valid")
+        };
+
+        writeln!(
+            f,
+            "Inventory of object {} is {}",
+            self.paint(*style::BOLD, object_id),
+            status
+        )?;
+
+        if let Some(inventory) = self.inventory {
+            writeln!(f, "  Type:              {}", inventory.type_declaration)?;
+            writeln!(f, "  Digest algorithm:  {}", inventory.digest_algorithm)?;
+            writeln!(f, "  Head:              {}", inventory.head)?;
+            writeln!(
+                f,
+                "  Content directory: {}",
+                inventory.defaulted_content_dir()
+            )?;
+            writeln!(f, "  Versions:          {}", inventory.versions.len())?;
+            writeln!(f, "  Content files:     {}", inventory.manifest().len())?;
+        }
+
+        let error_width = count_digits(self.diagnostics.errors().len());
+        let warning_width = count_digits(self.diagnostics.warnings().len());
+
+        if self.diagnostics.has_errors() {
+            writeln!(f, "  {}:", self.paint(*style::RED, "Errors"))?;
+        }
+        for (i, error) in self.diagnostics.errors().iter().enumerate() {
+            writeln!(
+                f,
+                "    {:width$}. [{}] {}",
+                i + 1,
+                error.code,
+                error.text,
+                width = error_width
+            )?;
+            if self.verbose {
+                write_spec_reference(f, error.code.spec_reference(), error_width)?;
+            }
+        }
+
+        if self.diagnostics.has_warnings() {
+            writeln!(f, "  {}:", self.paint(*style::YELLOW, "Warnings"))?;
+        }
+        for (i, warning) in self.diagnostics.warnings().iter().enumerate() {
+            writeln!(
+                f,
+                "    {:width$}. [{}] {}",
+                i + 1,
+                warning.code,
+                warning.text,
+                width = warning_width
+            )?;
+            if self.verbose {
+                write_spec_reference(f, warning.code.spec_reference(), warning_width)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Writes the spec section, URL, and text that defines a problem's code, indented to align with
+/// the text of the problem
+fn write_spec_reference(
+    f: &mut Formatter<'_>,
+    reference: Option<SpecReference>,
+    number_width: usize,
+) -> std::fmt::Result {
+    if let Some(reference) = reference {
+        let indent = " ".repeat(number_width + 6);
+        writeln!(f, "{}{}: {}", indent, reference.section, reference.url)?;
+        writeln!(f, "{}{}", indent, reference.description)?;
+    }
+    Ok(())
+}
+
+fn object_result_json(result: &ObjectValidationResult, level: Level) -> Value {
+    json!({
+        "type": "object",
+        "object_id": result.object_id.as_deref(),
+        "valid": !result.has_errors(),
+        "errors": errors_json(result),
+        "warnings": warnings_json(result, level),
+    })
+}
+
+fn storage_result_json(result: &StorageValidationResult, location: &str, level: Level) -> Value {
+    json!({
+        "type": "storage",
+        "location": location,
+        "valid": !result.has_errors(),
+        "errors": errors_json(result),
+        "warnings": warnings_json(result, level),
+    })
+}
+
+fn errors_json<T: ValidationResult>(result: &T) -> Vec<Value> {
+    result
+        .errors()
+        .iter()
+        .map(|error| {
+            problem_json(
+                error.code.to_string(),
+                error.location,
+                &error.text,
+                error.code.spec_reference(),
+            )
+        })
+        .collect()
+}
+
+fn warnings_json<T: ValidationResult>(result: &T, level: Level) -> Vec<Value> {
+    if level == Level::Error {
+        return Vec::new();
+    }
+
+    result
+        .warnings()
+        .iter()
+        .map(|warning| {
+            problem_json(
+                warning.code.to_string(),
+                warning.location,
+                &warning.text,
+                warning.code.spec_reference(),
+            )
+        })
+        .collect()
+}
+
+fn problem_json(
+    code: String,
+    location: ProblemLocation,
+    text: &str,
+    reference: Option<SpecReference>,
+) -> Value {
+    json!({
+        "code": code,
+        "location": display_location(location),
+        "text": text,
+        "spec": reference.map(|reference| json!({
+            "section": reference.section,
+            "description": reference.description,
+            "url": reference.url,
+        })),
+    })
+}
+
+fn summary_json(
+    obj_count: usize,
+    invalid_count: usize,
+    storage_errors: Option<usize>,
+    fixity_check: FixityCheck,
+    checked_count: usize,
+) -> Value {
+    let mut summary = json!({
+        "type": "summary",
+        "total_objects": obj_count,
+        "invalid_objects": invalid_count,
+    });
+
+    if let Some(storage_errors) = storage_errors {
+        summary["storage_issues"] = json!(storage_errors);
+    }
+
+    if let FixityCheck::Sample(sample) = fixity_check {
+        summary["fixity_sample"] = json!({
+            "percent": sample.percent,
+            "seed": sample.seed,
+            "round": sample.round,
+            "files_checked": checked_count,
+        });
+    }
+
+    summary
+}
+
 fn display_location(location: ProblemLocation) -> String {
     match location {
         ProblemLocation::ObjectRoot => "root".to_string(),
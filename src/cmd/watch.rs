@@ -0,0 +1,349 @@
+//! Continuously stages the changes that are made to a directory into an object. The directory is
+//! mirrored into the object's staged version: files that are added or modified in the directory
+//! are staged at their paths relative the directory, and files that are deleted are removed.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs::File;
+use std::io::{self, BufRead};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use log::{error, info};
+use notify::{RecursiveMode, Watcher};
+use walkdir::WalkDir;
+
+use crate::cmd::opts::WatchCmd;
+use crate::cmd::{println, Cmd, GlobalArgs};
+use crate::config::Config;
+use crate::ocfl::{
+    CommitMeta, DigestAlgorithm, HexDigest, OcflRepo, Result, RocflError, VersionRef,
+};
+
+/// How often the watch loop checks if it should stop or commit while there are no changes
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Messages that are processed by the watch loop
+enum WatchMessage {
+    /// Files or directories within the watched directory changed
+    Changed(Vec<PathBuf>),
+    /// The watcher failed to observe a change
+    Failed(notify::Error),
+    /// The user requested that the staged changes be committed
+    Commit,
+}
+
+/// The changes that were staged by a single sync
+#[derive(Default)]
+struct SyncSummary {
+    staged: usize,
+    removed: usize,
+}
+
+impl Cmd for WatchCmd {
+    fn exec(
+        &self,
+        repo: &OcflRepo,
+        args: GlobalArgs,
+        config: &Config,
+        terminate: &AtomicBool,
+    ) -> Result<()> {
+        if !self.directory.is_dir() {
+            return Err(RocflError::InvalidValue(format!(
+                "{} is not a directory",
+                self.directory.to_string_lossy()
+            )));
+        }
+
+        let root = self
+            .directory
+            .canonicalize()
+            .map_err(|e| RocflError::io_path("resolve directory", &self.directory, e))?;
+
+        let (sender, receiver) = mpsc::channel();
+
+        // The directory is watched before it is first synced so that no changes are missed
+        let _watcher = if self.once {
+            None
+        } else {
+            let sender = sender.clone();
+            let mut watcher = notify::recommended_watcher(move |event| {
+                let message = match event {
+                    Ok(notify::Event { kind, .. }) if kind.is_access() => return,
+                    Ok(notify::Event { paths, .. }) => WatchMessage::Changed(paths),
+                    Err(e) => WatchMessage::Failed(e),
+                };
+                let _ = sender.send(message);
+            })
+            .map_err(watch_error)?;
+            watcher
+                .watch(&root, RecursiveMode::Recursive)
+                .map_err(watch_error)?;
+            Some(watcher)
+        };
+
+        let summary = sync(repo, &self.object_id, &root, std::slice::from_ref(&root))?;
+        print_summary(&self.object_id, &summary, &args);
+
+        if self.once {
+            return Ok(());
+        }
+
+        if !args.quiet {
+            println(format!(
+                "Watching {} for changes to stage in object {}. Press enter to commit the staged changes, or ctrl-c to stop.",
+                root.to_string_lossy(),
+                self.object_id
+            ));
+        }
+
+        read_commit_requests(sender);
+
+        let commit_interval = self.commit_interval.map(Duration::from_secs);
+        let debounce = Duration::from_millis(self.debounce);
+        let mut next_commit = commit_interval.map(|interval| Instant::now() + interval);
+        let mut pending = BTreeSet::new();
+
+        while !terminate.load(Ordering::Acquire) {
+            let timeout = if pending.is_empty() {
+                POLL_INTERVAL
+            } else {
+                debounce
+            };
+
+            match receiver.recv_timeout(timeout) {
+                Ok(WatchMessage::Changed(paths)) => pending.extend(paths),
+                Ok(WatchMessage::Failed(e)) => error!("Failed to watch for changes: {}", e),
+                Ok(WatchMessage::Commit) => {
+                    self.stage_pending(repo, &root, &mut pending, &args);
+                    self.commit(repo, config, &args);
+                }
+                Err(RecvTimeoutError::Timeout) => {
+                    // Changes are only staged once they have stopped for the debounce period
+                    self.stage_pending(repo, &root, &mut pending, &args);
+                }
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+
+            if let (Some(interval), Some(at)) = (commit_interval, next_commit) {
+                if Instant::now() >= at {
+                    self.stage_pending(repo, &root, &mut pending, &args);
+                    self.commit(repo, config, &args);
+                    next_commit = Some(Instant::now() + interval);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl WatchCmd {
+    /// Stages the changes to the paths that changed since the last sync
+    fn stage_pending(
+        &self,
+        repo: &OcflRepo,
+        root: &Path,
+        pending: &mut BTreeSet<PathBuf>,
+        args: &GlobalArgs,
+    ) {
+        if pending.is_empty() {
+            return;
+        }
+
+        let paths: Vec<PathBuf> = std::mem::take(pending).into_iter().collect();
+
+        match sync(repo, &self.object_id, root, &paths) {
+            Ok(summary) => print_summary(&self.object_id, &summary, args),
+            Err(e) => error!(
+                "Failed to stage changes to object {}: {:#}",
+                self.object_id, e
+            ),
+        }
+    }
+
+    /// Commits the object's staged changes, if it has any
+    fn commit(&self, repo: &OcflRepo, config: &Config, args: &GlobalArgs) {
+        match repo.get_staged_object(&self.object_id) {
+            Ok(_) => (),
+            Err(RocflError::NotFound(_)) => {
+                info!("Object {} does not have staged changes", self.object_id);
+                return;
+            }
+            Err(e) => {
+                error!("Failed to commit object {}: {:#}", self.object_id, e);
+                return;
+            }
+        }
+
+        let result = CommitMeta::builder()
+            .defaults_from_config(config)
+            .message(self.message.clone())
+            .build()
+            .and_then(|meta| {
+                repo.commit(
+                    &self.object_id,
+                    meta,
+                    None,
+                    config.pretty_print.unwrap_or(false),
+                )
+            })
+            .and_then(|_| repo.get_object_details(&self.object_id, VersionRef::Head));
+
+        match result {
+            Ok(details) => {
+                if !args.quiet {
+                    println(format!(
+                        "Committed object {} version {}",
+                        self.object_id, details.version_details.version_num
+                    ));
+                }
+            }
+            Err(e) => error!("Failed to commit object {}: {:#}", self.object_id, e),
+        }
+    }
+}
+
+/// Stages the differences between the files in the directory at `root` and the object's staged
+/// version, or its HEAD version if it does not have one. Only the files at, or within, the
+/// specified paths are compared. Files that are not in the staged version, or that have a
+/// different digest, are staged, and files that are in the staged version but no longer exist
+/// in the directory are removed.
+fn sync(repo: &OcflRepo, object_id: &str, root: &Path, paths: &[PathBuf]) -> Result<SyncSummary> {
+    let (algorithm, state) = current_state(repo, object_id)?;
+
+    let scopes: Vec<String> = paths
+        .iter()
+        .filter_map(|path| logical_path(root, path))
+        .collect();
+
+    let in_scope = |logical_path: &str| {
+        scopes.iter().any(|scope| {
+            scope.is_empty()
+                || logical_path == scope
+                || (logical_path.starts_with(scope.as_str())
+                    && logical_path[scope.len()..].starts_with('/'))
+        })
+    };
+
+    let mut files = BTreeMap::new();
+
+    for path in paths {
+        for entry in WalkDir::new(path).into_iter().flatten() {
+            if entry.file_type().is_file() {
+                if let Some(logical_path) = logical_path(root, entry.path()) {
+                    files.insert(logical_path, entry.into_path());
+                }
+            }
+        }
+    }
+
+    let removed: Vec<String> = state
+        .keys()
+        .filter(|path| in_scope(path) && !files.contains_key(*path))
+        .map(|path| escape_glob(path))
+        .collect();
+
+    let mut summary = SyncSummary {
+        removed: removed.len(),
+        ..Default::default()
+    };
+
+    // Removals are staged first so that a file may replace a directory, or vice versa
+    if !removed.is_empty() {
+        repo.remove_files(object_id, &removed, false)?;
+    }
+
+    for (logical_path, path) in files {
+        let digest = match File::open(&path)
+            .map_err(RocflError::from)
+            .and_then(|mut file| algorithm.hash_hex(&mut file))
+        {
+            Ok(digest) => digest,
+            Err(e) => {
+                // The file was likely deleted before it could be read
+                info!("Skipping {}: {}", path.to_string_lossy(), e);
+                continue;
+            }
+        };
+
+        if state.get(&logical_path) != Some(&digest) {
+            repo.copy_files_external(object_id, &[&path], &logical_path, false)?;
+            summary.staged += 1;
+        }
+    }
+
+    Ok(summary)
+}
+
+/// Returns the object's digest algorithm, and a map of the logical paths in its staged version,
+/// or HEAD version if it does not have one, to their digests
+fn current_state(
+    repo: &OcflRepo,
+    object_id: &str,
+) -> Result<(DigestAlgorithm, HashMap<String, HexDigest>)> {
+    let object = match repo.get_staged_object(object_id) {
+        Err(RocflError::NotFound(_)) => repo.get_object(object_id, VersionRef::Head)?,
+        result => result?,
+    };
+
+    let state = object
+        .state
+        .into_iter()
+        .map(|(path, details)| (path.to_string(), details.digest.as_ref().clone()))
+        .collect();
+
+    Ok((object.digest_algorithm, state))
+}
+
+/// Returns the logical path of a file within the watched directory. `None` is returned if the
+/// file is not within the directory.
+fn logical_path(root: &Path, path: &Path) -> Option<String> {
+    let relative = path.strip_prefix(root).ok()?;
+
+    Some(
+        relative
+            .components()
+            .map(|part| part.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/"),
+    )
+}
+
+/// Escapes the glob metacharacters in a logical path so that it only matches itself
+fn escape_glob(path: &str) -> String {
+    let mut escaped = String::with_capacity(path.len());
+    for c in path.chars() {
+        if matches!(c, '*' | '?' | '[' | ']' | '{' | '}' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Requests a commit every time the user presses enter. Nothing is requested if stdin is closed.
+fn read_commit_requests(sender: Sender<WatchMessage>) {
+    thread::spawn(move || {
+        for line in io::stdin().lock().lines() {
+            if line.is_err() || sender.send(WatchMessage::Commit).is_err() {
+                break;
+            }
+        }
+    });
+}
+
+fn print_summary(object_id: &str, summary: &SyncSummary, args: &GlobalArgs) {
+    if !args.quiet && (summary.staged > 0 || summary.removed > 0) {
+        println(format!(
+            "Staged {} and removed {} file(s) in object {}",
+            summary.staged, summary.removed, object_id
+        ));
+    }
+}
+
+fn watch_error(e: notify::Error) -> RocflError {
+    RocflError::General(format!("Failed to watch directory: {}", e))
+}
@@ -1,15 +1,75 @@
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::fs;
 use std::io::Read;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 use directories::ProjectDirs;
 use serde::Deserialize;
+use toml_edit::{Document, Item, Value};
 
-use crate::ocfl::{DigestAlgorithm, Result, RocflError};
+use crate::ocfl::{
+    CloudStorage, CommitQuotas, DigestAlgorithm, InventoryPath, LogicalPath, Result, RocflError,
+    S3ClientOptions, S3WriteOptions,
+};
 
 const CONFIG_FILE: &str = "config.toml";
 const GLOBAL: &str = "global";
+const CONTENT_DIR_DEFAULT: &str = "content";
+const METADATA_PATH_DEFAULT: &str = "metadata/rocfl.json";
+pub const PROVIDER_S3: &str = "s3";
+pub const PROVIDER_AZURE: &str = "azure";
+pub const PROVIDER_GCS: &str = "gcs";
+const CONFIG_TEMPLATE: &str = include_str!("../../resources/main/files/config.toml");
+
+/// The properties that may be set in a config section, and the type of their values. This must
+/// be kept in sync with the fields of `Config`.
+const PROPERTIES: [(&str, PropertyType); 36] = [
+    ("author_name", PropertyType::String),
+    ("author_address", PropertyType::String),
+    ("root", PropertyType::String),
+    ("staging_root", PropertyType::String),
+    ("region", PropertyType::String),
+    ("bucket", PropertyType::String),
+    ("endpoint", PropertyType::String),
+    ("profile", PropertyType::String),
+    ("provider", PropertyType::String),
+    ("account", PropertyType::String),
+    ("credentials", PropertyType::String),
+    ("pretty_print", PropertyType::Bool),
+    ("digest_algorithm", PropertyType::String),
+    ("content_directory", PropertyType::String),
+    ("zero_padding", PropertyType::Integer),
+    ("hard_links", PropertyType::Bool),
+    ("content_storage_class", PropertyType::String),
+    ("content_tags", PropertyType::String),
+    ("inventory_storage_class", PropertyType::String),
+    ("inventory_tags", PropertyType::String),
+    ("metadata_path", PropertyType::String),
+    ("timeout", PropertyType::Integer),
+    ("audit_trail", PropertyType::Bool),
+    ("signing_key", PropertyType::String),
+    ("validation_history", PropertyType::Bool),
+    ("threads", PropertyType::Integer),
+    ("read_only", PropertyType::Bool),
+    ("user_agent", PropertyType::String),
+    ("request_timeout", PropertyType::Integer),
+    ("request_metrics", PropertyType::Bool),
+    ("bucket_staging", PropertyType::Bool),
+    ("max_object_size", PropertyType::Integer),
+    ("max_version_files", PropertyType::Integer),
+    ("max_staged_bytes", PropertyType::Integer),
+    ("commit_callout", PropertyType::String),
+    ("encryption_key", PropertyType::String),
+];
+
+#[derive(Debug, Copy, Clone)]
+enum PropertyType {
+    String,
+    Bool,
+    Integer,
+}
 
 /// Representation of user configuration
 #[derive(Deserialize, Debug)]
@@ -23,6 +83,60 @@ pub struct Config {
     pub bucket: Option<String>,
     pub endpoint: Option<String>,
     pub profile: Option<String>,
+    /// The cloud storage service the bucket is in: s3, azure, or gcs. Default: s3
+    pub provider: Option<String>,
+    /// The Azure storage account that the container is in
+    pub account: Option<String>,
+    /// The Azure storage account access key, or the path to a GCS service account key file
+    pub credentials: Option<String>,
+    pub pretty_print: Option<bool>,
+    pub digest_algorithm: Option<DigestAlgorithm>,
+    pub content_directory: Option<String>,
+    pub zero_padding: Option<u32>,
+    pub hard_links: Option<bool>,
+    /// The S3 storage class to write content files to
+    pub content_storage_class: Option<String>,
+    /// Comma separated `KEY=VALUE` tags to apply to content files written to S3
+    pub content_tags: Option<String>,
+    /// The S3 storage class to write inventories to
+    pub inventory_storage_class: Option<String>,
+    /// Comma separated `KEY=VALUE` tags to apply to inventories written to S3
+    pub inventory_tags: Option<String>,
+    /// The logical path of the file `meta` manages object metadata in
+    pub metadata_path: Option<String>,
+    /// The number of seconds after which a command is aborted
+    pub timeout: Option<u64>,
+    /// Whether to record rocfl operations in the logs directory of the objects they modify
+    pub audit_trail: Option<bool>,
+    /// The path to the PEM encoded ed25519 private key that committed inventories are signed with
+    pub signing_key: Option<String>,
+    /// Whether to record the results of validating objects in the repository's validation history
+    pub validation_history: Option<bool>,
+    /// The number of threads that parallelizable work is distributed across
+    pub threads: Option<usize>,
+    /// Whether operations that modify the repository are rejected
+    pub read_only: Option<bool>,
+    /// Prepended to the user agent of S3 requests
+    pub user_agent: Option<String>,
+    /// The number of seconds to wait for a response to an S3 request
+    pub request_timeout: Option<u64>,
+    /// Whether to print metrics about the S3 requests a command sent after it completes
+    pub request_metrics: Option<bool>,
+    /// Whether to stage the content of S3 objects in the bucket rather than on local disk
+    pub bucket_staging: Option<bool>,
+    /// The maximum size, in bytes, of an object's content that a commit may produce
+    pub max_object_size: Option<u64>,
+    /// The maximum number of files a committed version may contain
+    pub max_version_files: Option<u64>,
+    /// The maximum number of new content bytes a single commit may add
+    pub max_staged_bytes: Option<u64>,
+    /// The command that is run on each file a commit adds, and that vetoes the commit if it fails
+    pub commit_callout: Option<String>,
+    /// The path to the base64 encoded AES-256 key that the content of new objects is encrypted with
+    pub encryption_key: Option<String>,
+    /// The source of each configured value, keyed on the property name
+    #[serde(skip)]
+    pub sources: BTreeMap<&'static str, String>,
 }
 
 impl Config {
@@ -36,15 +150,52 @@ impl Config {
             bucket: None,
             endpoint: None,
             profile: None,
+            provider: None,
+            account: None,
+            credentials: None,
+            pretty_print: None,
+            digest_algorithm: None,
+            content_directory: None,
+            zero_padding: None,
+            hard_links: None,
+            content_storage_class: None,
+            content_tags: None,
+            inventory_storage_class: None,
+            inventory_tags: None,
+            metadata_path: None,
+            timeout: None,
+            audit_trail: None,
+            signing_key: None,
+            validation_history: None,
+            threads: None,
+            read_only: None,
+            user_agent: None,
+            request_timeout: None,
+            request_metrics: None,
+            bucket_staging: None,
+            max_object_size: None,
+            max_version_files: None,
+            max_staged_bytes: None,
+            commit_callout: None,
+            encryption_key: None,
+            sources: BTreeMap::new(),
         }
     }
 
     pub fn validate(&self) -> Result<()> {
         if self.bucket.is_some() {
-            if self.region.is_none() {
-                return Err(RocflError::InvalidConfiguration(
-                    "Region must be specified when using S3".to_string(),
-                ));
+            match self.provider() {
+                PROVIDER_S3 if self.region.is_none() => {
+                    return Err(RocflError::InvalidConfiguration(
+                        "Region must be specified when using S3".to_string(),
+                    ));
+                }
+                PROVIDER_AZURE if self.account.is_none() => {
+                    return Err(RocflError::InvalidConfiguration(
+                        "Account must be specified when using Azure".to_string(),
+                    ));
+                }
+                _ => (),
             }
         } else if self.region.is_some() || self.endpoint.is_some() {
             return Err(RocflError::InvalidConfiguration(
@@ -52,10 +203,267 @@ impl Config {
                 If you intended to use S3, then you must specify a bucket."
                     .to_string(),
             ));
+        } else if self.provider.is_some() || self.account.is_some() {
+            return Err(RocflError::InvalidConfiguration(
+                "Provider and account should not be set when not using cloud storage. \
+                If you intended to use cloud storage, then you must specify a bucket."
+                    .to_string(),
+            ));
+        }
+
+        self.validate_properties()
+    }
+
+    /// Validates the values of individual properties, without considering how they interact
+    fn validate_properties(&self) -> Result<()> {
+        if let Some(algorithm) = self.digest_algorithm {
+            if algorithm != DigestAlgorithm::Sha512 && algorithm != DigestAlgorithm::Sha256 {
+                return Err(RocflError::InvalidConfiguration(format!(
+                    "Digest algorithm must be sha512 or sha256. Found: {} (from {})",
+                    algorithm,
+                    self.source("digest_algorithm")
+                )));
+            }
+        }
+
+        if let Some(content_dir) = &self.content_directory {
+            if content_dir.is_empty()
+                || content_dir == "."
+                || content_dir == ".."
+                || content_dir.contains('/')
+            {
+                return Err(RocflError::InvalidConfiguration(format!(
+                    "Content directory cannot equal '.' or '..' and cannot contain a '/'. \
+                    Found: '{}' (from {})",
+                    content_dir,
+                    self.source("content_directory")
+                )));
+            }
+        }
+
+        if let Some(metadata_path) = &self.metadata_path {
+            if LogicalPath::try_from(metadata_path.as_str())
+                .map(|path| path.is_empty())
+                .unwrap_or(true)
+            {
+                return Err(RocflError::InvalidConfiguration(format!(
+                    "Metadata path must be a valid logical file path. Found: '{}' (from {})",
+                    metadata_path,
+                    self.source("metadata_path")
+                )));
+            }
+        }
+
+        if let Some(provider) = &self.provider {
+            if ![PROVIDER_S3, PROVIDER_AZURE, PROVIDER_GCS].contains(&provider.as_str()) {
+                return Err(RocflError::InvalidConfiguration(format!(
+                    "Provider must be s3, azure, or gcs. Found: {} (from {})",
+                    provider,
+                    self.source("provider")
+                )));
+            }
         }
 
+        if let Some(command) = &self.commit_callout {
+            if command.trim().is_empty() {
+                return Err(RocflError::InvalidConfiguration(format!(
+                    "Commit callout must not be blank (from {})",
+                    self.source("commit_callout")
+                )));
+            }
+        }
+
+        if self.timeout == Some(0) {
+            return Err(RocflError::InvalidConfiguration(format!(
+                "Timeout must be greater than 0 (from {})",
+                self.source("timeout")
+            )));
+        }
+
+        if self.request_timeout == Some(0) {
+            return Err(RocflError::InvalidConfiguration(format!(
+                "Request timeout must be greater than 0 (from {})",
+                self.source("request_timeout")
+            )));
+        }
+
+        if self.threads == Some(0) {
+            return Err(RocflError::InvalidConfiguration(format!(
+                "Threads must be greater than 0 (from {})",
+                self.source("threads")
+            )));
+        }
+
+        self.content_write_options()?;
+        self.inventory_write_options()?;
+
         Ok(())
     }
+
+    /// The S3 storage class and tags to apply to content files
+    pub fn content_write_options(&self) -> Result<S3WriteOptions> {
+        self.s3_write_options(
+            ("content_storage_class", &self.content_storage_class),
+            ("content_tags", &self.content_tags),
+        )
+    }
+
+    /// The S3 storage class and tags to apply to inventories
+    pub fn inventory_write_options(&self) -> Result<S3WriteOptions> {
+        self.s3_write_options(
+            ("inventory_storage_class", &self.inventory_storage_class),
+            ("inventory_tags", &self.inventory_tags),
+        )
+    }
+
+    fn s3_write_options(
+        &self,
+        (class_property, storage_class): (&str, &Option<String>),
+        (tags_property, tags): (&str, &Option<String>),
+    ) -> Result<S3WriteOptions> {
+        let invalid = |e: RocflError, property: &str| match e {
+            RocflError::InvalidValue(message) => RocflError::InvalidConfiguration(format!(
+                "{} (from {})",
+                message,
+                self.source(property)
+            )),
+            e => e,
+        };
+
+        let storage_class = S3WriteOptions::parse(storage_class.as_deref(), None)
+            .map_err(|e| invalid(e, class_property))?
+            .storage_class;
+        let tags = S3WriteOptions::parse(None, tags.as_deref())
+            .map_err(|e| invalid(e, tags_property))?
+            .tags;
+
+        Ok(S3WriteOptions {
+            storage_class,
+            tags,
+        })
+    }
+
+    /// The options of the HTTP client that S3 requests are sent with
+    pub fn s3_client_options(&self) -> S3ClientOptions {
+        S3ClientOptions {
+            user_agent: self.user_agent.clone(),
+            request_timeout: self.request_timeout.map(Duration::from_secs),
+            request_metrics: self.request_metrics.unwrap_or(false),
+        }
+    }
+
+    /// The cloud storage service that the bucket is in
+    pub fn provider(&self) -> &str {
+        self.provider.as_deref().unwrap_or(PROVIDER_S3)
+    }
+
+    /// The cloud storage service, other than S3, that the bucket is in. `None` is returned if
+    /// the bucket is in S3.
+    pub fn cloud_storage(&self) -> Option<CloudStorage> {
+        match self.provider() {
+            PROVIDER_AZURE => Some(CloudStorage::Azure {
+                account: self.account.clone().unwrap_or_default(),
+                access_key: self.credentials.clone(),
+            }),
+            PROVIDER_GCS => Some(CloudStorage::Gcs {
+                service_account: self.credentials.clone(),
+            }),
+            _ => None,
+        }
+    }
+
+    /// The quotas that are enforced when objects are committed
+    pub fn commit_quotas(&self) -> CommitQuotas {
+        CommitQuotas {
+            max_object_size: self.max_object_size,
+            max_version_files: self.max_version_files,
+            max_staged_bytes: self.max_staged_bytes,
+        }
+    }
+
+    /// The content directory to use when creating new objects
+    pub fn content_directory(&self) -> &str {
+        self.content_directory
+            .as_deref()
+            .unwrap_or(CONTENT_DIR_DEFAULT)
+    }
+
+    /// The logical path of the file that object metadata is stored in
+    pub fn metadata_path(&self) -> &str {
+        self.metadata_path
+            .as_deref()
+            .unwrap_or(METADATA_PATH_DEFAULT)
+    }
+
+    /// Describes where the value of the named property was sourced from
+    pub fn source(&self, property: &str) -> &str {
+        self.sources
+            .get(property)
+            .map(|source| source.as_str())
+            .unwrap_or("default")
+    }
+
+    /// Records the source of all of the properties that are set
+    fn record_sources(&mut self, source: &str) {
+        for property in self.set_properties() {
+            self.sources.insert(property, source.to_string());
+        }
+    }
+
+    fn set_properties(&self) -> Vec<&'static str> {
+        let mut properties = Vec::new();
+
+        let mut add = |name: &'static str, set: bool| {
+            if set {
+                properties.push(name);
+            }
+        };
+
+        add("author_name", self.author_name.is_some());
+        add("author_address", self.author_address.is_some());
+        add("root", self.root.is_some());
+        add("staging_root", self.staging_root.is_some());
+        add("region", self.region.is_some());
+        add("bucket", self.bucket.is_some());
+        add("endpoint", self.endpoint.is_some());
+        add("profile", self.profile.is_some());
+        add("provider", self.provider.is_some());
+        add("account", self.account.is_some());
+        add("credentials", self.credentials.is_some());
+        add("pretty_print", self.pretty_print.is_some());
+        add("digest_algorithm", self.digest_algorithm.is_some());
+        add("content_directory", self.content_directory.is_some());
+        add("zero_padding", self.zero_padding.is_some());
+        add("hard_links", self.hard_links.is_some());
+        add(
+            "content_storage_class",
+            self.content_storage_class.is_some(),
+        );
+        add("content_tags", self.content_tags.is_some());
+        add(
+            "inventory_storage_class",
+            self.inventory_storage_class.is_some(),
+        );
+        add("inventory_tags", self.inventory_tags.is_some());
+        add("metadata_path", self.metadata_path.is_some());
+        add("timeout", self.timeout.is_some());
+        add("audit_trail", self.audit_trail.is_some());
+        add("signing_key", self.signing_key.is_some());
+        add("validation_history", self.validation_history.is_some());
+        add("threads", self.threads.is_some());
+        add("read_only", self.read_only.is_some());
+        add("user_agent", self.user_agent.is_some());
+        add("request_timeout", self.request_timeout.is_some());
+        add("request_metrics", self.request_metrics.is_some());
+        add("bucket_staging", self.bucket_staging.is_some());
+        add("max_object_size", self.max_object_size.is_some());
+        add("max_version_files", self.max_version_files.is_some());
+        add("max_staged_bytes", self.max_staged_bytes.is_some());
+        add("commit_callout", self.commit_callout.is_some());
+        add("encryption_key", self.encryption_key.is_some());
+
+        properties
+    }
 }
 
 impl Default for Config {
@@ -81,6 +489,192 @@ pub fn config_path() -> Option<PathBuf> {
     project_dirs().map(|dirs| dirs.config_dir().join(CONFIG_FILE))
 }
 
+/// Writes the commented out example config to the specified path, if the file does not exist
+pub fn init_config_file(config_file: impl AsRef<Path>) -> Result<()> {
+    let config_file = config_file.as_ref();
+    if !config_file.exists() {
+        if let Some(parent) = config_file.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(config_file, CONFIG_TEMPLATE)?;
+    }
+    Ok(())
+}
+
+/// Returns every property that is set in the config file, as `SECTION.PROPERTY` and value pairs,
+/// in the order they appear in the file. If `section` is specified, only its properties are
+/// returned.
+pub fn list_config_values(
+    config_file: impl AsRef<Path>,
+    section: Option<&str>,
+) -> Result<Vec<(String, String)>> {
+    let document = read_document(config_file)?;
+    let mut values = Vec::new();
+
+    for (name, item) in document.iter() {
+        if section.map(|section| section != name).unwrap_or(false) {
+            continue;
+        }
+
+        if let Some(table) = item.as_table() {
+            for (property, item) in table.iter() {
+                if let Some(value) = item.as_value() {
+                    values.push((format!("{}.{}", name, property), display_value(value)));
+                }
+            }
+        }
+    }
+
+    Ok(values)
+}
+
+/// Returns the value of a `SECTION.PROPERTY` key in the config file, or `None` if it is not set
+pub fn get_config_value(config_file: impl AsRef<Path>, key: &str) -> Result<Option<String>> {
+    let (section, property, _) = parse_key(key)?;
+    let document = read_document(config_file)?;
+
+    Ok(document
+        .get(section)
+        .and_then(Item::as_table)
+        .and_then(|table| table.get(property))
+        .and_then(Item::as_value)
+        .map(display_value))
+}
+
+/// Sets the value of a `SECTION.PROPERTY` key in the config file. The value is parsed according
+/// to the property's type and validated before the file is written. The formatting and comments
+/// of the rest of the file are preserved.
+pub fn set_config_value(config_file: impl AsRef<Path>, key: &str, value: &str) -> Result<()> {
+    let config_file = config_file.as_ref();
+    let (section, property, property_type) = parse_key(key)?;
+
+    let value = match property_type {
+        PropertyType::String => Value::from(value),
+        PropertyType::Bool => Value::from(value.parse::<bool>().map_err(|_| {
+            RocflError::InvalidValue(format!(
+                "{} must be true or false. Found: {}",
+                property, value
+            ))
+        })?),
+        PropertyType::Integer => Value::from(value.parse::<i64>().map_err(|_| {
+            RocflError::InvalidValue(format!("{} must be an integer. Found: {}", property, value))
+        })?),
+    };
+
+    init_config_file(config_file)?;
+    let mut document = read_document(config_file)?;
+
+    let table = document
+        .entry(section)
+        .or_insert_with(toml_edit::table)
+        .as_table_mut()
+        .ok_or_else(|| {
+            RocflError::InvalidConfiguration(format!("{} is not a config section", section))
+        })?;
+
+    match table.get_mut(property).and_then(Item::as_value_mut) {
+        Some(existing) => {
+            let decor = existing.decor().clone();
+            *existing = value;
+            *existing.decor_mut() = decor;
+        }
+        None => {
+            table.insert(property, Item::Value(value));
+        }
+    }
+
+    write_document(config_file, &document, section)
+}
+
+/// Removes a `SECTION.PROPERTY` key from the config file. It is not an error if the key is
+/// not set.
+pub fn unset_config_value(config_file: impl AsRef<Path>, key: &str) -> Result<()> {
+    let config_file = config_file.as_ref();
+    let (section, property, _) = parse_key(key)?;
+
+    if !config_file.exists() {
+        return Ok(());
+    }
+
+    let mut document = read_document(config_file)?;
+
+    if let Some(table) = document.get_mut(section).and_then(Item::as_table_mut) {
+        if table.remove(property).is_some() {
+            return write_document(config_file, &document, section);
+        }
+    }
+
+    Ok(())
+}
+
+/// Splits a `SECTION.PROPERTY` key into its parts. Section names may contain `.`, but property
+/// names may not.
+fn parse_key(key: &str) -> Result<(&str, &str, PropertyType)> {
+    let (section, property) = match key.rsplit_once('.') {
+        Some((section, property)) if !section.is_empty() => (section, property),
+        _ => {
+            return Err(RocflError::InvalidValue(format!(
+                "Config keys must be in the form SECTION.PROPERTY. Found: {}",
+                key
+            )))
+        }
+    };
+
+    match PROPERTIES.iter().find(|(name, _)| *name == property) {
+        Some((_, property_type)) => Ok((section, property, *property_type)),
+        None => Err(RocflError::InvalidValue(format!(
+            "Unknown config property {}. Valid properties are: {}",
+            property,
+            PROPERTIES
+                .iter()
+                .map(|(name, _)| *name)
+                .collect::<Vec<&str>>()
+                .join(", ")
+        ))),
+    }
+}
+
+fn read_document(config_file: impl AsRef<Path>) -> Result<Document> {
+    let config_file = config_file.as_ref();
+    if !config_file.exists() {
+        return Ok(Document::new());
+    }
+
+    fs::read_to_string(config_file)?
+        .parse::<Document>()
+        .map_err(|e| {
+            RocflError::InvalidConfiguration(format!(
+                "Failed to parse {}: {}",
+                config_file.to_string_lossy(),
+                e
+            ))
+        })
+}
+
+/// Validates the edited section of the document before writing it to disk
+fn write_document(config_file: &Path, document: &Document, section: &str) -> Result<()> {
+    let contents = document.to_string();
+
+    let mut config: HashMap<String, Config> = toml::from_str(&contents).map_err(|e| {
+        RocflError::InvalidConfiguration(format!("Invalid [{}] section: {}", section, e))
+    })?;
+
+    if let Some(mut config) = config.remove(section) {
+        config.record_sources(&format!("[{}]", section));
+        config.validate_properties()?;
+    }
+
+    fs::write(config_file, contents)?;
+    Ok(())
+}
+
+fn display_value(value: &Value) -> String {
+    match value.as_str() {
+        Some(value) => value.to_string(),
+        None => value.clone().decorated("", "").to_string(),
+    }
+}
+
 /// Reference to the rocfl project directories. These directories do **not** necessarily exist
 pub fn project_dirs() -> Option<ProjectDirs> {
     ProjectDirs::from("org", "rocfl", "rocfl")
@@ -101,7 +695,15 @@ pub fn s3_staging_path(config: &Config) -> Result<String> {
 }
 
 fn s3_identifier(config: &Config) -> Result<String> {
-    let mut name = config.bucket.clone().unwrap();
+    let mut name = match config.provider() {
+        PROVIDER_S3 => String::new(),
+        provider => format!(
+            "{}:{}/",
+            provider,
+            config.account.as_deref().unwrap_or_default()
+        ),
+    };
+    name.push_str(config.bucket.as_ref().unwrap());
     if let Some(root) = &config.root {
         name.push('/');
         name.push_str(root);
@@ -118,19 +720,28 @@ fn parse_config(config_file: impl AsRef<Path>) -> Result<HashMap<String, Config>
 }
 
 fn resolve_config(name: &Option<String>, mut config: HashMap<String, Config>) -> Config {
-    let global_config = config.remove(GLOBAL);
+    let global_config = config.remove(GLOBAL).map(|mut global| {
+        global.record_sources(&format!("[{}]", GLOBAL));
+        global
+    });
     let repo_config = match name {
         None => None,
-        Some(name) => config.remove(name),
+        Some(name) => config.remove(name).map(|mut repo| {
+            repo.record_sources(&format!("[{}]", name));
+            repo
+        }),
     };
 
     match (global_config, repo_config) {
         (Some(global), None) => global,
         (None, Some(repo)) => repo,
         (None, None) => Config::new(),
-        (Some(global), Some(repo)) => {
+        (Some(mut global), Some(repo)) => {
             let mut resolved = Config::new();
 
+            resolved.sources.append(&mut global.sources);
+            resolved.sources.extend(repo.sources);
+
             resolved.author_name = resolve_field(global.author_name, repo.author_name);
             resolved.author_address = resolve_field(global.author_address, repo.author_address);
             resolved.root = resolve_field(global.root, repo.root);
@@ -139,16 +750,397 @@ fn resolve_config(name: &Option<String>, mut config: HashMap<String, Config>) ->
             resolved.bucket = resolve_field(global.bucket, repo.bucket);
             resolved.endpoint = resolve_field(global.endpoint, repo.endpoint);
             resolved.profile = resolve_field(global.profile, repo.profile);
+            resolved.provider = resolve_field(global.provider, repo.provider);
+            resolved.account = resolve_field(global.account, repo.account);
+            resolved.credentials = resolve_field(global.credentials, repo.credentials);
+            resolved.pretty_print = resolve_field(global.pretty_print, repo.pretty_print);
+            resolved.digest_algorithm =
+                resolve_field(global.digest_algorithm, repo.digest_algorithm);
+            resolved.content_directory =
+                resolve_field(global.content_directory, repo.content_directory);
+            resolved.zero_padding = resolve_field(global.zero_padding, repo.zero_padding);
+            resolved.hard_links = resolve_field(global.hard_links, repo.hard_links);
+            resolved.content_storage_class =
+                resolve_field(global.content_storage_class, repo.content_storage_class);
+            resolved.content_tags = resolve_field(global.content_tags, repo.content_tags);
+            resolved.inventory_storage_class =
+                resolve_field(global.inventory_storage_class, repo.inventory_storage_class);
+            resolved.inventory_tags = resolve_field(global.inventory_tags, repo.inventory_tags);
+            resolved.metadata_path = resolve_field(global.metadata_path, repo.metadata_path);
+            resolved.timeout = resolve_field(global.timeout, repo.timeout);
+            resolved.audit_trail = resolve_field(global.audit_trail, repo.audit_trail);
+            resolved.signing_key = resolve_field(global.signing_key, repo.signing_key);
+            resolved.validation_history =
+                resolve_field(global.validation_history, repo.validation_history);
+            resolved.threads = resolve_field(global.threads, repo.threads);
+            resolved.read_only = resolve_field(global.read_only, repo.read_only);
+            resolved.user_agent = resolve_field(global.user_agent, repo.user_agent);
+            resolved.request_timeout = resolve_field(global.request_timeout, repo.request_timeout);
+            resolved.request_metrics = resolve_field(global.request_metrics, repo.request_metrics);
+            resolved.bucket_staging = resolve_field(global.bucket_staging, repo.bucket_staging);
+            resolved.max_object_size = resolve_field(global.max_object_size, repo.max_object_size);
+            resolved.max_version_files =
+                resolve_field(global.max_version_files, repo.max_version_files);
+            resolved.max_staged_bytes =
+                resolve_field(global.max_staged_bytes, repo.max_staged_bytes);
+            resolved.commit_callout = resolve_field(global.commit_callout, repo.commit_callout);
+            resolved.encryption_key = resolve_field(global.encryption_key, repo.encryption_key);
 
             resolved
         }
     }
 }
 
-fn resolve_field(global_field: Option<String>, repo_field: Option<String>) -> Option<String> {
+fn resolve_field<T>(global_field: Option<T>, repo_field: Option<T>) -> Option<T> {
     if repo_field.is_some() {
         repo_field
     } else {
         global_field
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::fs;
+    use std::time::Duration;
+
+    use assert_fs::TempDir;
+
+    use super::{
+        get_config_value, list_config_values, resolve_config, set_config_value, unset_config_value,
+        Config,
+    };
+    use crate::ocfl::{CloudStorage, CommitQuotas, DigestAlgorithm, S3ClientOptions};
+
+    #[test]
+    fn named_config_overrides_global_and_records_sources() {
+        let config: HashMap<String, Config> = toml::from_str(
+            r#"
+            [global]
+            author_name = "Global"
+            pretty_print = true
+            digest_algorithm = "sha512"
+
+            [repo]
+            digest_algorithm = "sha256"
+            zero_padding = 4
+            hard_links = true
+            "#,
+        )
+        .unwrap();
+
+        let config = resolve_config(&Some("repo".to_string()), config);
+
+        assert_eq!(Some("Global".to_string()), config.author_name);
+        assert_eq!(Some(true), config.pretty_print);
+        assert_eq!(Some(DigestAlgorithm::Sha256), config.digest_algorithm);
+        assert_eq!(Some(4), config.zero_padding);
+        assert_eq!(Some(true), config.hard_links);
+        assert_eq!("content", config.content_directory());
+
+        assert_eq!("[global]", config.source("author_name"));
+        assert_eq!("[global]", config.source("pretty_print"));
+        assert_eq!("[repo]", config.source("digest_algorithm"));
+        assert_eq!("[repo]", config.source("zero_padding"));
+        assert_eq!("default", config.source("content_directory"));
+    }
+
+    #[test]
+    fn reject_unsupported_digest_algorithm() {
+        let config: HashMap<String, Config> = toml::from_str(
+            r#"
+            [repo]
+            digest_algorithm = "md5"
+            "#,
+        )
+        .unwrap();
+
+        let config = resolve_config(&Some("repo".to_string()), config);
+
+        let error = config.validate().unwrap_err().to_string();
+        assert!(error.contains("sha512 or sha256"), "{}", error);
+        assert!(error.contains("[repo]"), "{}", error);
+    }
+
+    #[test]
+    fn reject_invalid_content_directory() {
+        let mut config = Config::new();
+        config.content_directory = Some("a/b".to_string());
+
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn reject_zero_timeout() {
+        let mut config = Config::new();
+        config.timeout = Some(0);
+
+        assert!(config.validate().is_err());
+
+        config.timeout = Some(30);
+
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn reject_zero_threads() {
+        let mut config = Config::new();
+        config.threads = Some(0);
+
+        assert!(config.validate().is_err());
+
+        config.threads = Some(4);
+
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn reject_zero_request_timeout() {
+        let mut config = Config::new();
+        config.request_timeout = Some(0);
+
+        assert!(config.validate().is_err());
+
+        config.request_timeout = Some(30);
+
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn resolve_s3_client_options() {
+        let config: HashMap<String, Config> = toml::from_str(
+            r#"
+            [global]
+            user_agent = "archive-sync/2.0"
+            request_timeout = 60
+
+            [repo]
+            request_metrics = true
+            bucket_staging = true
+            "#,
+        )
+        .unwrap();
+
+        let config = resolve_config(&Some("repo".to_string()), config);
+        assert_eq!(Some(true), config.bucket_staging);
+
+        let options = config.s3_client_options();
+
+        assert_eq!(Some("archive-sync/2.0".to_string()), options.user_agent);
+        assert_eq!(Some(Duration::from_secs(60)), options.request_timeout);
+        assert!(options.request_metrics);
+        assert_eq!(
+            S3ClientOptions::default(),
+            Config::new().s3_client_options()
+        );
+    }
+
+    #[test]
+    fn resolve_cloud_storage() {
+        let parse = || -> HashMap<String, Config> {
+            toml::from_str(
+                r#"
+            [azure]
+            provider = "azure"
+            account = "archive"
+            bucket = "ocfl"
+            credentials = "secret"
+
+            [gcs]
+            provider = "gcs"
+            bucket = "ocfl"
+            "#,
+            )
+            .unwrap()
+        };
+
+        let azure = resolve_config(&Some("azure".to_string()), parse());
+        assert!(azure.validate().is_ok());
+        assert_eq!(
+            Some(CloudStorage::Azure {
+                account: "archive".to_string(),
+                access_key: Some("secret".to_string()),
+            }),
+            azure.cloud_storage()
+        );
+
+        let gcs = resolve_config(&Some("gcs".to_string()), parse());
+        assert!(gcs.validate().is_ok());
+        assert_eq!(
+            Some(CloudStorage::Gcs {
+                service_account: None
+            }),
+            gcs.cloud_storage()
+        );
+
+        assert_eq!(None, Config::new().cloud_storage());
+    }
+
+    #[test]
+    fn reject_invalid_cloud_storage() {
+        let mut config = Config::new();
+        config.bucket = Some("ocfl".to_string());
+        config.provider = Some("azure".to_string());
+
+        assert!(config.validate().is_err());
+
+        config.account = Some("archive".to_string());
+
+        assert!(config.validate().is_ok());
+
+        config.provider = Some("dropbox".to_string());
+
+        assert!(config.validate().is_err());
+
+        config.provider = Some("gcs".to_string());
+        config.bucket = None;
+
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn resolve_commit_quotas() {
+        let config: HashMap<String, Config> = toml::from_str(
+            r#"
+            [global]
+            max_object_size = 1000
+            max_version_files = 10
+
+            [repo]
+            max_version_files = 20
+            max_staged_bytes = 100
+            "#,
+        )
+        .unwrap();
+
+        let quotas = resolve_config(&Some("repo".to_string()), config).commit_quotas();
+
+        assert_eq!(
+            CommitQuotas {
+                max_object_size: Some(1000),
+                max_version_files: Some(20),
+                max_staged_bytes: Some(100),
+            },
+            quotas
+        );
+        assert_eq!(CommitQuotas::default(), Config::new().commit_quotas());
+    }
+
+    #[test]
+    fn resolve_s3_write_options() {
+        let config: HashMap<String, Config> = toml::from_str(
+            r#"
+            [global]
+            content_storage_class = "standard_ia"
+            content_tags = "project=archive, owner=library"
+
+            [repo]
+            inventory_tags = "kind=inventory"
+            "#,
+        )
+        .unwrap();
+
+        let config = resolve_config(&Some("repo".to_string()), config);
+
+        let content = config.content_write_options().unwrap();
+        assert_eq!(Some("STANDARD_IA".to_string()), content.storage_class);
+        assert_eq!(
+            vec![
+                ("project".to_string(), "archive".to_string()),
+                ("owner".to_string(), "library".to_string())
+            ],
+            content.tags
+        );
+
+        let inventory = config.inventory_write_options().unwrap();
+        assert_eq!(None, inventory.storage_class);
+        assert_eq!(
+            vec![("kind".to_string(), "inventory".to_string())],
+            inventory.tags
+        );
+    }
+
+    #[test]
+    fn reject_invalid_s3_write_options() {
+        let mut config = Config::new();
+        config.inventory_storage_class = Some("COLD".to_string());
+
+        let error = config.validate().unwrap_err().to_string();
+        assert!(
+            error.contains("Unknown S3 storage class 'COLD'"),
+            "{}",
+            error
+        );
+
+        let mut config = Config::new();
+        config.content_tags = Some("project".to_string());
+
+        let error = config.validate().unwrap_err().to_string();
+        assert!(error.contains("KEY=VALUE"), "{}", error);
+    }
+
+    #[test]
+    fn set_config_values_preserving_formatting() {
+        let temp = TempDir::new().unwrap();
+        let config_file = temp.path().join("config.toml");
+
+        fs::write(
+            &config_file,
+            "# My config\n[repo]\nroot = \"/a\" # the root\n",
+        )
+        .unwrap();
+
+        set_config_value(&config_file, "repo.root", "/b").unwrap();
+        set_config_value(&config_file, "repo.zero_padding", "4").unwrap();
+        set_config_value(&config_file, "global.pretty_print", "true").unwrap();
+
+        assert_eq!(
+            "# My config\n[repo]\nroot = \"/b\" # the root\nzero_padding = 4\n\n\
+            [global]\npretty_print = true\n",
+            fs::read_to_string(&config_file).unwrap()
+        );
+
+        assert_eq!(
+            vec![
+                ("repo.root".to_string(), "/b".to_string()),
+                ("repo.zero_padding".to_string(), "4".to_string()),
+                ("global.pretty_print".to_string(), "true".to_string()),
+            ],
+            list_config_values(&config_file, None).unwrap()
+        );
+        assert_eq!(
+            vec![("global.pretty_print".to_string(), "true".to_string())],
+            list_config_values(&config_file, Some("global")).unwrap()
+        );
+
+        assert_eq!(
+            Some("/b".to_string()),
+            get_config_value(&config_file, "repo.root").unwrap()
+        );
+        assert_eq!(None, get_config_value(&config_file, "repo.bucket").unwrap());
+
+        unset_config_value(&config_file, "repo.root").unwrap();
+        assert_eq!(None, get_config_value(&config_file, "repo.root").unwrap());
+    }
+
+    #[test]
+    fn reject_invalid_config_values() {
+        let temp = TempDir::new().unwrap();
+        let config_file = temp.path().join("config.toml");
+
+        fs::write(&config_file, "[repo]\nroot = \"/a\"\n").unwrap();
+
+        assert!(set_config_value(&config_file, "repo.pretty_print", "yes").is_err());
+        assert!(set_config_value(&config_file, "repo.zero_padding", "-1").is_err());
+        assert!(set_config_value(&config_file, "repo.digest_algorithm", "md5").is_err());
+        assert!(set_config_value(&config_file, "repo.content_directory", "a/b").is_err());
+        assert!(set_config_value(&config_file, "repo.unknown", "value").is_err());
+        assert!(set_config_value(&config_file, "root", "/b").is_err());
+
+        assert_eq!(
+            "[repo]\nroot = \"/a\"\n",
+            fs::read_to_string(&config_file).unwrap()
+        );
+    }
+}
@@ -1,3 +1,6 @@
+#[cfg(feature = "cli")]
 pub mod cmd;
 pub mod config;
 pub mod ocfl;
+#[cfg(feature = "python")]
+mod python;
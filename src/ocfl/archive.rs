@@ -0,0 +1,154 @@
+//! Zip archives of whole OCFL objects. An archive contains every file in an object's root,
+//! including all of its versions, inventories, sidecars, logs, and extensions, at the same paths
+//! that they have relative the object root. Archives are always written in the zip64 format so
+//! that objects, and files, larger than 4 GiB can be transferred.
+//!
+//! ```text
+//! object.zip
+//! ├── 0=ocfl_object_1.0
+//! ├── inventory.json
+//! ├── inventory.json.sha512
+//! └── v1/
+//!     ├── inventory.json
+//!     ├── inventory.json.sha512
+//!     └── content/
+//!         └── file.txt
+//! ```
+
+use std::fs::{self, File};
+use std::io::{self, Read, Seek, Write};
+use std::path::Path;
+
+use zip::result::ZipError;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::ocfl::consts::INVENTORY_FILE;
+use crate::ocfl::error::{Result, RocflError};
+use crate::ocfl::inventory::Inventory;
+use crate::ocfl::validate::parse_inventory;
+use crate::ocfl::VersionNum;
+
+/// The outcome of exporting an object to a zip archive
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct ZipExport {
+    /// The ID of the object
+    pub object_id: String,
+    /// The object's most recent version
+    pub head: VersionNum,
+    /// The number of files that were written to the archive
+    pub files: usize,
+}
+
+/// The outcome of importing an object from a zip archive
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct ZipImport {
+    /// The ID of the object
+    pub object_id: String,
+    /// The object's most recent version
+    pub head: VersionNum,
+    /// The number of files that were extracted from the archive
+    pub files: usize,
+}
+
+/// Writes object files to a zip archive
+pub(crate) struct ZipObjectWriter<W: Write + Seek> {
+    writer: ZipWriter<W>,
+    files: usize,
+}
+
+impl<W: Write + Seek> ZipObjectWriter<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer: ZipWriter::new(writer),
+            files: 0,
+        }
+    }
+
+    /// Adds a file to the archive at the path, relative the object root. `write` is called to
+    /// write the file's content.
+    pub fn add_file(
+        &mut self,
+        path: &str,
+        write: impl FnOnce(&mut dyn Write) -> Result<()>,
+    ) -> Result<()> {
+        let options = SimpleFileOptions::default()
+            .compression_method(CompressionMethod::Deflated)
+            .large_file(true);
+
+        self.writer.start_file(path, options).map_err(zip_error)?;
+        write(&mut self.writer)?;
+        self.files += 1;
+
+        Ok(())
+    }
+
+    /// Writes the archive's central directory, and returns the number of files in the archive
+    pub fn finish(self) -> Result<usize> {
+        self.writer.finish().map_err(zip_error)?;
+        Ok(self.files)
+    }
+}
+
+/// Reads the root inventory of the object in the archive
+pub(crate) fn read_inventory<R: Read + Seek>(archive: &mut ZipArchive<R>) -> Result<Inventory> {
+    let mut bytes = Vec::new();
+
+    match archive.by_name(INVENTORY_FILE) {
+        Ok(mut file) => file.read_to_end(&mut bytes)?,
+        Err(ZipError::FileNotFound) => {
+            return Err(RocflError::InvalidValue(format!(
+                "The archive does not contain an object; {} was not found at its root",
+                INVENTORY_FILE
+            )))
+        }
+        Err(e) => return Err(zip_error(e)),
+    };
+
+    parse_inventory(&bytes).0
+}
+
+/// Extracts every file in the archive into `dst`. Entries that are not regular files are
+/// skipped, and entries with paths that would be extracted outside of `dst` are rejected.
+/// Returns the number of files that were extracted.
+pub(crate) fn extract<R: Read + Seek>(archive: &mut ZipArchive<R>, dst: &Path) -> Result<usize> {
+    let mut files = 0;
+
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i).map_err(zip_error)?;
+
+        let relative = entry.enclosed_name().ok_or_else(|| {
+            RocflError::InvalidValue(format!(
+                "The archive contains an entry with an unsafe path: {}",
+                entry.name()
+            ))
+        })?;
+
+        if !entry.is_file() {
+            continue;
+        }
+
+        let path = dst.join(relative);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let mut file =
+            File::create(&path).map_err(|e| RocflError::io_path("create file", &path, e))?;
+        io::copy(&mut entry, &mut file)?;
+        files += 1;
+    }
+
+    Ok(files)
+}
+
+pub(crate) fn open<R: Read + Seek>(reader: R) -> Result<ZipArchive<R>> {
+    ZipArchive::new(reader).map_err(zip_error)
+}
+
+fn zip_error(e: ZipError) -> RocflError {
+    match e {
+        ZipError::Io(e) => RocflError::Io(e),
+        e => RocflError::InvalidValue(format!("Invalid zip archive: {}", e)),
+    }
+}
@@ -0,0 +1,152 @@
+//! Structured log records of the changes that are made to a repository
+
+use std::time::Instant;
+
+use log::info;
+use serde::Serialize;
+
+use crate::ocfl::{SpecVersion, VersionNum};
+
+/// The log target that repository mutation records are written to. The message of every record
+/// logged to this target is a JSON object that describes a single mutation.
+pub const AUDIT_LOG_TARGET: &str = "rocfl::audit";
+
+/// Records a repository mutation to the audit log when it is dropped. The mutation is recorded
+/// as failed unless `succeeded()` or `partially_succeeded()` is called.
+pub(crate) struct Audit {
+    operation: &'static str,
+    object_id: Option<String>,
+    alias: Option<String>,
+    version: Option<VersionNum>,
+    spec_version: Option<SpecVersion>,
+    paths: Vec<String>,
+    destination: Option<String>,
+    start: Instant,
+    outcome: Outcome,
+    dry_run: bool,
+}
+
+/// Whether a mutation made all, some, or none of its changes
+#[derive(Serialize, Copy, Clone)]
+#[serde(rename_all = "lowercase")]
+enum Outcome {
+    Success,
+    Partial,
+    Failure,
+}
+
+#[derive(Serialize)]
+struct MutationRecord<'a> {
+    operation: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    object_id: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    alias: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    spec_version: Option<&'a str>,
+    #[serde(skip_serializing_if = "<[String]>::is_empty")]
+    paths: &'a [String],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    destination: Option<&'a str>,
+    duration_ms: u128,
+    outcome: Outcome,
+}
+
+impl Audit {
+    /// Starts timing a new mutation
+    pub fn start(operation: &'static str) -> Self {
+        Self {
+            operation,
+            object_id: None,
+            alias: None,
+            version: None,
+            spec_version: None,
+            paths: Vec::new(),
+            destination: None,
+            start: Instant::now(),
+            outcome: Outcome::Failure,
+            dry_run: false,
+        }
+    }
+
+    pub fn object_id(mut self, object_id: &str) -> Self {
+        self.set_object_id(object_id);
+        self
+    }
+
+    pub fn alias(mut self, alias: &str) -> Self {
+        self.alias = Some(alias.to_string());
+        self
+    }
+
+    pub fn spec_version(mut self, spec_version: SpecVersion) -> Self {
+        self.spec_version = Some(spec_version);
+        self
+    }
+
+    pub fn paths<P: AsRef<str>>(mut self, paths: impl IntoIterator<Item = P>) -> Self {
+        self.paths
+            .extend(paths.into_iter().map(|p| p.as_ref().to_string()));
+        self
+    }
+
+    pub fn destination(mut self, destination: &str) -> Self {
+        self.destination = Some(destination.to_string());
+        self
+    }
+
+    /// The name of the operation that is being recorded
+    pub fn operation(&self) -> &'static str {
+        self.operation
+    }
+
+    /// Mutations that are only planned in dry-run mode are not recorded
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    pub fn set_object_id(&mut self, object_id: &str) {
+        self.object_id = Some(object_id.to_string());
+    }
+
+    pub fn set_version(&mut self, version: VersionNum) {
+        self.version = Some(version);
+    }
+
+    /// Marks the mutation as successful and writes its record
+    pub fn succeeded(mut self) {
+        self.outcome = Outcome::Success;
+    }
+
+    /// Marks the mutation as having made only some of its changes and writes its record
+    pub fn partially_succeeded(mut self) {
+        self.outcome = Outcome::Partial;
+    }
+}
+
+impl Drop for Audit {
+    fn drop(&mut self) {
+        if self.dry_run {
+            return;
+        }
+
+        let record = MutationRecord {
+            operation: self.operation,
+            object_id: self.object_id.as_deref(),
+            alias: self.alias.as_deref(),
+            version: self.version.map(|v| v.to_string()),
+            spec_version: self.spec_version.map(|v| v.version()),
+            paths: &self.paths,
+            destination: self.destination.as_deref(),
+            duration_ms: self.start.elapsed().as_millis(),
+            outcome: self.outcome,
+        };
+
+        if let Ok(json) = serde_json::to_string(&record) {
+            info!(target: AUDIT_LOG_TARGET, "{}", json);
+        }
+    }
+}
@@ -0,0 +1,171 @@
+//! Callouts that inspect the files a commit adds to an object, such as format identification
+//! or malware scanning, and that may veto the commit
+
+use std::path::PathBuf;
+use std::process::Command;
+
+use log::info;
+
+use crate::ocfl::digest::HexDigest;
+use crate::ocfl::error::{Result, RocflError};
+use crate::ocfl::{DigestAlgorithm, InventoryPath, LogicalPath};
+
+/// A file that a commit adds to an object, as it is presented to a `CommitCallout`
+#[derive(Debug, Clone)]
+pub struct CalloutFile {
+    /// The ID of the object that is being committed
+    pub object_id: String,
+    /// The logical paths in the new version that the file's content is mapped to
+    pub logical_paths: Vec<LogicalPath>,
+    /// The location of the staged file on local disk. The file must not be modified.
+    pub path: PathBuf,
+    /// The digest of the file's content
+    pub digest: HexDigest,
+    /// The algorithm that the digest was calculated with
+    pub digest_algorithm: DigestAlgorithm,
+}
+
+/// The decision a `CommitCallout` makes about a file
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub enum CalloutVerdict {
+    /// The file may be committed
+    Accept,
+    /// The file must not be committed, for the given reason
+    Reject(String),
+}
+
+/// Inspects each file that a commit adds to an object before anything is written to the
+/// repository. If any file is rejected, the commit fails with `RocflError::CommitRejected`, and
+/// the object's staged changes are left untouched. Files that were committed in earlier versions
+/// are not inspected again.
+pub trait CommitCallout: Send + Sync {
+    /// Inspects a staged file. Returning an error aborts the commit with that error.
+    fn inspect(&self, file: &CalloutFile) -> Result<CalloutVerdict>;
+}
+
+impl<F> CommitCallout for F
+where
+    F: Fn(&CalloutFile) -> Result<CalloutVerdict> + Send + Sync,
+{
+    fn inspect(&self, file: &CalloutFile) -> Result<CalloutVerdict> {
+        self(file)
+    }
+}
+
+/// A `CommitCallout` that runs an external command once per file. The path to the staged file
+/// is appended to the command's arguments, and the object ID, logical paths, digest, and digest
+/// algorithm are passed in the `ROCFL_OBJECT_ID`, `ROCFL_LOGICAL_PATHS` (newline separated),
+/// `ROCFL_DIGEST`, and `ROCFL_DIGEST_ALGORITHM` environment variables. The file is rejected if
+/// the command exits with a non-zero status, and the command's output is used as the reason.
+#[derive(Debug, Clone)]
+pub struct CommandCallout {
+    program: String,
+    args: Vec<String>,
+}
+
+impl CommandCallout {
+    /// Creates a callout from a command line. The program and its arguments are separated by
+    /// whitespace; quoting is not supported.
+    pub fn new(command: &str) -> Result<Self> {
+        let mut parts = command.split_whitespace().map(str::to_string);
+
+        match parts.next() {
+            Some(program) => Ok(Self {
+                program,
+                args: parts.collect(),
+            }),
+            None => Err(RocflError::InvalidConfiguration(
+                "The commit callout command must not be blank".to_string(),
+            )),
+        }
+    }
+}
+
+impl CommitCallout for CommandCallout {
+    fn inspect(&self, file: &CalloutFile) -> Result<CalloutVerdict> {
+        info!(
+            "Running commit callout {} on {}",
+            self.program,
+            file.path.to_string_lossy()
+        );
+
+        let logical_paths = file
+            .logical_paths
+            .iter()
+            .map(|path| path.as_str())
+            .collect::<Vec<&str>>()
+            .join("\n");
+
+        let output = Command::new(&self.program)
+            .args(&self.args)
+            .arg(&file.path)
+            .env("ROCFL_OBJECT_ID", &file.object_id)
+            .env("ROCFL_LOGICAL_PATHS", logical_paths)
+            .env("ROCFL_DIGEST", file.digest.as_ref())
+            .env("ROCFL_DIGEST_ALGORITHM", file.digest_algorithm.to_string())
+            .output()
+            .map_err(|e| RocflError::io_path("run commit callout", &self.program, e))?;
+
+        if output.status.success() {
+            return Ok(CalloutVerdict::Accept);
+        }
+
+        let mut reason = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if !stderr.trim().is_empty() {
+            if !reason.is_empty() {
+                reason.push('\n');
+            }
+            reason.push_str(stderr.trim());
+        }
+        if reason.is_empty() {
+            reason = format!("{} exited with {}", self.program, output.status);
+        }
+
+        Ok(CalloutVerdict::Reject(reason))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+    use std::fs;
+
+    use assert_fs::TempDir;
+
+    use super::{CalloutFile, CalloutVerdict, CommandCallout, CommitCallout};
+    use crate::ocfl::{DigestAlgorithm, LogicalPath};
+
+    #[test]
+    fn reject_blank_command() {
+        assert!(CommandCallout::new("  ").is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn command_verdict_follows_exit_status() {
+        let temp = TempDir::new().unwrap();
+        let empty = temp.path().join("empty");
+        let full = temp.path().join("full");
+        fs::write(&empty, "").unwrap();
+        fs::write(&full, "content").unwrap();
+
+        let callout = CommandCallout::new("test -s").unwrap();
+        let file = |path| CalloutFile {
+            object_id: "obj".to_string(),
+            logical_paths: vec![LogicalPath::try_from("a/b.txt").unwrap()],
+            path,
+            digest: "abc".into(),
+            digest_algorithm: DigestAlgorithm::Sha512,
+        };
+
+        assert_eq!(
+            CalloutVerdict::Accept,
+            callout.inspect(&file(full)).unwrap()
+        );
+        match callout.inspect(&file(empty)).unwrap() {
+            CalloutVerdict::Reject(reason) => assert!(reason.starts_with("test exited with")),
+            verdict => panic!("Expected rejection; found {:?}", verdict),
+        }
+    }
+}
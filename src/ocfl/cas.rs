@@ -0,0 +1,177 @@
+//! Content-addressed exports write the content of object versions to a directory organized by
+//! digest, so that content that is shared by many objects is only transferred once. Each object
+//! is described by a mapping manifest that maps its logical paths to digests.
+//!
+//! ```text
+//! DST/
+//! ├── content/
+//! │   └── sha512/
+//! │       └── 4d/
+//! │           └── 4dff4ea3...
+//! └── objects/
+//!     └── urn%3Aexample%3Aobj.json
+//! ```
+
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use once_cell::sync::Lazy;
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use serde::{Deserialize, Serialize};
+
+use crate::ocfl::digest::HexDigest;
+use crate::ocfl::error::{Result, RocflError};
+use crate::ocfl::{DigestAlgorithm, VersionNum};
+
+const CONTENT_DIR: &str = "content";
+const OBJECTS_DIR: &str = "objects";
+const PART_EXTENSION: &str = "part";
+
+static OBJECT_ID_ENCODE_SET: Lazy<AsciiSet> =
+    Lazy::new(|| NON_ALPHANUMERIC.remove(b'-').remove(b'_').remove(b'.'));
+
+/// The mapping manifest of an object version in a content-addressed export
+#[derive(Deserialize, Serialize, Debug, Eq, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CasManifest {
+    /// The ID of the object
+    pub id: String,
+    /// The version of the object that was exported
+    pub version: VersionNum,
+    /// The algorithm of the digests the content is addressed by
+    pub digest_algorithm: DigestAlgorithm,
+    /// Maps every logical path in the version to the digest of its content
+    pub files: BTreeMap<String, String>,
+}
+
+/// The outcome of exporting an object version
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct CasExport {
+    /// The ID of the object
+    pub object_id: String,
+    /// The version of the object that was exported
+    pub version_num: VersionNum,
+    /// The number of content files that were written
+    pub files_written: usize,
+    /// The number of content files that were skipped because they were already in the export
+    pub files_skipped: usize,
+    /// The number of bytes that were written
+    pub bytes_written: u64,
+}
+
+/// The path to the content file with the specified digest, within the export at `root`
+pub(crate) fn content_path(root: &Path, algorithm: DigestAlgorithm, digest: &HexDigest) -> PathBuf {
+    let digest = digest.as_ref().to_ascii_lowercase();
+    root.join(CONTENT_DIR)
+        .join(algorithm.to_string().replace('/', "-"))
+        .join(&digest[..2.min(digest.len())])
+        .join(digest)
+}
+
+/// The path to an object's mapping manifest, within the export at `root`
+pub(crate) fn manifest_path(root: &Path, object_id: &str) -> PathBuf {
+    let encoded = utf8_percent_encode(object_id, &OBJECT_ID_ENCODE_SET).to_string();
+    root.join(OBJECTS_DIR).join(format!("{}.json", encoded))
+}
+
+/// Reads an object's mapping manifest from the export at `root`, if it exists
+pub(crate) fn read_manifest(root: &Path, object_id: &str) -> Result<Option<CasManifest>> {
+    let path = manifest_path(root, object_id);
+    match fs::read(&path) {
+        Ok(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(RocflError::io_path("read export manifest", &path, e)),
+    }
+}
+
+/// Writes an object's mapping manifest to the export at `root`. The manifest is written last, so
+/// that an export that is interrupted does not appear to be complete.
+pub(crate) fn write_manifest(root: &Path, manifest: &CasManifest) -> Result<()> {
+    let path = manifest_path(root, &manifest.id);
+    write_atomic(&path, |writer| {
+        serde_json::to_writer_pretty(&mut *writer, manifest)?;
+        writer.write_all(b"\n")?;
+        Ok(())
+    })
+    .map(|_| ())
+}
+
+/// Writes a file by writing to a temporary file next to it and then renaming it, so that files
+/// that exist are always complete. Returns the number of bytes written.
+pub(crate) fn write_atomic(
+    path: &Path,
+    write: impl FnOnce(&mut dyn Write) -> Result<()>,
+) -> Result<u64> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| RocflError::io_path("create export directory", parent, e))?;
+    }
+
+    let part = path.with_extension(PART_EXTENSION);
+    let file =
+        File::create(&part).map_err(|e| RocflError::io_path("create export file", &part, e))?;
+    let mut writer = CountingWriter {
+        inner: BufWriter::new(file),
+        count: 0,
+    };
+
+    let result = write(&mut writer).and_then(|_| Ok(writer.flush()?));
+
+    if let Err(e) = result {
+        drop(writer);
+        let _ = fs::remove_file(&part);
+        return Err(e);
+    }
+
+    let count = writer.count;
+    drop(writer);
+
+    fs::rename(&part, path).map_err(|e| RocflError::io_path("move export file", path, e))?;
+
+    Ok(count)
+}
+
+struct CountingWriter<W: Write> {
+    inner: W,
+    count: u64,
+}
+
+impl<W: Write> Write for CountingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.count += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::{content_path, manifest_path};
+    use crate::ocfl::DigestAlgorithm;
+
+    #[test]
+    fn content_addressed_paths() {
+        let root = Path::new("export");
+
+        assert_eq!(
+            Path::new("export/content/sha512/ab/abcdef"),
+            content_path(root, DigestAlgorithm::Sha512, &"ABCDEF".into())
+        );
+        assert_eq!(
+            Path::new("export/content/sha512-256/01/0123"),
+            content_path(root, DigestAlgorithm::Sha512_256, &"0123".into())
+        );
+        assert_eq!(
+            Path::new("export/objects/urn%3Aexample%3Aobj-1.json"),
+            manifest_path(root, "urn:example:obj-1")
+        );
+    }
+}
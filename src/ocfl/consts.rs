@@ -39,6 +39,9 @@ pub const EXTENSIONS_CONFIG_FILE: &str = "config.json";
 
 pub const DEFAULT_CONTENT_DIR: &str = "content";
 
+/// The maximum length of a path on Windows when extended-length paths are not used
+pub const WINDOWS_MAX_PATH: usize = 260;
+
 pub const MUTABLE_HEAD_EXT_DIR: &str = "extensions/0005-mutable-head";
 pub const MUTABLE_HEAD_INVENTORY_FILE: &str = "extensions/0005-mutable-head/head/inventory.json";
 
@@ -49,18 +52,63 @@ pub const HASHED_NTUPLE_LAYOUT_EXTENSION: &str = "0004-hashed-n-tuple-storage-la
 pub const MUTABLE_HEAD_EXTENSION: &str = "0005-mutable-head";
 pub const FLAT_OMIT_PREFIX_LAYOUT_EXTENSION: &str = "0006-flat-omit-prefix-storage-layout";
 pub const NTUPLE_OMIT_PREFIX_LAYOUT_EXTENSION: &str = "0007-n-tuple-omit-prefix-storage-layout";
+/// Registers digest algorithms that are not defined by the OCFL spec, so that they may be used
+/// in fixity blocks
+pub const DIGEST_ALGORITHMS_EXTENSION: &str = "0009-digest-algorithms";
 pub const ROCFL_STAGING_EXTENSION: &str = "rocfl-staging";
 pub const ROCFL_LOCKS_EXTENSION: &str = "rocfl-locks";
+pub const ROCFL_OBJECT_INDEX_EXTENSION: &str = "rocfl-object-index";
+pub const ROCFL_ALIASES_EXTENSION: &str = "rocfl-object-aliases";
+pub const ROCFL_REPO_DEFAULTS_EXTENSION: &str = "rocfl-repo-defaults";
+pub const ROCFL_VALIDATION_HISTORY_EXTENSION: &str = "rocfl-validation-history";
+pub const ROCFL_FROZEN_EXTENSION: &str = "rocfl-frozen";
+pub const ROCFL_ENCRYPTION_EXTENSION: &str = "rocfl-encryption";
+pub const ROCFL_CONTENT_INDEX_EXTENSION: &str = "rocfl-content-index";
+
+pub const OBJECT_INDEX_FILE: &str = "index.json";
+pub const ALIAS_INDEX_FILE: &str = "aliases.json";
+pub const VALIDATION_HISTORY_FILE: &str = "history.json";
+pub const FROZEN_FILE: &str = "frozen.json";
+/// The logical path of the file that the original modification times of external files are
+/// recorded in, when `OcflRepo::set_preserve_timestamps()` is enabled
+pub const ORIGINAL_TIMESTAMPS_FILE: &str = "metadata/rocfl-timestamps.json";
+/// The file, relative an object's logs directory, that audit trail entries are appended to
+pub const AUDIT_TRAIL_FILE: &str = "rocfl-audit-trail.jsonl";
+/// The file, relative an object's logs directory, that inventory signatures are appended to
+pub const SIGNATURES_FILE: &str = "rocfl-signatures.jsonl";
 
 pub static SUPPORTED_EXTENSIONS: Lazy<HashSet<&str>> = Lazy::new(|| {
-    let mut set = HashSet::with_capacity(8);
+    let mut set = HashSet::with_capacity(13);
     set.insert(FLAT_DIRECT_LAYOUT_EXTENSION);
     set.insert(HASHED_NTUPLE_OBJECT_ID_LAYOUT_EXTENSION);
     set.insert(HASHED_NTUPLE_LAYOUT_EXTENSION);
     set.insert(MUTABLE_HEAD_EXTENSION);
     set.insert(FLAT_OMIT_PREFIX_LAYOUT_EXTENSION);
     set.insert(NTUPLE_OMIT_PREFIX_LAYOUT_EXTENSION);
+    set.insert(DIGEST_ALGORITHMS_EXTENSION);
     set.insert(ROCFL_STAGING_EXTENSION);
     set.insert(ROCFL_LOCKS_EXTENSION);
+    set.insert(ROCFL_OBJECT_INDEX_EXTENSION);
+    set.insert(ROCFL_ALIASES_EXTENSION);
+    set.insert(ROCFL_REPO_DEFAULTS_EXTENSION);
+    set.insert(ROCFL_VALIDATION_HISTORY_EXTENSION);
+    set.insert(ROCFL_FROZEN_EXTENSION);
+    set.insert(ROCFL_ENCRYPTION_EXTENSION);
+    set.insert(ROCFL_CONTENT_INDEX_EXTENSION);
     set
 });
+
+/// The S3 storage classes that objects may be written to
+pub const S3_STORAGE_CLASSES: [&str; 9] = [
+    "STANDARD",
+    "REDUCED_REDUNDANCY",
+    "STANDARD_IA",
+    "ONEZONE_IA",
+    "INTELLIGENT_TIERING",
+    "GLACIER_IR",
+    "GLACIER",
+    "DEEP_ARCHIVE",
+    "OUTPOSTS",
+];
+/// The maximum number of tags that S3 allows on an object
+pub const S3_MAX_TAGS: usize = 10;
@@ -15,7 +15,7 @@ use sha1::Sha1;
 use sha2::{Sha256, Sha512, Sha512_256};
 use strum_macros::{Display as EnumDisplay, EnumString};
 
-use crate::ocfl::error::Result;
+use crate::ocfl::error::{Result, RocflError};
 
 type Blake2b160 = Blake2b<U20>;
 type Blake2b256 = Blake2b<U32>;
@@ -57,19 +57,27 @@ pub enum DigestAlgorithm {
 
 /// Reader wrapper that calculates a digest while reading
 pub struct DigestReader<R: Read> {
+    algorithm: DigestAlgorithm,
     digest: Box<dyn DynDigest>,
     inner: R,
 }
 
 /// Writer wrapper that calculates a digest while writing
 pub struct DigestWriter<W: Write> {
+    algorithm: DigestAlgorithm,
     digest: Box<dyn DynDigest>,
     inner: W,
 }
 
+/// Reader wrapper that calculates multiple digests while reading
+pub struct MultiDigestReader<R: Read> {
+    digests: MultiDigest,
+    inner: R,
+}
+
 /// Writer wrapper that calculates multiple digests while writing
 pub struct MultiDigestWriter<W: Write> {
-    digests: HashMap<DigestAlgorithm, Box<dyn DynDigest>>,
+    digests: MultiDigest,
     inner: W,
 }
 
@@ -77,6 +85,8 @@ pub struct MultiDigestWriter<W: Write> {
 #[derive(Deserialize, Serialize, Debug, Eq, Clone)]
 pub struct HexDigest(String);
 
+struct MultiDigest(HashMap<DigestAlgorithm, Box<dyn DynDigest>>);
+
 impl DigestAlgorithm {
     /// Hashes the input and returns its hex encoded digest
     pub fn hash_hex(&self, data: &mut impl Read) -> Result<HexDigest> {
@@ -85,14 +95,35 @@ impl DigestAlgorithm {
         Ok(hasher.finalize_hex())
     }
 
+    /// Copies the reader into the writer, and returns the hex encoded digest of the bytes that
+    /// were copied
+    pub fn hash_copy(&self, reader: &mut impl Read, writer: &mut impl Write) -> Result<HexDigest> {
+        let mut hasher = self.writer(writer);
+        io::copy(reader, &mut hasher)?;
+        hasher.flush()?;
+        Ok(hasher.finalize_hex())
+    }
+
+    /// Returns true if the algorithm is not defined by the OCFL spec, and is instead registered
+    /// by the `0009-digest-algorithms` extension
+    pub fn is_extension_algorithm(&self) -> bool {
+        matches!(
+            self,
+            DigestAlgorithm::Sha512_256
+                | DigestAlgorithm::Blake2b160
+                | DigestAlgorithm::Blake2b256
+                | DigestAlgorithm::Blake2b384
+        )
+    }
+
     /// Wraps the specified reader in a `DigestReader`
     pub fn reader<R: Read>(&self, reader: R) -> DigestReader<R> {
-        DigestReader::new(self.new_digest(), reader)
+        DigestReader::new(*self, reader)
     }
 
     /// Wraps the specified writer in a `DigestWriter`
     pub fn writer<W: Write>(&self, writer: W) -> DigestWriter<W> {
-        DigestWriter::new(self.new_digest(), writer)
+        DigestWriter::new(*self, writer)
     }
 
     fn new_digest(&self) -> Box<dyn DynDigest> {
@@ -111,15 +142,33 @@ impl DigestAlgorithm {
 }
 
 impl<R: Read> DigestReader<R> {
-    pub fn new(digest: Box<dyn DynDigest>, reader: R) -> Self {
+    pub fn new(algorithm: DigestAlgorithm, reader: R) -> Self {
         Self {
-            digest,
+            algorithm,
+            digest: algorithm.new_digest(),
             inner: reader,
         }
     }
 
+    /// The algorithm the digest is calculated with
+    pub fn algorithm(&self) -> DigestAlgorithm {
+        self.algorithm
+    }
+
+    pub fn inner(&self) -> &R {
+        &self.inner
+    }
+
+    pub fn finalize_bytes(self) -> Vec<u8> {
+        self.digest.finalize().to_vec()
+    }
+
     pub fn finalize_hex(self) -> HexDigest {
-        self.digest.finalize().to_vec().into()
+        self.finalize_bytes().into()
+    }
+
+    pub fn finalize_base64(self) -> String {
+        base64::encode(self.finalize_bytes())
     }
 }
 
@@ -136,15 +185,33 @@ impl<R: Read> Read for DigestReader<R> {
 }
 
 impl<W: Write> DigestWriter<W> {
-    pub fn new(digest: Box<dyn DynDigest>, writer: W) -> Self {
+    pub fn new(algorithm: DigestAlgorithm, writer: W) -> Self {
         Self {
-            digest,
+            algorithm,
+            digest: algorithm.new_digest(),
             inner: writer,
         }
     }
 
+    /// The algorithm the digest is calculated with
+    pub fn algorithm(&self) -> DigestAlgorithm {
+        self.algorithm
+    }
+
+    pub fn inner(&self) -> &W {
+        &self.inner
+    }
+
+    pub fn finalize_bytes(self) -> Vec<u8> {
+        self.digest.finalize().to_vec()
+    }
+
     pub fn finalize_hex(self) -> HexDigest {
-        self.digest.finalize().to_vec().into()
+        self.finalize_bytes().into()
+    }
+
+    pub fn finalize_base64(self) -> String {
+        base64::encode(self.finalize_bytes())
     }
 }
 
@@ -164,15 +231,47 @@ impl<W: Write> Write for DigestWriter<W> {
     }
 }
 
-impl<W: Write> MultiDigestWriter<W> {
-    pub fn new(algorithms: &[DigestAlgorithm], writer: W) -> Self {
-        let mut digests = HashMap::with_capacity(algorithms.len());
-        for algorithm in algorithms {
-            digests.insert(*algorithm, algorithm.new_digest());
+impl<R: Read> MultiDigestReader<R> {
+    pub fn new(algorithms: &[DigestAlgorithm], reader: R) -> Self {
+        Self {
+            digests: MultiDigest::new(algorithms),
+            inner: reader,
         }
+    }
+
+    pub fn inner(&self) -> &R {
+        &self.inner
+    }
+
+    pub fn finalize_bytes(self) -> HashMap<DigestAlgorithm, Vec<u8>> {
+        self.digests.finalize_bytes()
+    }
+
+    pub fn finalize_hex(self) -> HashMap<DigestAlgorithm, HexDigest> {
+        self.digests.finalize_hex()
+    }
 
+    pub fn finalize_base64(self) -> HashMap<DigestAlgorithm, String> {
+        self.digests.finalize_base64()
+    }
+}
+
+impl<R: Read> Read for MultiDigestReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let result = self.inner.read(buf)?;
+
+        if result > 0 {
+            self.digests.update(&buf[0..result]);
+        }
+
+        Ok(result)
+    }
+}
+
+impl<W: Write> MultiDigestWriter<W> {
+    pub fn new(algorithms: &[DigestAlgorithm], writer: W) -> Self {
         Self {
-            digests,
+            digests: MultiDigest::new(algorithms),
             inner: writer,
         }
     }
@@ -181,12 +280,16 @@ impl<W: Write> MultiDigestWriter<W> {
         &self.inner
     }
 
+    pub fn finalize_bytes(self) -> HashMap<DigestAlgorithm, Vec<u8>> {
+        self.digests.finalize_bytes()
+    }
+
     pub fn finalize_hex(self) -> HashMap<DigestAlgorithm, HexDigest> {
-        let mut results = HashMap::with_capacity(self.digests.len());
-        for (algorithm, digest) in self.digests {
-            results.insert(algorithm, digest.finalize().to_vec().into());
-        }
-        results
+        self.digests.finalize_hex()
+    }
+
+    pub fn finalize_base64(self) -> HashMap<DigestAlgorithm, String> {
+        self.digests.finalize_base64()
     }
 }
 
@@ -195,10 +298,7 @@ impl<W: Write> Write for MultiDigestWriter<W> {
         let result = self.inner.write(buf)?;
 
         if result > 0 {
-            let part = &buf[0..result];
-            self.digests
-                .values_mut()
-                .for_each(|digest| digest.update(part));
+            self.digests.update(&buf[0..result]);
         }
 
         Ok(result)
@@ -209,6 +309,54 @@ impl<W: Write> Write for MultiDigestWriter<W> {
     }
 }
 
+impl MultiDigest {
+    fn new(algorithms: &[DigestAlgorithm]) -> Self {
+        let mut digests = HashMap::with_capacity(algorithms.len());
+        for algorithm in algorithms {
+            digests.insert(*algorithm, algorithm.new_digest());
+        }
+        Self(digests)
+    }
+
+    fn update(&mut self, part: &[u8]) {
+        self.0.values_mut().for_each(|digest| digest.update(part));
+    }
+
+    fn finalize_bytes(self) -> HashMap<DigestAlgorithm, Vec<u8>> {
+        self.0
+            .into_iter()
+            .map(|(algorithm, digest)| (algorithm, digest.finalize().to_vec()))
+            .collect()
+    }
+
+    fn finalize_hex(self) -> HashMap<DigestAlgorithm, HexDigest> {
+        self.finalize_bytes()
+            .into_iter()
+            .map(|(algorithm, bytes)| (algorithm, bytes.into()))
+            .collect()
+    }
+
+    fn finalize_base64(self) -> HashMap<DigestAlgorithm, String> {
+        self.finalize_bytes()
+            .into_iter()
+            .map(|(algorithm, bytes)| (algorithm, base64::encode(bytes)))
+            .collect()
+    }
+}
+
+impl HexDigest {
+    /// Decodes the hex digest into its raw bytes
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        hex::decode(&self.0)
+            .map_err(|e| RocflError::InvalidValue(format!("Invalid hex digest {}: {}", self.0, e)))
+    }
+
+    /// Re-encodes the hex digest as base64, as is expected by, for example, `Content-MD5` headers
+    pub fn to_base64(&self) -> Result<String> {
+        Ok(base64::encode(self.to_bytes()?))
+    }
+}
+
 impl From<Vec<u8>> for HexDigest {
     fn from(bytes: Vec<u8>) -> Self {
         Self(hex::encode(bytes))
@@ -298,7 +446,7 @@ impl Display for HexDigest {
 mod tests {
     use std::io;
 
-    use crate::ocfl::digest::MultiDigestWriter;
+    use crate::ocfl::digest::{HexDigest, MultiDigestReader, MultiDigestWriter};
     use crate::ocfl::error::Result;
     use crate::ocfl::DigestAlgorithm;
 
@@ -389,6 +537,52 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn calculate_multiple_digests_while_reading() -> Result<()> {
+        let input = "testing\n".to_string();
+        let mut output: Vec<u8> = Vec::new();
+
+        let mut reader = MultiDigestReader::new(
+            &[DigestAlgorithm::Md5, DigestAlgorithm::Sha256],
+            input.as_bytes(),
+        );
+
+        io::copy(&mut reader, &mut output)?;
+
+        let actual = reader.finalize_base64();
+
+        assert_eq!(input, String::from_utf8(output).unwrap());
+        assert_eq!(
+            "6xoyJ83D/tuuwv44v2wESg==",
+            actual.get(&DigestAlgorithm::Md5).unwrap()
+        );
+        assert_eq!(
+            "EqYfThc/s6EcBdZHH3Ryj3YjG0pfzZZnzvOvh6OuTcI=",
+            actual.get(&DigestAlgorithm::Sha256).unwrap()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn copy_while_hashing() -> Result<()> {
+        let input = "testing\n".to_string();
+        let mut output: Vec<u8> = Vec::new();
+
+        let digest = DigestAlgorithm::Md5.hash_copy(&mut input.as_bytes(), &mut output)?;
+
+        assert_eq!(input, String::from_utf8(output).unwrap());
+        assert_eq!(HexDigest::from("EB1A3227CDC3FEDBAEC2FE38BF6C044A"), digest);
+        assert_eq!("6xoyJ83D/tuuwv44v2wESg==", digest.to_base64()?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn reject_invalid_hex_digest() {
+        assert!(HexDigest::from("not hex").to_base64().is_err());
+    }
+
     #[test]
     fn blake2b_test() {
         let digest = DigestAlgorithm::Blake2b160
@@ -0,0 +1,383 @@
+//! Client-side encryption of content files, so that objects can be stored in untrusted object
+//! stores. Content is encrypted with AES-256-GCM in fixed size chunks, using the STREAM
+//! construction so that files can be decrypted as they are read and truncated files are
+//! detected. Inventories, and the digests within them, always describe the plaintext.
+
+use std::fmt::{Debug, Formatter};
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::Path;
+
+use aes_gcm::aead::generic_array::GenericArray;
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::stream::{DecryptorBE32, EncryptorBE32};
+use aes_gcm::aead::{KeyInit, OsRng};
+use aes_gcm::Aes256Gcm;
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::ocfl::error::{Result, RocflError};
+
+/// The only encryption algorithm that is currently supported
+pub(crate) const AES_256_GCM_STREAM: &str = "aes-256-gcm-stream";
+
+/// Identifies encrypted content files and the version of their format
+const MAGIC: &[u8; 8] = b"ROCFLEC1";
+/// The length of the random nonce prefix that is stored after the magic bytes
+const NONCE_LEN: usize = 7;
+const HEADER_LEN: usize = MAGIC.len() + NONCE_LEN;
+/// The number of plaintext bytes that are encrypted together
+const CHUNK_SIZE: usize = 64 * 1024;
+const TAG_LEN: usize = 16;
+const ENCRYPTED_CHUNK_SIZE: usize = CHUNK_SIZE + TAG_LEN;
+
+/// An AES-256 key that content files are encrypted with
+#[derive(Clone)]
+pub struct EncryptionKey {
+    key: [u8; 32],
+    id: String,
+}
+
+/// Marks an object's content files as encrypted. The marker is stored in the object's
+/// `rocfl-encryption` extension when the object is created, and every version of the object is
+/// encrypted with the same key.
+#[derive(Deserialize, Serialize, Debug, Eq, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct EncryptionMarker {
+    /// The encryption algorithm, which is always `aes-256-gcm-stream`
+    pub algorithm: String,
+    /// Identifies the key that the object is encrypted with, without revealing it
+    pub key_id: String,
+    /// When the object was created
+    pub created: DateTime<Local>,
+    /// The number of plaintext bytes that are encrypted together. Markers written before the
+    /// chunk size was recorded used the current chunk size.
+    #[serde(default = "default_chunk_size")]
+    pub chunk_size: u64,
+}
+
+/// Decrypts content as it is written, and writes the plaintext to the inner writer.
+/// `finish()` must be called after all of the content was written, otherwise the final chunk
+/// is not written or authenticated.
+pub(crate) struct DecryptingWriter<W: Write> {
+    key: EncryptionKey,
+    inner: W,
+    decryptor: Option<DecryptorBE32<Aes256Gcm>>,
+    buffer: Vec<u8>,
+}
+
+impl EncryptionKey {
+    /// Parses a base64 encoded 256-bit key, such as one created with `openssl rand -base64 32`
+    pub fn from_base64(encoded: &str) -> Result<Self> {
+        let key: [u8; 32] = base64::decode(encoded.trim())
+            .ok()
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or_else(|| {
+                RocflError::InvalidValue(
+                    "Invalid encryption key: it must be 32 base64 encoded bytes".to_string(),
+                )
+            })?;
+
+        let id = hex::encode(&Sha256::digest(key)[..8]);
+
+        Ok(Self { key, id })
+    }
+
+    /// Reads a base64 encoded 256-bit key from a file
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let encoded = fs::read_to_string(path).map_err(|e| {
+            RocflError::InvalidValue(format!(
+                "Failed to read key file {}: {}",
+                path.to_string_lossy(),
+                e
+            ))
+        })?;
+        Self::from_base64(&encoded)
+    }
+
+    /// Returns an identifier of the key that is derived from, but does not reveal, the key
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Creates the marker that is written to objects that are encrypted with this key
+    pub(crate) fn marker(&self) -> EncryptionMarker {
+        EncryptionMarker {
+            algorithm: AES_256_GCM_STREAM.to_string(),
+            key_id: self.id.clone(),
+            created: Local::now(),
+            chunk_size: CHUNK_SIZE as u64,
+        }
+    }
+
+    /// Returns an error if the object's content was not encrypted with this key
+    pub(crate) fn ensure_matches(&self, object_id: &str, marker: &EncryptionMarker) -> Result<()> {
+        if marker.algorithm != AES_256_GCM_STREAM {
+            return Err(RocflError::IllegalState(format!(
+                "Object {} is encrypted with an unsupported algorithm: {}",
+                object_id, marker.algorithm
+            )));
+        }
+        if marker.chunk_size != CHUNK_SIZE as u64 {
+            return Err(RocflError::IllegalState(format!(
+                "Object {} is encrypted with an unsupported chunk size: {}",
+                object_id, marker.chunk_size
+            )));
+        }
+        if marker.key_id != self.id {
+            return Err(RocflError::InvalidConfiguration(format!(
+                "Object {} is encrypted with key {}, but the configured key is {}",
+                object_id, marker.key_id, self.id
+            )));
+        }
+        Ok(())
+    }
+
+    /// Encrypts the file at `src` and writes the result to `dst`
+    pub(crate) fn encrypt_file(&self, src: &Path, dst: &Path) -> Result<()> {
+        let mut reader =
+            File::open(src).map_err(|e| RocflError::io_path("open file to encrypt", src, e))?;
+        let mut writer =
+            File::create(dst).map_err(|e| RocflError::io_path("create encrypted file", dst, e))?;
+
+        let mut nonce = [0u8; NONCE_LEN];
+        OsRng.fill_bytes(&mut nonce);
+
+        writer.write_all(MAGIC)?;
+        writer.write_all(&nonce)?;
+
+        let mut encryptor =
+            EncryptorBE32::from_aead(self.cipher(), GenericArray::from_slice(&nonce));
+
+        // The final chunk is encrypted differently, so each chunk is only encrypted once it is
+        // known whether more content follows it
+        let mut chunk = vec![0u8; CHUNK_SIZE];
+        let mut len = read_chunk(&mut reader, &mut chunk)?;
+
+        loop {
+            let mut next = vec![0u8; CHUNK_SIZE];
+            let next_len = if len == CHUNK_SIZE {
+                read_chunk(&mut reader, &mut next)?
+            } else {
+                0
+            };
+
+            if next_len == 0 {
+                let encrypted = encryptor
+                    .encrypt_last(&chunk[..len])
+                    .map_err(|_| encryption_error(src))?;
+                writer.write_all(&encrypted)?;
+                break;
+            }
+
+            let encrypted = encryptor
+                .encrypt_next(&chunk[..len])
+                .map_err(|_| encryption_error(src))?;
+            writer.write_all(&encrypted)?;
+
+            chunk = next;
+            len = next_len;
+        }
+
+        writer.flush()?;
+        Ok(())
+    }
+
+    fn cipher(&self) -> Aes256Gcm {
+        Aes256Gcm::new(GenericArray::from_slice(&self.key))
+    }
+}
+
+impl Debug for EncryptionKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EncryptionKey")
+            .field("id", &self.id)
+            .finish_non_exhaustive()
+    }
+}
+
+impl EncryptionMarker {
+    /// Returns the size of the plaintext of a content file that is `encrypted_size` bytes. Every
+    /// encrypted file starts with a header, and every chunk, including the final chunk, which
+    /// may be empty, is followed by an authentication tag.
+    pub fn plaintext_size(&self, encrypted_size: u64) -> u64 {
+        let body = encrypted_size.saturating_sub(HEADER_LEN as u64);
+        let chunks = body.div_ceil(self.chunk_size + TAG_LEN as u64).max(1);
+        body.saturating_sub(chunks * TAG_LEN as u64)
+    }
+}
+
+impl<W: Write> DecryptingWriter<W> {
+    pub(crate) fn new(key: &EncryptionKey, inner: W) -> Self {
+        Self {
+            key: key.clone(),
+            inner,
+            decryptor: None,
+            buffer: Vec::with_capacity(ENCRYPTED_CHUNK_SIZE + HEADER_LEN),
+        }
+    }
+
+    /// Decrypts and writes the final chunk, and returns the inner writer
+    pub(crate) fn finish(mut self) -> Result<W> {
+        let decryptor = match self.decryptor.take() {
+            Some(decryptor) => decryptor,
+            None => return Err(decryption_error("the file is truncated").into()),
+        };
+
+        let plaintext = decryptor
+            .decrypt_last(self.buffer.as_slice())
+            .map_err(|_| decryption_error("the final chunk could not be authenticated"))?;
+        self.inner.write_all(&plaintext)?;
+        self.inner.flush()?;
+
+        Ok(self.inner)
+    }
+}
+
+impl<W: Write> Write for DecryptingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+
+        if self.decryptor.is_none() {
+            if self.buffer.len() < HEADER_LEN {
+                return Ok(buf.len());
+            }
+            if &self.buffer[..MAGIC.len()] != MAGIC {
+                return Err(decryption_error(
+                    "it is not an encrypted rocfl content file",
+                ));
+            }
+            self.decryptor = Some(DecryptorBE32::from_aead(
+                self.key.cipher(),
+                GenericArray::from_slice(&self.buffer[MAGIC.len()..HEADER_LEN]),
+            ));
+            self.buffer.drain(..HEADER_LEN);
+        }
+
+        let decryptor = self.decryptor.as_mut().unwrap();
+
+        // A full chunk may be the final chunk, so it is only decrypted once more content follows
+        while self.buffer.len() > ENCRYPTED_CHUNK_SIZE {
+            let plaintext = decryptor
+                .decrypt_next(&self.buffer[..ENCRYPTED_CHUNK_SIZE])
+                .map_err(|_| decryption_error("a chunk could not be authenticated"))?;
+            self.inner.write_all(&plaintext)?;
+            self.buffer.drain(..ENCRYPTED_CHUNK_SIZE);
+        }
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+fn default_chunk_size() -> u64 {
+    CHUNK_SIZE as u64
+}
+
+/// Fills the buffer from the reader, returning fewer bytes than the buffer's length only at the
+/// end of the reader
+fn read_chunk(reader: &mut impl Read, buffer: &mut [u8]) -> Result<usize> {
+    let mut len = 0;
+    while len < buffer.len() {
+        match reader.read(&mut buffer[len..]) {
+            Ok(0) => break,
+            Ok(n) => len += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => (),
+            Err(e) => return Err(e.into()),
+        }
+    }
+    Ok(len)
+}
+
+fn encryption_error(path: &Path) -> RocflError {
+    RocflError::General(format!("Failed to encrypt {}", path.to_string_lossy()))
+}
+
+fn decryption_error(reason: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("Failed to decrypt content file: {}", reason),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::io::Write;
+
+    use assert_fs::TempDir;
+
+    use super::{DecryptingWriter, EncryptionKey, CHUNK_SIZE};
+
+    const KEY: &str = "q6/5kQmVrm0gCMkt1WPyEz3CFLOUbT5wSXfvY7Qh8Zs=";
+    const OTHER_KEY: &str = "hY0GJ0BzLSwUKkF4mG7VN2u7cZj/Mg8T5DxzQ8m7XqA=";
+
+    #[test]
+    fn round_trip_content_of_various_lengths() {
+        let temp = TempDir::new().unwrap();
+        let key = EncryptionKey::from_base64(KEY).unwrap();
+
+        for len in [
+            0,
+            1,
+            CHUNK_SIZE - 1,
+            CHUNK_SIZE,
+            CHUNK_SIZE + 1,
+            3 * CHUNK_SIZE,
+        ] {
+            let content: Vec<u8> = (0..len).map(|i| (i % 251) as u8).collect();
+            let src = temp.path().join(format!("plain-{}", len));
+            let dst = temp.path().join(format!("encrypted-{}", len));
+            fs::write(&src, &content).unwrap();
+
+            key.encrypt_file(&src, &dst).unwrap();
+            let encrypted = fs::read(&dst).unwrap();
+            assert_ne!(content, encrypted);
+            assert_eq!(
+                len as u64,
+                key.marker().plaintext_size(encrypted.len() as u64)
+            );
+
+            // Write in odd sized pieces to exercise the chunk buffering
+            let mut writer = DecryptingWriter::new(&key, Vec::new());
+            for piece in encrypted.chunks(1000) {
+                writer.write_all(piece).unwrap();
+            }
+            assert_eq!(content, writer.finish().unwrap());
+        }
+    }
+
+    #[test]
+    fn reject_wrong_key_and_truncated_content() {
+        let temp = TempDir::new().unwrap();
+        let key = EncryptionKey::from_base64(KEY).unwrap();
+        let other = EncryptionKey::from_base64(OTHER_KEY).unwrap();
+        assert_ne!(key.id(), other.id());
+
+        let src = temp.path().join("plain");
+        let dst = temp.path().join("encrypted");
+        fs::write(&src, vec![7u8; 2 * CHUNK_SIZE + 10]).unwrap();
+        key.encrypt_file(&src, &dst).unwrap();
+        let encrypted = fs::read(&dst).unwrap();
+
+        let mut writer = DecryptingWriter::new(&other, Vec::new());
+        let result = writer.write_all(&encrypted);
+        assert!(result.is_err() || writer.finish().is_err());
+
+        let mut writer = DecryptingWriter::new(&key, Vec::new());
+        let truncated = &encrypted[..encrypted.len() - CHUNK_SIZE / 2];
+        let result = writer.write_all(truncated);
+        assert!(result.is_err() || writer.finish().is_err());
+    }
+
+    #[test]
+    fn reject_invalid_keys() {
+        assert!(EncryptionKey::from_base64("not a key").is_err());
+        assert!(EncryptionKey::from_base64("c2hvcnQ=").is_err());
+    }
+}
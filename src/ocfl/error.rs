@@ -1,5 +1,6 @@
 use core::fmt;
 use std::fmt::{Debug, Display, Formatter};
+use std::time::Duration;
 use std::{error, io};
 
 #[cfg(feature = "s3")]
@@ -8,11 +9,13 @@ use rusoto_core::region::ParseRegionError;
 use rusoto_core::RusotoError;
 use thiserror::Error;
 
+use crate::ocfl::validate::ValidationError;
 use crate::ocfl::{LogicalPath, VersionNum};
 
 pub type Result<T, E = RocflError> = core::result::Result<T, E>;
 
-/// Application errors
+/// Application errors. Use `code()` or `code_number()` to programmatically identify the kind of
+/// error; these values are stable across releases, unlike the error messages.
 #[derive(Error)]
 pub enum RocflError {
     #[error("Object {object_id} is corrupt: {message}")]
@@ -42,18 +45,236 @@ pub enum RocflError {
     #[error("{0}")]
     CopyMoveError(MultiError),
 
+    #[error("{0}")]
+    UnreadableObject(Box<ObjectError>),
+
+    #[error("Cannot read {0} because it is in an archival S3 storage class. It must be restored before it can be read")]
+    ArchivedContent(String),
+
     #[error("The OCFL repository is closed")]
     Closed,
 
+    #[error("The operation timed out after {0:?}")]
+    TimedOut(Duration),
+
+    #[error("Cannot {0} because the OCFL repository is read-only")]
+    ReadOnly(String),
+
+    #[error("Cannot {0} because object {1} is frozen")]
+    Frozen(String, String),
+
     #[error("{0}")]
     Io(io::Error),
 
+    #[error("Failed to {action} {path}: {source}")]
+    IoPath {
+        /// What was being done with the path, eg "open archive"
+        action: String,
+        path: String,
+        source: io::Error,
+    },
+
+    #[error("S3 {operation} request failed for s3://{bucket}/{key}: {source}")]
+    S3 {
+        /// The S3 operation, eg "GetObject"
+        operation: String,
+        bucket: String,
+        key: String,
+        source: Box<dyn error::Error + Sync + Send>,
+    },
+
+    #[error("{service} {operation} request failed for {bucket}/{key}: {source}")]
+    Cloud {
+        /// The cloud storage service, eg "Azure"
+        service: String,
+        /// The operation, eg "Get"
+        operation: String,
+        bucket: String,
+        key: String,
+        source: Box<dyn error::Error + Sync + Send>,
+    },
+
+    #[error("Object {object_id} failed validation with {} error(s)", .errors.len())]
+    InvalidObject {
+        object_id: String,
+        errors: Vec<ValidationError>,
+    },
+
+    #[error("Illegal state: Conflicting logical path {path}: {message}")]
+    Conflict {
+        /// The logical path that could not be added
+        path: String,
+        /// The existing logical path that it conflicts with
+        existing: String,
+        message: String,
+    },
+
+    #[error(
+        "Cannot commit object {object_id} because it exceeds the {quota} quota: {actual} > {limit}"
+    )]
+    QuotaExceeded {
+        object_id: String,
+        /// The name of the quota, eg "max_version_files"
+        quota: String,
+        limit: u64,
+        actual: u64,
+    },
+
+    #[error(
+        "Illegal state: Cannot create version {staged} in object {object_id} because the current version is at {current}"
+    )]
+    VersionConflict {
+        object_id: String,
+        /// The version that was staged
+        staged: VersionNum,
+        /// The object's current version, which the staged version must immediately follow
+        current: VersionNum,
+    },
+
+    #[error("Cannot commit object {object_id} because {path} was rejected: {reason}")]
+    CommitRejected {
+        object_id: String,
+        /// The logical path of the rejected file
+        path: String,
+        /// Why the commit callout rejected the file
+        reason: String,
+    },
+
     #[error("{0}")]
     Wrapped(Box<dyn error::Error + Sync + Send>),
 }
 
 pub struct MultiError(pub Vec<String>);
 
+impl RocflError {
+    /// A stable string that identifies the kind of error
+    pub fn code(&self) -> &'static str {
+        match self {
+            RocflError::CorruptObject { .. } => "corrupt_object",
+            RocflError::NotFound(_) => "not_found",
+            RocflError::InvalidValue(_) => "invalid_value",
+            RocflError::InvalidConfiguration(_) => "invalid_configuration",
+            RocflError::IllegalState(_) => "illegal_state",
+            RocflError::IllegalOperation(_) => "illegal_operation",
+            RocflError::LockAcquire(_, _) => "lock_acquire",
+            RocflError::General(_) => "general",
+            RocflError::CopyMoveError(_) => "copy_move",
+            RocflError::UnreadableObject(_) => "unreadable_object",
+            RocflError::ArchivedContent(_) => "archived_content",
+            RocflError::Closed => "closed",
+            RocflError::TimedOut(_) => "timed_out",
+            RocflError::ReadOnly(_) => "read_only",
+            RocflError::Frozen(_, _) => "frozen",
+            RocflError::Io(_) | RocflError::IoPath { .. } => "io",
+            RocflError::S3 { .. } => "s3",
+            RocflError::InvalidObject { .. } => "invalid_object",
+            RocflError::Conflict { .. } => "conflict",
+            RocflError::QuotaExceeded { .. } => "quota_exceeded",
+            RocflError::VersionConflict { .. } => "version_conflict",
+            RocflError::Cloud { .. } => "cloud",
+            RocflError::CommitRejected { .. } => "commit_rejected",
+            RocflError::Wrapped(_) => "wrapped",
+        }
+    }
+
+    /// A stable number that identifies the kind of error. Each number maps to exactly one
+    /// `code()`.
+    pub fn code_number(&self) -> u16 {
+        match self {
+            RocflError::CorruptObject { .. } => 1,
+            RocflError::NotFound(_) => 2,
+            RocflError::InvalidValue(_) => 3,
+            RocflError::InvalidConfiguration(_) => 4,
+            RocflError::IllegalState(_) => 5,
+            RocflError::IllegalOperation(_) => 6,
+            RocflError::LockAcquire(_, _) => 7,
+            RocflError::General(_) => 8,
+            RocflError::CopyMoveError(_) => 9,
+            RocflError::UnreadableObject(_) => 10,
+            RocflError::ArchivedContent(_) => 11,
+            RocflError::Closed => 12,
+            RocflError::TimedOut(_) => 13,
+            RocflError::ReadOnly(_) => 14,
+            RocflError::Frozen(_, _) => 15,
+            RocflError::Io(_) | RocflError::IoPath { .. } => 16,
+            RocflError::S3 { .. } => 17,
+            RocflError::InvalidObject { .. } => 18,
+            RocflError::Conflict { .. } => 19,
+            RocflError::QuotaExceeded { .. } => 20,
+            RocflError::VersionConflict { .. } => 21,
+            RocflError::Cloud { .. } => 22,
+            RocflError::CommitRejected { .. } => 23,
+            RocflError::Wrapped(_) => 99,
+        }
+    }
+
+    /// Constructs a `RocflError::IoPath` error
+    pub fn io_path(action: &str, path: impl AsRef<std::path::Path>, source: io::Error) -> Self {
+        RocflError::IoPath {
+            action: action.to_string(),
+            path: path.as_ref().to_string_lossy().into(),
+            source,
+        }
+    }
+}
+
+/// An error that was encountered while reading a specific object when iterating over the objects
+/// in a repository. The object's ID is only known if it could be read from its inventory.
+#[derive(Debug)]
+pub struct ObjectError {
+    /// The ID of the object, if it could be determined
+    pub object_id: Option<String>,
+    /// The path to the object root, or to the directory that could not be read
+    pub path: Option<String>,
+    /// The underlying error
+    pub cause: RocflError,
+}
+
+impl ObjectError {
+    pub fn new(object_id: Option<String>, path: Option<String>, cause: RocflError) -> Self {
+        Self {
+            object_id,
+            path,
+            cause,
+        }
+    }
+
+    /// Constructs an `ObjectError` for an error that occurred at a path before the object's ID
+    /// was known
+    pub fn at_path(path: impl Into<String>, cause: impl Into<RocflError>) -> Self {
+        Self::new(None, Some(path.into()), cause.into())
+    }
+}
+
+impl Display for ObjectError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match (&self.object_id, &self.path) {
+            (Some(id), Some(path)) => write!(f, "Object {} at {}: {:#}", id, path, self.cause),
+            (Some(id), None) => write!(f, "Object {}: {:#}", id, self.cause),
+            (None, Some(path)) => write!(f, "Object at {}: {:#}", path, self.cause),
+            (None, None) => write!(f, "{:#}", self.cause),
+        }
+    }
+}
+
+impl error::Error for ObjectError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(&self.cause)
+    }
+}
+
+impl From<RocflError> for ObjectError {
+    fn from(e: RocflError) -> Self {
+        Self::new(None, None, e)
+    }
+}
+
+impl From<ObjectError> for RocflError {
+    fn from(e: ObjectError) -> Self {
+        RocflError::UnreadableObject(Box::new(e))
+    }
+}
+
 impl Display for MultiError {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         let mut iter = self.0.iter().peekable();
@@ -90,6 +311,21 @@ pub fn not_found_path(
     ))
 }
 
+/// Constructs a `RocflError::NotFound` error for files in an object's `logs` directory
+pub fn not_found_log(object_id: &str, path: &str) -> RocflError {
+    RocflError::NotFound(format!("Object {} log file {}", object_id, path))
+}
+
+/// Constructs a `RocflError::NotFound` error for files in an object's root
+pub fn not_found_object_file(object_id: &str, path: &str) -> RocflError {
+    RocflError::NotFound(format!("Object {} file {}", object_id, path))
+}
+
+/// Constructs a `RocflError::NotFound` error for files in an object's `extensions` directory
+pub fn not_found_extension_file(object_id: &str, path: &str) -> RocflError {
+    RocflError::NotFound(format!("Object {} extension file {}", object_id, path))
+}
+
 impl Debug for RocflError {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         Display::fmt(self, f)
@@ -126,6 +362,7 @@ impl From<walkdir::Error> for RocflError {
     }
 }
 
+#[cfg(feature = "cli")]
 impl From<ctrlc::Error> for RocflError {
     fn from(e: ctrlc::Error) -> Self {
         RocflError::Wrapped(Box::new(e))
@@ -145,3 +382,102 @@ impl<T: error::Error + Sync + Send + 'static> From<RusotoError<T>> for RocflErro
         RocflError::Wrapped(Box::new(e))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+    use std::time::Duration;
+
+    use super::RocflError;
+    use crate::ocfl::validate::{ErrorCode, ProblemLocation, ValidationError};
+    use crate::ocfl::VersionNum;
+
+    #[test]
+    fn codes_are_stable() {
+        let errors = vec![
+            (RocflError::NotFound("a".to_string()), "not_found", 2),
+            (
+                RocflError::TimedOut(Duration::from_secs(1)),
+                "timed_out",
+                13,
+            ),
+            (
+                RocflError::io_path("open archive", "a.zip", io::ErrorKind::NotFound.into()),
+                "io",
+                16,
+            ),
+            (
+                RocflError::Conflict {
+                    path: "a/b".to_string(),
+                    existing: "a".to_string(),
+                    message: "conflict".to_string(),
+                },
+                "conflict",
+                19,
+            ),
+            (
+                RocflError::QuotaExceeded {
+                    object_id: "obj".to_string(),
+                    quota: "max_version_files".to_string(),
+                    limit: 1,
+                    actual: 2,
+                },
+                "quota_exceeded",
+                20,
+            ),
+            (
+                RocflError::VersionConflict {
+                    object_id: "obj".to_string(),
+                    staged: VersionNum::v1(),
+                    current: VersionNum::v1(),
+                },
+                "version_conflict",
+                21,
+            ),
+            (
+                RocflError::CommitRejected {
+                    object_id: "obj".to_string(),
+                    path: "a.txt".to_string(),
+                    reason: "infected".to_string(),
+                },
+                "commit_rejected",
+                23,
+            ),
+        ];
+
+        for (error, code, number) in errors {
+            assert_eq!(code, error.code());
+            assert_eq!(number, error.code_number());
+        }
+    }
+
+    #[test]
+    fn structured_errors_include_details_in_message() {
+        let error = RocflError::io_path("open archive", "a.zip", io::ErrorKind::NotFound.into());
+        assert_eq!(
+            "Failed to open archive a.zip: entity not found",
+            error.to_string()
+        );
+
+        let error = RocflError::InvalidObject {
+            object_id: "obj".to_string(),
+            errors: vec![
+                ValidationError::new(
+                    ProblemLocation::ObjectRoot,
+                    ErrorCode::E001,
+                    "one".to_string(),
+                ),
+                ValidationError::new(
+                    ProblemLocation::ObjectRoot,
+                    ErrorCode::E002,
+                    "two".to_string(),
+                ),
+            ],
+        };
+        assert_eq!("invalid_object", error.code());
+        assert_eq!(
+            "Object obj failed validation with 2 error(s)",
+            error.to_string()
+        );
+    }
+}
@@ -2,14 +2,13 @@ use std::borrow::Cow;
 use std::collections::hash_map::Iter;
 use std::collections::{BTreeMap, HashMap, HashSet};
 use std::convert::{TryFrom, TryInto};
-use std::mem;
 use std::rc::Rc;
 use std::str::FromStr;
 
 use chrono::{DateTime, Local};
 use globset::GlobBuilder;
 use once_cell::unsync::OnceCell;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Serialize, Serializer};
 
 use crate::ocfl::bimap::PathBiMap;
 use crate::ocfl::consts::DEFAULT_CONTENT_DIR;
@@ -17,7 +16,7 @@ use crate::ocfl::digest::{DigestAlgorithm, HexDigest};
 use crate::ocfl::error::{not_found, not_found_path, Result, RocflError};
 use crate::ocfl::{
     validate, CommitMeta, ContentPath, ContentPathVersion, Diff, InventoryPath, LogicalPath,
-    SpecVersion, VersionNum, VersionRef,
+    RenameDetection, SpecVersion, VersionNum, VersionRef,
 };
 
 const STAGING_MESSAGE: &str = "Staging new version";
@@ -69,6 +68,7 @@ pub struct InventoryBuilder {
 /// OCFL version serialization object
 #[derive(Serialize, Debug, Clone)]
 pub struct Version {
+    #[serde(serialize_with = "serialize_created")]
     pub created: DateTime<Local>,
     state: PathBiMap<LogicalPath>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -145,6 +145,190 @@ impl Inventory {
         Ok(())
     }
 
+    /// Rebases the changes that were staged in the HEAD version onto the HEAD version of `onto`,
+    /// which must be the object's current inventory. The staged changes are the paths that differ
+    /// between the HEAD version and the version before it. The returned inventory is a copy of
+    /// `onto` with a new HEAD version that contains the changes, and the content that was added
+    /// in the staged version is renumbered to the new version in its manifest.
+    ///
+    /// If a path was changed both in the staged version and in the current inventory, then a
+    /// `RocflError::IllegalState` error is returned.
+    pub fn rebase_head(&self, onto: Inventory) -> Result<Inventory> {
+        let head = self.head_version();
+        let base = self
+            .versions
+            .range(..self.head)
+            .next_back()
+            .map(|(_, version)| version);
+
+        let mut changes = Vec::new();
+
+        for (path, digest) in head.state_iter() {
+            let original = base.and_then(|base| base.lookup_digest(path));
+            if original != Some(digest) {
+                changes.push((path.clone(), original.cloned(), Some(digest.clone())));
+            }
+        }
+        if let Some(base) = base {
+            for (path, digest) in base.state_iter() {
+                if head.lookup_digest(path).is_none() {
+                    changes.push((path.clone(), Some(digest.clone()), None));
+                }
+            }
+        }
+
+        let current = onto.head_version();
+        let mut conflicts: Vec<String> = changes
+            .iter()
+            .filter(|(path, original, staged)| {
+                let actual = current.lookup_digest(path);
+                actual != original.as_ref() && actual != staged.as_ref()
+            })
+            .map(|(path, _, _)| path.to_string())
+            .collect();
+
+        if !conflicts.is_empty() {
+            conflicts.sort_unstable();
+            return Err(RocflError::IllegalState(format!(
+                "Cannot rebase the staged changes to object {} onto version {} because the following paths were also changed in it: {}",
+                self.id,
+                onto.head,
+                conflicts.join(", ")
+            )));
+        }
+
+        let mut rebased = onto;
+        rebased.create_staging_head()?;
+        rebased.storage_path = self.storage_path.clone();
+
+        let staged_prefix = format!("{}/", self.head);
+        let rebased_prefix = format!("{}/", rebased.head);
+
+        for (content_path, digest) in self.manifest.iter() {
+            if let Some(suffix) = content_path.as_str().strip_prefix(&staged_prefix) {
+                let digest = match rebased.manifest.get_id_rc(digest) {
+                    Some(digest) => digest.clone(),
+                    None => digest.clone(),
+                };
+                let content_path = ContentPath::try_from(format!("{}{}", rebased_prefix, suffix))?;
+                rebased.manifest.insert_rc(digest, Rc::new(content_path));
+            }
+        }
+
+        // Deletions are applied first so that they do not conflict with additions
+        changes.sort_by_key(|(_, _, staged)| staged.is_some());
+
+        for (path, _, staged) in changes {
+            match staged {
+                Some(digest) => {
+                    let digest = match rebased.manifest.get_id_rc(&digest) {
+                        Some(digest) => digest.clone(),
+                        None => digest,
+                    };
+                    rebased
+                        .head_version_mut()
+                        .add_file(digest, path.as_ref().clone())?;
+                }
+                None => {
+                    rebased.head_version_mut().remove_file(&path);
+                }
+            }
+        }
+
+        Ok(rebased)
+    }
+
+    /// Returns a copy of the inventory in which the versions up to and including `through` are
+    /// collapsed into a single baseline version, `v1`, that has the state and metadata of
+    /// `through`. The later versions are renumbered to follow it. The manifest only contains the
+    /// content that is referenced by the remaining versions, and each file is placed in the first
+    /// version that references it. Fixity blocks are dropped, because they refer to the original
+    /// content paths.
+    pub fn squash_through(&self, through: VersionNum) -> Result<Inventory> {
+        if through.number < 2 || through.number > self.head.number {
+            return Err(RocflError::InvalidValue(format!(
+                "Object {} can only be squashed through a version between v2 and {}",
+                self.id, self.head
+            )));
+        }
+
+        let renumber = |version_num: VersionNum| VersionNum {
+            number: version_num.number.saturating_sub(through.number) + 1,
+            width: version_num.width,
+        };
+
+        let mut versions = BTreeMap::new();
+        for (version_num, version) in self.versions.range(through..) {
+            versions.insert(renumber(*version_num), version.clone());
+        }
+
+        let content_dir = self.defaulted_content_dir();
+        let mut manifest = PathBiMap::new();
+
+        for (version_num, version) in &versions {
+            let mut state: Vec<_> = version.state_iter().collect();
+            state.sort_unstable_by(|a, b| a.0.cmp(b.0));
+
+            for (path, digest) in state {
+                if !manifest.contains_id(digest) {
+                    let content_path = path.to_content_path(*version_num, content_dir);
+                    manifest.insert_rc(digest.clone(), Rc::new(content_path));
+                }
+            }
+        }
+
+        Ok(Inventory {
+            id: self.id.clone(),
+            type_declaration: self.type_declaration.clone(),
+            digest_algorithm: self.digest_algorithm,
+            head: renumber(self.head),
+            content_directory: self.content_directory.clone(),
+            manifest,
+            versions,
+            fixity: None,
+            object_root: self.object_root.clone(),
+            storage_path: self.storage_path.clone(),
+            mutable_head: false,
+        })
+    }
+
+    /// Returns a copy of the inventory as it was when `version_num` was its HEAD version. The
+    /// copy only contains the versions up to and including `version_num`, and the content that
+    /// was added in them. Fixity blocks are not copied.
+    pub fn as_of_version(&self, version_num: VersionNum) -> Result<Inventory> {
+        self.get_version(version_num)?;
+
+        let versions = self
+            .versions
+            .range(..=version_num)
+            .map(|(num, version)| (*num, version.clone()))
+            .collect();
+
+        let mut manifest = PathBiMap::new();
+        for (content_path, digest) in self.manifest.iter() {
+            match content_path.version {
+                ContentPathVersion::VersionNum(num) if num <= version_num => {
+                    manifest.insert_rc(digest.clone(), content_path.clone());
+                }
+                _ => (),
+            }
+        }
+
+        Ok(Inventory {
+            id: self.id.clone(),
+            type_declaration: self.type_declaration.clone(),
+            digest_algorithm: self.digest_algorithm,
+            head: version_num,
+            content_directory: self.content_directory.clone(),
+            manifest,
+            versions,
+            fixity: None,
+            object_root: self.object_root.clone(),
+            storage_path: self.storage_path.clone(),
+            mutable_head: false,
+        })
+    }
+
     /// Returns true if the HEAD version is equal to 1
     pub fn is_new(&self) -> bool {
         self.head.number == 1
@@ -295,6 +479,16 @@ impl Inventory {
     /// does not exist. If only one version is specified, then the diff is between the specified
     /// version and the version before it.
     pub fn diff_versions(&self, left: Option<VersionNum>, right: VersionNum) -> Result<Vec<Diff>> {
+        self.diff_versions_with_renames(left, right, RenameDetection::default())
+    }
+
+    /// The same as `diff_versions()`, except that renames are detected as specified
+    pub fn diff_versions_with_renames(
+        &self,
+        left: Option<VersionNum>,
+        right: VersionNum,
+        renames: RenameDetection,
+    ) -> Result<Vec<Diff>> {
         if let Some(left) = left {
             if left == right {
                 return Ok(Vec::new());
@@ -312,7 +506,7 @@ impl Inventory {
             }
         };
 
-        Ok(self.get_version(right)?.diff(left))
+        Ok(self.get_version(right)?.diff_with_renames(left, renames))
     }
 
     /// Dedups all of the content paths that were added in the most recent version. All of the
@@ -329,7 +523,7 @@ impl Inventory {
                     if path.starts_with(&prefix) {
                         matches
                             .entry(digest.clone())
-                            .or_insert_with(HashSet::new)
+                            .or_default()
                             .insert(path.clone());
                     }
                 }
@@ -414,6 +608,70 @@ impl Inventory {
         Ok(())
     }
 
+    /// Replaces a set of logical paths that all have the same content with a new set of paths in
+    /// the head version. This is how a rename that was detected by a diff is replayed. The
+    /// manifest is unchanged, because the content is still referenced by the new paths.
+    pub fn rename_files_in_head(
+        &mut self,
+        src_paths: &[LogicalPath],
+        dst_paths: Vec<LogicalPath>,
+    ) -> Result<()> {
+        let id = self.id.clone();
+        let head_num = self.head;
+        let head = self.head_version_mut();
+
+        let digest = match src_paths.first() {
+            Some(src_path) => match head.lookup_digest(src_path) {
+                Some(digest) => digest.clone(),
+                None => return Err(not_found_path(&id, head_num, src_path)),
+            },
+            None => return Ok(()),
+        };
+
+        for src_path in src_paths {
+            match head.lookup_digest(src_path) {
+                Some(src_digest) if *src_digest == digest => (),
+                Some(_) => {
+                    return Err(RocflError::IllegalState(format!(
+                        "Cannot rename {} because its content differs from {}",
+                        src_path, src_paths[0]
+                    )))
+                }
+                None => return Err(not_found_path(&id, head_num, src_path)),
+            }
+        }
+
+        for src_path in src_paths {
+            head.remove_file(src_path);
+        }
+
+        for dst_path in dst_paths {
+            head.add_file(digest.clone(), dst_path)?;
+        }
+
+        Ok(())
+    }
+
+    /// Adds a logical path to the head version that references content that is already in the
+    /// manifest. The path is validated prior to the add.
+    pub fn add_existing_content_to_head(
+        &mut self,
+        digest: &HexDigest,
+        logical_path: LogicalPath,
+    ) -> Result<()> {
+        let digest_rc = match self.manifest.get_id_rc(digest) {
+            Some(digest_rc) => digest_rc.clone(),
+            None => {
+                return Err(RocflError::NotFound(format!(
+                    "Object {} does not contain content with digest {}",
+                    self.id, digest
+                )))
+            }
+        };
+
+        self.head_version_mut().add_file(digest_rc, logical_path)
+    }
+
     /// This method should **only** be used for internal move operations on files that are
     /// new to the head version where the moved file was physically moved to a new location
     /// on disk.
@@ -481,6 +739,43 @@ impl Inventory {
         }
     }
 
+    /// Returns descriptions of the manifest's content paths that are not within their version's
+    /// content directory. This is usually the result of an object that was created by a tool that
+    /// used a different content directory than the one declared in its inventory.
+    pub fn content_dir_anomalies(&self) -> Vec<String> {
+        let content_dir = self.defaulted_content_dir();
+        let mut misplaced: BTreeMap<Option<&str>, usize> = BTreeMap::new();
+
+        for (content_path, _) in self.manifest.iter() {
+            // The mutable head extension's content paths are not within a version directory
+            if let ContentPathVersion::MutableHead = content_path.version {
+                continue;
+            }
+
+            let parts: Vec<&str> = content_path.parts().collect();
+            match parts.as_slice() {
+                [_, dir, _, ..] if *dir == content_dir => (),
+                [_, dir, _, ..] => *misplaced.entry(Some(*dir)).or_default() += 1,
+                // The path is a file directly within the version directory
+                _ => *misplaced.entry(None).or_default() += 1,
+            }
+        }
+
+        misplaced
+            .into_iter()
+            .map(|(dir, count)| match dir {
+                Some(dir) => format!(
+                    "{} content path(s) are in '{}' directories rather than '{}'",
+                    count, dir, content_dir
+                ),
+                None => format!(
+                    "{} content path(s) are directly within a version directory",
+                    count
+                ),
+            })
+            .collect()
+    }
+
     /// Returns a reference to the inventory's manifest
     pub fn manifest(&self) -> &PathBiMap<ContentPath> {
         &self.manifest
@@ -529,6 +824,18 @@ impl Inventory {
         paths
     }
 
+    /// Returns the names of the algorithms in the fixity block, sorted
+    pub fn fixity_algorithms(&self) -> Vec<&str> {
+        let mut algorithms: Vec<&str> = self
+            .fixity
+            .iter()
+            .flat_map(|fixity| fixity.keys())
+            .map(String::as_str)
+            .collect();
+        algorithms.sort_unstable();
+        algorithms
+    }
+
     /// Returns a set of all of the content paths in the fixity block
     pub fn fixity_paths(&self) -> HashSet<&str> {
         let mut paths = HashSet::new();
@@ -641,7 +948,7 @@ impl Version {
     }
 
     /// Returns non-consuming iterator for the version's state
-    pub fn state_iter(&self) -> Iter<Rc<LogicalPath>, Rc<HexDigest>> {
+    pub fn state_iter(&self) -> Iter<'_, Rc<LogicalPath>, Rc<HexDigest>> {
         self.state.iter()
     }
 
@@ -660,7 +967,7 @@ impl Version {
         if self.logical_dirs.get().is_some() {
             self.logical_dirs = OnceCell::default();
         }
-        mem::replace(&mut self.state, PathBiMap::new())
+        std::mem::take(&mut self.state)
     }
 
     /// Returns a reference to the digest associated to a logical path, or None if the logical
@@ -694,18 +1001,20 @@ impl Version {
     /// and a file.
     pub fn validate_non_conflicting(&self, path: &LogicalPath) -> Result<()> {
         if self.is_dir(path) {
-            return Err(RocflError::IllegalState(format!(
-                "Conflicting logical path {}: This path is already in use as a directory",
-                path
-            )));
+            return Err(RocflError::Conflict {
+                path: path.to_string(),
+                existing: path.to_string(),
+                message: "This path is already in use as a directory".to_string(),
+            });
         }
 
         for dir in create_logical_dirs(path) {
             if self.is_file(&dir) {
-                return Err(RocflError::IllegalState(format!(
-                    "Conflicting logical path {}: The path part {} is an existing logical file",
-                    path, dir
-                )));
+                return Err(RocflError::Conflict {
+                    path: path.to_string(),
+                    existing: dir.to_string(),
+                    message: format!("The path part {} is an existing logical file", dir),
+                });
             }
         }
 
@@ -796,6 +1105,15 @@ impl Version {
     /// other version is the left hand version. If the other version is None, then all of
     /// this version's paths are returned as Adds.
     pub fn diff(&self, other: Option<&Version>) -> Vec<Diff> {
+        self.diff_with_renames(other, RenameDetection::default())
+    }
+
+    /// The same as `diff()`, except that renames are detected as specified
+    pub fn diff_with_renames(
+        &self,
+        other: Option<&Version>,
+        detection: RenameDetection,
+    ) -> Vec<Diff> {
         let mut diffs = Vec::new();
         let mut deletes: HashMap<Rc<HexDigest>, Vec<Rc<LogicalPath>>> = HashMap::new();
 
@@ -807,7 +1125,7 @@ impl Version {
                     None => {
                         deletes
                             .entry(left_digest.clone())
-                            .or_insert_with(Vec::new)
+                            .or_default()
                             .push(path.clone());
                     }
                     Some(right_digest) => {
@@ -819,6 +1137,28 @@ impl Version {
                 }
             }
 
+            if detection == RenameDetection::OneToOne {
+                let mut adds: HashMap<&Rc<HexDigest>, usize> = HashMap::new();
+                for (path, digest) in self.state_iter() {
+                    if !seen.contains(path) {
+                        *adds.entry(digest).or_default() += 1;
+                    }
+                }
+                // Content that is deleted or added at more than one path is never a rename
+                deletes.retain(|digest, paths| {
+                    if paths.len() == 1 && adds.get(digest) == Some(&1) {
+                        true
+                    } else {
+                        diffs.extend(paths.drain(..).map(Diff::Deleted));
+                        false
+                    }
+                });
+            } else if detection == RenameDetection::Off {
+                for (_digest, paths) in deletes.drain() {
+                    diffs.extend(paths.into_iter().map(Diff::Deleted));
+                }
+            }
+
             let mut renames: HashMap<Rc<HexDigest>, Diff> = HashMap::new();
 
             for (path, digest) in self.state_iter() {
@@ -920,3 +1260,13 @@ fn create_logical_dirs(path: &LogicalPath) -> HashSet<LogicalPath> {
 
     dirs
 }
+
+/// Serializes a version's created timestamp as RFC 3339 with a numeric offset, such as `+00:00`,
+/// even when the timestamp is in UTC, so that inventories are written in the same format
+/// regardless of the version of chrono
+fn serialize_created<S: Serializer>(
+    created: &DateTime<Local>,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    serializer.serialize_str(&created.to_rfc3339())
+}
@@ -1,14 +1,19 @@
-use std::fs::OpenOptions;
+use std::fs::{self, File, OpenOptions};
 use std::path::{Path, PathBuf};
+use std::process;
+use std::time::{Duration, SystemTime};
 
-use log::error;
+use chrono::{DateTime, Local};
+use log::{error, warn};
+use serde::{Deserialize, Serialize};
 
 use crate::ocfl::error::Result;
 use crate::ocfl::{util, DigestAlgorithm, RocflError};
 
 /// An object lock manager that works by attempting to atomically create files using a hash
 /// of the object's id. The lock files should be automatically removed when the lock goes out
-/// of scope.
+/// of scope. Each lock file records the process that acquired it, so that locks that were left
+/// behind by processes that died can be identified.
 pub struct LockManager {
     locks_dir: PathBuf,
     digest_algorithm: DigestAlgorithm,
@@ -18,6 +23,29 @@ pub struct ObjectLock {
     lock_path: PathBuf,
 }
 
+/// The contents of a lock file
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LockInfo {
+    pub object_id: String,
+    pub pid: u32,
+    pub host: Option<String>,
+    pub created: DateTime<Local>,
+}
+
+/// A lock file that exists in a locks directory
+#[derive(Debug)]
+pub struct HeldLock {
+    pub path: PathBuf,
+    /// The lock's contents, if they could be read. Locks that were created by older versions of
+    /// rocfl are empty.
+    pub info: Option<LockInfo>,
+    /// The time since the lock file was last modified
+    pub age: Duration,
+    /// When the lock file was last modified
+    pub modified: SystemTime,
+}
+
 impl LockManager {
     /// Creates a new lock manager. `locks_dir` must already exist.
     pub fn new(locks_dir: impl AsRef<Path>) -> Self {
@@ -38,7 +66,16 @@ impl LockManager {
             .create_new(true)
             .open(&lock_path)
         {
-            Ok(_) => Ok(ObjectLock { lock_path }),
+            Ok(file) => {
+                if let Err(e) = write_lock_info(file, object_id) {
+                    warn!(
+                        "Failed to record the owner of lock file {}: {}",
+                        lock_path.to_string_lossy(),
+                        e
+                    );
+                }
+                Ok(ObjectLock { lock_path })
+            }
             Err(_) => Err(RocflError::LockAcquire(
                 object_id.to_string(),
                 lock_path.to_string_lossy().into(),
@@ -47,6 +84,124 @@ impl LockManager {
     }
 }
 
+impl HeldLock {
+    /// Returns the reason the lock is stale, or `None` if it may still be held. A lock is stale
+    /// if the process that acquired it was on this host and is no longer running. When this
+    /// cannot be determined, the lock is stale if it is older than `max_age`.
+    pub fn stale_reason(&self, max_age: Duration) -> Option<String> {
+        if let Some(info) = &self.info {
+            if info.host.is_some() && info.host == hostname() {
+                match process_is_running(info.pid) {
+                    Some(true) => return None,
+                    Some(false) => {
+                        return Some(format!(
+                            "The process that acquired it, {}, is no longer running",
+                            info.pid
+                        ))
+                    }
+                    None => (),
+                }
+            }
+        }
+
+        if self.age > max_age {
+            Some(format!(
+                "The lock file is {} minutes old",
+                self.age.as_secs() / 60
+            ))
+        } else {
+            None
+        }
+    }
+}
+
+/// Returns every lock file in the locks directory. Nothing is returned if the directory does not
+/// exist.
+pub fn list_locks(locks_dir: impl AsRef<Path>) -> Result<Vec<HeldLock>> {
+    let locks_dir = locks_dir.as_ref();
+    let mut locks = Vec::new();
+
+    if !locks_dir.exists() {
+        return Ok(locks);
+    }
+
+    for entry in fs::read_dir(locks_dir)? {
+        let entry = entry?;
+        let path = entry.path();
+
+        if path.extension().is_none_or(|ext| ext != "lock") {
+            continue;
+        }
+
+        let modified = match entry.metadata() {
+            Ok(meta) => meta.modified()?,
+            // The lock was released while it was being listed
+            Err(_) => continue,
+        };
+        let age = modified.elapsed().unwrap_or_default();
+
+        let info = fs::read(&path)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok());
+
+        locks.push(HeldLock {
+            path,
+            info,
+            age,
+            modified,
+        });
+    }
+
+    Ok(locks)
+}
+
+fn write_lock_info(file: File, object_id: &str) -> Result<()> {
+    let info = LockInfo {
+        object_id: object_id.to_string(),
+        pid: process::id(),
+        host: hostname(),
+        created: Local::now(),
+    };
+    serde_json::to_writer(file, &info)?;
+    Ok(())
+}
+
+/// Returns whether the process is running, or `None` if this cannot be determined
+#[cfg(unix)]
+fn process_is_running(pid: u32) -> Option<bool> {
+    let pid = libc::pid_t::try_from(pid).ok()?;
+    // Signal 0 only checks whether the process exists and may be signaled
+    if unsafe { libc::kill(pid, 0) } == 0 {
+        return Some(true);
+    }
+    match std::io::Error::last_os_error().raw_os_error() {
+        Some(libc::ESRCH) => Some(false),
+        Some(libc::EPERM) => Some(true),
+        _ => None,
+    }
+}
+
+#[cfg(not(unix))]
+fn process_is_running(_pid: u32) -> Option<bool> {
+    None
+}
+
+/// Returns the name of the host, or `None` if it cannot be determined
+#[cfg(unix)]
+pub(crate) fn hostname() -> Option<String> {
+    let mut buf = [0u8; 256];
+    if unsafe { libc::gethostname(buf.as_mut_ptr().cast(), buf.len()) } != 0 {
+        return None;
+    }
+    let len = buf.iter().position(|b| *b == 0).unwrap_or(buf.len());
+    String::from_utf8(buf[..len].to_vec()).ok()
+}
+
+#[cfg(not(unix))]
+pub(crate) fn hostname() -> Option<String> {
+    std::env::var("COMPUTERNAME").ok()
+}
+
 impl Drop for ObjectLock {
     fn drop(&mut self) {
         if let Err(e) = util::remove_file_ignore_not_found(&self.lock_path) {
@@ -63,7 +218,12 @@ impl Drop for ObjectLock {
 mod tests {
     use assert_fs::TempDir;
 
-    use crate::ocfl::lock::LockManager;
+    use std::fs::File;
+    use std::time::{Duration, SystemTime};
+
+    use chrono::Local;
+
+    use crate::ocfl::lock::{hostname, list_locks, LockInfo, LockManager};
     use crate::ocfl::RocflError;
 
     #[test]
@@ -117,6 +277,74 @@ mod tests {
         let _lock1 = manager.acquire(object_1_id).unwrap();
     }
 
+    #[test]
+    fn locks_of_live_processes_are_not_stale() {
+        let temp = TempDir::new().unwrap();
+        let manager = LockManager::new(temp.path());
+
+        let _lock = manager.acquire("one").unwrap();
+
+        let locks = list_locks(temp.path()).unwrap();
+        assert_eq!(1, locks.len());
+        let info = locks[0].info.as_ref().unwrap();
+        assert_eq!("one", info.object_id);
+        assert_eq!(std::process::id(), info.pid);
+        assert_eq!(None, locks[0].stale_reason(Duration::ZERO));
+    }
+
+    #[test]
+    fn locks_without_info_are_stale_when_old() {
+        let temp = TempDir::new().unwrap();
+        let lock_path = temp.path().join("abc.lock");
+        std::fs::write(&lock_path, "").unwrap();
+        File::options()
+            .write(true)
+            .open(&lock_path)
+            .unwrap()
+            .set_modified(SystemTime::now() - Duration::from_secs(600))
+            .unwrap();
+
+        let locks = list_locks(temp.path()).unwrap();
+        assert_eq!(1, locks.len());
+        assert!(locks[0].info.is_none());
+        assert_eq!(None, locks[0].stale_reason(Duration::from_secs(3600)));
+        assert_eq!(
+            Some("The lock file is 10 minutes old".to_string()),
+            locks[0].stale_reason(Duration::from_secs(60))
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn locks_of_dead_processes_on_this_host_are_stale() {
+        let temp = TempDir::new().unwrap();
+
+        let mut child = std::process::Command::new("true").spawn().unwrap();
+        let pid = child.id();
+        child.wait().unwrap();
+
+        let info = LockInfo {
+            object_id: "one".to_string(),
+            pid,
+            host: hostname(),
+            created: Local::now(),
+        };
+        std::fs::write(
+            temp.path().join("abc.lock"),
+            serde_json::to_vec(&info).unwrap(),
+        )
+        .unwrap();
+
+        let locks = list_locks(temp.path()).unwrap();
+        assert_eq!(
+            Some(format!(
+                "The process that acquired it, {}, is no longer running",
+                pid
+            )),
+            locks[0].stale_reason(Duration::from_secs(3600))
+        );
+    }
+
     fn assert_cannot_acquire_lock(object_id: &str, manager: &LockManager) {
         match manager.acquire(object_id) {
             Err(RocflError::LockAcquire(..)) => (),
@@ -8,28 +8,53 @@
 //! let repo = OcflRepo::fs_repo("path/to/ocfl/storage/root", None);
 //! ```
 
-pub use self::digest::DigestAlgorithm;
-pub use self::error::{Result, RocflError};
+pub use self::archive::{ZipExport, ZipImport};
+pub use self::audit::AUDIT_LOG_TARGET;
+pub use self::callout::{CalloutFile, CalloutVerdict, CommandCallout, CommitCallout};
+pub use self::cas::{CasExport, CasManifest};
+pub use self::digest::{
+    DigestAlgorithm, DigestReader, DigestWriter, HexDigest, MultiDigestReader, MultiDigestWriter,
+};
+pub use self::encryption::{EncryptionKey, EncryptionMarker};
+pub use self::error::{ObjectError, Result, RocflError};
+pub use self::inventory::{Inventory, User, Version};
+pub use self::patch::{ObjectPatch, PatchChange, PatchFile, PATCH_FORMAT};
 pub use self::repo::OcflRepo;
+pub use self::signature::{
+    InventorySignature, SignatureReport, SignatureStatus, SigningKey, VerifyingKey,
+    VersionSignature,
+};
 pub use self::store::layout::{LayoutExtensionName, StorageLayout};
+pub use self::template::{ObjectTemplate, TemplateFile};
 pub use self::types::*;
+pub use self::util::ThreadPool;
 pub use self::validate::{
-    ErrorCode, IncrementalValidator, IncrementalValidatorImpl, ObjectValidationResult,
-    ProblemLocation, StorageValidationResult, ValidationError, ValidationResult, ValidationWarning,
+    parse_inventory, validate_inventory_digest, ErrorCode, FixityCheck, FixitySample,
+    FixitySampleRecord, IncrementalValidator, IncrementalValidatorImpl, ObjectValidationResult,
+    ParseDiagnostics, ProblemLocation, SpecReference, StorageValidationResult,
+    ValidationCheckpoint, ValidationError, ValidationFilter, ValidationResult, ValidationWarning,
     WarnCode,
 };
 
+mod archive;
+mod audit;
 mod bimap;
+mod callout;
+mod cas;
 mod consts;
 mod digest;
+mod encryption;
 mod error;
 mod inventory;
 mod lock;
+mod patch;
 mod paths;
 mod repo;
 mod serde;
+mod signature;
 mod specs;
 mod store;
+mod template;
 mod types;
 mod util;
 mod validate;
@@ -0,0 +1,205 @@
+//! Patches describe the changes between two versions of an object in a form that can be replayed
+//! onto another object, such as a mirror of the object in a different repository. A patch is a
+//! JSON document that lists the files that were added, modified, deleted, and renamed.
+//!
+//! ```text
+//! {
+//!   "format": "rocfl-patch/1",
+//!   "objectId": "urn:example:obj",
+//!   "fromVersion": "v1",
+//!   "toVersion": "v2",
+//!   "digestAlgorithm": "sha512",
+//!   "changes": [
+//!     { "op": "add", "path": "a.txt", "digest": "4dff4ea3...", "data": "aGVsbG8K" },
+//!     { "op": "modify", "path": "b.txt", "digest": "9b71d2...", "previousDigest": "0cf918..." },
+//!     { "op": "delete", "path": "c.txt", "digest": "cf83e135..." },
+//!     { "op": "rename", "from": ["d.txt"], "to": ["e/d.txt"], "digest": "87aa7cde..." }
+//!   ]
+//! }
+//! ```
+//!
+//! Modified, deleted, and renamed files record the digest their content had in the from version,
+//! so that a patch is not applied to an object that has diverged from the version it was created
+//! from.
+//!
+//! The content of added and modified files is either inlined as base64 encoded `data`, or
+//! referenced by digest. Referenced content is resolved from the target object's manifest, or
+//! from the source object, if it exists in the repository the patch is applied to.
+
+use std::io::{Read, Write};
+
+use serde::{Deserialize, Serialize};
+
+use crate::ocfl::error::{Result, RocflError};
+use crate::ocfl::{DigestAlgorithm, VersionNum};
+
+/// The identifier of the patch format that is written and understood by rocfl
+pub const PATCH_FORMAT: &str = "rocfl-patch/1";
+
+/// The changes between two versions of an object
+#[derive(Deserialize, Serialize, Debug, Eq, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ObjectPatch {
+    /// The format of the patch, always `rocfl-patch/1`
+    pub format: String,
+    /// The ID of the object the patch was created from
+    pub object_id: String,
+    /// The version the changes are relative to, or `None` if they are relative to an empty object
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub from_version: Option<VersionNum>,
+    /// The version the changes were made in
+    pub to_version: VersionNum,
+    /// The algorithm of the digests in the patch
+    pub digest_algorithm: DigestAlgorithm,
+    /// The changes, in the order they were reported by the diff
+    pub changes: Vec<PatchChange>,
+}
+
+/// A single change in a patch
+#[derive(Deserialize, Serialize, Debug, Eq, PartialEq, Clone)]
+#[serde(tag = "op", rename_all = "camelCase")]
+pub enum PatchChange {
+    /// A file that did not exist in the from version
+    Add(PatchFile),
+    /// A file whose content changed
+    Modify(PatchFile),
+    /// A file that no longer exists
+    Delete {
+        path: String,
+        /// The digest of the file's content in the from version
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        digest: Option<String>,
+    },
+    /// Files whose content is unchanged, but whose paths changed
+    Rename {
+        from: Vec<String>,
+        to: Vec<String>,
+        /// The digest of the files' content
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        digest: Option<String>,
+    },
+}
+
+/// A file that was added or modified, and the content it has in the to version
+#[derive(Deserialize, Serialize, Debug, Eq, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PatchFile {
+    /// The logical path of the file
+    pub path: String,
+    /// The digest of the file's content
+    pub digest: String,
+    /// The digest of the file's content in the from version, if it was modified
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous_digest: Option<String>,
+    /// The file's content, base64 encoded, if it was inlined
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<String>,
+}
+
+impl ObjectPatch {
+    /// Reads a patch from its JSON serialization. An error is returned if the patch is not in a
+    /// format that is understood.
+    pub fn from_reader(mut reader: impl Read) -> Result<Self> {
+        // VersionNum can only be deserialized from borrowed strings
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+        let patch: Self = serde_json::from_slice(&bytes)?;
+
+        if patch.format != PATCH_FORMAT {
+            return Err(RocflError::InvalidValue(format!(
+                "Unsupported patch format '{}'. Expected '{}'",
+                patch.format, PATCH_FORMAT
+            )));
+        }
+
+        Ok(patch)
+    }
+
+    /// Writes the patch as pretty printed JSON
+    pub fn to_writer(&self, mut writer: impl Write) -> Result<()> {
+        serde_json::to_writer_pretty(&mut writer, self)?;
+        writer.write_all(b"\n")?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ObjectPatch, PatchChange, PatchFile, PATCH_FORMAT};
+    use crate::ocfl::{DigestAlgorithm, VersionNum};
+
+    #[test]
+    fn patch_round_trip() {
+        let patch = ObjectPatch {
+            format: PATCH_FORMAT.to_string(),
+            object_id: "urn:example:obj".to_string(),
+            from_version: Some(VersionNum::v1()),
+            to_version: VersionNum::try_from(2).unwrap(),
+            digest_algorithm: DigestAlgorithm::Sha512,
+            changes: vec![
+                PatchChange::Add(PatchFile {
+                    path: "a.txt".to_string(),
+                    digest: "abc".to_string(),
+                    previous_digest: None,
+                    data: Some("aGVsbG8K".to_string()),
+                }),
+                PatchChange::Modify(PatchFile {
+                    path: "b.txt".to_string(),
+                    digest: "def".to_string(),
+                    previous_digest: Some("abc".to_string()),
+                    data: None,
+                }),
+                PatchChange::Delete {
+                    path: "c.txt".to_string(),
+                    digest: Some("abc".to_string()),
+                },
+                PatchChange::Rename {
+                    from: vec!["d.txt".to_string()],
+                    to: vec!["e/d.txt".to_string()],
+                    digest: Some("abc".to_string()),
+                },
+            ],
+        };
+
+        let mut json = Vec::new();
+        patch.to_writer(&mut json).unwrap();
+        let json = String::from_utf8(json).unwrap();
+
+        assert!(json.contains("\"op\": \"add\""));
+        assert!(json.contains("\"fromVersion\": \"v1\""));
+        assert!(json.contains("\"previousDigest\": \"abc\""));
+        assert_eq!(patch, ObjectPatch::from_reader(json.as_bytes()).unwrap());
+    }
+
+    #[test]
+    fn read_patch_without_previous_digests() {
+        let json = r#"{"format":"rocfl-patch/1","objectId":"o","fromVersion":"v1",
+            "toVersion":"v2","digestAlgorithm":"sha512","changes":[
+            {"op":"delete","path":"c.txt"},{"op":"rename","from":["d.txt"],"to":["e.txt"]}]}"#;
+
+        let patch = ObjectPatch::from_reader(json.as_bytes()).unwrap();
+
+        assert_eq!(
+            vec![
+                PatchChange::Delete {
+                    path: "c.txt".to_string(),
+                    digest: None,
+                },
+                PatchChange::Rename {
+                    from: vec!["d.txt".to_string()],
+                    to: vec!["e.txt".to_string()],
+                    digest: None,
+                },
+            ],
+            patch.changes
+        );
+    }
+
+    #[test]
+    fn reject_unknown_patch_format() {
+        let json = r#"{"format":"other/1","objectId":"o","toVersion":"v1",
+            "digestAlgorithm":"sha512","changes":[]}"#;
+
+        assert!(ObjectPatch::from_reader(json.as_bytes()).is_err());
+    }
+}
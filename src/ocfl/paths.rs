@@ -59,6 +59,14 @@ where
     content_path(object_root, inventory.head, inventory)
 }
 
+/// Returns the path to the `logs` directory within the specified directory
+pub fn logs_path<P>(dir: P) -> PathBuf
+where
+    P: AsRef<Path>,
+{
+    dir.as_ref().join(LOGS_DIR)
+}
+
 /// Returns the path to the `extensions` directory within the specified directory
 pub fn extensions_path<P>(dir: P) -> PathBuf
 where
@@ -87,6 +95,106 @@ where
     extensions
 }
 
+/// Returns the path to the object index file within the object index extension
+pub fn object_index_path<P>(storage_root: P) -> PathBuf
+where
+    P: AsRef<Path>,
+{
+    let mut extensions = extensions_path(storage_root);
+    extensions.push(ROCFL_OBJECT_INDEX_EXTENSION);
+    extensions.push(OBJECT_INDEX_FILE);
+    extensions
+}
+
+/// Returns the path to the config file within the object index extension
+pub fn object_index_config_path<P>(storage_root: P) -> PathBuf
+where
+    P: AsRef<Path>,
+{
+    let mut extensions = extensions_path(storage_root);
+    extensions.push(ROCFL_OBJECT_INDEX_EXTENSION);
+    extensions.push(EXTENSIONS_CONFIG_FILE);
+    extensions
+}
+
+/// Returns the path to the alias index file within the object aliases extension
+pub fn alias_index_path<P>(storage_root: P) -> PathBuf
+where
+    P: AsRef<Path>,
+{
+    let mut extensions = extensions_path(storage_root);
+    extensions.push(ROCFL_ALIASES_EXTENSION);
+    extensions.push(ALIAS_INDEX_FILE);
+    extensions
+}
+
+/// Returns the path to the directory within the content index extension that the content of
+/// objects that use the digest algorithm is indexed in
+pub fn content_index_dir<P>(storage_root: P, algorithm: DigestAlgorithm) -> PathBuf
+where
+    P: AsRef<Path>,
+{
+    let mut extensions = extensions_path(storage_root);
+    extensions.push(ROCFL_CONTENT_INDEX_EXTENSION);
+    extensions.push(algorithm.to_string());
+    extensions
+}
+
+/// Returns the path to the content index shard that the digest is indexed in. Digests are
+/// sharded by their first two characters.
+pub fn content_index_path<P>(storage_root: P, algorithm: DigestAlgorithm, digest: &str) -> PathBuf
+where
+    P: AsRef<Path>,
+{
+    let mut dir = content_index_dir(storage_root, algorithm);
+    dir.push(format!("{}.json", digest.get(..2).unwrap_or(digest)));
+    dir
+}
+
+/// Returns the path to the config file within the repository defaults extension
+pub fn repo_defaults_path<P>(storage_root: P) -> PathBuf
+where
+    P: AsRef<Path>,
+{
+    let mut extensions = extensions_path(storage_root);
+    extensions.push(ROCFL_REPO_DEFAULTS_EXTENSION);
+    extensions.push(EXTENSIONS_CONFIG_FILE);
+    extensions
+}
+
+/// Returns the path to the freeze marker within an object's frozen extension
+pub fn frozen_marker_path<P>(object_root: P) -> PathBuf
+where
+    P: AsRef<Path>,
+{
+    let mut extensions = extensions_path(object_root);
+    extensions.push(ROCFL_FROZEN_EXTENSION);
+    extensions.push(FROZEN_FILE);
+    extensions
+}
+
+/// Returns the path to the encryption marker within an object's encryption extension
+pub fn encryption_marker_path<P>(object_root: P) -> PathBuf
+where
+    P: AsRef<Path>,
+{
+    let mut extensions = extensions_path(object_root);
+    extensions.push(ROCFL_ENCRYPTION_EXTENSION);
+    extensions.push(EXTENSIONS_CONFIG_FILE);
+    extensions
+}
+
+/// Returns the path to the validation history file within the validation history extension
+pub fn validation_history_path<P>(storage_root: P) -> PathBuf
+where
+    P: AsRef<Path>,
+{
+    let mut extensions = extensions_path(storage_root);
+    extensions.push(ROCFL_VALIDATION_HISTORY_EXTENSION);
+    extensions.push(VALIDATION_HISTORY_FILE);
+    extensions
+}
+
 /// Returns the path to the `ocfl_layout.json`
 pub fn ocfl_layout_path<P>(storage_root: P) -> PathBuf
 where
@@ -130,6 +238,7 @@ pub fn join(part1: &str, part2: &str) -> String {
 
 /// Joins two string path parts, inserting at `/` if needed, and appends a `/` to the end,
 /// if there is not already one
+#[cfg(feature = "s3")]
 pub fn join_with_trailing_slash(part1: &str, part2: &str) -> String {
     let mut joined = join(part1, part2);
 
@@ -1,39 +1,70 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::convert::TryInto;
 use std::fs;
 use std::fs::File;
-use std::io::Write;
+use std::io::{self, BufRead, BufReader, Read, Seek, Write};
 use std::ops::Deref;
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
 use std::rc::Rc;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::RwLock;
-
-use chrono::Local;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender, SyncSender};
+use std::sync::{Arc, Mutex, RwLock};
+use std::thread;
+use std::time::Duration;
+
+use chrono::{DateTime, Local};
+use flate2::read::GzDecoder;
+use globset::GlobBuilder;
+use grep_regex::RegexMatcher;
+use grep_searcher::{BinaryDetection, Searcher, SearcherBuilder, Sink, SinkMatch};
 use log::{info, warn};
 use once_cell::sync::OnceCell;
 #[cfg(feature = "s3")]
 use rusoto_core::Region;
 use walkdir::WalkDir;
 
+use crate::ocfl::archive::{self, ZipObjectWriter};
+use crate::ocfl::audit::Audit;
+use crate::ocfl::cas::{self, CasExport, CasManifest};
 use crate::ocfl::consts::*;
 use crate::ocfl::digest::HexDigest;
-use crate::ocfl::error::{MultiError, Result, RocflError};
-use crate::ocfl::inventory::Inventory;
-use crate::ocfl::lock::LockManager;
+use crate::ocfl::encryption::{DecryptingWriter, EncryptionKey, EncryptionMarker};
+use crate::ocfl::error::{not_found, not_found_path, MultiError, ObjectError, Result, RocflError};
+use crate::ocfl::inventory::{Inventory, User, Version};
+use crate::ocfl::lock::{self, LockManager, ObjectLock};
+use crate::ocfl::signature::{self, InventorySignature, SigningKey, VerifyingKey};
 use crate::ocfl::store::fs::FsOcflStore;
 use crate::ocfl::store::layout::{LayoutExtensionName, StorageLayout};
 #[cfg(feature = "s3")]
 use crate::ocfl::store::s3::S3OcflStore;
-use crate::ocfl::store::{OcflStore, StagingStore};
-use crate::ocfl::validate::ObjectValidationResult;
+use crate::ocfl::store::{BucketStaging, OcflStore, StagingStore};
+use crate::ocfl::validate::{
+    FixityCheck, ObjectValidationResult, ValidationFilter, ValidationResult,
+};
+#[cfg(any(feature = "azure", feature = "gcs"))]
+use crate::ocfl::CloudStorage;
 use crate::ocfl::Knowable::*;
+#[cfg(feature = "s3")]
+use crate::ocfl::S3ClientOptions;
 use crate::ocfl::{
-    paths, util, validate, CommitMeta, ContentPath, Diff, DigestAlgorithm, IncrementalValidator,
-    InventoryPath, Knowable, LogicalPath, ObjectInfo, ObjectVersion, ObjectVersionDetails,
-    RepoInfo, SpecVersion, VersionDetails, VersionNum, VersionRef,
+    paths, util, validate, AuditTrailEntry, BucketStagedObject, CalloutFile, CalloutVerdict,
+    ChangePlan, CommitCallout, CommitMeta, CommitQuotas, ContentMatch, ContentPath,
+    ContentPathVersion, Diff, DigestAlgorithm, FreezeMarker, HealthCheck, HealthProblem,
+    HealthReport, IncrementalValidator, InventoryPath, Knowable, LayoutInfo, ListFilter,
+    LogicalPath, ObjectAlias, ObjectDiff, ObjectFingerprint, ObjectInfo, ObjectPatch,
+    ObjectTemplate, ObjectVersion, ObjectVersionDetails, Orphan, OrphanKind, PatchChange,
+    PatchFile, PathMatch, PlannedChange, RenameDetection, RepoInfo, RepoInitOptions,
+    S3RequestMetrics, S3WriteOptions, SignatureReport, SignatureStatus, SpecVersion, SquashReport,
+    StateTree, SymlinkPolicy, ThreadPool, ValidationRecord, VersionContentSize, VersionDetails,
+    VersionDiff, VersionNum, VersionPage, VersionRange, VersionRef, VersionSignature, ZipExport,
+    ZipImport, PATCH_FORMAT,
 };
 
+/// The number of chunks of a file that may be read before they are searched
+const SEARCH_CHUNKS_IN_FLIGHT: usize = 4;
+/// The leading bytes of a gzip stream
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
 /// OCFL repository
 pub struct OcflRepo {
     /// Interfaces with the files on disk or in S3
@@ -48,7 +79,51 @@ pub struct OcflRepo {
     /// Indicates if the repository should convert separators to backslashes when rendering
     /// physical paths.
     use_backslashes: bool,
+    /// Indicates if committed content that duplicates existing repository content should be
+    /// hard linked to the existing content
+    hard_links: bool,
+    /// Indicates if an audit trail entry should be appended to an object's logs whenever
+    /// rocfl creates a new version of it
+    audit_trail: bool,
+    /// The key that the inventories of new versions are signed with, if signing is enabled
+    signing_key: Option<SigningKey>,
+    /// Indicates if validation results should be recorded in the repository's validation history
+    validation_history: bool,
+    /// Indicates if operations that modify the repository, or its staged objects, are rejected
+    read_only: bool,
+    /// How symbolic links are treated when copying or moving external files into objects
+    symlink_policy: RwLock<SymlinkPolicy>,
+    /// Indicates if operations on frozen objects are allowed
+    override_frozen: AtomicBool,
+    /// Indicates if the modification times of external files are recorded when they are copied
+    /// or moved into objects
+    preserve_timestamps: AtomicBool,
+    /// Indicates if mutating operations record the changes they would make instead of making them
+    dry_run: AtomicBool,
+    /// The changes that were planned by operations run in dry-run mode
+    change_plans: Mutex<Vec<ChangePlan>>,
+    /// The limits that are enforced when objects are committed
+    quotas: CommitQuotas,
+    /// Indicates if commits that exceed the quotas are allowed
+    override_quotas: AtomicBool,
+    /// Inspect the files that commits add to objects, and may veto the commits
+    callouts: Vec<Box<dyn CommitCallout>>,
+    /// The key that the content of new objects is encrypted with, and that the content of
+    /// encrypted objects is decrypted with, if encryption is enabled
+    encryption_key: Option<EncryptionKey>,
+    /// The pool that parallelizable work is run on, if it was configured with `with_threads()`
+    pool: Option<ThreadPool>,
     closed: AtomicBool,
+    /// Indicates if the repository was closed because its timeout elapsed
+    timed_out: AtomicBool,
+    deadline: Mutex<Option<Deadline>>,
+}
+
+/// The timeout of a repository's operations. The watchdog thread that enforces the timeout
+/// stops when the deadline is dropped.
+struct Deadline {
+    timeout: Duration,
+    _cancel: Sender<()>,
 }
 
 impl OcflRepo {
@@ -70,10 +145,37 @@ impl OcflRepo {
             staging_lock_manager: OnceCell::default(),
             spec_version: RwLock::new(spec_version),
             use_backslashes: util::BACKSLASH_SEPARATOR,
+            hard_links: false,
+            audit_trail: false,
+            signing_key: None,
+            validation_history: false,
+            read_only: false,
+            symlink_policy: RwLock::default(),
+            override_frozen: AtomicBool::new(false),
+            preserve_timestamps: AtomicBool::new(false),
+            dry_run: AtomicBool::new(false),
+            change_plans: Mutex::default(),
+            quotas: CommitQuotas::default(),
+            override_quotas: AtomicBool::new(false),
+            callouts: Vec::new(),
+            encryption_key: None,
+            pool: None,
             closed: AtomicBool::new(false),
+            timed_out: AtomicBool::new(false),
+            deadline: Mutex::new(None),
         })
     }
 
+    /// Creates a new `OcflRepo` instance backed by the local filesystem that rejects every
+    /// operation that would modify the repository or its staged objects. This is the same as
+    /// calling `fs_repo()` followed by `with_read_only(true)`.
+    pub fn fs_repo_read_only(
+        storage_root: impl AsRef<Path>,
+        staging: Option<&Path>,
+    ) -> Result<Self> {
+        Ok(Self::fs_repo(storage_root, staging)?.with_read_only(true))
+    }
+
     /// Initializes a new `OcflRepo` instance backed by the local filesystem. The OCFL repository
     /// most not already exist.
     pub fn init_fs_repo(
@@ -81,6 +183,24 @@ impl OcflRepo {
         staging: Option<&Path>,
         version: SpecVersion,
         layout: Option<StorageLayout>,
+    ) -> Result<Self> {
+        Self::init_fs_repo_with_options(
+            storage_root,
+            staging,
+            version,
+            layout,
+            RepoInitOptions::default(),
+        )
+    }
+
+    /// Initializes a new `OcflRepo` instance backed by the local filesystem, in the same way as
+    /// `init_fs_repo()`, using the specified `options`.
+    pub fn init_fs_repo_with_options(
+        storage_root: impl AsRef<Path>,
+        staging: Option<&Path>,
+        version: SpecVersion,
+        layout: Option<StorageLayout>,
+        options: RepoInitOptions,
     ) -> Result<Self> {
         let staging_root = match staging {
             Some(staging) => staging.to_path_buf(),
@@ -89,18 +209,86 @@ impl OcflRepo {
 
         Ok(Self {
             staging_root,
-            store: Box::new(FsOcflStore::init(storage_root, version, layout)?),
+            store: Box::new(FsOcflStore::init(
+                storage_root,
+                version,
+                layout,
+                options.spec_copy,
+            )?),
             staging: OnceCell::default(),
             staging_lock_manager: OnceCell::default(),
             spec_version: RwLock::new(Some(Known(version))),
             use_backslashes: util::BACKSLASH_SEPARATOR,
+            hard_links: false,
+            audit_trail: false,
+            signing_key: None,
+            validation_history: false,
+            read_only: false,
+            symlink_policy: RwLock::default(),
+            override_frozen: AtomicBool::new(false),
+            preserve_timestamps: AtomicBool::new(false),
+            dry_run: AtomicBool::new(false),
+            change_plans: Mutex::default(),
+            quotas: CommitQuotas::default(),
+            override_quotas: AtomicBool::new(false),
+            callouts: Vec::new(),
+            encryption_key: None,
+            pool: None,
+            closed: AtomicBool::new(false),
+            timed_out: AtomicBool::new(false),
+            deadline: Mutex::new(None),
+        })
+    }
+
+    /// Initializes a new `OcflRepo` instance backed by the local filesystem over an existing
+    /// OCFL repository that was created by another tool. The repository's spec version and
+    /// storage layout are verified, and any missing optional files are written. Existing objects
+    /// are not modified.
+    pub fn init_fs_repo_from_existing(
+        storage_root: impl AsRef<Path>,
+        staging: Option<&Path>,
+    ) -> Result<Self> {
+        let staging_root = match staging {
+            Some(staging) => staging.to_path_buf(),
+            None => paths::staging_extension_path(storage_root.as_ref()),
+        };
+
+        let store = FsOcflStore::init_from_existing(storage_root)?;
+        let spec_version = store.repo_spec_version()?;
+
+        Ok(Self {
+            staging_root,
+            store: Box::new(store),
+            staging: OnceCell::default(),
+            staging_lock_manager: OnceCell::default(),
+            spec_version: RwLock::new(spec_version),
+            use_backslashes: util::BACKSLASH_SEPARATOR,
+            hard_links: false,
+            audit_trail: false,
+            signing_key: None,
+            validation_history: false,
+            read_only: false,
+            symlink_policy: RwLock::default(),
+            override_frozen: AtomicBool::new(false),
+            preserve_timestamps: AtomicBool::new(false),
+            dry_run: AtomicBool::new(false),
+            change_plans: Mutex::default(),
+            quotas: CommitQuotas::default(),
+            override_quotas: AtomicBool::new(false),
+            callouts: Vec::new(),
+            encryption_key: None,
+            pool: None,
             closed: AtomicBool::new(false),
+            timed_out: AtomicBool::new(false),
+            deadline: Mutex::new(None),
         })
     }
 
     /// Initializes a new `OcflRepo` instance backed by S3. The OCFL repository
-    /// most not already exist.
+    /// most not already exist. `client_options` configures the HTTP client that requests are
+    /// sent with.
     #[cfg(feature = "s3")]
+    #[allow(clippy::too_many_arguments)]
     pub fn init_s3_repo(
         region: Region,
         bucket: &str,
@@ -110,22 +298,120 @@ impl OcflRepo {
         staging_root: impl AsRef<Path>,
         version: SpecVersion,
         layout: Option<StorageLayout>,
+        client_options: &S3ClientOptions,
+    ) -> Result<Self> {
+        Self::init_s3_repo_with_options(
+            region,
+            bucket,
+            prefix,
+            profile,
+            staging_root,
+            version,
+            layout,
+            client_options,
+            RepoInitOptions::default(),
+        )
+    }
+
+    /// Initializes a new `OcflRepo` instance backed by S3, in the same way as `init_s3_repo()`,
+    /// using the specified `options`.
+    #[cfg(feature = "s3")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn init_s3_repo_with_options(
+        region: Region,
+        bucket: &str,
+        prefix: Option<&str>,
+        profile: Option<&str>,
+        staging_root: impl AsRef<Path>,
+        version: SpecVersion,
+        layout: Option<StorageLayout>,
+        client_options: &S3ClientOptions,
+        options: RepoInitOptions,
     ) -> Result<Self> {
         Ok(Self {
             staging_root: staging_root.as_ref().to_path_buf(),
             store: Box::new(S3OcflStore::init(
-                region, bucket, prefix, profile, version, layout,
+                region,
+                bucket,
+                prefix,
+                profile,
+                version,
+                layout,
+                options.spec_copy,
+                client_options,
             )?),
             staging: OnceCell::default(),
             staging_lock_manager: OnceCell::default(),
             spec_version: RwLock::new(Some(Known(version))),
             use_backslashes: false,
+            hard_links: false,
+            audit_trail: false,
+            signing_key: None,
+            validation_history: false,
+            read_only: false,
+            symlink_policy: RwLock::default(),
+            override_frozen: AtomicBool::new(false),
+            preserve_timestamps: AtomicBool::new(false),
+            dry_run: AtomicBool::new(false),
+            change_plans: Mutex::default(),
+            quotas: CommitQuotas::default(),
+            override_quotas: AtomicBool::new(false),
+            callouts: Vec::new(),
+            encryption_key: None,
+            pool: None,
+            closed: AtomicBool::new(false),
+            timed_out: AtomicBool::new(false),
+            deadline: Mutex::new(None),
+        })
+    }
+
+    /// Initializes a new `OcflRepo` instance backed by S3 over an existing OCFL repository that
+    /// was created by another tool. The repository's spec version and storage layout are
+    /// verified, and any missing optional files are written. Existing objects are not modified.
+    #[cfg(feature = "s3")]
+    pub fn init_s3_repo_from_existing(
+        region: Region,
+        bucket: &str,
+        prefix: Option<&str>,
+        profile: Option<&str>,
+        staging_root: impl AsRef<Path>,
+        client_options: &S3ClientOptions,
+    ) -> Result<Self> {
+        let store =
+            S3OcflStore::init_from_existing(region, bucket, prefix, profile, client_options)?;
+        let spec_version = store.repo_spec_version()?;
+
+        Ok(Self {
+            staging_root: staging_root.as_ref().to_path_buf(),
+            store: Box::new(store),
+            staging: OnceCell::default(),
+            staging_lock_manager: OnceCell::default(),
+            spec_version: RwLock::new(spec_version),
+            use_backslashes: false,
+            hard_links: false,
+            audit_trail: false,
+            signing_key: None,
+            validation_history: false,
+            read_only: false,
+            symlink_policy: RwLock::default(),
+            override_frozen: AtomicBool::new(false),
+            preserve_timestamps: AtomicBool::new(false),
+            dry_run: AtomicBool::new(false),
+            change_plans: Mutex::default(),
+            quotas: CommitQuotas::default(),
+            override_quotas: AtomicBool::new(false),
+            callouts: Vec::new(),
+            encryption_key: None,
+            pool: None,
             closed: AtomicBool::new(false),
+            timed_out: AtomicBool::new(false),
+            deadline: Mutex::new(None),
         })
     }
 
     /// Creates a new `OcflRepo` instance backed by S3. `prefix` used to specify a
-    /// sub directory within a bucket that the OCFL repository is rooted in.
+    /// sub directory within a bucket that the OCFL repository is rooted in. `client_options`
+    /// configures the HTTP client that requests are sent with.
     #[cfg(feature = "s3")]
     pub fn s3_repo(
         region: Region,
@@ -133,8 +419,9 @@ impl OcflRepo {
         prefix: Option<&str>,
         staging_root: impl AsRef<Path>,
         profile: Option<&str>,
+        client_options: &S3ClientOptions,
     ) -> Result<Self> {
-        let store = S3OcflStore::new(region, bucket, prefix, profile)?;
+        let store = S3OcflStore::new(region, bucket, prefix, profile, client_options)?;
         let spec_version = store.repo_spec_version()?;
 
         Ok(Self {
@@ -144,10 +431,243 @@ impl OcflRepo {
             staging_lock_manager: OnceCell::default(),
             spec_version: RwLock::new(spec_version),
             use_backslashes: false,
+            hard_links: false,
+            audit_trail: false,
+            signing_key: None,
+            validation_history: false,
+            read_only: false,
+            symlink_policy: RwLock::default(),
+            override_frozen: AtomicBool::new(false),
+            preserve_timestamps: AtomicBool::new(false),
+            dry_run: AtomicBool::new(false),
+            change_plans: Mutex::default(),
+            quotas: CommitQuotas::default(),
+            override_quotas: AtomicBool::new(false),
+            callouts: Vec::new(),
+            encryption_key: None,
+            pool: None,
             closed: AtomicBool::new(false),
+            timed_out: AtomicBool::new(false),
+            deadline: Mutex::new(None),
         })
     }
 
+    /// Creates a new `OcflRepo` instance backed by a cloud storage service other than S3, such
+    /// as Azure Blob Storage or Google Cloud Storage. `bucket` is the name of the bucket, or
+    /// container, that the repository is in, and `prefix` is the sub directory within it that
+    /// the repository is rooted in.
+    #[cfg(any(feature = "azure", feature = "gcs"))]
+    pub fn cloud_repo(
+        storage: &CloudStorage,
+        bucket: &str,
+        prefix: Option<&str>,
+        staging_root: impl AsRef<Path>,
+    ) -> Result<Self> {
+        let store = S3OcflStore::new_cloud(storage, bucket, prefix)?;
+        let spec_version = store.repo_spec_version()?;
+        Ok(Self::cloud_repo_with_store(
+            store,
+            staging_root,
+            spec_version,
+        ))
+    }
+
+    /// Initializes a new `OcflRepo` instance backed by a cloud storage service other than S3.
+    /// The OCFL repository must not already exist.
+    #[cfg(any(feature = "azure", feature = "gcs"))]
+    pub fn init_cloud_repo(
+        storage: &CloudStorage,
+        bucket: &str,
+        prefix: Option<&str>,
+        staging_root: impl AsRef<Path>,
+        version: SpecVersion,
+        layout: Option<StorageLayout>,
+        options: RepoInitOptions,
+    ) -> Result<Self> {
+        let store =
+            S3OcflStore::init_cloud(storage, bucket, prefix, version, layout, options.spec_copy)?;
+        Ok(Self::cloud_repo_with_store(
+            store,
+            staging_root,
+            Some(Known(version)),
+        ))
+    }
+
+    /// Initializes a new `OcflRepo` instance backed by a cloud storage service other than S3
+    /// over an existing OCFL repository that was created by another tool. Existing objects are
+    /// not modified.
+    #[cfg(any(feature = "azure", feature = "gcs"))]
+    pub fn init_cloud_repo_from_existing(
+        storage: &CloudStorage,
+        bucket: &str,
+        prefix: Option<&str>,
+        staging_root: impl AsRef<Path>,
+    ) -> Result<Self> {
+        let store = S3OcflStore::init_cloud_from_existing(storage, bucket, prefix)?;
+        let spec_version = store.repo_spec_version()?;
+        Ok(Self::cloud_repo_with_store(
+            store,
+            staging_root,
+            spec_version,
+        ))
+    }
+
+    #[cfg(any(feature = "azure", feature = "gcs"))]
+    fn cloud_repo_with_store(
+        store: S3OcflStore,
+        staging_root: impl AsRef<Path>,
+        spec_version: Option<Knowable<SpecVersion, String>>,
+    ) -> Self {
+        Self {
+            staging_root: staging_root.as_ref().to_path_buf(),
+            store: Box::new(store),
+            staging: OnceCell::default(),
+            staging_lock_manager: OnceCell::default(),
+            spec_version: RwLock::new(spec_version),
+            use_backslashes: false,
+            hard_links: false,
+            audit_trail: false,
+            signing_key: None,
+            validation_history: false,
+            read_only: false,
+            symlink_policy: RwLock::default(),
+            override_frozen: AtomicBool::new(false),
+            preserve_timestamps: AtomicBool::new(false),
+            dry_run: AtomicBool::new(false),
+            change_plans: Mutex::default(),
+            quotas: CommitQuotas::default(),
+            override_quotas: AtomicBool::new(false),
+            callouts: Vec::new(),
+            encryption_key: None,
+            pool: None,
+            closed: AtomicBool::new(false),
+            timed_out: AtomicBool::new(false),
+            deadline: Mutex::new(None),
+        }
+    }
+
+    /// Enables or disables hard linking when committing. When enabled, content files that are
+    /// added in a new version, and duplicate content that already exists in another object in the
+    /// repository, are replaced with hard links to the existing files once the version is
+    /// committed. Files that cannot be linked, or whose existing counterparts no longer match
+    /// their digests, are kept as copies. This only applies to repositories on the local
+    /// filesystem.
+    ///
+    /// Duplicate content is located using a content index. The first commit that links content
+    /// builds the index by reading the inventory of every object in the repository.
+    pub fn with_hard_links(mut self, hard_links: bool) -> Self {
+        self.hard_links = hard_links;
+        self
+    }
+
+    /// Enables appending an audit trail entry to an object's `logs` directory every time rocfl
+    /// commits or upgrades the object. Entries record the operation, the version it created, the
+    /// user, the time, the affected paths, and the version of rocfl. They can be read back with
+    /// `list_audit_trail()`.
+    pub fn with_audit_trail(mut self, audit_trail: bool) -> Self {
+        self.audit_trail = audit_trail;
+        self
+    }
+
+    /// Enables signing the inventory of every version that rocfl commits with the key. The
+    /// signatures are appended to the object's `logs` directory, and can be checked with
+    /// `verify_signatures()`.
+    pub fn with_signing_key(mut self, signing_key: SigningKey) -> Self {
+        self.signing_key = Some(signing_key);
+        self
+    }
+
+    /// Enables recording the results of validating objects in the repository's
+    /// `rocfl-validation-history` extension when `record_validations()` is called. Each object's
+    /// most recent validation is recorded, and can be read back with `validation_history()`.
+    pub fn with_validation_history(mut self, validation_history: bool) -> Self {
+        self.validation_history = validation_history;
+        self
+    }
+
+    /// Enables or disables read-only mode. When enabled, every operation that would modify the
+    /// repository or its staged objects, such as creating, committing, or purging objects, or
+    /// copying, moving, and removing staged files, fails with `RocflError::ReadOnly` before
+    /// anything is written.
+    pub fn with_read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
+
+    /// Sets the limits that are enforced when objects are committed. A commit that would exceed
+    /// one of the limits fails with `RocflError::QuotaExceeded` before anything is written,
+    /// unless `set_override_quotas()` is enabled. By default, no limits are enforced.
+    pub fn with_commit_quotas(mut self, quotas: CommitQuotas) -> Self {
+        self.quotas = quotas;
+        self
+    }
+
+    /// Adds a callout that inspects every file that a commit adds to an object, before anything
+    /// is written to the repository. A commit fails with `RocflError::CommitRejected` if any
+    /// callout rejects any of its files. Callouts are run in the order they were added, and are
+    /// not bypassed by `set_override_quotas()`.
+    pub fn with_commit_callout(mut self, callout: impl CommitCallout + 'static) -> Self {
+        self.callouts.push(Box::new(callout));
+        self
+    }
+
+    /// Enables client-side encryption of content files with the key. The content files of objects
+    /// that are created while a key is configured are encrypted before they are written to
+    /// storage, and the objects are marked as encrypted in their `rocfl-encryption` extension.
+    /// New versions of encrypted objects are encrypted with the same key, and their content is
+    /// transparently decrypted when it is read. Objects that were created without encryption are
+    /// not encrypted. Inventories always contain the digests of the plaintext content.
+    ///
+    /// Reading or committing to an encrypted object fails with
+    /// `RocflError::InvalidConfiguration` if the object was encrypted with a different key, or
+    /// if no key is configured. Content that is staged in an S3 bucket cannot be encrypted.
+    pub fn with_encryption_key(mut self, key: EncryptionKey) -> Self {
+        self.store.set_encryption_key(key.clone());
+        self.encryption_key = Some(key);
+        self
+    }
+
+    /// Sets the S3 storage class and tags that are applied to content files and to inventories
+    /// when they are committed. This only applies to repositories in S3.
+    pub fn with_s3_write_options(
+        mut self,
+        content: S3WriteOptions,
+        inventory: S3WriteOptions,
+    ) -> Self {
+        self.store.set_s3_write_options(content, inventory);
+        self
+    }
+
+    /// Sets whether the content of staged versions is staged within the repository's S3 bucket,
+    /// under the staging extension, rather than on local disk. Content that is staged in the
+    /// bucket is uploaded once, when it is staged, and is copied into the object server-side when
+    /// it is committed. This only applies to repositories in S3.
+    ///
+    /// Content is staged under an ID that is derived from the host and the local staging root, so
+    /// that machines that share a repository do not overwrite or remove each other's content.
+    pub fn with_bucket_staging(mut self, enabled: bool) -> Self {
+        let staging_id = enabled.then(|| bucket_staging_id(&self.staging_root));
+        self.store.set_bucket_staging(staging_id);
+        self
+    }
+
+    /// Sets the number of threads that parallelizable work is distributed across. This includes
+    /// fixity checking an object's content files, and hashing and staging the files that are
+    /// copied or moved into an object from outside the repository. By default, a thread is used
+    /// for each available core, and fixity checks use no more than 8 threads per object.
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        let pool = ThreadPool::new(threads);
+        self.store.set_thread_pool(pool);
+        self.pool = Some(pool);
+        self
+    }
+
+    /// Returns the pool that parallelizable work is run on, so that callers that operate on
+    /// many objects can use the same number of threads as the repository
+    pub fn thread_pool(&self) -> ThreadPool {
+        self.pool.unwrap_or_default()
+    }
+
     /// Instructs the repo to gracefully stop any in-flight work and not accept any additional
     /// requests.
     pub fn close(&self) {
@@ -156,15 +676,146 @@ impl OcflRepo {
         self.store.close();
     }
 
+    /// Closes the repo if it is still open after `timeout` has elapsed. In-flight work is
+    /// gracefully stopped, the same as when the repo is closed, and subsequent requests fail with
+    /// `RocflError::TimedOut`. Setting a new timeout replaces the previous one.
+    pub fn set_timeout(self: &Arc<Self>, timeout: Duration) {
+        let (cancel, watchdog) = mpsc::channel::<()>();
+        let repo = Arc::downgrade(self);
+
+        thread::spawn(move || {
+            // The sender is dropped, disconnecting the channel, when the deadline is replaced or
+            // the repo is dropped
+            if let Err(RecvTimeoutError::Timeout) = watchdog.recv_timeout(timeout) {
+                if let Some(repo) = repo.upgrade() {
+                    warn!("Operation timed out after {:?}", timeout);
+                    repo.timed_out.store(true, Ordering::Release);
+                    repo.close();
+                }
+            }
+        });
+
+        *self.deadline.lock().unwrap() = Some(Deadline {
+            timeout,
+            _cancel: cancel,
+        });
+    }
+
+    /// Sets whether validation reports paths that are likely to break when objects are moved
+    /// between platforms as errors (`RE001`-`RE003`) rather than warnings (`RW005`-`RW007`).
+    /// These are paths that contain control characters, that are not in Unicode Normalization
+    /// Form C, or that contain names Windows reserves for devices.
+    pub fn set_strict_paths(&self, strict: bool) {
+        self.store.set_strict_paths(strict);
+        if let Some(staging) = self.staging.get() {
+            staging.set_strict_paths(strict);
+        }
+    }
+
+    /// Sets how symbolic links are treated by `copy_files_external()` and
+    /// `move_files_external()`. Links are followed by default. Moving a link that is followed
+    /// copies the file it points to into the object and removes the link, leaving the file in
+    /// place.
+    pub fn set_symlink_policy(&self, policy: SymlinkPolicy) {
+        *self.symlink_policy.write().unwrap() = policy;
+    }
+
+    /// Sets whether frozen objects may be committed to, upgraded, or purged. By default, these
+    /// operations fail with `RocflError::Frozen`.
+    pub fn set_override_frozen(&self, override_frozen: bool) {
+        self.override_frozen
+            .store(override_frozen, Ordering::Release);
+    }
+
+    /// Sets whether commits may exceed the quotas configured with `with_commit_quotas()`
+    pub fn set_override_quotas(&self, override_quotas: bool) {
+        self.override_quotas
+            .store(override_quotas, Ordering::Release);
+    }
+
+    /// Sets whether `copy_files_external()` and `move_files_external()` record the modification
+    /// times of the source files. The times are stored, keyed on logical path, in a JSON file at
+    /// the logical path `metadata/rocfl-timestamps.json`, so that they are versioned alongside
+    /// the files. They may be retrieved with `get_original_timestamps()`.
+    pub fn set_preserve_timestamps(&self, preserve: bool) {
+        self.preserve_timestamps.store(preserve, Ordering::Release);
+    }
+
+    /// Sets whether the repository is in dry-run mode. In dry-run mode, `commit()`, the copy,
+    /// move, and remove operations, `purge_object()`, `upgrade_object()`, and `upgrade_repo()`
+    /// validate their inputs and record the changes they would make, without modifying the
+    /// repository or its staged objects. The plans are retrieved with `take_change_plans()`.
+    /// All other operations that modify the repository fail with `RocflError::IllegalOperation`.
+    pub fn set_dry_run(&self, dry_run: bool) {
+        self.dry_run.store(dry_run, Ordering::Release);
+    }
+
+    /// Returns the changes that were planned by the operations that were run in dry-run mode,
+    /// in the order the operations were run, and clears them.
+    pub fn take_change_plans(&self) -> Vec<ChangePlan> {
+        std::mem::take(&mut *self.change_plans.lock().unwrap())
+    }
+
+    /// Returns the files, relative the storage root, that initializing a new repository with the
+    /// specified settings would write. Nothing is written.
+    pub fn plan_init(
+        version: SpecVersion,
+        layout: Option<&StorageLayout>,
+        options: RepoInitOptions,
+    ) -> ChangePlan {
+        let mut plan = ChangePlan::new("init", None);
+
+        plan.changes.push(PlannedChange::WriteFile(
+            version.root_namaste().filename.to_string(),
+        ));
+
+        if options.spec_copy {
+            plan.changes.push(PlannedChange::WriteFile(
+                version.spec_filename().to_string(),
+            ));
+        }
+
+        if let Some(layout) = layout {
+            let extension_name = layout.extension_name().to_string();
+            plan.changes
+                .push(PlannedChange::WriteFile(OCFL_LAYOUT_FILE.to_string()));
+            plan.changes.push(PlannedChange::WriteFile(format!(
+                "{}/{}/{}",
+                EXTENSIONS_DIR, extension_name, EXTENSIONS_CONFIG_FILE
+            )));
+            plan.changes
+                .push(PlannedChange::WriteFile(format!("{}.md", extension_name)));
+        }
+
+        plan
+    }
+
+    /// Returns the metrics of the requests that have been sent to S3, grouped by operation. This
+    /// is only available when the repository is backed by S3 and it was created with
+    /// `S3ClientOptions::request_metrics` enabled.
+    pub fn s3_request_metrics(&self) -> Option<S3RequestMetrics> {
+        self.store.s3_request_metrics()
+    }
+
+    /// Returns true if the repo was closed because its timeout elapsed
+    pub fn is_timed_out(&self) -> bool {
+        self.timed_out.load(Ordering::Acquire)
+    }
+
+    /// Returns true if operations that modify the repository are rejected
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
     /// Validates the specified object and returns any problems found. Err will only be returned
     /// if a non-validation problem was encountered.
     pub fn validate_object(
         &self,
         object_id: &str,
-        fixity_check: bool,
+        fixity_check: impl Into<FixityCheck>,
     ) -> Result<ObjectValidationResult> {
         self.ensure_open()?;
-        self.store.validate_object(object_id, fixity_check)
+        self.store.validate_object(object_id, fixity_check.into())
     }
 
     /// Validates the specified object at the specified path, relative the storage root, and
@@ -173,23 +824,76 @@ impl OcflRepo {
     pub fn validate_object_at(
         &self,
         path: &str,
-        fixity_check: bool,
+        fixity_check: impl Into<FixityCheck>,
     ) -> Result<ObjectValidationResult> {
         self.ensure_open()?;
-        self.store.validate_object_at(path, fixity_check)
+        self.store.validate_object_at(path, fixity_check.into())
+    }
+
+    /// Validates an object's staged version, before it is committed, and returns any problems
+    /// found. The staged inventory must be valid, the staged version must be the next version
+    /// in sequence, and every content file added in the staged version must match its digest.
+    /// Err will only be returned if a non-validation problem was encountered.
+    ///
+    /// If the object does not have a staged version, then a `RocflError::NotFound`
+    /// error is returned.
+    pub fn validate_staged(&self, object_id: &str) -> Result<ObjectValidationResult> {
+        self.ensure_open()?;
+
+        let inventory = self.get_staged_inventory(object_id)?;
+
+        let committed_head = match self.store.get_inventory(object_id) {
+            Ok(committed) => Some(committed.head),
+            Err(RocflError::NotFound(_)) => None,
+            Err(e) => return Err(e),
+        };
+
+        // Content that is staged in the bucket must be on disk to be validated
+        let downloaded = self.download_bucket_staged_content(&inventory)?;
+
+        let result = self
+            .get_staging()?
+            .validate_staged_version(&inventory, committed_head);
+
+        for file in downloaded {
+            util::remove_file_ignore_not_found(&file)?;
+            util::clean_dirs_up(file.parent().unwrap())?;
+        }
+
+        result
     }
 
     /// Validates the structure of an OCFL repository as well as all of the objects in the repository
-    /// When `fixity_check` is `false`, then the digests of object content files are not validated.
+    /// `fixity_check` determines which object content files have their digests validated. It may
+    /// be a `bool`, where `true` checks every file.
     ///
     /// The storage root is validated immediately, and an incremental validator is returned that
     /// is used to lazily validate the rest of the repository.
     pub fn validate_repo<'a>(
         &'a self,
-        fixity_check: bool,
+        fixity_check: impl Into<FixityCheck>,
+    ) -> Result<Box<dyn IncrementalValidator + 'a>> {
+        self.validate_repo_filtered(fixity_check, ValidationFilter::new())
+    }
+
+    /// Same as `validate_repo()`, except only the objects that match the filter are validated.
+    /// The storage root is still validated in its entirety, but only the branches of the
+    /// storage hierarchy that may contain matching objects are descended into.
+    pub fn validate_repo_filtered<'a>(
+        &'a self,
+        fixity_check: impl Into<FixityCheck>,
+        filter: ValidationFilter,
     ) -> Result<Box<dyn IncrementalValidator + 'a>> {
         self.ensure_open()?;
-        self.store.validate_repo(fixity_check)
+        self.store.validate_repo(fixity_check.into(), filter)
+    }
+
+    /// Returns the IDs of up to `limit` objects whose IDs start with `prefix`, sorted. This is
+    /// much faster than listing objects because only the object IDs are read, which makes it
+    /// suitable for completing object IDs in a shell.
+    pub fn list_object_ids(&self, prefix: &str, limit: usize) -> Result<Vec<String>> {
+        self.ensure_open()?;
+        self.store.list_object_ids(prefix, limit)
     }
 
     /// Returns details about an OCFL repository
@@ -198,65 +902,455 @@ impl OcflRepo {
         self.store.describe_repo()
     }
 
-    /// Returns details about an OCFL object
-    pub fn describe_object(&self, object_id: &str) -> Result<ObjectInfo> {
+    /// Returns the repository's storage layout configuration, along with any discrepancies
+    /// between the layout declared in `ocfl_layout.json` and the extension configs in the
+    /// repository's `extensions` directory.
+    pub fn verify_layout(&self) -> Result<LayoutInfo> {
         self.ensure_open()?;
-        self.store.describe_object(object_id)
+        self.store.verify_layout()
     }
 
-    /// Returns details about a staged OCFL object
-    ///
-    /// If the object does not have a staged version, then a `RocflError::NotFound`
-    /// error is returned.
-    pub fn describe_staged_object(&self, object_id: &str) -> Result<ObjectInfo> {
+    /// Runs quick structural checks over the repository, without validating it against the OCFL
+    /// spec or fixity checking any content. The checks are: the storage root has a valid namaste
+    /// file and a consistent storage layout, every object can be read, object roots do not
+    /// contain version directories that were left behind by interrupted commits, root
+    /// inventories have sidecars, staged versions follow their object's HEAD version, and no
+    /// lock files are older than `lock_age`.
+    pub fn check_health(&self, lock_age: Duration) -> Result<HealthReport> {
         self.ensure_open()?;
 
+        let mut report = HealthReport::default();
+
+        report.problems.extend(self.store.check_storage_root()?);
+
+        for inventory in self.store.iter_inventories(None)? {
+            report.objects_checked += 1;
+
+            match inventory {
+                Ok(inventory) => report
+                    .problems
+                    .extend(self.store.check_object_root(&inventory)?),
+                Err(e) => report.problems.push(unreadable_object_problem(e, false)),
+            }
+        }
+
         if !self.staging_root.exists() {
-            return Err(RocflError::NotFound(format!(
-                "{} does not have a staged version.",
-                object_id
-            )));
+            return Ok(report);
         }
 
-        match self.get_staging()?.describe_object(object_id) {
-            Ok(inventory) => Ok(inventory),
-            Err(RocflError::NotFound(_)) => Err(RocflError::NotFound(format!(
-                "{} does not have a staged version.",
-                object_id
-            ))),
-            Err(e) => Err(e),
+        for staged in self.get_staging()?.iter_inventories(None)? {
+            report.staged_objects_checked += 1;
+
+            let staged = match staged {
+                Ok(staged) => staged,
+                Err(e) => {
+                    report.problems.push(unreadable_object_problem(e, true));
+                    continue;
+                }
+            };
+
+            let expected = match self.store.get_inventory(&staged.id) {
+                Ok(inventory) => inventory.head.next()?,
+                Err(RocflError::NotFound(_)) => VersionNum::v1(),
+                Err(e) => return Err(e),
+            };
+
+            if staged.head != expected {
+                report.problems.push(
+                    HealthProblem::new(
+                        HealthCheck::Staging,
+                        format!(
+                            "The staged version is {}, but the next version of the object is {}",
+                            staged.head, expected
+                        ),
+                        format!(
+                            "Run 'rocfl reset {}' to discard the staged version",
+                            staged.id
+                        ),
+                    )
+                    .with_object_id(&staged.id)
+                    .with_path(&staged.object_root),
+                );
+            }
+        }
+
+        for lock in lock::list_locks(paths::locks_extension_path(&self.staging_root))? {
+            if let Some(reason) = lock.stale_reason(lock_age) {
+                let mut problem = HealthProblem::new(
+                    HealthCheck::StaleLock,
+                    reason,
+                    "If rocfl is not running against the repository, delete the lock file",
+                )
+                .with_path(lock.path.to_string_lossy());
+                if let Some(info) = &lock.info {
+                    problem = problem.with_object_id(&info.object_id);
+                }
+                report.problems.push(problem);
+            }
         }
+
+        Ok(report)
     }
 
-    /// Returns an iterator that iterate through all of the objects in an OCFL repository.
-    /// Objects are lazy-loaded. An optional glob pattern may be provided to filter the objects
-    /// that are returned.
+    /// Looks for lock files and staged object directories that were left behind by rocfl
+    /// processes that stopped unexpectedly, such as when they crashed or were killed.
     ///
-    /// The iterator returns an error if it encounters a problem accessing an object. This does
-    /// terminate the iterator; there are still more objects until it returns `None`.
-    pub fn list_objects<'a>(
-        &'a self,
-        filter_glob: Option<&str>,
-    ) -> Result<Box<dyn Iterator<Item = Result<ObjectVersionDetails>> + 'a>> {
+    /// A lock is orphaned if the process that acquired it was on this host and is no longer
+    /// running, or, when this cannot be determined, if it is older than `lock_age`. A staged
+    /// object directory is orphaned if it does not contain an inventory, because staging was
+    /// interrupted before it was written, and it is either older than `lock_age` or no other
+    /// process holds a lock in the repository.
+    pub fn find_orphans(&self, lock_age: Duration) -> Result<Vec<Orphan>> {
         self.ensure_open()?;
 
-        let inv_iter = self.store.iter_inventories(filter_glob)?;
+        let mut orphans = Vec::new();
 
-        Ok(Box::new(InventoryAdapterIter::new(inv_iter, |inventory| {
-            ObjectVersionDetails::from_inventory(inventory, VersionRef::Head)
-        })))
-    }
+        if !self.staging_root.exists() {
+            return Ok(orphans);
+        }
 
-    /// Returns an iterator that iterate through all of the staged objects in an OCFL repository.
-    /// Objects are lazy-loaded. An optional glob pattern may be provided to filter the objects
-    /// that are returned.
-    ///
-    /// The iterator returns an error if it encounters a problem accessing an object. This does
-    /// terminate the iterator; there are still more objects until it returns `None`.
+        let mut live_locks = false;
+
+        for lock in lock::list_locks(paths::locks_extension_path(&self.staging_root))? {
+            match lock.stale_reason(lock_age) {
+                Some(reason) => orphans.push(Orphan {
+                    kind: OrphanKind::StaleLock,
+                    object_id: lock.info.map(|info| info.object_id),
+                    path: lock.path,
+                    reason,
+                    modified: lock.modified,
+                }),
+                None => live_locks = true,
+            }
+        }
+
+        let mut walker = WalkDir::new(&self.staging_root)
+            .min_depth(1)
+            .into_iter()
+            .filter_entry(|entry| !(entry.depth() == 1 && entry.file_name() == EXTENSIONS_DIR));
+
+        while let Some(entry) = walker.next() {
+            let entry = entry?;
+
+            if !entry.file_type().is_dir()
+                || !(entry.path().join(OBJECT_NAMASTE_FILE_1_0).exists()
+                    || entry.path().join(OBJECT_NAMASTE_FILE_1_1).exists())
+            {
+                continue;
+            }
+
+            walker.skip_current_dir();
+
+            if paths::inventory_path(entry.path()).exists() {
+                continue;
+            }
+
+            let modified = entry.metadata()?.modified()?;
+            let age = modified.elapsed().unwrap_or_default();
+
+            if live_locks && age <= lock_age {
+                continue;
+            }
+
+            orphans.push(Orphan {
+                kind: OrphanKind::StagingDir,
+                object_id: None,
+                path: entry.into_path(),
+                reason: "The staged object does not have an inventory".to_string(),
+                modified,
+            });
+        }
+
+        Ok(orphans)
+    }
+
+    /// Deletes the lock files and staged object directories that were identified by
+    /// `find_orphans()`. Each orphan is moved aside before it is deleted, and is checked again
+    /// once it has been moved. Orphans that were modified after they were found, or staged objects
+    /// that now have an inventory, are not deleted, because another process is using them.
+    pub fn clean_orphans(&self, orphans: &[Orphan]) -> Result<()> {
+        self.ensure_writable("clean orphaned locks and staging directories")?;
+
+        if orphans.is_empty() {
+            return Ok(());
+        }
+
+        let audit = Audit::start("clean_orphans")
+            .paths(orphans.iter().map(|orphan| orphan.path.to_string_lossy()));
+
+        for orphan in orphans {
+            let aside = match move_orphan_aside(orphan)? {
+                Some(aside) => aside,
+                None => {
+                    info!(
+                        "Not removing {} because it is in use",
+                        orphan.path.to_string_lossy()
+                    );
+                    continue;
+                }
+            };
+
+            match orphan.kind {
+                OrphanKind::StaleLock => util::remove_file_ignore_not_found(&aside)?,
+                OrphanKind::StagingDir => {
+                    fs::remove_dir_all(&aside)?;
+                    if let Some(parent) = orphan.path.parent() {
+                        util::clean_dirs_up(parent)?;
+                    }
+                }
+            }
+        }
+
+        audit.succeeded();
+        Ok(())
+    }
+
+    /// Rewrites the description in the repository's `ocfl_layout.json`. The declared layout
+    /// extension is not changed. If the repository does not have an `ocfl_layout.json`, then a
+    /// `RocflError::NotFound` error is returned.
+    pub fn set_layout_description(&self, description: &str) -> Result<()> {
+        self.ensure_writable("set the layout description")?;
+
+        let audit = Audit::start("set_layout_description");
+        self.store.set_layout_description(description)?;
+
+        audit.succeeded();
+        Ok(())
+    }
+
+    /// Records the validation records in the repository's validation history, replacing the
+    /// existing records of the same objects. Nothing is recorded unless validation history was
+    /// enabled with `with_validation_history()`.
+    pub fn record_validations(&self, records: &[ValidationRecord]) -> Result<()> {
+        if !self.validation_history || records.is_empty() {
+            return Ok(());
+        }
+
+        self.ensure_writable("record validation history")?;
+
+        let audit = Audit::start("record_validations");
+
+        let mut history = self.store.get_validation_history()?;
+        for record in records {
+            history.insert(record.clone());
+        }
+        self.store.set_validation_history(&history)?;
+
+        audit.succeeded();
+        Ok(())
+    }
+
+    /// Returns the record of every object's most recent validation, sorted by object ID. Only
+    /// validations that were recorded with `record_validations()` are returned.
+    pub fn validation_history(&self) -> Result<Vec<ValidationRecord>> {
+        self.ensure_open()?;
+        Ok(self.store.get_validation_history()?.records())
+    }
+
+    /// Returns the record of the object's most recent validation, if one was recorded
+    pub fn validation_record(&self, object_id: &str) -> Result<Option<ValidationRecord>> {
+        self.ensure_open()?;
+        Ok(self.store.get_validation_history()?.get(object_id).cloned())
+    }
+
+    /// Loads the fixity sampling state of every object from the validation history. Afterwards,
+    /// sampling an object with `FixitySample::continue_recorded` set, and the same percentage as
+    /// its recorded sample, checks the content files that its previous samples did not, until
+    /// every file was checked, and the sample's seed and round only apply to objects without a
+    /// recorded sample. Nothing is loaded unless validation history was enabled with
+    /// `with_validation_history()`.
+    pub fn continue_fixity_samples(&self) -> Result<()> {
+        self.ensure_open()?;
+
+        if !self.validation_history {
+            return Ok(());
+        }
+
+        let samples = self
+            .store
+            .get_validation_history()?
+            .records()
+            .into_iter()
+            .filter_map(|record| {
+                record
+                    .fixity_sample
+                    .map(|sample| (record.object_id, sample))
+            })
+            .collect();
+        self.store.set_sample_history(samples);
+
+        Ok(())
+    }
+
+    /// Marks an object as frozen. Frozen objects cannot be committed to, upgraded, or purged
+    /// until they are unfrozen, unless `set_override_frozen()` is enabled. If the object is
+    /// already frozen, its marker is replaced.
+    ///
+    /// If the object does not exist, then a `RocflError::NotFound` error is returned.
+    pub fn freeze_object(&self, object_id: &str, reason: Option<&str>) -> Result<()> {
+        self.ensure_writable("freeze an object")?;
+
+        let audit = Audit::start("freeze").object_id(object_id);
+        let _lock = self.get_lock_manager()?.acquire(object_id)?;
+
+        let marker = FreezeMarker {
+            object_id: object_id.to_string(),
+            timestamp: Local::now(),
+            reason: reason.map(str::to_string),
+        };
+
+        self.store.set_freeze_marker(object_id, Some(&marker))?;
+
+        audit.succeeded();
+        Ok(())
+    }
+
+    /// Removes an object's freeze marker. Nothing happens if the object is not frozen.
+    ///
+    /// If the object does not exist, then a `RocflError::NotFound` error is returned.
+    pub fn unfreeze_object(&self, object_id: &str) -> Result<()> {
+        self.ensure_writable("unfreeze an object")?;
+
+        let audit = Audit::start("unfreeze").object_id(object_id);
+        let _lock = self.get_lock_manager()?.acquire(object_id)?;
+
+        self.store.set_freeze_marker(object_id, None)?;
+
+        audit.succeeded();
+        Ok(())
+    }
+
+    /// Returns the object's freeze marker, or `None` if the object is not frozen.
+    ///
+    /// If the object does not exist, then a `RocflError::NotFound` error is returned.
+    pub fn get_freeze_marker(&self, object_id: &str) -> Result<Option<FreezeMarker>> {
+        self.ensure_open()?;
+        self.with_alias_fallback(object_id, |id| self.store.get_freeze_marker(id))
+    }
+
+    /// Returns true if the object exists in the repository. This is much cheaper than reading
+    /// the object because only the object's namaste file is checked. If the object is not found,
+    /// and the ID is an alias in the repository's alias index, then the existence of the object
+    /// the alias identifies is returned.
+    pub fn object_exists(&self, object_id: &str) -> Result<bool> {
+        self.ensure_open()?;
+
+        if self.store.object_exists(object_id)? {
+            return Ok(true);
+        }
+
+        match self.store.resolve_alias(object_id)? {
+            Some(resolved) => self.store.object_exists(&resolved),
+            None => Ok(false),
+        }
+    }
+
+    /// Returns the number of the object's HEAD version without reading the object's inventory,
+    /// unless the object has a mutable HEAD. If the object does not exist, then a
+    /// `RocflError::NotFound` error is returned.
+    pub fn head_version(&self, object_id: &str) -> Result<VersionNum> {
+        self.ensure_open()?;
+        self.with_alias_fallback(object_id, |id| self.store.head_version(id))
+    }
+
+    /// Returns the digest of the object's root inventory, as recorded in its sidecar, and, for
+    /// repositories in S3 or other cloud storage, the inventory's ETag. The inventory itself is
+    /// not read, so this is a cheap way for sync tools to detect that an object changed since
+    /// they last saw it. Changes to a mutable HEAD are not reflected in the fingerprint. If the
+    /// object does not exist, then a `RocflError::NotFound` error is returned.
+    pub fn object_fingerprint(&self, object_id: &str) -> Result<ObjectFingerprint> {
+        self.ensure_open()?;
+        self.with_alias_fallback(object_id, |id| self.store.get_object_fingerprint(id))
+    }
+
+    /// Returns details about an OCFL object
+    pub fn describe_object(&self, object_id: &str) -> Result<ObjectInfo> {
+        self.ensure_open()?;
+        self.with_alias_fallback(object_id, |id| self.store.describe_object(id))
+    }
+
+    /// Returns details about a staged OCFL object
+    ///
+    /// If the object does not have a staged version, then a `RocflError::NotFound`
+    /// error is returned.
+    pub fn describe_staged_object(&self, object_id: &str) -> Result<ObjectInfo> {
+        self.ensure_open()?;
+
+        if !self.staging_root.exists() {
+            return Err(RocflError::NotFound(format!(
+                "{} does not have a staged version.",
+                object_id
+            )));
+        }
+
+        match self.get_staging()?.describe_object(object_id) {
+            Ok(inventory) => Ok(inventory),
+            Err(RocflError::NotFound(_)) => Err(RocflError::NotFound(format!(
+                "{} does not have a staged version.",
+                object_id
+            ))),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Returns an iterator that iterate through all of the objects in an OCFL repository.
+    /// Objects are lazy-loaded. An optional glob pattern may be provided to filter the objects
+    /// that are returned.
+    ///
+    /// The iterator returns an `ObjectError` if it encounters a problem accessing an object. The
+    /// error identifies the object, by ID and/or path, that could not be read. This does not
+    /// terminate the iterator; there are still more objects until it returns `None`.
+    pub fn list_objects<'a>(
+        &'a self,
+        filter_glob: Option<&str>,
+    ) -> Result<Box<dyn Iterator<Item = Result<ObjectVersionDetails, ObjectError>> + 'a>> {
+        self.list_objects_filtered(filter_glob, ListFilter::new())
+    }
+
+    /// Same as `list_objects()`, except only the objects that match the filter are returned.
+    /// Objects are filtered as their inventories are read, before their details are constructed.
+    /// Filtering on size requires the size of every content file in each object to be read,
+    /// which is slow, particularly in S3, and is only done for objects that match the date range.
+    pub fn list_objects_filtered<'a>(
+        &'a self,
+        filter_glob: Option<&str>,
+        filter: ListFilter,
+    ) -> Result<Box<dyn Iterator<Item = Result<ObjectVersionDetails, ObjectError>> + 'a>> {
+        self.ensure_open()?;
+
+        let inv_iter = self
+            .store
+            .iter_inventories(filter_glob)?
+            .filter_map(move |inventory| match inventory {
+                Ok(inventory) => match self.inventory_matches(&inventory, &filter) {
+                    Ok(true) => Some(Ok(inventory)),
+                    Ok(false) => None,
+                    Err(e) => Some(Err(ObjectError::new(
+                        Some(inventory.id.clone()),
+                        Some(inventory.storage_path.clone()),
+                        e,
+                    ))),
+                },
+                Err(e) => Some(Err(e)),
+            });
+
+        Ok(Box::new(InventoryAdapterIter::new(
+            Box::new(inv_iter),
+            |inventory| ObjectVersionDetails::from_inventory(inventory, VersionRef::Head),
+        )))
+    }
+
+    /// Returns an iterator that iterate through all of the staged objects in an OCFL repository.
+    /// Objects are lazy-loaded. An optional glob pattern may be provided to filter the objects
+    /// that are returned.
+    ///
+    /// The iterator returns an `ObjectError` if it encounters a problem accessing an object. The
+    /// error identifies the object, by ID and/or path, that could not be read. This does not
+    /// terminate the iterator; there are still more objects until it returns `None`.
     pub fn list_staged_objects<'a>(
         &'a self,
         filter_glob: Option<&str>,
-    ) -> Result<Box<dyn Iterator<Item = Result<ObjectVersionDetails>> + 'a>> {
+    ) -> Result<Box<dyn Iterator<Item = Result<ObjectVersionDetails, ObjectError>> + 'a>> {
         self.ensure_open()?;
 
         if !self.staging_root.exists() {
@@ -278,7 +1372,7 @@ impl OcflRepo {
     pub fn get_object(&self, object_id: &str, version_num: VersionRef) -> Result<ObjectVersion> {
         self.ensure_open()?;
 
-        let inventory = self.store.get_inventory(object_id)?;
+        let inventory = self.get_inventory(object_id)?;
         let object_root = inventory.storage_path.clone();
 
         ObjectVersion::from_inventory(
@@ -290,6 +1384,137 @@ impl OcflRepo {
         )
     }
 
+    /// Same as `get_object()`, except the version's state only contains the logical paths that
+    /// match `path_glob`, or that are within a logical directory that matches it. A path prefix,
+    /// such as `a/b`, selects everything under the directory. Only the details of the matching
+    /// paths are resolved, which is much cheaper than reconstructing the entire state of an
+    /// object with many files.
+    ///
+    /// If the object or version of the object cannot be found, then a `RocflError::NotFound`
+    /// error is returned.
+    pub fn get_object_subset(
+        &self,
+        object_id: &str,
+        version_num: VersionRef,
+        path_glob: &str,
+    ) -> Result<ObjectVersion> {
+        self.ensure_open()?;
+
+        let matcher = GlobBuilder::new(path_glob.trim_matches('/'))
+            .literal_separator(true)
+            .backslash_escape(true)
+            .build()?
+            .compile_matcher();
+
+        let inventory = self.get_inventory(object_id)?;
+        let object_root = inventory.storage_path.clone();
+
+        ObjectVersion::from_inventory_subset(
+            inventory,
+            version_num,
+            &object_root,
+            None,
+            self.use_backslashes,
+            |path| {
+                let path = path.as_str();
+                matcher.is_match(path)
+                    || path
+                        .match_indices('/')
+                        .any(|(i, _)| matcher.is_match(&path[..i]))
+            },
+        )
+    }
+
+    /// Same as `get_object()`, except the version's state only contains the files that match the
+    /// filter. Files are matched on the date of the version they were last updated in, and the
+    /// size of their content, which is only read when the filter restricts sizes.
+    pub fn get_object_filtered(
+        &self,
+        object_id: &str,
+        version_num: VersionRef,
+        filter: ListFilter,
+    ) -> Result<ObjectVersion> {
+        self.ensure_open()?;
+
+        let inventory = self.get_inventory(object_id)?;
+        let object_root = inventory.storage_path.clone();
+
+        let sizes = if filter.filters_size() {
+            Some(self.content_sizes(&inventory, version_num)?)
+        } else {
+            None
+        };
+
+        let mut object = ObjectVersion::from_inventory(
+            inventory,
+            version_num,
+            &object_root,
+            None,
+            self.use_backslashes,
+        )?;
+
+        object.state.retain(|_, details| {
+            filter.matches_created(&details.last_update.created)
+                && sizes.as_ref().is_none_or(|sizes| {
+                    sizes
+                        .get(&details.digest)
+                        .is_some_and(|size| filter.matches_size(*size))
+                })
+        });
+
+        Ok(object)
+    }
+
+    /// Returns the logical paths of the specified version of an object as a tree of logical
+    /// directories and files. When `sizes` is true, the size of every file and directory is
+    /// set, which requires looking up the size of every content file in the version.
+    ///
+    /// If the object or version cannot be found, then a `RocflError::NotFound` error is returned.
+    pub fn get_object_tree(
+        &self,
+        object_id: &str,
+        version_num: VersionRef,
+        sizes: bool,
+    ) -> Result<StateTree> {
+        self.ensure_open()?;
+
+        let inventory = self.get_inventory(object_id)?;
+
+        let mut digest_sizes = HashMap::new();
+        if sizes {
+            let marker = self.encryption_marker(&inventory.id)?;
+            let version = inventory.get_version(version_num.resolve(inventory.head))?;
+            for (_, digest) in version.state_iter() {
+                if digest_sizes.contains_key(digest) {
+                    continue;
+                }
+                if let Some(content_path) = inventory
+                    .content_paths(digest)
+                    .and_then(|paths| paths.iter().next())
+                {
+                    let size = self.content_file_size(&inventory, content_path, marker.as_ref())?;
+                    digest_sizes.insert(digest.clone(), size);
+                }
+            }
+        }
+
+        let object_root = inventory.storage_path.clone();
+        let object = ObjectVersion::from_inventory(
+            inventory,
+            version_num,
+            &object_root,
+            None,
+            self.use_backslashes,
+        )?;
+
+        let mut tree = StateTree::from_state(object.state);
+        if sizes {
+            tree.set_sizes(&digest_sizes);
+        }
+
+        Ok(tree)
+    }
+
     /// Same as `get_object()` except that it returns the staged version of an object.
     ///
     /// If the object does not have a staged version, then a `RocflError::NotFound`
@@ -335,7 +1560,7 @@ impl OcflRepo {
     ) -> Result<ObjectVersionDetails> {
         self.ensure_open()?;
 
-        let inventory = self.store.get_inventory(object_id)?;
+        let inventory = self.get_inventory(object_id)?;
         ObjectVersionDetails::from_inventory(inventory, version_num)
     }
 
@@ -356,16 +1581,69 @@ impl OcflRepo {
     ///
     /// If the object cannot be found, then a `RocflError::NotFound` error is returned.
     pub fn list_object_versions(&self, object_id: &str) -> Result<Vec<VersionDetails>> {
+        self.list_object_versions_page(object_id, VersionPage::new())
+    }
+
+    /// Returns the version metadata for the versions of an object that are selected by `page`.
+    /// Only the selected versions are processed, which makes this suitable for displaying the
+    /// most recent versions of objects with long histories.
+    ///
+    /// If the object cannot be found, then a `RocflError::NotFound` error is returned.
+    pub fn list_object_versions_page(
+        &self,
+        object_id: &str,
+        page: VersionPage,
+    ) -> Result<Vec<VersionDetails>> {
         self.ensure_open()?;
 
-        let inventory = self.store.get_inventory(object_id)?;
-        let mut versions = Vec::with_capacity(inventory.versions.len());
+        let inventory = self.get_inventory(object_id)?;
+        Ok(page
+            .select(inventory.versions.into_iter())
+            .into_iter()
+            .map(|(id, version)| VersionDetails::from_version(id, version))
+            .collect())
+    }
 
-        for (id, version) in inventory.versions {
-            versions.push(VersionDetails::from_version(id, version))
+    /// Returns a vector containing the number of content files, and their total size, that were
+    /// added to an object in each of its versions. The vector is sorted in ascending order.
+    ///
+    /// If the object cannot be found, then a `RocflError::NotFound` error is returned.
+    pub fn list_object_version_sizes(&self, object_id: &str) -> Result<Vec<VersionContentSize>> {
+        self.ensure_open()?;
+
+        let inventory = self.get_inventory(object_id)?;
+
+        let mut sizes: HashMap<VersionNum, VersionContentSize> = inventory
+            .versions
+            .keys()
+            .map(|version_num| {
+                (
+                    *version_num,
+                    VersionContentSize {
+                        version_num: *version_num,
+                        file_count: 0,
+                        byte_count: 0,
+                    },
+                )
+            })
+            .collect();
+
+        let marker = self.encryption_marker(&inventory.id)?;
+
+        for (content_path, _) in inventory.manifest().iter() {
+            if let ContentPathVersion::VersionNum(version_num) = content_path.version {
+                if let Some(size) = sizes.get_mut(&version_num) {
+                    size.file_count += 1;
+                    size.byte_count +=
+                        self.content_file_size(&inventory, content_path, marker.as_ref())?;
+                }
+            }
         }
 
-        Ok(versions)
+        let mut sizes: Vec<VersionContentSize> = sizes.into_values().collect();
+        sizes.sort_unstable_by_key(|size| size.version_num);
+
+        Ok(sizes)
     }
 
     /// Writes the specified file to the sink.
@@ -380,11 +1658,242 @@ impl OcflRepo {
     ) -> Result<()> {
         self.ensure_open()?;
 
-        self.store
-            .get_object_file(object_id, path, version_num, sink)
+        let read = |object_id: &str, sink: &mut dyn Write| {
+            read_content(self.content_key(object_id)?, sink, |writer| {
+                self.store
+                    .get_object_file(object_id, path, version_num, writer)
+            })
+        };
+
+        match read(object_id, sink) {
+            Err(RocflError::NotFound(message)) => match self.store.resolve_alias(object_id)? {
+                Some(resolved) => read(&resolved, sink),
+                None => Err(RocflError::NotFound(message)),
+            },
+            result => result,
+        }
     }
 
-    /// Writes the specified file from the staged version of the object to the sink.
+    /// Same as `get_object_file()`, except that the file's content is hashed as it is written to
+    /// the sink, and a `RocflError::CorruptObject` error is returned if its digest does not match
+    /// the digest in the object's manifest. The content has already been written to the sink
+    /// when the mismatch is detected.
+    pub fn get_object_file_verified(
+        &self,
+        object_id: &str,
+        path: &LogicalPath,
+        version_num: VersionRef,
+        sink: &mut dyn Write,
+    ) -> Result<()> {
+        self.ensure_open()?;
+
+        let inventory = self.get_inventory(object_id)?;
+        let content_path = inventory.content_path_for_logical_path(path, version_num)?;
+        let expected = inventory
+            .digest_for_content_path(content_path)
+            .ok_or_else(|| {
+                not_found_path(&inventory.id, version_num.resolve(inventory.head), path)
+            })?;
+
+        let key = self.content_key(&inventory.id)?;
+
+        verify_read(&inventory, path, expected, sink, |writer| {
+            read_content(key, writer, |writer| {
+                self.store
+                    .get_content_file(&inventory, content_path, writer)
+            })
+        })
+    }
+
+    /// Returns the paths, relative the object's `logs` directory, of every file in the object's
+    /// `logs` directory, sorted by path. If the object does not have a `logs` directory, then an
+    /// empty vector is returned.
+    pub fn list_object_logs(&self, object_id: &str) -> Result<Vec<String>> {
+        self.ensure_open()?;
+        self.with_alias_fallback(object_id, |id| self.store.list_object_logs(id))
+    }
+
+    /// Writes the file at the specified path, relative the object's `logs` directory, to the sink.
+    ///
+    /// If the file cannot be found, then a `RocflError::NotFound` error is returned.
+    pub fn get_object_log_file(
+        &self,
+        object_id: &str,
+        path: &str,
+        sink: &mut dyn Write,
+    ) -> Result<()> {
+        self.ensure_open()?;
+
+        // Log paths follow the same rules as logical paths, which prevents escaping the logs dir
+        let path = LogicalPath::try_from(path)?;
+
+        if path.as_str().is_empty() {
+            return Err(RocflError::InvalidValue(
+                "A log file path must be specified".to_string(),
+            ));
+        }
+
+        match self
+            .store
+            .get_object_log_file(object_id, path.as_str(), sink)
+        {
+            Err(RocflError::NotFound(message)) => match self.store.resolve_alias(object_id)? {
+                Some(resolved) => self
+                    .store
+                    .get_object_log_file(&resolved, path.as_str(), sink),
+                None => Err(RocflError::NotFound(message)),
+            },
+            result => result,
+        }
+    }
+
+    /// Returns the paths, relative the object's `extensions` directory, of every file in the
+    /// object's `extensions` directory, sorted by path. The first segment of each path is the
+    /// name of the extension the file belongs to. If the object does not have an `extensions`
+    /// directory, then an empty vector is returned.
+    pub fn list_object_extension_files(&self, object_id: &str) -> Result<Vec<String>> {
+        self.ensure_open()?;
+        self.with_alias_fallback(object_id, |id| self.store.list_object_extension_files(id))
+    }
+
+    /// Writes the file at the specified path, relative the object's `extensions` directory, to
+    /// the sink.
+    ///
+    /// If the file cannot be found, then a `RocflError::NotFound` error is returned.
+    pub fn get_object_extension_file(
+        &self,
+        object_id: &str,
+        path: &str,
+        sink: &mut dyn Write,
+    ) -> Result<()> {
+        self.ensure_open()?;
+
+        // Extension paths follow the same rules as logical paths, which prevents escaping the
+        // extensions dir
+        let path = LogicalPath::try_from(path)?;
+
+        if path.as_str().is_empty() {
+            return Err(RocflError::InvalidValue(
+                "An extension file path must be specified".to_string(),
+            ));
+        }
+
+        match self
+            .store
+            .get_object_extension_file(object_id, path.as_str(), sink)
+        {
+            Err(RocflError::NotFound(message)) => match self.store.resolve_alias(object_id)? {
+                Some(resolved) => {
+                    self.store
+                        .get_object_extension_file(&resolved, path.as_str(), sink)
+                }
+                None => Err(RocflError::NotFound(message)),
+            },
+            result => result,
+        }
+    }
+
+    /// Returns the audit trail entries that were recorded in the object's `logs` directory, oldest
+    /// first. An empty list is returned if the object does not have an audit trail.
+    ///
+    /// If the object cannot be found, then a `RocflError::NotFound` error is returned.
+    pub fn list_audit_trail(&self, object_id: &str) -> Result<Vec<AuditTrailEntry>> {
+        self.ensure_open()?;
+
+        let inventory = self.get_inventory(object_id)?;
+
+        let mut bytes = Vec::new();
+        match self
+            .store
+            .get_object_log_file(&inventory.id, AUDIT_TRAIL_FILE, &mut bytes)
+        {
+            Ok(()) => (),
+            Err(RocflError::NotFound(_)) => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        }
+
+        bytes
+            .split(|b| *b == b'\n')
+            .filter(|line| !line.iter().all(u8::is_ascii_whitespace))
+            .map(|line| serde_json::from_slice(line).map_err(RocflError::from))
+            .collect()
+    }
+
+    /// Returns the inventory signatures that were recorded in the object's `logs` directory,
+    /// oldest first. An empty list is returned if none of the object's versions were signed.
+    ///
+    /// If the object cannot be found, then a `RocflError::NotFound` error is returned.
+    pub fn list_inventory_signatures(&self, object_id: &str) -> Result<Vec<InventorySignature>> {
+        self.ensure_open()?;
+
+        let inventory = self.get_inventory(object_id)?;
+        self.read_signatures(&inventory.id)
+    }
+
+    /// Verifies the signature of every version of the object against the version's inventory.
+    /// The HEAD version's signature is verified against the object's root inventory, and every
+    /// other version's against the inventory in its version directory. A version is only valid
+    /// if its inventory was signed by one of the trusted keys.
+    ///
+    /// If the object cannot be found, then a `RocflError::NotFound` error is returned.
+    pub fn verify_signatures(
+        &self,
+        object_id: &str,
+        trusted_keys: &[VerifyingKey],
+    ) -> Result<SignatureReport> {
+        self.ensure_open()?;
+
+        let inventory = self.get_inventory(object_id)?;
+        let signatures = self.read_signatures(&inventory.id)?;
+
+        let mut versions = Vec::with_capacity(inventory.versions.len());
+
+        for version_num in inventory.versions.keys() {
+            self.ensure_open()?;
+
+            let version_signatures: Vec<&InventorySignature> = signatures
+                .iter()
+                .filter(|signature| signature.version == *version_num)
+                .rev()
+                .collect();
+
+            let status = if version_signatures.is_empty() {
+                SignatureStatus::Unsigned
+            } else {
+                let location = if *version_num == inventory.head {
+                    None
+                } else {
+                    Some(*version_num)
+                };
+
+                let mut bytes = Vec::new();
+                match self
+                    .store
+                    .get_inventory_file(&inventory.id, location, &mut bytes)
+                {
+                    Ok(()) => {
+                        signature::verify_inventory(&bytes, &version_signatures, trusted_keys)
+                    }
+                    Err(RocflError::NotFound(_)) => {
+                        SignatureStatus::Invalid("The inventory does not exist".to_string())
+                    }
+                    Err(e) => return Err(e),
+                }
+            };
+
+            versions.push(VersionSignature {
+                version: *version_num,
+                status,
+            });
+        }
+
+        Ok(SignatureReport {
+            object_id: inventory.id,
+            versions,
+        })
+    }
+
+    /// Writes the specified file from the staged version of the object to the sink.
     ///
     /// If the file cannot be found, then a `RocflError::NotFound` error is returned.
     pub fn get_staged_object_file(
@@ -396,19 +1905,98 @@ impl OcflRepo {
         self.ensure_open()?;
 
         let inventory = self.get_staged_inventory(object_id)?;
-        let content_path = inventory.content_path_for_logical_path(path, VersionRef::Head)?;
+        self.read_staged_file(&inventory, path, sink)
+    }
 
-        let version_prefix = format!("{}/", inventory.head);
+    /// Same as `get_staged_object_file()`, except that the file's content is verified against
+    /// its digest in the staged inventory, as described in `get_object_file_verified()`.
+    pub fn get_staged_object_file_verified(
+        &self,
+        object_id: &str,
+        path: &LogicalPath,
+        sink: &mut dyn Write,
+    ) -> Result<()> {
+        self.ensure_open()?;
 
-        if content_path.starts_with(&version_prefix) {
-            // The content exists in staging
-            self.get_staging()?
-                .get_object_file(object_id, path, VersionRef::Head, sink)
-        } else {
-            // The content exists in the main repo
-            self.store
-                .get_object_file(object_id, path, inventory.head.previous()?.into(), sink)
+        let inventory = self.get_staged_inventory(object_id)?;
+        let expected = inventory
+            .head_version()
+            .lookup_digest(path)
+            .ok_or_else(|| not_found_path(&inventory.id, inventory.head, path))?;
+
+        verify_read(&inventory, path, expected, sink, |writer| {
+            self.read_staged_file(&inventory, path, writer)
+        })
+    }
+
+    /// Returns the key/value descriptive metadata that is stored in the JSON file at
+    /// `metadata_path` in the specified version of an object. An empty map is returned if the
+    /// version does not contain the file.
+    pub fn get_object_metadata(
+        &self,
+        object_id: &str,
+        version_num: VersionRef,
+        metadata_path: &LogicalPath,
+    ) -> Result<BTreeMap<String, String>> {
+        self.ensure_open()?;
+
+        let inventory = self.get_inventory(object_id)?;
+
+        if inventory
+            .content_path_for_logical_path(metadata_path, version_num)
+            .is_err()
+        {
+            return Ok(BTreeMap::new());
         }
+
+        let mut bytes = Vec::new();
+        read_content(self.content_key(&inventory.id)?, &mut bytes, |writer| {
+            self.store
+                .get_object_file(&inventory.id, metadata_path, version_num, writer)
+        })?;
+        parse_metadata(metadata_path, &bytes)
+    }
+
+    /// Returns the key/value descriptive metadata that is stored in the JSON file at
+    /// `metadata_path` in the staged version of an object. An empty map is returned if the
+    /// staged version does not contain the file.
+    pub fn get_staged_object_metadata(
+        &self,
+        object_id: &str,
+        metadata_path: &LogicalPath,
+    ) -> Result<BTreeMap<String, String>> {
+        self.ensure_open()?;
+
+        let inventory = self.get_staged_inventory(object_id)?;
+        self.read_staged_metadata(&inventory, metadata_path)
+    }
+
+    /// Returns the original modification times of the files in the specified version of an
+    /// object, keyed on logical path, that were recorded when the files were copied or moved into
+    /// the object with `set_preserve_timestamps()` enabled. Files without a recorded time are
+    /// not included.
+    pub fn get_original_timestamps(
+        &self,
+        object_id: &str,
+        version_num: VersionRef,
+    ) -> Result<BTreeMap<String, DateTime<Local>>> {
+        let timestamps = self.get_object_metadata(
+            object_id,
+            version_num,
+            &ORIGINAL_TIMESTAMPS_FILE.try_into()?,
+        )?;
+        Ok(parse_timestamps(timestamps))
+    }
+
+    /// Returns the original modification times of the files in the staged version of an
+    /// object. See `get_original_timestamps()`.
+    pub fn get_staged_original_timestamps(
+        &self,
+        object_id: &str,
+    ) -> Result<BTreeMap<String, DateTime<Local>>> {
+        let timestamps =
+            self.get_staged_object_metadata(object_id, &ORIGINAL_TIMESTAMPS_FILE.try_into()?)?;
+        Ok(parse_timestamps(timestamps))
     }
 
     /// Returns a vector contain the version metadata for every version of an object that
@@ -422,7 +2010,7 @@ impl OcflRepo {
     ) -> Result<Vec<VersionDetails>> {
         self.ensure_open()?;
 
-        let inventory = self.store.get_inventory(object_id)?;
+        let inventory = self.get_inventory(object_id)?;
 
         let mut versions = Vec::new();
 
@@ -466,946 +2054,4324 @@ impl OcflRepo {
         object_id: &str,
         left_version: Option<VersionNum>,
         right_version: VersionNum,
+    ) -> Result<Vec<Diff>> {
+        self.diff_with_renames(
+            object_id,
+            left_version,
+            right_version,
+            RenameDetection::default(),
+        )
+    }
+
+    /// The same as `diff()`, except that renames are detected as specified. Use
+    /// `RenameDetection::Off` to report every change as an add or delete.
+    pub fn diff_with_renames(
+        &self,
+        object_id: &str,
+        left_version: Option<VersionNum>,
+        right_version: VersionNum,
+        renames: RenameDetection,
     ) -> Result<Vec<Diff>> {
         self.ensure_open()?;
 
-        self.store
-            .get_inventory(object_id)?
-            .diff_versions(left_version, right_version)
+        self.get_inventory(object_id)?.diff_versions_with_renames(
+            left_version,
+            right_version,
+            renames,
+        )
     }
 
-    /// Returns all of the staged changes to the specified object, if there are any.
-    pub fn diff_staged(&self, object_id: &str) -> Result<Vec<Diff>> {
+    /// Returns the changes made in each version in the range, relative the version before it.
+    /// The object's inventory is read once, and each version's changes are computed as the
+    /// iterator is advanced. Renames are detected as specified.
+    ///
+    /// If the object, or the end version of the range, cannot be found, then a
+    /// `RocflError::NotFound` error is returned.
+    pub fn diff_steps(
+        &self,
+        object_id: &str,
+        range: VersionRange,
+        renames: RenameDetection,
+    ) -> Result<impl Iterator<Item = Result<VersionDiff>>> {
         self.ensure_open()?;
 
-        if !self.staging_root.exists() {
-            return Ok(Vec::new());
-        }
+        let inventory = self.get_inventory(object_id)?;
+        inventory.get_version(range.end)?;
 
-        match self.get_staging()?.get_inventory(object_id) {
-            Err(RocflError::NotFound(_)) => Ok(Vec::new()),
-            Err(e) => Err(e),
-            Ok(inventory) => inventory.diff_versions(None, inventory.head),
-        }
+        Ok(range.versions().map(move |version_num| {
+            Ok(VersionDiff {
+                version_num,
+                diffs: inventory.diff_versions_with_renames(None, version_num, renames)?,
+            })
+        }))
     }
 
-    /// Completely removes the specified object from the repository. If the object doest not exist,
-    /// nothing happens.
-    pub fn purge_object(&self, object_id: &str) -> Result<()> {
+    /// Returns the changes made in each of the specified versions, relative the version before
+    /// it, in the order the versions were specified. The object's inventory is read once for all
+    /// of the versions. Renames are detected as specified.
+    ///
+    /// If the object, or any of the versions, cannot be found, then a `RocflError::NotFound`
+    /// error is returned.
+    pub fn diff_versions(
+        &self,
+        object_id: &str,
+        versions: &[VersionNum],
+        renames: RenameDetection,
+    ) -> Result<Vec<VersionDiff>> {
         self.ensure_open()?;
 
-        if self.staging_root.exists() {
-            self.get_staging()?.purge_object(object_id)?;
-        }
+        let inventory = self.get_inventory(object_id)?;
 
-        // Last chance for the user to have ctrl-c'd the operation
-        if self.is_open() {
-            self.store.purge_object(object_id)
-        } else {
-            Ok(())
-        }
+        versions
+            .iter()
+            .map(|version_num| {
+                Ok(VersionDiff {
+                    version_num: *version_num,
+                    diffs: inventory.diff_versions_with_renames(None, *version_num, renames)?,
+                })
+            })
+            .collect()
     }
 
-    /// Stages a new OCFL object if there is not an existing object with the same ID. The object
-    /// is not inserted into the repository until it is committed.
+    /// Creates a patch that describes the changes between two versions of an object, so that
+    /// they can be replayed onto another object with `apply_patch()`. When `left_version` is
+    /// `None`, the changes are relative the version before `right_version`. Renames are detected
+    /// as specified. When `inline` is true, the content of every added and modified file is
+    /// included in the patch, otherwise it is referenced by digest.
     ///
-    /// If `spec_version` is not provided, then the repository version is used. If the repository
-    /// version is unknown, then the latest supported OCFL version is used.
-    pub fn create_object(
+    /// If the object, or either version, cannot be found, then a `RocflError::NotFound` error is
+    /// returned.
+    pub fn create_patch(
         &self,
         object_id: &str,
-        spec_version: Option<SpecVersion>,
-        digest_algorithm: DigestAlgorithm,
-        content_dir: &str,
-        padding_width: u32,
-    ) -> Result<()> {
+        left_version: Option<VersionNum>,
+        right_version: VersionNum,
+        renames: RenameDetection,
+        inline: bool,
+    ) -> Result<ObjectPatch> {
         self.ensure_open()?;
 
-        let object_id = object_id.trim();
-        let repo_version = self.spec_version.read().unwrap().clone();
+        let inventory = self.get_inventory(object_id)?;
+        let diffs = inventory.diff_versions_with_renames(left_version, right_version, renames)?;
+        let version = inventory.get_version(right_version)?;
 
-        let object_version = if let Some(object_version) = spec_version {
-            if let Some(Known(repo_version)) = &repo_version {
-                validate::validate_spec_version(object_version, *repo_version)?;
-            }
-            object_version
-        } else if let Some(Known(repo_version)) = &repo_version {
-            info!("Defaulting object version to {}", repo_version.version());
-            *repo_version
-        } else {
-            info!(
-                "Unknown repository version, defaulting object version to {}",
-                DEFAULT_VERSION.version()
-            );
-            DEFAULT_VERSION
+        let from_version = match left_version {
+            Some(left_version) => Some(left_version),
+            None if right_version.number > 1 => Some(right_version.previous()?),
+            None => None,
         };
+        let from = from_version
+            .map(|from_version| inventory.get_version(from_version))
+            .transpose()?;
 
-        validate::validate_object_id(object_id)?;
-        validate::validate_digest_algorithm(digest_algorithm)?;
-        validate::validate_content_dir(content_dir)?;
-
-        let _lock = self.get_lock_manager()?.acquire(object_id)?;
+        let previous_digest = |path: &LogicalPath| -> Option<String> {
+            from.and_then(|from| from.lookup_digest(path))
+                .map(|digest| digest.to_string())
+        };
 
-        match self.store.get_inventory(object_id) {
-            Err(RocflError::NotFound(_)) => (),
-            Err(e) => return Err(e),
-            _ => {
-                return Err(RocflError::IllegalState(format!(
-                    "Cannot create object {} because it already exists",
-                    object_id
-                )));
-            }
-        }
+        let patch_file = |path: &LogicalPath, modified: bool| -> Result<PatchFile> {
+            let digest = version
+                .lookup_digest(path)
+                .ok_or_else(|| not_found_path(object_id, right_version, path))?;
+
+            let data = if inline {
+                let mut content = Vec::new();
+                self.get_object_file(
+                    object_id,
+                    path,
+                    VersionRef::Number(right_version),
+                    &mut content,
+                )?;
+                Some(base64::encode(content))
+            } else {
+                None
+            };
 
-        let version_num = VersionNum::v1_with_width(padding_width);
+            Ok(PatchFile {
+                path: path.to_string(),
+                digest: digest.to_string(),
+                previous_digest: if modified {
+                    previous_digest(path)
+                } else {
+                    None
+                },
+                data,
+            })
+        };
 
-        let mut inventory = Inventory::builder(object_id, object_version)
-            .with_digest_algorithm(digest_algorithm)
-            .with_content_directory(content_dir)
-            .with_head(version_num)
-            .build()?;
+        let mut changes = Vec::with_capacity(diffs.len());
+
+        for diff in diffs {
+            changes.push(match diff {
+                Diff::Added(path) => PatchChange::Add(patch_file(&path, false)?),
+                Diff::Modified(path) => PatchChange::Modify(patch_file(&path, true)?),
+                Diff::Deleted(path) => PatchChange::Delete {
+                    path: path.to_string(),
+                    digest: previous_digest(&path),
+                },
+                Diff::Renamed { original, renamed } => PatchChange::Rename {
+                    from: original.iter().map(|path| path.to_string()).collect(),
+                    to: renamed.iter().map(|path| path.to_string()).collect(),
+                    digest: original.first().and_then(|path| previous_digest(path)),
+                },
+            });
+        }
 
-        self.get_staging()?.stage_object(&mut inventory)
+        Ok(ObjectPatch {
+            format: PATCH_FORMAT.to_string(),
+            object_id: object_id.to_string(),
+            from_version,
+            to_version: right_version,
+            digest_algorithm: inventory.digest_algorithm,
+            changes,
+        })
     }
 
-    /// Copies files from outside the OCFL repository into the specified OCFL object.
-    /// A destination of `/` specifies the object's root.
-    pub fn copy_files_external(
+    /// Compares the files in versions of two different objects, such as an object and its
+    /// replica. The paths that only exist in one of the objects, and the paths whose content
+    /// differs, are returned sorted by logical path.
+    ///
+    /// Content is compared by digest. When the objects use different digest algorithms, the
+    /// digests of object B's files are calculated using object A's algorithm, which requires
+    /// reading every file that exists in both objects.
+    ///
+    /// If either object cannot be found, then a `RocflError::NotFound` error is returned.
+    pub fn compare_objects(
         &self,
-        object_id: &str,
-        src: &[impl AsRef<Path>],
-        dst: &str,
-        recursive: bool,
-    ) -> Result<()> {
+        object_id_a: &str,
+        version_a: VersionRef,
+        object_id_b: &str,
+        version_b: VersionRef,
+    ) -> Result<Vec<ObjectDiff>> {
         self.ensure_open()?;
 
-        self.operate_on_external_source(
-            object_id,
-            src,
-            dst,
-            recursive,
-            |file, logical_path, inventory| self.copy_file(file, logical_path, inventory),
-        )
+        let a = self.get_object(object_id_a, version_a)?;
+        let b = self.get_object(object_id_b, version_b)?;
+        let b_version = b.version_details.version_num;
+
+        let mut diffs = Vec::new();
+
+        for (path, a_details) in &a.state {
+            match b.state.get(path) {
+                Some(b_details) => {
+                    let matches = if a_details.digest_algorithm == b_details.digest_algorithm {
+                        a_details.digest == b_details.digest
+                    } else {
+                        let mut writer = a_details.digest_algorithm.writer(io::sink());
+                        self.get_object_file(&b.id, path, b_version.into(), &mut writer)?;
+                        writer.finalize_hex() == *a_details.digest
+                    };
+
+                    if !matches {
+                        diffs.push(ObjectDiff::ContentDiffers(path.clone()));
+                    }
+                }
+                None => diffs.push(ObjectDiff::OnlyInA(path.clone())),
+            }
+        }
+
+        for path in b.state.keys() {
+            if !a.state.contains_key(path) {
+                diffs.push(ObjectDiff::OnlyInB(path.clone()));
+            }
+        }
+
+        diffs.sort_unstable_by(|left, right| left.path().cmp(right.path()));
+
+        Ok(diffs)
     }
 
-    /// Copies files within an OCFL object. The source paths may be glob patterns.
-    pub fn copy_files_internal(
+    /// Searches the content of the files in an object version for lines that match the regular
+    /// expression `pattern`. The files that are searched may be restricted using a logical path
+    /// glob. Files are searched in logical path order as they are read, and each matching line is
+    /// passed to `on_match` as soon as it is found. If `on_match` returns false, then the rest of
+    /// the file is not searched. Files that are larger than `max_file_size` bytes are skipped, and
+    /// the search of a file stops if it appears to be binary.
+    ///
+    /// If the object or version of the object cannot be found, then a `RocflError::NotFound`
+    /// error is returned.
+    pub fn search_object_content(
         &self,
         object_id: &str,
         version_num: VersionRef,
-        src: &[impl AsRef<str>],
-        dst: &str,
-        recursive: bool,
+        pattern: &str,
+        path_glob: Option<&str>,
+        max_file_size: u64,
+        mut on_match: impl FnMut(ContentMatch) -> bool,
     ) -> Result<()> {
         self.ensure_open()?;
 
-        if src.is_empty() {
-            return Ok(());
-        }
-
-        let _lock = self.get_lock_manager()?.acquire(object_id)?;
+        let matcher = RegexMatcher::new(pattern).map_err(|e| {
+            RocflError::InvalidValue(format!("Invalid search pattern '{}': {}", pattern, e))
+        })?;
+
+        let glob_matcher = match path_glob {
+            Some(glob) => Some(
+                GlobBuilder::new(glob.trim_start_matches('/'))
+                    .backslash_escape(true)
+                    .build()?
+                    .compile_matcher(),
+            ),
+            None => None,
+        };
 
-        let mut inventory = self.get_or_created_staged_inventory(object_id)?;
-        let src_version_num = version_num.resolve(inventory.head);
-        let staging = self.get_staging()?;
+        let inventory = self.get_inventory(object_id)?;
+        let version_num = version_num.resolve(inventory.head);
+        let version = inventory.get_version(version_num)?;
+        let key = self.content_key(&inventory.id)?;
+        let marker = self.encryption_marker(&inventory.id)?;
+
+        let mut files: Vec<_> = version
+            .state_iter()
+            .filter(|(path, _)| match &glob_matcher {
+                Some(matcher) => matcher.is_match(path.as_str()),
+                None => true,
+            })
+            .collect();
+        files.sort_unstable_by(|a, b| a.0.cmp(b.0));
 
-        let (to_copy, mut errors) =
-            self.resolve_internal_moves(&inventory, src_version_num, src, dst, recursive)?;
+        let mut searcher = SearcherBuilder::new()
+            .line_number(true)
+            .binary_detection(BinaryDetection::quit(b'\x00'))
+            .build();
 
-        for (src_path, dst_path) in to_copy {
+        for (path, digest) in files {
             if self.is_closed() {
+                info!("Terminating search of object {}", object_id);
                 break;
             }
 
-            let attempt = || -> Result<()> {
+            let content_path =
+                inventory.content_path_for_digest(digest, version_num.into(), Some(path))?;
+
+            if self.content_file_size(&inventory, content_path, marker.as_ref())? > max_file_size {
                 info!(
-                    "Copying file {} from {} to {}",
-                    src_path, src_version_num, dst_path
+                    "Skipping {} because it is larger than {} bytes",
+                    path, max_file_size
                 );
+                continue;
+            }
 
-                let digest_and_path =
-                    lookup_staged_digest_and_content_path(&inventory, src_version_num, &src_path)?;
-
-                // Copies of files new in the staged version must be copied on disk as well
-                if let Some((digest, content_path)) = digest_and_path {
-                    // Validate before copy to decrease the chance of failure after copying on disk
-                    inventory
-                        .head_version()
-                        .validate_non_conflicting(&dst_path)?;
-                    staging.copy_staged_file(&inventory, &content_path, &dst_path)?;
-                    // Should be impossible to fail
-                    inventory.add_file_to_head(digest, dst_path)
-                } else {
-                    inventory.copy_file_to_head(src_version_num, &src_path, dst_path)
-                }
+            // The store writes the file's content, so it is searched on another thread that
+            // reads the content as it is written
+            let (chunks, chunk_receiver) = mpsc::sync_channel(SEARCH_CHUNKS_IN_FLIGHT);
+            let (lines, line_receiver) = mpsc::channel();
+            let sink = MatchSink {
+                path: path.to_string(),
+                lines,
             };
 
-            if let Err(e) = attempt() {
-                errors.push(format!("Failed to copy file {}: {}", src_path, e));
-            }
-        }
-
-        inventory.head_version_mut().created = Local::now();
-        staging.stage_inventory(&inventory, false, false)?;
+            thread::scope(|scope| {
+                let search = scope.spawn(|| {
+                    searcher.search_reader(&matcher, ChunkReader::new(chunk_receiver), sink)
+                });
+
+                let mut writer = SearchWriter::new(chunks, &line_receiver, |line_number, line| {
+                    on_match(ContentMatch {
+                        logical_path: path.clone(),
+                        line_number,
+                        line,
+                    })
+                });
+                let read = read_content(key, &mut writer, |writer| {
+                    self.store
+                        .get_content_file(&inventory, content_path, writer)
+                });
+                let stopped = writer.finish();
+
+                let searched = search
+                    .join()
+                    .unwrap_or_else(|e| std::panic::resume_unwind(e));
+
+                match read {
+                    Err(_) if stopped => (),
+                    Err(e) => return Err(e),
+                    Ok(_) => (),
+                }
 
-        if !errors.is_empty() {
-            return Err(RocflError::CopyMoveError(MultiError(errors)));
+                Ok(searched?)
+            })?;
         }
 
         Ok(())
     }
 
-    /// Moves files from outside the OCFL repository into the specified OCFL object.
-    /// A destination of `/` specifies the object's root.
-    pub fn move_files_external(
+    /// Exports a version of an object to the directory `dst` in a content-addressed layout. Each
+    /// content file is written to `content/ALGORITHM/XX/DIGEST`, where `XX` is the first two
+    /// characters of its digest, and a mapping manifest of the version's logical paths is written
+    /// to `objects/ENCODED_ID.json`. Many objects may be exported to the same directory, and
+    /// content that is shared between them is only written once.
+    ///
+    /// Exports are resumable. Content files that are already in the export are skipped, and the
+    /// manifest is written last, so an object whose manifest already describes the version is
+    /// skipped entirely. Content is verified against its digest as it is written.
+    ///
+    /// If the object or version of the object cannot be found, then a `RocflError::NotFound`
+    /// error is returned.
+    pub fn export_cas(
         &self,
         object_id: &str,
-        src: &[impl AsRef<Path>],
-        dst: &str,
-    ) -> Result<()> {
+        version_num: VersionRef,
+        dst: impl AsRef<Path>,
+    ) -> Result<CasExport> {
         self.ensure_open()?;
 
-        self.operate_on_external_source(
-            object_id,
-            src,
-            dst,
-            true,
-            |file, logical_path, inventory| self.move_file(file, logical_path, inventory),
-        )?;
+        let dst = dst.as_ref();
+        let inventory = self.get_inventory(object_id)?;
+        let version_num = version_num.resolve(inventory.head);
+        let version = inventory.get_version(version_num)?;
+        let algorithm = inventory.digest_algorithm;
+        let key = self.content_key(&inventory.id)?;
 
-        if self.is_open() {
-            for path in src {
-                let path = path.as_ref();
-                if path.exists() && path.is_dir() {
-                    util::clean_dirs_down(path)?;
-                }
+        let mut export = CasExport {
+            object_id: inventory.id.clone(),
+            version_num,
+            files_written: 0,
+            files_skipped: 0,
+            bytes_written: 0,
+        };
+
+        if let Some(existing) = cas::read_manifest(dst, &inventory.id)? {
+            if existing.version == version_num && existing.digest_algorithm == algorithm {
+                info!(
+                    "Skipping object {} because version {} was already exported",
+                    inventory.id, version_num
+                );
+                export.files_skipped = existing.files.values().collect::<HashSet<_>>().len();
+                return Ok(export);
             }
         }
 
-        Ok(())
-    }
-
-    /// Moves files within an OCFL object. The source paths may be glob patterns.
-    pub fn move_files_internal(
-        &self,
-        object_id: &str,
-        src: &[impl AsRef<str>],
-        dst: &str,
-    ) -> Result<()> {
-        self.ensure_open()?;
+        let mut files = BTreeMap::new();
+        let mut exported = HashSet::new();
 
-        if src.is_empty() {
-            return Ok(());
-        }
+        for (path, digest) in version.state_iter() {
+            files.insert(path.to_string(), digest.to_string());
 
-        let _lock = self.get_lock_manager()?.acquire(object_id)?;
+            if !exported.insert(digest.clone()) {
+                continue;
+            }
 
-        let mut inventory = self.get_or_created_staged_inventory(object_id)?;
-        let staging = self.get_staging()?;
+            self.ensure_open()?;
 
-        let (to_move, mut errors) =
-            self.resolve_internal_moves(&inventory, inventory.head, src, dst, true)?;
+            let dst_path = cas::content_path(dst, algorithm, digest);
 
-        for (src_path, dst_path) in to_move {
-            if self.is_closed() {
-                break;
+            if dst_path.exists() {
+                export.files_skipped += 1;
+                continue;
             }
 
-            info!("Moving {} to {}", src_path, dst_path);
+            let content_path =
+                inventory.content_path_for_digest(digest, version_num.into(), Some(path))?;
+
+            export.bytes_written += cas::write_atomic(&dst_path, |writer| {
+                verify_read(&inventory, path, digest, writer, |writer| {
+                    read_content(key, writer, |writer| {
+                        self.store
+                            .get_content_file(&inventory, content_path, writer)
+                    })
+                })
+            })?;
+            export.files_written += 1;
+        }
 
-            let attempt = || -> Result<()> {
-                let digest_and_path =
-                    lookup_staged_digest_and_content_path(&inventory, inventory.head, &src_path)?;
+        cas::write_manifest(
+            dst,
+            &CasManifest {
+                id: inventory.id.clone(),
+                version: version_num,
+                digest_algorithm: algorithm,
+                files,
+            },
+        )?;
 
-                // Moves of files new in the staged version must be moved on disk as well
-                if let Some((digest, content_path)) = digest_and_path {
-                    // Validate before move to decrease the chance of failure after moving on disk
-                    inventory
-                        .head_version()
-                        .validate_non_conflicting(&dst_path)?;
-                    staging.move_staged_file(&inventory, &content_path, &dst_path)?;
-                    // Should be impossible to fail
-                    inventory.move_new_in_head_file(digest, &src_path, dst_path)
-                } else {
-                    inventory.move_file_in_head(&src_path, dst_path)
-                }
-            };
+        Ok(export)
+    }
 
-            if let Err(e) = attempt() {
-                errors.push(format!("Failed to move file {}: {}", src_path, e));
-            }
+    /// Exports an object, including all of its versions, inventories, sidecars, logs, and
+    /// extensions, to a zip64 archive that is written to `dst`. Every file is written at its path
+    /// relative the object root. The object is validated, including the fixity of all of its
+    /// content, before it is exported, and a `RocflError::InvalidObject` error is returned if it
+    /// is not valid.
+    ///
+    /// If the object cannot be found, then a `RocflError::NotFound` error is returned.
+    pub fn export_object_zip(&self, object_id: &str, dst: impl Write + Seek) -> Result<ZipExport> {
+        self.ensure_open()?;
+
+        let inventory = self.get_inventory(object_id)?;
+
+        let result = self.validate_object(&inventory.id, FixityCheck::All)?;
+        if result.has_errors() {
+            return Err(RocflError::InvalidObject {
+                object_id: inventory.id.clone(),
+                errors: result.errors().to_vec(),
+            });
         }
 
-        inventory.head_version_mut().created = Local::now();
-        staging.stage_inventory(&inventory, false, false)?;
+        let prefix = format!("{}/", inventory.object_root);
+        let mut writer = ZipObjectWriter::new(dst);
 
-        if !errors.is_empty() {
-            return Err(RocflError::CopyMoveError(MultiError(errors)));
+        for path in self.store.list_object_files(&inventory.id)? {
+            self.ensure_open()?;
+
+            let path = path.strip_prefix(&prefix).unwrap_or(&path);
+            info!("Exporting object {} file {}", inventory.id, path);
+
+            writer.add_file(path, |sink| {
+                self.store.get_object_root_file(&inventory.id, path, sink)
+            })?;
         }
 
-        Ok(())
+        Ok(ZipExport {
+            object_id: inventory.id.clone(),
+            head: inventory.head,
+            files: writer.finish()?,
+        })
     }
 
-    /// Removes the specified files from the staged version of the object. The files still
-    /// exist in prior versions.
-    pub fn remove_files<P: AsRef<str>>(
-        &self,
-        object_id: &str,
-        paths: &[P],
-        recursive: bool,
-    ) -> Result<()> {
-        self.ensure_open()?;
+    /// Imports an object from a zip archive that was created by `export_object_zip()`. The
+    /// archive is extracted into staging, and the extracted object is validated, including the
+    /// fixity of all of its content, before it is moved into the repository. A
+    /// `RocflError::InvalidObject` error is returned if it is not valid.
+    ///
+    /// The object must not already exist in the repository, and its OCFL version must not be
+    /// newer than the repository's.
+    pub fn import_object_zip(&self, archive: impl Read + Seek) -> Result<ZipImport> {
+        self.ensure_writable("import an object")?;
 
-        if paths.is_empty() {
-            return Ok(());
-        }
+        let mut archive = archive::open(archive)?;
+        let mut inventory = archive::read_inventory(&mut archive)?;
 
-        let _lock = self.get_lock_manager()?.acquire(object_id)?;
+        let mut audit = Audit::start("import").object_id(&inventory.id);
 
-        let mut inventory = self.get_or_created_staged_inventory(object_id)?;
-        let version = inventory.head_version();
+        let object_version = SpecVersion::try_from_inventory_type(&inventory.type_declaration)?;
+        if let Some(Known(repo_version)) = self.spec_version.read().unwrap().as_ref() {
+            validate::validate_spec_version(object_version, *repo_version)?;
+        }
+        validate::validate_object_id(&inventory.id)?;
 
-        let mut paths_to_remove = HashSet::new();
+        let _lock = self.get_lock_manager()?.acquire(&inventory.id)?;
 
-        for path in paths {
-            paths_to_remove.extend(version.resolve_glob(path.as_ref(), recursive)?);
+        if self.store.object_exists(&inventory.id)? {
+            return Err(RocflError::IllegalState(format!(
+                "Cannot import object {} because it already exists",
+                inventory.id
+            )));
         }
 
         let staging = self.get_staging()?;
-
-        for path in paths_to_remove {
-            if self.is_closed() {
-                break;
+        staging.stage_object(&mut inventory)?;
+        let staged_root = PathBuf::from(&inventory.storage_path);
+
+        let extracted = || -> Result<usize> {
+            // Only the archive's files should be validated, not those written when staging
+            fs::remove_dir_all(&staged_root)?;
+            fs::create_dir_all(&staged_root)?;
+
+            let files = archive::extract(&mut archive, &staged_root)?;
+
+            let result = staging.validate_object_at(&inventory.object_root, FixityCheck::All)?;
+            if result.has_errors() {
+                return Err(RocflError::InvalidObject {
+                    object_id: inventory.id.clone(),
+                    errors: result.errors().to_vec(),
+                });
             }
 
-            info!("Removing path from staged version: {}", path);
-            if let Some(content_path) = inventory.remove_logical_path_from_head(&path) {
-                staging.rm_staged_files(&inventory, &[&content_path])?;
+            Ok(files)
+        }();
+
+        let files = match extracted {
+            Ok(files) => files,
+            Err(e) => {
+                staging.purge_object(&inventory.id)?;
+                return Err(e);
             }
+        };
+
+        // Last chance for the user to have ctrl-c'd the operation
+        if !self.is_open() {
+            staging.purge_object(&inventory.id)?;
+            return Err(RocflError::Closed);
         }
 
-        staging.stage_inventory(&inventory, false, false)?;
+        self.store
+            .write_new_object(&mut inventory, &staged_root, None)?;
+        self.purge_staged_object(staging, &inventory.id)?;
+
+        audit.set_version(inventory.head);
+        audit.spec_version(object_version).succeeded();
 
-        Ok(())
+        Ok(ZipImport {
+            object_id: inventory.id,
+            head: inventory.head,
+            files,
+        })
     }
 
-    /// Reset all staged changes for an object by dropping the object's staged version completely.
-    pub fn reset_all(&self, object_id: &str) -> Result<()> {
+    /// Returns an iterator that scans the inventories of all of the objects in an OCFL repository
+    /// for logical paths that match `path_glob`. Each item contains the matches within a single
+    /// object, sorted by version and then logical path, and objects without any matches are
+    /// skipped. Every version of each object is searched unless `head_only` is set. An optional
+    /// glob pattern may be provided to filter the objects that are searched.
+    ///
+    /// The iterator returns an `ObjectError` if it encounters a problem accessing an object. The
+    /// error identifies the object, by ID and/or path, that could not be read. This does not
+    /// terminate the iterator; there are still more objects until it returns `None`.
+    pub fn find_paths<'a>(
+        &'a self,
+        path_glob: &str,
+        object_glob: Option<&str>,
+        head_only: bool,
+    ) -> Result<Box<dyn Iterator<Item = Result<Vec<PathMatch>, ObjectError>> + 'a>> {
         self.ensure_open()?;
 
-        if self.staging_root.exists() {
-            self.get_staging()?.purge_object(object_id)
-        } else {
-            Ok(())
-        }
-    }
+        let matcher = GlobBuilder::new(path_glob.trim_start_matches('/'))
+            .backslash_escape(true)
+            .build()?
+            .compile_matcher();
 
-    /// Resets to specified staged changes to an object. Paths may be a glob and is resolved
-    /// against both the staged version and the previous version. Matches in the staged version
-    /// are treated as add/update resets and matches in the previous version are treated as
-    /// remove resets.
-    pub fn reset<P: AsRef<str>>(
-        &self,
-        object_id: &str,
-        paths: &[P],
-        recursive: bool,
-    ) -> Result<()> {
-        self.ensure_open()?;
+        let iter = InventoryAdapterIter::new(
+            self.store.iter_inventories(object_glob)?,
+            move |inventory| {
+                let mut matches = Vec::new();
 
-        if paths.is_empty() {
-            return Ok(());
-        }
+                for (version_num, version) in &inventory.versions {
+                    if head_only && *version_num != inventory.head {
+                        continue;
+                    }
 
-        let staging = self.get_staging()?;
+                    let mut paths: Vec<_> = version
+                        .state_iter()
+                        .filter(|(path, _)| matcher.is_match(path.as_str()))
+                        .collect();
+                    paths.sort_unstable_by(|a, b| a.0.cmp(b.0));
+
+                    matches.extend(paths.into_iter().map(|(path, digest)| PathMatch {
+                        object_id: inventory.id.clone(),
+                        version_num: *version_num,
+                        logical_path: path.clone(),
+                        digest: digest.clone(),
+                    }));
+                }
 
-        let _lock = self.get_lock_manager()?.acquire(object_id)?;
+                Ok(matches)
+            },
+        )
+        .filter(|matches| !matches.as_ref().is_ok_and(|matches| matches.is_empty()));
 
-        let mut inventory = match staging.get_inventory(object_id) {
-            Ok(inventory) => inventory,
-            Err(RocflError::NotFound(_)) => return Ok(()),
-            Err(e) => return Err(e),
-        };
+        Ok(Box::new(iter))
+    }
 
-        let head = inventory.head_version();
-        let (previous, previous_num) = if inventory.is_new() {
-            (None, None)
-        } else {
-            let previous_num = inventory.head.previous()?;
-            (
-                Some(inventory.get_version(previous_num)?),
-                Some(previous_num),
-            )
-        };
+    /// Returns all of the staged changes to the specified object, if there are any.
+    pub fn diff_staged(&self, object_id: &str) -> Result<Vec<Diff>> {
+        self.ensure_open()?;
 
-        let mut head_paths = HashSet::new();
-        let mut previous_paths = HashSet::new();
+        if !self.staging_root.exists() {
+            return Ok(Vec::new());
+        }
 
-        for path in paths {
-            head_paths.extend(head.resolve_glob(path.as_ref(), recursive)?);
-            if let Some(previous) = previous {
-                previous_paths.extend(previous.resolve_glob(path.as_ref(), recursive)?);
-            }
+        match self.get_staging()?.get_inventory(object_id) {
+            Err(RocflError::NotFound(_)) => Ok(Vec::new()),
+            Err(e) => Err(e),
+            Ok(inventory) => inventory.diff_versions(None, inventory.head),
         }
+    }
 
-        let reset_adds = head_paths
-            .into_iter()
-            .filter(|path| !previous_paths.contains(path))
-            .collect::<HashSet<Rc<LogicalPath>>>();
-
-        // Need to apply add resets first to attempt to avoid path conflicts
-        for path in reset_adds {
-            if self.is_closed() {
-                break;
+    /// Completely removes the specified object from the repository. If the object doest not exist,
+    /// nothing happens.
+    pub fn purge_object(&self, object_id: &str) -> Result<()> {
+        self.ensure_plannable("purge an object")?;
+
+        self.ensure_not_frozen("purge an object", object_id)?;
+
+        let audit = Audit::start("purge")
+            .dry_run(self.is_dry_run())
+            .object_id(object_id);
+
+        if self.is_dry_run() {
+            let mut plan = ChangePlan::new("purge", Some(object_id));
+            match self.get_staged_inventory(object_id) {
+                Ok(inventory) => plan
+                    .changes
+                    .push(PlannedChange::DiscardStagedVersion(inventory.head)),
+                Err(RocflError::NotFound(_)) => (),
+                Err(e) => return Err(e),
             }
-
-            if let Some(content_path) = inventory.remove_logical_path_from_head(&path) {
-                staging.rm_staged_files(&inventory, &[&content_path])?;
+            match self.store.list_object_files(object_id) {
+                Ok(files) => plan
+                    .changes
+                    .extend(files.into_iter().map(PlannedChange::DeleteFile)),
+                Err(RocflError::NotFound(_)) => (),
+                Err(e) => return Err(e),
             }
+            self.record_plan(plan);
+            return Ok(());
         }
 
-        // Resetting deleted or modified files is the same
-        for path in previous_paths {
-            if self.is_closed() {
-                break;
-            }
+        if self.staging_root.exists() {
+            self.purge_staged_object(self.get_staging()?, object_id)?;
+        }
 
-            if let Some(previous_num) = previous_num {
-                // In the case of a modification we need to delete the new file
-                if let Some(content_path) = inventory.remove_logical_path_from_head(&path) {
-                    staging.rm_staged_files(&inventory, &[&content_path])?;
-                }
-                inventory.copy_file_to_head(previous_num, &path, path.as_ref().clone())?;
-            }
+        // Last chance for the user to have ctrl-c'd the operation
+        if self.is_open() {
+            self.store.purge_object(object_id)?;
+            audit.succeeded();
         }
 
-        inventory.head_version_mut().created = Local::now();
-        staging.stage_inventory(&inventory, false, false)
+        Ok(())
     }
 
-    /// Commits all of an object's staged changes. If `user_address` is provided, then `user_name`
-    /// must also be. If `created` is not provided, then it defaults to the current time.
+    /// Rewrites an object so that its versions up to and including `through` are collapsed into a
+    /// single baseline version, `v1`, that has the state of `through`. The later versions are
+    /// renumbered to follow it, and keep their state and metadata. Content that is only
+    /// referenced by the versions that were collapsed is permanently deleted. The object's
+    /// extensions, logs, and aliases are preserved.
     ///
-    /// `object_root` may be specified to define the storage root relative path to the object's
-    /// root. This value is only respected if the object does not already exist, and the
-    /// repo does not have defined storage layout.
-    pub fn commit(
-        &self,
-        object_id: &str,
-        meta: CommitMeta,
-        object_root: Option<&str>,
-        pretty_print: bool,
-    ) -> Result<()> {
-        self.ensure_open()?;
-
-        let staging = self.get_staging()?;
-        let _lock = self.get_lock_manager()?.acquire(object_id)?;
-
-        self.commit_inner(object_id, meta, object_root, pretty_print, staging)
-    }
-
-    /// Upgrades an existing object to the specified OCFL spec version. This requires creating
-    /// a new object version. If the object currently has staged changes, then the changes are
-    /// committed as part of the upgrade.
-    pub fn upgrade_object(
+    /// This is destructive, and cannot be undone. The squashed object is built in staging, and
+    /// then replaces the original object. The object must not have staged changes. A report that
+    /// maps the original versions to their new numbers is returned. In dry-run mode, the report
+    /// is returned without changing the object.
+    pub fn squash_object(
         &self,
         object_id: &str,
-        version: SpecVersion,
-        meta: CommitMeta,
+        through: VersionNum,
         pretty_print: bool,
-    ) -> Result<()> {
-        self.ensure_open()?;
+    ) -> Result<SquashReport> {
+        self.ensure_plannable("squash an object")?;
+        self.ensure_not_frozen("squash an object", object_id)?;
 
-        let staging = self.get_staging()?;
-        let _lock = self.get_lock_manager()?.acquire(object_id)?;
+        let mut audit = Audit::start("squash")
+            .dry_run(self.is_dry_run())
+            .object_id(object_id);
 
-        let mut inventory = self.get_or_created_staged_inventory(object_id)?;
+        let _lock = self.lock_staged_object(object_id)?;
 
-        if let Some(current_version) = inventory.spec_version() {
-            if version <= current_version {
-                return Err(RocflError::IllegalOperation(
-                    format!("Cannot upgrade object to {} because the current version, {}, is greater than or equal to the new version.",
-                            version.version(), current_version.version())));
+        match self.get_staged_inventory(object_id) {
+            Ok(_) => {
+                return Err(RocflError::IllegalState(format!(
+                    "Cannot squash object {} because it has staged changes. Commit or reset them first.",
+                    object_id
+                )));
             }
+            Err(RocflError::NotFound(_)) => (),
+            Err(e) => return Err(e),
+        }
 
-            let repo_version = self.spec_version.read().unwrap().clone();
+        let inventory = self.get_inventory(object_id)?;
+        let mut squashed = inventory.squash_through(through)?;
+        let report = squash_report(&inventory, &squashed, through);
 
-            if let Some(Known(repo_version)) = repo_version {
-                if repo_version < version {
-                    return Err(RocflError::IllegalOperation(
-                        format!("Cannot upgrade object to {} because it is less than the repository version, {}.",
-                                version.version(), repo_version.version())));
-                }
-            } else if let Some(Unknown(repo_version)) = repo_version {
-                return Err(RocflError::IllegalOperation(format!(
-                    "Cannot upgrade object to {} because the repository version, {}, is unrecognized.",
-                    version.version(), repo_version
-                )));
-            }
-        } else {
-            return Err(RocflError::IllegalOperation(format!(
-                "Cannot upgrade object to {} because the current version is unknown",
-                version.version()
-            )));
+        if self.is_dry_run() {
+            let mut plan = ChangePlan::new("squash", Some(&inventory.id));
+            plan.changes.extend(
+                report.versions.iter().map(|(original, squashed)| {
+                    PlannedChange::RewriteVersion(*original, *squashed)
+                }),
+            );
+            plan.changes.extend(report.removed_files.iter().map(|path| {
+                PlannedChange::DeleteFile(format!("{}/{}", inventory.object_root, path))
+            }));
+            self.record_plan(plan);
+            return Ok(report);
         }
 
-        inventory.type_declaration = version.inventory_type().to_string();
-        staging.stage_inventory(&inventory, false, false)?;
+        let staging = self.get_staging()?;
+        let object_root = inventory.object_root.clone();
 
-        self.commit_inner(object_id, meta, None, pretty_print, staging)
-    }
+        staging.stage_object(&mut squashed)?;
+        let staged_root = PathBuf::from(&squashed.storage_path);
 
-    /// Upgrades the repository to the specified version
-    pub fn upgrade_repo(&self, version: SpecVersion) -> Result<()> {
-        self.ensure_open()?;
+        if let Err(e) = self.write_squashed_object(&inventory, &squashed, staging, pretty_print) {
+            staging.purge_object(&inventory.id)?;
+            return Err(e);
+        }
 
-        let current_version = self.spec_version.read().unwrap().clone();
+        // Last chance for the user to have ctrl-c'd the operation
+        if !self.is_open() {
+            staging.purge_object(&inventory.id)?;
+            return Err(RocflError::Closed);
+        }
 
-        if let Some(Known(current)) = current_version {
-            if current >= version {
-                return Err(RocflError::IllegalOperation(
-                    format!("Cannot upgrade repository to {} because the current version, {}, is greater than or equal to the new version.",
-                            version.version(), current.version())));
-            }
-        } else if let Some(Unknown(current)) = current_version {
-            return Err(RocflError::IllegalOperation(format!(
-                "Cannot upgrade repository to {} because the current version, {}, is unrecognized.",
-                version.version(),
-                current
-            )));
+        let aliases: Vec<ObjectAlias> = self
+            .store
+            .list_aliases()?
+            .into_iter()
+            .filter(|alias| alias.object_id == inventory.id)
+            .collect();
+
+        self.store.purge_object(&inventory.id)?;
+
+        squashed.object_root = object_root.clone();
+        if let Err(e) = self
+            .store
+            .write_new_object(&mut squashed, &staged_root, Some(&object_root))
+        {
+            return Err(RocflError::CorruptObject {
+                object_id: inventory.id.clone(),
+                message: format!(
+                    "Failed to install the squashed object at {}. The squashed object is in staging at {}, and must be moved into place manually. Error: {}",
+                    object_root,
+                    staged_root.to_string_lossy(),
+                    e
+                ),
+            });
         }
 
-        self.store.upgrade_repo(version)?;
-        let mut repo_version = self.spec_version.write().unwrap();
-        *repo_version = Some(Known(version));
+        for alias in aliases {
+            self.store.add_alias(&alias.object_id, &alias.alias)?;
+        }
 
-        Ok(())
+        self.purge_staged_object(staging, &inventory.id)?;
+
+        audit.set_version(squashed.head);
+        audit.succeeded();
+
+        Ok(report)
     }
 
-    fn commit_inner(
+    /// Changes the ID of an object. Every inventory in the object, including those in its version
+    /// directories, is rewritten with the new ID, and its sidecar is recomputed. The object is
+    /// moved to the location that the storage layout dictates for the new ID, and any aliases of
+    /// the object are updated to refer to the new ID. The object must not have staged changes.
+    ///
+    /// The renamed object is built in staging and validated, including the fixity of all of its
+    /// content, before it is written to the repository, and the original object is only purged
+    /// after the renamed object is in place. Inventory signatures no longer match the rewritten
+    /// inventories, and must be recreated if they are needed.
+    ///
+    /// `object_root` may be specified to define the storage root relative path to the renamed
+    /// object's root. It is required if the repository does not have a defined storage layout.
+    pub fn rename_object(
         &self,
         object_id: &str,
-        meta: CommitMeta,
+        new_object_id: &str,
         object_root: Option<&str>,
         pretty_print: bool,
-        staging: &FsOcflStore,
     ) -> Result<()> {
-        let mut inventory = match staging.get_inventory(object_id) {
-            Ok(inventory) => inventory,
-            Err(RocflError::NotFound(_)) => {
-                return Err(RocflError::General(format!(
-                    "No staged changes found for object {}",
-                    object_id
-                )));
-            }
-            Err(e) => return Err(e),
-        };
-
-        let duplicates = inventory.dedup_head();
+        self.ensure_writable("rename an object")?;
+        self.ensure_not_frozen("rename an object", object_id)?;
 
-        // TODO validate staged version before committing
+        let new_object_id = new_object_id.trim();
+        validate::validate_object_id(new_object_id)?;
 
-        inventory.head_version_mut().update_meta(meta);
+        let mut audit = Audit::start("rename")
+            .object_id(object_id)
+            .destination(new_object_id);
 
-        staging.stage_inventory(&inventory, true, pretty_print)?;
-        staging.rm_staged_files(
-            &inventory,
-            &duplicates
-                .iter()
-                .map(|p| p.as_ref())
-                .collect::<Vec<&ContentPath>>(),
-        )?;
-        staging.rm_orphaned_files(&inventory)?;
+        let _lock = self.lock_staged_object(object_id)?;
+        let _new_lock = self.get_lock_manager()?.acquire(new_object_id)?;
 
-        // Last chance to ctrl-c before committing
-        if self.is_open() {
-            if inventory.is_new() {
-                let src_object_root = PathBuf::from(&inventory.storage_path);
-                self.store
-                    .write_new_object(&mut inventory, &src_object_root, object_root)?;
-            } else {
-                let version_root = paths::version_path(&inventory.storage_path, inventory.head);
-                self.store
-                    .write_new_version(&mut inventory, &version_root)?;
-            }
+        let inventory = self.get_inventory(object_id)?;
 
-            staging.purge_object(object_id)?;
+        if inventory.id == new_object_id {
+            return Err(RocflError::InvalidValue(format!(
+                "Object {} already has the ID {}",
+                inventory.id, new_object_id
+            )));
         }
 
-        Ok(())
-    }
+        if self.store.object_exists(new_object_id)? {
+            return Err(RocflError::IllegalState(format!(
+                "Cannot rename object {} to {} because an object with that ID already exists",
+                inventory.id, new_object_id
+            )));
+        }
 
-    /// Attempts to get the inventory from staging. If it is not found, it is loaded from the
-    /// main repo, and moved into staging. If it is not found in the main repo, then an error is
-    /// returned.
-    fn get_or_created_staged_inventory(&self, object_id: &str) -> Result<Inventory> {
-        let staging = self.get_staging()?;
+        if let Some(aliased) = self.store.resolve_alias(new_object_id)? {
+            return Err(RocflError::IllegalState(format!(
+                "Cannot rename object {} to {} because it is an alias of object {}",
+                inventory.id, new_object_id, aliased
+            )));
+        }
 
-        match staging.get_inventory(object_id) {
-            Ok(inventory) => Ok(inventory),
-            Err(RocflError::NotFound(_)) => {
-                let mut inventory = self.store.get_inventory(object_id)?;
+        match self.get_staged_inventory(&inventory.id) {
+            Ok(_) => {
+                return Err(RocflError::IllegalState(format!(
+                    "Cannot rename object {} because it has staged changes. Commit or reset them first.",
+                    inventory.id
+                )));
+            }
+            Err(RocflError::NotFound(_)) => (),
+            Err(e) => return Err(e),
+        }
 
-                if inventory.spec_version().is_none() {
-                    return Err(RocflError::IllegalOperation(
-                        "Cannot update object because it conforms to an unknown/unsupported spec version".to_string(),
-                    ));
-                }
+        let staging = self.get_staging()?;
 
-                if inventory.mutable_head {
-                    return Err(RocflError::IllegalState(
-                        "Cannot stage changes for object because it has an active mutable HEAD."
-                            .to_string(),
-                    ));
+        let mut renamed = self.get_inventory(&inventory.id)?;
+        renamed.id = new_object_id.to_string();
+        staging.stage_object(&mut renamed)?;
+        let staged_root = PathBuf::from(&renamed.storage_path);
+
+        let staged = self
+            .write_renamed_object(&inventory, &renamed, staging, pretty_print)
+            .and_then(|_| {
+                let result = staging.validate_object_at(&renamed.object_root, FixityCheck::All)?;
+                if result.has_errors() {
+                    return Err(RocflError::InvalidObject {
+                        object_id: renamed.id.clone(),
+                        errors: result.errors().to_vec(),
+                    });
                 }
+                Ok(())
+            });
 
-                for extension in self.store.list_object_extensions(object_id)? {
-                    if !SUPPORTED_EXTENSIONS.contains(&extension.as_ref()) {
-                        warn!("Object {} uses unsupported extension {}. Modifying this object may have unintended consequences.",
-                              object_id, extension);
-                    }
-                }
+        if let Err(e) = staged {
+            staging.purge_object(&renamed.id)?;
+            return Err(e);
+        }
 
-                inventory.create_staging_head()?;
-                staging.stage_object(&mut inventory)?;
-                Ok(inventory)
-            }
-            Err(e) => Err(e),
+        // Last chance for the user to have ctrl-c'd the operation
+        if !self.is_open() {
+            staging.purge_object(&renamed.id)?;
+            return Err(RocflError::Closed);
         }
-    }
 
-    /// Attempts to load the object's inventory from staging. If it does not exist,
-    /// then `RocflError::NotFound` is returned.
-    fn get_staged_inventory(&self, object_id: &str) -> Result<Inventory> {
-        if !self.staging_root.exists() {
-            return Err(RocflError::NotFound(format!(
-                "{} does not have a staged version.",
-                object_id
-            )));
+        if let Err(e) = self
+            .store
+            .write_new_object(&mut renamed, &staged_root, object_root)
+        {
+            staging.purge_object(&renamed.id)?;
+            return Err(e);
         }
 
-        match self.get_staging()?.get_inventory(object_id) {
-            Ok(inventory) => Ok(inventory),
-            Err(RocflError::NotFound(_)) => Err(RocflError::NotFound(format!(
-                "{} does not have a staged version.",
-                object_id
-            ))),
-            Err(e) => Err(e),
+        let aliases: Vec<ObjectAlias> = self
+            .store
+            .list_aliases()?
+            .into_iter()
+            .filter(|alias| alias.object_id == inventory.id)
+            .collect();
+
+        if let Err(e) = self.store.purge_object(&inventory.id) {
+            return Err(RocflError::CorruptObject {
+                object_id: inventory.id.clone(),
+                message: format!(
+                    "The object was renamed to {}, but the original object could not be purged, and must be purged manually. Error: {}",
+                    renamed.id, e
+                ),
+            });
         }
-    }
 
-    /// Iterates over every file in an input source and applies the operator to the file. This
-    /// is intended to be used to copy/move files from the filesystem into staging.
-    fn operate_on_external_source(
-        &self,
-        object_id: &str,
-        src: &[impl AsRef<Path>],
-        dst: &str,
-        recursive: bool,
-        operator: impl Fn(&Path, LogicalPath, &mut Inventory) -> Result<()>,
-    ) -> Result<()> {
-        if src.is_empty() {
-            return Ok(());
+        for alias in aliases {
+            self.store.add_alias(&renamed.id, &alias.alias)?;
         }
 
-        let _lock = self.get_lock_manager()?.acquire(object_id)?;
+        self.purge_staged_object(staging, &renamed.id)?;
 
-        let mut inventory = self.get_or_created_staged_inventory(object_id)?;
+        audit.set_version(renamed.head);
+        audit.succeeded();
 
-        let dst_path = dst.try_into()?;
+        Ok(())
+    }
 
-        let dst_dir_exists = inventory.head_version().is_dir(&dst_path);
-        let src_is_many = src.len() > 1;
-        let dst_has_slash = dst.ends_with('/');
+    /// Finishes purging the object at the storage root relative path. This is used when a purge
+    /// was interrupted, leaving behind an object that can no longer be found by its ID. The path
+    /// must contain either the object's namaste file or its inventory, but not both. An intact
+    /// object must be purged by ID instead. The paths, relative the object root, of the files
+    /// that were deleted are returned.
+    pub fn purge_object_root(&self, object_root: &str) -> Result<Vec<String>> {
+        self.ensure_writable("purge an object")?;
 
-        let mut errors = Vec::new();
+        let audit = Audit::start("purge").paths([object_root]);
 
-        for path in src.iter() {
-            if self.is_closed() {
-                break;
-            }
+        let deleted = self.store.purge_object_root(object_root)?;
 
-            let path = path.as_ref();
+        audit.succeeded();
+        Ok(deleted)
+    }
 
-            if !path.exists() {
-                errors.push(format!(
-                    "Failed to copy/move {}: Does not exist",
-                    path.to_string_lossy()
-                ));
-                continue;
-            }
+    /// Returns the paths, relative the object root, of every file within the object's version
+    /// content directories that is not referenced in the object's manifest. These are typically
+    /// left behind by interrupted commits.
+    pub fn list_orphaned_content(&self, object_id: &str) -> Result<Vec<String>> {
+        self.ensure_open()?;
+        self.store.list_orphaned_content(object_id)
+    }
 
-            let mut attempt = || -> Result<()> {
-                if path.is_file() {
-                    let parent = path.parent().unwrap();
+    /// Permanently deletes every file within the object's version content directories that is not
+    /// referenced in the object's manifest, along with any directories that are empty as a result.
+    /// The paths, relative the object root, of the deleted files are returned.
+    pub fn rm_orphaned_content(&self, object_id: &str) -> Result<Vec<String>> {
+        self.ensure_writable("remove orphaned content")?;
 
-                    let logical_path = if dst_dir_exists || src_is_many || dst_has_slash {
-                        logical_path_in_dst_dir(path, parent, dst)?
-                    } else {
-                        dst_path.clone()
-                    };
+        // Prevents the content of an in-progress commit from being mistaken for orphans
+        let _lock = self.get_lock_manager()?.acquire(object_id)?;
 
-                    inventory
-                        .head_version()
-                        .validate_non_conflicting(&logical_path)?;
-                    operator(path, logical_path, &mut inventory)?;
-                } else if recursive {
-                    for file in WalkDir::new(path) {
-                        if self.is_closed() {
-                            break;
+        let audit = Audit::start("gc").object_id(object_id);
+        let orphans = self.store.rm_orphaned_content(object_id)?;
+        audit.paths(&orphans).succeeded();
+
+        Ok(orphans)
+    }
+
+    /// Returns the objects that have content staged in the repository's bucket, but that no longer
+    /// have a staged version in local staging, and whose most recently staged file is older than
+    /// `older_than`. This content is left behind when staging is abandoned, such as when the
+    /// local staging directory is deleted. Nothing is returned if content is not staged in the
+    /// bucket.
+    ///
+    /// Only content that was staged by this staging root, on this host, is considered. Content
+    /// that other staging roots staged in the same bucket is never returned.
+    pub fn list_abandoned_staging(&self, older_than: Duration) -> Result<Vec<BucketStagedObject>> {
+        self.ensure_open()?;
+
+        let bucket = match self.store.bucket_staging() {
+            Some(bucket) => bucket,
+            None => return Ok(Vec::new()),
+        };
+
+        let mut staged_roots = HashSet::new();
+
+        if self.staging_root.exists() {
+            for inventory in self.get_staging()?.iter_inventories(None)? {
+                match inventory {
+                    Ok(inventory) => {
+                        staged_roots.insert(inventory.object_root);
+                    }
+                    Err(e) => return Err(e.cause),
+                }
+            }
+        }
+
+        let cutoff = Local::now()
+            - chrono::Duration::from_std(older_than)
+                .map_err(|e| RocflError::InvalidValue(e.to_string()))?;
+
+        Ok(bucket
+            .list_staged_objects()?
+            .into_iter()
+            .filter(|object| !staged_roots.contains(&object.object_root))
+            .filter(|object| {
+                object
+                    .last_modified
+                    .map(|modified| modified < cutoff)
+                    .unwrap_or(true)
+            })
+            .collect())
+    }
+
+    /// Deletes the content of all of the objects that `list_abandoned_staging()` returns, and
+    /// returns the objects that were deleted.
+    pub fn rm_abandoned_staging(&self, older_than: Duration) -> Result<Vec<BucketStagedObject>> {
+        self.ensure_writable("remove abandoned staging")?;
+
+        let abandoned = self.list_abandoned_staging(older_than)?;
+
+        if let Some(bucket) = self.store.bucket_staging() {
+            for object in &abandoned {
+                if self.is_closed() {
+                    break;
+                }
+                info!(
+                    "Deleting abandoned staged content at {}",
+                    object.object_root
+                );
+                bucket.purge_staged_object(&object.object_root)?;
+            }
+        }
+
+        Ok(abandoned)
+    }
+
+    /// Adopts the existing OCFL object rooted at `object_root`, relative the storage root, into
+    /// the repository's object index. Once adopted, the object may be operated on by ID, even if
+    /// it does not reside where the storage layout expects it, or the repository does not have a
+    /// storage layout. The ID of the adopted object is returned.
+    ///
+    /// If there is no object at the specified location, then a `RocflError::NotFound` error is
+    /// returned.
+    pub fn adopt_object(&self, object_root: &str) -> Result<String> {
+        self.ensure_writable("adopt an object")?;
+
+        let mut audit = Audit::start("adopt").paths([object_root]);
+        let inventory = self.store.adopt_object(object_root)?;
+        audit.set_object_id(&inventory.id);
+        audit.set_version(inventory.head);
+        audit.succeeded();
+
+        Ok(inventory.id)
+    }
+
+    /// Returns `true` if the repository's object index is a registry that records the location of
+    /// every object, so that objects are located and listed without scanning the storage root.
+    pub fn is_object_registry(&self) -> bool {
+        self.store.is_object_registry()
+    }
+
+    /// Enables or disables the repository's object registry. While it is enabled, the location of
+    /// every object is recorded in the object index when it is created or purged, and objects are
+    /// located and listed using the index rather than by scanning the storage root. This is useful
+    /// for repositories that do not have a storage layout. Enabling the registry scans the
+    /// repository once to index its existing objects.
+    ///
+    /// The number of objects in the object index is returned.
+    pub fn set_object_registry(&self, enabled: bool) -> Result<usize> {
+        self.ensure_writable("change the object registry")?;
+
+        let audit = Audit::start(if enabled {
+            "enable_registry"
+        } else {
+            "disable_registry"
+        });
+        let count = self.store.set_object_registry(enabled)?;
+        audit.succeeded();
+
+        Ok(count)
+    }
+
+    /// Adds an alias, a secondary identifier such as a DOI or handle, for the object to the
+    /// repository's alias index. Once added, the alias may be used in place of the object's ID
+    /// when reading the object.
+    ///
+    /// The object must exist, and the alias may not be the ID of an existing object or identify
+    /// a different object.
+    pub fn add_object_alias(&self, object_id: &str, alias: &str) -> Result<()> {
+        self.ensure_writable("add an object alias")?;
+
+        let audit = Audit::start("add_alias").object_id(object_id).alias(alias);
+
+        if alias.is_empty() {
+            return Err(RocflError::InvalidValue(
+                "Object aliases may not be blank".to_string(),
+            ));
+        } else if alias == object_id {
+            return Err(RocflError::InvalidValue(format!(
+                "Object {} cannot be an alias of itself",
+                object_id
+            )));
+        }
+
+        if !self.store.object_exists(object_id)? {
+            return Err(not_found(object_id, None));
+        }
+
+        if self.store.object_exists(alias)? {
+            return Err(RocflError::IllegalOperation(format!(
+                "Cannot use {} as an alias because it is the ID of an existing object",
+                alias
+            )));
+        }
+
+        self.store.add_alias(object_id, alias)?;
+        audit.succeeded();
+        Ok(())
+    }
+
+    /// Removes an alias from the repository's alias index.
+    ///
+    /// If the alias does not exist, then a `RocflError::NotFound` error is returned.
+    pub fn remove_object_alias(&self, alias: &str) -> Result<()> {
+        self.ensure_writable("remove an object alias")?;
+
+        let audit = Audit::start("remove_alias").alias(alias);
+        self.store.remove_alias(alias)?;
+        audit.succeeded();
+
+        Ok(())
+    }
+
+    /// Returns the aliases in the repository's alias index, sorted by alias. If an object ID is
+    /// specified, then only the aliases of that object are returned.
+    pub fn list_object_aliases(&self, object_id: Option<&str>) -> Result<Vec<ObjectAlias>> {
+        self.ensure_open()?;
+
+        let mut aliases = self.store.list_aliases()?;
+
+        if let Some(object_id) = object_id {
+            aliases.retain(|alias| alias.object_id == object_id);
+        }
+
+        Ok(aliases)
+    }
+
+    /// Returns the digest algorithm that the repository is configured to use for new objects,
+    /// if one has been set with `set_default_digest_algorithm()`
+    pub fn default_digest_algorithm(&self) -> Result<Option<DigestAlgorithm>> {
+        self.ensure_open()?;
+        Ok(self.store.get_repo_defaults()?.digest_algorithm)
+    }
+
+    /// Sets the digest algorithm that new objects in the repository use by default. The value is
+    /// persisted in the repository's `rocfl-repo-defaults` extension. The algorithm must be
+    /// sha512 or sha256.
+    pub fn set_default_digest_algorithm(&self, digest_algorithm: DigestAlgorithm) -> Result<()> {
+        self.ensure_writable("set the default digest algorithm")?;
+
+        validate::validate_digest_algorithm(digest_algorithm)?;
+
+        let audit = Audit::start("set_default_digest_algorithm");
+
+        let mut defaults = self.store.get_repo_defaults()?;
+        defaults.digest_algorithm = Some(digest_algorithm);
+        self.store.set_repo_defaults(&defaults)?;
+
+        audit.succeeded();
+        Ok(())
+    }
+
+    /// Stages a new OCFL object if there is not an existing object with the same ID. The object
+    /// is not inserted into the repository until it is committed.
+    ///
+    /// If `spec_version` is not provided, then the repository version is used. If the repository
+    /// version is unknown, then the latest supported OCFL version is used.
+    pub fn create_object(
+        &self,
+        object_id: &str,
+        spec_version: Option<SpecVersion>,
+        digest_algorithm: DigestAlgorithm,
+        content_dir: &str,
+        padding_width: u32,
+    ) -> Result<()> {
+        self.ensure_writable("create an object")?;
+
+        let object_id = object_id.trim();
+        let mut audit = Audit::start("create").object_id(object_id);
+        let repo_version = self.spec_version.read().unwrap().clone();
+
+        let object_version = if let Some(object_version) = spec_version {
+            if let Some(Known(repo_version)) = &repo_version {
+                validate::validate_spec_version(object_version, *repo_version)?;
+            }
+            object_version
+        } else if let Some(Known(repo_version)) = &repo_version {
+            info!("Defaulting object version to {}", repo_version.version());
+            *repo_version
+        } else {
+            info!(
+                "Unknown repository version, defaulting object version to {}",
+                DEFAULT_VERSION.version()
+            );
+            DEFAULT_VERSION
+        };
+
+        validate::validate_object_id(object_id)?;
+        validate::validate_digest_algorithm(digest_algorithm)?;
+        validate::validate_content_dir(content_dir)?;
+        validate::validate_padding_width(padding_width)?;
+
+        let _lock = self.get_lock_manager()?.acquire(object_id)?;
+
+        if self.store.object_exists(object_id)? {
+            return Err(RocflError::IllegalState(format!(
+                "Cannot create object {} because it already exists",
+                object_id
+            )));
+        }
+
+        let version_num = VersionNum::v1_with_width(padding_width);
+        audit.set_version(version_num);
+
+        let mut inventory = Inventory::builder(object_id, object_version)
+            .with_digest_algorithm(digest_algorithm)
+            .with_content_directory(content_dir)
+            .with_head(version_num)
+            .build()?;
+
+        self.get_staging()?.stage_object(&mut inventory)?;
+        audit.spec_version(object_version).succeeded();
+
+        Ok(())
+    }
+
+    /// Stages a new OCFL object, like `create_object()`, and populates its first version with the
+    /// files in the template. If the template defines metadata, it is written to the JSON file at
+    /// `metadata_path`. If any of the template's files cannot be staged, the staged object is
+    /// dropped.
+    ///
+    /// The object is not inserted into the repository until it is committed.
+    pub fn create_object_from_template(
+        &self,
+        object_id: &str,
+        spec_version: Option<SpecVersion>,
+        template: &ObjectTemplate,
+        metadata_path: &LogicalPath,
+    ) -> Result<()> {
+        template.validate()?;
+
+        self.create_object(
+            object_id,
+            spec_version,
+            template.digest_algorithm(),
+            template.content_directory(),
+            template.zero_padding(),
+        )?;
+
+        let object_id = object_id.trim();
+
+        if let Err(e) = self.stage_template(object_id, template, metadata_path) {
+            if let Err(purge_err) = self.purge_staged_object(self.get_staging()?, object_id) {
+                warn!(
+                    "Failed to drop partially staged object {}: {}",
+                    object_id, purge_err
+                );
+            }
+            return Err(e);
+        }
+
+        Ok(())
+    }
+
+    /// Copies files from outside the OCFL repository into the specified OCFL object.
+    /// A destination of `/` specifies the object's root.
+    pub fn copy_files_external(
+        &self,
+        object_id: &str,
+        src: &[impl AsRef<Path>],
+        dst: &str,
+        recursive: bool,
+    ) -> Result<()> {
+        self.ensure_plannable("copy files")?;
+
+        let mut audit = Audit::start("copy")
+            .dry_run(self.is_dry_run())
+            .object_id(object_id)
+            .paths(src.iter().map(|p| p.as_ref().to_string_lossy()))
+            .destination(dst);
+
+        self.operate_on_external_source(
+            object_id,
+            src,
+            dst,
+            recursive,
+            ExternalOperation::Copy,
+            &mut audit,
+        )?;
+
+        audit.succeeded();
+        Ok(())
+    }
+
+    /// Copies the files in a tar archive into the specified OCFL object, under the destination
+    /// directory. A destination of `/` specifies the object's root. The archive may optionally be
+    /// gzip compressed. The archive's entries are hashed and staged as they are read from the
+    /// stream, without first being extracted, and the archive's internal paths are preserved as
+    /// logical paths. Entries that are not regular files, such as directories and links, are
+    /// skipped.
+    pub fn copy_archive_external(
+        &self,
+        object_id: &str,
+        archive: impl Read,
+        dst: &str,
+    ) -> Result<()> {
+        self.ensure_plannable("copy files")?;
+
+        let mut audit = Audit::start("copy")
+            .dry_run(self.is_dry_run())
+            .object_id(object_id)
+            .destination(dst);
+
+        let _lock = self.lock_staged_object(object_id)?;
+
+        let mut inventory = self.get_or_created_staged_inventory(object_id)?;
+        audit.set_version(inventory.head);
+        let changes = StagedChanges::new(self, "copy", &inventory)?;
+
+        let mut archive = BufReader::new(archive);
+        let is_gzip = archive.fill_buf()?.starts_with(&GZIP_MAGIC);
+
+        let archive: Box<dyn Read> = if is_gzip {
+            Box::new(GzDecoder::new(archive))
+        } else {
+            Box::new(archive)
+        };
+
+        let mut archive = tar::Archive::new(archive);
+        let mut errors = Vec::new();
+        let mut paths = Vec::new();
+
+        for entry in archive.entries()? {
+            if self.is_closed() {
+                break;
+            }
+
+            let mut entry = entry?;
+            let entry_path = archive_entry_path(&entry.path()?);
+
+            if !entry.header().entry_type().is_file() {
+                info!(
+                    "Skipping archive entry {} because it is not a file",
+                    entry_path
+                );
+                continue;
+            }
+
+            let mut attempt = || -> Result<()> {
+                let logical_path: LogicalPath =
+                    paths::join(dst, &entry_path).as_str().try_into()?;
+
+                inventory
+                    .head_version()
+                    .validate_non_conflicting(&logical_path)?;
+
+                info!(
+                    "Copying archive entry {} into object at {}",
+                    entry_path, logical_path
+                );
+
+                changes.copy_file(&mut inventory, &mut entry, logical_path)
+            };
+
+            match attempt() {
+                Ok(()) => paths.push(entry_path),
+                Err(e) => errors.push(format!("Failed to copy {}: {}", entry_path, e)),
+            }
+        }
+
+        inventory.head_version_mut().created = Local::now();
+        changes.finish(&inventory)?;
+
+        if !errors.is_empty() {
+            return Err(RocflError::CopyMoveError(MultiError(errors)));
+        }
+
+        audit.paths(paths).succeeded();
+        Ok(())
+    }
+
+    /// Stages changes to the key/value descriptive metadata that is stored in the JSON file at
+    /// `metadata_path` in an object. Keys mapped to `Some` are set to the value, and keys mapped
+    /// to `None` are removed. The changes are applied on top of the object's staged metadata, if
+    /// it has any, or its HEAD metadata. The file is created if it does not exist.
+    ///
+    /// The changes must be committed with `commit()`.
+    pub fn stage_object_metadata(
+        &self,
+        object_id: &str,
+        metadata_path: &LogicalPath,
+        changes: &BTreeMap<String, Option<String>>,
+    ) -> Result<()> {
+        self.ensure_writable("stage object metadata")?;
+
+        if metadata_path.as_str().is_empty() {
+            return Err(RocflError::InvalidValue(
+                "A metadata file path must be specified".to_string(),
+            ));
+        }
+
+        let mut audit = Audit::start("metadata")
+            .object_id(object_id)
+            .paths([metadata_path.as_str()]);
+
+        let _lock = self.get_lock_manager()?.acquire(object_id)?;
+
+        let mut inventory = self.get_or_created_staged_inventory(object_id)?;
+        audit.set_version(inventory.head);
+
+        let mut metadata = self.read_staged_metadata(&inventory, metadata_path)?;
+
+        for (key, value) in changes {
+            match value {
+                Some(value) => metadata.insert(key.clone(), value.clone()),
+                None => metadata.remove(key),
+            };
+        }
+
+        let mut bytes = serde_json::to_vec_pretty(&metadata).map_err(|e| {
+            RocflError::General(format!("Failed to serialize object metadata: {}", e))
+        })?;
+        bytes.push(b'\n');
+
+        inventory
+            .head_version()
+            .validate_non_conflicting(metadata_path)?;
+
+        info!(
+            "Staging metadata file {} in object {}",
+            metadata_path, object_id
+        );
+
+        let mut reader = inventory.digest_algorithm.reader(bytes.as_slice());
+        self.get_staging()?
+            .stage_file_copy(&inventory, &mut reader, metadata_path)?;
+        inventory.add_file_to_head(reader.finalize_hex(), metadata_path.clone())?;
+
+        inventory.head_version_mut().created = Local::now();
+        self.stage_inventory(&inventory)?;
+
+        audit.succeeded();
+        Ok(())
+    }
+
+    /// Copies files within an OCFL object. The source paths may be glob patterns.
+    pub fn copy_files_internal(
+        &self,
+        object_id: &str,
+        version_num: VersionRef,
+        src: &[impl AsRef<str>],
+        dst: &str,
+        recursive: bool,
+    ) -> Result<()> {
+        self.ensure_plannable("copy files")?;
+
+        if src.is_empty() {
+            return Ok(());
+        }
+
+        let mut audit = Audit::start("copy")
+            .dry_run(self.is_dry_run())
+            .object_id(object_id)
+            .paths(src)
+            .destination(dst);
+
+        let _lock = self.lock_staged_object(object_id)?;
+
+        let mut inventory = self.get_or_created_staged_inventory(object_id)?;
+        audit.set_version(inventory.head);
+        let changes = StagedChanges::new(self, "copy", &inventory)?;
+        let src_version_num = version_num.resolve(inventory.head);
+
+        let (to_copy, mut errors) =
+            self.resolve_internal_moves(&inventory, src_version_num, src, dst, recursive)?;
+
+        for (src_path, dst_path) in to_copy {
+            if self.is_closed() {
+                break;
+            }
+
+            let attempt = || -> Result<()> {
+                info!(
+                    "Copying file {} from {} to {}",
+                    src_path, src_version_num, dst_path
+                );
+
+                let digest_and_path =
+                    lookup_staged_digest_and_content_path(&inventory, src_version_num, &src_path)?;
+
+                // Copies of files new in the staged version must be copied on disk as well
+                if let Some((digest, content_path)) = digest_and_path {
+                    // Validate before copy to decrease the chance of failure after copying on disk
+                    inventory
+                        .head_version()
+                        .validate_non_conflicting(&dst_path)?;
+                    changes.copy_staged_file(&inventory, &content_path, &dst_path)?;
+                    // Should be impossible to fail
+                    inventory.add_file_to_head(digest, dst_path)
+                } else {
+                    inventory.copy_file_to_head(src_version_num, &src_path, dst_path)
+                }
+            };
+
+            if let Err(e) = attempt() {
+                errors.push(format!("Failed to copy file {}: {}", src_path, e));
+            }
+        }
+
+        inventory.head_version_mut().created = Local::now();
+        changes.finish(&inventory)?;
+
+        if !errors.is_empty() {
+            return Err(RocflError::CopyMoveError(MultiError(errors)));
+        }
+
+        audit.succeeded();
+        Ok(())
+    }
+
+    /// Like `copy_files_external()`, but the source paths are supplied by an iterator, such as
+    /// the entries of a file list that is too long to pass as arguments.
+    pub fn copy_files_external_iter<P: AsRef<Path>>(
+        &self,
+        object_id: &str,
+        src: impl IntoIterator<Item = P>,
+        dst: &str,
+        recursive: bool,
+    ) -> Result<()> {
+        let src: Vec<P> = src.into_iter().collect();
+        self.copy_files_external(object_id, &src, dst, recursive)
+    }
+
+    /// Moves files from outside the OCFL repository into the specified OCFL object.
+    /// A destination of `/` specifies the object's root.
+    pub fn move_files_external(
+        &self,
+        object_id: &str,
+        src: &[impl AsRef<Path>],
+        dst: &str,
+    ) -> Result<()> {
+        self.ensure_plannable("move files")?;
+
+        let mut audit = Audit::start("move")
+            .dry_run(self.is_dry_run())
+            .object_id(object_id)
+            .paths(src.iter().map(|p| p.as_ref().to_string_lossy()))
+            .destination(dst);
+
+        self.operate_on_external_source(
+            object_id,
+            src,
+            dst,
+            true,
+            ExternalOperation::Move,
+            &mut audit,
+        )?;
+
+        audit.succeeded();
+        Ok(())
+    }
+
+    /// Like `move_files_external()`, but the source paths are supplied by an iterator, such as
+    /// the entries of a file list that is too long to pass as arguments.
+    pub fn move_files_external_iter<P: AsRef<Path>>(
+        &self,
+        object_id: &str,
+        src: impl IntoIterator<Item = P>,
+        dst: &str,
+    ) -> Result<()> {
+        let src: Vec<P> = src.into_iter().collect();
+        self.move_files_external(object_id, &src, dst)
+    }
+
+    /// Moves files within an OCFL object. The source paths may be glob patterns.
+    pub fn move_files_internal(
+        &self,
+        object_id: &str,
+        src: &[impl AsRef<str>],
+        dst: &str,
+    ) -> Result<()> {
+        self.ensure_plannable("move files")?;
+
+        if src.is_empty() {
+            return Ok(());
+        }
+
+        let mut audit = Audit::start("move")
+            .dry_run(self.is_dry_run())
+            .object_id(object_id)
+            .paths(src)
+            .destination(dst);
+
+        let _lock = self.lock_staged_object(object_id)?;
+
+        let mut inventory = self.get_or_created_staged_inventory(object_id)?;
+        audit.set_version(inventory.head);
+        let changes = StagedChanges::new(self, "move", &inventory)?;
+
+        let (to_move, mut errors) =
+            self.resolve_internal_moves(&inventory, inventory.head, src, dst, true)?;
+
+        for (src_path, dst_path) in to_move {
+            if self.is_closed() {
+                break;
+            }
+
+            info!("Moving {} to {}", src_path, dst_path);
+
+            let attempt = || -> Result<()> {
+                let digest_and_path =
+                    lookup_staged_digest_and_content_path(&inventory, inventory.head, &src_path)?;
+
+                // Moves of files new in the staged version must be moved on disk as well
+                if let Some((digest, content_path)) = digest_and_path {
+                    // Validate before move to decrease the chance of failure after moving on disk
+                    inventory
+                        .head_version()
+                        .validate_non_conflicting(&dst_path)?;
+                    changes.move_staged_file(&inventory, &content_path, &dst_path)?;
+                    // Should be impossible to fail
+                    inventory.move_new_in_head_file(digest, &src_path, dst_path)
+                } else {
+                    inventory.move_file_in_head(&src_path, dst_path)
+                }
+            };
+
+            if let Err(e) = attempt() {
+                errors.push(format!("Failed to move file {}: {}", src_path, e));
+            }
+        }
+
+        inventory.head_version_mut().created = Local::now();
+        changes.finish(&inventory)?;
+
+        if !errors.is_empty() {
+            return Err(RocflError::CopyMoveError(MultiError(errors)));
+        }
+
+        audit.succeeded();
+        Ok(())
+    }
+
+    /// Removes the specified files from the staged version of the object. The files still
+    /// exist in prior versions.
+    pub fn remove_files<P: AsRef<str>>(
+        &self,
+        object_id: &str,
+        paths: &[P],
+        recursive: bool,
+    ) -> Result<()> {
+        self.ensure_plannable("remove files")?;
+
+        if paths.is_empty() {
+            return Ok(());
+        }
+
+        let mut audit = Audit::start("remove")
+            .dry_run(self.is_dry_run())
+            .object_id(object_id)
+            .paths(paths);
+
+        let _lock = self.lock_staged_object(object_id)?;
+
+        let mut inventory = self.get_or_created_staged_inventory(object_id)?;
+        audit.set_version(inventory.head);
+        let changes = StagedChanges::new(self, "remove", &inventory)?;
+        let version = inventory.head_version();
+
+        let mut paths_to_remove = HashSet::new();
+
+        for path in paths {
+            paths_to_remove.extend(version.resolve_glob(path.as_ref(), recursive)?);
+        }
+
+        for path in paths_to_remove {
+            if self.is_closed() {
+                break;
+            }
+
+            info!("Removing path from staged version: {}", path);
+            changes.remove_path(&mut inventory, &path)?;
+        }
+
+        changes.finish(&inventory)?;
+
+        audit.succeeded();
+        Ok(())
+    }
+
+    /// Replays the changes in a patch, created by `create_patch()`, onto the staged version of
+    /// the specified object. Renames are applied first, then deletes, and then adds and
+    /// modifications. The content of added and modified files is taken from the patch, if it was
+    /// inlined, otherwise it is resolved by digest from the object's manifest, and finally from
+    /// the version of the object the patch was created from, if it exists in this repository.
+    ///
+    /// The object must already exist, and its head version must match the state the patch was
+    /// created from: the modified, deleted, and renamed files must have the digests recorded in
+    /// the patch, and the added files must not exist. Otherwise, nothing is staged and a
+    /// `RocflError::IllegalState` error is returned. Changes that cannot be applied after the
+    /// object is checked do not prevent the others from being staged, and are reported together
+    /// in a `RocflError::CopyMoveError`.
+    pub fn apply_patch(&self, object_id: &str, patch: &ObjectPatch) -> Result<()> {
+        self.ensure_plannable("apply a patch")?;
+
+        let mut audit = Audit::start("apply")
+            .dry_run(self.is_dry_run())
+            .object_id(object_id);
+
+        let _lock = self.lock_staged_object(object_id)?;
+
+        let mut inventory = self.get_or_created_staged_inventory(object_id)?;
+        audit.set_version(inventory.head);
+        let changes = StagedChanges::new(self, "apply", &inventory)?;
+
+        let conflicts = patch_conflicts(&inventory, patch);
+        if !conflicts.is_empty() {
+            return Err(RocflError::IllegalState(format!(
+                "Object {} does not match the state the patch was created from: {}",
+                object_id,
+                conflicts.join("; ")
+            )));
+        }
+
+        let mut errors = Vec::new();
+        let mut paths = Vec::new();
+
+        for change in &patch.changes {
+            if let PatchChange::Rename { from, to, .. } = change {
+                let mut attempt = || -> Result<()> {
+                    let src_paths = from
+                        .iter()
+                        .map(|path| path.as_str().try_into())
+                        .collect::<Result<Vec<LogicalPath>>>()?;
+                    let dst_paths = to
+                        .iter()
+                        .map(|path| path.as_str().try_into())
+                        .collect::<Result<Vec<LogicalPath>>>()?;
+
+                    info!("Renaming {:?} to {:?}", from, to);
+                    inventory.rename_files_in_head(&src_paths, dst_paths)
+                };
+
+                match attempt() {
+                    Ok(()) => paths.extend(to.iter().cloned()),
+                    Err(e) => errors.push(format!("Failed to rename {:?}: {}", from, e)),
+                }
+            }
+        }
+
+        for change in &patch.changes {
+            if self.is_closed() {
+                break;
+            }
+
+            if let PatchChange::Delete { path, .. } = change {
+                let mut attempt = || -> Result<()> {
+                    let logical_path: LogicalPath = path.as_str().try_into()?;
+
+                    if !inventory.head_version().is_file(&logical_path) {
+                        return Err(not_found_path(object_id, inventory.head, &logical_path));
+                    }
+
+                    info!("Removing path from staged version: {}", logical_path);
+                    changes.remove_path(&mut inventory, &logical_path)
+                };
+
+                match attempt() {
+                    Ok(()) => paths.push(path.clone()),
+                    Err(e) => errors.push(format!("Failed to delete {}: {}", path, e)),
+                }
+            }
+        }
+
+        for change in &patch.changes {
+            if self.is_closed() {
+                break;
+            }
+
+            let file = match change {
+                PatchChange::Add(file) | PatchChange::Modify(file) => file,
+                _ => continue,
+            };
+
+            let mut attempt = || -> Result<()> {
+                let logical_path: LogicalPath = file.path.as_str().try_into()?;
+                let digest = HexDigest::from(file.digest.as_str());
+
+                inventory
+                    .head_version()
+                    .validate_non_conflicting(&logical_path)?;
+
+                if file.data.is_none()
+                    && patch.digest_algorithm == inventory.digest_algorithm
+                    && inventory.manifest().contains_id(&digest)
+                {
+                    info!("Adding existing content to object at {}", logical_path);
+                    changes.remove_path(&mut inventory, &logical_path)?;
+                    return inventory.add_existing_content_to_head(&digest, logical_path);
+                }
+
+                let verify = |actual: HexDigest| {
+                    if actual != digest {
+                        return Err(RocflError::General(format!(
+                            "Expected content to have {} digest {}, but it was {}",
+                            patch.digest_algorithm, digest, actual
+                        )));
+                    }
+                    Ok(())
+                };
+
+                info!("Adding patch content to object at {}", logical_path);
+
+                match &file.data {
+                    Some(data) => {
+                        let content = base64::decode(data).map_err(|e| {
+                            RocflError::InvalidValue(format!(
+                                "The inline data is not base64: {}",
+                                e
+                            ))
+                        })?;
+                        verify(patch.digest_algorithm.hash_hex(&mut content.as_slice())?)?;
+
+                        changes.remove_path(&mut inventory, &logical_path)?;
+                        changes.copy_file(&mut inventory, content.as_slice(), logical_path)
+                    }
+                    None => {
+                        // The content is streamed from the object the patch was created from,
+                        // and is only staged if it has the expected digest
+                        let source_path = logical_path.clone();
+                        changes.write_file(&mut inventory, logical_path, |sink| {
+                            let mut writer = patch.digest_algorithm.writer(sink);
+                            self.get_object_file(
+                                &patch.object_id,
+                                &source_path,
+                                VersionRef::Number(patch.to_version),
+                                &mut writer,
+                            )
+                            .map_err(|e| {
+                                RocflError::General(format!(
+                                    "The content is not inline in the patch, and could not be \
+                                     read from object {} version {}: {}",
+                                    patch.object_id, patch.to_version, e
+                                ))
+                            })?;
+                            verify(writer.finalize_hex())
+                        })
+                    }
+                }
+            };
+
+            match attempt() {
+                Ok(()) => paths.push(file.path.clone()),
+                Err(e) => errors.push(format!("Failed to apply {}: {}", file.path, e)),
+            }
+        }
+
+        inventory.head_version_mut().created = Local::now();
+        changes.finish(&inventory)?;
+
+        let audit = audit.paths(paths);
+
+        if !errors.is_empty() {
+            audit.partially_succeeded();
+            return Err(RocflError::CopyMoveError(MultiError(errors)));
+        }
+
+        audit.succeeded();
+        Ok(())
+    }
+
+    /// Reset all staged changes for an object by dropping the object's staged version completely.
+    pub fn reset_all(&self, object_id: &str) -> Result<()> {
+        self.ensure_writable("reset staged changes")?;
+
+        let audit = Audit::start("reset").object_id(object_id);
+
+        if self.staging_root.exists() {
+            self.purge_staged_object(self.get_staging()?, object_id)?;
+        }
+
+        audit.succeeded();
+        Ok(())
+    }
+
+    /// Resets to specified staged changes to an object. Paths may be a glob and is resolved
+    /// against both the staged version and the previous version. Matches in the staged version
+    /// are treated as add/update resets and matches in the previous version are treated as
+    /// remove resets.
+    pub fn reset<P: AsRef<str>>(
+        &self,
+        object_id: &str,
+        paths: &[P],
+        recursive: bool,
+    ) -> Result<()> {
+        self.ensure_writable("reset staged changes")?;
+
+        if paths.is_empty() {
+            return Ok(());
+        }
+
+        let mut audit = Audit::start("reset").object_id(object_id).paths(paths);
+
+        let staging = self.get_staging()?;
+
+        let _lock = self.get_lock_manager()?.acquire(object_id)?;
+
+        let mut inventory = match staging.get_inventory(object_id) {
+            Ok(inventory) => inventory,
+            Err(RocflError::NotFound(_)) => {
+                audit.succeeded();
+                return Ok(());
+            }
+            Err(e) => return Err(e),
+        };
+        audit.set_version(inventory.head);
+
+        let head = inventory.head_version();
+        let (previous, previous_num) = if inventory.is_new() {
+            (None, None)
+        } else {
+            let previous_num = inventory.head.previous()?;
+            (
+                Some(inventory.get_version(previous_num)?),
+                Some(previous_num),
+            )
+        };
+
+        let mut head_paths = HashSet::new();
+        let mut previous_paths = HashSet::new();
+
+        for path in paths {
+            head_paths.extend(head.resolve_glob(path.as_ref(), recursive)?);
+            if let Some(previous) = previous {
+                previous_paths.extend(previous.resolve_glob(path.as_ref(), recursive)?);
+            }
+        }
+
+        let reset_adds = head_paths
+            .into_iter()
+            .filter(|path| !previous_paths.contains(path))
+            .collect::<HashSet<Rc<LogicalPath>>>();
+
+        // Need to apply add resets first to attempt to avoid path conflicts
+        for path in reset_adds {
+            if self.is_closed() {
+                break;
+            }
+
+            if let Some(content_path) = inventory.remove_logical_path_from_head(&path) {
+                self.rm_staged_files(staging, &inventory, &[&content_path])?;
+            }
+        }
+
+        // Resetting deleted or modified files is the same
+        for path in previous_paths {
+            if self.is_closed() {
+                break;
+            }
+
+            if let Some(previous_num) = previous_num {
+                // In the case of a modification we need to delete the new file
+                if let Some(content_path) = inventory.remove_logical_path_from_head(&path) {
+                    self.rm_staged_files(staging, &inventory, &[&content_path])?;
+                }
+                inventory.copy_file_to_head(previous_num, &path, path.as_ref().clone())?;
+            }
+        }
+
+        inventory.head_version_mut().created = Local::now();
+        self.stage_inventory(&inventory)?;
+
+        audit.succeeded();
+        Ok(())
+    }
+
+    /// Stages a new version of an object whose state contains only the files in the specified
+    /// version that match the paths, or every file in the version if no paths are specified.
+    /// Paths may be globs. The staged files reference the version's existing content, and
+    /// nothing is copied. This allows the next version to be built from a subset of a version
+    /// without first removing everything else.
+    ///
+    /// An error is returned if the object already has staged changes.
+    pub fn stage_from_version<P: AsRef<str>>(
+        &self,
+        object_id: &str,
+        version_num: VersionRef,
+        paths: &[P],
+        recursive: bool,
+    ) -> Result<()> {
+        self.ensure_writable("stage a version")?;
+
+        let mut audit = Audit::start("stage").object_id(object_id).paths(paths);
+
+        let _lock = self.get_lock_manager()?.acquire(object_id)?;
+
+        let staging = self.get_staging()?;
+
+        match staging.get_inventory(object_id) {
+            Ok(_) => {
+                return Err(RocflError::IllegalState(format!(
+                    "Cannot stage a version of object {} because it already has staged changes",
+                    object_id
+                )));
+            }
+            Err(RocflError::NotFound(_)) => (),
+            Err(e) => return Err(e),
+        }
+
+        let mut inventory = self.get_stageable_inventory(object_id)?;
+        let src_version_num = version_num.resolve(inventory.head);
+        let src_version = inventory.get_version(src_version_num)?;
+
+        let src_paths = if paths.is_empty() {
+            src_version.logical_paths()
+        } else {
+            let mut src_paths = HashSet::new();
+            for path in paths {
+                src_paths.extend(src_version.resolve_glob(path.as_ref(), recursive)?);
+            }
+            if src_paths.is_empty() {
+                return Err(RocflError::NotFound(format!(
+                    "Object {} version {} does not contain any files that match the paths",
+                    object_id, src_version_num
+                )));
+            }
+            src_paths
+        };
+
+        inventory.create_staging_head()?;
+        audit.set_version(inventory.head);
+        inventory.head_version_mut().remove_state();
+
+        for path in src_paths {
+            info!("Staging path from version {}: {}", src_version_num, path);
+            inventory.copy_file_to_head(src_version_num, &path, path.as_ref().clone())?;
+        }
+
+        staging.stage_object(&mut inventory)?;
+
+        audit.succeeded();
+        Ok(())
+    }
+
+    /// Commits all of an object's staged changes. If `user_address` is provided, then `user_name`
+    /// must also be. If `created` is not provided, then it defaults to the current time.
+    ///
+    /// `object_root` may be specified to define the storage root relative path to the object's
+    /// root. This value is only respected if the object does not already exist, and the
+    /// repo does not have defined storage layout.
+    pub fn commit(
+        &self,
+        object_id: &str,
+        meta: CommitMeta,
+        object_root: Option<&str>,
+        pretty_print: bool,
+    ) -> Result<()> {
+        self.ensure_plannable("commit")?;
+
+        let mut audit = Audit::start("commit")
+            .dry_run(self.is_dry_run())
+            .object_id(object_id);
+
+        if self.is_dry_run() {
+            self.ensure_not_frozen("commit", object_id)?;
+            let inventory = match self.get_staged_inventory(object_id) {
+                Ok(inventory) => inventory,
+                Err(RocflError::NotFound(_)) => {
+                    return Err(RocflError::General(format!(
+                        "No staged changes found for object {}",
+                        object_id
+                    )));
+                }
+                Err(e) => return Err(e),
+            };
+            self.ensure_within_quotas(object_id, self.get_staging()?)?;
+            validate_version_padding(&inventory)?;
+            self.run_commit_callouts(&inventory)?;
+            return self.plan_new_version(
+                ChangePlan::new("commit", Some(object_id)),
+                inventory,
+                object_root,
+            );
+        }
+
+        let staging = self.get_staging()?;
+        let _lock = self.get_lock_manager()?.acquire(object_id)?;
+        self.ensure_not_frozen("commit", object_id)?;
+        self.ensure_within_quotas(object_id, staging)?;
+
+        if let Some(version_num) = self.commit_inner(
+            "commit",
+            object_id,
+            meta,
+            object_root,
+            pretty_print,
+            staging,
+        )? {
+            audit.set_version(version_num);
+            audit.succeeded();
+        }
+
+        Ok(())
+    }
+
+    /// Rebases an object's staged changes onto its current HEAD version, so that they can be
+    /// committed after another process committed a version to the object, or after its staged
+    /// inventory fell out of sync with the repository. The staged changes are the paths that
+    /// differ between the staged HEAD version and the version before it. The number of the
+    /// version the changes are staged in is returned, and nothing is changed if they already
+    /// follow the current version.
+    ///
+    /// If a path was changed both in the staged version and in the current version, then a
+    /// `RocflError::IllegalState` error is returned and the staged changes are not modified.
+    pub fn rebase_staged(&self, object_id: &str) -> Result<VersionNum> {
+        self.ensure_writable("rebase staged changes")?;
+
+        let mut audit = Audit::start("rebase").object_id(object_id);
+
+        if self.store.bucket_staging().is_some() {
+            return Err(RocflError::IllegalOperation(
+                "Staged changes cannot be rebased when content is staged in the bucket".to_string(),
+            ));
+        }
+
+        let staging = self.get_staging()?;
+        let _lock = self.get_lock_manager()?.acquire(object_id)?;
+
+        let staged = match staging.get_inventory(object_id) {
+            Ok(inventory) => inventory,
+            Err(RocflError::NotFound(_)) => {
+                return Err(RocflError::General(format!(
+                    "No staged changes found for object {}",
+                    object_id
+                )));
+            }
+            Err(e) => return Err(e),
+        };
+
+        match self.ensure_next_version(&staged) {
+            Ok(()) => {
+                audit.set_version(staged.head);
+                audit.succeeded();
+                return Ok(staged.head);
+            }
+            Err(RocflError::VersionConflict { .. }) => (),
+            Err(e) => return Err(e),
+        }
+
+        let current = self.store.get_inventory(&staged.id)?;
+        let rebased = staged.rebase_head(current)?;
+
+        let src = paths::version_path(&staged.storage_path, staged.head);
+        let dst = paths::version_path(&rebased.storage_path, rebased.head);
+
+        if src != dst && src.exists() {
+            if dst.exists() {
+                return Err(RocflError::IllegalState(format!(
+                    "Cannot rebase the staged changes to object {} because {} already exists",
+                    object_id,
+                    dst.to_string_lossy()
+                )));
+            }
+            util::move_path(&src, &dst)?;
+        }
+
+        info!(
+            "Rebased the staged changes to object {} from version {} onto version {}",
+            object_id, staged.head, rebased.head
+        );
+
+        staging.stage_inventory(&rebased, false, false)?;
+
+        audit.set_version(rebased.head);
+        audit.succeeded();
+        Ok(rebased.head)
+    }
+
+    /// Installs a fully formed version directory, such as one produced by a migration tool, as
+    /// the next version of an existing object. This bypasses the per-file staging APIs.
+    ///
+    /// `version_dir` must contain an `inventory.json` that describes the object as of the new
+    /// version, and a content directory that contains the files the new version adds. The
+    /// inventory's prior versions and manifest entries must match the object's current
+    /// inventory, and every file in the content directory must match the digest the manifest
+    /// records for it. The content files are moved into the repository.
+    ///
+    /// The object must not have staged changes. The number of the new version is returned.
+    pub fn adopt_version(
+        &self,
+        object_id: &str,
+        version_dir: impl AsRef<Path>,
+        pretty_print: bool,
+    ) -> Result<VersionNum> {
+        self.ensure_writable("adopt a version")?;
+
+        let mut audit = Audit::start("adopt_version").object_id(object_id);
+
+        let version_dir = version_dir.as_ref();
+        let staging = self.get_staging()?;
+        let _lock = self.get_lock_manager()?.acquire(object_id)?;
+        self.ensure_not_frozen("adopt a version", object_id)?;
+
+        match staging.get_inventory(object_id) {
+            Err(RocflError::NotFound(_)) => (),
+            Err(e) => return Err(e),
+            Ok(_) => {
+                return Err(RocflError::IllegalState(format!(
+                    "Cannot adopt a version into object {} because it has staged changes",
+                    object_id
+                )));
+            }
+        }
+
+        let existing = self.store.get_inventory(object_id)?;
+
+        if existing.mutable_head {
+            return Err(RocflError::IllegalState(
+                "Cannot adopt a version into an object that has an active mutable HEAD."
+                    .to_string(),
+            ));
+        }
+
+        let inventory_path = paths::inventory_path(version_dir);
+        let mut inventory: Inventory = match fs::read(&inventory_path) {
+            Ok(bytes) => serde_json::from_slice(&bytes).map_err(|e| {
+                RocflError::InvalidValue(format!(
+                    "Failed to parse inventory at {}: {}",
+                    inventory_path.to_string_lossy(),
+                    e
+                ))
+            })?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                return Err(RocflError::NotFound(format!(
+                    "Inventory {}",
+                    inventory_path.to_string_lossy()
+                )));
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        validate_adopted_inventory(&existing, &inventory)?;
+        verify_adopted_content(&inventory, version_dir)?;
+
+        let version = inventory.head_version();
+        let user = version.user.clone().unwrap_or(User {
+            name: None,
+            address: None,
+        });
+        let meta = CommitMeta::new()
+            .with_user(user.name, user.address)?
+            .with_message(version.message.clone())
+            .with_created(Some(version.created));
+
+        staging.stage_object(&mut inventory)?;
+
+        let content_dir = version_dir.join(inventory.defaulted_content_dir());
+        if content_dir.exists() {
+            fs::create_dir_all(paths::version_path(&inventory.storage_path, inventory.head))?;
+            util::move_path(
+                &content_dir,
+                paths::content_path(&inventory.storage_path, inventory.head, &inventory),
+            )?;
+        }
+
+        if let Some(version_num) = self.commit_inner(
+            "adopt_version",
+            object_id,
+            meta,
+            None,
+            pretty_print,
+            staging,
+        )? {
+            audit.set_version(version_num);
+            audit.succeeded();
+            Ok(version_num)
+        } else {
+            Err(RocflError::IllegalState(format!(
+                "The version adopted into object {} was not committed because the repository was closed",
+                object_id
+            )))
+        }
+    }
+
+    /// Upgrades an existing object to the specified OCFL spec version. This requires creating
+    /// a new object version. If the object currently has staged changes, then the changes are
+    /// committed as part of the upgrade.
+    pub fn upgrade_object(
+        &self,
+        object_id: &str,
+        version: SpecVersion,
+        meta: CommitMeta,
+        pretty_print: bool,
+    ) -> Result<()> {
+        self.ensure_plannable("upgrade an object")?;
+
+        let mut audit = Audit::start("upgrade")
+            .dry_run(self.is_dry_run())
+            .object_id(object_id)
+            .spec_version(version);
+
+        let _lock = self.lock_staged_object(object_id)?;
+        self.ensure_not_frozen("upgrade an object", object_id)?;
+
+        let mut inventory = self.get_or_created_staged_inventory(object_id)?;
+
+        if let Some(current_version) = inventory.spec_version() {
+            if version <= current_version {
+                return Err(RocflError::IllegalOperation(
+                    format!("Cannot upgrade object to {} because the current version, {}, is greater than or equal to the new version.",
+                            version.version(), current_version.version())));
+            }
+
+            let repo_version = self.spec_version.read().unwrap().clone();
+
+            if let Some(Known(repo_version)) = repo_version {
+                if repo_version < version {
+                    return Err(RocflError::IllegalOperation(
+                        format!("Cannot upgrade object to {} because it is less than the repository version, {}.",
+                                version.version(), repo_version.version())));
+                }
+            } else if let Some(Unknown(repo_version)) = repo_version {
+                return Err(RocflError::IllegalOperation(format!(
+                    "Cannot upgrade object to {} because the repository version, {}, is unrecognized.",
+                    version.version(), repo_version
+                )));
+            }
+        } else {
+            return Err(RocflError::IllegalOperation(format!(
+                "Cannot upgrade object to {} because the current version is unknown",
+                version.version()
+            )));
+        }
+
+        inventory.type_declaration = version.inventory_type().to_string();
+
+        if self.is_dry_run() {
+            let mut plan = ChangePlan::new("upgrade", Some(object_id));
+            plan.changes.push(PlannedChange::UpgradeObject(version));
+            return self.plan_new_version(plan, inventory, None);
+        }
+
+        let staging = self.get_staging()?;
+        self.stage_inventory(&inventory)?;
+
+        if let Some(version_num) =
+            self.commit_inner("upgrade", object_id, meta, None, pretty_print, staging)?
+        {
+            audit.set_version(version_num);
+            audit.succeeded();
+        }
+
+        Ok(())
+    }
+
+    /// Upgrades the repository to the specified version
+    pub fn upgrade_repo(&self, version: SpecVersion) -> Result<()> {
+        self.ensure_plannable("upgrade the repository")?;
+
+        let audit = Audit::start("upgrade")
+            .dry_run(self.is_dry_run())
+            .spec_version(version);
+        let current_version = self.spec_version.read().unwrap().clone();
+
+        if let Some(Known(current)) = current_version {
+            if current >= version {
+                return Err(RocflError::IllegalOperation(
+                    format!("Cannot upgrade repository to {} because the current version, {}, is greater than or equal to the new version.",
+                            version.version(), current.version())));
+            }
+        } else if let Some(Unknown(current)) = current_version {
+            return Err(RocflError::IllegalOperation(format!(
+                "Cannot upgrade repository to {} because the current version, {}, is unrecognized.",
+                version.version(),
+                current
+            )));
+        }
+
+        if self.is_dry_run() {
+            let mut plan = ChangePlan::new("upgrade", None);
+            plan.changes.push(PlannedChange::WriteFile(
+                version.root_namaste().filename.to_string(),
+            ));
+            plan.changes.push(PlannedChange::WriteFile(
+                version.spec_filename().to_string(),
+            ));
+            if let Some(Known(current)) = current_version {
+                plan.changes.push(PlannedChange::DeleteFile(
+                    current.root_namaste().filename.to_string(),
+                ));
+            }
+            self.record_plan(plan);
+            return Ok(());
+        }
+
+        self.store.upgrade_repo(version)?;
+        let mut repo_version = self.spec_version.write().unwrap();
+        *repo_version = Some(Known(version));
+
+        audit.succeeded();
+        Ok(())
+    }
+
+    /// Records the plan with the creation of the inventory's staged version, the path changes
+    /// it makes, and the files, relative the storage root, that committing it writes. Staged
+    /// content that duplicates existing content is not written. `object_root` is the object root
+    /// that was requested for a new object.
+    fn plan_new_version(
+        &self,
+        mut plan: ChangePlan,
+        mut inventory: Inventory,
+        object_root: Option<&str>,
+    ) -> Result<()> {
+        inventory.dedup_head();
+
+        // The staged inventory's object root is not relative the main storage root
+        let object_root = if inventory.is_new() {
+            self.store.new_object_root(&inventory.id, object_root)?
+        } else {
+            self.store.get_inventory(&inventory.id)?.object_root
+        };
+
+        plan.changes
+            .push(PlannedChange::CreateVersion(inventory.head));
+        plan.changes.extend(planned_path_changes(
+            inventory.diff_versions(None, inventory.head)?,
+        ));
+
+        let upgrade = plan
+            .changes
+            .iter()
+            .any(|change| matches!(change, PlannedChange::UpgradeObject(_)));
+
+        let mut files = Vec::new();
+        if inventory.is_new() || upgrade {
+            if let Some(version) = inventory.spec_version() {
+                files.push(version.object_namaste().filename.to_string());
+            }
+        }
+        files.extend(staged_content_paths(&inventory));
+        let sidecar = paths::sidecar_name(inventory.digest_algorithm);
+        for dir in [inventory.head.to_string(), String::new()] {
+            files.push(paths::join(&dir, INVENTORY_FILE));
+            files.push(paths::join(&dir, &sidecar));
+        }
+
+        plan.changes.extend(
+            files
+                .into_iter()
+                .map(|file| PlannedChange::WriteFile(paths::join(&object_root, &file))),
+        );
+
+        self.record_plan(plan);
+        Ok(())
+    }
+
+    fn commit_inner(
+        &self,
+        operation: &str,
+        object_id: &str,
+        meta: CommitMeta,
+        object_root: Option<&str>,
+        pretty_print: bool,
+        staging: &FsOcflStore,
+    ) -> Result<Option<VersionNum>> {
+        let mut inventory = match staging.get_inventory(object_id) {
+            Ok(inventory) => inventory,
+            Err(RocflError::NotFound(_)) => {
+                return Err(RocflError::General(format!(
+                    "No staged changes found for object {}",
+                    object_id
+                )));
+            }
+            Err(e) => return Err(e),
+        };
+
+        validate_version_padding(&inventory)?;
+        self.ensure_next_version(&inventory)?;
+        self.run_commit_callouts(&inventory)?;
+        let encryption_key = self.commit_encryption_key(&inventory)?;
+
+        let duplicates = inventory.dedup_head();
+
+        // TODO validate staged version before committing
+
+        inventory.head_version_mut().update_meta(meta);
+
+        staging.stage_inventory(&inventory, true, pretty_print)?;
+        staging.rm_staged_files(
+            &inventory,
+            &duplicates
+                .iter()
+                .map(|p| p.as_ref())
+                .collect::<Vec<&ContentPath>>(),
+        )?;
+        staging.rm_orphaned_files(&inventory)?;
+
+        // Existing content cannot be linked into encrypted objects because the same content is
+        // encrypted differently every time
+        let link_content = self.hard_links && encryption_key.is_none();
+
+        let signature = match &self.signing_key {
+            Some(key) => {
+                let inventory_path = Path::new(&inventory.storage_path).join(INVENTORY_FILE);
+                Some(key.sign(inventory.head, &fs::read(inventory_path)?))
+            }
+            None => None,
+        };
+
+        let encrypted = match encryption_key {
+            Some(key) => Some(self.encrypt_staged_content(&inventory, key)?),
+            None => None,
+        };
+
+        // Last chance to ctrl-c before committing
+        if self.is_open() {
+            let result = if inventory.is_new() {
+                let src_object_root = encrypted
+                    .clone()
+                    .unwrap_or_else(|| PathBuf::from(&inventory.storage_path));
+                self.store
+                    .write_new_object(&mut inventory, &src_object_root, object_root)
+            } else {
+                let version_root = encrypted.clone().unwrap_or_else(|| {
+                    paths::version_path(&inventory.storage_path, inventory.head)
+                });
+                self.store.write_new_version(&mut inventory, &version_root)
+            };
+
+            if let Some(encrypted) = &encrypted {
+                remove_encrypted_content(encrypted);
+            }
+            result?;
+
+            self.purge_staged_object(staging, object_id)?;
+
+            // Content is only linked once it is committed, so that staged changes can never
+            // write through a link into existing content
+            if link_content {
+                if let Err(e) = self.store.link_duplicate_content(&inventory) {
+                    warn!(
+                        "Failed to link the content of object {} to existing content: {:#}",
+                        object_id, e
+                    );
+                }
+            }
+
+            if self.audit_trail {
+                self.record_audit_trail(operation, &inventory);
+            }
+
+            if let Some(signature) = signature {
+                self.record_signature(&inventory, &signature);
+            }
+
+            return Ok(Some(inventory.head));
+        }
+
+        if let Some(encrypted) = &encrypted {
+            remove_encrypted_content(encrypted);
+        }
+
+        Ok(None)
+    }
+
+    /// Returns the key that the content of the staged version must be encrypted with, or `None`
+    /// if it is not encrypted. New objects are encrypted when a key is configured, and new
+    /// versions of existing objects are encrypted if the object is encrypted.
+    fn commit_encryption_key(&self, inventory: &Inventory) -> Result<Option<&EncryptionKey>> {
+        if inventory.is_new() {
+            Ok(self.encryption_key.as_ref())
+        } else {
+            self.content_key(&inventory.id)
+        }
+    }
+
+    /// Returns the key that the object's content must be decrypted with, or `None` if the object
+    /// is not encrypted
+    fn content_key(&self, object_id: &str) -> Result<Option<&EncryptionKey>> {
+        let marker = match self.encryption_marker(object_id)? {
+            Some(marker) => marker,
+            None => return Ok(None),
+        };
+
+        match &self.encryption_key {
+            Some(key) => {
+                key.ensure_matches(object_id, &marker)?;
+                Ok(Some(key))
+            }
+            None => Err(RocflError::InvalidConfiguration(format!(
+                "Object {} is encrypted, but an encryption key is not configured",
+                object_id
+            ))),
+        }
+    }
+
+    /// Returns the object's encryption marker, or `None` if the object is not encrypted
+    fn encryption_marker(&self, object_id: &str) -> Result<Option<EncryptionMarker>> {
+        let mut bytes = Vec::new();
+        let marker_path = format!("{}/{}", ROCFL_ENCRYPTION_EXTENSION, EXTENSIONS_CONFIG_FILE);
+
+        match self
+            .store
+            .get_object_extension_file(object_id, &marker_path, &mut bytes)
+        {
+            Ok(_) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            Err(RocflError::NotFound(_)) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Returns the size of the plaintext of a content file in the repository. This is the size
+    /// of the file, unless the object is encrypted, in which case `marker` must be its
+    /// encryption marker.
+    fn content_file_size(
+        &self,
+        inventory: &Inventory,
+        content_path: &ContentPath,
+        marker: Option<&EncryptionMarker>,
+    ) -> Result<u64> {
+        let size = self.store.get_content_file_size(inventory, content_path)?;
+        Ok(marker.map_or(size, |marker| marker.plaintext_size(size)))
+    }
+
+    /// Writes a copy of the staged object root, if the object is new, or of the staged HEAD
+    /// version directory to the staging root's `rocfl-encryption` extension, with every new
+    /// content file encrypted. New objects also have their encryption marker written. The path
+    /// to the copy is returned.
+    fn encrypt_staged_content(
+        &self,
+        inventory: &Inventory,
+        key: &EncryptionKey,
+    ) -> Result<PathBuf> {
+        let object_root = Path::new(&inventory.storage_path);
+        let version_prefix = format!("{}/", inventory.head);
+
+        let mut content_files = HashSet::new();
+        for (content_path, _) in inventory.manifest().iter() {
+            if !content_path.starts_with(&version_prefix) {
+                continue;
+            }
+            if self.bucket_staged_file(inventory, content_path).is_some() {
+                return Err(RocflError::IllegalOperation(format!(
+                    "Cannot encrypt the content of object {} because {} is staged in the bucket",
+                    inventory.id, content_path
+                )));
+            }
+            content_files.insert(object_root.join(util::native_path(content_path.as_str())));
+        }
+
+        let src = if inventory.is_new() {
+            object_root.to_path_buf()
+        } else {
+            paths::version_path(object_root, inventory.head)
+        };
+        let dst = paths::extensions_path(&self.staging_root)
+            .join(ROCFL_ENCRYPTION_EXTENSION)
+            .join(
+                DigestAlgorithm::Sha256
+                    .hash_hex(&mut inventory.id.as_bytes())?
+                    .as_ref(),
+            );
+
+        if dst.exists() {
+            fs::remove_dir_all(&dst)?;
+        }
+
+        info!("Encrypting the new content of object {}", inventory.id);
+
+        let result = (|| -> Result<()> {
+            for entry in WalkDir::new(&src) {
+                self.ensure_open()?;
+
+                let entry = entry?;
+                let target = dst.join(entry.path().strip_prefix(&src).unwrap());
+
+                if entry.file_type().is_dir() {
+                    fs::create_dir_all(&target)?;
+                } else if content_files.contains(entry.path()) {
+                    key.encrypt_file(entry.path(), &target)?;
+                } else {
+                    fs::copy(entry.path(), &target)?;
+                }
+            }
+
+            if inventory.is_new() {
+                let marker_path = paths::encryption_marker_path(&dst);
+                fs::create_dir_all(marker_path.parent().unwrap())?;
+                fs::write(&marker_path, serde_json::to_vec_pretty(&key.marker())?)?;
+            }
+
+            Ok(())
+        })();
+
+        match result {
+            Ok(_) => Ok(dst),
+            Err(e) => {
+                remove_encrypted_content(&dst);
+                Err(e)
+            }
+        }
+    }
+
+    /// Verifies, against the object's current inventory in the repository, that the staged HEAD
+    /// version immediately follows the current HEAD version, and that the staged inventory's
+    /// versions do not have gaps. The staged inventory is not trusted, because it may have been
+    /// edited, or the object may have been committed to by another process since its changes were
+    /// staged. The object's lock must be held.
+    fn ensure_next_version(&self, staged: &Inventory) -> Result<()> {
+        let current = match self.store.get_inventory(&staged.id) {
+            Ok(current) => current.head,
+            Err(RocflError::NotFound(_)) if staged.is_new() => return Ok(()),
+            Err(e) => return Err(e),
+        };
+
+        let contiguous = staged
+            .versions
+            .keys()
+            .map(|version_num| version_num.number)
+            .eq(1..=staged.head.number);
+
+        if !contiguous || staged.head.number != current.number + 1 {
+            return Err(RocflError::VersionConflict {
+                object_id: staged.id.clone(),
+                staged: staged.head,
+                current,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Writes the content files, version inventories, extensions, and logs of a squashed object
+    /// to its object root in staging. Each content file is read from the original object.
+    fn write_squashed_object(
+        &self,
+        original: &Inventory,
+        squashed: &Inventory,
+        staging: &FsOcflStore,
+        pretty_print: bool,
+    ) -> Result<()> {
+        let object_root = PathBuf::from(&squashed.storage_path);
+
+        for (content_path, digest) in squashed.manifest().iter() {
+            self.ensure_open()?;
+
+            let src = original.content_path_for_digest(digest, VersionRef::Head, None)?;
+            let dst = object_root.join(util::native_path(content_path.as_str()));
+            if let Some(parent) = dst.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            let mut file = File::create(&dst)?;
+            self.store.get_content_file(original, src, &mut file)?;
+        }
+
+        for version_num in squashed.versions.keys() {
+            staging.stage_inventory(&squashed.as_of_version(*version_num)?, true, pretty_print)?;
+        }
+
+        for path in self.store.list_object_extension_files(&original.id)? {
+            let dst = object_root
+                .join(EXTENSIONS_DIR)
+                .join(util::native_path(&path));
+            if let Some(parent) = dst.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            let mut file = File::create(&dst)?;
+            self.store
+                .get_object_extension_file(&original.id, &path, &mut file)?;
+        }
+
+        for path in self.store.list_object_logs(&original.id)? {
+            let dst = object_root.join(LOGS_DIR).join(util::native_path(&path));
+            if let Some(parent) = dst.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            let mut file = File::create(&dst)?;
+            self.store
+                .get_object_log_file(&original.id, &path, &mut file)?;
+        }
+
+        Ok(())
+    }
+
+    /// Copies every file in the original object into the renamed object in staging, except for
+    /// its inventories and sidecars, which are rewritten with the renamed object's ID. Each
+    /// version's inventory retains its own digest algorithm and fixity block.
+    fn write_renamed_object(
+        &self,
+        original: &Inventory,
+        renamed: &Inventory,
+        staging: &FsOcflStore,
+        pretty_print: bool,
+    ) -> Result<()> {
+        let object_root = PathBuf::from(&renamed.storage_path);
+        let prefix = format!("{}/", original.object_root);
+
+        let is_inventory_file = |path: &str| {
+            let (dir, name) = match path.rsplit_once('/') {
+                Some((dir, name)) => (Some(dir), name),
+                None => (None, path),
+            };
+            name.starts_with(INVENTORY_FILE)
+                && dir.is_none_or(|dir| {
+                    VersionNum::try_from(dir).is_ok_and(|num| original.versions.contains_key(&num))
+                })
+        };
+
+        for path in self.store.list_object_files(&original.id)? {
+            self.ensure_open()?;
+
+            let path = path.strip_prefix(&prefix).unwrap_or(&path);
+            if is_inventory_file(path) {
+                continue;
+            }
+
+            let dst = object_root.join(util::native_path(path));
+            if let Some(parent) = dst.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            let mut file = File::create(&dst)?;
+            self.store
+                .get_object_root_file(&original.id, path, &mut file)?;
+        }
+
+        let mut algorithms = HashSet::new();
+
+        // The HEAD version's inventory must be identical to the root inventory
+        for version_num in original.versions.keys().filter(|num| **num != renamed.head) {
+            let inventory_path = format!("{}/{}", version_num, INVENTORY_FILE);
+            let mut bytes = Vec::new();
+
+            let mut version_inventory =
+                match self
+                    .store
+                    .get_object_root_file(&original.id, &inventory_path, &mut bytes)
+                {
+                    Ok(()) => validate::parse_inventory(&bytes).0?,
+                    Err(RocflError::NotFound(_)) => original.as_of_version(*version_num)?,
+                    Err(e) => return Err(e),
+                };
+
+            version_inventory.id = renamed.id.clone();
+            version_inventory.object_root = renamed.object_root.clone();
+            version_inventory.storage_path = renamed.storage_path.clone();
+
+            algorithms.insert(version_inventory.digest_algorithm);
+            staging.stage_inventory(&version_inventory, true, pretty_print)?;
+        }
+
+        // The root must only have the sidecar of the root inventory
+        staging.stage_inventory(renamed, true, pretty_print)?;
+        for algorithm in algorithms {
+            if algorithm != renamed.digest_algorithm {
+                util::remove_file_ignore_not_found(paths::sidecar_path(&object_root, algorithm))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Reads the signatures in the object's signatures log, oldest first
+    fn read_signatures(&self, object_id: &str) -> Result<Vec<InventorySignature>> {
+        let mut bytes = Vec::new();
+        match self
+            .store
+            .get_object_log_file(object_id, SIGNATURES_FILE, &mut bytes)
+        {
+            Ok(()) => (),
+            Err(RocflError::NotFound(_)) => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        }
+
+        bytes
+            .split(|b| *b == b'\n')
+            .filter(|line| !line.iter().all(u8::is_ascii_whitespace))
+            .map(|line| serde_json::from_slice(line).map_err(RocflError::from))
+            .collect()
+    }
+
+    /// Appends an entry describing the operation that created the inventory's HEAD version to
+    /// the object's audit trail. The version is already committed at this point, so failures are
+    /// logged rather than returned.
+    fn record_audit_trail(&self, operation: &str, inventory: &Inventory) {
+        let paths = match inventory.diff_versions(None, inventory.head) {
+            Ok(diffs) => diffs
+                .iter()
+                .flat_map(|diff| match diff {
+                    Diff::Added(path) | Diff::Modified(path) | Diff::Deleted(path) => {
+                        vec![path.to_string()]
+                    }
+                    Diff::Renamed { original, renamed } => original
+                        .iter()
+                        .chain(renamed.iter())
+                        .map(|path| path.to_string())
+                        .collect(),
+                })
+                .collect(),
+            Err(e) => {
+                warn!(
+                    "Failed to diff version {} of object {}: {}",
+                    inventory.head, inventory.id, e
+                );
+                Vec::new()
+            }
+        };
+
+        let version = inventory.head_version();
+        let user = version.user.as_ref();
+
+        let entry = AuditTrailEntry {
+            operation: operation.to_string(),
+            version: inventory.head,
+            timestamp: Local::now(),
+            user_name: user.and_then(|user| user.name.clone()),
+            user_address: user.and_then(|user| user.address.clone()),
+            paths,
+            tool_version: format!("rocfl {}", env!("CARGO_PKG_VERSION")),
+        };
+
+        let result = serde_json::to_vec(&entry)
+            .map_err(RocflError::from)
+            .and_then(|mut line| {
+                line.push(b'\n');
+                self.store
+                    .append_object_log(&inventory.id, AUDIT_TRAIL_FILE, &line)
+            });
+
+        if let Err(e) = result {
+            warn!(
+                "Failed to record the audit trail of object {}: {}",
+                inventory.id, e
+            );
+        }
+    }
+
+    /// Appends the signature of the inventory's HEAD version to the object's signatures log. The
+    /// version is already committed at this point, so failures are logged rather than returned.
+    fn record_signature(&self, inventory: &Inventory, signature: &InventorySignature) {
+        let result = serde_json::to_vec(signature)
+            .map_err(RocflError::from)
+            .and_then(|mut line| {
+                line.push(b'\n');
+                self.store
+                    .append_object_log(&inventory.id, SIGNATURES_FILE, &line)
+            });
+
+        if let Err(e) = result {
+            warn!(
+                "Failed to record the signature of object {} version {}: {}",
+                inventory.id, inventory.head, e
+            );
+        }
+    }
+
+    /// Attempts to get the inventory from staging. If it is not found, it is loaded from the
+    /// main repo, and moved into staging. If it is not found in the main repo, then an error is
+    /// returned.
+    ///
+    /// In dry-run mode, the inventory is not moved into staging, and staging is not created.
+    fn get_or_created_staged_inventory(&self, object_id: &str) -> Result<Inventory> {
+        let staged = if self.is_dry_run() {
+            self.get_staged_inventory(object_id)
+        } else {
+            self.get_staging()?.get_inventory(object_id)
+        };
+
+        match staged {
+            Ok(inventory) => Ok(inventory),
+            Err(RocflError::NotFound(_)) => {
+                let mut inventory = self.get_stageable_inventory(object_id)?;
+                inventory.create_staging_head()?;
+                if !self.is_dry_run() {
+                    self.get_staging()?.stage_object(&mut inventory)?;
+                }
+                Ok(inventory)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Loads an object's inventory from the main repo, and verifies that a new version of the
+    /// object may be staged.
+    fn get_stageable_inventory(&self, object_id: &str) -> Result<Inventory> {
+        let inventory = self.store.get_inventory(object_id)?;
+
+        if inventory.spec_version().is_none() {
+            return Err(RocflError::IllegalOperation(
+                "Cannot update object because it conforms to an unknown/unsupported spec version"
+                    .to_string(),
+            ));
+        }
+
+        if inventory.mutable_head {
+            return Err(RocflError::IllegalState(
+                "Cannot stage changes for object because it has an active mutable HEAD."
+                    .to_string(),
+            ));
+        }
+
+        for extension in self.store.list_object_extensions(object_id)? {
+            if !SUPPORTED_EXTENSIONS.contains(&extension.as_ref()) {
+                warn!("Object {} uses unsupported extension {}. Modifying this object may have unintended consequences.",
+                      object_id, extension);
+            }
+        }
+
+        Ok(inventory)
+    }
+
+    /// Writes a file in the staged version of an object to the sink. The file is read from
+    /// staging if it was added in the staged version, and from the main repo otherwise.
+    fn read_staged_file(
+        &self,
+        inventory: &Inventory,
+        path: &LogicalPath,
+        sink: &mut dyn Write,
+    ) -> Result<()> {
+        let content_path = inventory.content_path_for_logical_path(path, VersionRef::Head)?;
+
+        let version_prefix = format!("{}/", inventory.head);
+
+        if content_path.starts_with(&version_prefix) {
+            // The content exists in staging
+            match self.bucket_staged_file(inventory, content_path) {
+                Some(bucket) => bucket.get_staged_file(inventory, content_path, sink),
+                None => {
+                    self.get_staging()?
+                        .get_object_file(&inventory.id, path, VersionRef::Head, sink)
+                }
+            }
+        } else {
+            // The content exists in the main repo
+            read_content(self.content_key(&inventory.id)?, sink, |writer| {
+                self.store.get_object_file(
+                    &inventory.id,
+                    path,
+                    inventory.head.previous()?.into(),
+                    writer,
+                )
+            })
+        }
+    }
+
+    /// Reads the metadata file from the staged version of an object, returning an empty map if
+    /// the staged version does not contain it.
+    fn read_staged_metadata(
+        &self,
+        inventory: &Inventory,
+        metadata_path: &LogicalPath,
+    ) -> Result<BTreeMap<String, String>> {
+        if inventory
+            .head_version()
+            .lookup_digest(metadata_path)
+            .is_none()
+        {
+            return Ok(BTreeMap::new());
+        }
+
+        let mut bytes = Vec::new();
+        self.read_staged_file(inventory, metadata_path, &mut bytes)?;
+        parse_metadata(metadata_path, &bytes)
+    }
+
+    /// Attempts to load the object's inventory from staging. If it does not exist,
+    /// then `RocflError::NotFound` is returned.
+    fn get_staged_inventory(&self, object_id: &str) -> Result<Inventory> {
+        if !self.staging_root.exists() {
+            return Err(RocflError::NotFound(format!(
+                "{} does not have a staged version.",
+                object_id
+            )));
+        }
+
+        match self.get_staging()?.get_inventory(object_id) {
+            Ok(inventory) => Ok(inventory),
+            Err(RocflError::NotFound(_)) => Err(RocflError::NotFound(format!(
+                "{} does not have a staged version.",
+                object_id
+            ))),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Copies or moves every file in an input source into staging. The sources are resolved to
+    /// the files to operate on first. Then the files are hashed, and copied into staging, on the
+    /// repository's thread pool, and finally they are added to the inventory in the order they
+    /// were resolved in.
+    fn operate_on_external_source(
+        &self,
+        object_id: &str,
+        src: &[impl AsRef<Path>],
+        dst: &str,
+        recursive: bool,
+        operation: ExternalOperation,
+        audit: &mut Audit,
+    ) -> Result<()> {
+        if src.is_empty() {
+            return Ok(());
+        }
+
+        let _lock = self.lock_staged_object(object_id)?;
+
+        let mut inventory = self.get_or_created_staged_inventory(object_id)?;
+        audit.set_version(inventory.head);
+        let changes = StagedChanges::new(self, audit.operation(), &inventory)?;
+
+        let dst_path = dst.try_into()?;
+
+        let dst_dir_exists = inventory.head_version().is_dir(&dst_path);
+        let src_is_many = src.len() > 1;
+        let dst_has_slash = dst.ends_with('/');
+
+        let symlink_policy = *self.symlink_policy.read().unwrap();
+        let follow_links = symlink_policy == SymlinkPolicy::Follow;
+        let preserve_timestamps = self.preserve_timestamps.load(Ordering::Acquire);
+
+        let mut errors = Vec::new();
+        let mut batch = ExternalBatch::default();
+
+        for path in src.iter() {
+            if self.is_closed() {
+                break;
+            }
+
+            let path = path.as_ref();
+
+            let is_link = match path.symlink_metadata() {
+                Ok(meta) => meta.file_type().is_symlink(),
+                Err(_) => {
+                    errors.push(format!(
+                        "Failed to copy/move {}: Does not exist",
+                        path.to_string_lossy()
+                    ));
+                    continue;
+                }
+            };
+
+            if is_link && !follow_links {
+                if let Some(error) = reject_symlink(path, symlink_policy) {
+                    errors.push(error);
+                }
+                continue;
+            }
+
+            if !path.exists() {
+                errors.push(format!(
+                    "Failed to copy/move {}: Symbolic link target does not exist",
+                    path.to_string_lossy()
+                ));
+                continue;
+            }
+
+            let mut attempt = || -> Result<()> {
+                if path.is_file() {
+                    let parent = path.parent().unwrap();
+
+                    let logical_path = if dst_dir_exists || src_is_many || dst_has_slash {
+                        logical_path_in_dst_dir(path, parent, dst)?
+                    } else {
+                        dst_path.clone()
+                    };
+
+                    batch.add(&inventory, path, logical_path, preserve_timestamps)?;
+                } else if recursive {
+                    for file in WalkDir::new(path).follow_links(follow_links) {
+                        if self.is_closed() {
+                            break;
+                        }
+
+                        let file = match file {
+                            Ok(file) => file,
+                            Err(e) => {
+                                match (e.loop_ancestor(), e.path()) {
+                                    (Some(ancestor), Some(link)) => warn!(
+                                        "Skipping {} because it links to its ancestor {}",
+                                        link.to_string_lossy(),
+                                        ancestor.to_string_lossy()
+                                    ),
+                                    (_, Some(link)) => errors.push(format!(
+                                        "Failed to copy/move {}: {}",
+                                        link.to_string_lossy(),
+                                        e
+                                    )),
+                                    _ => errors.push(format!(
+                                        "Failed to copy/move {}: {}",
+                                        path.to_string_lossy(),
+                                        e
+                                    )),
+                                }
+                                continue;
+                            }
+                        };
+
+                        if file.path_is_symlink() && !follow_links {
+                            if let Some(error) = reject_symlink(file.path(), symlink_policy) {
+                                errors.push(error);
+                            }
+                        } else if file.file_type().is_file() {
+                            let mut attempt = || -> Result<()> {
+                                let logical_path = if dst_dir_exists || src_is_many {
+                                    let grandparent = path.parent().unwrap_or(path);
+                                    logical_path_in_dst_dir(file.path(), grandparent, dst)?
+                                } else {
+                                    logical_path_in_dst_dir(file.path(), path, dst)?
+                                };
+
+                                batch.add(
+                                    &inventory,
+                                    file.path(),
+                                    logical_path,
+                                    preserve_timestamps,
+                                )
+                            };
+
+                            if let Err(e) = attempt() {
+                                errors.push(format!(
+                                    "Failed to copy/move {}: {}",
+                                    file.path().to_string_lossy(),
+                                    e
+                                ));
+                            }
                         }
+                    }
+                } else {
+                    errors.push(format!(
+                        "Skipping directory {} because recursion is not enabled",
+                        path.to_string_lossy()
+                    ));
+                }
+
+                Ok(())
+            };
+
+            if let Err(e) = attempt() {
+                errors.push(format!(
+                    "Failed to copy/move {}: {}",
+                    path.to_string_lossy(),
+                    e
+                ));
+            }
+        }
+
+        let digest_algorithm = inventory.digest_algorithm;
+        let object_root = inventory.storage_path.clone();
+        let staging = changes.staging();
+
+        let prepared = self.thread_pool().map(
+            &batch.files,
+            || self.is_closed(),
+            |file| {
+                if file.deferred {
+                    Ok(None)
+                } else {
+                    prepare_external_file(operation, file, digest_algorithm, &object_root, staging)
+                }
+            },
+        );
+
+        let mut timestamps = BTreeMap::new();
+
+        for (file, prepared) in batch.files.into_iter().zip(prepared) {
+            // Files are not prepared once the repository is closed
+            let prepared = match prepared {
+                Some(prepared) => prepared,
+                None => break,
+            };
+
+            let attempt = prepared.and_then(|digest| {
+                inventory
+                    .head_version()
+                    .validate_non_conflicting(&file.logical_path)?;
+                self.apply_external_file(operation, &file, digest, &mut inventory, &changes)
+            });
+
+            match attempt {
+                Ok(()) => {
+                    if let Some(modified) = file.modified {
+                        timestamps.insert(file.logical_path.to_string(), modified);
+                    }
+                }
+                Err(e) => errors.push(format!(
+                    "Failed to copy/move {}: {}",
+                    file.path.to_string_lossy(),
+                    e
+                )),
+            }
+        }
+
+        if !timestamps.is_empty() {
+            if let Err(e) = self.stage_original_timestamps(&mut inventory, timestamps, &changes) {
+                errors.push(format!(
+                    "Failed to record original modification times: {}",
+                    e
+                ));
+            }
+        }
+
+        if errors.is_empty() && operation == ExternalOperation::Move && self.is_open() {
+            changes.clean_source_dirs(src)?;
+        }
+
+        inventory.head_version_mut().created = Local::now();
+        changes.finish(&inventory)?;
+
+        if !errors.is_empty() {
+            return Err(RocflError::CopyMoveError(MultiError(errors)));
+        }
+
+        Ok(())
+    }
+
+    /// Adds an external file to the inventory, finishing any work that was not done when it was
+    /// prepared. The file's destination must already be validated.
+    fn apply_external_file(
+        &self,
+        operation: ExternalOperation,
+        file: &ExternalFile,
+        digest: Option<HexDigest>,
+        inventory: &mut Inventory,
+        changes: &StagedChanges,
+    ) -> Result<()> {
+        let logical_path = file.logical_path.clone();
+
+        match (digest, operation) {
+            (None, ExternalOperation::Copy) => {
+                self.copy_file(&file.path, logical_path, inventory, changes)
+            }
+            (None, ExternalOperation::Move) => {
+                self.move_file(&file.path, logical_path, inventory, changes)
+            }
+            (Some(digest), ExternalOperation::Move) => {
+                info!(
+                    "Moving file {} into object at {}",
+                    file.path.to_string_lossy(),
+                    logical_path
+                );
+
+                changes.move_file(inventory, &file.path, digest, logical_path)
+            }
+            (Some(digest), ExternalOperation::Copy) => {
+                inventory.add_file_to_head(digest, logical_path)
+            }
+        }
+    }
+
+    /// Merges the modification times into the object's staged timestamps file. The inventory
+    /// is not staged.
+    fn stage_original_timestamps(
+        &self,
+        inventory: &mut Inventory,
+        timestamps: BTreeMap<String, String>,
+        changes: &StagedChanges,
+    ) -> Result<()> {
+        let timestamps_path: LogicalPath = ORIGINAL_TIMESTAMPS_FILE.try_into()?;
+
+        let mut existing = self.read_staged_metadata(inventory, &timestamps_path)?;
+        existing.extend(timestamps);
+
+        let mut bytes = serde_json::to_vec_pretty(&existing)?;
+        bytes.push(b'\n');
+
+        inventory
+            .head_version()
+            .validate_non_conflicting(&timestamps_path)?;
+
+        info!(
+            "Staging original timestamps file {} in object {}",
+            timestamps_path, inventory.id
+        );
+
+        changes.copy_file(inventory, bytes.as_slice(), timestamps_path)
+    }
+
+    fn copy_file(
+        &self,
+        file: impl AsRef<Path>,
+        logical_path: LogicalPath,
+        inventory: &mut Inventory,
+        changes: &StagedChanges,
+    ) -> Result<()> {
+        info!(
+            "Copying file {} into object at {}",
+            file.as_ref().to_string_lossy(),
+            logical_path
+        );
+
+        // It should be impossible for the inventory update to fail because the destination
+        // paths were already validated for conflicts. It is possible the file move could fail
+        // if the source files conflict, but this will not corrupt anything.
+        changes.copy_file(inventory, File::open(&file)?, logical_path)
+    }
+
+    /// Stages the files and metadata defined in the template in the object's staged version
+    fn stage_template(
+        &self,
+        object_id: &str,
+        template: &ObjectTemplate,
+        metadata_path: &LogicalPath,
+    ) -> Result<()> {
+        let _lock = self.get_lock_manager()?.acquire(object_id)?;
+
+        let mut inventory = self.get_staged_inventory(object_id)?;
+        let changes = StagedChanges::new(self, "create", &inventory)?;
+
+        for file in &template.files {
+            let logical_path: LogicalPath = file.path.as_str().try_into()?;
+            inventory
+                .head_version()
+                .validate_non_conflicting(&logical_path)?;
+
+            if let Some(source) = &file.source {
+                self.copy_file(source, logical_path, &mut inventory, &changes)
+                    .map_err(|e| {
+                        RocflError::General(format!(
+                            "Failed to copy template file {}: {}",
+                            source.to_string_lossy(),
+                            e
+                        ))
+                    })?;
+            } else if let Some(content) = &file.content {
+                info!("Writing template file into object at {}", logical_path);
+                changes.copy_file(&mut inventory, content.as_bytes(), logical_path)?;
+            }
+        }
+
+        if !template.metadata.is_empty() {
+            let mut bytes = serde_json::to_vec_pretty(&template.metadata).map_err(|e| {
+                RocflError::General(format!("Failed to serialize object metadata: {}", e))
+            })?;
+            bytes.push(b'\n');
+
+            inventory
+                .head_version()
+                .validate_non_conflicting(metadata_path)?;
+
+            info!(
+                "Staging metadata file {} in object {}",
+                metadata_path, object_id
+            );
+
+            changes.copy_file(&mut inventory, bytes.as_slice(), metadata_path.clone())?;
+        }
+
+        inventory.head_version_mut().created = Local::now();
+        changes.finish(&inventory)
+    }
+
+    fn move_file(
+        &self,
+        file: impl AsRef<Path>,
+        logical_path: LogicalPath,
+        inventory: &mut Inventory,
+        changes: &StagedChanges,
+    ) -> Result<()> {
+        if file.as_ref().symlink_metadata()?.file_type().is_symlink() {
+            // Moving the link would install a link in the object, so the file it points to is
+            // copied instead, and only the link is removed
+            self.copy_file(&file, logical_path, inventory, changes)?;
+            return changes.remove_source(file.as_ref());
+        }
+
+        info!(
+            "Moving file {} into object at {}",
+            file.as_ref().to_string_lossy(),
+            logical_path
+        );
+
+        let digest = inventory
+            .digest_algorithm
+            .hash_hex(&mut File::open(file.as_ref())?)?;
+
+        // It should be impossible for the inventory update to fail because the destination
+        // paths were already validated for conflicts. It is possible the file move could fail
+        // if the source files conflict, but this will not corrupt anything.
+        changes.move_file(inventory, file.as_ref(), digest, logical_path)
+    }
+
+    /// Returns a map of source logical paths to destination logical paths that represent a source
+    /// logical path being copied or moved from to the destination.
+    #[allow(clippy::type_complexity)]
+    fn resolve_internal_moves(
+        &self,
+        inventory: &Inventory,
+        src_version_num: VersionNum,
+        src: &[impl AsRef<str>],
+        dst: &str,
+        recursive: bool,
+    ) -> Result<(HashMap<Rc<LogicalPath>, LogicalPath>, Vec<String>)> {
+        let mut to_move = HashMap::new();
+        let mut errors = Vec::new();
 
-                        let file = file?;
-                        if file.file_type().is_file() {
-                            let mut attempt = || -> Result<()> {
-                                let logical_path = if dst_dir_exists || src_is_many {
-                                    let grandparent = path.parent().unwrap_or(path);
-                                    logical_path_in_dst_dir(file.path(), grandparent, dst)?
-                                } else {
-                                    logical_path_in_dst_dir(file.path(), path, dst)?
-                                };
+        let dst_path = dst.try_into()?;
+        let dst_dir_exists = inventory.head_version().is_dir(&dst_path);
+        let src_is_many = src.len() > 1;
+        let dst_has_slash = dst.ends_with('/');
+
+        let version = inventory.get_version(src_version_num)?;
+
+        for path in src {
+            let mut has_matches = false;
+
+            let files = match version.resolve_glob(path.as_ref(), false) {
+                Ok(files) => files,
+                Err(e) => {
+                    errors.push(format!("Failed to resolve path {}: {}", path.as_ref(), e));
+                    continue;
+                }
+            };
+            let many_files = files.len() > 1;
+
+            if recursive {
+                let dirs = match version.resolve_glob_to_dirs(path.as_ref()) {
+                    Ok(dirs) => dirs,
+                    Err(e) => {
+                        errors.push(format!("Failed to resolve path {}: {}", path.as_ref(), e));
+                        HashSet::new()
+                    }
+                };
+                let many_dirs = dirs.len() > 1;
+
+                for dir in dirs {
+                    let children = version.paths_with_prefix(dir.as_ref());
+                    let many_children = children.len() > 1;
 
-                                inventory
-                                    .head_version()
-                                    .validate_non_conflicting(&logical_path)?;
-                                operator(file.path(), logical_path, &mut inventory)
+                    for file in children {
+                        let mut attempt = || -> Result<()> {
+                            let logical_path = if dst_dir_exists
+                                || src_is_many
+                                || many_children
+                                || many_dirs
+                                || !files.is_empty()
+                            {
+                                logical_path_in_dst_dir_internal(&file, &dir.parent(), dst)?
+                            } else {
+                                logical_path_in_dst_dir_internal(&file, dir, dst)?
                             };
 
-                            if let Err(e) = attempt() {
-                                errors.push(format!(
-                                    "Failed to copy/move {}: {}",
-                                    file.path().to_string_lossy(),
-                                    e
-                                ));
-                            }
+                            has_matches = true;
+                            to_move.insert(file.clone(), logical_path);
+                            Ok(())
+                        };
+
+                        if let Err(e) = attempt() {
+                            errors.push(format!("Failed to copy/move file {}: {}", file, e));
                         }
                     }
-                } else {
-                    errors.push(format!(
-                        "Skipping directory {} because recursion is not enabled",
-                        path.to_string_lossy()
-                    ));
                 }
+            }
+
+            for file in files {
+                let mut attempt = || -> Result<()> {
+                    let logical_path = if dst_dir_exists
+                        || src_is_many
+                        || dst_has_slash
+                        || many_files
+                        || !to_move.is_empty()
+                    {
+                        dst_path.resolve(&file.filename().try_into()?)
+                    } else {
+                        dst_path.clone()
+                    };
+
+                    has_matches = true;
+                    to_move.insert(file.clone(), logical_path);
+                    Ok(())
+                };
+
+                if let Err(e) = attempt() {
+                    errors.push(format!("Failed to copy/move file {}: {}", file, e));
+                }
+            }
+
+            if !has_matches {
+                errors.push(format!(
+                    "Object {} version {} does not contain any files at {}",
+                    inventory.id,
+                    src_version_num,
+                    path.as_ref()
+                ));
+            }
+        }
+
+        Ok((to_move, errors))
+    }
+
+    /// Serializes the staged inventory to staging, and then moves the content that was added to
+    /// the staged version into the bucket, if content is staged in the bucket
+    fn stage_inventory(&self, inventory: &Inventory) -> Result<()> {
+        self.get_staging()?
+            .stage_inventory(inventory, false, false)?;
+        self.offload_staged_content(inventory)
+    }
+
+    /// Uploads all of the content files in the staged version's local content directory to the
+    /// bucket, and deletes the local copies. Files that are not referenced in the manifest, or
+    /// that are too large to be copied server-side, are left on disk.
+    fn offload_staged_content(&self, inventory: &Inventory) -> Result<()> {
+        let bucket = match self.store.bucket_staging() {
+            Some(bucket) => bucket,
+            None => return Ok(()),
+        };
+
+        let object_root = PathBuf::from(&inventory.storage_path);
+        let content_dir = paths::head_content_path(&object_root, inventory);
+
+        if !content_dir.exists() {
+            return Ok(());
+        }
+
+        let mut files = Vec::new();
+
+        for file in WalkDir::new(&content_dir) {
+            let file = file?;
+            if file.file_type().is_file() {
+                files.push(file.into_path());
+            }
+        }
+
+        for file in files {
+            let relative = pathdiff::diff_paths(&file, &object_root)
+                .unwrap()
+                .to_string_lossy()
+                .to_string();
+            let content_path =
+                ContentPath::try_from(util::convert_backslash_to_forward(&relative).as_ref())?;
+
+            if inventory.contains_content_path(&content_path)
+                && bucket.put_staged_file(inventory, &content_path, &file)?
+            {
+                util::remove_file_ignore_not_found(&file)?;
+                util::clean_dirs_up(file.parent().unwrap())?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Downloads the content files of the staged version that are staged in the bucket into the
+    /// staged version's local content directory. The paths of the downloaded files are returned.
+    fn download_bucket_staged_content(&self, inventory: &Inventory) -> Result<Vec<PathBuf>> {
+        let bucket = match self.store.bucket_staging() {
+            Some(bucket) => bucket,
+            None => return Ok(Vec::new()),
+        };
+
+        let object_root = PathBuf::from(&inventory.storage_path);
+        let mut downloaded = Vec::new();
+
+        for content_path in bucket.list_staged_files(inventory)? {
+            let file = object_root.join(util::native_path(content_path.as_str()));
+
+            if inventory.contains_content_path(&content_path) && !file.exists() {
+                fs::create_dir_all(file.parent().unwrap())?;
+                downloaded.push(file.clone());
+                bucket.get_staged_file(inventory, &content_path, &mut File::create(&file)?)?;
+            }
+        }
+
+        Ok(downloaded)
+    }
+
+    /// Returns the bucket's staging area if the staged content file is staged in the bucket
+    /// rather than on local disk
+    fn bucket_staged_file(
+        &self,
+        inventory: &Inventory,
+        content_path: &ContentPath,
+    ) -> Option<&dyn BucketStaging> {
+        self.store.bucket_staging().filter(|_| {
+            !Path::new(&inventory.storage_path)
+                .join(util::native_path(content_path.as_str()))
+                .exists()
+        })
+    }
+
+    /// Deletes staged content files from local staging, and from the bucket if content is
+    /// staged in the bucket
+    fn rm_staged_files(
+        &self,
+        staging: &FsOcflStore,
+        inventory: &Inventory,
+        paths: &[&ContentPath],
+    ) -> Result<()> {
+        staging.rm_staged_files(inventory, paths)?;
+        if let Some(bucket) = self.store.bucket_staging() {
+            bucket.rm_staged_files(inventory, paths)?;
+        }
+        Ok(())
+    }
+
+    /// Drops the object's staged version, including any of its content that is staged in the
+    /// bucket
+    fn purge_staged_object(&self, staging: &FsOcflStore, object_id: &str) -> Result<()> {
+        if let Some(bucket) = self.store.bucket_staging() {
+            match staging.get_inventory(object_id) {
+                Ok(inventory) => bucket.purge_staged_object(&inventory.object_root)?,
+                Err(RocflError::NotFound(_)) => (),
+                Err(e) => return Err(e),
+            }
+        }
+        staging.purge_object(object_id)
+    }
+
+    fn get_staging(&self) -> Result<&FsOcflStore> {
+        // This is deferred so that the extension directories are only created if needed
+        self.staging.get_or_try_init(|| {
+            let mut staging = FsOcflStore::init_if_needed(
+                &self.staging_root,
+                StorageLayout::new(LayoutExtensionName::HashedNTupleLayout, None)?,
+            )?;
+            if let Some(pool) = self.pool {
+                staging.set_thread_pool(pool);
+            }
+            if let Some(key) = &self.encryption_key {
+                staging.set_encryption_key(key.clone());
+            }
+            Ok(staging)
+        })
+    }
+
+    fn get_lock_manager(&self) -> Result<&LockManager> {
+        // Staging must exist first
+        self.get_staging()?;
+        // This is deferred so that the extension directories are only created if needed
+        self.staging_lock_manager
+            .get_or_try_init(|| -> Result<LockManager> {
+                let dir = paths::locks_extension_path(&self.staging_root);
+                fs::create_dir_all(&dir)?;
+                Ok(LockManager::new(dir))
+            })
+    }
+
+    /// Returns the object's inventory. If the object is not found, and the ID is an alias in the
+    /// repository's alias index, then the inventory of the object the alias identifies is returned.
+    fn get_inventory(&self, object_id: &str) -> Result<Inventory> {
+        self.with_alias_fallback(object_id, |id| self.store.get_inventory(id))
+    }
+
+    /// Executes the operation using the object ID. If the operation fails because the object was
+    /// not found, and the ID is an alias in the repository's alias index, then the operation is
+    /// retried using the ID of the object the alias identifies.
+    fn with_alias_fallback<T>(
+        &self,
+        object_id: &str,
+        operation: impl Fn(&str) -> Result<T>,
+    ) -> Result<T> {
+        match operation(object_id) {
+            Err(RocflError::NotFound(message)) => match self.store.resolve_alias(object_id)? {
+                Some(resolved) => {
+                    info!("Resolved alias {} to object {}", object_id, resolved);
+                    operation(&resolved)
+                }
+                None => Err(RocflError::NotFound(message)),
+            },
+            result => result,
+        }
+    }
+
+    /// Returns true if the object described by the inventory matches the filter. Its HEAD
+    /// version's creation date is checked before the size of its content files is read.
+    fn inventory_matches(&self, inventory: &Inventory, filter: &ListFilter) -> Result<bool> {
+        if !filter.matches_created(&inventory.head_version().created) {
+            return Ok(false);
+        }
+
+        if filter.filters_frozen() && self.store.get_freeze_marker(&inventory.id)?.is_none() {
+            return Ok(false);
+        }
+
+        if filter.filters_size() {
+            let marker = self.encryption_marker(&inventory.id)?;
+            let mut size = 0;
+            for (content_path, _) in inventory.manifest().iter() {
+                size += self.content_file_size(inventory, content_path, marker.as_ref())?;
+            }
+            return Ok(filter.matches_size(size));
+        }
+
+        Ok(true)
+    }
+
+    /// Returns the size of the content of each of the digests in the version's state
+    fn content_sizes(
+        &self,
+        inventory: &Inventory,
+        version_num: VersionRef,
+    ) -> Result<HashMap<Rc<HexDigest>, u64>> {
+        let version = inventory.get_version(version_num.resolve(inventory.head))?;
+        let marker = self.encryption_marker(&inventory.id)?;
+        let mut sizes = HashMap::new();
+
+        for (_, digest) in version.state_iter() {
+            if !sizes.contains_key(digest) {
+                let content_path = inventory.content_path_for_digest(digest, version_num, None)?;
+                let size = self.content_file_size(inventory, content_path, marker.as_ref())?;
+                sizes.insert(digest.clone(), size);
+            }
+        }
+
+        Ok(sizes)
+    }
+
+    fn ensure_open(&self) -> Result<()> {
+        if !self.is_closed() {
+            Ok(())
+        } else if self.is_timed_out() {
+            let timeout = self.deadline.lock().unwrap().as_ref().map(|d| d.timeout);
+            Err(RocflError::TimedOut(timeout.unwrap_or_default()))
+        } else {
+            Err(RocflError::Closed)
+        }
+    }
+
+    /// Returns an error if the repo is closed, or if it is read-only. `operation` describes the
+    /// operation that was attempted, and is used in the error message.
+    fn ensure_writable(&self, operation: &str) -> Result<()> {
+        self.ensure_open()?;
+        if self.read_only {
+            return Err(RocflError::ReadOnly(operation.to_string()));
+        }
+        if self.is_dry_run() {
+            return Err(RocflError::IllegalOperation(format!(
+                "Cannot {} in dry-run mode",
+                operation
+            )));
+        }
+        Ok(())
+    }
+
+    /// The same as `ensure_writable()`, except that operations that support dry-run mode are
+    /// allowed on read-only repositories when dry-run mode is enabled, because they do not
+    /// modify anything.
+    fn ensure_plannable(&self, operation: &str) -> Result<()> {
+        if self.is_dry_run() {
+            self.ensure_open()
+        } else {
+            self.ensure_writable(operation)
+        }
+    }
+
+    fn is_dry_run(&self) -> bool {
+        self.dry_run.load(Ordering::Acquire)
+    }
+
+    fn record_plan(&self, plan: ChangePlan) {
+        self.change_plans.lock().unwrap().push(plan);
+    }
+
+    /// Acquires the lock on the object's staged version. No lock is acquired in dry-run mode,
+    /// because the staged version is not modified.
+    fn lock_staged_object(&self, object_id: &str) -> Result<Option<ObjectLock>> {
+        if self.is_dry_run() {
+            return Ok(None);
+        }
+        self.get_lock_manager()?.acquire(object_id).map(Some)
+    }
+
+    /// Returns an error if the object is frozen, unless frozen objects have been overridden.
+    /// Objects that do not exist yet are not frozen.
+    fn ensure_not_frozen(&self, operation: &str, object_id: &str) -> Result<()> {
+        if self.override_frozen.load(Ordering::Acquire) {
+            return Ok(());
+        }
+
+        match self.store.get_freeze_marker(object_id) {
+            Ok(Some(_)) => Err(RocflError::Frozen(
+                operation.to_string(),
+                object_id.to_string(),
+            )),
+            Ok(None) | Err(RocflError::NotFound(_)) => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Returns an error if committing the object's staged version would exceed one of the
+    /// repository's quotas, unless quotas have been overridden. Staged content that duplicates
+    /// content that was committed in an earlier version is not counted as staged bytes, because
+    /// it is deduplicated when committed.
+    fn ensure_within_quotas(&self, object_id: &str, staging: &FsOcflStore) -> Result<()> {
+        let quotas = self.quotas;
+
+        if quotas == CommitQuotas::default() || self.override_quotas.load(Ordering::Acquire) {
+            return Ok(());
+        }
+
+        let inventory = match staging.get_inventory(object_id) {
+            Ok(inventory) => inventory,
+            // The commit fails later because there is nothing to commit
+            Err(RocflError::NotFound(_)) => return Ok(()),
+            Err(e) => return Err(e),
+        };
+
+        let check = |quota: &str, limit: Option<u64>, actual: u64| match limit {
+            Some(limit) if actual > limit => Err(RocflError::QuotaExceeded {
+                object_id: object_id.to_string(),
+                quota: quota.to_string(),
+                limit,
+                actual,
+            }),
+            _ => Ok(()),
+        };
 
-                Ok(())
-            };
+        let file_count = inventory.head_version().state_iter().count() as u64;
+        check("max_version_files", quotas.max_version_files, file_count)?;
 
-            if let Err(e) = attempt() {
-                errors.push(format!(
-                    "Failed to copy/move {}: {}",
-                    path.to_string_lossy(),
-                    e
-                ));
+        if quotas.max_staged_bytes.is_none() && quotas.max_object_size.is_none() {
+            return Ok(());
+        }
+
+        let version_prefix = format!("{}/", inventory.head);
+        let mut staged_bytes = 0;
+        let mut committed = Vec::new();
+
+        for (_, paths) in inventory.manifest().iter_id_paths() {
+            match paths.iter().find(|path| !path.starts_with(&version_prefix)) {
+                Some(path) => committed.push(path.clone()),
+                None => {
+                    if let Some(path) = paths.iter().next() {
+                        staged_bytes += match self.bucket_staged_file(&inventory, path) {
+                            Some(bucket) => bucket.staged_file_size(&inventory, path)?,
+                            None => staging.get_content_file_size(&inventory, path)?,
+                        };
+                    }
+                }
             }
         }
 
-        inventory.head_version_mut().created = Local::now();
-        self.get_staging()?
-            .stage_inventory(&inventory, false, false)?;
+        check("max_staged_bytes", quotas.max_staged_bytes, staged_bytes)?;
 
-        if !errors.is_empty() {
-            return Err(RocflError::CopyMoveError(MultiError(errors)));
+        if quotas.max_object_size.is_some() {
+            let mut object_size = staged_bytes;
+
+            if !committed.is_empty() {
+                let existing = self.store.get_inventory(object_id)?;
+                let marker = self.encryption_marker(object_id)?;
+                for path in committed {
+                    object_size += self.content_file_size(&existing, &path, marker.as_ref())?;
+                }
+            }
+
+            check("max_object_size", quotas.max_object_size, object_size)?;
         }
 
         Ok(())
     }
 
-    fn copy_file(
-        &self,
-        file: impl AsRef<Path>,
-        logical_path: LogicalPath,
-        inventory: &mut Inventory,
-    ) -> Result<()> {
-        let mut reader = inventory.digest_algorithm.reader(File::open(&file)?);
+    /// Presents every file whose content is new in the staged HEAD version to each of the
+    /// configured callouts. Content that is staged in the bucket is downloaded to a temporary
+    /// file in the staging root for the duration of the inspection.
+    fn run_commit_callouts(&self, inventory: &Inventory) -> Result<()> {
+        if self.callouts.is_empty() {
+            return Ok(());
+        }
 
-        info!(
-            "Copying file {} into object at {}",
-            file.as_ref().to_string_lossy(),
-            logical_path
-        );
+        let version_prefix = format!("{}/", inventory.head);
+        let head = inventory.head_version();
 
-        // It should be impossible for the inventory update to fail because the destination
-        // paths were already validated for conflicts. It is possible the file move could fail
-        // if the source files conflict, but this will not corrupt anything.
-        self.get_staging()?
-            .stage_file_copy(inventory, &mut reader, &logical_path)?;
-        let digest = reader.finalize_hex();
-        inventory.add_file_to_head(digest, logical_path)
-    }
+        for (digest, paths) in inventory.manifest().iter_id_paths() {
+            if !paths.iter().all(|path| path.starts_with(&version_prefix)) {
+                continue;
+            }
+            let content_path = match paths.iter().min() {
+                Some(path) => path,
+                None => continue,
+            };
 
-    fn move_file(
-        &self,
-        file: impl AsRef<Path>,
-        logical_path: LogicalPath,
-        inventory: &mut Inventory,
-    ) -> Result<()> {
-        info!(
-            "Moving file {} into object at {}",
-            file.as_ref().to_string_lossy(),
-            logical_path
-        );
+            self.ensure_open()?;
 
-        let digest = inventory
-            .digest_algorithm
-            .hash_hex(&mut File::open(file.as_ref())?)?;
+            let mut logical_paths = head
+                .state_iter()
+                .filter(|(_, state_digest)| *state_digest == digest)
+                .map(|(path, _)| path.as_ref().clone())
+                .collect::<Vec<LogicalPath>>();
+            logical_paths.sort_unstable();
 
-        // It should be impossible for the inventory update to fail because the destination
-        // paths were already validated for conflicts. It is possible the file move could fail
-        // if the source files conflict, but this will not corrupt anything.
-        self.get_staging()?
-            .stage_file_move(inventory, &file, &logical_path)?;
-        inventory.add_file_to_head(digest, logical_path)
-    }
+            if logical_paths.is_empty() {
+                // Orphaned content is removed before it is committed
+                continue;
+            }
 
-    /// Returns a map of source logical paths to destination logical paths that represent a source
-    /// logical path being copied or moved from to the destination.
-    #[allow(clippy::type_complexity)]
-    fn resolve_internal_moves(
-        &self,
-        inventory: &Inventory,
-        src_version_num: VersionNum,
-        src: &[impl AsRef<str>],
-        dst: &str,
-        recursive: bool,
-    ) -> Result<(HashMap<Rc<LogicalPath>, LogicalPath>, Vec<String>)> {
-        let mut to_move = HashMap::new();
-        let mut errors = Vec::new();
+            let downloaded = match self.bucket_staged_file(inventory, content_path) {
+                Some(bucket) => {
+                    let path = self
+                        .staging_root
+                        .join(format!(".callout-{}", digest.as_ref()));
+                    let mut file = File::create(&path)
+                        .map_err(|e| RocflError::io_path("create callout file", &path, e))?;
+                    let result = bucket.get_staged_file(inventory, content_path, &mut file);
+                    drop(file);
+                    if let Err(e) = result {
+                        let _ = fs::remove_file(&path);
+                        return Err(e);
+                    }
+                    Some(path)
+                }
+                None => None,
+            };
 
-        let dst_path = dst.try_into()?;
-        let dst_dir_exists = inventory.head_version().is_dir(&dst_path);
-        let src_is_many = src.len() > 1;
-        let dst_has_slash = dst.ends_with('/');
+            let file = CalloutFile {
+                object_id: inventory.id.clone(),
+                logical_paths,
+                path: downloaded.clone().unwrap_or_else(|| {
+                    Path::new(&inventory.storage_path)
+                        .join(util::native_path(content_path.as_str()))
+                }),
+                digest: digest.as_ref().clone(),
+                digest_algorithm: inventory.digest_algorithm,
+            };
 
-        let version = inventory.get_version(src_version_num)?;
+            let verdicts = self
+                .callouts
+                .iter()
+                .map(|callout| callout.inspect(&file))
+                .find(|verdict| !matches!(verdict, Ok(CalloutVerdict::Accept)));
 
-        for path in src {
-            let mut has_matches = false;
+            if let Some(path) = downloaded {
+                if let Err(e) = fs::remove_file(&path) {
+                    warn!("Failed to remove callout file {}: {}", path.display(), e);
+                }
+            }
 
-            let files = match version.resolve_glob(path.as_ref(), false) {
-                Ok(files) => files,
-                Err(e) => {
-                    errors.push(format!("Failed to resolve path {}: {}", path.as_ref(), e));
-                    continue;
+            match verdicts {
+                Some(Ok(CalloutVerdict::Reject(reason))) => {
+                    return Err(RocflError::CommitRejected {
+                        object_id: inventory.id.clone(),
+                        path: file.logical_paths[0].to_string(),
+                        reason,
+                    });
                 }
-            };
-            let many_files = files.len() > 1;
+                Some(Err(e)) => return Err(e),
+                _ => (),
+            }
+        }
 
-            if recursive {
-                let dirs = match version.resolve_glob_to_dirs(path.as_ref()) {
-                    Ok(dirs) => dirs,
-                    Err(e) => {
-                        errors.push(format!("Failed to resolve path {}: {}", path.as_ref(), e));
-                        HashSet::new()
-                    }
-                };
-                let many_dirs = dirs.len() > 1;
+        Ok(())
+    }
 
-                for dir in dirs {
-                    let children = version.paths_with_prefix(dir.as_ref());
-                    let many_children = children.len() > 1;
+    fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::Acquire)
+    }
 
-                    for file in children {
-                        let mut attempt = || -> Result<()> {
-                            let logical_path = if dst_dir_exists
-                                || src_is_many
-                                || many_children
-                                || many_dirs
-                                || !files.is_empty()
-                            {
-                                logical_path_in_dst_dir_internal(&file, &dir.parent(), dst)?
-                            } else {
-                                logical_path_in_dst_dir_internal(&file, dir, dst)?
-                            };
+    fn is_open(&self) -> bool {
+        !self.is_closed()
+    }
+}
 
-                            has_matches = true;
-                            to_move.insert(file.clone(), logical_path);
-                            Ok(())
-                        };
+/// How files from outside the repository are added to an object
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum ExternalOperation {
+    Copy,
+    Move,
+}
 
-                        if let Err(e) = attempt() {
-                            errors.push(format!("Failed to copy/move file {}: {}", file, e));
-                        }
-                    }
-                }
-            }
+/// A file from outside the repository that is copied or moved into an object
+struct ExternalFile {
+    path: PathBuf,
+    logical_path: LogicalPath,
+    /// The content path the file is staged at
+    content_path: ContentPath,
+    /// The file's modification time, when timestamps are preserved. It must be read before the
+    /// file is moved.
+    modified: Option<String>,
+    /// Indicates that the file's logical path conflicts with, or duplicates, the path of a file
+    /// earlier in the batch, and so the file must be operated on after the earlier file
+    deferred: bool,
+}
 
-            for file in files {
-                let mut attempt = || -> Result<()> {
-                    let logical_path = if dst_dir_exists
-                        || src_is_many
-                        || dst_has_slash
-                        || many_files
-                        || !to_move.is_empty()
-                    {
-                        dst_path.resolve(&file.filename().try_into()?)
-                    } else {
-                        dst_path.clone()
-                    };
+/// The external files that are resolved from the sources of a copy or move
+#[derive(Default)]
+struct ExternalBatch {
+    files: Vec<ExternalFile>,
+    paths: HashSet<LogicalPath>,
+    dirs: HashSet<LogicalPath>,
+}
 
-                    has_matches = true;
-                    to_move.insert(file.clone(), logical_path);
-                    Ok(())
-                };
+impl ExternalBatch {
+    /// Adds a file to the batch after validating that it does not conflict with the files that
+    /// are already in the inventory
+    fn add(
+        &mut self,
+        inventory: &Inventory,
+        path: &Path,
+        logical_path: LogicalPath,
+        preserve_timestamps: bool,
+    ) -> Result<()> {
+        inventory
+            .head_version()
+            .validate_non_conflicting(&logical_path)?;
+
+        let mut deferred = self.paths.contains(&logical_path) || self.dirs.contains(&logical_path);
+
+        let mut parent = logical_path.parent();
+        while !parent.is_empty() {
+            deferred |= self.paths.contains(&parent);
+            let next = parent.parent();
+            self.dirs.insert(parent);
+            parent = next;
+        }
 
-                if let Err(e) = attempt() {
-                    errors.push(format!("Failed to copy/move file {}: {}", file, e));
-                }
+        self.paths.insert(logical_path.clone());
+        self.files.push(ExternalFile {
+            path: path.to_path_buf(),
+            content_path: inventory.new_content_path(&logical_path),
+            logical_path,
+            modified: preserve_timestamps.then(|| modified_time(path)).flatten(),
+            deferred,
+        });
+
+        Ok(())
+    }
+}
+
+/// The changes an operation makes to an object's staged version. The operations that support
+/// dry-run mode only modify staging through it. In dry-run mode it does not have access to
+/// staging, and, when it is finished, the changes are recorded in a `ChangePlan` rather than
+/// staged.
+struct StagedChanges<'a> {
+    repo: &'a OcflRepo,
+    operation: String,
+    /// The staging store, or `None` in dry-run mode
+    staging: Option<&'a FsOcflStore>,
+    /// The state of the staged version before it was changed
+    before: Version,
+    /// The content paths of the files that were staged before the staged version was changed
+    before_content: BTreeSet<String>,
+}
+
+impl<'a> StagedChanges<'a> {
+    fn new(repo: &'a OcflRepo, operation: &str, inventory: &Inventory) -> Result<Self> {
+        let staging = if repo.is_dry_run() {
+            None
+        } else {
+            Some(repo.get_staging()?)
+        };
+
+        Ok(Self {
+            repo,
+            operation: operation.to_string(),
+            staging,
+            before: inventory.head_version().clone(),
+            before_content: staged_content_paths(inventory),
+        })
+    }
+
+    /// Returns the staging store, or `None` in dry-run mode, for work that is done in parallel
+    fn staging(&self) -> Option<&'a FsOcflStore> {
+        self.staging
+    }
+
+    /// Stages the content that is read from `source` at the logical path
+    fn copy_file(
+        &self,
+        inventory: &mut Inventory,
+        source: impl Read,
+        logical_path: LogicalPath,
+    ) -> Result<()> {
+        let mut reader = inventory.digest_algorithm.reader(source);
+        match self.staging {
+            Some(staging) => staging.stage_file_copy(inventory, &mut reader, &logical_path)?,
+            None => {
+                io::copy(&mut reader, &mut io::sink())?;
             }
+        }
+        inventory.add_file_to_head(reader.finalize_hex(), logical_path)
+    }
 
-            if !has_matches {
-                errors.push(format!(
-                    "Object {} version {} does not contain any files at {}",
-                    inventory.id,
-                    src_version_num,
-                    path.as_ref()
-                ));
+    /// Stages the content that `write` writes at the logical path, replacing the file that is
+    /// there, if any. Nothing is changed if `write` fails.
+    fn write_file(
+        &self,
+        inventory: &mut Inventory,
+        logical_path: LogicalPath,
+        write: impl FnOnce(&mut dyn Write) -> Result<()>,
+    ) -> Result<()> {
+        let algorithm = inventory.digest_algorithm;
+
+        let digest = match self.staging {
+            Some(staging) => {
+                let object_root = inventory.storage_path.clone();
+                let content_path = inventory.new_content_path(&logical_path);
+
+                staging.stage_content_write(&object_root, &content_path, |file| {
+                    let mut writer = algorithm.writer(file);
+                    write(&mut writer)?;
+                    // The replaced file's content is deleted before the new content is renamed
+                    // into place, in case they have the same content path
+                    self.remove_path(inventory, &logical_path)?;
+                    Ok(writer.finalize_hex())
+                })?
             }
+            None => {
+                let mut writer = algorithm.writer(io::sink());
+                write(&mut writer)?;
+                self.remove_path(inventory, &logical_path)?;
+                writer.finalize_hex()
+            }
+        };
+
+        inventory.add_file_to_head(digest, logical_path)
+    }
+
+    /// Moves a file from outside the repository, whose content has the specified digest, into
+    /// staging at the logical path
+    fn move_file(
+        &self,
+        inventory: &mut Inventory,
+        file: &Path,
+        digest: HexDigest,
+        logical_path: LogicalPath,
+    ) -> Result<()> {
+        if let Some(staging) = self.staging {
+            staging.stage_file_move(inventory, &file, &logical_path)?;
         }
+        inventory.add_file_to_head(digest, logical_path)
+    }
 
-        Ok((to_move, errors))
+    /// Deletes a file outside of the repository after its content was staged
+    fn remove_source(&self, file: &Path) -> Result<()> {
+        if self.staging.is_some() {
+            fs::remove_file(file)?;
+        }
+        Ok(())
     }
 
-    fn get_staging(&self) -> Result<&FsOcflStore> {
-        // This is deferred so that the extension directories are only created if needed
-        self.staging.get_or_try_init(|| {
-            FsOcflStore::init_if_needed(
-                &self.staging_root,
-                StorageLayout::new(LayoutExtensionName::HashedNTupleLayout, None)?,
-            )
-        })
+    /// Deletes the empty directories that are left behind in the source directories of a move
+    fn clean_source_dirs(&self, src: &[impl AsRef<Path>]) -> Result<()> {
+        if self.staging.is_some() {
+            for path in src {
+                let path = path.as_ref();
+                if path.exists() && path.is_dir() {
+                    util::clean_dirs_down(path)?;
+                }
+            }
+        }
+        Ok(())
     }
 
-    fn get_lock_manager(&self) -> Result<&LockManager> {
-        // Staging must exist first
-        self.get_staging()?;
-        // This is deferred so that the extension directories are only created if needed
-        self.staging_lock_manager
-            .get_or_try_init(|| -> Result<LockManager> {
-                let dir = paths::locks_extension_path(&self.staging_root);
-                fs::create_dir_all(&dir)?;
-                Ok(LockManager::new(dir))
-            })
+    /// Copies a file that was added in the staged version to the logical path
+    fn copy_staged_file(
+        &self,
+        inventory: &Inventory,
+        content_path: &ContentPath,
+        logical_path: &LogicalPath,
+    ) -> Result<()> {
+        if let Some(staging) = self.staging {
+            match self.repo.bucket_staged_file(inventory, content_path) {
+                Some(bucket) => bucket.copy_staged_file(
+                    inventory,
+                    content_path,
+                    &inventory.new_content_path(logical_path),
+                )?,
+                None => staging.copy_staged_file(inventory, content_path, logical_path)?,
+            }
+        }
+        Ok(())
     }
 
-    fn ensure_open(&self) -> Result<()> {
-        if self.is_closed() {
-            Err(RocflError::Closed)
-        } else {
-            Ok(())
+    /// Moves a file that was added in the staged version to the logical path
+    fn move_staged_file(
+        &self,
+        inventory: &Inventory,
+        content_path: &ContentPath,
+        logical_path: &LogicalPath,
+    ) -> Result<()> {
+        if let Some(staging) = self.staging {
+            match self.repo.bucket_staged_file(inventory, content_path) {
+                Some(bucket) => {
+                    bucket.copy_staged_file(
+                        inventory,
+                        content_path,
+                        &inventory.new_content_path(logical_path),
+                    )?;
+                    bucket.rm_staged_files(inventory, &[content_path])?;
+                }
+                None => staging.move_staged_file(inventory, content_path, logical_path)?,
+            }
         }
+        Ok(())
     }
 
-    fn is_closed(&self) -> bool {
-        self.closed.load(Ordering::Acquire)
+    /// Removes a logical path from the staged version, if it exists, and deletes its staged
+    /// content, if it was added in the staged version
+    fn remove_path(&self, inventory: &mut Inventory, logical_path: &LogicalPath) -> Result<()> {
+        if let Some(content_path) = inventory.remove_logical_path_from_head(logical_path) {
+            if let Some(staging) = self.staging {
+                self.repo
+                    .rm_staged_files(staging, inventory, &[&content_path])?;
+            }
+        }
+        Ok(())
     }
 
-    fn is_open(&self) -> bool {
-        !self.is_closed()
+    /// Stages the inventory with `stage_inventory()`. In dry-run mode, the logical paths that
+    /// were changed, and the content files that would be staged and unstaged, are recorded
+    /// instead.
+    fn finish(self, inventory: &Inventory) -> Result<()> {
+        if self.staging.is_some() {
+            return self.repo.stage_inventory(inventory);
+        }
+
+        let mut plan = ChangePlan::new(&self.operation, Some(&inventory.id));
+        plan.changes = planned_path_changes(inventory.head_version().diff(Some(&self.before)));
+
+        let after_content = staged_content_paths(inventory);
+        plan.changes.extend(
+            after_content
+                .difference(&self.before_content)
+                .map(|path| PlannedChange::StageFile(path.clone())),
+        );
+        plan.changes.extend(
+            self.before_content
+                .difference(&after_content)
+                .map(|path| PlannedChange::UnstageFile(path.clone())),
+        );
+
+        self.repo.record_plan(plan);
+        Ok(())
     }
 }
 
 /// An iterator that adapts the output of a delegate `Inventory` iterator into another type.
+/// Adapter failures are reported as `ObjectError`s that identify the object that failed.
 struct InventoryAdapterIter<'a, T> {
-    iter: Box<dyn Iterator<Item = Result<Inventory>> + 'a>,
+    iter: Box<dyn Iterator<Item = Result<Inventory, ObjectError>> + 'a>,
     adapter: Box<dyn Fn(Inventory) -> Result<T>>,
 }
 
@@ -1413,7 +6379,7 @@ impl<'a, T> InventoryAdapterIter<'a, T> {
     /// Creates a new `InventoryAdapterIter` that applies the `adapter` closure to the output
     /// of every `next()` call.
     fn new(
-        iter: Box<dyn Iterator<Item = Result<Inventory>> + 'a>,
+        iter: Box<dyn Iterator<Item = Result<Inventory, ObjectError>> + 'a>,
         adapter: impl Fn(Inventory) -> Result<T> + 'a + 'static,
     ) -> Self {
         Self {
@@ -1424,20 +6390,303 @@ impl<'a, T> InventoryAdapterIter<'a, T> {
 }
 
 impl<'a, T> Iterator for InventoryAdapterIter<'a, T> {
-    type Item = Result<T>;
+    type Item = Result<T, ObjectError>;
 
     fn next(&mut self) -> Option<Self::Item> {
         match self.iter.next() {
             None => None,
-            Some(Ok(inventory)) => match self.adapter.deref()(inventory) {
-                Ok(adapted) => Some(Ok(adapted)),
-                Err(e) => Some(Err(e)),
-            },
+            Some(Ok(inventory)) => {
+                let object_id = inventory.id.clone();
+                let path = inventory.storage_path.clone();
+                match self.adapter.deref()(inventory) {
+                    Ok(adapted) => Some(Ok(adapted)),
+                    Err(e) => Some(Err(ObjectError::new(Some(object_id), Some(path), e))),
+                }
+            }
             Some(Err(e)) => Some(Err(e)),
         }
     }
 }
 
+/// Forwards the content that is written to it to a search that is running on another thread, and
+/// passes the lines that the search matched to `on_line` as they are received. Writes fail once
+/// `on_line` returns false, or once the search ended without reading all of the content, such as
+/// because the content appears to be binary.
+struct SearchWriter<'a, F: FnMut(u64, String) -> bool> {
+    chunks: Option<SyncSender<Vec<u8>>>,
+    lines: &'a Receiver<(u64, String)>,
+    on_line: F,
+    stopped: bool,
+}
+
+impl<'a, F: FnMut(u64, String) -> bool> SearchWriter<'a, F> {
+    fn new(chunks: SyncSender<Vec<u8>>, lines: &'a Receiver<(u64, String)>, on_line: F) -> Self {
+        Self {
+            chunks: Some(chunks),
+            lines,
+            on_line,
+            stopped: false,
+        }
+    }
+
+    /// Signals the end of the content, and passes the lines that the search matches in the rest of
+    /// it to `on_line`. Returns true if the search was stopped.
+    fn finish(mut self) -> bool {
+        self.chunks = None;
+        self.receive_lines(true);
+        self.stopped
+    }
+
+    /// Passes the lines that the search matched so far to `on_line`. If `wait` is true, then the
+    /// lines are received until the search ends.
+    fn receive_lines(&mut self, wait: bool) {
+        while !self.stopped {
+            let received = if wait {
+                self.lines.recv().ok()
+            } else {
+                self.lines.try_recv().ok()
+            };
+
+            match received {
+                Some((line_number, line)) => self.stopped = !(self.on_line)(line_number, line),
+                None => break,
+            }
+        }
+    }
+}
+
+impl<F: FnMut(u64, String) -> bool> Write for SearchWriter<'_, F> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.receive_lines(false);
+
+        if !self.stopped {
+            let sent = match &self.chunks {
+                Some(chunks) => chunks.send(buf.to_vec()).is_ok(),
+                None => false,
+            };
+            self.stopped = !sent;
+        }
+
+        if self.stopped {
+            return Err(io::Error::other("The search of the file was stopped"));
+        }
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Reads the chunks of content that are sent to it, until the sender is dropped
+struct ChunkReader {
+    chunks: Receiver<Vec<u8>>,
+    chunk: Vec<u8>,
+    position: usize,
+}
+
+impl ChunkReader {
+    fn new(chunks: Receiver<Vec<u8>>) -> Self {
+        Self {
+            chunks,
+            chunk: Vec::new(),
+            position: 0,
+        }
+    }
+}
+
+impl Read for ChunkReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.position == self.chunk.len() {
+            match self.chunks.recv() {
+                Ok(chunk) => {
+                    self.chunk = chunk;
+                    self.position = 0;
+                }
+                Err(_) => return Ok(0),
+            }
+        }
+
+        let read = buf.len().min(self.chunk.len() - self.position);
+        buf[..read].copy_from_slice(&self.chunk[self.position..self.position + read]);
+        self.position += read;
+
+        Ok(read)
+    }
+}
+
+/// Sends each line that a search matches as soon as it is found. The search stops once the lines
+/// are no longer received.
+struct MatchSink {
+    path: String,
+    lines: Sender<(u64, String)>,
+}
+
+impl Sink for MatchSink {
+    type Error = io::Error;
+
+    fn matched(&mut self, _searcher: &Searcher, mat: &SinkMatch<'_>) -> io::Result<bool> {
+        let line = String::from_utf8_lossy(mat.bytes())
+            .trim_end_matches(&['\r', '\n'][..])
+            .to_string();
+        let line_number = mat.line_number().unwrap_or_default();
+
+        Ok(self.lines.send((line_number, line)).is_ok())
+    }
+
+    fn binary_data(&mut self, _searcher: &Searcher, _offset: u64) -> io::Result<bool> {
+        info!(
+            "Stopping search of {} because it appears to be binary",
+            self.path
+        );
+        Ok(false)
+    }
+}
+
+/// Parses the contents of a metadata file, which must be a JSON object with string values
+fn parse_metadata(metadata_path: &LogicalPath, bytes: &[u8]) -> Result<BTreeMap<String, String>> {
+    serde_json::from_slice(bytes).map_err(|e| {
+        RocflError::InvalidValue(format!(
+            "Metadata file {} must be a JSON object with string values: {}",
+            metadata_path, e
+        ))
+    })
+}
+
+/// Creates a health problem for an object that could not be read
+fn unreadable_object_problem(error: ObjectError, staged: bool) -> HealthProblem {
+    let (check, remedy) = if staged {
+        (
+            HealthCheck::Staging,
+            "Remove the staged object's directory from the staging area",
+        )
+    } else {
+        (
+            HealthCheck::UnreadableObject,
+            "Run 'rocfl validate' on the object to identify the problem",
+        )
+    };
+
+    let mut problem = HealthProblem::new(check, format!("{:#}", error.cause), remedy);
+    if let Some(object_id) = error.object_id {
+        problem = problem.with_object_id(object_id);
+    }
+    if let Some(path) = error.path {
+        problem = problem.with_path(path);
+    }
+    problem
+}
+
+/// Returns the ID that content is staged in a repository's bucket under, for the local staging
+/// root. The ID is a digest of the host name and the staging root's absolute path, so it does not
+/// change when the staging root is deleted and recreated, and it is different for every other
+/// staging root that shares the bucket.
+fn bucket_staging_id(staging_root: &Path) -> String {
+    let staging_root =
+        std::path::absolute(staging_root).unwrap_or_else(|_| staging_root.to_path_buf());
+    let owner = format!(
+        "{}:{}",
+        lock::hostname().unwrap_or_default(),
+        staging_root.to_string_lossy()
+    );
+
+    let mut digest = DigestAlgorithm::Sha256
+        .hash_hex(&mut owner.as_bytes())
+        .map(|digest| digest.to_string())
+        .unwrap_or_default();
+    digest.truncate(16);
+    digest
+}
+
+/// Renames an orphaned lock file or staged object directory so that no other process can begin
+/// using it, and returns the new path. `None` is returned, and the orphan is left in place, if it
+/// no longer exists or another process began using it after it was found. A lock file is in use
+/// if it has been modified, and a staged object is in use if it has been modified or now has an
+/// inventory.
+fn move_orphan_aside(orphan: &Orphan) -> Result<Option<PathBuf>> {
+    let is_unchanged = |path: &Path| {
+        fs::metadata(path)
+            .and_then(|meta| meta.modified())
+            .is_ok_and(|modified| modified == orphan.modified)
+    };
+    let has_inventory =
+        |path: &Path| orphan.kind == OrphanKind::StagingDir && paths::inventory_path(path).exists();
+
+    if !is_unchanged(&orphan.path) || has_inventory(&orphan.path) {
+        return Ok(None);
+    }
+
+    let mut aside = orphan.path.as_os_str().to_owned();
+    aside.push(format!(".removing-{}", std::process::id()));
+    let aside = PathBuf::from(aside);
+
+    match fs::rename(&orphan.path, &aside) {
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        result => result?,
+    }
+
+    // Renaming a directory may update its modification time, but anything that is staged in it
+    // after it was found must have been staged before it was renamed
+    let in_use = match orphan.kind {
+        OrphanKind::StaleLock => !is_unchanged(&aside),
+        OrphanKind::StagingDir => has_inventory(&aside),
+    };
+
+    if in_use {
+        let restored = match orphan.kind {
+            // A lock is linked back, rather than renamed, so that a lock another process
+            // acquired in the meantime is not replaced
+            OrphanKind::StaleLock => {
+                fs::hard_link(&aside, &orphan.path).and_then(|_| fs::remove_file(&aside))
+            }
+            OrphanKind::StagingDir => fs::rename(&aside, &orphan.path),
+        };
+        if let Err(e) = restored {
+            warn!(
+                "Failed to restore {} to {}: {}",
+                aside.to_string_lossy(),
+                orphan.path.to_string_lossy(),
+                e
+            );
+        }
+        return Ok(None);
+    }
+
+    Ok(Some(aside))
+}
+
+/// Returns the RFC 3339 modification time of the file, or `None` if it cannot be read
+fn modified_time(path: &Path) -> Option<String> {
+    match fs::metadata(path).and_then(|meta| meta.modified()) {
+        Ok(modified) => Some(DateTime::<Local>::from(modified).to_rfc3339()),
+        Err(e) => {
+            warn!(
+                "Failed to read the modification time of {}: {}",
+                path.to_string_lossy(),
+                e
+            );
+            None
+        }
+    }
+}
+
+/// Parses the RFC 3339 timestamps in an original timestamps file, skipping any that are invalid
+fn parse_timestamps(timestamps: BTreeMap<String, String>) -> BTreeMap<String, DateTime<Local>> {
+    timestamps
+        .into_iter()
+        .filter_map(
+            |(path, timestamp)| match DateTime::parse_from_rfc3339(&timestamp) {
+                Ok(timestamp) => Some((path, timestamp.with_timezone(&Local))),
+                Err(e) => {
+                    warn!("Invalid original timestamp for {}: {}", path, e);
+                    None
+                }
+            },
+        )
+        .collect()
+}
+
 /// Creates a logical path that combines `dst` with the relativized `src` path.
 fn logical_path_in_dst_dir(
     src: impl AsRef<Path>,
@@ -1456,6 +6705,104 @@ fn logical_path_in_dst_dir(
     logical_path.try_into()
 }
 
+/// Describes how squashing an object through the specified version changes its versions, and
+/// which of its content files are deleted
+fn squash_report(original: &Inventory, squashed: &Inventory, through: VersionNum) -> SquashReport {
+    let versions = original
+        .versions
+        .keys()
+        .map(|version_num| {
+            let number = version_num.number.saturating_sub(through.number) + 1;
+            (
+                *version_num,
+                VersionNum {
+                    number,
+                    width: version_num.width,
+                },
+            )
+        })
+        .collect();
+
+    let mut removed_files: Vec<String> = original
+        .manifest()
+        .iter()
+        .filter(|(_, digest)| !squashed.manifest().contains_id(digest))
+        .map(|(content_path, _)| content_path.to_string())
+        .collect();
+    removed_files.sort_unstable();
+
+    SquashReport {
+        object_id: original.id.clone(),
+        versions,
+        removed_files,
+    }
+}
+
+/// Removes the encrypted copy of staged content, if it was not moved into the repository
+fn remove_encrypted_content(path: &Path) {
+    if path.exists() {
+        if let Err(e) = fs::remove_dir_all(path) {
+            warn!(
+                "Failed to remove encrypted content at {}: {}",
+                path.display(),
+                e
+            );
+        }
+    }
+}
+
+/// Writes content to the sink using `read`, decrypting it first if a key is provided
+fn read_content(
+    key: Option<&EncryptionKey>,
+    sink: &mut dyn Write,
+    read: impl FnOnce(&mut dyn Write) -> Result<()>,
+) -> Result<()> {
+    match key {
+        Some(key) => {
+            let mut writer = DecryptingWriter::new(key, sink);
+            read(&mut writer)?;
+            writer.finish()?;
+            Ok(())
+        }
+        None => read(sink),
+    }
+}
+
+/// Writes a file to the sink using `read`, hashing its content as it is written, and returns a
+/// `RocflError::CorruptObject` error if its digest does not match the expected digest
+fn verify_read(
+    inventory: &Inventory,
+    path: &LogicalPath,
+    expected: &HexDigest,
+    sink: &mut dyn Write,
+    read: impl FnOnce(&mut dyn Write) -> Result<()>,
+) -> Result<()> {
+    let mut writer = inventory.digest_algorithm.writer(sink);
+    read(&mut writer)?;
+    let actual = writer.finalize_hex();
+
+    if actual != *expected {
+        return Err(RocflError::CorruptObject {
+            object_id: inventory.id.clone(),
+            message: format!(
+                "File {} has {} digest {}, but its expected digest is {}",
+                path, inventory.digest_algorithm, actual, expected
+            ),
+        });
+    }
+
+    Ok(())
+}
+
+/// Converts the path of a tar entry into a `/` separated path, dropping any `.` or root parts
+fn archive_entry_path(path: &Path) -> String {
+    path.components()
+        .filter(|part| !matches!(part, Component::CurDir | Component::RootDir))
+        .map(|part| part.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
 /// Same as `logical_path_in_dst_dir()` but operates on `LogicalPath`s
 fn logical_path_in_dst_dir_internal(
     src: &LogicalPath,
@@ -1477,6 +6824,23 @@ fn logical_path_in_dst_dir_internal(
     logical_path.try_into()
 }
 
+/// Handles a symbolic link that is not followed. A warning is logged when links are skipped,
+/// otherwise the error to report is returned.
+fn reject_symlink(path: &Path, policy: SymlinkPolicy) -> Option<String> {
+    if policy == SymlinkPolicy::Error {
+        Some(format!(
+            "Failed to copy/move {}: Is a symbolic link",
+            path.to_string_lossy()
+        ))
+    } else {
+        warn!(
+            "Skipping {} because it is a symbolic link",
+            path.to_string_lossy()
+        );
+        None
+    }
+}
+
 /// Looks up the digest of the specified logical path in the specified version, and then
 /// attempts to resolve the digest to a content path within the staging directory. If it
 /// is able to, then the digest and content path are returned. If it is not, nothing is
@@ -1508,3 +6872,336 @@ fn lookup_staged_digest_and_content_path(
         ))),
     }
 }
+
+/// Compares the head version of an object with the state that a patch expects it to be in, and
+/// returns a description of each difference. Modified, deleted, and renamed files must exist
+/// with the digest they had when the patch was created, and added files must not already exist,
+/// unless the patch deletes or renames them first.
+fn patch_conflicts(inventory: &Inventory, patch: &ObjectPatch) -> Vec<String> {
+    let head = inventory.head_version();
+    let compare_digests = patch.digest_algorithm == inventory.digest_algorithm;
+
+    let mut vacated = HashSet::new();
+    for change in &patch.changes {
+        match change {
+            PatchChange::Delete { path, .. } => {
+                vacated.insert(path.as_str());
+            }
+            PatchChange::Rename { from, .. } => {
+                vacated.extend(from.iter().map(|path| path.as_str()));
+            }
+            _ => (),
+        }
+    }
+
+    let mut conflicts = Vec::new();
+
+    let expect_file = |path: &str, expected: Option<&String>, conflicts: &mut Vec<String>| {
+        let logical_path: LogicalPath = match path.try_into() {
+            Ok(logical_path) => logical_path,
+            Err(e) => return conflicts.push(format!("{}: {}", path, e)),
+        };
+
+        match (head.lookup_digest(&logical_path), expected) {
+            (None, _) => conflicts.push(format!("{} does not exist", path)),
+            (Some(actual), Some(expected))
+                if compare_digests && **actual != HexDigest::from(expected.as_str()) =>
+            {
+                conflicts.push(format!(
+                    "{} has digest {}, but the patch expects {}",
+                    path, actual, expected
+                ))
+            }
+            _ => (),
+        }
+    };
+
+    let expect_no_file = |path: &str, conflicts: &mut Vec<String>| {
+        if vacated.contains(path) {
+            return;
+        }
+        if let Ok(logical_path) = LogicalPath::try_from(path) {
+            if head.is_file(&logical_path) {
+                conflicts.push(format!("{} already exists", path));
+            }
+        }
+    };
+
+    for change in &patch.changes {
+        match change {
+            PatchChange::Modify(file) => {
+                expect_file(&file.path, file.previous_digest.as_ref(), &mut conflicts)
+            }
+            PatchChange::Delete { path, digest } => {
+                expect_file(path, digest.as_ref(), &mut conflicts)
+            }
+            PatchChange::Rename { from, digest, .. } => from
+                .iter()
+                .for_each(|path| expect_file(path, digest.as_ref(), &mut conflicts)),
+            PatchChange::Add(_) => (),
+        }
+    }
+
+    for change in &patch.changes {
+        match change {
+            PatchChange::Add(file) => expect_no_file(&file.path, &mut conflicts),
+            PatchChange::Rename { to, .. } => to
+                .iter()
+                .for_each(|path| expect_no_file(path, &mut conflicts)),
+            _ => (),
+        }
+    }
+
+    conflicts
+}
+
+/// Does the work for an external file that may be done in parallel with other files, which
+/// is hashing the file and, when it is copied, writing it into staging. Nothing is written when
+/// `staging` is `None`, in dry-run mode. The file's digest is returned, or `None` if all of the
+/// work must be done when the file is applied.
+fn prepare_external_file(
+    operation: ExternalOperation,
+    file: &ExternalFile,
+    digest_algorithm: DigestAlgorithm,
+    object_root: &str,
+    staging: Option<&FsOcflStore>,
+) -> Result<Option<HexDigest>> {
+    match operation {
+        ExternalOperation::Copy => {
+            info!(
+                "Copying file {} into object at {}",
+                file.path.to_string_lossy(),
+                file.logical_path
+            );
+
+            let mut reader = digest_algorithm.reader(File::open(&file.path)?);
+            match staging {
+                Some(staging) => {
+                    staging.stage_content_copy(object_root, &file.content_path, &mut reader)?
+                }
+                None => {
+                    io::copy(&mut reader, &mut io::sink())?;
+                }
+            }
+            Ok(Some(reader.finalize_hex()))
+        }
+        // Links are copied, rather than moved, so they are handled entirely by move_file()
+        ExternalOperation::Move if file.path.symlink_metadata()?.file_type().is_symlink() => {
+            Ok(None)
+        }
+        ExternalOperation::Move => Ok(Some(
+            digest_algorithm.hash_hex(&mut File::open(&file.path)?)?,
+        )),
+    }
+}
+
+/// Returns the content paths, relative the object root, of the files that were added to the
+/// inventory's head version, sorted
+fn staged_content_paths(inventory: &Inventory) -> BTreeSet<String> {
+    let prefix = format!("{}/", inventory.head);
+
+    inventory
+        .manifest()
+        .iter()
+        .map(|(path, _)| path.as_str())
+        .filter(|path| path.starts_with(&prefix))
+        .map(|path| path.to_string())
+        .collect()
+}
+
+/// Converts the differences between two versions into the path changes that produce them,
+/// sorted by path
+fn planned_path_changes(diffs: Vec<Diff>) -> Vec<PlannedChange> {
+    let mut changes = Vec::new();
+
+    for diff in diffs {
+        match diff {
+            Diff::Added(path) | Diff::Modified(path) => {
+                changes.push(PlannedChange::AddPath(path.to_string()))
+            }
+            Diff::Deleted(path) => changes.push(PlannedChange::RemovePath(path.to_string())),
+            Diff::Renamed { original, renamed } => {
+                changes.extend(
+                    original
+                        .iter()
+                        .map(|path| PlannedChange::RemovePath(path.to_string())),
+                );
+                changes.extend(
+                    renamed
+                        .iter()
+                        .map(|path| PlannedChange::AddPath(path.to_string())),
+                );
+            }
+        }
+    }
+
+    changes.sort_by(|a, b| match (a, b) {
+        (
+            PlannedChange::AddPath(a) | PlannedChange::RemovePath(a),
+            PlannedChange::AddPath(b) | PlannedChange::RemovePath(b),
+        ) => a.cmp(b),
+        _ => std::cmp::Ordering::Equal,
+    });
+
+    changes
+}
+
+/// Verifies that a staged version uses the same zero-padding as the object's existing versions.
+/// The existing padding is detected from the version that precedes the staged version.
+fn validate_version_padding(inventory: &Inventory) -> Result<()> {
+    if inventory.is_new() {
+        return Ok(());
+    }
+
+    if let Some((previous, _)) = inventory
+        .versions
+        .get_key_value(&inventory.head.previous()?)
+    {
+        if previous.width != inventory.head.width {
+            return Err(RocflError::IllegalState(format!(
+                "Cannot commit version {} of object {} because its zero-padding does not match the object's existing versions, such as {}",
+                inventory.head, inventory.id, previous
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Verifies that an inventory supplied to `adopt_version()` describes the next version of the
+/// object described by `existing`, and does not alter any of its prior versions
+fn validate_adopted_inventory(existing: &Inventory, inventory: &Inventory) -> Result<()> {
+    let invalid = |message: String| Err(RocflError::InvalidValue(message));
+
+    if inventory.id != existing.id {
+        return invalid(format!(
+            "The adopted inventory is for object {}, not object {}",
+            inventory.id, existing.id
+        ));
+    }
+    if inventory.type_declaration != existing.type_declaration {
+        return invalid(format!(
+            "The adopted inventory's type, {}, does not match the object's type, {}",
+            inventory.type_declaration, existing.type_declaration
+        ));
+    }
+    if inventory.digest_algorithm != existing.digest_algorithm {
+        return invalid(format!(
+            "The adopted inventory's digest algorithm, {}, does not match the object's algorithm, {}",
+            inventory.digest_algorithm, existing.digest_algorithm
+        ));
+    }
+    if inventory.defaulted_content_dir() != existing.defaulted_content_dir() {
+        return invalid(format!(
+            "The adopted inventory's content directory, {}, does not match the object's content directory, {}",
+            inventory.defaulted_content_dir(),
+            existing.defaulted_content_dir()
+        ));
+    }
+
+    let expected_head = existing.head.next()?;
+    if inventory.head != expected_head {
+        return invalid(format!(
+            "The adopted inventory's head must be {}. Found: {}",
+            expected_head, inventory.head
+        ));
+    }
+    if inventory.versions.len() != existing.versions.len() + 1 {
+        return invalid(format!(
+            "The adopted inventory must contain versions v1 through {}",
+            inventory.head
+        ));
+    }
+
+    for (version_num, version) in &existing.versions {
+        let matches = match inventory.versions.get(version_num) {
+            Some(adopted) => serde_json::to_value(adopted)? == serde_json::to_value(version)?,
+            None => false,
+        };
+        if !matches {
+            return invalid(format!(
+                "The adopted inventory's version {} does not match the object's version {}",
+                version_num, version_num
+            ));
+        }
+    }
+
+    for (path, digest) in existing.manifest().iter() {
+        if inventory.digest_for_content_path(path) != Some(digest) {
+            return invalid(format!(
+                "The adopted inventory's manifest does not contain {} with digest {}",
+                path, digest
+            ));
+        }
+    }
+
+    for (path, _) in inventory.manifest().iter() {
+        if path.version != ContentPathVersion::VersionNum(inventory.head)
+            && !existing.contains_content_path(path)
+        {
+            return invalid(format!(
+                "The adopted inventory's manifest contains {}, which is not in the object",
+                path
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Verifies that the content directory within `version_dir` contains exactly the files that the
+/// inventory's manifest adds in its HEAD version, and that each file matches its digest
+fn verify_adopted_content(inventory: &Inventory, version_dir: &Path) -> Result<()> {
+    let mut expected = HashSet::new();
+
+    for (path, digest) in inventory.manifest().iter() {
+        if path.version != ContentPathVersion::VersionNum(inventory.head) {
+            continue;
+        }
+
+        let relative = path
+            .as_str()
+            .strip_prefix(&format!("{}/", inventory.head))
+            .unwrap_or_else(|| path.as_str());
+        let file = version_dir.join(util::native_path(relative));
+
+        if !file.is_file() {
+            return Err(RocflError::NotFound(format!(
+                "Content file {}",
+                file.to_string_lossy()
+            )));
+        }
+
+        let actual = inventory
+            .digest_algorithm
+            .hash_hex(&mut File::open(&file)?)?;
+        if actual != **digest {
+            return Err(RocflError::CorruptObject {
+                object_id: inventory.id.clone(),
+                message: format!(
+                    "Expected {} to have digest {}. Found: {}",
+                    file.to_string_lossy(),
+                    digest,
+                    actual
+                ),
+            });
+        }
+
+        expected.insert(file);
+    }
+
+    let content_dir = version_dir.join(inventory.defaulted_content_dir());
+    if content_dir.exists() {
+        for entry in WalkDir::new(&content_dir) {
+            let entry = entry?;
+            if !entry.file_type().is_dir() && !expected.contains(entry.path()) {
+                return Err(RocflError::InvalidValue(format!(
+                    "Content file {} is not in the adopted inventory's manifest",
+                    entry.path().to_string_lossy()
+                )));
+            }
+        }
+    }
+
+    Ok(())
+}
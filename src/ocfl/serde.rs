@@ -2,11 +2,13 @@
 //! dedup all digests and logical paths, greatly reducing the memory used, and marginally increasing
 //! the deserialization speed.
 
+use std::borrow::Cow;
 use std::collections::hash_map::Entry;
 use std::collections::{BTreeMap, HashMap};
 use std::convert::TryFrom;
 use std::fmt::Formatter;
 use std::marker::PhantomData;
+use std::ops::Deref;
 use std::rc::Rc;
 
 use serde::de::{DeserializeSeed, Error as SerdeError, MapAccess, Visitor};
@@ -46,6 +48,59 @@ pub const NAME_FIELD: &str = "name";
 pub const ADDRESS_FIELD: &str = "address";
 pub const USER_FIELDS: &[&str] = &[NAME_FIELD, ADDRESS_FIELD];
 
+/// A string that is borrowed from the input when possible. Strings that contain JSON escape
+/// sequences cannot be borrowed, and are copied instead.
+#[derive(Debug)]
+pub(crate) struct CowStr<'a>(pub Cow<'a, str>);
+
+impl<'a> Deref for CowStr<'a> {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<'de> Deserialize<'de> for CowStr<'de> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct CowStrVisitor;
+
+        impl<'de> Visitor<'de> for CowStrVisitor {
+            type Value = CowStr<'de>;
+
+            fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
+                formatter.write_str("a string")
+            }
+
+            fn visit_borrowed_str<E>(self, v: &'de str) -> Result<Self::Value, E>
+            where
+                E: SerdeError,
+            {
+                Ok(CowStr(Cow::Borrowed(v)))
+            }
+
+            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+            where
+                E: SerdeError,
+            {
+                Ok(CowStr(Cow::Owned(v.to_string())))
+            }
+
+            fn visit_string<E>(self, v: String) -> Result<Self::Value, E>
+            where
+                E: SerdeError,
+            {
+                Ok(CowStr(Cow::Owned(v)))
+            }
+        }
+
+        deserializer.deserialize_str(CowStrVisitor)
+    }
+}
+
 impl<'de> Deserialize<'de> for Inventory {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -446,12 +501,12 @@ impl<'de: 'b, 'a, 'b> DeserializeSeed<'de> for StateSeed<'a, 'b> {
             {
                 let mut state = PathBiMap::with_capacity(map.size_hint().unwrap_or(0));
 
-                while let Some((digest, paths)) = map.next_entry::<&str, Vec<&str>>()? {
+                while let Some((digest, paths)) = map.next_entry::<&str, Vec<CowStr>>()? {
                     let digest_ref = self.data.insert_digest(digest);
                     let mut path_refs = Vec::with_capacity(paths.len());
 
                     for path in paths {
-                        path_refs.push(self.data.insert_path(path)?);
+                        path_refs.push(self.data.insert_path(path.0)?);
                     }
 
                     state.insert_multiple_rc(digest_ref, path_refs);
@@ -468,7 +523,7 @@ impl<'de: 'b, 'a, 'b> DeserializeSeed<'de> for StateSeed<'a, 'b> {
 #[derive(Debug)]
 struct DigestsAndPaths<'a> {
     digests: HashMap<&'a str, Rc<HexDigest>>,
-    paths: HashMap<&'a str, Rc<LogicalPath>>,
+    paths: HashMap<Cow<'a, str>, Rc<LogicalPath>>,
 }
 
 impl<'a> DigestsAndPaths<'a> {
@@ -486,15 +541,15 @@ impl<'a> DigestsAndPaths<'a> {
             .clone()
     }
 
-    fn insert_path<E>(&mut self, path: &'a str) -> Result<Rc<LogicalPath>, E>
+    fn insert_path<E>(&mut self, path: Cow<'a, str>) -> Result<Rc<LogicalPath>, E>
     where
         E: SerdeError,
     {
         match self.paths.entry(path) {
             Entry::Occupied(entry) => Ok(entry.get().clone()),
             Entry::Vacant(vacant) => {
-                let path =
-                    LogicalPath::try_from(path).map_err(|e| SerdeError::custom(e.to_string()))?;
+                let path = LogicalPath::try_from(vacant.key().as_ref())
+                    .map_err(|e| SerdeError::custom(e.to_string()))?;
                 let path_rc = Rc::new(path);
                 let clone = path_rc.clone();
                 vacant.insert(path_rc);
@@ -509,7 +564,7 @@ mod tests {
     use std::convert::{TryFrom, TryInto};
     use std::rc::Rc;
 
-    use chrono::DateTime;
+    use chrono::{DateTime, Local};
     use serde_json::json;
 
     use crate::ocfl::inventory::Inventory;
@@ -655,6 +710,43 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn created_round_trips_with_numeric_offset() -> Result<(), RocflError> {
+        let created = "2022-06-05T00:24:44.620008510+00:00";
+        let json = json!({
+            "id": "test",
+            "type": "https://ocfl.io/1.0/spec/#inventory",
+            "digestAlgorithm": "sha512",
+            "head": "v1",
+            "manifest": {},
+            "versions": {
+                "v1": {
+                    "created": created,
+                    "state": {}
+                }
+            }
+        })
+        .to_string();
+
+        let inv: Inventory = serde_json::from_str(&json)?;
+        let serialized: serde_json::Value = serde_json::to_value(&inv)?;
+        let serialized_created = serialized["versions"]["v1"]["created"].as_str().unwrap();
+
+        let expected = DateTime::parse_from_rfc3339(created)
+            .unwrap()
+            .with_timezone(&Local);
+        assert_eq!(expected.to_rfc3339(), serialized_created);
+        assert!(!serialized_created.ends_with('Z'));
+
+        let reread: Inventory = serde_json::from_str(&serialized.to_string())?;
+        assert_eq!(
+            inv.versions[&VersionNum::v1()].created,
+            reread.versions[&VersionNum::v1()].created
+        );
+
+        Ok(())
+    }
+
     #[test]
     fn all_fields_mapped_minimal() -> Result<(), RocflError> {
         let json = json!({
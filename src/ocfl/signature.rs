@@ -0,0 +1,289 @@
+//! Detached ed25519 signatures of object inventories. Digests only detect accidental corruption,
+//! because anyone who is able to modify an object is also able to recompute its digests. A
+//! signature can only be recomputed by someone who holds the private key.
+
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::path::Path;
+
+use chrono::{DateTime, Local};
+use ed25519_dalek::pkcs8::{DecodePrivateKey, DecodePublicKey};
+use ed25519_dalek::{Signature, Signer, Verifier};
+use serde::{Deserialize, Serialize};
+
+use crate::ocfl::error::{Result, RocflError};
+use crate::ocfl::VersionNum;
+
+/// The only signature algorithm that is currently supported
+const ED25519: &str = "ed25519";
+
+/// An ed25519 private key that is used to sign inventories when they are committed
+#[derive(Clone)]
+pub struct SigningKey {
+    key: ed25519_dalek::SigningKey,
+}
+
+/// An ed25519 public key that is used to verify inventory signatures
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct VerifyingKey {
+    key: ed25519_dalek::VerifyingKey,
+}
+
+/// A detached signature of a version's inventory. Signatures are appended to a file in the
+/// object's `logs` directory when the version is committed.
+#[derive(Deserialize, Serialize, Debug, Eq, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct InventorySignature {
+    /// The version whose inventory was signed
+    pub version: VersionNum,
+    /// The signature algorithm, which is always `ed25519`
+    pub algorithm: String,
+    /// The base64 encoded public key of the key that created the signature
+    pub public_key: String,
+    /// The base64 encoded signature of the bytes of the version's inventory
+    pub signature: String,
+    /// When the signature was created
+    pub created: DateTime<Local>,
+}
+
+/// The outcome of verifying the signature of a version's inventory
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub enum SignatureStatus {
+    /// The inventory was signed by one of the trusted keys
+    Valid { public_key: String },
+    /// The inventory was signed, but not by any of the trusted keys
+    Untrusted { public_key: String },
+    /// The inventory does not match its signature, or the signature could not be checked
+    Invalid(String),
+    /// The inventory was not signed
+    Unsigned,
+}
+
+/// The signature status of a single version of an object
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct VersionSignature {
+    pub version: VersionNum,
+    pub status: SignatureStatus,
+}
+
+/// The signature statuses of every version of an object, as returned by
+/// `OcflRepo::verify_signatures()`
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct SignatureReport {
+    pub object_id: String,
+    pub versions: Vec<VersionSignature>,
+}
+
+impl SigningKey {
+    /// Parses a PKCS#8 PEM encoded ed25519 private key, such as one created with
+    /// `openssl genpkey -algorithm ed25519`
+    pub fn from_pem(pem: &str) -> Result<Self> {
+        Ok(Self {
+            key: ed25519_dalek::SigningKey::from_pkcs8_pem(pem).map_err(|e| {
+                RocflError::InvalidValue(format!("Invalid ed25519 private key: {}", e))
+            })?,
+        })
+    }
+
+    /// Reads a PKCS#8 PEM encoded ed25519 private key from a file
+    pub fn from_pem_file(path: impl AsRef<Path>) -> Result<Self> {
+        Self::from_pem(&read_key_file(path.as_ref())?)
+    }
+
+    /// Returns the public key that corresponds to this private key
+    pub fn verifying_key(&self) -> VerifyingKey {
+        VerifyingKey {
+            key: self.key.verifying_key(),
+        }
+    }
+
+    /// Signs the bytes of a version's inventory
+    pub(crate) fn sign(&self, version: VersionNum, inventory: &[u8]) -> InventorySignature {
+        InventorySignature {
+            version,
+            algorithm: ED25519.to_string(),
+            public_key: self.verifying_key().to_base64(),
+            signature: base64::encode(self.key.sign(inventory).to_bytes()),
+            created: Local::now(),
+        }
+    }
+}
+
+impl VerifyingKey {
+    /// Parses a PEM encoded ed25519 public key, such as one created with
+    /// `openssl pkey -pubout`
+    pub fn from_pem(pem: &str) -> Result<Self> {
+        Ok(Self {
+            key: ed25519_dalek::VerifyingKey::from_public_key_pem(pem).map_err(|e| {
+                RocflError::InvalidValue(format!("Invalid ed25519 public key: {}", e))
+            })?,
+        })
+    }
+
+    /// Reads a PEM encoded ed25519 public key from a file
+    pub fn from_pem_file(path: impl AsRef<Path>) -> Result<Self> {
+        Self::from_pem(&read_key_file(path.as_ref())?)
+    }
+
+    /// Parses a base64 encoded ed25519 public key, as it is recorded in `InventorySignature`
+    pub fn from_base64(encoded: &str) -> Result<Self> {
+        let invalid =
+            || RocflError::InvalidValue(format!("Invalid ed25519 public key: {}", encoded));
+
+        let bytes: [u8; 32] = base64::decode(encoded)
+            .map_err(|_| invalid())?
+            .try_into()
+            .map_err(|_| invalid())?;
+
+        Ok(Self {
+            key: ed25519_dalek::VerifyingKey::from_bytes(&bytes).map_err(|_| invalid())?,
+        })
+    }
+
+    /// Returns the base64 encoding of the key's bytes
+    pub fn to_base64(&self) -> String {
+        base64::encode(self.key.as_bytes())
+    }
+}
+
+impl Display for VerifyingKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_base64())
+    }
+}
+
+impl SignatureStatus {
+    /// True if the status indicates that the inventory may have been tampered with, or that it
+    /// was signed by an unknown key
+    pub fn is_failure(&self) -> bool {
+        matches!(
+            self,
+            SignatureStatus::Untrusted { .. } | SignatureStatus::Invalid(_)
+        )
+    }
+}
+
+impl SignatureReport {
+    /// True if the inventory of every version was signed by a trusted key
+    pub fn is_verified(&self) -> bool {
+        self.versions
+            .iter()
+            .all(|version| matches!(version.status, SignatureStatus::Valid { .. }))
+    }
+
+    /// True if any version's signature is invalid or was not made by a trusted key
+    pub fn has_failures(&self) -> bool {
+        self.versions
+            .iter()
+            .any(|version| version.status.is_failure())
+    }
+}
+
+impl InventorySignature {
+    /// Verifies the signature against the inventory bytes. The signature's public key is returned
+    /// if the signature matches.
+    fn verify(&self, inventory: &[u8]) -> std::result::Result<VerifyingKey, String> {
+        if self.algorithm != ED25519 {
+            return Err(format!(
+                "Unsupported signature algorithm: {}",
+                self.algorithm
+            ));
+        }
+
+        let key = VerifyingKey::from_base64(&self.public_key).map_err(|e| e.to_string())?;
+        let signature = base64::decode(&self.signature)
+            .ok()
+            .and_then(|bytes| Signature::from_slice(&bytes).ok())
+            .ok_or_else(|| "The signature is malformed".to_string())?;
+
+        key.key
+            .verify(inventory, &signature)
+            .map_err(|_| "The inventory does not match its signature".to_string())?;
+
+        Ok(key)
+    }
+}
+
+/// Determines the status of a version's inventory, given every signature that was recorded for
+/// the version. The inventory is valid if any of the signatures was made by a trusted key.
+pub(crate) fn verify_inventory(
+    inventory: &[u8],
+    signatures: &[&InventorySignature],
+    trusted_keys: &[VerifyingKey],
+) -> SignatureStatus {
+    let mut status = SignatureStatus::Unsigned;
+
+    for signature in signatures {
+        match signature.verify(inventory) {
+            Ok(key) if trusted_keys.contains(&key) => {
+                return SignatureStatus::Valid {
+                    public_key: key.to_base64(),
+                }
+            }
+            Ok(key) => {
+                status = SignatureStatus::Untrusted {
+                    public_key: key.to_base64(),
+                }
+            }
+            Err(reason) => {
+                if !matches!(status, SignatureStatus::Untrusted { .. }) {
+                    status = SignatureStatus::Invalid(reason)
+                }
+            }
+        }
+    }
+
+    status
+}
+
+fn read_key_file(path: &Path) -> Result<String> {
+    fs::read_to_string(path).map_err(|e| {
+        RocflError::InvalidValue(format!(
+            "Failed to read key file {}: {}",
+            path.to_string_lossy(),
+            e
+        ))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{verify_inventory, SignatureStatus, SigningKey, VerifyingKey};
+    use crate::ocfl::VersionNum;
+
+    const SIGNING_KEY: &str = include_str!("../../resources/test/keys/signing-key.pem");
+    const VERIFYING_KEY: &str = include_str!("../../resources/test/keys/signing-key.pub.pem");
+    const OTHER_KEY: &str = include_str!("../../resources/test/keys/other-key.pub.pem");
+
+    #[test]
+    fn verify_signed_inventory() {
+        let key = SigningKey::from_pem(SIGNING_KEY).unwrap();
+        let trusted = VerifyingKey::from_pem(VERIFYING_KEY).unwrap();
+        let other = VerifyingKey::from_pem(OTHER_KEY).unwrap();
+
+        assert_eq!(trusted, key.verifying_key());
+
+        let signature = key.sign(VersionNum::v1(), b"inventory");
+
+        assert_eq!(
+            SignatureStatus::Valid {
+                public_key: trusted.to_base64()
+            },
+            verify_inventory(b"inventory", &[&signature], &[other.clone(), trusted])
+        );
+        assert_eq!(
+            SignatureStatus::Untrusted {
+                public_key: key.verifying_key().to_base64()
+            },
+            verify_inventory(b"inventory", &[&signature], std::slice::from_ref(&other))
+        );
+        assert_eq!(
+            SignatureStatus::Invalid("The inventory does not match its signature".to_string()),
+            verify_inventory(b"tampered", &[&signature], &[other])
+        );
+        assert_eq!(
+            SignatureStatus::Unsigned,
+            verify_inventory(b"inventory", &[], &[])
+        );
+    }
+}
@@ -1,5 +1,11 @@
+use crate::ocfl::{LayoutExtensionName, SpecVersion};
+
 pub const OCFL_1_0_SPEC: &str = include_str!("../../resources/main/specs/ocfl_1.0.txt");
 pub const OCFL_1_1_SPEC: &str = include_str!("../../resources/main/specs/ocfl_1.1.md");
+/// Tab separated table of each OCFL 1.1 validation code, the spec section it is defined in, and
+/// the spec text that defines it. The text is extracted from the bundled OCFL 1.1 spec.
+pub const OCFL_1_1_VALIDATION_CODES: &str =
+    include_str!("../../resources/main/specs/ocfl_1.1_validation_codes.tsv");
 pub const EXT_0002_SPEC: &str =
     include_str!("../../resources/main/specs/0002-flat-direct-storage-layout.md");
 pub const EXT_0003_SPEC: &str =
@@ -10,3 +16,22 @@ pub const EXT_0006_SPEC: &str =
     include_str!("../../resources/main/specs/0006-flat-omit-prefix-storage-layout.md");
 pub const EXT_0007_SPEC: &str =
     include_str!("../../resources/main/specs/0007-n-tuple-omit-prefix-storage-layout.md");
+
+/// Returns the text of the specified OCFL spec
+pub fn ocfl_spec(version: SpecVersion) -> &'static str {
+    match version {
+        SpecVersion::Ocfl1_0 => OCFL_1_0_SPEC,
+        SpecVersion::Ocfl1_1 => OCFL_1_1_SPEC,
+    }
+}
+
+/// Returns the text of the specified storage layout extension spec
+pub fn layout_extension_spec(extension: LayoutExtensionName) -> &'static str {
+    match extension {
+        LayoutExtensionName::FlatDirectLayout => EXT_0002_SPEC,
+        LayoutExtensionName::HashedNTupleObjectIdLayout => EXT_0003_SPEC,
+        LayoutExtensionName::HashedNTupleLayout => EXT_0004_SPEC,
+        LayoutExtensionName::FlatOmitPrefixLayout => EXT_0006_SPEC,
+        LayoutExtensionName::NTupleOmitPrefixLayout => EXT_0007_SPEC,
+    }
+}
@@ -0,0 +1,538 @@
+//! Access to repositories in cloud storage services other than S3, such as Azure Blob Storage and
+//! Google Cloud Storage. The services are accessed through the `object_store` crate, and the
+//! repository is managed by `S3OcflStore` in the same way as it is in S3.
+
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::path::Path;
+use std::sync::Arc;
+
+use bytes::Bytes;
+use futures::StreamExt;
+use log::{error, info, warn};
+use object_store::path::Path as ObjectPath;
+use object_store::{
+    Attribute, Attributes, ObjectStore, PutMultipartOpts, PutOptions, PutPayload, TagSet,
+    WriteMultipart,
+};
+use tokio::runtime::{self, Runtime};
+
+use super::s3::{BlobClient, ListResult};
+use crate::ocfl::digest::HexDigest;
+use crate::ocfl::error::{Result, RocflError};
+use crate::ocfl::paths::join;
+use crate::ocfl::{CloudStorage, DigestAlgorithm, S3RequestMetrics, S3WriteOptions};
+
+/// Files larger than this are uploaded in multiple parts
+const PART_SIZE: u64 = 1024 * 1024 * 5;
+
+/// A `BlobClient` for the bucket, or container, of a cloud storage service that is supported by
+/// the `object_store` crate
+pub(super) struct ObjectStoreClient {
+    store: Arc<dyn ObjectStore>,
+    /// The name of the service, used to identify it in logs and errors
+    service: &'static str,
+    bucket: String,
+    prefix: String,
+    runtime: Runtime,
+}
+
+impl ObjectStoreClient {
+    pub(super) fn new(storage: &CloudStorage, bucket: &str, prefix: Option<&str>) -> Result<Self> {
+        let (service, store) = build_store(storage, bucket)?;
+        Self::with_store(store, service, bucket, prefix)
+    }
+
+    fn with_store(
+        store: Arc<dyn ObjectStore>,
+        service: &'static str,
+        bucket: &str,
+        prefix: Option<&str>,
+    ) -> Result<Self> {
+        Ok(Self {
+            store,
+            service,
+            bucket: bucket.to_owned(),
+            prefix: prefix.unwrap_or_default().to_owned(),
+            runtime: runtime::Builder::new_multi_thread().enable_all().build()?,
+        })
+    }
+
+    /// Converts a path relative the repository prefix into a key within the bucket. Keys are
+    /// used as-is, and are not percent-encoded.
+    fn location(&self, path: &str) -> Result<ObjectPath> {
+        let key = join(&self.prefix, path);
+        ObjectPath::parse(&key)
+            .map_err(|e| RocflError::InvalidValue(format!("Invalid key {}: {}", key, e)))
+    }
+
+    /// Strips the repository prefix from a key that was listed
+    fn relative<'a>(&self, key: &'a str) -> &'a str {
+        if self.prefix.is_empty() {
+            key
+        } else {
+            key.get(self.prefix.len() + 1..).unwrap_or_default()
+        }
+    }
+
+    /// Wraps an error returned by the service so that it identifies the failed operation and key
+    fn cloud_error(
+        &self,
+        operation: &str,
+        key: &ObjectPath,
+        error: object_store::Error,
+    ) -> RocflError {
+        RocflError::Cloud {
+            service: self.service.to_string(),
+            operation: operation.to_string(),
+            bucket: self.bucket.clone(),
+            key: key.to_string(),
+            source: Box::new(error),
+        }
+    }
+
+    fn put_options(
+        content_type: Option<&str>,
+        write_options: &S3WriteOptions,
+    ) -> (TagSet, Attributes) {
+        if let Some(storage_class) = &write_options.storage_class {
+            warn!(
+                "Ignoring storage class {} because it only applies to S3",
+                storage_class
+            );
+        }
+
+        let mut tags = TagSet::default();
+        for (key, value) in &write_options.tags {
+            tags.push(key, value);
+        }
+
+        let mut attributes = Attributes::new();
+        if let Some(content_type) = content_type {
+            attributes.insert(Attribute::ContentType, content_type.to_string().into());
+        }
+
+        (tags, attributes)
+    }
+
+    fn multipart_put_file(
+        &self,
+        location: &ObjectPath,
+        file_path: &Path,
+        content_type: Option<&str>,
+        digest_algorithm: Option<DigestAlgorithm>,
+        write_options: &S3WriteOptions,
+    ) -> Result<Option<HexDigest>> {
+        info!(
+            "Initiating {} multipart upload of {} to {}",
+            self.service,
+            file_path.to_string_lossy(),
+            location
+        );
+
+        let (tags, attributes) = Self::put_options(content_type, write_options);
+
+        let upload = self
+            .runtime
+            .block_on(
+                self.store
+                    .put_multipart_opts(location, PutMultipartOpts { tags, attributes }),
+            )
+            .map_err(|e| self.cloud_error("PutMultipart", location, e))?;
+
+        let mut writer = WriteMultipart::new_with_chunk_size(upload, PART_SIZE as usize);
+        let mut reader = File::open(file_path)?;
+        let mut buffer = vec![0; PART_SIZE as usize];
+        let mut digester = digest_algorithm.map(|algorithm| algorithm.writer(io::sink()));
+
+        let result = self.runtime.block_on(async {
+            loop {
+                let read = reader.read(&mut buffer)?;
+                if read == 0 {
+                    break;
+                }
+
+                let content = &buffer[..read];
+                if let Some(digester) = digester.as_mut() {
+                    digester.write_all(content)?;
+                }
+
+                writer
+                    .wait_for_capacity(1)
+                    .await
+                    .map_err(|e| self.cloud_error("PutPart", location, e))?;
+                writer.write(content);
+            }
+            Ok(())
+        });
+
+        if let Err(e) = result {
+            info!("Aborting multipart upload to {}", location);
+            if let Err(abort) = self.runtime.block_on(writer.abort()) {
+                error!(
+                    "Failed to abort multipart upload to {}: {}",
+                    location, abort
+                );
+            }
+            return Err(e);
+        }
+
+        self.runtime
+            .block_on(writer.finish())
+            .map_err(|e| self.cloud_error("CompleteMultipart", location, e))?;
+
+        Ok(digester.map(|digester| digester.finalize_hex()))
+    }
+}
+
+impl BlobClient for ObjectStoreClient {
+    fn bucket(&self) -> &str {
+        &self.bucket
+    }
+
+    fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    /// Request metrics are only recorded for S3
+    fn request_metrics(&self) -> Option<S3RequestMetrics> {
+        None
+    }
+
+    /// The whole listing is always returned in a single page
+    fn list_page(
+        &self,
+        path: &str,
+        delimiter: Option<&str>,
+        _continuation: Option<String>,
+    ) -> Result<(ListResult, Option<String>)> {
+        let location = self.location(path)?;
+        let prefix = if location.as_ref().is_empty() {
+            None
+        } else {
+            Some(&location)
+        };
+
+        info!("Listing {} prefix: {}", self.service, location);
+
+        let mut page = ListResult::default();
+
+        let objects = if delimiter.is_some() {
+            let result = self
+                .runtime
+                .block_on(self.store.list_with_delimiter(prefix))
+                .map_err(|e| self.cloud_error("List", &location, e))?;
+
+            for directory in result.common_prefixes {
+                page.directories
+                    .push(self.relative(directory.as_ref()).to_string());
+            }
+
+            result.objects
+        } else {
+            self.runtime
+                .block_on(async {
+                    let mut objects = Vec::new();
+                    let mut stream = self.store.list(prefix);
+                    while let Some(object) = stream.next().await {
+                        objects.push(object?);
+                    }
+                    Ok(objects)
+                })
+                .map_err(|e| self.cloud_error("List", &location, e))?
+        };
+
+        for object in objects {
+            page.objects
+                .push(self.relative(object.location.as_ref()).to_string());
+            page.object_details.push((
+                object.size as u64,
+                Some(
+                    object
+                        .last_modified
+                        .format("%Y-%m-%dT%H:%M:%S%.3fZ")
+                        .to_string(),
+                ),
+            ));
+        }
+
+        Ok((page, None))
+    }
+
+    fn get_object(&self, path: &str) -> Result<Option<Vec<u8>>> {
+        let location = self.location(path)?;
+
+        info!("Getting object from {}: {}", self.service, location);
+
+        let result = self.runtime.block_on(async {
+            match self.store.get(&location).await {
+                Ok(result) => result.bytes().await.map(Some),
+                Err(object_store::Error::NotFound { .. }) => Ok(None),
+                Err(e) => Err(e),
+            }
+        });
+
+        match result {
+            Ok(bytes) => Ok(bytes.map(|bytes| bytes.to_vec())),
+            Err(e) => Err(self.cloud_error("Get", &location, e)),
+        }
+    }
+
+    fn stream_object(&self, path: &str, sink: &mut dyn Write) -> Result<()> {
+        let location = self.location(path)?;
+
+        info!("Streaming object from {}: {}", self.service, location);
+
+        self.runtime.block_on(async {
+            let mut stream = self
+                .store
+                .get(&location)
+                .await
+                .map_err(|e| self.cloud_error("Get", &location, e))?
+                .into_stream();
+
+            while let Some(chunk) = stream.next().await {
+                let chunk = chunk.map_err(|e| self.cloud_error("Get", &location, e))?;
+                sink.write_all(&chunk)?;
+            }
+
+            Ok(())
+        })
+    }
+
+    fn object_size(&self, path: &str) -> Result<u64> {
+        let location = self.location(path)?;
+
+        info!("Getting object size from {}: {}", self.service, location);
+
+        let meta = self
+            .runtime
+            .block_on(self.store.head(&location))
+            .map_err(|e| self.cloud_error("Head", &location, e))?;
+
+        Ok(meta.size as u64)
+    }
+
+    fn object_e_tag(&self, path: &str) -> Result<Option<String>> {
+        let location = self.location(path)?;
+
+        info!("Getting object ETag from {}: {}", self.service, location);
+
+        let meta = self
+            .runtime
+            .block_on(self.store.head(&location))
+            .map_err(|e| self.cloud_error("Head", &location, e))?;
+
+        Ok(meta.e_tag.map(|e_tag| e_tag.trim_matches('"').to_string()))
+    }
+
+    fn delete_object(&self, path: &str) -> Result<()> {
+        let location = self.location(path)?;
+
+        info!("Deleting object in {}: {}", self.service, location);
+
+        self.runtime
+            .block_on(self.store.delete(&location))
+            .map_err(|e| self.cloud_error("Delete", &location, e))
+    }
+
+    /// Copies an object to a new key in the same bucket without downloading it. The storage
+    /// class and tags of the source object are not changed.
+    fn copy_object(
+        &self,
+        src_path: &str,
+        dst_path: &str,
+        _write_options: &S3WriteOptions,
+    ) -> Result<()> {
+        let src = self.location(src_path)?;
+        let dst = self.location(dst_path)?;
+
+        info!("Copying object in {} from {} to {}", self.service, src, dst);
+
+        self.runtime
+            .block_on(self.store.copy(&src, &dst))
+            .map_err(|e| self.cloud_error("Copy", &dst, e))
+    }
+
+    fn put_object_bytes(
+        &self,
+        path: &str,
+        content: Bytes,
+        content_type: Option<&str>,
+    ) -> Result<()> {
+        let location = self.location(path)?;
+
+        info!("Putting object in {}: {}", self.service, location);
+
+        let (tags, attributes) = Self::put_options(content_type, &S3WriteOptions::default());
+
+        self.runtime
+            .block_on(self.store.put_opts(
+                &location,
+                PutPayload::from(content),
+                PutOptions {
+                    tags,
+                    attributes,
+                    ..Default::default()
+                },
+            ))
+            .map_err(|e| self.cloud_error("Put", &location, e))?;
+
+        Ok(())
+    }
+
+    /// Uploads the file. Files larger than 5 MiB are uploaded in multiple parts. If a
+    /// `digest_algorithm` is specified, then the digest of the uploaded bytes is computed using
+    /// it and returned.
+    fn put_object_file(
+        &self,
+        path: &str,
+        file_path: &Path,
+        content_type: Option<&str>,
+        digest_algorithm: Option<DigestAlgorithm>,
+        write_options: &S3WriteOptions,
+    ) -> Result<Option<HexDigest>> {
+        let location = self.location(path)?;
+
+        if std::fs::metadata(file_path)?.len() > PART_SIZE {
+            return self.multipart_put_file(
+                &location,
+                file_path,
+                content_type,
+                digest_algorithm,
+                write_options,
+            );
+        }
+
+        info!(
+            "Putting {} in {} at {}",
+            file_path.display(),
+            self.service,
+            location
+        );
+
+        let content = std::fs::read(file_path)?;
+        let digest = match digest_algorithm {
+            Some(algorithm) => Some(algorithm.hash_hex(&mut content.as_slice())?),
+            None => None,
+        };
+
+        let (tags, attributes) = Self::put_options(content_type, write_options);
+
+        self.runtime
+            .block_on(self.store.put_opts(
+                &location,
+                PutPayload::from(content),
+                PutOptions {
+                    tags,
+                    attributes,
+                    ..Default::default()
+                },
+            ))
+            .map_err(|e| self.cloud_error("Put", &location, e))?;
+
+        Ok(digest)
+    }
+}
+
+/// Builds the `object_store` client for the bucket, or container, in the service
+fn build_store(
+    storage: &CloudStorage,
+    bucket: &str,
+) -> Result<(&'static str, Arc<dyn ObjectStore>)> {
+    match storage {
+        #[cfg(feature = "azure")]
+        CloudStorage::Azure {
+            account,
+            access_key,
+        } => {
+            let mut builder = object_store::azure::MicrosoftAzureBuilder::from_env()
+                .with_account(account)
+                .with_container_name(bucket);
+            if let Some(access_key) = access_key {
+                builder = builder.with_access_key(access_key);
+            }
+            let store = builder.build().map_err(|e| {
+                RocflError::InvalidConfiguration(format!("Invalid Azure configuration: {}", e))
+            })?;
+            Ok(("Azure", Arc::new(store)))
+        }
+        #[cfg(feature = "gcs")]
+        CloudStorage::Gcs { service_account } => {
+            let mut builder =
+                object_store::gcp::GoogleCloudStorageBuilder::from_env().with_bucket_name(bucket);
+            if let Some(service_account) = service_account {
+                builder = builder.with_service_account_path(service_account);
+            }
+            let store = builder.build().map_err(|e| {
+                RocflError::InvalidConfiguration(format!("Invalid GCS configuration: {}", e))
+            })?;
+            Ok(("GCS", Arc::new(store)))
+        }
+        #[allow(unreachable_patterns)]
+        _ => Err(RocflError::General(format!(
+            "This binary was not compiled with support for {:?}",
+            storage
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use bytes::Bytes;
+    use object_store::memory::InMemory;
+
+    use super::ObjectStoreClient;
+    use crate::ocfl::store::s3::BlobClient;
+
+    #[test]
+    fn list_put_and_get_objects_under_prefix() {
+        let client = ObjectStoreClient::with_store(
+            Arc::new(InMemory::new()),
+            "Memory",
+            "bucket",
+            Some("repo"),
+        )
+        .unwrap();
+        let client: &dyn BlobClient = &client;
+
+        client
+            .put_object_bytes("0=ocfl_1.1", Bytes::from("ocfl_1.1\n"), None)
+            .unwrap();
+        client
+            .put_object_bytes("a/b/inventory.json", Bytes::from("{}"), None)
+            .unwrap();
+        client
+            .put_object_bytes("a/c.txt", Bytes::from("c"), None)
+            .unwrap();
+
+        let root = client.list_dir("").unwrap();
+        assert_eq!(vec!["0=ocfl_1.1".to_string()], root.objects);
+        assert_eq!(vec!["a".to_string()], root.directories);
+
+        let a = client.list_dir("a").unwrap();
+        assert_eq!(vec!["a/c.txt".to_string()], a.objects);
+        assert_eq!(vec!["a/b".to_string()], a.directories);
+
+        assert_eq!(
+            vec!["a/b/inventory.json".to_string(), "a/c.txt".to_string()],
+            client.list_objects("a").unwrap()
+        );
+
+        assert_eq!(Some(b"c".to_vec()), client.get_object("a/c.txt").unwrap());
+        assert_eq!(None, client.get_object("a/missing.txt").unwrap());
+        assert_eq!(1, client.object_size("a/c.txt").unwrap());
+
+        client
+            .copy_object("a/c.txt", "a/d.txt", &Default::default())
+            .unwrap();
+        client.delete_object("a/c.txt").unwrap();
+
+        let mut content = Vec::new();
+        client.stream_object("a/d.txt", &mut content).unwrap();
+        assert_eq!(b"c".to_vec(), content);
+        assert_eq!(None, client.get_object("a/c.txt").unwrap());
+    }
+}
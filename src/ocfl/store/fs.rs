@@ -2,7 +2,7 @@
 
 use std::borrow::Cow;
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::convert::TryFrom;
 use std::ffi::OsString;
 use std::fs::{self, File, OpenOptions, ReadDir};
@@ -10,8 +10,12 @@ use std::io::{self, Read, Write};
 use std::ops::Deref;
 use std::path;
 use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::str::FromStr;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, RwLock};
+use std::thread;
+use std::time::Duration;
 
 use globset::GlobBuilder;
 use grep_matcher::{Captures, Matcher};
@@ -21,21 +25,41 @@ use grep_searcher::Searcher;
 use log::{error, info, warn};
 use once_cell::sync::Lazy;
 use serde::de::DeserializeOwned;
+use serde::Serialize;
 use walkdir::WalkDir;
 
 use super::layout::{LayoutExtensionName, StorageLayout};
-use super::{OcflLayout, OcflStore, StagingStore};
+use super::{
+    alias_lock_error, missing_sidecar_error, object_index_lock_error, parse_inventory_sidecar,
+    purge_order, validate_object_root, verify_partially_purged, AliasIndex, BucketStaging,
+    ContentIndex, ObjectIndex, ObjectIndexConfig, OcflLayout, OcflStore, RepoDefaults,
+    StagingStore, ValidationHistory, SIDECAR_ALGORITHMS,
+};
 use crate::ocfl::consts::*;
-use crate::ocfl::error::{not_found, Result, RocflError};
+use crate::ocfl::digest::HexDigest;
+use crate::ocfl::error::{
+    not_found, not_found_extension_file, not_found_log, not_found_object_file, ObjectError, Result,
+    RocflError,
+};
 use crate::ocfl::inventory::Inventory;
+use crate::ocfl::lock::{LockManager, ObjectLock};
 use crate::ocfl::store::{Listing, OcflLayoutLenient, Storage};
-use crate::ocfl::validate::{IncrementalValidator, ObjectValidationResult, Validator};
+use crate::ocfl::validate::{
+    FixityCheck, FixitySampleRecord, IncrementalValidator, ObjectValidationResult,
+    ValidationFilter, Validator,
+};
 use crate::ocfl::Knowable::{Known, Unknown};
 use crate::ocfl::{
-    paths, specs, util, ContentPath, InventoryPath, Knowable, LogicalPath, ObjectInfo, RepoInfo,
-    SpecVersion, VersionRef,
+    paths, specs, util, ContentPath, DigestAlgorithm, EncryptionKey, FreezeMarker, HealthProblem,
+    InventoryPath, Knowable, LayoutInfo, LogicalPath, ObjectAlias, ObjectFingerprint, ObjectInfo,
+    RepoInfo, S3RequestMetrics, S3WriteOptions, SpecVersion, ThreadPool, VersionNum, VersionRef,
 };
 
+/// The name of the lock that is held while the repository's indexes are updated
+const INDEX_LOCK_NAME: &str = "rocfl-indexes";
+/// The number of times to retry acquiring the index lock, 50ms apart
+const INDEX_LOCK_ATTEMPTS: u32 = 100;
+
 static OBJECT_ID_MATCHER: Lazy<RegexMatcher> =
     Lazy::new(|| RegexMatcher::new(r#""id"\s*:\s*"([^"]+)""#).unwrap());
 
@@ -48,6 +72,12 @@ pub struct FsOcflStore {
     // TODO this never expires entries and is only intended to be useful within the scope of the cli
     /// Caches object ID to path mappings
     id_path_cache: RwLock<HashMap<String, String>>,
+    /// Persisted object ID to path mappings for objects that are not where the layout expects
+    object_index: RwLock<ObjectIndex>,
+    /// Whether the object index records the location of every object
+    object_registry: AtomicBool,
+    /// Persisted alias to object ID mappings
+    alias_index: RwLock<AliasIndex>,
     validator: Validator<FsStorage>,
     closed: Arc<AtomicBool>,
 }
@@ -59,7 +89,7 @@ pub struct FsStorage {
 impl FsOcflStore {
     /// Creates a new FsOcflStore
     pub fn new(storage_root: impl AsRef<Path>) -> Result<Self> {
-        let storage_root = storage_root.as_ref().to_path_buf();
+        let storage_root = util::extended_length_path(storage_root);
 
         if !storage_root.exists() {
             return Err(RocflError::IllegalState(format!(
@@ -76,35 +106,183 @@ impl FsOcflStore {
         check_extensions(&storage_root);
 
         let storage_layout = load_storage_layout(&storage_root);
+        let object_index = load_index(paths::object_index_path(&storage_root), "object index");
+        let object_index_config: ObjectIndexConfig = load_index(
+            paths::object_index_config_path(&storage_root),
+            "object index config",
+        );
+        let alias_index = load_index(paths::alias_index_path(&storage_root), "alias index");
 
         Ok(Self {
             validator: Validator::new(FsStorage::new(storage_root.clone())),
             storage_root,
             storage_layout,
             id_path_cache: RwLock::new(HashMap::new()),
+            object_index: RwLock::new(object_index),
+            object_registry: AtomicBool::new(object_index_config.registry),
+            alias_index: RwLock::new(alias_index),
             closed: Arc::new(AtomicBool::new(false)),
         })
     }
 
-    /// Initializes a new OCFL repository at the specified location
+    /// Initializes a new OCFL repository at the specified location. A copy of the OCFL spec is
+    /// written to the storage root when `spec_copy` is true.
     pub fn init(
         root: impl AsRef<Path>,
         version: SpecVersion,
         layout: Option<StorageLayout>,
+        spec_copy: bool,
     ) -> Result<Self> {
-        let root = root.as_ref().to_path_buf();
+        let root = util::extended_length_path(root);
 
-        init_new_repo(&root, version, layout.as_ref())?;
+        init_new_repo(&root, version, layout.as_ref(), spec_copy)?;
 
         Ok(Self {
             validator: Validator::new(FsStorage::new(root.clone())),
             storage_root: root,
             storage_layout: layout,
             id_path_cache: RwLock::new(HashMap::new()),
+            object_index: RwLock::new(ObjectIndex::default()),
+            object_registry: AtomicBool::new(false),
+            alias_index: RwLock::new(AliasIndex::default()),
             closed: Arc::new(AtomicBool::new(false)),
         })
     }
 
+    /// Initializes rocfl over an existing OCFL repository that was created by another tool.
+    /// The repository's spec version and storage layout are detected and verified, and any
+    /// missing optional files, such as the spec and layout extension documents, are written.
+    /// Existing objects are not modified.
+    pub fn init_from_existing(root: impl AsRef<Path>) -> Result<Self> {
+        let store = Self::new(root)?;
+        let root = &store.storage_root;
+
+        let version = match store.repo_spec_version()? {
+            Some(Known(version)) => version,
+            Some(Unknown(version)) => {
+                return Err(RocflError::IllegalState(format!(
+                    "Cannot initialize from existing storage root {}. OCFL spec version {} \
+                    is not supported",
+                    canonical_str(root),
+                    version
+                )));
+            }
+            None => {
+                return Err(RocflError::IllegalState(format!(
+                    "Cannot initialize from existing storage root {}. It does not contain an \
+                    OCFL version declaration",
+                    canonical_str(root)
+                )));
+            }
+        };
+
+        let namaste = version.root_namaste();
+        if fs::read_to_string(root.join(namaste.filename))? != namaste.content {
+            return Err(RocflError::IllegalState(format!(
+                "Cannot initialize from existing storage root {}. Version declaration {} has \
+                invalid contents",
+                canonical_str(root),
+                namaste.filename
+            )));
+        }
+
+        info!(
+            "Initializing from existing OCFL {} storage root at {}",
+            version.version(),
+            canonical_str(root)
+        );
+
+        let spec_path = paths::ocfl_spec_path(root, version);
+        if !spec_path.exists() {
+            info!(
+                "Writing missing spec document {}",
+                spec_path.to_string_lossy()
+            );
+            write!(File::create(spec_path)?, "{}", specs::ocfl_spec(version))?;
+        }
+
+        if let Some(layout) = verify_existing_layout(root)? {
+            let extension_name = layout.extension_name().to_string();
+
+            let mut config_file = paths::extensions_path(root);
+            config_file.push(&extension_name);
+            config_file.push(EXTENSIONS_CONFIG_FILE);
+
+            if !config_file.exists() {
+                info!(
+                    "Writing missing layout config {}",
+                    config_file.to_string_lossy()
+                );
+                fs::create_dir_all(config_file.parent().unwrap())?;
+                File::create(config_file)?.write_all(&layout.serialize()?)?;
+            }
+
+            let layout_spec_path = root.join(format!("{}.md", extension_name));
+            if !layout_spec_path.exists() {
+                info!(
+                    "Writing missing layout extension document {}",
+                    layout_spec_path.to_string_lossy()
+                );
+                write!(
+                    File::create(layout_spec_path)?,
+                    "{}",
+                    specs::layout_extension_spec(layout.extension_name())
+                )?;
+            }
+        }
+
+        Ok(store)
+    }
+
+    /// Copies a file into a staged object at the specified content path. Unlike
+    /// `stage_file_copy()`, this does not require the object's inventory, so it may be called from
+    /// multiple threads while the inventory is being updated.
+    pub fn stage_content_copy(
+        &self,
+        object_root: &str,
+        content_path: &ContentPath,
+        source: &mut impl Read,
+    ) -> Result<()> {
+        self.stage_content_write(object_root, content_path, |file| {
+            io::copy(source, file)?;
+            Ok(())
+        })
+    }
+
+    /// Stages the content that `write` writes to a temporary file, and then renames it to the
+    /// content path within the staged object. Nothing is staged if `write` fails. Because the
+    /// file is replaced rather than truncated, other links to an existing file are not modified.
+    pub fn stage_content_write<T>(
+        &self,
+        object_root: &str,
+        content_path: &ContentPath,
+        write: impl FnOnce(&mut File) -> Result<T>,
+    ) -> Result<T> {
+        let mut storage_path = PathBuf::from(object_root);
+        storage_path.push(util::native_path(content_path.as_str()));
+
+        fs::create_dir_all(storage_path.parent().unwrap())?;
+
+        let mut part = storage_path.as_os_str().to_owned();
+        part.push(".part");
+        let part = PathBuf::from(part);
+
+        let result = File::create(&part)
+            .map_err(RocflError::from)
+            .and_then(|mut file| write(&mut file));
+
+        match result {
+            Ok(value) => {
+                fs::rename(&part, &storage_path)?;
+                Ok(value)
+            }
+            Err(e) => {
+                util::remove_file_ignore_not_found(&part)?;
+                Err(e)
+            }
+        }
+    }
+
     /// Conditionally initializes a new OCFL repository at the specified location if one does
     /// not already exist.
     pub fn init_if_needed<P: AsRef<Path>>(root: P, layout: StorageLayout) -> Result<Self> {
@@ -115,7 +293,7 @@ impl FsOcflStore {
             Self::new(root)
         } else {
             // TODO this needs to be based on parent repo
-            Self::init(root, SpecVersion::Ocfl1_0, Some(layout))
+            Self::init(root, SpecVersion::Ocfl1_0, Some(layout), true)
         }
     }
 
@@ -133,9 +311,24 @@ impl FsOcflStore {
         }
     }
 
+    /// Returns the storage root relative path to the object's root. `RocflError::NotFound` is
+    /// returned if there is not an object at the location.
+    fn require_object_root_path(&self, object_id: &str) -> Result<String> {
+        let object_root = self.lookup_or_find_object_root_path(object_id)?;
+
+        find_first_version_declaration(
+            OBJECT_NAMASTE_FILE_PREFIX,
+            self.storage_root.join(util::native_path(&object_root)),
+        )
+        .map_err(|_| not_found(object_id, None))?;
+
+        Ok(object_root)
+    }
+
     /// Returns the storage root relative path to the object by doing a cache look up. If
-    /// the mapping was not found in the cache, then it is computed using the configured
-    /// storage layout. If there is no storage layout, then `None` is returned.
+    /// the mapping was not found in the cache, then the object index is consulted, and, finally,
+    /// it is computed using the configured storage layout. If there is no storage layout, then
+    /// `None` is returned.
     fn get_object_root_path(&self, object_id: &str) -> Option<String> {
         if let Ok(cache) = self.id_path_cache.read() {
             if let Some(object_root) = cache.get(object_id) {
@@ -143,6 +336,12 @@ impl FsOcflStore {
             }
         }
 
+        if let Ok(index) = self.object_index.read() {
+            if let Some(object_root) = index.get(object_id) {
+                return Some(object_root.clone());
+            }
+        }
+
         if let Some(storage_layout) = &self.storage_layout {
             let object_root = storage_layout.map_object_id(object_id);
 
@@ -155,7 +354,13 @@ impl FsOcflStore {
         None
     }
 
+    /// Scans the repository for the object's inventory. When the object index is a registry, the
+    /// repository is not scanned, because every object is in the index.
     fn scan_for_inventory(&self, object_id: &str) -> Result<Inventory> {
+        if self.is_object_registry() {
+            return Err(not_found(object_id, None));
+        }
+
         info!(
             "Storage layout not configured, scanning repository to locate object {}",
             &object_id
@@ -182,7 +387,7 @@ impl FsOcflStore {
     }
 
     fn get_inventory_by_path(&self, object_id: &str, object_root: &str) -> Result<Inventory> {
-        let object_root = self.storage_root.join(object_root);
+        let object_root = self.storage_root.join(util::native_path(object_root));
 
         if object_root.exists() {
             let inventory = parse_inventory(&object_root, &self.storage_root)?;
@@ -225,6 +430,168 @@ impl FsOcflStore {
         Ok(())
     }
 
+    /// Records the object's location in the object index, and persists the index, if the
+    /// location differs from the location dictated by the storage layout or the index is a
+    /// registry.
+    fn index_object(&self, object_id: &str, object_root: &str) -> Result<()> {
+        let layout_root = self
+            .storage_layout
+            .as_ref()
+            .map(|layout| layout.map_object_id(object_id));
+
+        let index_file = paths::object_index_path(&self.storage_root);
+        let _lock = self.lock_indexes()?;
+        let mut index = self
+            .object_index
+            .write()
+            .map_err(|_| object_index_lock_error())?;
+        *index = read_index(&index_file)?;
+
+        let changed = if !self.is_object_registry() && layout_root.as_deref() == Some(object_root) {
+            index.remove(object_id)
+        } else {
+            index.insert(object_id, object_root)
+        };
+
+        if let Ok(mut cache) = self.id_path_cache.write() {
+            cache.insert(object_id.to_string(), object_root.to_string());
+        }
+
+        if changed {
+            write_index(&index_file, &*index)?;
+        }
+
+        Ok(())
+    }
+
+    /// Deletes every file in the object root, in purge order, followed by the object root and any
+    /// dangling directories above it. The paths, relative the object root, of the deleted files
+    /// are returned.
+    fn delete_object_root(&self, object_root: &str) -> Result<Vec<String>> {
+        let storage_path = self.storage_root.join(util::native_path(object_root));
+
+        if !storage_path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut files = Vec::new();
+
+        for entry in WalkDir::new(&storage_path).min_depth(1) {
+            let entry = entry?;
+            if entry.file_type().is_file() {
+                let relative_path = pathdiff::diff_paths(entry.path(), &storage_path).unwrap();
+                files.push(
+                    util::convert_backslash_to_forward(relative_path.to_string_lossy().as_ref())
+                        .into_owned(),
+                );
+            }
+        }
+
+        if !files.is_empty() && !files.iter().any(|file| purge_order(file) == 2) {
+            warn!(
+                "Object at {} was partially purged. Deleting its {} remaining files",
+                object_root,
+                files.len()
+            );
+        }
+
+        files.sort_unstable_by(|a, b| purge_order(a).cmp(&purge_order(b)).then(a.cmp(b)));
+
+        for file in &files {
+            fs::remove_file(storage_path.join(util::native_path(file)))?;
+        }
+
+        // Only empty directories remain
+        fs::remove_dir_all(&storage_path)?;
+
+        let parent = storage_path.parent().unwrap();
+
+        if parent.exists() {
+            if let Err(e) = util::clean_dirs_up(parent) {
+                error!(
+                    "Failed to cleanup dangling directories at {}: {}",
+                    storage_path.to_string_lossy(),
+                    e
+                );
+            }
+        }
+
+        Ok(files)
+    }
+
+    /// Returns an iterator over the inventories of the objects in the object index, optionally
+    /// filtered by a glob pattern that object IDs must match
+    #[allow(clippy::result_large_err)]
+    fn iter_registered_inventories<'a>(
+        &'a self,
+        filter_glob: Option<&str>,
+    ) -> Result<Box<dyn Iterator<Item = Result<Inventory, ObjectError>> + 'a>> {
+        let matcher = match filter_glob {
+            Some(glob) => Some(
+                GlobBuilder::new(glob)
+                    .backslash_escape(true)
+                    .build()?
+                    .compile_matcher(),
+            ),
+            None => None,
+        };
+
+        let entries: Vec<(String, String)> = self
+            .object_index
+            .read()
+            .map_err(|_| object_index_lock_error())?
+            .entries()
+            .filter(|(object_id, _)| matcher.as_ref().is_none_or(|m| m.is_match(object_id)))
+            .map(|(object_id, object_root)| (object_id.clone(), object_root.clone()))
+            .collect();
+
+        Ok(Box::new(
+            entries
+                .into_iter()
+                .take_while(move |_| !self.closed.load(Ordering::Acquire))
+                .map(move |(object_id, object_root)| {
+                    let storage_path = self.storage_root.join(util::native_path(&object_root));
+                    parse_inventory(storage_path, &self.storage_root)
+                        .map_err(|e| ObjectError::new(Some(object_id), Some(object_root), e))
+                }),
+        ))
+    }
+
+    /// Removes the object from the object index, if it is indexed
+    fn unindex_object(&self, object_id: &str) -> Result<()> {
+        if let Ok(mut cache) = self.id_path_cache.write() {
+            cache.remove(object_id);
+        }
+
+        let index_file = paths::object_index_path(&self.storage_root);
+        let _lock = self.lock_indexes()?;
+        let mut index = self
+            .object_index
+            .write()
+            .map_err(|_| object_index_lock_error())?;
+        *index = read_index(&index_file)?;
+
+        if index.remove(object_id) {
+            write_index(&index_file, &*index)?;
+        }
+
+        Ok(())
+    }
+
+    /// Removes all of the object's aliases from the alias index
+    fn unalias_object(&self, object_id: &str) -> Result<()> {
+        let index_file = paths::alias_index_path(&self.storage_root);
+        let _lock = self.lock_indexes()?;
+        let mut index = self.alias_index.write().map_err(|_| alias_lock_error())?;
+        *index = read_index(&index_file)?;
+
+        if index.remove_object(object_id) {
+            write_index(&index_file, &*index)?;
+        }
+
+        Ok(())
+    }
+
     fn require_layout(&self) -> Result<&StorageLayout> {
         match &self.storage_layout {
             Some(layout) => Ok(layout),
@@ -235,6 +602,77 @@ impl FsOcflStore {
         }
     }
 
+    /// Acquires the lock that serializes updates to the repository's indexes, such as the object
+    /// index and alias index, across processes. The lock is retried for a few seconds before
+    /// giving up, because it is only ever held while an index is rewritten.
+    fn lock_indexes(&self) -> Result<ObjectLock> {
+        let locks_dir = paths::locks_extension_path(&self.storage_root);
+        fs::create_dir_all(&locks_dir)?;
+        let manager = LockManager::new(locks_dir);
+
+        let mut attempts = 0;
+        loop {
+            match manager.acquire(INDEX_LOCK_NAME) {
+                Err(RocflError::LockAcquire(..)) if attempts < INDEX_LOCK_ATTEMPTS => {
+                    attempts += 1;
+                    thread::sleep(Duration::from_millis(50));
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Scans every object in the repository that uses the digest algorithm, except for the
+    /// specified object, and records the location of its content in the content index. Encrypted
+    /// objects are skipped because their content does not match their digests. The caller must
+    /// hold the index lock.
+    fn build_content_index(&self, algorithm: DigestAlgorithm, skip_object_id: &str) -> Result<()> {
+        info!(
+            "Indexing the content of every {} object in the repository",
+            algorithm
+        );
+
+        let mut shards: BTreeMap<PathBuf, ContentIndex> = BTreeMap::new();
+
+        for inventory in InventoryIter::new(&self.storage_root, None, self.closed.clone())? {
+            let inventory = match inventory {
+                Ok(inventory) => inventory,
+                Err(e) => {
+                    warn!("Skipping object that could not be indexed: {:#}", e);
+                    continue;
+                }
+            };
+
+            if inventory.id == skip_object_id
+                || inventory.digest_algorithm != algorithm
+                || paths::encryption_marker_path(&inventory.storage_path).exists()
+            {
+                continue;
+            }
+
+            for (path, digest) in inventory.manifest().iter() {
+                let digest: &str = digest.as_ref().as_ref();
+                shards
+                    .entry(paths::content_index_path(
+                        &self.storage_root,
+                        algorithm,
+                        digest,
+                    ))
+                    .or_default()
+                    .insert_if_absent(digest, &format!("{}/{}", inventory.object_root, path));
+            }
+        }
+
+        self.ensure_open()?;
+
+        fs::create_dir_all(paths::content_index_dir(&self.storage_root, algorithm))?;
+        for (shard_file, index) in shards {
+            write_index(&shard_file, &index)?;
+        }
+
+        Ok(())
+    }
+
     /// Returns an error if the store is closed
     fn ensure_open(&self) -> Result<()> {
         if self.is_closed() {
@@ -275,15 +713,75 @@ impl OcflStore for FsOcflStore {
         }
     }
 
+    /// Returns true if the specified object exists. Only the object's namaste file is checked,
+    /// and its inventory is not read.
+    fn object_exists(&self, object_id: &str) -> Result<bool> {
+        self.ensure_open()?;
+
+        match self.require_object_root_path(object_id) {
+            Ok(_) => Ok(true),
+            Err(RocflError::NotFound(_)) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Returns the number of the specified object's HEAD version, or a `RocflError::NotFound` if
+    /// the object does not exist. The version is identified from the object's version
+    /// directories rather than by reading its inventory, unless the object has a mutable HEAD.
+    fn head_version(&self, object_id: &str) -> Result<VersionNum> {
+        self.ensure_open()?;
+
+        let object_root = self.require_object_root_path(object_id)?;
+        let object_path = self.storage_root.join(util::native_path(&object_root));
+
+        if object_path.join(MUTABLE_HEAD_INVENTORY_FILE).exists() {
+            return Ok(self.get_inventory_by_path(object_id, &object_root)?.head);
+        }
+
+        let mut versions = Vec::new();
+
+        for entry in fs::read_dir(&object_path)? {
+            let entry = entry?;
+            if let Ok(version_num) =
+                VersionNum::try_from(entry.file_name().to_string_lossy().as_ref())
+            {
+                versions.push(version_num);
+            }
+        }
+
+        versions.sort_unstable();
+
+        // A version is only complete once its inventory has been written
+        versions
+            .into_iter()
+            .rev()
+            .find(|version_num| {
+                object_path
+                    .join(version_num.to_string())
+                    .join(INVENTORY_FILE)
+                    .is_file()
+            })
+            .ok_or_else(|| RocflError::CorruptObject {
+                object_id: object_id.to_string(),
+                message: "The object does not contain any versions".to_string(),
+            })
+    }
+
     /// Returns an iterator that iterates over every object in an OCFL repository, returning
     /// the most recent inventory of each. Optionally, a glob pattern may be provided that filters
     /// the objects that are returned by OCFL ID.
+    ///
+    /// Objects that cannot be read are returned as `ObjectError`s that identify the object.
     fn iter_inventories<'a>(
         &'a self,
         filter_glob: Option<&str>,
-    ) -> Result<Box<dyn Iterator<Item = Result<Inventory>> + 'a>> {
+    ) -> Result<Box<dyn Iterator<Item = Result<Inventory, ObjectError>> + 'a>> {
         self.ensure_open()?;
 
+        if self.is_object_registry() {
+            return self.iter_registered_inventories(filter_glob);
+        }
+
         Ok(Box::new(match filter_glob {
             Some(glob) => {
                 InventoryIter::new_glob_matching(&self.storage_root, glob, self.closed.clone())?
@@ -292,6 +790,62 @@ impl OcflStore for FsOcflStore {
         }))
     }
 
+    /// Returns the IDs of up to `limit` objects whose IDs start with `prefix`, sorted. Object IDs
+    /// are located within the inventories, rather than parsing the inventories.
+    fn list_object_ids(&self, prefix: &str, limit: usize) -> Result<Vec<String>> {
+        self.ensure_open()?;
+
+        if self.is_object_registry() {
+            let index = self
+                .object_index
+                .read()
+                .map_err(|_| object_index_lock_error())?;
+            return Ok(index
+                .entries()
+                .map(|(object_id, _)| object_id)
+                .filter(|object_id| object_id.starts_with(prefix))
+                .take(limit)
+                .cloned()
+                .collect());
+        }
+
+        let mut ids = Vec::new();
+        let mut walker = WalkDir::new(&self.storage_root).min_depth(1).into_iter();
+
+        while ids.len() < limit && !self.closed.load(Ordering::Acquire) {
+            let entry = match walker.next() {
+                Some(Ok(entry)) => entry,
+                Some(Err(e)) => {
+                    info!("Skipping unreadable path while listing object IDs: {}", e);
+                    continue;
+                }
+                None => break,
+            };
+
+            if !entry.file_type().is_dir() {
+                continue;
+            }
+
+            if entry.depth() == 1 && entry.file_name() == EXTENSIONS_DIR {
+                walker.skip_current_dir();
+                continue;
+            }
+
+            if is_object_root(entry.path()).unwrap_or(false) {
+                walker.skip_current_dir();
+
+                match extract_object_id(paths::inventory_path(entry.path())) {
+                    Ok(id) if id.starts_with(prefix) => ids.push(id),
+                    Ok(_) => (),
+                    Err(e) => info!("Skipping object while listing object IDs: {:#}", e),
+                }
+            }
+        }
+
+        ids.sort();
+        Ok(ids)
+    }
+
     /// Writes the specified file to the sink.
     ///
     /// If the file cannot be found, then a `RocflError::NotFound` error is returned.
@@ -307,8 +861,21 @@ impl OcflStore for FsOcflStore {
         let inventory = self.get_inventory(object_id)?;
 
         let content_path = inventory.content_path_for_logical_path(path, version_num)?;
+        self.get_content_file(&inventory, content_path, sink)
+    }
+
+    /// Writes the file at the specified content path within the object described by the
+    /// inventory to the sink.
+    fn get_content_file(
+        &self,
+        inventory: &Inventory,
+        content_path: &ContentPath,
+        sink: &mut dyn Write,
+    ) -> Result<()> {
+        self.ensure_open()?;
+
         let mut storage_path = PathBuf::from(&inventory.storage_path);
-        storage_path.push(content_path.as_path());
+        storage_path.push(util::native_path(content_path.as_str()));
 
         let mut file = File::open(storage_path)?;
         io::copy(&mut file, sink)?;
@@ -316,10 +883,37 @@ impl OcflStore for FsOcflStore {
         Ok(())
     }
 
+    /// Returns the size, in bytes, of the file at the specified content path within the object
+    /// described by the inventory.
+    fn get_content_file_size(
+        &self,
+        inventory: &Inventory,
+        content_path: &ContentPath,
+    ) -> Result<u64> {
+        self.ensure_open()?;
+
+        let mut storage_path = PathBuf::from(&inventory.storage_path);
+        storage_path.push(util::native_path(content_path.as_str()));
+
+        Ok(fs::metadata(storage_path)?.len())
+    }
+
     /// Writes a new OCFL object. The contents at `object_path` must be a fully formed OCFL
     /// object that is able to be moved into place with no additional modifications.
     ///
     /// The object must not already exist.
+    fn new_object_root(&self, object_id: &str, object_root: Option<&str>) -> Result<String> {
+        match object_root {
+            Some(root) => Ok(util::trim_slashes(root).to_string()),
+            None => self.get_object_root_path(object_id).ok_or_else(|| {
+                RocflError::IllegalState(
+                    "Cannot create object because the repository does not have a defined storage layout, and an object root path was not specified."
+                        .to_string(),
+                )
+            }),
+        }
+    }
+
     fn write_new_object(
         &self,
         inventory: &mut Inventory,
@@ -328,21 +922,9 @@ impl OcflStore for FsOcflStore {
     ) -> Result<()> {
         self.ensure_open()?;
 
-        let root_path = match self.get_object_root_path(&inventory.id) {
-            Some(object_root) => object_root,
-            None => {
-                if let Some(root) = object_root {
-                    util::trim_slashes(root).to_string()
-                } else {
-                    return Err(RocflError::IllegalState(
-                            "Cannot create object because the repository does not have a defined storage layout, and an object root path was not specified."
-                                .to_string(),
-                        ));
-                }
-            }
-        };
+        let root_path = self.new_object_root(&inventory.id, object_root)?;
 
-        let storage_path = self.storage_root.join(root_path);
+        let storage_path = self.storage_root.join(util::native_path(&root_path));
 
         if storage_path.exists() {
             return Err(RocflError::IllegalState(format!(
@@ -355,10 +937,14 @@ impl OcflStore for FsOcflStore {
         info!("Creating new object {}", inventory.id);
 
         fs::create_dir_all(storage_path.parent().unwrap())?;
-        fs::rename(src_object_path, &storage_path)?;
+        util::move_path(src_object_path, &storage_path)?;
 
         inventory.storage_path = storage_path.to_string_lossy().into();
 
+        if object_root.is_some() || self.is_object_registry() {
+            self.index_object(&inventory.id, &root_path)?;
+        }
+
         Ok(())
     }
 
@@ -387,7 +973,9 @@ impl OcflStore for FsOcflStore {
             )));
         }
 
-        let object_root = self.storage_root.join(&existing_inventory.object_root);
+        let object_root = self
+            .storage_root
+            .join(util::native_path(&existing_inventory.object_root));
         let destination = object_root.join(&version_str);
 
         if destination.exists() {
@@ -401,10 +989,10 @@ impl OcflStore for FsOcflStore {
             version_str, inventory.id
         );
 
-        fs::rename(version_path, &destination)?;
+        util::move_path(version_path, &destination)?;
 
         if let Err(e) = self.copy_inventory_files(inventory, &destination, &object_root) {
-            if let Err(e) = fs::rename(&destination, version_path) {
+            if let Err(e) = util::move_path(&destination, version_path) {
                 error!("Failed to rollback version {} of object {} at {}: {}. Manual intervention may be required.",
                        version_str, inventory.id, version_path.to_string_lossy(), e);
             }
@@ -422,13 +1010,137 @@ impl OcflStore for FsOcflStore {
             let old_namastes = find_files(&object_root, OBJECT_NAMASTE_FILE_PREFIX)?;
             write_object_namaste(&object_root, inventory.spec_version().unwrap())?;
             for old in old_namastes {
-                util::remove_file_ignore_not_found(&object_root.join(old))?;
+                util::remove_file_ignore_not_found(object_root.join(old))?;
             }
         }
 
         Ok(())
     }
 
+    /// Replaces the content files that were added in the HEAD version of a committed object with
+    /// hard links to existing files in the repository that have the same size and digest. Files
+    /// that cannot be linked, for example because they are on a different filesystem, are left
+    /// as copies.
+    ///
+    /// Existing content is located using the repository's content index, which is built by
+    /// scanning every object the first time it is needed, and is then updated with the content
+    /// of each version that is linked. Content committed without linking is not indexed.
+    fn link_duplicate_content(&self, inventory: &Inventory) -> Result<usize> {
+        self.ensure_open()?;
+
+        let algorithm = inventory.digest_algorithm;
+        let prefix = format!("{}/", inventory.head);
+        let mut shards: ContentShards = BTreeMap::new();
+
+        for (path, digest) in inventory.manifest().iter() {
+            if path.starts_with(&prefix) {
+                shards
+                    .entry(paths::content_index_path(
+                        &self.storage_root,
+                        algorithm,
+                        digest.as_ref().as_ref(),
+                    ))
+                    .or_default()
+                    .push((digest.clone(), path.clone()));
+            }
+        }
+
+        let _lock = self.lock_indexes()?;
+
+        if !paths::content_index_dir(&self.storage_root, algorithm).exists() {
+            self.build_content_index(algorithm, &inventory.id)?;
+        }
+
+        let object_root = PathBuf::from(&inventory.storage_path);
+        let object_prefix = match object_root.strip_prefix(&self.storage_root) {
+            Ok(relative) => format!(
+                "{}/",
+                util::convert_backslash_to_forward(&relative.to_string_lossy())
+            ),
+            Err(_) => {
+                return Err(RocflError::IllegalState(format!(
+                    "Object {} is not within the storage root",
+                    inventory.id
+                )))
+            }
+        };
+        let mut linked = 0;
+
+        for (shard_file, entries) in shards {
+            self.ensure_open()?;
+
+            let mut index: ContentIndex = read_index(&shard_file)?;
+            let mut changed = false;
+
+            for (digest, path) in entries {
+                let relative = format!("{}{}", object_prefix, path);
+                let digest_str: &str = digest.as_ref().as_ref();
+
+                let existing = match index.get(digest_str) {
+                    Some(existing) if !existing.starts_with(&object_prefix) => existing,
+                    Some(_) => continue,
+                    None => {
+                        changed |= index.insert(digest_str, &relative);
+                        continue;
+                    }
+                };
+
+                let src = self.storage_root.join(util::native_path(existing));
+                let dst = object_root.join(util::native_path(path.as_str()));
+
+                if is_same_content(&src, &dst, algorithm, &digest) {
+                    if hard_link_or_keep(&src, &dst) {
+                        linked += 1;
+                    }
+                } else {
+                    // The indexed file no longer exists or has changed, so this file replaces it
+                    changed |= index.insert(digest_str, &relative);
+                }
+            }
+
+            if changed {
+                write_index(&shard_file, &index)?;
+            }
+        }
+
+        info!(
+            "Linked {} files in object {} to existing content",
+            linked, inventory.id
+        );
+
+        Ok(linked)
+    }
+
+    /// The filesystem does not have storage classes, so this does nothing
+    fn set_s3_write_options(&mut self, _content: S3WriteOptions, _inventory: S3WriteOptions) {}
+
+    fn set_thread_pool(&mut self, pool: ThreadPool) {
+        self.validator.set_thread_pool(pool);
+    }
+
+    fn set_encryption_key(&mut self, key: EncryptionKey) {
+        self.validator.set_encryption_key(key);
+    }
+
+    fn s3_request_metrics(&self) -> Option<S3RequestMetrics> {
+        None
+    }
+
+    /// Content is always staged on local disk, so this does nothing
+    fn set_bucket_staging(&mut self, _staging_id: Option<String>) {}
+
+    fn bucket_staging(&self) -> Option<&dyn BucketStaging> {
+        None
+    }
+
+    fn set_strict_paths(&self, strict: bool) {
+        self.validator.set_strict_paths(strict);
+    }
+
+    fn set_sample_history(&self, history: HashMap<String, FixitySampleRecord>) {
+        self.validator.set_sample_history(history);
+    }
+
     /// Purges the specified object from the repository, if it exists. If it does not exist,
     /// nothing happens. Any dangling directories that were created as a result of purging
     /// the object are also removed.
@@ -441,35 +1153,154 @@ impl OcflStore for FsOcflStore {
             Ok(object_root) => object_root,
         };
 
-        let storage_path = self.storage_root.join(&object_root);
-        info!(
-            "Purging object {} at {}",
-            object_id,
-            storage_path.to_string_lossy()
-        );
+        info!("Purging object {} at {}", object_id, object_root);
 
-        if storage_path.exists() {
-            if let Err(e) = fs::remove_dir_all(&storage_path) {
-                return Err(RocflError::CorruptObject {
-                    object_id: object_id.to_string(),
-                    message: format!("Failed to purge object at {}. This object may need to be removed manually. Error: {}",
-                                     storage_path.to_string_lossy(), e)
-                });
+        if let Err(e) = self.delete_object_root(&object_root) {
+            return Err(RocflError::CorruptObject {
+                object_id: object_id.to_string(),
+                message: format!("Failed to purge object at {}. This object may need to be removed manually. Error: {}",
+                                 object_root, e)
+            });
+        }
+
+        self.unindex_object(object_id)?;
+        self.unalias_object(object_id)
+    }
+
+    /// Finishes purging the object at the storage root relative path, for objects whose purge
+    /// was interrupted and can no longer be found by ID. The paths, relative the object root, of
+    /// the deleted files are returned.
+    fn purge_object_root(&self, object_root: &str) -> Result<Vec<String>> {
+        self.ensure_open()?;
+
+        let object_root = util::trim_slashes(object_root);
+        validate_object_root(object_root)?;
+
+        let storage_path = self.storage_root.join(util::native_path(object_root));
+
+        if !storage_path.is_dir() {
+            return Err(RocflError::NotFound(format!(
+                "Partially purged object at path {}",
+                object_root
+            )));
+        }
+
+        let mut names = Vec::new();
+        for entry in fs::read_dir(&storage_path)? {
+            names.push(entry?.file_name().to_string_lossy().into_owned());
+        }
+
+        verify_partially_purged(object_root, names.iter().map(String::as_str))?;
+
+        info!("Resuming purge of object at {}", object_root);
+
+        let deleted = self.delete_object_root(object_root).map_err(|e| {
+            RocflError::General(format!(
+                "Failed to purge object at {}. This object may need to be removed manually. Error: {}",
+                object_root, e
+            ))
+        })?;
+
+        let object_ids = self
+            .object_index
+            .read()
+            .map_err(|_| object_index_lock_error())?
+            .object_ids_at(object_root);
+
+        for object_id in object_ids {
+            self.unindex_object(&object_id)?;
+            self.unalias_object(&object_id)?;
+        }
+
+        Ok(deleted)
+    }
+
+    /// Returns the paths, relative the object root, of every file within the object's version
+    /// content directories that is not referenced in the object's manifest. The paths are sorted.
+    fn list_orphaned_content(&self, object_id: &str) -> Result<Vec<String>> {
+        self.ensure_open()?;
+
+        let inventory = self.get_inventory(object_id)?;
+        find_orphaned_content(&inventory)
+    }
+
+    /// Deletes every file within the object's version content directories that is not referenced
+    /// in the object's manifest, along with any directories that are empty as a result. The paths,
+    /// relative the object root, of the deleted files are returned.
+    fn rm_orphaned_content(&self, object_id: &str) -> Result<Vec<String>> {
+        self.ensure_open()?;
+
+        let inventory = self.get_inventory(object_id)?;
+        let orphans = find_orphaned_content(&inventory)?;
+
+        if orphans.is_empty() {
+            return Ok(orphans);
+        }
+
+        let object_root = PathBuf::from(&inventory.storage_path);
+
+        for orphan in &orphans {
+            let path = object_root.join(util::native_path(orphan));
+            info!("Deleting orphaned file: {}", path.to_string_lossy());
+            util::remove_file_ignore_not_found(&path)?;
+        }
+
+        for version_num in inventory.versions.keys() {
+            let content_dir = paths::content_path(&object_root, *version_num, &inventory);
+            if content_dir.exists() {
+                util::clean_dirs_down(&content_dir)?;
             }
         }
 
-        let parent = storage_path.parent().unwrap();
+        Ok(orphans)
+    }
 
-        if parent.exists() {
-            if let Err(e) = util::clean_dirs_up(parent) {
-                error!(
-                    "Failed to cleanup dangling directories at {}: {}",
-                    storage_path.to_string_lossy(),
-                    e
+    /// Returns the paths, relative the storage root, of every file within the object's root,
+    /// sorted. `RocflError::NotFound` is returned if the object does not exist.
+    fn list_object_files(&self, object_id: &str) -> Result<Vec<String>> {
+        self.ensure_open()?;
+
+        let object_root = self.require_object_root_path(object_id)?;
+        let mut files = Vec::new();
+
+        for entry in WalkDir::new(self.storage_root.join(util::native_path(&object_root))) {
+            let entry = entry?;
+            if entry.file_type().is_file() {
+                let relative = entry.path().strip_prefix(&self.storage_root).unwrap();
+                files.push(
+                    util::convert_backslash_to_forward(&relative.to_string_lossy()).into_owned(),
                 );
             }
         }
 
+        files.sort_unstable();
+        Ok(files)
+    }
+
+    /// Writes the file at the specified path, relative the object's root, to the sink.
+    ///
+    /// If the file cannot be found, then a `RocflError::NotFound` error is returned.
+    fn get_object_root_file(
+        &self,
+        object_id: &str,
+        path: &str,
+        sink: &mut dyn Write,
+    ) -> Result<()> {
+        self.ensure_open()?;
+
+        let object_root = self.require_object_root_path(object_id)?;
+        let file_path = self
+            .storage_root
+            .join(util::native_path(&object_root))
+            .join(util::native_path(path));
+
+        if !file_path.is_file() {
+            return Err(not_found_object_file(object_id, path));
+        }
+
+        let mut file = File::open(file_path)?;
+        io::copy(&mut file, sink)?;
+
         Ok(())
     }
 
@@ -477,11 +1308,164 @@ impl OcflStore for FsOcflStore {
     fn list_object_extensions(&self, object_id: &str) -> Result<Vec<String>> {
         self.ensure_open()?;
 
-        let object_root = self.lookup_or_find_object_root_path(object_id)?;
-        let storage_path = self.storage_root.join(&object_root);
-        let extensions_dir = paths::extensions_path(&storage_path);
+        let object_root = self.lookup_or_find_object_root_path(object_id)?;
+        let storage_path = self.storage_root.join(util::native_path(&object_root));
+        let extensions_dir = paths::extensions_path(&storage_path);
+
+        list_extensions(extensions_dir)
+    }
+
+    /// Returns the paths, relative the object's `extensions` directory, of every file in the
+    /// object's `extensions` directory. If the object does not have an `extensions` directory,
+    /// then an empty vector is returned.
+    fn list_object_extension_files(&self, object_id: &str) -> Result<Vec<String>> {
+        self.ensure_open()?;
+
+        let object_root = self.require_object_root_path(object_id)?;
+        let extensions_dir =
+            paths::extensions_path(self.storage_root.join(util::native_path(&object_root)));
+
+        let mut files = Vec::new();
+
+        if extensions_dir.exists() {
+            for entry in WalkDir::new(&extensions_dir) {
+                let entry = entry?;
+                if entry.file_type().is_file() {
+                    let relative = entry.path().strip_prefix(&extensions_dir).unwrap();
+                    files.push(
+                        util::convert_backslash_to_forward(&relative.to_string_lossy()).into(),
+                    );
+                }
+            }
+        }
+
+        files.sort_unstable();
+
+        Ok(files)
+    }
+
+    /// Writes the file at the specified path, relative the object's `extensions` directory, to
+    /// the sink.
+    ///
+    /// If the file cannot be found, then a `RocflError::NotFound` error is returned.
+    fn get_object_extension_file(
+        &self,
+        object_id: &str,
+        path: &str,
+        sink: &mut dyn Write,
+    ) -> Result<()> {
+        self.ensure_open()?;
+
+        let object_root = self.require_object_root_path(object_id)?;
+        let file_path =
+            paths::extensions_path(self.storage_root.join(util::native_path(&object_root)))
+                .join(util::native_path(path));
+
+        if !file_path.is_file() {
+            return Err(not_found_extension_file(object_id, path));
+        }
+
+        let mut file = File::open(file_path)?;
+        io::copy(&mut file, sink)?;
+
+        Ok(())
+    }
+
+    /// Returns the paths, relative the object's `logs` directory, of every file in the object's
+    /// `logs` directory. If the object does not have a `logs` directory, then an empty vector
+    /// is returned.
+    fn list_object_logs(&self, object_id: &str) -> Result<Vec<String>> {
+        self.ensure_open()?;
+
+        let object_root = self.require_object_root_path(object_id)?;
+        let logs_dir = paths::logs_path(self.storage_root.join(util::native_path(&object_root)));
+
+        let mut logs = Vec::new();
+
+        if logs_dir.exists() {
+            for entry in WalkDir::new(&logs_dir) {
+                let entry = entry?;
+                if entry.file_type().is_file() {
+                    let relative = entry.path().strip_prefix(&logs_dir).unwrap();
+                    logs.push(
+                        util::convert_backslash_to_forward(&relative.to_string_lossy()).into(),
+                    );
+                }
+            }
+        }
+
+        logs.sort_unstable();
+
+        Ok(logs)
+    }
+
+    /// Writes the file at the specified path, relative the object's `logs` directory, to the sink.
+    ///
+    /// If the file cannot be found, then a `RocflError::NotFound` error is returned.
+    fn get_object_log_file(&self, object_id: &str, path: &str, sink: &mut dyn Write) -> Result<()> {
+        self.ensure_open()?;
+
+        let object_root = self.require_object_root_path(object_id)?;
+        let log_path = paths::logs_path(self.storage_root.join(util::native_path(&object_root)))
+            .join(util::native_path(path));
+
+        if !log_path.is_file() {
+            return Err(not_found_log(object_id, path));
+        }
+
+        let mut file = File::open(log_path)?;
+        io::copy(&mut file, sink)?;
+
+        Ok(())
+    }
+
+    /// Appends the bytes to the file at the specified path, relative the object's `logs`
+    /// directory. The file, and the `logs` directory, are created if they do not exist.
+    fn append_object_log(&self, object_id: &str, path: &str, bytes: &[u8]) -> Result<()> {
+        self.ensure_open()?;
+
+        let object_root = self.require_object_root_path(object_id)?;
+        let log_path = paths::logs_path(self.storage_root.join(util::native_path(&object_root)))
+            .join(util::native_path(path));
+
+        fs::create_dir_all(log_path.parent().unwrap())?;
+
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(log_path)?
+            .write_all(bytes)?;
+
+        Ok(())
+    }
+
+    /// Writes the bytes of the object's root inventory, or of the inventory in the specified
+    /// version's directory, to the sink.
+    ///
+    /// If the inventory cannot be found, then a `RocflError::NotFound` error is returned.
+    fn get_inventory_file(
+        &self,
+        object_id: &str,
+        version_num: Option<VersionNum>,
+        sink: &mut dyn Write,
+    ) -> Result<()> {
+        self.ensure_open()?;
+
+        let object_root = self.require_object_root_path(object_id)?;
+        let mut inventory_path = self.storage_root.join(util::native_path(&object_root));
+        if let Some(version_num) = version_num {
+            inventory_path.push(version_num.to_string());
+        }
+        inventory_path.push(INVENTORY_FILE);
 
-        list_extensions(extensions_dir)
+        if !inventory_path.is_file() {
+            return Err(not_found(object_id, version_num));
+        }
+
+        let mut file = File::open(inventory_path)?;
+        io::copy(&mut file, sink)?;
+
+        Ok(())
     }
 
     /// Validates the specified object and returns any problems found. Err will only be returned
@@ -489,12 +1473,24 @@ impl OcflStore for FsOcflStore {
     fn validate_object(
         &self,
         object_id: &str,
-        fixity_check: bool,
+        fixity_check: FixityCheck,
     ) -> Result<ObjectValidationResult> {
         let object_root = self.lookup_or_find_object_root_path(object_id)?;
 
-        self.validator
-            .validate_object(Some(object_id), &object_root, None, fixity_check)
+        let mut result =
+            self.validator
+                .validate_object(Some(object_id), &object_root, None, fixity_check)?;
+
+        if let Some(layout) = &self.storage_layout {
+            self.validator.validate_no_duplicate_object(
+                object_id,
+                &object_root,
+                &layout.candidate_object_roots(object_id),
+                &mut result,
+            )?;
+        }
+
+        Ok(result)
     }
 
     /// Validates the specified object at the specified path, relative the storage root, and
@@ -503,22 +1499,25 @@ impl OcflStore for FsOcflStore {
     fn validate_object_at(
         &self,
         object_root: &str,
-        fixity_check: bool,
+        fixity_check: FixityCheck,
     ) -> Result<ObjectValidationResult> {
         self.validator
             .validate_object(None, object_root, None, fixity_check)
     }
 
     /// Validates the structure of an OCFL repository as well as all of the objects in the repository
-    /// When `fixity_check` is `false`, then the digests of object content files are not validated.
+    /// `fixity_check` determines which object content files have their digests validated.
     ///
     /// The storage root is validated immediately, and an incremental validator is returned that
     /// is used to lazily validate the rest of the repository.
     fn validate_repo<'a>(
         &'a self,
-        fixity_check: bool,
+        fixity_check: FixityCheck,
+        filter: ValidationFilter,
     ) -> Result<Box<dyn IncrementalValidator + 'a>> {
-        Ok(Box::new(self.validator.validate_repo(fixity_check)?))
+        Ok(Box::new(
+            self.validator.validate_repo(fixity_check, filter)?,
+        ))
     }
 
     /// Returns details about an OCFL repository
@@ -536,6 +1535,48 @@ impl OcflStore for FsOcflStore {
         Ok(RepoInfo::new(version, layout, extensions))
     }
 
+    /// Returns the repository's storage layout configuration, and any discrepancies between the
+    /// layout declared in `ocfl_layout.json` and the extension configs in `extensions`.
+    fn verify_layout(&self) -> Result<LayoutInfo> {
+        self.ensure_open()?;
+        super::verify_layout(&FsStorage::new(&self.storage_root))
+    }
+
+    /// Checks the storage root's namaste file and storage layout for problems
+    fn check_storage_root(&self) -> Result<Vec<HealthProblem>> {
+        self.ensure_open()?;
+        super::check_storage_root(&FsStorage::new(&self.storage_root))
+    }
+
+    /// Checks the object's root for version directories that are not in its inventory, and for
+    /// a missing root inventory sidecar
+    fn check_object_root(&self, inventory: &Inventory) -> Result<Vec<HealthProblem>> {
+        self.ensure_open()?;
+        super::check_object_root(&FsStorage::new(&self.storage_root), inventory)
+    }
+
+    /// Rewrites the description in the repository's `ocfl_layout.json`
+    fn set_layout_description(&self, description: &str) -> Result<()> {
+        self.ensure_open()?;
+
+        let layout_file = paths::ocfl_layout_path(&self.storage_root);
+
+        if !layout_file.exists() {
+            return Err(RocflError::NotFound(format!(
+                "The repository at {} does not contain an {} file",
+                canonical_str(&self.storage_root),
+                OCFL_LAYOUT_FILE
+            )));
+        }
+
+        let mut layout: OcflLayoutLenient = parse_layout_file(&layout_file)?;
+        layout.description = description.to_string();
+
+        serde_json::to_writer_pretty(File::create(&layout_file)?, &layout)?;
+
+        Ok(())
+    }
+
     /// Returns details about an OCFL object
     fn describe_object(&self, object_id: &str) -> Result<ObjectInfo> {
         self.ensure_open()?;
@@ -543,23 +1584,30 @@ impl OcflStore for FsOcflStore {
         let object_root = self
             .lookup_or_find_object_root_path(object_id)
             .map_err(|_| not_found(object_id, None))?;
-        let storage_path = self.storage_root.join(&object_root);
+        let storage_path = self.storage_root.join(util::native_path(&object_root));
 
         let version = find_first_version_declaration(OBJECT_NAMASTE_FILE_PREFIX, &storage_path)
             .map_err(|_| not_found(object_id, None))?;
         let extensions = self.list_object_extensions(object_id)?;
 
-        let algorithm = if SUPPORTED_VERSIONS.contains(&version.as_str()) {
-            Some(
-                self.get_inventory_by_path(object_id, &object_root)?
-                    .digest_algorithm
-                    .to_string(),
-            )
+        let inventory = if SUPPORTED_VERSIONS.contains(&version.as_str()) {
+            Some(self.get_inventory_by_path(object_id, &object_root)?)
+        } else {
+            None
+        };
+
+        let frozen = if extensions.iter().any(|ext| ext == ROCFL_FROZEN_EXTENSION) {
+            self.get_freeze_marker(object_id)?
         } else {
             None
         };
 
-        Ok(ObjectInfo::new(version, algorithm, extensions))
+        Ok(ObjectInfo::new(
+            version,
+            inventory.as_ref(),
+            extensions,
+            frozen,
+        ))
     }
 
     /// Upgrades the repository to the specified version
@@ -568,7 +1616,7 @@ impl OcflStore for FsOcflStore {
 
         let old_namastes = find_files(&self.storage_root, ROOT_NAMASTE_FILE_PREFIX)?;
 
-        write_namaste_and_spec(&self.storage_root, version)?;
+        write_namaste_and_spec(&self.storage_root, version, true)?;
 
         for old in old_namastes {
             util::remove_file_ignore_not_found(self.storage_root.join(old))?;
@@ -577,6 +1625,248 @@ impl OcflStore for FsOcflStore {
         Ok(())
     }
 
+    /// Adds the existing object rooted at `object_root`, relative the storage root, to the
+    /// repository's object index. Indexed objects are located by ID, even when they do not reside
+    /// where the storage layout expects them, or the repository does not have a storage layout.
+    ///
+    /// The adopted object's inventory is returned.
+    fn adopt_object(&self, object_root: &str) -> Result<Inventory> {
+        self.ensure_open()?;
+
+        let object_root = util::trim_slashes(object_root);
+        let storage_path = self.storage_root.join(util::native_path(object_root));
+
+        if !storage_path.is_dir() || !is_object_root(&storage_path)? {
+            return Err(RocflError::NotFound(format!(
+                "Object at path {}",
+                object_root
+            )));
+        }
+
+        let inventory = parse_inventory(&storage_path, &self.storage_root)?;
+
+        if let Some(existing_root) = self.get_object_root_path(&inventory.id) {
+            if existing_root != object_root
+                && is_object_root(self.storage_root.join(util::native_path(&existing_root)))
+                    .unwrap_or(false)
+            {
+                return Err(RocflError::IllegalState(format!(
+                    "Cannot adopt object {} at {} because it already exists at {}",
+                    inventory.id, object_root, existing_root
+                )));
+            }
+        }
+
+        info!("Adopting object {} at {}", inventory.id, object_root);
+
+        self.index_object(&inventory.id, object_root)?;
+
+        Ok(inventory)
+    }
+
+    /// Returns `true` if the object index is a registry that records the location of every object
+    /// in the repository
+    fn is_object_registry(&self) -> bool {
+        self.object_registry.load(Ordering::Acquire)
+    }
+
+    /// Enables or disables the object registry. Enabling the registry scans the repository once
+    /// to index its existing objects. The number of objects in the object index is returned.
+    fn set_object_registry(&self, enabled: bool) -> Result<usize> {
+        self.ensure_open()?;
+
+        let index_file = paths::object_index_path(&self.storage_root);
+        let _lock = self.lock_indexes()?;
+        let mut index = self
+            .object_index
+            .write()
+            .map_err(|_| object_index_lock_error())?;
+        *index = read_index(&index_file)?;
+
+        if enabled {
+            info!("Indexing every object in the repository");
+
+            for inventory in InventoryIter::new(&self.storage_root, None, self.closed.clone())? {
+                match inventory {
+                    Ok(inventory) => {
+                        index.insert(&inventory.id, &inventory.object_root);
+                    }
+                    Err(e) => warn!("Skipping object that could not be indexed: {:#}", e),
+                }
+            }
+
+            self.ensure_open()?;
+        } else if let Some(layout) = &self.storage_layout {
+            // Objects that are where the layout expects them do not need to be indexed
+            index
+                .remove_if(|object_id, object_root| layout.map_object_id(object_id) == object_root);
+        }
+
+        write_index(&index_file, &*index)?;
+        write_index(
+            &paths::object_index_config_path(&self.storage_root),
+            &ObjectIndexConfig { registry: enabled },
+        )?;
+        self.object_registry.store(enabled, Ordering::Release);
+
+        Ok(index.len())
+    }
+
+    /// Returns the ID of the object that the alias identifies, or `None` if the alias is not in
+    /// the repository's alias index.
+    fn resolve_alias(&self, alias: &str) -> Result<Option<String>> {
+        self.ensure_open()?;
+
+        let index = self.alias_index.read().map_err(|_| alias_lock_error())?;
+        Ok(index.get(alias).cloned())
+    }
+
+    /// Adds an alias for the object to the repository's alias index. An alias may only identify
+    /// a single object. It is the caller's responsibility to verify that the object exists.
+    fn add_alias(&self, object_id: &str, alias: &str) -> Result<()> {
+        self.ensure_open()?;
+
+        let index_file = paths::alias_index_path(&self.storage_root);
+        let _lock = self.lock_indexes()?;
+        let mut index = self.alias_index.write().map_err(|_| alias_lock_error())?;
+        *index = read_index(&index_file)?;
+
+        if index.insert(alias, object_id)? {
+            info!("Adding alias {} for object {}", alias, object_id);
+            write_index(&index_file, &*index)?;
+        }
+
+        Ok(())
+    }
+
+    /// Removes an alias from the repository's alias index. If the alias does not exist, then
+    /// `RocflError::NotFound` is returned.
+    fn remove_alias(&self, alias: &str) -> Result<()> {
+        self.ensure_open()?;
+
+        let index_file = paths::alias_index_path(&self.storage_root);
+        let _lock = self.lock_indexes()?;
+        let mut index = self.alias_index.write().map_err(|_| alias_lock_error())?;
+        *index = read_index(&index_file)?;
+
+        if index.remove(alias) {
+            info!("Removing alias {}", alias);
+            write_index(&index_file, &*index)
+        } else {
+            Err(RocflError::NotFound(format!("Alias {}", alias)))
+        }
+    }
+
+    /// Returns all of the aliases in the repository's alias index, sorted by alias
+    fn list_aliases(&self) -> Result<Vec<ObjectAlias>> {
+        self.ensure_open()?;
+
+        let index = self.alias_index.read().map_err(|_| alias_lock_error())?;
+        Ok(index.aliases())
+    }
+
+    /// Returns the defaults that are applied to objects created in the repository. If none
+    /// have been configured, then empty defaults are returned.
+    fn get_repo_defaults(&self) -> Result<RepoDefaults> {
+        self.ensure_open()?;
+
+        let defaults_file = paths::repo_defaults_path(&self.storage_root);
+        if !defaults_file.exists() {
+            return Ok(RepoDefaults::default());
+        }
+
+        Ok(serde_json::from_slice(&file_to_bytes(&defaults_file)?)?)
+    }
+
+    /// Writes the defaults that are applied to objects created in the repository, replacing any
+    /// existing defaults
+    fn set_repo_defaults(&self, defaults: &RepoDefaults) -> Result<()> {
+        self.ensure_open()?;
+
+        info!("Writing repository defaults: {:?}", defaults);
+        let _lock = self.lock_indexes()?;
+        write_index(&paths::repo_defaults_path(&self.storage_root), defaults)
+    }
+
+    /// Returns the repository's validation history. If no validations have been recorded, then
+    /// an empty history is returned.
+    fn get_validation_history(&self) -> Result<ValidationHistory> {
+        self.ensure_open()?;
+
+        let history_file = paths::validation_history_path(&self.storage_root);
+        if !history_file.exists() {
+            return Ok(ValidationHistory::default());
+        }
+
+        Ok(serde_json::from_slice(&file_to_bytes(&history_file)?)?)
+    }
+
+    /// Writes the repository's validation history, replacing the existing history
+    fn set_validation_history(&self, history: &ValidationHistory) -> Result<()> {
+        self.ensure_open()?;
+
+        info!("Writing validation history");
+        let _lock = self.lock_indexes()?;
+        write_index(&paths::validation_history_path(&self.storage_root), history)
+    }
+
+    /// Returns the object's freeze marker, or `None` if the object is not frozen
+    fn get_freeze_marker(&self, object_id: &str) -> Result<Option<FreezeMarker>> {
+        self.ensure_open()?;
+
+        let object_root = self.require_object_root_path(object_id)?;
+        let marker_file =
+            paths::frozen_marker_path(self.storage_root.join(util::native_path(&object_root)));
+
+        if !marker_file.exists() {
+            return Ok(None);
+        }
+
+        Ok(Some(serde_json::from_slice(&file_to_bytes(&marker_file)?)?))
+    }
+
+    /// Reads the object's root inventory sidecar. Objects on disk do not have an ETag.
+    fn get_object_fingerprint(&self, object_id: &str) -> Result<ObjectFingerprint> {
+        self.ensure_open()?;
+
+        let object_root = self.require_object_root_path(object_id)?;
+        let object_path = self.storage_root.join(util::native_path(&object_root));
+
+        for algorithm in SIDECAR_ALGORITHMS {
+            let sidecar = object_path.join(paths::sidecar_name(algorithm));
+            if sidecar.is_file() {
+                return parse_inventory_sidecar(object_id, algorithm, &file_to_bytes(&sidecar)?);
+            }
+        }
+
+        Err(missing_sidecar_error(object_id))
+    }
+
+    /// Writes the object's freeze marker, or removes it if `marker` is `None`
+    fn set_freeze_marker(&self, object_id: &str, marker: Option<&FreezeMarker>) -> Result<()> {
+        self.ensure_open()?;
+
+        let object_root = self.require_object_root_path(object_id)?;
+        let marker_file =
+            paths::frozen_marker_path(self.storage_root.join(util::native_path(&object_root)));
+
+        match marker {
+            Some(marker) => {
+                info!("Writing freeze marker for object {}", object_id);
+                write_index(&marker_file, marker)
+            }
+            None => {
+                info!("Removing freeze marker for object {}", object_id);
+                util::remove_file_ignore_not_found(&marker_file)?;
+                let extension_dir = marker_file.parent().unwrap();
+                if extension_dir.exists() {
+                    util::clean_dirs_up(extension_dir)?;
+                }
+                Ok(())
+            }
+        }
+    }
+
     /// Instructs the store to gracefully stop any in-flight work and not accept any additional
     /// requests.
     fn close(&self) {
@@ -607,7 +1897,9 @@ impl StagingStore for FsOcflStore {
         let object_root = self.require_layout()?.map_object_id(&inventory.id);
         inventory.object_root = object_root;
 
-        let storage_path = self.storage_root.join(&inventory.object_root);
+        let storage_path = self
+            .storage_root
+            .join(util::native_path(&inventory.object_root));
         inventory.storage_path =
             util::convert_forwardslash_to_back(&storage_path.to_string_lossy()).into();
 
@@ -626,15 +1918,11 @@ impl StagingStore for FsOcflStore {
         source: &mut impl Read,
         logical_path: &LogicalPath,
     ) -> Result<()> {
-        let content_path = inventory.new_content_path(logical_path);
-
-        let mut storage_path = PathBuf::from(&inventory.storage_path);
-        storage_path.push(content_path.as_path());
-
-        fs::create_dir_all(storage_path.parent().unwrap())?;
-        io::copy(source, &mut File::create(&storage_path)?)?;
-
-        Ok(())
+        self.stage_content_copy(
+            &inventory.storage_path,
+            &inventory.new_content_path(logical_path),
+            source,
+        )
     }
 
     /// Copies an existing staged file to a new location
@@ -648,13 +1936,12 @@ impl StagingStore for FsOcflStore {
 
         let dst_content = inventory.new_content_path(dst_logical);
 
-        let src_storage = object_root.join(src_content.as_path());
-        let dst_storage = object_root.join(dst_content.as_path());
-
-        fs::create_dir_all(dst_storage.parent().unwrap())?;
-        fs::copy(&src_storage, &dst_storage)?;
+        let src_storage = object_root.join(util::native_path(src_content.as_str()));
 
-        Ok(())
+        self.stage_content_write(&inventory.storage_path, &dst_content, |file| {
+            io::copy(&mut File::open(&src_storage)?, file)?;
+            Ok(())
+        })
     }
 
     /// Moves a file in the staging area
@@ -667,10 +1954,10 @@ impl StagingStore for FsOcflStore {
         let content_path = inventory.new_content_path(logical_path);
 
         let mut storage_path = PathBuf::from(&inventory.storage_path);
-        storage_path.push(content_path.as_path());
+        storage_path.push(util::native_path(content_path.as_str()));
 
         fs::create_dir_all(storage_path.parent().unwrap())?;
-        fs::rename(source, &storage_path)?;
+        util::move_path(source, &storage_path)?;
 
         Ok(())
     }
@@ -686,11 +1973,11 @@ impl StagingStore for FsOcflStore {
 
         let dst_content = inventory.new_content_path(dst_logical);
 
-        let src_storage = object_root.join(src_content.as_path());
-        let dst_storage = object_root.join(dst_content.as_path());
+        let src_storage = object_root.join(util::native_path(src_content.as_str()));
+        let dst_storage = object_root.join(util::native_path(dst_content.as_str()));
 
         fs::create_dir_all(dst_storage.parent().unwrap())?;
-        fs::rename(&src_storage, &dst_storage)?;
+        util::move_path(&src_storage, &dst_storage)?;
         util::clean_dirs_up(src_storage.parent().unwrap())?;
 
         Ok(())
@@ -701,7 +1988,7 @@ impl StagingStore for FsOcflStore {
         let object_root = PathBuf::from(&inventory.storage_path);
 
         for path in paths.iter() {
-            let full_path = object_root.join(path.as_path());
+            let full_path = object_root.join(util::native_path(path.as_str()));
             info!("Deleting staged file: {}", full_path.to_string_lossy());
             util::remove_file_ignore_not_found(&full_path)?;
             util::clean_dirs_up(full_path.parent().unwrap())?;
@@ -774,10 +2061,24 @@ impl StagingStore for FsOcflStore {
 
         Ok(())
     }
+
+    /// Validates the object's staged version prior to committing it. `committed_head` is the
+    /// object's HEAD version in the main repository, or `None` if the object is new.
+    fn validate_staged_version(
+        &self,
+        inventory: &Inventory,
+        committed_head: Option<VersionNum>,
+    ) -> Result<ObjectValidationResult> {
+        self.validator
+            .validate_staged_version(inventory, committed_head)
+    }
 }
 
 type IdMatcher = Box<dyn Fn(&str) -> bool>;
 
+/// The content added in a version, grouped by the content index shard its digest belongs in
+type ContentShards = BTreeMap<PathBuf, Vec<(Rc<HexDigest>, Rc<ContentPath>)>>;
+
 /// Iterates over ever object in an OCFL repository by walking the file tree.
 struct InventoryIter {
     root: PathBuf,
@@ -828,60 +2129,39 @@ impl InventoryIter {
         })
     }
 
-    fn create_if_matches<P: AsRef<Path>>(&self, object_root: P) -> Option<Result<Inventory>> {
+    fn create_if_matches<P: AsRef<Path>>(
+        &self,
+        object_root: P,
+    ) -> Option<Result<Inventory, ObjectError>> {
         let inventory_path = paths::inventory_path(&object_root);
-
-        if self.id_matcher.is_some() {
-            match self.extract_object_id(&inventory_path) {
-                Some(Ok(object_id)) => {
-                    if self.id_matcher.as_ref().unwrap().deref()(&object_id) {
-                        Some(parse_inventory(object_root, &self.root))
+        let root_display = object_root.as_ref().to_string_lossy().to_string();
+
+        if let Some(id_matcher) = &self.id_matcher {
+            match extract_object_id(&inventory_path) {
+                Ok(object_id) => {
+                    if id_matcher.deref()(&object_id) {
+                        Some(
+                            parse_inventory(object_root, &self.root).map_err(|e| {
+                                ObjectError::new(Some(object_id), Some(root_display), e)
+                            }),
+                        )
                     } else {
                         None
                     }
                 }
-                Some(Err(e)) => Some(Err(e)),
-                None => None,
+                Err(e) => Some(Err(ObjectError::at_path(root_display, e))),
             }
         } else {
-            Some(parse_inventory(object_root, &self.root))
-        }
-    }
-
-    fn extract_object_id<P: AsRef<Path>>(&self, path: P) -> Option<Result<String>> {
-        let mut matches: Vec<String> = vec![];
-
-        let result = Searcher::new().search_path(
-            &*OBJECT_ID_MATCHER,
-            &path,
-            UTF8(|_, line| {
-                let mut captures = OBJECT_ID_MATCHER.new_captures()?;
-                OBJECT_ID_MATCHER.captures(line.as_bytes(), &mut captures)?;
-                matches.push(line[captures.get(1).unwrap()].to_string());
-                Ok(true)
-            }),
-        );
-
-        if let Err(e) = result {
-            Some(Err(RocflError::General(format!(
-                "Failed to locate object ID in inventory at {}: {:#}",
-                path.as_ref().display(),
-                e
-            ))))
-        } else {
-            match matches.get(0) {
-                Some(id) => Some(Ok(id.to_string())),
-                None => Some(Err(RocflError::General(format!(
-                    "Failed to locate object ID in inventory at {}",
-                    path.as_ref().display()
-                )))),
-            }
+            Some(
+                parse_inventory(object_root, &self.root)
+                    .map_err(|e| ObjectError::at_path(root_display, e)),
+            )
         }
     }
 }
 
 impl Iterator for InventoryIter {
-    type Item = Result<Inventory>;
+    type Item = Result<Inventory, ObjectError>;
 
     fn next(&mut self) -> Option<Self::Item> {
         loop {
@@ -902,11 +2182,16 @@ impl Iterator for InventoryIter {
                 None => {
                     self.current.replace(None);
                 }
-                Some(Err(e)) => return Some(Err(e.into())),
+                Some(Err(e)) => return Some(Err(RocflError::from(e).into())),
                 Some(Ok(entry)) => {
                     let ftype = match entry.file_type() {
                         Ok(ftype) => ftype,
-                        Err(e) => return Some(Err(e.into())),
+                        Err(e) => {
+                            return Some(Err(ObjectError::at_path(
+                                entry.path().to_string_lossy(),
+                                e,
+                            )))
+                        }
                     };
 
                     if ftype.is_dir() {
@@ -918,7 +2203,9 @@ impl Iterator for InventoryIter {
 
                         let is_object_root = match is_object_root(&path) {
                             Ok(is_object_root) => is_object_root,
-                            Err(e) => return Some(Err(e)),
+                            Err(e) => {
+                                return Some(Err(ObjectError::at_path(path.to_string_lossy(), e)))
+                            }
                         };
 
                         if is_object_root {
@@ -933,7 +2220,12 @@ impl Iterator for InventoryIter {
                                 Ok(next) => {
                                     self.current.replace(Some(next));
                                 }
-                                Err(e) => return Some(Err(e.into())),
+                                Err(e) => {
+                                    return Some(Err(ObjectError::at_path(
+                                        path.to_string_lossy(),
+                                        e,
+                                    )))
+                                }
                             }
                         }
                     }
@@ -946,7 +2238,7 @@ impl Iterator for InventoryIter {
 impl FsStorage {
     pub fn new(storage_root: impl AsRef<Path>) -> Self {
         Self {
-            storage_root: storage_root.as_ref().to_path_buf(),
+            storage_root: util::extended_length_path(storage_root),
         }
     }
 }
@@ -954,16 +2246,19 @@ impl FsStorage {
 impl Storage for FsStorage {
     /// Reads the file at the specified path and writes its contents to the provided sink.
     fn read<W: Write>(&self, path: &str, sink: &mut W) -> Result<()> {
-        io::copy(&mut File::open(self.storage_root.join(path))?, sink)?;
+        io::copy(
+            &mut File::open(self.storage_root.join(util::native_path(path)))?,
+            sink,
+        )?;
         Ok(())
     }
 
     /// Lists the contents of the specified directory. If `recursive` is `true`, then all leaf-nodes
     /// are returned. If the directory does not exist, or is empty, then an empty vector is returned.
     /// The returned paths are all relative the directory that was listed.
-    fn list(&self, path: &str, recursive: bool) -> Result<Vec<Listing>> {
+    fn list(&self, path: &str, recursive: bool) -> Result<Vec<Listing<'_>>> {
         let mut listings = Vec::new();
-        let root = self.storage_root.join(path);
+        let root = self.storage_root.join(util::native_path(path));
 
         if fs::metadata(&root).is_err() {
             return Ok(listings);
@@ -1009,6 +2304,38 @@ impl Storage for FsStorage {
     }
 }
 
+/// Reads an object's ID from its inventory without parsing the rest of the inventory
+fn extract_object_id<P: AsRef<Path>>(path: P) -> Result<String> {
+    let mut matches: Vec<String> = vec![];
+
+    let result = Searcher::new().search_path(
+        &*OBJECT_ID_MATCHER,
+        &path,
+        UTF8(|_, line| {
+            let mut captures = OBJECT_ID_MATCHER.new_captures()?;
+            OBJECT_ID_MATCHER.captures(line.as_bytes(), &mut captures)?;
+            matches.push(line[captures.get(1).unwrap()].to_string());
+            Ok(true)
+        }),
+    );
+
+    if let Err(e) = result {
+        Err(RocflError::General(format!(
+            "Failed to locate object ID in inventory at {}: {:#}",
+            path.as_ref().display(),
+            e
+        )))
+    } else {
+        match matches.first() {
+            Some(id) => Ok(id.to_string()),
+            None => Err(RocflError::General(format!(
+                "Failed to locate object ID in inventory at {}",
+                path.as_ref().display()
+            ))),
+        }
+    }
+}
+
 /// Returns true if the path contains an OCFL object root marker file
 fn is_object_root<P: AsRef<Path>>(path: P) -> Result<bool> {
     for entry in fs::read_dir(path)? {
@@ -1018,7 +2345,7 @@ fn is_object_root<P: AsRef<Path>>(path: P) -> Result<bool> {
                 .file_name()
                 .unwrap_or_default()
                 .to_str()
-                .map_or(false, |name| name.starts_with(OBJECT_NAMASTE_FILE_PREFIX))
+                .is_some_and(|name| name.starts_with(OBJECT_NAMASTE_FILE_PREFIX))
         {
             return Ok(true);
         }
@@ -1026,6 +2353,106 @@ fn is_object_root<P: AsRef<Path>>(path: P) -> Result<bool> {
     Ok(false)
 }
 
+/// Replaces `dst` with a hard link to `src`. If the link cannot be created, then `dst` is left
+/// unchanged and `false` is returned.
+/// Returns `true` if `src` is a different file than `dst`, with the same size, and its content
+/// has the expected digest. `dst` is assumed to have the expected digest.
+fn is_same_content(src: &Path, dst: &Path, algorithm: DigestAlgorithm, digest: &HexDigest) -> bool {
+    let (src_meta, dst_meta) = match (fs::metadata(src), fs::metadata(dst)) {
+        (Ok(src_meta), Ok(dst_meta)) => (src_meta, dst_meta),
+        _ => return false,
+    };
+
+    if !src_meta.is_file() || src_meta.len() != dst_meta.len() {
+        return false;
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        if src_meta.dev() == dst_meta.dev() && src_meta.ino() == dst_meta.ino() {
+            return false;
+        }
+    }
+
+    match File::open(src)
+        .map_err(RocflError::from)
+        .and_then(|mut file| algorithm.hash_hex(&mut file))
+    {
+        Ok(actual) => actual == *digest,
+        Err(e) => {
+            info!(
+                "Not linking to {} because it could not be read: {}",
+                src.to_string_lossy(),
+                e
+            );
+            false
+        }
+    }
+}
+
+fn hard_link_or_keep(src: &Path, dst: &Path) -> bool {
+    let mut link_name = dst.file_name().unwrap_or_default().to_os_string();
+    link_name.push(".rocfl-link");
+    let link = dst.with_file_name(link_name);
+
+    if let Err(e) = fs::hard_link(src, &link) {
+        info!(
+            "Keeping copy of {} because it could not be linked to {}: {}",
+            dst.to_string_lossy(),
+            src.to_string_lossy(),
+            e
+        );
+        return false;
+    }
+
+    if let Err(e) = fs::rename(&link, dst) {
+        warn!(
+            "Keeping copy of {} because it could not be replaced with a link: {}",
+            dst.to_string_lossy(),
+            e
+        );
+        let _ = fs::remove_file(&link);
+        return false;
+    }
+
+    true
+}
+
+/// Returns the paths, relative the object root, of all of the files within the object's version
+/// content directories that are not referenced in its manifest
+fn find_orphaned_content(inventory: &Inventory) -> Result<Vec<String>> {
+    let object_root = PathBuf::from(&inventory.storage_path);
+    let mut orphans = Vec::new();
+
+    for version_num in inventory.versions.keys() {
+        let content_dir = paths::content_path(&object_root, *version_num, inventory);
+
+        if !content_dir.exists() {
+            continue;
+        }
+
+        for entry in WalkDir::new(&content_dir) {
+            let entry = entry?;
+            if entry.file_type().is_file() {
+                let relative = entry.path().strip_prefix(&object_root).unwrap();
+                let path =
+                    util::convert_backslash_to_forward(&relative.to_string_lossy()).into_owned();
+                let referenced = ContentPath::try_from(path.as_str())
+                    .map(|content_path| inventory.contains_content_path(&content_path))
+                    .unwrap_or(false);
+                if !referenced {
+                    orphans.push(path);
+                }
+            }
+        }
+    }
+
+    orphans.sort_unstable();
+
+    Ok(orphans)
+}
+
 /// Parses the HEAD inventory of the OCFL object that's rooted in the specified directory.
 /// This is normally the `inventory.json` file in the object's root, but it could also be
 /// the inventory file in an extension directory, such as the mutable HEAD extension.
@@ -1107,6 +2534,59 @@ fn check_extensions(storage_root: impl AsRef<Path>) {
     }
 }
 
+/// Loads an index, such as the object index, from the specified file, if it exists. An empty
+/// index is returned if it does not exist or cannot be read.
+fn load_index<T: DeserializeOwned + Default>(index_file: PathBuf, name: &str) -> T {
+    if !index_file.exists() {
+        return T::default();
+    }
+
+    match read_index(&index_file) {
+        Ok(index) => {
+            info!("Loaded {} at {}", name, index_file.to_string_lossy());
+            index
+        }
+        Err(e) => {
+            error!(
+                "Failed to load {} at {}: {:#}",
+                name,
+                index_file.to_string_lossy(),
+                e
+            );
+            T::default()
+        }
+    }
+}
+
+/// Reads an index, such as the object index, from the specified file. An empty index is
+/// returned if it does not exist.
+fn read_index<T: DeserializeOwned + Default>(index_file: &Path) -> Result<T> {
+    if !index_file.exists() {
+        return Ok(T::default());
+    }
+
+    Ok(serde_json::from_slice(&file_to_bytes(index_file)?)?)
+}
+
+/// Writes an index to a temporary file in the same directory, and then renames it into place,
+/// so that the index is never partially written
+fn write_index<T: Serialize>(index_file: &Path, index: &T) -> Result<()> {
+    fs::create_dir_all(index_file.parent().unwrap())?;
+
+    let mut part = index_file.as_os_str().to_owned();
+    part.push(".part");
+    let part = PathBuf::from(part);
+
+    let mut file = File::create(&part)?;
+    serde_json::to_writer_pretty(&mut file, index)?;
+    file.write_all(b"\n")?;
+    file.sync_all()?;
+
+    fs::rename(&part, index_file)?;
+
+    Ok(())
+}
+
 fn load_storage_layout<P: AsRef<Path>>(storage_root: P) -> Option<StorageLayout> {
     let layout = parse_layout(&storage_root);
 
@@ -1117,17 +2597,13 @@ fn load_storage_layout<P: AsRef<Path>>(storage_root: P) -> Option<StorageLayout>
 
             match storage_layout {
                 Ok(storage_layout) => {
-                    info!(
-                        "Loaded storage layout extension {}",
-                        layout.extension.to_string()
-                    );
+                    info!("Loaded storage layout extension {}", layout.extension);
                     Some(storage_layout)
                 }
                 Err(e) => {
                     error!(
                         "Failed to load storage layout extension {}: {:#}",
-                        layout.extension.to_string(),
-                        e
+                        layout.extension, e
                     );
                     None
                 }
@@ -1137,6 +2613,48 @@ fn load_storage_layout<P: AsRef<Path>>(storage_root: P) -> Option<StorageLayout>
     }
 }
 
+/// Parses and verifies the storage root's `ocfl_layout.json` file and its extension config, if
+/// they exist. Unlike `load_storage_layout()`, problems are returned as errors.
+fn verify_existing_layout(storage_root: impl AsRef<Path>) -> Result<Option<StorageLayout>> {
+    let layout_file = paths::ocfl_layout_path(&storage_root);
+
+    if !layout_file.exists() {
+        return Ok(None);
+    }
+
+    let layout: OcflLayoutLenient = parse_layout_file(&layout_file).map_err(|e| {
+        RocflError::IllegalState(format!(
+            "Failed to parse OCFL layout file at {}: {:#}",
+            layout_file.to_string_lossy(),
+            e
+        ))
+    })?;
+
+    let extension = LayoutExtensionName::from_str(&layout.extension).map_err(|_| {
+        RocflError::IllegalState(format!(
+            "Storage layout extension {} is not supported",
+            layout.extension
+        ))
+    })?;
+
+    let config_bytes = read_layout_config(
+        &storage_root,
+        &OcflLayout {
+            extension,
+            description: layout.description,
+        },
+    );
+
+    let storage_layout = StorageLayout::new(extension, config_bytes.as_deref()).map_err(|e| {
+        RocflError::IllegalState(format!(
+            "Invalid storage layout extension {} config: {:#}",
+            extension, e
+        ))
+    })?;
+
+    Ok(Some(storage_layout))
+}
+
 /// Parses the `ocfl_layout.json` file if it exists
 fn parse_layout<P: AsRef<Path>, T: DeserializeOwned>(storage_root: P) -> Option<T> {
     let layout_file = paths::ocfl_layout_path(&storage_root);
@@ -1196,6 +2714,7 @@ fn init_new_repo(
     root: impl AsRef<Path>,
     version: SpecVersion,
     layout: Option<&StorageLayout>,
+    spec_copy: bool,
 ) -> Result<()> {
     let root = root.as_ref().to_path_buf();
 
@@ -1219,7 +2738,7 @@ fn init_new_repo(
 
     fs::create_dir_all(&root)?;
 
-    write_namaste_and_spec(&root, version)?;
+    write_namaste_and_spec(&root, version, spec_copy)?;
 
     if let Some(layout) = layout {
         write_layout_config(&root, layout)?;
@@ -1228,17 +2747,22 @@ fn init_new_repo(
     Ok(())
 }
 
-fn write_namaste_and_spec(root: impl AsRef<Path>, version: SpecVersion) -> Result<()> {
+fn write_namaste_and_spec(
+    root: impl AsRef<Path>,
+    version: SpecVersion,
+    spec_copy: bool,
+) -> Result<()> {
     write!(
         File::create(paths::root_namaste_path(&root, version))?,
         "{}",
         version.root_namaste().content
     )?;
 
-    let spec = match version {
-        SpecVersion::Ocfl1_0 => specs::OCFL_1_0_SPEC,
-        SpecVersion::Ocfl1_1 => specs::OCFL_1_1_SPEC,
-    };
+    if !spec_copy {
+        return Ok(());
+    }
+
+    let spec = specs::ocfl_spec(version);
 
     write!(
         File::create(paths::ocfl_spec_path(&root, version))?,
@@ -1267,13 +2791,7 @@ fn write_layout_config(root: impl AsRef<Path>, layout: &StorageLayout) -> Result
 
     File::create(layout_ext_dir.join(EXTENSIONS_CONFIG_FILE))?.write_all(&layout.serialize()?)?;
 
-    let extension_spec = match layout.extension_name() {
-        LayoutExtensionName::FlatDirectLayout => specs::EXT_0002_SPEC,
-        LayoutExtensionName::HashedNTupleObjectIdLayout => specs::EXT_0003_SPEC,
-        LayoutExtensionName::HashedNTupleLayout => specs::EXT_0004_SPEC,
-        LayoutExtensionName::FlatOmitPrefixLayout => specs::EXT_0006_SPEC,
-        LayoutExtensionName::NTupleOmitPrefixLayout => specs::EXT_0007_SPEC,
-    };
+    let extension_spec = specs::layout_extension_spec(layout.extension_name());
 
     write!(
         File::create(root.as_ref().join(format!("{}.md", extension_name)))?,
@@ -1315,7 +2833,7 @@ fn find_files(dir: impl AsRef<Path>, prefix: &str) -> Result<Vec<OsString>> {
         .flatten()
         .map(|entry| entry.file_name())
         .filter(|name| name.len() > prefix.len())
-        .filter(|name| name.to_str().map_or(false, |name| name.starts_with(prefix)))
+        .filter(|name| name.to_str().is_some_and(|name| name.starts_with(prefix)))
         .collect())
 }
 
@@ -1328,7 +2846,7 @@ fn find_first_version_declaration(prefix: &str, dir: impl AsRef<Path>) -> Result
             && entry
                 .file_name()
                 .to_str()
-                .map_or(false, |name| name.starts_with(prefix))
+                .is_some_and(|name| name.starts_with(prefix))
         {
             let version = entry.file_name().to_str().unwrap()[prefix.len()..].to_string();
             return Ok(version);
@@ -20,6 +20,16 @@ pub struct StorageLayout {
     extension: LayoutExtension,
 }
 
+/// The shape of the storage hierarchy that a layout produces, when every object root is at the
+/// same depth. Object roots are the directories directly beneath `number_of_tuples` levels of
+/// directories that are named with `tuple_size` lowercase hex characters.
+#[cfg(feature = "s3")]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub(crate) struct HierarchyShape {
+    tuple_size: usize,
+    number_of_tuples: usize,
+}
+
 /// Enum of known storage layout extensions
 #[derive(Deserialize, Serialize, Debug, Clone, Copy, Eq, PartialEq, EnumString, EnumDisplay)]
 pub enum LayoutExtensionName {
@@ -72,10 +82,22 @@ impl StorageLayout {
     }
 
     /// Maps an object ID to an object root directory
+    ///
+    /// # Panics
+    ///
+    /// Panics if the layout cannot map the object ID. Use `try_map_object_id` when the ID may
+    /// not be supported by the layout.
     pub fn map_object_id(&self, object_id: &str) -> String {
         self.extension.map_object_id(object_id)
     }
 
+    /// Maps an object ID to an object root directory, or returns an error if the layout cannot
+    /// map the ID, such as when a `0007-n-tuple-omit-prefix-storage-layout` ID contains non-ASCII
+    /// characters
+    pub fn try_map_object_id(&self, object_id: &str) -> Result<String> {
+        self.extension.try_map_object_id(object_id)
+    }
+
     /// Returns the extension name of the layout extension in use
     pub fn extension_name(&self) -> LayoutExtensionName {
         self.extension.extension_name()
@@ -84,6 +106,73 @@ impl StorageLayout {
     pub fn serialize(&self) -> Result<Vec<u8>> {
         self.extension.serialize()
     }
+
+    /// Returns the shape of the storage hierarchy the layout produces, or `None` if the depth of
+    /// object roots depends on the object IDs
+    #[cfg(feature = "s3")]
+    pub(crate) fn hierarchy_shape(&self) -> Option<HierarchyShape> {
+        match &self.extension {
+            LayoutExtension::HashedNTuple(ext) => Some(HierarchyShape {
+                tuple_size: ext.config.tuple_size,
+                number_of_tuples: ext.config.number_of_tuples,
+            }),
+            LayoutExtension::HashedNTupleObjectId(ext) => Some(HierarchyShape {
+                tuple_size: ext.config.tuple_size,
+                number_of_tuples: ext.config.number_of_tuples,
+            }),
+            LayoutExtension::FlatDirect(_)
+            | LayoutExtension::FlatOmitPrefix(_)
+            | LayoutExtension::NTupleOmitPrefix(_) => None,
+        }
+    }
+
+    /// Returns the paths that an object may have been stored at. This is the path the object
+    /// maps to using this layout, followed by the paths it maps to using the default
+    /// configurations of the other layouts. Layouts that require a configuration are skipped.
+    pub(crate) fn candidate_object_roots(&self, object_id: &str) -> Vec<String> {
+        let mut roots = vec![self.map_object_id(object_id)];
+
+        for name in [
+            LayoutExtensionName::FlatDirectLayout,
+            LayoutExtensionName::HashedNTupleObjectIdLayout,
+            LayoutExtensionName::HashedNTupleLayout,
+        ] {
+            if let Ok(layout) = StorageLayout::new(name, None) {
+                let root = layout.map_object_id(object_id);
+                if !roots.contains(&root) {
+                    roots.push(root);
+                }
+            }
+        }
+
+        roots
+    }
+}
+
+#[cfg(feature = "s3")]
+impl HierarchyShape {
+    /// The depth of object roots, where directories directly within the storage root have a depth
+    /// of 0
+    pub fn object_depth(&self) -> usize {
+        self.number_of_tuples
+    }
+
+    /// Returns true if the name is a valid tuple directory name
+    pub fn is_tuple(&self, name: &str) -> bool {
+        name.len() == self.tuple_size
+            && name
+                .chars()
+                .all(|c| c.is_ascii_digit() || ('a'..='f').contains(&c))
+    }
+
+    /// Returns true if the storage root relative path is an object root that conforms to the shape
+    pub fn contains_root(&self, object_root: &str) -> bool {
+        let parts: Vec<&str> = object_root.split('/').collect();
+        parts.len() == self.number_of_tuples + 1
+            && parts[..self.number_of_tuples]
+                .iter()
+                .all(|part| self.is_tuple(part))
+    }
 }
 
 /// [Flat Direct Storage Layout Extension](https://ocfl.github.io/extensions/0002-flat-direct-storage-layout.html)
@@ -317,7 +406,7 @@ impl NTupleOmitPrefixLayoutConfig {
         if self.number_of_tuples < 1 || self.number_of_tuples > 32 {
             return Err(RocflError::InvalidConfiguration(format!(
                 "numberOfTuples must be between 1 and 32, inclusive, but was {}.",
-                self.tuple_size
+                self.number_of_tuples
             )));
         }
 
@@ -327,12 +416,19 @@ impl NTupleOmitPrefixLayoutConfig {
 
 impl LayoutExtension {
     fn map_object_id(&self, object_id: &str) -> String {
+        match self.try_map_object_id(object_id) {
+            Ok(path) => path,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    fn try_map_object_id(&self, object_id: &str) -> Result<String> {
         match self {
-            LayoutExtension::FlatDirect(ext) => ext.map_object_id(object_id),
-            LayoutExtension::HashedNTuple(ext) => ext.map_object_id(object_id),
-            LayoutExtension::HashedNTupleObjectId(ext) => ext.map_object_id(object_id),
-            LayoutExtension::FlatOmitPrefix(ext) => ext.map_object_id(object_id),
-            LayoutExtension::NTupleOmitPrefix(ext) => ext.map_object_id(object_id),
+            LayoutExtension::FlatDirect(ext) => Ok(ext.map_object_id(object_id)),
+            LayoutExtension::HashedNTuple(ext) => Ok(ext.map_object_id(object_id)),
+            LayoutExtension::HashedNTupleObjectId(ext) => Ok(ext.map_object_id(object_id)),
+            LayoutExtension::FlatOmitPrefix(ext) => ext.try_map_object_id(object_id),
+            LayoutExtension::NTupleOmitPrefix(ext) => ext.try_map_object_id(object_id),
         }
     }
 
@@ -469,7 +565,8 @@ impl HashedNTupleObjectIdLayoutExtension {
     }
 
     /// Object IDs are hashed and then divided into tuples to create a pair-tree like layout. The
-    /// difference here is that the object encapsulation directory is the url-encoded object ID
+    /// difference here is that the object encapsulation directory is the url-encoded object ID.
+    /// When `tupleSize` is 0, the encapsulation directory is directly within the storage root.
     fn map_object_id(&self, object_id: &str) -> String {
         let digest: String = self
             .config
@@ -478,10 +575,6 @@ impl HashedNTupleObjectIdLayoutExtension {
             .unwrap()
             .into();
 
-        if self.config.tuple_size == 0 {
-            return digest;
-        }
-
         let mut path = to_tuples(
             &digest,
             self.config.tuple_size,
@@ -535,7 +628,14 @@ impl FlatOmitPrefixLayoutExtension {
     }
 
     /// Object IDs have a prefix removed and the remaining part is returned
+    #[cfg(test)]
     fn map_object_id(&self, object_id: &str) -> String {
+        self.try_map_object_id(object_id).unwrap()
+    }
+
+    /// Object IDs have a prefix removed and the remaining part is returned. IDs that end with the
+    /// delimiter cannot be mapped.
+    fn try_map_object_id(&self, object_id: &str) -> Result<String> {
         let test_id = if self.case_matters {
             Cow::Owned(object_id.to_lowercase())
         } else {
@@ -543,15 +643,17 @@ impl FlatOmitPrefixLayoutExtension {
         };
 
         match test_id.rfind(&self.normalized_delimiter) {
-            None => object_id.to_string(),
+            None => Ok(object_id.to_string()),
             Some(index) => {
                 let length = self.normalized_delimiter.len();
                 if object_id.len() == index + length {
-                    // Unfortunately, this needs to panic because I don't want to introduce a Result here
-                    panic!("The id '{}' cannot be mapped to a storage path using layout {} because it ends with the delimiter '{}'",
-                           object_id, self.config.extension_name, self.config.delimiter);
+                    Err(ends_with_delimiter(
+                        object_id,
+                        self.config.extension_name,
+                        &self.config.delimiter,
+                    ))
                 } else {
-                    object_id[index + length..].to_string()
+                    Ok(object_id[index + length..].to_string())
                 }
             }
         }
@@ -591,11 +693,21 @@ impl NTupleOmitPrefixLayoutExtension {
 
     /// Object IDs have a prefix removed and the remaining part turned into an n tuple and returned
     /// as the object root path
+    #[cfg(test)]
     fn map_object_id(&self, object_id: &str) -> String {
+        self.try_map_object_id(object_id).unwrap()
+    }
+
+    /// Object IDs have a prefix removed and the remaining part turned into an n tuple and returned
+    /// as the object root path. IDs that contain non-ASCII characters or end with the delimiter
+    /// cannot be mapped.
+    fn try_map_object_id(&self, object_id: &str) -> Result<String> {
         if !object_id.is_ascii() {
-            // TODO it would seem that map_object_id() needs to return a Result :(
-            panic!("The id '{}' cannot be mapped to a storage path using layout {} because it contains non-ASCII characters",
-                   object_id, self.config.extension_name);
+            return Err(RocflError::InvalidValue(format!(
+                "The id '{}' cannot be mapped to a storage path using layout {} because it \
+                 contains non-ASCII characters",
+                object_id, self.config.extension_name
+            )));
         }
 
         let test_id = if self.case_matters {
@@ -609,9 +721,11 @@ impl NTupleOmitPrefixLayoutExtension {
             Some(index) => {
                 let length = self.normalized_delimiter.len();
                 if object_id.len() == index + length {
-                    // Unfortunately, this needs to panic because I don't want to introduce a Result here
-                    panic!("The id '{}' cannot be mapped to a storage path using layout {} because it ends with the delimiter '{}'",
-                           object_id, self.config.extension_name, self.config.delimiter);
+                    return Err(ends_with_delimiter(
+                        object_id,
+                        self.config.extension_name,
+                        &self.config.delimiter,
+                    ));
                 } else {
                     &object_id[index + length..]
                 }
@@ -635,10 +749,22 @@ impl NTupleOmitPrefixLayoutExtension {
         );
 
         path.push_str(id_part);
-        path
+        Ok(path)
     }
 }
 
+fn ends_with_delimiter(
+    object_id: &str,
+    extension_name: LayoutExtensionName,
+    delimiter: &str,
+) -> RocflError {
+    RocflError::InvalidValue(format!(
+        "The id '{}' cannot be mapped to a storage path using layout {} because it ends with \
+         the delimiter '{}'",
+        object_id, extension_name, delimiter
+    ))
+}
+
 /// Splits the value into N tuples of M size, joined with a /, and ending with a trailing /
 fn to_tuples(value: &str, tuple_size: usize, number_of_tuples: usize) -> String {
     let mut path = String::new();
@@ -658,11 +784,11 @@ fn to_tuples(value: &str, tuple_size: usize, number_of_tuples: usize) -> String
 ///
 /// This method assumes that ALL unicode characters have been percent encoded. It is NOT SAFE
 /// to use on strings that contain unicode.
-fn lower_percent_escape(original: &str) -> Cow<str> {
+fn lower_percent_escape(original: &str) -> Cow<'_, str> {
     if let Some(first) = original.find('%') {
         let start = first + 1;
         let mut out = Vec::with_capacity(original.len());
-        out.extend_from_slice(original[..start].as_bytes());
+        out.extend_from_slice(&original.as_bytes()[..start]);
         let search = original[start..].bytes();
 
         let mut count = 2;
@@ -703,6 +829,20 @@ fn validate_extension_name(
 }
 
 fn validate_tuple_config(tuple_size: usize, number_of_tuples: usize) -> Result<()> {
+    if tuple_size > 32 {
+        return Err(RocflError::InvalidConfiguration(format!(
+            "tupleSize must be between 0 and 32, inclusive, but was {}.",
+            tuple_size
+        )));
+    }
+
+    if number_of_tuples > 32 {
+        return Err(RocflError::InvalidConfiguration(format!(
+            "numberOfTuples must be between 0 and 32, inclusive, but was {}.",
+            number_of_tuples
+        )));
+    }
+
     if (tuple_size == 0 || number_of_tuples == 0) && (tuple_size != 0 || number_of_tuples != 0) {
         Err(RocflError::InvalidConfiguration(format!(
             "If tupleSize (={}) or numberOfTuples (={}) is set to 0, then both must be 0.",
@@ -765,9 +905,12 @@ mod tests {
     use super::{
         lower_percent_escape, HashedNTupleLayoutExtension, HashedNTupleObjectIdLayoutExtension,
     };
+    #[cfg(feature = "s3")]
+    use crate::ocfl::store::layout::HierarchyShape;
     use crate::ocfl::store::layout::{
         FlatOmitPrefixLayoutExtension, NTupleOmitPrefixLayoutExtension, Padding,
     };
+    use crate::ocfl::store::layout::{LayoutExtensionName, StorageLayout};
     use crate::ocfl::Result;
 
     const ID_1: &str = "info:example/test-123";
@@ -857,6 +1000,73 @@ mod tests {
         );
     }
 
+    #[test]
+    fn percent_encode_reserved_and_unicode_chars_0003() {
+        let ext = HashedNTupleObjectIdLayoutExtension::new(None).unwrap();
+
+        assert_eq!(
+            "5a9/e0b/1cb/object%20with%20spaces",
+            ext.map_object_id("object with spaces")
+        );
+        assert_eq!("361/15d/bed/100%25-real", ext.map_object_id("100%-real"));
+        assert_eq!(
+            "ce6/21a/fe4/a%2bb%7ec%2ed%2ae",
+            ext.map_object_id("a+b~c.d*e")
+        );
+        assert_eq!(
+            "b5b/5ce/4e8/ID_With-MixedCASE",
+            ext.map_object_id("ID_With-MixedCASE")
+        );
+        assert_eq!(
+            "e63/98c/e6e/%f0%9f%a6%80%20crab",
+            ext.map_object_id("🦀 crab")
+        );
+        assert_eq!(
+            "375/4d6/cb3/%2e%2e%2f%2e%2e%2fetc%2fpasswd",
+            ext.map_object_id("../../etc/passwd")
+        );
+    }
+
+    #[test]
+    fn truncate_encapsulation_directory_longer_than_100_chars_0003() {
+        let ext = HashedNTupleObjectIdLayoutExtension::new(None).unwrap();
+
+        let exactly_100 = format!("{} ", "a".repeat(97));
+        assert_eq!(
+            format!("f98/18f/6a4/{}%20", "a".repeat(97)),
+            ext.map_object_id(&exactly_100)
+        );
+
+        let over_100 = format!("{} ", "a".repeat(98));
+        assert_eq!(
+            format!(
+                "cd9/b63/83a/{}%2-cd9b6383a4aa4c4b9ee5cfcdf92ce16359a400be583e02fc40342f0ae2235b3f",
+                "a".repeat(98)
+            ),
+            ext.map_object_id(&over_100)
+        );
+    }
+
+    #[test]
+    fn map_id_without_tuples_0003() {
+        let ext = hashed_ntuple_id_ext("sha256", 0, 0).unwrap();
+
+        assert_eq!("info%3aexample%2ftest-123", ext.map_object_id(ID_1));
+        assert_eq!("%2e%2eHor%2frib%3al%c3%a8-%24id", ext.map_object_id(ID_2));
+    }
+
+    #[test]
+    #[should_panic(expected = "tupleSize must be between 0 and 32")]
+    fn fail_0003_init_when_tuple_size_too_large() {
+        let _ = hashed_ntuple_id_ext("sha512", 33, 1).unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "numberOfTuples must be between 0 and 32")]
+    fn fail_0003_init_when_number_of_tuples_too_large() {
+        let _ = hashed_ntuple_id_ext("sha512", 1, 33).unwrap();
+    }
+
     #[test]
     #[should_panic(expected = "unknown variant `md6`")]
     fn fail_0003_init_when_invalid_digest() {
@@ -1210,6 +1420,60 @@ mod tests {
         ext.map_object_id(ID_2);
     }
 
+    #[test]
+    fn try_map_returns_error_when_id_cannot_be_mapped() -> Result<()> {
+        let layout = StorageLayout::new(
+            LayoutExtensionName::NTupleOmitPrefixLayout,
+            Some(br#"{"extensionName": "0007-n-tuple-omit-prefix-storage-layout", "delimiter": ":"}"#),
+        )?;
+
+        assert_eq!("000/000/123/123", layout.try_map_object_id("ns:123")?);
+        assert!(layout.try_map_object_id("ns:").is_err());
+        assert!(layout.try_map_object_id(ID_2).is_err());
+        Ok(())
+    }
+
+    #[cfg(feature = "s3")]
+    #[test]
+    fn hashed_layouts_have_fixed_depth_hierarchy_shape() -> Result<()> {
+        let layout = StorageLayout::new(LayoutExtensionName::HashedNTupleLayout, None)?;
+        let shape = layout.hierarchy_shape().unwrap();
+
+        assert_eq!(3, shape.object_depth());
+        assert!(shape.is_tuple("0a9"));
+        assert!(!shape.is_tuple("0A9"));
+        assert!(!shape.is_tuple("0a9f"));
+        assert!(!shape.is_tuple("extensions"));
+        assert!(shape.contains_root(&layout.map_object_id(ID_1)));
+        assert!(!shape.contains_root("0a9/12f/object"));
+        assert!(!shape.contains_root("0a9/12f/xyz/object"));
+
+        let layout = StorageLayout::new(LayoutExtensionName::HashedNTupleObjectIdLayout, None)?;
+        let shape = layout.hierarchy_shape().unwrap();
+
+        assert!(shape.contains_root(&layout.map_object_id(ID_2)));
+
+        let shape = HierarchyShape {
+            tuple_size: 0,
+            number_of_tuples: 0,
+        };
+
+        assert_eq!(0, shape.object_depth());
+        assert!(shape.contains_root("object"));
+        assert!(!shape.contains_root("a/object"));
+
+        Ok(())
+    }
+
+    #[cfg(feature = "s3")]
+    #[test]
+    fn flat_layouts_do_not_have_hierarchy_shape() -> Result<()> {
+        let layout = StorageLayout::new(LayoutExtensionName::FlatDirectLayout, None)?;
+        assert_eq!(None, layout.hierarchy_shape());
+
+        Ok(())
+    }
+
     fn hashed_ntuple_ext(
         algorithm: &str,
         tuple_size: usize,
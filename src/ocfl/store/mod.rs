@@ -1,18 +1,31 @@
 use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt::Debug;
 use std::io::{Read, Write};
 use std::path::Path;
+use std::str::FromStr;
 
 use serde::{Deserialize, Serialize};
 
-use crate::ocfl::error::Result;
+use crate::ocfl::consts::{
+    EXTENSIONS_CONFIG_FILE, EXTENSIONS_DIR, INVENTORY_FILE, INVENTORY_SIDECAR_PREFIX,
+    OBJECT_NAMASTE_FILE_PREFIX, OCFL_LAYOUT_FILE, ROOT_NAMASTE_FILE_PREFIX,
+};
+use crate::ocfl::error::{ObjectError, Result, RocflError};
 use crate::ocfl::inventory::Inventory;
-use crate::ocfl::store::layout::LayoutExtensionName;
-use crate::ocfl::validate::{IncrementalValidator, ObjectValidationResult};
+use crate::ocfl::store::layout::{LayoutExtensionName, StorageLayout};
+use crate::ocfl::validate::{
+    FixityCheck, FixitySampleRecord, IncrementalValidator, ObjectValidationResult, ValidationFilter,
+};
 use crate::ocfl::{
-    ContentPath, Knowable, LogicalPath, ObjectInfo, RepoInfo, SpecVersion, VersionRef,
+    paths, BucketStagedObject, ContentPath, DigestAlgorithm, EncryptionKey, FreezeMarker,
+    HealthCheck, HealthProblem, HexDigest, Knowable, LayoutInfo, LogicalPath, ObjectAlias,
+    ObjectFingerprint, ObjectInfo, RepoInfo, S3RequestMetrics, S3WriteOptions, SpecVersion,
+    ThreadPool, ValidationRecord, VersionNum, VersionRef,
 };
 
+#[cfg(any(feature = "azure", feature = "gcs"))]
+mod cloud;
 pub mod fs;
 pub mod layout;
 #[cfg(feature = "s3")]
@@ -28,13 +41,29 @@ pub trait OcflStore {
     /// `RocflError::NotFound` if it does not exist.
     fn get_inventory(&self, object_id: &str) -> Result<Inventory>;
 
+    /// Returns true if the specified object exists. Only the object's namaste file is checked,
+    /// and its inventory is not read.
+    fn object_exists(&self, object_id: &str) -> Result<bool>;
+
+    /// Returns the number of the specified object's HEAD version, or a `RocflError::NotFound` if
+    /// the object does not exist. The version is identified from the object's version
+    /// directories rather than by reading its inventory, unless the object has a mutable HEAD.
+    fn head_version(&self, object_id: &str) -> Result<VersionNum>;
+
     /// Returns an iterator that iterates over every object in an OCFL repository, returning
     /// the most recent inventory of each. Optionally, a glob pattern may be provided that filters
     /// the objects that are returned by OCFL ID.
+    ///
+    /// Objects that cannot be read are returned as `ObjectError`s that identify the object.
     fn iter_inventories<'a>(
         &'a self,
         filter_glob: Option<&str>,
-    ) -> Result<Box<dyn Iterator<Item = Result<Inventory>> + 'a>>;
+    ) -> Result<Box<dyn Iterator<Item = Result<Inventory, ObjectError>> + 'a>>;
+
+    /// Returns the IDs of up to `limit` objects whose IDs start with `prefix`, sorted. Only the
+    /// object IDs are read, and objects that cannot be read are skipped. When there are more
+    /// matching objects than `limit`, which objects are returned is unspecified.
+    fn list_object_ids(&self, prefix: &str, limit: usize) -> Result<Vec<String>>;
 
     /// Writes the specified file to the sink.
     ///
@@ -47,6 +76,28 @@ pub trait OcflStore {
         sink: &mut dyn Write,
     ) -> Result<()>;
 
+    /// Writes the file at the specified content path within the object described by the
+    /// inventory to the sink.
+    fn get_content_file(
+        &self,
+        inventory: &Inventory,
+        content_path: &ContentPath,
+        sink: &mut dyn Write,
+    ) -> Result<()>;
+
+    /// Returns the size, in bytes, of the file at the specified content path within the object
+    /// described by the inventory.
+    fn get_content_file_size(
+        &self,
+        inventory: &Inventory,
+        content_path: &ContentPath,
+    ) -> Result<u64>;
+
+    /// Returns the storage root relative path to the object root that `write_new_object()`
+    /// writes the object to. `object_root` is used if it is specified, otherwise the path is
+    /// mapped by the storage layout.
+    fn new_object_root(&self, object_id: &str, object_root: Option<&str>) -> Result<String>;
+
     /// Writes a new OCFL object. The contents at `object_path` must be a fully formed OCFL
     /// object that is able to be moved into place with no additional modifications.
     ///
@@ -65,20 +116,131 @@ pub trait OcflStore {
     /// The object must already exist, and the new version must not exist.
     fn write_new_version(&self, inventory: &mut Inventory, version_path: &Path) -> Result<()>;
 
+    /// Replaces the content files that were added in the HEAD version of a committed object with
+    /// hard links to existing files in the repository that have the same size and digest. Files
+    /// that cannot be linked, for example because they are on a different filesystem, are left
+    /// as copies. Stores that do not support hard links do nothing.
+    ///
+    /// Returns the number of files that were replaced with links.
+    fn link_duplicate_content(&self, inventory: &Inventory) -> Result<usize>;
+
+    /// Sets the storage class and tags that are applied to content files and to inventories when
+    /// they are written. Stores that do not support storage classes ignore the options.
+    fn set_s3_write_options(&mut self, content: S3WriteOptions, inventory: S3WriteOptions);
+
+    /// Sets the pool that parallelizable work, such as fixity checking, is run on
+    fn set_thread_pool(&mut self, pool: ThreadPool);
+
+    /// Sets the key that the content of encrypted objects is decrypted with when it is fixity
+    /// checked
+    fn set_encryption_key(&mut self, key: EncryptionKey);
+
+    /// Returns the metrics of the requests that have been sent to S3, if the store is backed by
+    /// S3 and request metrics are enabled
+    fn s3_request_metrics(&self) -> Option<S3RequestMetrics>;
+
+    /// Enables staging the content of staged versions within the repository's storage, rather
+    /// than on local disk, under the staging ID, or disables it if the ID is `None`. Each local
+    /// staging root has its own staging ID, so that staging roots that share a repository do not
+    /// overwrite or remove each other's staged content. Stores that are not backed by S3 ignore
+    /// this.
+    fn set_bucket_staging(&mut self, staging_id: Option<String>);
+
+    /// Returns the store's in-bucket staging area, if it is enabled
+    fn bucket_staging(&self) -> Option<&dyn BucketStaging>;
+
+    /// Sets whether validation reports unportable paths as errors rather than warnings
+    fn set_strict_paths(&self, strict: bool);
+
+    /// Sets the fixity sampling state of objects, keyed on object ID, that sampled validations
+    /// continue from
+    fn set_sample_history(&self, history: HashMap<String, FixitySampleRecord>);
+
     /// Purges the specified object from the repository, if it exists. If it does not exist,
     /// nothing happens. Any dangling directories that were created as a result of purging
     /// the object are also removed.
+    ///
+    /// The object's content is deleted first, and its namaste file last, so that an interrupted
+    /// purge leaves behind an object root that can still be identified. If the object's root
+    /// was already partially deleted, the remaining files are deleted.
     fn purge_object(&self, object_id: &str) -> Result<()>;
 
+    /// Finishes purging the object at the storage root relative path, for objects whose purge
+    /// was interrupted and can no longer be found by ID. The path must contain the object's
+    /// namaste file or an inventory, but not both, which would indicate an intact object that
+    /// should be purged by ID instead. The paths, relative the object root, of the deleted files
+    /// are returned.
+    fn purge_object_root(&self, object_root: &str) -> Result<Vec<String>>;
+
+    /// Returns the paths, relative the object root, of every file within the object's version
+    /// content directories that is not referenced in the object's manifest. The paths are sorted.
+    fn list_orphaned_content(&self, object_id: &str) -> Result<Vec<String>>;
+
+    /// Deletes every file within the object's version content directories that is not referenced
+    /// in the object's manifest, along with any directories that are empty as a result. The paths,
+    /// relative the object root, of the deleted files are returned.
+    fn rm_orphaned_content(&self, object_id: &str) -> Result<Vec<String>>;
+
+    /// Returns the paths, relative the storage root, of every file within the object's root,
+    /// sorted. `RocflError::NotFound` is returned if the object does not exist.
+    fn list_object_files(&self, object_id: &str) -> Result<Vec<String>>;
+
+    /// Writes the file at the specified path, relative the object's root, to the sink.
+    ///
+    /// If the file cannot be found, then a `RocflError::NotFound` error is returned.
+    fn get_object_root_file(&self, object_id: &str, path: &str, sink: &mut dyn Write)
+        -> Result<()>;
+
     /// Returns a list of all of the extension names that are associated with the object
     fn list_object_extensions(&self, object_id: &str) -> Result<Vec<String>>;
 
+    /// Returns the paths, relative the object's `extensions` directory, of every file in the
+    /// object's `extensions` directory. If the object does not have an `extensions` directory,
+    /// then an empty vector is returned.
+    fn list_object_extension_files(&self, object_id: &str) -> Result<Vec<String>>;
+
+    /// Writes the file at the specified path, relative the object's `extensions` directory, to
+    /// the sink.
+    ///
+    /// If the file cannot be found, then a `RocflError::NotFound` error is returned.
+    fn get_object_extension_file(
+        &self,
+        object_id: &str,
+        path: &str,
+        sink: &mut dyn Write,
+    ) -> Result<()>;
+
+    /// Returns the paths, relative the object's `logs` directory, of every file in the object's
+    /// `logs` directory. If the object does not have a `logs` directory, then an empty vector
+    /// is returned.
+    fn list_object_logs(&self, object_id: &str) -> Result<Vec<String>>;
+
+    /// Writes the file at the specified path, relative the object's `logs` directory, to the sink.
+    ///
+    /// If the file cannot be found, then a `RocflError::NotFound` error is returned.
+    fn get_object_log_file(&self, object_id: &str, path: &str, sink: &mut dyn Write) -> Result<()>;
+
+    /// Appends the bytes to the file at the specified path, relative the object's `logs`
+    /// directory. The file, and the `logs` directory, are created if they do not exist.
+    fn append_object_log(&self, object_id: &str, path: &str, bytes: &[u8]) -> Result<()>;
+
+    /// Writes the bytes of the object's root inventory, or of the inventory in the specified
+    /// version's directory, to the sink.
+    ///
+    /// If the inventory cannot be found, then a `RocflError::NotFound` error is returned.
+    fn get_inventory_file(
+        &self,
+        object_id: &str,
+        version_num: Option<VersionNum>,
+        sink: &mut dyn Write,
+    ) -> Result<()>;
+
     /// Validates the specified object and returns any problems found. Err will only be returned
     /// if a non-validation problem was encountered.
     fn validate_object(
         &self,
         object_id: &str,
-        fixity_check: bool,
+        fixity_check: FixityCheck,
     ) -> Result<ObjectValidationResult>;
 
     /// Validates the specified object at the specified path, relative the storage root, and
@@ -87,28 +249,111 @@ pub trait OcflStore {
     fn validate_object_at(
         &self,
         object_root: &str,
-        fixity_check: bool,
+        fixity_check: FixityCheck,
     ) -> Result<ObjectValidationResult>;
 
     /// Validates the structure of an OCFL repository as well as all of the objects in the repository
-    /// When `fixity_check` is `false`, then the digests of object content files are not validated.
+    /// that match the filter. `fixity_check` determines which object content files have their
+    /// digests validated.
     ///
     /// The storage root is validated immediately, and an incremental validator is returned that
     /// is used to lazily validate the rest of the repository.
     fn validate_repo<'a>(
         &'a self,
-        fixity_check: bool,
+        fixity_check: FixityCheck,
+        filter: ValidationFilter,
     ) -> Result<Box<dyn IncrementalValidator + 'a>>;
 
     /// Returns details about an OCFL repository
     fn describe_repo(&self) -> Result<RepoInfo>;
 
+    /// Returns the repository's storage layout configuration, and any discrepancies between the
+    /// layout declared in `ocfl_layout.json` and the extension configs in `extensions`.
+    fn verify_layout(&self) -> Result<LayoutInfo>;
+
+    /// Checks the storage root's namaste file and storage layout for problems
+    fn check_storage_root(&self) -> Result<Vec<HealthProblem>>;
+
+    /// Checks the object's root for version directories that are not in its inventory, and for
+    /// a missing root inventory sidecar
+    fn check_object_root(&self, inventory: &Inventory) -> Result<Vec<HealthProblem>>;
+
+    /// Rewrites the description in the repository's `ocfl_layout.json`. If the repository does
+    /// not have an `ocfl_layout.json`, then `RocflError::NotFound` is returned.
+    fn set_layout_description(&self, description: &str) -> Result<()>;
+
     /// Returns details about an OCFL object
     fn describe_object(&self, object_id: &str) -> Result<ObjectInfo>;
 
     /// Upgrades the repository to the specified version
     fn upgrade_repo(&self, version: SpecVersion) -> Result<()>;
 
+    /// Adds the existing object rooted at `object_root`, relative the storage root, to the
+    /// repository's object index. Indexed objects are located by ID, even when they do not reside
+    /// where the storage layout expects them, or the repository does not have a storage layout.
+    ///
+    /// The adopted object's inventory is returned.
+    fn adopt_object(&self, object_root: &str) -> Result<Inventory>;
+
+    /// Returns `true` if the object index is a registry that records the location of every object
+    /// in the repository
+    fn is_object_registry(&self) -> bool;
+
+    /// Enables or disables the object registry. While it is enabled, the location of every object
+    /// is recorded in the object index when it is created or purged, and objects are located and
+    /// listed using the index rather than by scanning the storage root. Enabling the registry
+    /// scans the repository once to index its existing objects.
+    ///
+    /// The number of objects in the object index is returned.
+    fn set_object_registry(&self, enabled: bool) -> Result<usize>;
+
+    /// Returns the ID of the object that the alias identifies, or `None` if the alias is not in
+    /// the repository's alias index.
+    fn resolve_alias(&self, alias: &str) -> Result<Option<String>>;
+
+    /// Adds an alias for the object to the repository's alias index. An alias may only identify
+    /// a single object. It is the caller's responsibility to verify that the object exists.
+    fn add_alias(&self, object_id: &str, alias: &str) -> Result<()>;
+
+    /// Removes an alias from the repository's alias index. If the alias does not exist, then
+    /// `RocflError::NotFound` is returned.
+    fn remove_alias(&self, alias: &str) -> Result<()>;
+
+    /// Returns all of the aliases in the repository's alias index, sorted by alias
+    fn list_aliases(&self) -> Result<Vec<ObjectAlias>>;
+
+    /// Returns the defaults that are applied to objects created in the repository. If none
+    /// have been configured, then empty defaults are returned.
+    fn get_repo_defaults(&self) -> Result<RepoDefaults>;
+
+    /// Writes the defaults that are applied to objects created in the repository, replacing any
+    /// existing defaults
+    fn set_repo_defaults(&self, defaults: &RepoDefaults) -> Result<()>;
+
+    /// Returns the repository's validation history. If no validations have been recorded, then
+    /// an empty history is returned.
+    fn get_validation_history(&self) -> Result<ValidationHistory>;
+
+    /// Writes the repository's validation history, replacing the existing history
+    fn set_validation_history(&self, history: &ValidationHistory) -> Result<()>;
+
+    /// Returns the object's freeze marker, or `None` if the object is not frozen.
+    ///
+    /// If the object does not exist, then a `RocflError::NotFound` error is returned.
+    fn get_freeze_marker(&self, object_id: &str) -> Result<Option<FreezeMarker>>;
+
+    /// Writes the object's freeze marker, replacing any existing marker. If `marker` is `None`,
+    /// then the existing marker, if there is one, is removed.
+    ///
+    /// If the object does not exist, then a `RocflError::NotFound` error is returned.
+    fn set_freeze_marker(&self, object_id: &str, marker: Option<&FreezeMarker>) -> Result<()>;
+
+    /// Returns the digest of the object's root inventory, as recorded in its sidecar, along with
+    /// its ETag if the store has one, without reading the inventory.
+    ///
+    /// If the object does not exist, then a `RocflError::NotFound` error is returned.
+    fn get_object_fingerprint(&self, object_id: &str) -> Result<ObjectFingerprint>;
+
     /// Instructs the store to gracefully stop any in-flight work and not accept any additional
     /// requests.
     fn close(&self);
@@ -165,6 +410,62 @@ pub trait StagingStore: OcflStore {
         finalize: bool,
         pretty_print: bool,
     ) -> Result<()>;
+
+    /// Validates the object's staged version prior to committing it. `committed_head` is the
+    /// object's HEAD version in the main repository, or `None` if the object is new.
+    fn validate_staged_version(
+        &self,
+        inventory: &Inventory,
+        committed_head: Option<VersionNum>,
+    ) -> Result<ObjectValidationResult>;
+}
+
+/// Operations on content files that are staged within an S3 bucket, under the repository's
+/// staging extension, rather than on local disk. Staged files are identified by the content
+/// paths they have in the staged inventory, and are copied into the object server-side when the
+/// object is committed. Only the content of the staging ID the store was configured with is
+/// visible.
+pub trait BucketStaging {
+    /// Uploads a staged content file. The file's digest is verified against the digest that the
+    /// inventory's manifest records for it. False is returned, and nothing is uploaded, if the
+    /// file is too large to be copied server-side, in which case it must remain on local disk.
+    fn put_staged_file(
+        &self,
+        inventory: &Inventory,
+        content_path: &ContentPath,
+        file: &Path,
+    ) -> Result<bool>;
+
+    /// Writes the content of a staged file to the sink
+    fn get_staged_file(
+        &self,
+        inventory: &Inventory,
+        content_path: &ContentPath,
+        sink: &mut dyn Write,
+    ) -> Result<()>;
+
+    /// Returns the size of a staged file in bytes
+    fn staged_file_size(&self, inventory: &Inventory, content_path: &ContentPath) -> Result<u64>;
+
+    /// Returns the content paths of all of the files that are staged for the object
+    fn list_staged_files(&self, inventory: &Inventory) -> Result<Vec<ContentPath>>;
+
+    /// Copies a staged file to a new staged content path
+    fn copy_staged_file(
+        &self,
+        inventory: &Inventory,
+        src_content: &ContentPath,
+        dst_content: &ContentPath,
+    ) -> Result<()>;
+
+    /// Deletes staged files. Files that do not exist are ignored.
+    fn rm_staged_files(&self, inventory: &Inventory, paths: &[&ContentPath]) -> Result<()>;
+
+    /// Returns every object that has content staged in the bucket under the store's staging ID
+    fn list_staged_objects(&self) -> Result<Vec<BucketStagedObject>>;
+
+    /// Deletes all of the content that is staged for the object at the staging object root
+    fn purge_staged_object(&self, object_root: &str) -> Result<()>;
 }
 
 /// ocfl_layout.json serialization object
@@ -181,16 +482,552 @@ pub(crate) struct OcflLayoutLenient {
     description: String,
 }
 
+impl OcflLayoutLenient {
+    /// The name of the extension that defines the layout
+    pub fn extension(&self) -> &str {
+        &self.extension
+    }
+}
+
+/// Object index serialization object. Maps object IDs to object roots, relative the storage root,
+/// for objects that do not reside at the location dictated by the storage layout.
+#[derive(Deserialize, Serialize, Debug, Default)]
+#[serde(transparent)]
+pub(crate) struct ObjectIndex {
+    entries: BTreeMap<String, String>,
+}
+
+impl ObjectIndex {
+    /// Returns the indexed object root for the object, if it exists
+    pub fn get(&self, object_id: &str) -> Option<&String> {
+        self.entries.get(object_id)
+    }
+
+    /// Adds an object root to the index, replacing any existing mapping. Returns `true` if the
+    /// index was changed.
+    pub fn insert(&mut self, object_id: &str, object_root: &str) -> bool {
+        self.entries
+            .insert(object_id.to_string(), object_root.to_string())
+            .as_deref()
+            != Some(object_root)
+    }
+
+    /// Removes an object from the index. Returns `true` if the index was changed.
+    pub fn remove(&mut self, object_id: &str) -> bool {
+        self.entries.remove(object_id).is_some()
+    }
+
+    /// Returns the indexed object roots
+    #[cfg(feature = "s3")]
+    pub fn object_roots(&self) -> impl Iterator<Item = &String> {
+        self.entries.values()
+    }
+
+    /// Returns the indexed object IDs and their object roots, sorted by object ID
+    pub fn entries(&self) -> impl Iterator<Item = (&String, &String)> {
+        self.entries.iter()
+    }
+
+    /// Returns the number of indexed objects
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Removes the objects whose object roots the predicate returns `true` for. Returns `true` if
+    /// the index was changed.
+    pub fn remove_if(&mut self, predicate: impl Fn(&str, &str) -> bool) -> bool {
+        let before = self.entries.len();
+        self.entries
+            .retain(|object_id, object_root| !predicate(object_id, object_root));
+        before != self.entries.len()
+    }
+
+    /// Returns the IDs of the objects that are indexed at the object root
+    pub fn object_ids_at(&self, object_root: &str) -> Vec<String> {
+        self.entries
+            .iter()
+            .filter(|(_, root)| root.as_str() == object_root)
+            .map(|(object_id, _)| object_id.clone())
+            .collect()
+    }
+}
+
+/// Content index serialization object. Maps the digests of content files to the path, relative
+/// the storage root, of a file in the repository with that digest. Entries are not removed when
+/// objects are purged, so a file must be verified before it is used.
+#[derive(Deserialize, Serialize, Debug, Default)]
+#[serde(transparent)]
+pub(crate) struct ContentIndex {
+    entries: BTreeMap<String, String>,
+}
+
+impl ContentIndex {
+    /// Returns the indexed path of a file with the digest, if it exists
+    pub fn get(&self, digest: &str) -> Option<&String> {
+        self.entries.get(digest)
+    }
+
+    /// Adds a path to the index, replacing any existing mapping. Returns `true` if the index
+    /// was changed.
+    pub fn insert(&mut self, digest: &str, path: &str) -> bool {
+        self.entries
+            .insert(digest.to_string(), path.to_string())
+            .as_deref()
+            != Some(path)
+    }
+
+    /// Adds a path to the index if the digest is not already indexed. Returns `true` if the index
+    /// was changed.
+    pub fn insert_if_absent(&mut self, digest: &str, path: &str) -> bool {
+        if self.entries.contains_key(digest) {
+            false
+        } else {
+            self.entries.insert(digest.to_string(), path.to_string());
+            true
+        }
+    }
+}
+
+/// Object index extension config serialization object. When `registry` is `true`, the object
+/// index records the location of every object, rather than only the objects that are not where
+/// the storage layout expects them, so that objects can be located without scanning.
+#[derive(Deserialize, Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ObjectIndexConfig {
+    #[serde(default)]
+    pub registry: bool,
+}
+
+/// Alias index serialization object. Maps aliases, secondary identifiers such as DOIs or handles,
+/// to the IDs of the objects they identify.
+#[derive(Deserialize, Serialize, Debug, Default)]
+#[serde(transparent)]
+pub(crate) struct AliasIndex {
+    entries: BTreeMap<String, String>,
+}
+
+impl AliasIndex {
+    /// Returns the ID of the object the alias identifies, if it exists
+    pub fn get(&self, alias: &str) -> Option<&String> {
+        self.entries.get(alias)
+    }
+
+    /// Adds an alias for an object. An alias may only identify a single object, so an error is
+    /// returned if the alias already identifies a different object. Returns `true` if the index
+    /// was changed.
+    pub fn insert(&mut self, alias: &str, object_id: &str) -> Result<bool> {
+        match self.entries.get(alias) {
+            Some(existing) if existing == object_id => Ok(false),
+            Some(existing) => Err(RocflError::IllegalOperation(format!(
+                "Alias {} already identifies object {}",
+                alias, existing
+            ))),
+            None => {
+                self.entries
+                    .insert(alias.to_string(), object_id.to_string());
+                Ok(true)
+            }
+        }
+    }
+
+    /// Removes an alias from the index. Returns `true` if the index was changed.
+    pub fn remove(&mut self, alias: &str) -> bool {
+        self.entries.remove(alias).is_some()
+    }
+
+    /// Removes all of the aliases of an object from the index. Returns `true` if the index
+    /// was changed.
+    pub fn remove_object(&mut self, object_id: &str) -> bool {
+        let before = self.entries.len();
+        self.entries.retain(|_, id| id != object_id);
+        before != self.entries.len()
+    }
+
+    /// Returns all of the aliases in the index, sorted by alias
+    pub fn aliases(&self) -> Vec<ObjectAlias> {
+        self.entries
+            .iter()
+            .map(|(alias, object_id)| ObjectAlias {
+                alias: alias.clone(),
+                object_id: object_id.clone(),
+            })
+            .collect()
+    }
+}
+
+/// Validation history serialization object. Maps object IDs to the record of their most recent
+/// validation.
+#[derive(Deserialize, Serialize, Debug, Default)]
+#[serde(transparent)]
+pub(crate) struct ValidationHistory {
+    entries: BTreeMap<String, ValidationRecord>,
+}
+
+impl ValidationHistory {
+    /// Returns the record of the object's most recent validation, if it exists
+    pub fn get(&self, object_id: &str) -> Option<&ValidationRecord> {
+        self.entries.get(object_id)
+    }
+
+    /// Adds a record, replacing the object's existing record
+    pub fn insert(&mut self, record: ValidationRecord) {
+        self.entries.insert(record.object_id.clone(), record);
+    }
+
+    /// Returns all of the records in the history, sorted by object ID
+    pub fn records(&self) -> Vec<ValidationRecord> {
+        self.entries.values().cloned().collect()
+    }
+}
+
+/// Repository defaults serialization object. The defaults are stored in the config file of the
+/// `rocfl-repo-defaults` extension, and are applied to objects that are created in the repository
+/// when a value is not explicitly specified.
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RepoDefaults {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub digest_algorithm: Option<DigestAlgorithm>,
+}
+
+/// Compares the layout declared in the storage root's `ocfl_layout.json` with the layout
+/// extension configs in its `extensions` directory. Unlike loading the layout when a store is
+/// opened, problems are collected rather than logged, so that they can be reported together.
+pub(crate) fn verify_layout<S: Storage>(storage: &S) -> Result<LayoutInfo> {
+    let mut info = LayoutInfo::default();
+
+    let root_files = storage.list("", false)?;
+    let extension_dirs: Vec<String> = storage
+        .list(EXTENSIONS_DIR, false)?
+        .into_iter()
+        .filter(|listing| matches!(listing, Listing::Directory(_)))
+        .map(|listing| listing.path().trim_end_matches('/').to_string())
+        .collect();
+
+    let declared = if root_files.contains(&Listing::file(OCFL_LAYOUT_FILE)) {
+        let mut bytes = Vec::new();
+        storage.read(OCFL_LAYOUT_FILE, &mut bytes)?;
+
+        match serde_json::from_slice::<OcflLayoutLenient>(&bytes) {
+            Ok(layout) => {
+                if layout.description.trim().is_empty() {
+                    info.problems
+                        .push(format!("{} has a blank description", OCFL_LAYOUT_FILE));
+                }
+
+                let name = LayoutExtensionName::from_str(&layout.extension).ok();
+                if name.is_none() {
+                    info.problems.push(format!(
+                        "{} declares unsupported layout extension {}",
+                        OCFL_LAYOUT_FILE, layout.extension
+                    ));
+                }
+
+                info.extension = Some(layout.extension);
+                info.description = Some(layout.description);
+                name
+            }
+            Err(e) => {
+                info.problems
+                    .push(format!("{} could not be parsed: {}", OCFL_LAYOUT_FILE, e));
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    if let Some(name) = declared {
+        let ext_dir = paths::join(EXTENSIONS_DIR, &name.to_string());
+        let config_path = paths::join(&ext_dir, EXTENSIONS_CONFIG_FILE);
+
+        let config_bytes = if storage
+            .list(&ext_dir, false)?
+            .contains(&Listing::file(EXTENSIONS_CONFIG_FILE))
+        {
+            let mut bytes = Vec::new();
+            storage.read(&config_path, &mut bytes)?;
+            info.has_config_file = true;
+            Some(bytes)
+        } else {
+            None
+        };
+
+        match StorageLayout::new(name, config_bytes.as_deref())
+            .and_then(|layout| layout.serialize())
+        {
+            Ok(config) => info.config = Some(String::from_utf8_lossy(&config).into_owned()),
+            Err(e) => info.problems.push(format!(
+                "{} is not a valid {} config: {:#}",
+                config_path, name, e
+            )),
+        }
+    }
+
+    for dir in extension_dirs {
+        let other = match LayoutExtensionName::from_str(&dir) {
+            Ok(other) if Some(other) != declared => other,
+            _ => continue,
+        };
+
+        match &info.extension {
+            Some(extension) => info.problems.push(format!(
+                "{} contains layout extension {}, but {} declares {}",
+                EXTENSIONS_DIR, other, OCFL_LAYOUT_FILE, extension
+            )),
+            None => info.problems.push(format!(
+                "{} contains layout extension {}, but {} does not declare a layout",
+                EXTENSIONS_DIR, other, OCFL_LAYOUT_FILE
+            )),
+        }
+    }
+
+    Ok(info)
+}
+
+/// Checks that the storage root has exactly one namaste file with the expected content, and that
+/// its storage layout is consistent
+pub(crate) fn check_storage_root<S: Storage>(storage: &S) -> Result<Vec<HealthProblem>> {
+    let mut problems = Vec::new();
+
+    let namastes: Vec<String> = storage
+        .list("", false)?
+        .into_iter()
+        .filter_map(|listing| match listing {
+            Listing::File(name) if name.starts_with(ROOT_NAMASTE_FILE_PREFIX) => {
+                Some(name.into_owned())
+            }
+            _ => None,
+        })
+        .collect();
+
+    match namastes.as_slice() {
+        [] => problems.push(HealthProblem::new(
+            HealthCheck::StorageRoot,
+            "The storage root does not have a namaste file",
+            "Run 'rocfl init --from-existing' to write the missing namaste file",
+        )),
+        [namaste] => {
+            let expected = format!("{}\n", namaste.split_once('=').unwrap().1);
+            let mut bytes = Vec::new();
+            storage.read(namaste, &mut bytes)?;
+
+            if bytes != expected.as_bytes() {
+                problems.push(
+                    HealthProblem::new(
+                        HealthCheck::StorageRoot,
+                        format!(
+                            "Namaste file {} does not contain '{}'",
+                            namaste,
+                            expected.trim_end()
+                        ),
+                        format!(
+                            "Rewrite the file so that it only contains '{}'",
+                            expected.trim_end()
+                        ),
+                    )
+                    .with_path(namaste),
+                );
+            }
+        }
+        _ => problems.push(HealthProblem::new(
+            HealthCheck::StorageRoot,
+            format!(
+                "The storage root has multiple namaste files: {}",
+                namastes.join(", ")
+            ),
+            "Remove the namaste files that do not match the repository's OCFL version",
+        )),
+    }
+
+    for problem in verify_layout(storage)?.problems {
+        problems.push(HealthProblem::new(
+            HealthCheck::Layout,
+            problem,
+            "Run 'rocfl layout verify', and correct the layout declaration or extension configs",
+        ));
+    }
+
+    Ok(problems)
+}
+
+/// Checks an object root for version directories that are newer than the object's HEAD, which
+/// are left behind by interrupted commits, and for a missing root inventory sidecar
+pub(crate) fn check_object_root<S: Storage>(
+    storage: &S,
+    inventory: &Inventory,
+) -> Result<Vec<HealthProblem>> {
+    let mut problems = Vec::new();
+    let mut has_sidecar = false;
+
+    for listing in storage.list(&inventory.object_root, false)? {
+        match listing {
+            Listing::File(name) if name.starts_with(INVENTORY_SIDECAR_PREFIX) => {
+                has_sidecar = true;
+            }
+            Listing::Directory(name) => {
+                let name = name.trim_end_matches('/');
+                if let Ok(version_num) = VersionNum::from_str(name) {
+                    if version_num > inventory.head {
+                        problems.push(
+                            HealthProblem::new(
+                                HealthCheck::InterruptedCommit,
+                                format!(
+                                    "Version directory {} is not in the inventory, which has HEAD {}",
+                                    name, inventory.head
+                                ),
+                                "Move the directory out of the object root, and then commit its changes again",
+                            )
+                            .with_object_id(&inventory.id)
+                            .with_path(paths::join(&inventory.object_root, name)),
+                        );
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+
+    if !has_sidecar {
+        problems.push(
+            HealthProblem::new(
+                HealthCheck::InventorySidecar,
+                "The root inventory does not have a sidecar",
+                format!(
+                    "Write the inventory's {} digest to {}{}",
+                    inventory.digest_algorithm,
+                    INVENTORY_SIDECAR_PREFIX,
+                    inventory.digest_algorithm
+                ),
+            )
+            .with_object_id(&inventory.id)
+            .with_path(paths::join(&inventory.object_root, INVENTORY_FILE)),
+        );
+    }
+
+    Ok(problems)
+}
+
+/// Returns the order, lowest first, that a file is deleted in when an object is purged. The path
+/// is relative the object root. Content is deleted first, then the root inventory and its
+/// sidecar, and finally the namaste file, so that an interrupted purge does not leave behind
+/// files that cannot be traced back to an object.
+pub(crate) fn purge_order(path: &str) -> u8 {
+    if path.starts_with(OBJECT_NAMASTE_FILE_PREFIX) {
+        2
+    } else if path == INVENTORY_FILE || path.starts_with(INVENTORY_SIDECAR_PREFIX) {
+        1
+    } else {
+        0
+    }
+}
+
+/// The digest algorithms that an object's root inventory sidecar may use, in the order that the
+/// sidecars are looked for
+pub(crate) const SIDECAR_ALGORITHMS: [DigestAlgorithm; 2] =
+    [DigestAlgorithm::Sha512, DigestAlgorithm::Sha256];
+
+/// Builds an object's fingerprint, without an ETag, from the contents of its root inventory
+/// sidecar
+pub(crate) fn parse_inventory_sidecar(
+    object_id: &str,
+    algorithm: DigestAlgorithm,
+    bytes: &[u8],
+) -> Result<ObjectFingerprint> {
+    let contents = String::from_utf8_lossy(bytes);
+    let parts: Vec<&str> = contents.split_whitespace().collect();
+
+    if parts.len() != 2 || parts[1] != INVENTORY_FILE {
+        return Err(RocflError::CorruptObject {
+            object_id: object_id.to_string(),
+            message: format!("Invalid inventory sidecar: {}", contents.trim()),
+        });
+    }
+
+    Ok(ObjectFingerprint {
+        object_id: object_id.to_string(),
+        digest_algorithm: algorithm,
+        inventory_digest: HexDigest::from(parts[0]),
+        e_tag: None,
+    })
+}
+
+/// Constructs the error that is returned when an object's root inventory does not have a sidecar
+pub(crate) fn missing_sidecar_error(object_id: &str) -> RocflError {
+    RocflError::CorruptObject {
+        object_id: object_id.to_string(),
+        message: "The root inventory does not have a sidecar".to_string(),
+    }
+}
+
+/// Validates that the path is a storage root relative path that may be an object root
+pub(crate) fn validate_object_root(object_root: &str) -> Result<()> {
+    if object_root.is_empty()
+        || object_root
+            .split('/')
+            .any(|part| part.is_empty() || part == "." || part == "..")
+    {
+        return Err(RocflError::InvalidValue(format!(
+            "Object root must be a path relative the storage root. Found: {}",
+            object_root
+        )));
+    }
+    Ok(())
+}
+
+/// Returns an error if the names of the files in the root of a partially purged object do not
+/// include its namaste file or an inventory, or include both
+pub(crate) fn verify_partially_purged<'a>(
+    object_root: &str,
+    names: impl IntoIterator<Item = &'a str>,
+) -> Result<()> {
+    let mut namaste = false;
+    let mut inventory = false;
+
+    for name in names {
+        match purge_order(name) {
+            2 => namaste = true,
+            1 if name == INVENTORY_FILE => inventory = true,
+            _ => (),
+        }
+    }
+
+    if namaste && inventory {
+        Err(RocflError::IllegalState(format!(
+            "Cannot resume purging {} because it contains an intact object. Purge the object by ID instead",
+            object_root
+        )))
+    } else if !namaste && !inventory {
+        Err(RocflError::NotFound(format!(
+            "Partially purged object at path {}",
+            object_root
+        )))
+    } else {
+        Ok(())
+    }
+}
+
+/// Returns the error that is returned when the object index lock is poisoned
+pub(crate) fn object_index_lock_error() -> RocflError {
+    RocflError::General("Failed to acquire object index lock".to_string())
+}
+
+/// Returns the error that is returned when the alias index lock is poisoned
+pub(crate) fn alias_lock_error() -> RocflError {
+    RocflError::General("Failed to acquire alias index lock".to_string())
+}
+
 /// Abstraction over reading files and listing directory contents. `/` _must_ be used as the file
-/// path separator.
-pub trait Storage {
+/// path separator. Implementations must be safe to read from concurrently, as content files are
+/// fixity checked in parallel.
+pub trait Storage: Sync {
     /// Reads the file at the specified path and writes its contents to the provided sink.
     fn read<W: Write>(&self, path: &str, sink: &mut W) -> Result<()>;
 
     /// Lists the contents of the specified directory. If `recursive` is `true`, then all leaf-nodes
     /// are returned. If the directory does not exist, or is empty, then an empty vector is returned.
     /// The returned paths are all relative the directory that was listed.
-    fn list(&self, path: &str, recursive: bool) -> Result<Vec<Listing>>;
+    fn list(&self, path: &str, recursive: bool) -> Result<Vec<Listing<'_>>>;
 
     /// Returns the native path separator used by the store.
     fn path_separator(&self) -> char;
@@ -208,11 +1045,11 @@ pub enum Listing<'a> {
 }
 
 impl<'a> Listing<'a> {
-    pub fn file(path: &str) -> Listing {
+    pub fn file(path: &str) -> Listing<'_> {
         Listing::File(Cow::Borrowed(path))
     }
 
-    pub fn dir(path: &str) -> Listing {
+    pub fn dir(path: &str) -> Listing<'_> {
         Listing::Directory(Cow::Borrowed(path))
     }
     pub fn file_owned(path: String) -> Listing<'a> {
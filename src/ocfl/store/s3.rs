@@ -1,45 +1,73 @@
-//! S3 OCFL storage implementation.
+//! S3 OCFL storage implementation. The same implementation is used for the other cloud storage
+//! services, such as Azure Blob Storage and Google Cloud Storage, which are accessed through a
+//! different `BlobClient`.
 
-use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs::File;
-use std::io::{Read, Write};
-use std::path::Path;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::{Arc, RwLock};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
 use std::vec::IntoIter;
 
 use bytes::Bytes;
+use chrono::{DateTime, Local};
 use const_format::concatcp;
-use futures::{FutureExt, TryStreamExt};
 use globset::GlobBuilder;
 use log::{debug, error, info, warn};
-use rusoto_core::credential::{AutoRefreshingProvider, ChainProvider, ProfileProvider};
-use rusoto_core::{ByteStream, Client, HttpClient, Region, RusotoError};
+use md5::{Digest, Md5};
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use rusoto_core::credential::{
+    AutoRefreshingProvider, ChainProvider, DefaultCredentialsProvider, ProfileProvider,
+};
+use rusoto_core::request::DispatchSignedRequestFuture;
+use rusoto_core::signature::{SignedRequest, SignedRequestPayload};
+use rusoto_core::{ByteStream, Client, DispatchSignedRequest, HttpClient, Region, RusotoError};
 use rusoto_s3::{
     AbortMultipartUploadRequest, CompleteMultipartUploadRequest, CompletedMultipartUpload,
-    CompletedPart, CreateMultipartUploadRequest, DeleteObjectRequest, GetObjectError,
-    GetObjectRequest, ListObjectsV2Output, ListObjectsV2Request, PutObjectRequest,
-    S3Client as RusotoS3Client, StreamingBody, UploadPartRequest, S3,
+    CompletedPart, CopyObjectRequest, CreateMultipartUploadRequest, DeleteObjectRequest,
+    GetObjectError, GetObjectRequest, HeadObjectRequest, ListObjectsV2Output, ListObjectsV2Request,
+    PutObjectRequest, S3Client as RusotoS3Client, UploadPartRequest, S3,
 };
 use serde::de::DeserializeOwned;
+use serde::Serialize;
 use tokio::io::AsyncReadExt;
 use tokio::runtime;
 use tokio::runtime::Runtime;
 use walkdir::WalkDir;
 
-use super::layout::StorageLayout;
-use super::{OcflLayout, OcflStore};
+#[cfg(any(feature = "azure", feature = "gcs"))]
+use super::cloud::ObjectStoreClient;
+use super::layout::{HierarchyShape, LayoutExtensionName, StorageLayout};
+use super::{
+    alias_lock_error, missing_sidecar_error, object_index_lock_error, parse_inventory_sidecar,
+    purge_order, validate_object_root, verify_partially_purged, AliasIndex, BucketStaging,
+    ObjectIndex, ObjectIndexConfig, OcflLayout, OcflStore, RepoDefaults, ValidationHistory,
+    SIDECAR_ALGORITHMS,
+};
 use crate::ocfl::consts::*;
-use crate::ocfl::error::{not_found, Result, RocflError};
+use crate::ocfl::digest::HexDigest;
+use crate::ocfl::error::{
+    not_found, not_found_extension_file, not_found_log, not_found_object_file, ObjectError, Result,
+    RocflError,
+};
 use crate::ocfl::inventory::Inventory;
 use crate::ocfl::paths::{join, join_with_trailing_slash};
 use crate::ocfl::store::{Listing, OcflLayoutLenient, Storage};
-use crate::ocfl::validate::{IncrementalValidator, ObjectValidationResult, Validator};
+use crate::ocfl::validate::{
+    FixityCheck, FixitySampleRecord, IncrementalValidator, ObjectValidationResult,
+    ValidationFilter, Validator,
+};
+#[cfg(any(feature = "azure", feature = "gcs"))]
+use crate::ocfl::CloudStorage;
 use crate::ocfl::Knowable::{Known, Unknown};
 use crate::ocfl::{
-    paths, specs, util, DigestAlgorithm, InventoryPath, Knowable, LayoutExtensionName, LogicalPath,
-    ObjectInfo, RepoInfo, SpecVersion, VersionRef,
+    paths, specs, util, BucketStagedObject, ContentPath, DigestAlgorithm, EncryptionKey,
+    FreezeMarker, HealthProblem, InventoryPath, Knowable, LayoutInfo, LogicalPath, ObjectAlias,
+    ObjectFingerprint, ObjectInfo, RepoInfo, S3ClientOptions, S3RequestMetrics, S3WriteOptions,
+    SpecVersion, ThreadPool, VersionNum, VersionRef,
 };
 
 const TYPE_PLAIN: &str = "text/plain; charset=UTF-8";
@@ -49,16 +77,42 @@ const TYPE_JSON: &str = "application/json; charset=UTF-8";
 const PART_SIZE: u64 = 1024 * 1024 * 5;
 
 const EXTENSIONS_DIR_SUFFIX: &str = concatcp!("/", EXTENSIONS_DIR);
+const BUCKET_STAGING_DIR: &str = concatcp!(EXTENSIONS_DIR, "/", ROCFL_STAGING_EXTENSION);
+
+/// The largest object that S3 is able to copy in a single request
+const MAX_COPY_SIZE: u64 = 1024 * 1024 * 1024 * 5;
+
+/// The characters that are encoded in the source key of a copy request
+const COPY_SOURCE_ENCODE_SET: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'/')
+    .remove(b'-')
+    .remove(b'_')
+    .remove(b'.')
+    .remove(b'~');
 
 pub struct S3OcflStore {
-    s3_client: Arc<S3Client>,
+    client: Arc<dyn BlobClient>,
     /// Maps object IDs to paths within the storage root
     storage_layout: Option<StorageLayout>,
     validator: Validator<S3Storage>,
     // TODO this never expires entries and is only intended to be useful within the scope of the cli
     /// Caches object ID to path mappings
     id_path_cache: RwLock<HashMap<String, String>>,
+    /// Persisted object ID to path mappings for objects that are not where the layout expects
+    object_index: RwLock<ObjectIndex>,
+    /// Whether the object index records the location of every object
+    object_registry: AtomicBool,
+    /// Persisted alias to object ID mappings
+    alias_index: RwLock<AliasIndex>,
     prefix: Option<String>,
+    /// The storage class and tags to apply to content files
+    content_write_options: S3WriteOptions,
+    /// The storage class and tags to apply to inventories and their sidecars
+    inventory_write_options: S3WriteOptions,
+    /// The directory, relative the repository prefix, that the content of staged versions is
+    /// staged in within the bucket, if it is staged in the bucket. Each local staging root has
+    /// its own directory.
+    bucket_staging: Option<String>,
     closed: Arc<AtomicBool>,
 }
 
@@ -69,25 +123,48 @@ impl S3OcflStore {
         bucket: &str,
         prefix: Option<&str>,
         profile: Option<&str>,
+        client_options: &S3ClientOptions,
     ) -> Result<Self> {
-        let s3_client = S3Client::new(region, bucket, prefix, profile)?;
+        let client = S3Client::new(region, bucket, prefix, profile, client_options)?;
+        Ok(Self::with_client(Arc::new(client), prefix))
+    }
 
-        check_extensions(&s3_client);
-        let storage_layout = load_storage_layout(&s3_client);
+    /// Creates a new store over a repository in a cloud storage service other than S3.
+    /// `bucket` is the name of the bucket, or container, that the repository is in.
+    #[cfg(any(feature = "azure", feature = "gcs"))]
+    pub fn new_cloud(storage: &CloudStorage, bucket: &str, prefix: Option<&str>) -> Result<Self> {
+        let client = ObjectStoreClient::new(storage, bucket, prefix)?;
+        Ok(Self::with_client(Arc::new(client), prefix))
+    }
 
-        let s3_client = Arc::new(s3_client);
+    /// Creates a store over an existing repository that is accessed through the client
+    fn with_client(client: Arc<dyn BlobClient>, prefix: Option<&str>) -> Self {
+        check_extensions(&*client);
+        let storage_layout = load_storage_layout(&*client);
+        let object_index = load_index(&*client, &object_index_path(), "object index");
+        let object_index_config: ObjectIndexConfig =
+            load_index(&*client, &object_index_config_path(), "object index config");
+        let alias_index = load_index(&*client, &alias_index_path(), "alias index");
 
-        Ok(Self {
-            validator: Validator::new(S3Storage::new(s3_client.clone())),
-            s3_client,
+        Self {
+            validator: Validator::new(S3Storage::new(client.clone())),
+            client,
             storage_layout,
             id_path_cache: RwLock::new(HashMap::new()),
+            object_index: RwLock::new(object_index),
+            object_registry: AtomicBool::new(object_index_config.registry),
+            alias_index: RwLock::new(alias_index),
             prefix: prefix.map(|p| util::trim_trailing_slashes(p).to_string()),
+            content_write_options: S3WriteOptions::default(),
+            inventory_write_options: S3WriteOptions::default(),
+            bucket_staging: None,
             closed: Arc::new(AtomicBool::new(false)),
-        })
+        }
     }
 
-    /// Initializes a new OCFL repository at the specified location
+    /// Initializes a new OCFL repository at the specified location. A copy of the OCFL spec is
+    /// written to the storage root when `spec_copy` is true.
+    #[allow(clippy::too_many_arguments)]
     pub fn init(
         region: Region,
         bucket: &str,
@@ -95,23 +172,165 @@ impl S3OcflStore {
         profile: Option<&str>,
         version: SpecVersion,
         layout: Option<StorageLayout>,
+        spec_copy: bool,
+        client_options: &S3ClientOptions,
     ) -> Result<Self> {
-        let s3_client = S3Client::new(region, bucket, prefix, profile)?;
+        let client = S3Client::new(region, bucket, prefix, profile, client_options)?;
+        Self::init_with_client(Arc::new(client), prefix, version, layout, spec_copy)
+    }
 
-        init_new_repo(&s3_client, version, layout.as_ref())?;
+    /// Initializes a new OCFL repository in a cloud storage service other than S3. A copy of the
+    /// OCFL spec is written to the storage root when `spec_copy` is true.
+    #[cfg(any(feature = "azure", feature = "gcs"))]
+    pub fn init_cloud(
+        storage: &CloudStorage,
+        bucket: &str,
+        prefix: Option<&str>,
+        version: SpecVersion,
+        layout: Option<StorageLayout>,
+        spec_copy: bool,
+    ) -> Result<Self> {
+        let client = ObjectStoreClient::new(storage, bucket, prefix)?;
+        Self::init_with_client(Arc::new(client), prefix, version, layout, spec_copy)
+    }
 
-        let s3_client = Arc::new(s3_client);
+    fn init_with_client(
+        client: Arc<dyn BlobClient>,
+        prefix: Option<&str>,
+        version: SpecVersion,
+        layout: Option<StorageLayout>,
+        spec_copy: bool,
+    ) -> Result<Self> {
+        init_new_repo(&*client, version, layout.as_ref(), spec_copy)?;
 
         Ok(Self {
-            validator: Validator::new(S3Storage::new(s3_client.clone())),
-            s3_client,
+            validator: Validator::new(S3Storage::new(client.clone())),
+            client,
             storage_layout: layout,
             id_path_cache: RwLock::new(HashMap::new()),
+            object_index: RwLock::new(ObjectIndex::default()),
+            object_registry: AtomicBool::new(false),
+            alias_index: RwLock::new(AliasIndex::default()),
             prefix: prefix.map(|p| util::trim_trailing_slashes(p).to_string()),
+            content_write_options: S3WriteOptions::default(),
+            inventory_write_options: S3WriteOptions::default(),
+            bucket_staging: None,
             closed: Arc::new(AtomicBool::new(false)),
         })
     }
 
+    /// Initializes rocfl over an existing OCFL repository that was created by another tool.
+    /// The repository's spec version and storage layout are detected and verified, and any
+    /// missing optional files, such as the spec and layout extension documents, are written.
+    /// Existing objects are not modified.
+    pub fn init_from_existing(
+        region: Region,
+        bucket: &str,
+        prefix: Option<&str>,
+        profile: Option<&str>,
+        client_options: &S3ClientOptions,
+    ) -> Result<Self> {
+        Self::new(region, bucket, prefix, profile, client_options)?.complete_existing()
+    }
+
+    /// Initializes rocfl over an existing OCFL repository, in a cloud storage service other than
+    /// S3, that was created by another tool. Existing objects are not modified.
+    #[cfg(any(feature = "azure", feature = "gcs"))]
+    pub fn init_cloud_from_existing(
+        storage: &CloudStorage,
+        bucket: &str,
+        prefix: Option<&str>,
+    ) -> Result<Self> {
+        Self::new_cloud(storage, bucket, prefix)?.complete_existing()
+    }
+
+    /// Verifies the spec version and storage layout of an existing repository that was created
+    /// by another tool, and writes any missing optional files
+    fn complete_existing(self) -> Result<Self> {
+        let client = &*self.client;
+
+        let version = match self.repo_spec_version()? {
+            Some(Known(version)) => version,
+            Some(Unknown(version)) => {
+                return Err(RocflError::IllegalState(format!(
+                    "Cannot initialize from existing storage root. OCFL spec version {} \
+                    is not supported",
+                    version
+                )));
+            }
+            None => {
+                return Err(RocflError::IllegalState(
+                    "Cannot initialize from existing storage root. It does not contain an \
+                    OCFL version declaration"
+                        .to_string(),
+                ));
+            }
+        };
+
+        let namaste = version.root_namaste();
+        if client.get_object(namaste.filename)?.as_deref() != Some(namaste.content.as_bytes()) {
+            return Err(RocflError::IllegalState(format!(
+                "Cannot initialize from existing storage root. Version declaration {} has \
+                invalid contents",
+                namaste.filename
+            )));
+        }
+
+        info!(
+            "Initializing from existing OCFL {} storage root in bucket {} under prefix {}",
+            version.version(),
+            client.bucket(),
+            client.prefix()
+        );
+
+        let root_files = client.list_dir("")?.objects;
+
+        if !root_files
+            .iter()
+            .any(|file| file == version.spec_filename())
+        {
+            info!("Writing missing spec document {}", version.spec_filename());
+            client.put_object_bytes(
+                version.spec_filename(),
+                Bytes::from(specs::ocfl_spec(version).as_bytes()),
+                Some(TYPE_PLAIN),
+            )?;
+        }
+
+        if let Some(layout) = verify_existing_layout(client)? {
+            let extension_name = layout.extension_name().to_string();
+
+            let config_path = join(
+                &join(EXTENSIONS_DIR, &extension_name),
+                EXTENSIONS_CONFIG_FILE,
+            );
+
+            if client.get_object(&config_path)?.is_none() {
+                info!("Writing missing layout config {}", config_path);
+                client.put_object_bytes(
+                    &config_path,
+                    Bytes::from(layout.serialize()?),
+                    Some(TYPE_JSON),
+                )?;
+            }
+
+            let layout_spec_path = format!("{}.md", extension_name);
+            if !root_files.contains(&layout_spec_path) {
+                info!(
+                    "Writing missing layout extension document {}",
+                    layout_spec_path
+                );
+                client.put_object_bytes(
+                    &layout_spec_path,
+                    Bytes::from(specs::layout_extension_spec(layout.extension_name())),
+                    Some(TYPE_MARKDOWN),
+                )?;
+            }
+        }
+
+        Ok(self)
+    }
+
     /// This method first attempts to locate the path to the object using the storage layout.
     /// If it is not able to, then it scans the repository looking for the object.
     fn lookup_or_find_object_root_path(&self, object_id: &str) -> Result<String> {
@@ -124,9 +343,21 @@ impl S3OcflStore {
         }
     }
 
+    /// Returns the storage root relative path to the object's root. `RocflError::NotFound` is
+    /// returned if there is not an object at the location.
+    fn require_object_root_path(&self, object_id: &str) -> Result<String> {
+        let object_root = self.lookup_or_find_object_root_path(object_id)?;
+
+        self.find_first_version_declaration(OBJECT_NAMASTE_FILE_PREFIX, &object_root)
+            .map_err(|_| not_found(object_id, None))?;
+
+        Ok(object_root)
+    }
+
     /// Returns the storage root relative path to the object by doing a cache look up. If
-    /// the mapping was not found in the cache, then it is computed using the configured
-    /// storage layout. If there is no storage layout, then `None` is returned.
+    /// the mapping was not found in the cache, then the object index is consulted, and, finally,
+    /// it is computed using the configured storage layout. If there is no storage layout, then
+    /// `None` is returned.
     fn get_object_root_path(&self, object_id: &str) -> Option<String> {
         if let Ok(cache) = self.id_path_cache.read() {
             if let Some(object_root) = cache.get(object_id) {
@@ -134,6 +365,12 @@ impl S3OcflStore {
             }
         }
 
+        if let Ok(index) = self.object_index.read() {
+            if let Some(object_root) = index.get(object_id) {
+                return Some(object_root.clone());
+            }
+        }
+
         if let Some(storage_layout) = &self.storage_layout {
             let object_root = storage_layout.map_object_id(object_id);
 
@@ -146,7 +383,13 @@ impl S3OcflStore {
         None
     }
 
+    /// Scans the repository for the object's inventory. When the object index is a registry, the
+    /// repository is not scanned, because every object is in the index.
     fn scan_for_inventory(&self, object_id: &str) -> Result<Inventory> {
+        if self.is_object_registry() {
+            return Err(not_found(object_id, None));
+        }
+
         info!(
             "Storage layout not configured, scanning repository to locate object {}",
             &object_id
@@ -166,6 +409,157 @@ impl S3OcflStore {
         }
     }
 
+    /// Records the object's location in the object index, and persists the index, if the
+    /// location differs from the location dictated by the storage layout or the index is a
+    /// registry.
+    fn index_object(&self, object_id: &str, object_root: &str) -> Result<()> {
+        let layout_root = self
+            .storage_layout
+            .as_ref()
+            .map(|layout| layout.map_object_id(object_id));
+
+        let mut index = self
+            .object_index
+            .write()
+            .map_err(|_| object_index_lock_error())?;
+
+        let changed = if !self.is_object_registry() && layout_root.as_deref() == Some(object_root) {
+            index.remove(object_id)
+        } else {
+            index.insert(object_id, object_root)
+        };
+
+        if let Ok(mut cache) = self.id_path_cache.write() {
+            cache.insert(object_id.to_string(), object_root.to_string());
+        }
+
+        if changed {
+            write_index(&*self.client, &object_index_path(), &*index)?;
+        }
+
+        Ok(())
+    }
+
+    /// Deletes every file in the object root in purge order. Files that fail to delete are logged,
+    /// and an error is returned after attempting to delete the rest. The paths, relative the
+    /// object root, of the deleted files are returned.
+    fn delete_object_root(&self, object_root: &str) -> Result<Vec<String>> {
+        let prefix = format!("{}/", object_root);
+
+        let mut files = self
+            .client
+            .list_objects(object_root)?
+            .into_iter()
+            .filter_map(|key| key.strip_prefix(&prefix).map(String::from))
+            .collect::<Vec<String>>();
+
+        if !files.is_empty() && !files.iter().any(|file| purge_order(file) == 2) {
+            warn!(
+                "Object at {} was partially purged. Deleting its {} remaining files",
+                object_root,
+                files.len()
+            );
+        }
+
+        files.sort_unstable_by(|a, b| purge_order(a).cmp(&purge_order(b)).then(a.cmp(b)));
+
+        let mut deleted = Vec::with_capacity(files.len());
+        let mut failed = false;
+
+        for file in files {
+            if self.is_closed() {
+                error!(
+                    "Terminating purge of object at {}. Resume it with 'purge --resume-at {}'.",
+                    object_root, object_root
+                );
+                failed = true;
+                break;
+            }
+            match self.client.delete_object(&format!("{}{}", prefix, file)) {
+                Ok(_) => deleted.push(file),
+                Err(e) => {
+                    error!("Failed to delete file {}{}: {}", prefix, file, e);
+                    failed = true;
+                }
+            }
+        }
+
+        if failed {
+            Err(RocflError::General(format!(
+                "Failed to purge object at {}",
+                object_root
+            )))
+        } else {
+            Ok(deleted)
+        }
+    }
+
+    /// Returns an iterator over the inventories of the objects in the object index, optionally
+    /// filtered by a glob pattern that object IDs must match
+    #[allow(clippy::result_large_err)]
+    fn iter_registered_inventories<'a>(
+        &'a self,
+        filter_glob: Option<&str>,
+    ) -> Result<Box<dyn Iterator<Item = Result<Inventory, ObjectError>> + 'a>> {
+        let matcher = match filter_glob {
+            Some(glob) => Some(
+                GlobBuilder::new(glob)
+                    .backslash_escape(true)
+                    .build()?
+                    .compile_matcher(),
+            ),
+            None => None,
+        };
+
+        let entries: Vec<(String, String)> = self
+            .object_index
+            .read()
+            .map_err(|_| object_index_lock_error())?
+            .entries()
+            .filter(|(object_id, _)| matcher.as_ref().is_none_or(|m| m.is_match(object_id)))
+            .map(|(object_id, object_root)| (object_id.clone(), object_root.clone()))
+            .collect();
+
+        Ok(Box::new(
+            entries
+                .into_iter()
+                .take_while(move |_| !self.closed.load(Ordering::Acquire))
+                .map(move |(object_id, object_root)| {
+                    self.parse_inventory_required(&object_id, &object_root)
+                        .map_err(|e| ObjectError::new(Some(object_id), Some(object_root), e))
+                }),
+        ))
+    }
+
+    /// Removes the object from the object index, if it is indexed
+    fn unindex_object(&self, object_id: &str) -> Result<()> {
+        if let Ok(mut cache) = self.id_path_cache.write() {
+            cache.remove(object_id);
+        }
+
+        let mut index = self
+            .object_index
+            .write()
+            .map_err(|_| object_index_lock_error())?;
+
+        if index.remove(object_id) {
+            write_index(&*self.client, &object_index_path(), &*index)?;
+        }
+
+        Ok(())
+    }
+
+    /// Removes all of the object's aliases from the alias index
+    fn unalias_object(&self, object_id: &str) -> Result<()> {
+        let mut index = self.alias_index.write().map_err(|_| alias_lock_error())?;
+
+        if index.remove_object(object_id) {
+            write_index(&*self.client, &alias_index_path(), &*index)?;
+        }
+
+        Ok(())
+    }
+
     fn parse_inventory_required(&self, object_id: &str, object_root: &str) -> Result<Inventory> {
         match self.parse_inventory(object_root)? {
             Some(inventory) => {
@@ -224,14 +618,14 @@ impl S3OcflStore {
     fn get_inventory_bytes(&self, object_root: &str) -> Result<Option<(Vec<u8>, bool)>> {
         let mutable_head_inv = join(object_root, MUTABLE_HEAD_INVENTORY_FILE);
 
-        match self.s3_client.get_object(&mutable_head_inv)? {
+        match self.client.get_object(&mutable_head_inv)? {
             Some(bytes) => {
                 info!("Found mutable HEAD at {}", &mutable_head_inv);
                 Ok(Some((bytes, true)))
             }
             None => {
                 let inv_path = join(object_root, INVENTORY_FILE);
-                match self.s3_client.get_object(&inv_path)? {
+                match self.client.get_object(&inv_path)? {
                     Some(bytes) => Ok(Some((bytes, false))),
                     None => Ok(None),
                 }
@@ -239,35 +633,159 @@ impl S3OcflStore {
         }
     }
 
+    /// Uploads all of the files in `src_dir` to `dst_path`. `object_path` is the path of
+    /// `src_dir` relative the object root, and is used to identify the content files in the
+    /// upload. The digest of every content file that is uploaded is verified against the digest
+    /// in the inventory's manifest. The inventory files in `src_dir` are uploaded last. If any
+    /// upload fails, all of the files that were uploaded are deleted.
     fn upload_all_files_with_rollback(
         &self,
         dst_path: &str,
         src_dir: impl AsRef<Path>,
+        inventory: &Inventory,
+        object_path: &str,
     ) -> Result<Vec<String>> {
+        let mut files = Vec::new();
+
+        for file in WalkDir::new(src_dir.as_ref()) {
+            let file = file?;
+            if !file.file_type().is_dir() {
+                files.push(file.into_path());
+            }
+        }
+
+        let inventory_path = join(object_path, INVENTORY_FILE);
+        let mut uploads = Vec::with_capacity(files.len());
+
+        for file in files {
+            let relative_path = pathdiff::diff_paths(&file, src_dir.as_ref())
+                .unwrap()
+                .to_string_lossy()
+                .to_string();
+            let relative_path = util::convert_backslash_to_forward(relative_path.as_ref());
+            let object_relative = join(object_path, relative_path.as_ref());
+            uploads.push((
+                object_relative.starts_with(&inventory_path),
+                join(dst_path, relative_path.as_ref()),
+                object_relative,
+                file,
+            ));
+        }
+
+        // The inventory must not be installed until all of the content is verified
+        uploads.sort_by_key(|(is_inventory, ..)| *is_inventory);
+
+        let copies = if self.bucket_staging.is_some() {
+            self.staged_content_copies(dst_path, inventory, object_path, &uploads)
+        } else {
+            Vec::new()
+        };
+
         self.do_with_rollback(Vec::new(), |done: &mut Vec<String>| -> Result<()> {
-            for file in WalkDir::new(src_dir.as_ref()) {
+            for (staged_path, storage_path) in &copies {
+                self.ensure_open()?;
+                self.client
+                    .copy_object(staged_path, storage_path, &self.content_write_options)?;
+                done.push(storage_path.clone());
+            }
+
+            for (is_inventory, storage_path, object_relative, file) in &uploads {
                 // Want an error returned here so that we rollback
                 self.ensure_open()?;
 
-                let file = file?;
-                if file.file_type().is_dir() {
-                    continue;
-                }
+                let expected = ContentPath::try_from(object_relative.as_str())
+                    .ok()
+                    .and_then(|content_path| inventory.digest_for_content_path(&content_path));
 
-                let relative_path = pathdiff::diff_paths(file.path(), src_dir.as_ref())
-                    .unwrap()
-                    .to_string_lossy()
-                    .to_string();
-                let content_path = util::convert_backslash_to_forward(relative_path.as_ref());
-                let storage_path = join(dst_path, content_path.as_ref());
-                self.s3_client
-                    .put_object_file(&storage_path, file.path(), None)?;
-                done.push(storage_path);
+                let write_options = if *is_inventory {
+                    &self.inventory_write_options
+                } else {
+                    &self.content_write_options
+                };
+
+                let actual = self.client.put_object_file(
+                    storage_path,
+                    file,
+                    None,
+                    expected.map(|_| inventory.digest_algorithm),
+                    write_options,
+                )?;
+                done.push(storage_path.clone());
+
+                if let (Some(expected), Some(actual)) = (expected, actual) {
+                    if expected.as_ref() != &actual {
+                        return Err(RocflError::CorruptObject {
+                            object_id: inventory.id.clone(),
+                            message: format!(
+                                "Content file {} does not match its manifest digest. Expected: {}; Found: {}",
+                                object_relative, expected, actual
+                            ),
+                        });
+                    }
+                }
             }
             Ok(())
         })
     }
 
+    /// Returns the directory, relative the repository prefix, that the content of the staged
+    /// object at the staging object root is staged in within the bucket
+    fn bucket_staged_root(&self, object_root: &str) -> String {
+        join(
+            self.bucket_staging.as_deref().unwrap_or(BUCKET_STAGING_DIR),
+            object_root,
+        )
+    }
+
+    /// Returns the path, relative the repository prefix, that a staged content file is staged at
+    /// in the bucket
+    fn bucket_staging_path(&self, object_root: &str, content_path: &str) -> String {
+        join(&self.bucket_staged_root(object_root), content_path)
+    }
+
+    /// Returns the bucket staging path and destination path of every content file in the staged
+    /// version that is staged in the bucket rather than in the local version directory, which are
+    /// the files that are not being uploaded
+    fn staged_content_copies(
+        &self,
+        dst_path: &str,
+        inventory: &Inventory,
+        object_path: &str,
+        uploads: &[(bool, String, String, PathBuf)],
+    ) -> Vec<(String, String)> {
+        let uploaded = uploads
+            .iter()
+            .map(|(_, _, object_relative, _)| object_relative.as_str())
+            .collect::<HashSet<&str>>();
+        let version_prefix = format!("{}/", inventory.head);
+        let object_prefix = format!("{}/", object_path);
+
+        let mut copies = Vec::new();
+
+        for (_, content_paths) in inventory.manifest().iter_id_paths() {
+            for content_path in content_paths {
+                if content_path.starts_with(&version_prefix)
+                    && !uploaded.contains(content_path.as_str())
+                {
+                    let relative_path = if object_path.is_empty() {
+                        content_path.as_str()
+                    } else {
+                        content_path
+                            .as_str()
+                            .strip_prefix(&object_prefix)
+                            .unwrap_or_else(|| content_path.as_str())
+                    };
+                    copies.push((
+                        self.bucket_staging_path(&inventory.object_root, content_path.as_str()),
+                        join(dst_path, relative_path),
+                    ));
+                }
+            }
+        }
+
+        copies
+    }
+
     fn install_inventory_in_root_with_rollback(
         &self,
         object_root: &str,
@@ -284,11 +802,21 @@ impl S3OcflStore {
         );
 
         self.do_with_rollback(uploaded, |done: &mut Vec<String>| -> Result<()> {
-            self.s3_client
-                .put_object_file(&inventory_dst, &inventory_src, Some(TYPE_JSON))?;
+            self.client.put_object_file(
+                &inventory_dst,
+                &inventory_src,
+                Some(TYPE_JSON),
+                None,
+                &self.inventory_write_options,
+            )?;
             done.push(inventory_dst.clone());
-            self.s3_client
-                .put_object_file(&sidecar_dst, &sidecar_src, Some(TYPE_PLAIN))?;
+            self.client.put_object_file(
+                &sidecar_dst,
+                &sidecar_src,
+                Some(TYPE_PLAIN),
+                None,
+                &self.inventory_write_options,
+            )?;
             Ok(())
         })?;
 
@@ -302,7 +830,7 @@ impl S3OcflStore {
     ) -> Result<Vec<String>> {
         if let Err(e) = callable(&mut done) {
             for path in &done {
-                if let Err(e2) = self.s3_client.delete_object(path) {
+                if let Err(e2) = self.client.delete_object(path) {
                     error!("Failed to rollback file {}: {}", path, e2);
                 }
             }
@@ -314,7 +842,7 @@ impl S3OcflStore {
 
     fn write_object_namaste(&self, object_root: &str, version: SpecVersion) -> Result<()> {
         let object_namaste = version.object_namaste();
-        self.s3_client.put_object_bytes(
+        self.client.put_object_bytes(
             &join(object_root, object_namaste.filename),
             Bytes::from(object_namaste.content.as_bytes()),
             Some(TYPE_PLAIN),
@@ -324,7 +852,36 @@ impl S3OcflStore {
 
     /// Pass through to S3 to list the contents of a path in S3
     fn list_dir(&self, path: &str) -> Result<ListResult> {
-        self.s3_client.list_dir(path)
+        self.client.list_dir(path)
+    }
+
+    /// Returns the paths, relative the object root, of all of the files within the object's version
+    /// content directories that are not referenced in its manifest
+    fn find_orphaned_content(&self, object_id: &str, object_root: &str) -> Result<Vec<String>> {
+        let inventory = self.get_inventory(object_id)?;
+        let content_dir = inventory.defaulted_content_dir();
+        let mut orphans = Vec::new();
+
+        for version_num in inventory.versions.keys() {
+            let version_content = join(&version_num.to_string(), content_dir);
+
+            for key in self
+                .client
+                .list_objects(&join(object_root, &version_content))?
+            {
+                let path = &key[object_root.len() + 1..];
+                let referenced = ContentPath::try_from(path)
+                    .map(|content_path| inventory.contains_content_path(&content_path))
+                    .unwrap_or(false);
+                if !referenced {
+                    orphans.push(path.to_string());
+                }
+            }
+        }
+
+        orphans.sort_unstable();
+
+        Ok(orphans)
     }
 
     /// Lists all extension names in the `extensions` directory under the specified `base_dir`
@@ -407,21 +964,133 @@ impl OcflStore for S3OcflStore {
         }
     }
 
+    /// Returns true if the specified object exists. Only the object's namaste file is checked,
+    /// and its inventory is not read.
+    fn object_exists(&self, object_id: &str) -> Result<bool> {
+        self.ensure_open()?;
+
+        match self.require_object_root_path(object_id) {
+            Ok(_) => Ok(true),
+            Err(RocflError::NotFound(_)) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Returns the number of the specified object's HEAD version, or a `RocflError::NotFound` if
+    /// the object does not exist. The version is identified from the object's version
+    /// directories rather than by reading its inventory, unless the object has a mutable HEAD.
+    fn head_version(&self, object_id: &str) -> Result<VersionNum> {
+        self.ensure_open()?;
+
+        let object_root = self.lookup_or_find_object_root_path(object_id)?;
+        let listing = self.client.list_dir(&object_root)?;
+
+        let namaste_prefix = join(&object_root, OBJECT_NAMASTE_FILE_PREFIX);
+        if !listing
+            .objects
+            .iter()
+            .any(|key| key.starts_with(&namaste_prefix))
+        {
+            return Err(not_found(object_id, None));
+        }
+
+        let extensions_dir = join(&object_root, EXTENSIONS_DIR);
+        if listing.directories.contains(&extensions_dir)
+            && self
+                .client
+                .list_dir(&extensions_dir)?
+                .directories
+                .contains(&join(&object_root, MUTABLE_HEAD_EXT_DIR))
+        {
+            return Ok(self.parse_inventory_required(object_id, &object_root)?.head);
+        }
+
+        let mut versions: Vec<VersionNum> = listing
+            .directories
+            .iter()
+            .filter_map(|dir| {
+                let name = dir.rsplit('/').next().unwrap_or(dir);
+                VersionNum::try_from(name).ok()
+            })
+            .collect();
+
+        versions.sort_unstable();
+
+        // A version is only complete once its inventory has been uploaded, which is done last
+        for version_num in versions.into_iter().rev() {
+            let version_dir = join(&object_root, &version_num.to_string());
+            let inventory_key = join(&version_dir, INVENTORY_FILE);
+
+            if self
+                .client
+                .list_dir(&version_dir)?
+                .objects
+                .contains(&inventory_key)
+            {
+                return Ok(version_num);
+            }
+        }
+
+        Err(RocflError::CorruptObject {
+            object_id: object_id.to_string(),
+            message: "The object does not contain any versions".to_string(),
+        })
+    }
+
     /// Returns an iterator that iterates over every object in an OCFL repository, returning
     /// the most recent inventory of each. Optionally, a glob pattern may be provided that filters
     /// the objects that are returned by OCFL ID.
+    ///
+    /// Objects that cannot be read are returned as `ObjectError`s that identify the object.
     fn iter_inventories<'a>(
         &'a self,
         filter_glob: Option<&str>,
-    ) -> Result<Box<dyn Iterator<Item = Result<Inventory>> + 'a>> {
+    ) -> Result<Box<dyn Iterator<Item = Result<Inventory, ObjectError>> + 'a>> {
         self.ensure_open()?;
 
+        if self.is_object_registry() {
+            return self.iter_registered_inventories(filter_glob);
+        }
+
         Ok(Box::new(match filter_glob {
             Some(glob) => InventoryIter::new_glob_matching(self, glob, self.closed.clone())?,
             None => InventoryIter::new(self, None, self.closed.clone()),
         }))
     }
 
+    /// Returns the IDs of up to `limit` objects whose IDs start with `prefix`, sorted
+    fn list_object_ids(&self, prefix: &str, limit: usize) -> Result<Vec<String>> {
+        self.ensure_open()?;
+
+        if self.is_object_registry() {
+            let index = self
+                .object_index
+                .read()
+                .map_err(|_| object_index_lock_error())?;
+            return Ok(index
+                .entries()
+                .map(|(object_id, _)| object_id)
+                .filter(|object_id| object_id.starts_with(prefix))
+                .take(limit)
+                .cloned()
+                .collect());
+        }
+
+        let prefix = prefix.to_string();
+        let mut ids: Vec<String> = InventoryIter::new(
+            self,
+            Some(Box::new(move |id| id.starts_with(&prefix))),
+            self.closed.clone(),
+        )
+        .filter_map(|inventory| inventory.ok())
+        .map(|inventory| inventory.id)
+        .take(limit)
+        .collect();
+
+        ids.sort();
+        Ok(ids)
+    }
+
     /// Writes the specified file to the sink.
     ///
     /// If the file cannot be found, then a `RocflError::NotFound` error is returned.
@@ -437,15 +1106,54 @@ impl OcflStore for S3OcflStore {
         let inventory = self.get_inventory(object_id)?;
 
         let content_path = inventory.content_path_for_logical_path(path, version_num)?;
+        self.get_content_file(&inventory, content_path, sink)
+    }
+
+    /// Writes the file at the specified content path within the object described by the
+    /// inventory to the sink.
+    fn get_content_file(
+        &self,
+        inventory: &Inventory,
+        content_path: &ContentPath,
+        sink: &mut dyn Write,
+    ) -> Result<()> {
+        self.ensure_open()?;
+
+        let storage_path = join(&inventory.object_root, content_path.as_str());
+
+        self.client.stream_object(&storage_path, sink)
+    }
+
+    /// Returns the size, in bytes, of the file at the specified content path within the object
+    /// described by the inventory.
+    fn get_content_file_size(
+        &self,
+        inventory: &Inventory,
+        content_path: &ContentPath,
+    ) -> Result<u64> {
+        self.ensure_open()?;
+
         let storage_path = join(&inventory.object_root, content_path.as_str());
 
-        self.s3_client.stream_object(&storage_path, sink)
+        self.client.object_size(&storage_path)
     }
 
     /// Writes a new OCFL object. The contents at `object_path` must be a fully formed OCFL
     /// object that is able to be moved into place with no additional modifications.
     ///
     /// The object must not already exist.
+    fn new_object_root(&self, object_id: &str, object_root: Option<&str>) -> Result<String> {
+        match object_root {
+            Some(root) => Ok(util::trim_slashes(root).to_string()),
+            None => self.get_object_root_path(object_id).ok_or_else(|| {
+                RocflError::IllegalState(
+                    "Cannot create object because the repository does not have a defined storage layout, and an object root path was not specified."
+                        .to_string(),
+                )
+            }),
+        }
+    }
+
     fn write_new_object(
         &self,
         inventory: &mut Inventory,
@@ -454,21 +1162,10 @@ impl OcflStore for S3OcflStore {
     ) -> Result<()> {
         self.ensure_open()?;
 
-        let object_root = match self.get_object_root_path(&inventory.id) {
-            Some(object_root) => object_root,
-            None => {
-                if let Some(root) = object_root {
-                    util::trim_slashes(root).to_string()
-                } else {
-                    return Err(RocflError::IllegalState(
-                            "Cannot create object because the repository does not have a defined storage layout, and an object root path was not specified."
-                                .to_string(),
-                        ));
-                }
-            }
-        };
+        let requested_root = object_root;
+        let object_root = self.new_object_root(&inventory.id, requested_root)?;
 
-        if !self.s3_client.list_dir(&object_root)?.is_empty() {
+        if !self.client.list_dir(&object_root)?.is_empty() {
             return Err(RocflError::IllegalState(format!(
                 "Cannot create object {} because there are existing files at {}",
                 inventory.id, object_root
@@ -477,7 +1174,11 @@ impl OcflStore for S3OcflStore {
 
         info!("Creating new object {}", inventory.id);
 
-        self.upload_all_files_with_rollback(&object_root, src_object_path)?;
+        self.upload_all_files_with_rollback(&object_root, src_object_path, inventory, "")?;
+
+        if requested_root.is_some() || self.is_object_registry() {
+            self.index_object(&inventory.id, &object_root)?;
+        }
 
         inventory.storage_path = match &self.prefix {
             Some(prefix) => join(prefix, &inventory.object_root),
@@ -514,7 +1215,7 @@ impl OcflStore for S3OcflStore {
 
         let version_dst_path = join(&existing_inventory.object_root, &version_str);
 
-        if !self.s3_client.list_dir(&version_dst_path)?.is_empty() {
+        if !self.client.list_dir(&version_dst_path)?.is_empty() {
             return Err(RocflError::IllegalState(
                 format!("Cannot create version {} in object {} because the version directory already exists.",
                         version_str, inventory.id)));
@@ -525,7 +1226,12 @@ impl OcflStore for S3OcflStore {
             version_str, inventory.id
         );
 
-        let uploaded = self.upload_all_files_with_rollback(&version_dst_path, version_path)?;
+        let uploaded = self.upload_all_files_with_rollback(
+            &version_dst_path,
+            version_path,
+            inventory,
+            &version_str,
+        )?;
         self.install_inventory_in_root_with_rollback(
             &existing_inventory.object_root,
             inventory.digest_algorithm,
@@ -544,13 +1250,55 @@ impl OcflStore for S3OcflStore {
                 inventory.spec_version().unwrap(),
             )?;
             for old in old_namastes {
-                self.s3_client.delete_object(&old)?;
+                self.client.delete_object(&old)?;
             }
         }
 
         Ok(())
     }
 
+    /// S3 does not support hard links, so this does nothing
+    fn link_duplicate_content(&self, _inventory: &Inventory) -> Result<usize> {
+        Ok(0)
+    }
+
+    fn set_s3_write_options(&mut self, content: S3WriteOptions, inventory: S3WriteOptions) {
+        self.content_write_options = content;
+        self.inventory_write_options = inventory;
+    }
+
+    fn set_thread_pool(&mut self, pool: ThreadPool) {
+        self.validator.set_thread_pool(pool);
+    }
+
+    fn set_encryption_key(&mut self, key: EncryptionKey) {
+        self.validator.set_encryption_key(key);
+    }
+
+    fn s3_request_metrics(&self) -> Option<S3RequestMetrics> {
+        self.client.request_metrics()
+    }
+
+    fn set_bucket_staging(&mut self, staging_id: Option<String>) {
+        self.bucket_staging = staging_id.map(|id| join(BUCKET_STAGING_DIR, &id));
+    }
+
+    fn bucket_staging(&self) -> Option<&dyn BucketStaging> {
+        if self.bucket_staging.is_some() {
+            Some(self)
+        } else {
+            None
+        }
+    }
+
+    fn set_strict_paths(&self, strict: bool) {
+        self.validator.set_strict_paths(strict);
+    }
+
+    fn set_sample_history(&self, history: HashMap<String, FixitySampleRecord>) {
+        self.validator.set_sample_history(history);
+    }
+
     /// Purges the specified object from the repository, if it exists. If it does not exist,
     /// nothing happens. Any dangling directories that were created as a result of purging
     /// the object are also removed.
@@ -565,22 +1313,7 @@ impl OcflStore for S3OcflStore {
 
         info!("Purging object {} at {}", object_id, object_root);
 
-        let mut failed = false;
-
-        for file in self.s3_client.list_objects(&object_root)? {
-            if self.is_closed() {
-                error!("Terminating purge of object {} at {}. This object will need to be cleaned up manually.",
-                       object_id, object_root);
-                failed = true;
-                break;
-            }
-            if let Err(e) = self.s3_client.delete_object(&file) {
-                error!("Failed to delete file {}: {}", file, e);
-                failed = true;
-            }
-        }
-
-        if failed {
+        if self.delete_object_root(&object_root).is_err() {
             return Err(RocflError::CorruptObject {
                 object_id: object_id.to_string(),
                 message: format!(
@@ -590,7 +1323,109 @@ impl OcflStore for S3OcflStore {
             });
         }
 
-        Ok(())
+        self.unindex_object(object_id)?;
+        self.unalias_object(object_id)
+    }
+
+    /// Finishes purging the object at the storage root relative path, for objects whose purge
+    /// was interrupted and can no longer be found by ID. The paths, relative the object root, of
+    /// the deleted files are returned.
+    fn purge_object_root(&self, object_root: &str) -> Result<Vec<String>> {
+        self.ensure_open()?;
+
+        let object_root = util::trim_slashes(object_root);
+        validate_object_root(object_root)?;
+
+        let listing = self.client.list_dir(object_root)?;
+
+        verify_partially_purged(
+            object_root,
+            listing
+                .objects
+                .iter()
+                .map(|key| key.rsplit('/').next().unwrap_or_default()),
+        )?;
+
+        info!("Resuming purge of object at {}", object_root);
+
+        let deleted = self.delete_object_root(object_root).map_err(|_| {
+            RocflError::General(format!(
+                "Failed to purge object at {}. This object may need to be removed manually.",
+                object_root
+            ))
+        })?;
+
+        let object_ids = self
+            .object_index
+            .read()
+            .map_err(|_| object_index_lock_error())?
+            .object_ids_at(object_root);
+
+        for object_id in object_ids {
+            self.unindex_object(&object_id)?;
+            self.unalias_object(&object_id)?;
+        }
+
+        Ok(deleted)
+    }
+
+    /// Returns the paths, relative the object root, of every file within the object's version
+    /// content directories that is not referenced in the object's manifest. The paths are sorted.
+    fn list_orphaned_content(&self, object_id: &str) -> Result<Vec<String>> {
+        self.ensure_open()?;
+
+        let object_root = self.require_object_root_path(object_id)?;
+        self.find_orphaned_content(object_id, &object_root)
+    }
+
+    /// Deletes every file within the object's version content directories that is not referenced
+    /// in the object's manifest. S3 does not have directories, so there are no empty directories
+    /// to remove. The paths, relative the object root, of the deleted files are returned.
+    fn rm_orphaned_content(&self, object_id: &str) -> Result<Vec<String>> {
+        self.ensure_open()?;
+
+        let object_root = self.require_object_root_path(object_id)?;
+        let orphans = self.find_orphaned_content(object_id, &object_root)?;
+
+        for orphan in &orphans {
+            self.client.delete_object(&join(&object_root, orphan))?;
+        }
+
+        Ok(orphans)
+    }
+
+    /// Returns the paths, relative the storage root, of every file within the object's root,
+    /// sorted. `RocflError::NotFound` is returned if the object does not exist.
+    fn list_object_files(&self, object_id: &str) -> Result<Vec<String>> {
+        self.ensure_open()?;
+
+        let object_root = self.require_object_root_path(object_id)?;
+        let mut files = self.client.list_objects(&object_root)?;
+
+        files.sort_unstable();
+        Ok(files)
+    }
+
+    /// Writes the file at the specified path, relative the object's root, to the sink.
+    ///
+    /// If the file cannot be found, then a `RocflError::NotFound` error is returned.
+    fn get_object_root_file(
+        &self,
+        object_id: &str,
+        path: &str,
+        sink: &mut dyn Write,
+    ) -> Result<()> {
+        self.ensure_open()?;
+
+        let object_root = self.require_object_root_path(object_id)?;
+
+        match self.client.get_object(&join(&object_root, path))? {
+            Some(bytes) => {
+                sink.write_all(&bytes)?;
+                Ok(())
+            }
+            None => Err(not_found_object_file(object_id, path)),
+        }
     }
 
     /// Returns a list of all of the extension names that are associated with the object
@@ -602,17 +1437,159 @@ impl OcflStore for S3OcflStore {
         self.list_extensions(&object_root)
     }
 
+    /// Returns the paths, relative the object's `extensions` directory, of every file in the
+    /// object's `extensions` directory. If the object does not have an `extensions` directory,
+    /// then an empty vector is returned.
+    fn list_object_extension_files(&self, object_id: &str) -> Result<Vec<String>> {
+        self.ensure_open()?;
+
+        let object_root = self.require_object_root_path(object_id)?;
+        let extensions_dir = join(&object_root, EXTENSIONS_DIR);
+
+        let mut files: Vec<String> = self
+            .client
+            .list_objects(&extensions_dir)?
+            .into_iter()
+            .map(|key| key[extensions_dir.len() + 1..].to_string())
+            .collect();
+
+        files.sort_unstable();
+
+        Ok(files)
+    }
+
+    /// Writes the file at the specified path, relative the object's `extensions` directory, to
+    /// the sink.
+    ///
+    /// If the file cannot be found, then a `RocflError::NotFound` error is returned.
+    fn get_object_extension_file(
+        &self,
+        object_id: &str,
+        path: &str,
+        sink: &mut dyn Write,
+    ) -> Result<()> {
+        self.ensure_open()?;
+
+        let object_root = self.require_object_root_path(object_id)?;
+        let file_path = join(&join(&object_root, EXTENSIONS_DIR), path);
+
+        match self.client.get_object(&file_path)? {
+            Some(bytes) => {
+                sink.write_all(&bytes)?;
+                Ok(())
+            }
+            None => Err(not_found_extension_file(object_id, path)),
+        }
+    }
+
+    /// Returns the paths, relative the object's `logs` directory, of every file in the object's
+    /// `logs` directory. If the object does not have a `logs` directory, then an empty vector
+    /// is returned.
+    fn list_object_logs(&self, object_id: &str) -> Result<Vec<String>> {
+        self.ensure_open()?;
+
+        let object_root = self.require_object_root_path(object_id)?;
+        let logs_dir = join(&object_root, LOGS_DIR);
+
+        let mut logs: Vec<String> = self
+            .client
+            .list_objects(&logs_dir)?
+            .into_iter()
+            .map(|key| key[logs_dir.len() + 1..].to_string())
+            .collect();
+
+        logs.sort_unstable();
+
+        Ok(logs)
+    }
+
+    /// Writes the file at the specified path, relative the object's `logs` directory, to the sink.
+    ///
+    /// If the file cannot be found, then a `RocflError::NotFound` error is returned.
+    fn get_object_log_file(&self, object_id: &str, path: &str, sink: &mut dyn Write) -> Result<()> {
+        self.ensure_open()?;
+
+        let object_root = self.require_object_root_path(object_id)?;
+        let log_path = join(&join(&object_root, LOGS_DIR), path);
+
+        match self.client.get_object(&log_path)? {
+            Some(bytes) => {
+                sink.write_all(&bytes)?;
+                Ok(())
+            }
+            None => Err(not_found_log(object_id, path)),
+        }
+    }
+
+    /// Appends the bytes to the file at the specified path, relative the object's `logs`
+    /// directory. The file is created if it does not exist. S3 does not support appending, so
+    /// the existing file is read and rewritten.
+    fn append_object_log(&self, object_id: &str, path: &str, bytes: &[u8]) -> Result<()> {
+        self.ensure_open()?;
+
+        let object_root = self.require_object_root_path(object_id)?;
+        let log_path = join(&join(&object_root, LOGS_DIR), path);
+
+        let mut contents = self.client.get_object(&log_path)?.unwrap_or_default();
+        contents.extend_from_slice(bytes);
+
+        self.client
+            .put_object_bytes(&log_path, Bytes::from(contents), Some(TYPE_PLAIN))
+    }
+
+    /// Writes the bytes of the object's root inventory, or of the inventory in the specified
+    /// version's directory, to the sink.
+    ///
+    /// If the inventory cannot be found, then a `RocflError::NotFound` error is returned.
+    fn get_inventory_file(
+        &self,
+        object_id: &str,
+        version_num: Option<VersionNum>,
+        sink: &mut dyn Write,
+    ) -> Result<()> {
+        self.ensure_open()?;
+
+        let object_root = self.require_object_root_path(object_id)?;
+        let inventory_path = match version_num {
+            Some(version_num) => join(
+                &join(&object_root, &version_num.to_string()),
+                INVENTORY_FILE,
+            ),
+            None => join(&object_root, INVENTORY_FILE),
+        };
+
+        match self.client.get_object(&inventory_path)? {
+            Some(bytes) => {
+                sink.write_all(&bytes)?;
+                Ok(())
+            }
+            None => Err(not_found(object_id, version_num)),
+        }
+    }
+
     /// Validates the specified object and returns any problems found. Err will only be returned
     /// if a non-validation problem was encountered.
     fn validate_object(
         &self,
         object_id: &str,
-        fixity_check: bool,
+        fixity_check: FixityCheck,
     ) -> Result<ObjectValidationResult> {
         let object_root = self.lookup_or_find_object_root_path(object_id)?;
 
-        self.validator
-            .validate_object(Some(object_id), &object_root, None, fixity_check)
+        let mut result =
+            self.validator
+                .validate_object(Some(object_id), &object_root, None, fixity_check)?;
+
+        if let Some(layout) = &self.storage_layout {
+            self.validator.validate_no_duplicate_object(
+                object_id,
+                &object_root,
+                &layout.candidate_object_roots(object_id),
+                &mut result,
+            )?;
+        }
+
+        Ok(result)
     }
 
     /// Validates the specified object at the specified path, relative the storage root, and
@@ -621,22 +1598,25 @@ impl OcflStore for S3OcflStore {
     fn validate_object_at(
         &self,
         object_root: &str,
-        fixity_check: bool,
+        fixity_check: FixityCheck,
     ) -> Result<ObjectValidationResult> {
         self.validator
             .validate_object(None, object_root, None, fixity_check)
     }
 
     /// Validates the structure of an OCFL repository as well as all of the objects in the repository
-    /// When `fixity_check` is `false`, then the digests of object content files are not validated.
+    /// `fixity_check` determines which object content files have their digests validated.
     ///
     /// The storage root is validated immediately, and an incremental validator is returned that
     /// is used to lazily validate the rest of the repository.
     fn validate_repo<'a>(
         &'a self,
-        fixity_check: bool,
+        fixity_check: FixityCheck,
+        filter: ValidationFilter,
     ) -> Result<Box<dyn IncrementalValidator + 'a>> {
-        Ok(Box::new(self.validator.validate_repo(fixity_check)?))
+        Ok(Box::new(
+            self.validator.validate_repo(fixity_check, filter)?,
+        ))
     }
 
     /// Returns details about an OCFL repository
@@ -646,13 +1626,56 @@ impl OcflStore for S3OcflStore {
         let version = self.find_first_version_declaration(ROOT_NAMASTE_FILE_PREFIX, "")?;
 
         let layout =
-            load_ocfl_layout::<OcflLayoutLenient>(&self.s3_client).map(|layout| layout.extension);
+            load_ocfl_layout::<OcflLayoutLenient>(&*self.client).map(|layout| layout.extension);
 
         let extensions = self.list_extensions("")?;
 
         Ok(RepoInfo::new(version, layout, extensions))
     }
 
+    /// Returns the repository's storage layout configuration, and any discrepancies between the
+    /// layout declared in `ocfl_layout.json` and the extension configs in `extensions`.
+    fn verify_layout(&self) -> Result<LayoutInfo> {
+        self.ensure_open()?;
+        super::verify_layout(&S3Storage::new(self.client.clone()))
+    }
+
+    /// Checks the storage root's namaste file and storage layout for problems
+    fn check_storage_root(&self) -> Result<Vec<HealthProblem>> {
+        self.ensure_open()?;
+        super::check_storage_root(&S3Storage::new(self.client.clone()))
+    }
+
+    /// Checks the object's root for version directories that are not in its inventory, and for
+    /// a missing root inventory sidecar
+    fn check_object_root(&self, inventory: &Inventory) -> Result<Vec<HealthProblem>> {
+        self.ensure_open()?;
+        super::check_object_root(&S3Storage::new(self.client.clone()), inventory)
+    }
+
+    /// Rewrites the description in the repository's `ocfl_layout.json`
+    fn set_layout_description(&self, description: &str) -> Result<()> {
+        self.ensure_open()?;
+
+        let bytes = self.client.get_object(OCFL_LAYOUT_FILE)?.ok_or_else(|| {
+            RocflError::NotFound(format!(
+                "The repository in bucket {} under prefix {} does not contain an {} file",
+                self.client.bucket(),
+                self.client.prefix(),
+                OCFL_LAYOUT_FILE
+            ))
+        })?;
+
+        let mut layout: OcflLayoutLenient = serde_json::from_slice(&bytes)?;
+        layout.description = description.to_string();
+
+        self.client.put_object_bytes(
+            OCFL_LAYOUT_FILE,
+            Bytes::from(serde_json::to_vec_pretty(&layout)?),
+            Some(TYPE_JSON),
+        )
+    }
+
     /// Returns details about an OCFL object
     fn describe_object(&self, object_id: &str) -> Result<ObjectInfo> {
         self.ensure_open()?;
@@ -665,67 +1688,630 @@ impl OcflStore for S3OcflStore {
             .map_err(|_| not_found(object_id, None))?;
         let extensions = self.list_object_extensions(object_id)?;
 
-        let algorithm = if SUPPORTED_VERSIONS.contains(&version.as_str()) {
-            Some(
-                self.parse_inventory_required(object_id, &object_root)?
-                    .digest_algorithm
-                    .to_string(),
-            )
+        let inventory = if SUPPORTED_VERSIONS.contains(&version.as_str()) {
+            Some(self.parse_inventory_required(object_id, &object_root)?)
+        } else {
+            None
+        };
+
+        let frozen = if extensions.iter().any(|ext| ext == ROCFL_FROZEN_EXTENSION) {
+            self.get_freeze_marker(object_id)?
         } else {
             None
         };
 
-        Ok(ObjectInfo::new(version, algorithm, extensions))
+        Ok(ObjectInfo::new(
+            version,
+            inventory.as_ref(),
+            extensions,
+            frozen,
+        ))
+    }
+
+    /// Upgrades the repository to the specified version
+    fn upgrade_repo(&self, version: SpecVersion) -> Result<()> {
+        self.ensure_open()?;
+
+        let old_namastes = self.find_files("", ROOT_NAMASTE_FILE_PREFIX)?;
+
+        write_namaste_and_spec(&*self.client, version, true)?;
+
+        for old in old_namastes {
+            self.client.delete_object(&old)?;
+        }
+
+        Ok(())
+    }
+
+    /// Adds the existing object rooted at `object_root`, relative the storage root, to the
+    /// repository's object index. Indexed objects are located by ID, even when they do not reside
+    /// where the storage layout expects them, or the repository does not have a storage layout.
+    ///
+    /// The adopted object's inventory is returned.
+    fn adopt_object(&self, object_root: &str) -> Result<Inventory> {
+        self.ensure_open()?;
+
+        let object_root = util::trim_slashes(object_root);
+
+        if !is_object_dir(&self.client.list_dir(object_root)?.objects) {
+            return Err(RocflError::NotFound(format!(
+                "Object at path {}",
+                object_root
+            )));
+        }
+
+        let inventory = match self.parse_inventory(object_root)? {
+            Some(inventory) => inventory,
+            None => {
+                return Err(RocflError::NotFound(format!(
+                    "Object at path {}",
+                    object_root
+                )))
+            }
+        };
+
+        if let Some(existing_root) = self.get_object_root_path(&inventory.id) {
+            if existing_root != object_root
+                && self
+                    .client
+                    .list_dir(&existing_root)
+                    .map(|result| is_object_dir(&result.objects))
+                    .unwrap_or(false)
+            {
+                return Err(RocflError::IllegalState(format!(
+                    "Cannot adopt object {} at {} because it already exists at {}",
+                    inventory.id, object_root, existing_root
+                )));
+            }
+        }
+
+        info!("Adopting object {} at {}", inventory.id, object_root);
+
+        self.index_object(&inventory.id, object_root)?;
+
+        Ok(inventory)
+    }
+
+    /// Returns `true` if the object index is a registry that records the location of every object
+    /// in the repository
+    fn is_object_registry(&self) -> bool {
+        self.object_registry.load(Ordering::Acquire)
+    }
+
+    /// Enables or disables the object registry. Enabling the registry scans the repository once
+    /// to index its existing objects. The number of objects in the object index is returned.
+    fn set_object_registry(&self, enabled: bool) -> Result<usize> {
+        self.ensure_open()?;
+
+        // The scan reads the object index, so it must complete before the index is locked
+        let scanned: Vec<(String, String)> = if enabled {
+            info!("Indexing every object in the repository");
+
+            InventoryIter::new(self, None, self.closed.clone())
+                .filter_map(|inventory| match inventory {
+                    Ok(inventory) => Some((inventory.id, inventory.object_root)),
+                    Err(e) => {
+                        warn!("Skipping object that could not be indexed: {:#}", e);
+                        None
+                    }
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        self.ensure_open()?;
+
+        let mut index = self
+            .object_index
+            .write()
+            .map_err(|_| object_index_lock_error())?;
+
+        if enabled {
+            for (object_id, object_root) in &scanned {
+                index.insert(object_id, object_root);
+            }
+        } else if let Some(layout) = &self.storage_layout {
+            // Objects that are where the layout expects them do not need to be indexed
+            index
+                .remove_if(|object_id, object_root| layout.map_object_id(object_id) == object_root);
+        }
+
+        write_index(&*self.client, &object_index_path(), &*index)?;
+        write_index(
+            &*self.client,
+            &object_index_config_path(),
+            &ObjectIndexConfig { registry: enabled },
+        )?;
+        self.object_registry.store(enabled, Ordering::Release);
+
+        Ok(index.len())
+    }
+
+    /// Returns the ID of the object that the alias identifies, or `None` if the alias is not in
+    /// the repository's alias index.
+    fn resolve_alias(&self, alias: &str) -> Result<Option<String>> {
+        self.ensure_open()?;
+
+        let index = self.alias_index.read().map_err(|_| alias_lock_error())?;
+        Ok(index.get(alias).cloned())
+    }
+
+    /// Adds an alias for the object to the repository's alias index. An alias may only identify
+    /// a single object. It is the caller's responsibility to verify that the object exists.
+    fn add_alias(&self, object_id: &str, alias: &str) -> Result<()> {
+        self.ensure_open()?;
+
+        let mut index = self.alias_index.write().map_err(|_| alias_lock_error())?;
+
+        if index.insert(alias, object_id)? {
+            info!("Adding alias {} for object {}", alias, object_id);
+            write_index(&*self.client, &alias_index_path(), &*index)?;
+        }
+
+        Ok(())
+    }
+
+    /// Removes an alias from the repository's alias index. If the alias does not exist, then
+    /// `RocflError::NotFound` is returned.
+    fn remove_alias(&self, alias: &str) -> Result<()> {
+        self.ensure_open()?;
+
+        let mut index = self.alias_index.write().map_err(|_| alias_lock_error())?;
+
+        if index.remove(alias) {
+            info!("Removing alias {}", alias);
+            write_index(&*self.client, &alias_index_path(), &*index)
+        } else {
+            Err(RocflError::NotFound(format!("Alias {}", alias)))
+        }
+    }
+
+    /// Returns all of the aliases in the repository's alias index, sorted by alias
+    fn list_aliases(&self) -> Result<Vec<ObjectAlias>> {
+        self.ensure_open()?;
+
+        let index = self.alias_index.read().map_err(|_| alias_lock_error())?;
+        Ok(index.aliases())
+    }
+
+    /// Returns the defaults that are applied to objects created in the repository. If none
+    /// have been configured, then empty defaults are returned.
+    fn get_repo_defaults(&self) -> Result<RepoDefaults> {
+        self.ensure_open()?;
+
+        match self.client.get_object(&repo_defaults_path())? {
+            Some(bytes) => Ok(serde_json::from_slice(&bytes)?),
+            None => Ok(RepoDefaults::default()),
+        }
+    }
+
+    /// Writes the defaults that are applied to objects created in the repository, replacing any
+    /// existing defaults
+    fn set_repo_defaults(&self, defaults: &RepoDefaults) -> Result<()> {
+        self.ensure_open()?;
+
+        info!("Writing repository defaults: {:?}", defaults);
+        write_index(&*self.client, &repo_defaults_path(), defaults)
+    }
+
+    /// Returns the repository's validation history. If no validations have been recorded, then
+    /// an empty history is returned.
+    fn get_validation_history(&self) -> Result<ValidationHistory> {
+        self.ensure_open()?;
+
+        match self.client.get_object(&validation_history_path())? {
+            Some(bytes) => Ok(serde_json::from_slice(&bytes)?),
+            None => Ok(ValidationHistory::default()),
+        }
+    }
+
+    /// Writes the repository's validation history, replacing the existing history
+    fn set_validation_history(&self, history: &ValidationHistory) -> Result<()> {
+        self.ensure_open()?;
+
+        info!("Writing validation history");
+        write_index(&*self.client, &validation_history_path(), history)
+    }
+
+    /// Returns the object's freeze marker, or `None` if the object is not frozen
+    fn get_freeze_marker(&self, object_id: &str) -> Result<Option<FreezeMarker>> {
+        self.ensure_open()?;
+
+        let object_root = self.require_object_root_path(object_id)?;
+
+        match self.client.get_object(&frozen_marker_path(&object_root))? {
+            Some(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Reads the object's root inventory sidecar, and requests the ETag of its root inventory
+    fn get_object_fingerprint(&self, object_id: &str) -> Result<ObjectFingerprint> {
+        self.ensure_open()?;
+
+        let object_root = self.require_object_root_path(object_id)?;
+
+        for algorithm in SIDECAR_ALGORITHMS {
+            let sidecar = join(&object_root, &paths::sidecar_name(algorithm));
+            if let Some(bytes) = self.client.get_object(&sidecar)? {
+                let mut fingerprint = parse_inventory_sidecar(object_id, algorithm, &bytes)?;
+                fingerprint.e_tag = self
+                    .client
+                    .object_e_tag(&join(&object_root, INVENTORY_FILE))?;
+                return Ok(fingerprint);
+            }
+        }
+
+        Err(missing_sidecar_error(object_id))
+    }
+
+    /// Writes the object's freeze marker, or removes it if `marker` is `None`
+    fn set_freeze_marker(&self, object_id: &str, marker: Option<&FreezeMarker>) -> Result<()> {
+        self.ensure_open()?;
+
+        let object_root = self.require_object_root_path(object_id)?;
+        let marker_path = frozen_marker_path(&object_root);
+
+        match marker {
+            Some(marker) => {
+                info!("Writing freeze marker for object {}", object_id);
+                write_index(&*self.client, &marker_path, marker)
+            }
+            None => {
+                info!("Removing freeze marker for object {}", object_id);
+                self.client.delete_object(&marker_path)
+            }
+        }
+    }
+
+    /// Instructs the store to gracefully stop any in-flight work and not accept any additional
+    /// requests.
+    fn close(&self) {
+        self.closed.store(true, Ordering::Release);
+        self.validator.close();
+    }
+}
+
+impl BucketStaging for S3OcflStore {
+    fn put_staged_file(
+        &self,
+        inventory: &Inventory,
+        content_path: &ContentPath,
+        file: &Path,
+    ) -> Result<bool> {
+        self.ensure_open()?;
+
+        if std::fs::metadata(file)?.len() > MAX_COPY_SIZE {
+            return Ok(false);
+        }
+
+        let staged_path = self.bucket_staging_path(&inventory.object_root, content_path.as_str());
+        let expected = inventory.digest_for_content_path(content_path);
+
+        let actual = self.client.put_object_file(
+            &staged_path,
+            file,
+            None,
+            expected.map(|_| inventory.digest_algorithm),
+            &S3WriteOptions::default(),
+        )?;
+
+        if let (Some(expected), Some(actual)) = (expected, actual) {
+            if expected.as_ref() != &actual {
+                if let Err(e) = self.client.delete_object(&staged_path) {
+                    error!("Failed to delete staged file {}: {}", staged_path, e);
+                }
+                return Err(RocflError::CorruptObject {
+                    object_id: inventory.id.clone(),
+                    message: format!(
+                        "Staged file {} does not match its manifest digest. Expected: {}; Found: {}",
+                        content_path, expected, actual
+                    ),
+                });
+            }
+        }
+
+        Ok(true)
+    }
+
+    fn get_staged_file(
+        &self,
+        inventory: &Inventory,
+        content_path: &ContentPath,
+        sink: &mut dyn Write,
+    ) -> Result<()> {
+        self.ensure_open()?;
+        self.client.stream_object(
+            &self.bucket_staging_path(&inventory.object_root, content_path.as_str()),
+            sink,
+        )
+    }
+
+    fn staged_file_size(&self, inventory: &Inventory, content_path: &ContentPath) -> Result<u64> {
+        self.ensure_open()?;
+        self.client
+            .object_size(&self.bucket_staging_path(&inventory.object_root, content_path.as_str()))
+    }
+
+    fn list_staged_files(&self, inventory: &Inventory) -> Result<Vec<ContentPath>> {
+        self.ensure_open()?;
+
+        let staged_root = self.bucket_staged_root(&inventory.object_root);
+        let mut files = Vec::new();
+
+        for key in self.client.list_objects(&staged_root)? {
+            if let Some(relative) = key.strip_prefix(&format!("{}/", staged_root)) {
+                files.push(ContentPath::try_from(relative)?);
+            }
+        }
+
+        Ok(files)
+    }
+
+    fn copy_staged_file(
+        &self,
+        inventory: &Inventory,
+        src_content: &ContentPath,
+        dst_content: &ContentPath,
+    ) -> Result<()> {
+        self.ensure_open()?;
+        self.client.copy_object(
+            &self.bucket_staging_path(&inventory.object_root, src_content.as_str()),
+            &self.bucket_staging_path(&inventory.object_root, dst_content.as_str()),
+            &S3WriteOptions::default(),
+        )
+    }
+
+    fn rm_staged_files(&self, inventory: &Inventory, paths: &[&ContentPath]) -> Result<()> {
+        for path in paths {
+            self.client
+                .delete_object(&self.bucket_staging_path(&inventory.object_root, path.as_str()))?;
+        }
+        Ok(())
     }
 
-    /// Upgrades the repository to the specified version
-    fn upgrade_repo(&self, version: SpecVersion) -> Result<()> {
+    fn list_staged_objects(&self) -> Result<Vec<BucketStagedObject>> {
         self.ensure_open()?;
 
-        let old_namastes = self.find_files("", ROOT_NAMASTE_FILE_PREFIX)?;
+        let mut objects: BTreeMap<String, BucketStagedObject> = BTreeMap::new();
+        let staging_dir = self.bucket_staged_root("");
+        let staging_prefix = format!("{}/", staging_dir);
 
-        write_namaste_and_spec(&self.s3_client, version)?;
+        for (key, size, last_modified) in self.client.list_object_details(&staging_dir)? {
+            let relative = match key.strip_prefix(&staging_prefix) {
+                Some(relative) => relative,
+                None => continue,
+            };
 
-        for old in old_namastes {
-            self.s3_client.delete_object(&old)?;
+            // The object root is everything before the staged version directory
+            let parts = relative.split('/').collect::<Vec<&str>>();
+            let object_root = match parts
+                .iter()
+                .position(|part| VersionNum::try_from(*part).is_ok())
+            {
+                Some(index) if index > 0 => parts[..index].join("/"),
+                _ => continue,
+            };
+
+            let last_modified = last_modified
+                .and_then(|modified| DateTime::parse_from_rfc3339(&modified).ok())
+                .map(|modified| modified.with_timezone(&Local));
+
+            let object = objects
+                .entry(object_root.clone())
+                .or_insert_with(|| BucketStagedObject {
+                    object_root,
+                    files: 0,
+                    bytes: 0,
+                    last_modified: None,
+                });
+
+            object.files += 1;
+            object.bytes += size;
+            if last_modified > object.last_modified {
+                object.last_modified = last_modified;
+            }
         }
 
-        Ok(())
+        Ok(objects.into_values().collect())
     }
 
-    /// Instructs the store to gracefully stop any in-flight work and not accept any additional
-    /// requests.
-    fn close(&self) {
-        self.closed.store(true, Ordering::Release);
-        self.validator.close();
+    fn purge_staged_object(&self, object_root: &str) -> Result<()> {
+        self.ensure_open()?;
+
+        let staged_root = self.bucket_staged_root(object_root);
+
+        for key in self.client.list_objects(&staged_root)? {
+            self.client.delete_object(&key)?;
+        }
+
+        Ok(())
     }
 }
 
 struct S3Client {
-    s3_client: RusotoS3Client,
+    client: RusotoS3Client,
     bucket: String,
     prefix: String,
     // TODO this should ideally be externalized, but wait for new aws rust client
     runtime: Runtime,
+    /// The metrics of the requests sent by the client, if they are being recorded
+    metrics: Option<Arc<Mutex<S3RequestMetrics>>>,
+}
+
+/// Dispatches requests with the configured request timeout, and records metrics about each
+/// request when metrics are enabled
+struct InstrumentedDispatcher {
+    http_client: HttpClient,
+    request_timeout: Option<Duration>,
+    metrics: Option<Arc<Mutex<S3RequestMetrics>>>,
 }
 
-struct ListResult {
-    objects: Vec<String>,
-    directories: Vec<String>,
+#[derive(Default)]
+pub(super) struct ListResult {
+    pub(super) objects: Vec<String>,
+    pub(super) directories: Vec<String>,
+    /// The size and last modified timestamp of each of the `objects`, in the same order
+    pub(super) object_details: Vec<(u64, Option<String>)>,
+}
+
+/// Lazily lists the object keys and logical directories under a prefix, one page at a time. The
+/// next page is only requested once the previous page has been consumed.
+struct ListPages<'a> {
+    client: &'a dyn BlobClient,
+    path: String,
+    delimiter: Option<String>,
+    continuation: Option<String>,
+    done: bool,
 }
 
 type IdMatcher = Box<dyn Fn(&str) -> bool>;
 
 struct InventoryIter<'a> {
     store: &'a S3OcflStore,
-    dir_iters: Vec<IntoIter<String>>,
-    current: RefCell<Option<IntoIter<String>>>,
+    /// The shape of the storage hierarchy dictated by the storage layout, if it has one. It's
+    /// used to avoid listing directories that cannot contain objects, and object roots.
+    shape: Option<HierarchyShape>,
+    dirs: Vec<DirIter<'a>>,
+    /// Indexed object roots that are outside of the layout's hierarchy, and so are not found by
+    /// traversing it
+    indexed_roots: IntoIter<String>,
     id_matcher: Option<IdMatcher>,
     closed: Arc<AtomicBool>,
 }
 
+/// Lazily iterates over the logical directories within a directory
+struct DirIter<'a> {
+    path: String,
+    /// The depth of the directories within the directory, where the directories in the storage
+    /// root are at depth 0
+    depth: usize,
+    directories: IntoIter<String>,
+    pages: ListPages<'a>,
+}
+
 pub struct S3Storage {
-    s3_client: Arc<S3Client>,
+    client: Arc<dyn BlobClient>,
+}
+
+/// The operations on the bucket, or container, of a cloud storage service that `S3OcflStore` is
+/// built on. All paths are relative the repository prefix. S3 is accessed using `S3Client`, and
+/// the other services using `ObjectStoreClient`.
+pub(super) trait BlobClient: Send + Sync {
+    /// The name of the bucket, or container, that the repository is in
+    fn bucket(&self) -> &str;
+
+    /// The prefix within the bucket that the repository is rooted at
+    fn prefix(&self) -> &str;
+
+    /// Returns the metrics of the requests that have been sent so far, if they are being
+    /// recorded
+    fn request_metrics(&self) -> Option<S3RequestMetrics>;
+
+    /// Requests a single page of the object keys, or logical directories, that are under the
+    /// specified prefix. The continuation token of the next page is returned, if there is one.
+    fn list_page(
+        &self,
+        path: &str,
+        delimiter: Option<&str>,
+        continuation: Option<String>,
+    ) -> Result<(ListResult, Option<String>)>;
+
+    /// Returns the object's content, or `None` if it does not exist
+    fn get_object(&self, path: &str) -> Result<Option<Vec<u8>>>;
+
+    /// Writes the object's content to the sink
+    fn stream_object(&self, path: &str, sink: &mut dyn Write) -> Result<()>;
+
+    fn object_size(&self, path: &str) -> Result<u64>;
+
+    /// Returns the object's ETag, without surrounding quotes, if the service returned one
+    fn object_e_tag(&self, path: &str) -> Result<Option<String>>;
+
+    fn delete_object(&self, path: &str) -> Result<()>;
+
+    /// Copies an object to a new key in the same bucket without downloading it
+    fn copy_object(
+        &self,
+        src_path: &str,
+        dst_path: &str,
+        write_options: &S3WriteOptions,
+    ) -> Result<()>;
+
+    fn put_object_bytes(
+        &self,
+        path: &str,
+        content: Bytes,
+        content_type: Option<&str>,
+    ) -> Result<()>;
+
+    /// Uploads the file. If a `digest_algorithm` is specified, then the digest of the uploaded
+    /// bytes is computed using it and returned.
+    fn put_object_file(
+        &self,
+        path: &str,
+        file_path: &Path,
+        content_type: Option<&str>,
+        digest_algorithm: Option<DigestAlgorithm>,
+        write_options: &S3WriteOptions,
+    ) -> Result<Option<HexDigest>>;
+}
+
+impl dyn BlobClient + '_ {
+    /// Returns all of the object keys or logical directories that are under the specified prefix.
+    /// All returned keys and key parts are relative the repository prefix; not the search prefix.
+    pub(super) fn list_dir(&self, path: &str) -> Result<ListResult> {
+        self.list_prefix(path, Some("/".to_string()))
+    }
+
+    /// Returns all of the object keys under the specified prefix. All returned keys and key parts
+    /// are relative the repository prefix; not the search prefix.
+    pub(super) fn list_objects(&self, path: &str) -> Result<Vec<String>> {
+        Ok(self.list_prefix(path, None)?.objects)
+    }
+
+    /// Returns all of the object keys or logical directories that are under the specified prefix.
+    /// All returned keys and key parts are relative the repository prefix; not the search prefix.
+    pub(super) fn list_prefix(&self, path: &str, delimiter: Option<String>) -> Result<ListResult> {
+        let mut result = ListResult::default();
+
+        for page in self.list_pages(path, delimiter) {
+            let mut page = page?;
+            result.objects.append(&mut page.objects);
+            result.directories.append(&mut page.directories);
+            result.object_details.append(&mut page.object_details);
+        }
+
+        Ok(result)
+    }
+
+    /// Returns the key, size, and last modified timestamp of every object under the specified
+    /// prefix. All returned keys are relative the repository prefix; not the search prefix.
+    pub(super) fn list_object_details(
+        &self,
+        path: &str,
+    ) -> Result<Vec<(String, u64, Option<String>)>> {
+        let result = self.list_prefix(path, None)?;
+        Ok(result
+            .objects
+            .into_iter()
+            .zip(result.object_details)
+            .map(|(key, (size, last_modified))| (key, size, last_modified))
+            .collect())
+    }
+
+    /// Returns an iterator over the pages of object keys and logical directories that are under
+    /// the specified prefix. Each page is requested when the iterator is advanced.
+    fn list_pages(&self, path: &str, delimiter: Option<String>) -> ListPages<'_> {
+        ListPages {
+            client: self,
+            path: path.to_string(),
+            delimiter,
+            continuation: None,
+            done: false,
+        }
+    }
 }
 
 impl S3Client {
@@ -734,80 +2320,251 @@ impl S3Client {
         bucket: &str,
         prefix: Option<&str>,
         profile: Option<&str>,
+        client_options: &S3ClientOptions,
     ) -> Result<Self> {
+        let metrics = if client_options.request_metrics {
+            Some(Arc::new(Mutex::new(S3RequestMetrics::default())))
+        } else {
+            None
+        };
+
         Ok(S3Client {
-            s3_client: create_rusoto_client(region, profile),
+            client: create_rusoto_client(region, profile, client_options, metrics.clone()),
             bucket: bucket.to_owned(),
             prefix: prefix.unwrap_or_default().to_owned(),
             runtime: runtime::Builder::new_multi_thread().enable_all().build()?,
+            metrics,
         })
     }
 
-    /// Returns all of the object keys or logical directories that are under the specified prefix.
-    /// All returned keys and key parts are relative the repository prefix; not the search prefix.
-    fn list_dir(&self, path: &str) -> Result<ListResult> {
-        self.list_prefix(path, Some("/".to_string()))
-    }
-
-    /// Returns all of the object keys under the specified prefix. All returned keys and key parts
-    /// are relative the repository prefix; not the search prefix.
-    fn list_objects(&self, path: &str) -> Result<Vec<String>> {
-        Ok(self.list_prefix(path, None)?.objects)
+    /// Wraps an error returned by S3 so that it identifies the failed operation and key
+    fn s3_error<E: std::error::Error + Sync + Send + 'static>(
+        &self,
+        operation: &str,
+        key: &str,
+        error: RusotoError<E>,
+    ) -> RocflError {
+        RocflError::S3 {
+            operation: operation.to_string(),
+            bucket: self.bucket.clone(),
+            key: key.to_string(),
+            source: Box::new(error),
+        }
     }
 
-    /// Returns all of the object keys or logical directories that are under the specified prefix.
-    /// All returned keys and key parts are relative the repository prefix; not the search prefix.
-    fn list_prefix(&self, path: &str, delimiter: Option<String>) -> Result<ListResult> {
-        let prefix = join_with_trailing_slash(&self.prefix, path);
+    fn multipart_put_file(
+        &self,
+        path: &str,
+        file_path: impl AsRef<Path>,
+        content_length: u64,
+        content_type: Option<&str>,
+        digest_algorithm: Option<DigestAlgorithm>,
+        write_options: &S3WriteOptions,
+    ) -> Result<Option<HexDigest>> {
+        let key = join(&self.prefix, path);
 
-        info!("Listing S3 prefix: {}", prefix);
+        info!(
+            "Initiating S3 multipart upload of {} to {}",
+            file_path.as_ref().to_string_lossy(),
+            key
+        );
 
-        let mut objects = Vec::new();
-        let mut directories = Vec::new();
-        let mut continuation = None;
+        let mut i = 1;
+        let mut reader = File::open(file_path)?;
+        let mut buffer = [b'a'; PART_SIZE as usize];
+        let mut parts = Vec::with_capacity(((content_length / PART_SIZE) + 1) as usize);
+        let mut part_md5s = Vec::with_capacity(parts.capacity());
+        let mut digester = digest_algorithm.map(|algorithm| algorithm.writer(io::sink()));
 
-        loop {
-            let result: ListObjectsV2Output =
-                self.runtime
-                    .block_on(self.s3_client.list_objects_v2(ListObjectsV2Request {
+        let upload_id = self
+            .runtime
+            .block_on(
+                self.client
+                    .create_multipart_upload(CreateMultipartUploadRequest {
                         bucket: self.bucket.clone(),
-                        prefix: Some(prefix.clone()),
-                        delimiter: delimiter.clone(),
-                        continuation_token: continuation.clone(),
+                        content_type: content_type.map(|s| s.to_string()),
+                        key: key.clone(),
+                        storage_class: write_options.storage_class.clone(),
+                        tagging: encode_tags(&write_options.tags),
                         ..Default::default()
-                    }))?;
+                    }),
+            )
+            .map_err(|e| self.s3_error("CreateMultipartUpload", &key, e))?
+            .upload_id
+            .unwrap();
 
-            let prefix_offset = if self.prefix.is_empty() {
-                0
-            } else {
-                self.prefix.len() + 1
+        let create_upload_part =
+            |content: Vec<u8>, md5: &[u8], part_number: i64| -> UploadPartRequest {
+                UploadPartRequest {
+                    upload_id: upload_id.clone(),
+                    part_number,
+                    bucket: self.bucket.clone(),
+                    key: key.clone(),
+                    content_md5: Some(base64::encode(md5)),
+                    body: Some(content.into()),
+                    ..Default::default()
+                }
             };
 
-            if let Some(contents) = &result.contents {
-                for object in contents {
-                    objects.push(object.key.as_ref().unwrap()[prefix_offset..].to_owned());
+        loop {
+            let read = match reader.read(&mut buffer) {
+                Ok(read) => read,
+                Err(e) => {
+                    self.abort_multipart(&key, &upload_id);
+                    return Err(e.into());
                 }
+            };
+
+            if read == 0 {
+                break;
+            }
+
+            debug!("Upload part {} for {}", i, read);
+
+            let content = &buffer[..read];
+            let md5 = Md5::digest(content);
+
+            if let Some(digester) = digester.as_mut() {
+                digester.write_all(content)?;
             }
 
-            if let Some(prefixes) = &result.common_prefixes {
-                for prefix in prefixes {
-                    let length = prefix.prefix.as_ref().unwrap().len() - 1;
-                    directories
-                        .push(prefix.prefix.as_ref().unwrap()[prefix_offset..length].to_owned());
+            let e_tag = match self
+                .runtime
+                .block_on(
+                    self.client
+                        .upload_part(create_upload_part(content.to_vec(), &md5, i)),
+                ) {
+                Ok(result) => result.e_tag,
+                Err(e) => {
+                    self.abort_multipart(&key, &upload_id);
+                    return Err(self.s3_error("UploadPart", &key, e));
                 }
+            };
+
+            parts.push(CompletedPart {
+                e_tag,
+                part_number: Some(i),
+            });
+            part_md5s.push(md5.to_vec());
+
+            i += 1;
+        }
+
+        debug!("Finish multipart upload for {}", key);
+
+        let result = self
+            .runtime
+            .block_on(
+                self.client
+                    .complete_multipart_upload(CompleteMultipartUploadRequest {
+                        bucket: self.bucket.clone(),
+                        key: key.clone(),
+                        multipart_upload: Some(CompletedMultipartUpload { parts: Some(parts) }),
+                        upload_id: upload_id.clone(),
+                        ..Default::default()
+                    }),
+            )
+            .map_err(|e| self.s3_error("CompleteMultipartUpload", &key, e))?;
+
+        verify_e_tag(
+            &key,
+            result.e_tag.as_deref(),
+            result.server_side_encryption.as_deref(),
+            &multipart_e_tag(&part_md5s),
+        )?;
+
+        Ok(digester.map(|digester| digester.finalize_hex()))
+    }
+
+    fn abort_multipart(&self, key: &str, upload_id: &str) {
+        info!("Aborting multipart upload to {}", key);
+        if let Err(e) = self.runtime.block_on(self.client.abort_multipart_upload(
+            AbortMultipartUploadRequest {
+                bucket: self.bucket.clone(),
+                key: key.to_string(),
+                upload_id: upload_id.to_string(),
+                ..Default::default()
+            },
+        )) {
+            error!("Failed to abort multipart upload to {}: {}", key, e);
+        };
+    }
+}
+
+impl BlobClient for S3Client {
+    fn bucket(&self) -> &str {
+        &self.bucket
+    }
+
+    fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    /// Returns the metrics of the requests that have been sent so far, if they are being
+    /// recorded
+    fn request_metrics(&self) -> Option<S3RequestMetrics> {
+        self.metrics
+            .as_ref()
+            .map(|metrics| metrics.lock().unwrap().clone())
+    }
+
+    fn list_page(
+        &self,
+        path: &str,
+        delimiter: Option<&str>,
+        continuation: Option<String>,
+    ) -> Result<(ListResult, Option<String>)> {
+        let prefix = join_with_trailing_slash(&self.prefix, path);
+
+        if continuation.is_none() {
+            info!("Listing S3 prefix: {}", prefix);
+        }
+
+        let result: ListObjectsV2Output = self
+            .runtime
+            .block_on(self.client.list_objects_v2(ListObjectsV2Request {
+                bucket: self.bucket.clone(),
+                prefix: Some(prefix.clone()),
+                delimiter: delimiter.map(|d| d.to_string()),
+                continuation_token: continuation,
+                ..Default::default()
+            }))
+            .map_err(|e| self.s3_error("ListObjectsV2", &prefix, e))?;
+
+        let prefix_offset = if self.prefix.is_empty() {
+            0
+        } else {
+            self.prefix.len() + 1
+        };
+
+        let mut page = ListResult::default();
+
+        if let Some(contents) = &result.contents {
+            for object in contents {
+                page.objects
+                    .push(object.key.as_ref().unwrap()[prefix_offset..].to_owned());
+                page.object_details.push((
+                    object.size.unwrap_or(0) as u64,
+                    object.last_modified.clone(),
+                ));
             }
+        }
 
-            if result.is_truncated.unwrap() {
-                continuation = result.next_continuation_token.clone();
-            } else {
-                break;
+        if let Some(prefixes) = &result.common_prefixes {
+            for prefix in prefixes {
+                let length = prefix.prefix.as_ref().unwrap().len() - 1;
+                page.directories
+                    .push(prefix.prefix.as_ref().unwrap()[prefix_offset..length].to_owned());
             }
         }
 
-        Ok(ListResult {
-            objects,
-            directories,
-        })
+        let continuation = if result.is_truncated.unwrap_or(false) {
+            result.next_continuation_token
+        } else {
+            None
+        };
+
+        Ok((page, continuation))
     }
 
     fn get_object(&self, path: &str) -> Result<Option<Vec<u8>>> {
@@ -817,9 +2574,9 @@ impl S3Client {
 
         let result = self
             .runtime
-            .block_on(self.s3_client.get_object(GetObjectRequest {
+            .block_on(self.client.get_object(GetObjectRequest {
                 bucket: self.bucket.clone(),
-                key,
+                key: key.clone(),
                 ..Default::default()
             }));
 
@@ -835,7 +2592,7 @@ impl S3Client {
                 Ok(Some(buffer))
             }),
             Err(RusotoError::Service(GetObjectError::NoSuchKey(_e))) => Ok(None),
-            Err(e) => Err(e.into()),
+            Err(e) => Err(self.s3_error("GetObject", &key, e)),
         }
     }
 
@@ -846,9 +2603,9 @@ impl S3Client {
 
         let result = self
             .runtime
-            .block_on(self.s3_client.get_object(GetObjectRequest {
+            .block_on(self.client.get_object(GetObjectRequest {
                 bucket: self.bucket.clone(),
-                key,
+                key: key.clone(),
                 ..Default::default()
             }));
 
@@ -865,21 +2622,94 @@ impl S3Client {
                 }
                 Ok(())
             }),
-            Err(e) => Err(e.into()),
+            Err(RusotoError::Service(GetObjectError::InvalidObjectState(_e))) => {
+                Err(RocflError::ArchivedContent(path.to_string()))
+            }
+            Err(e) => Err(self.s3_error("GetObject", &key, e)),
         }
     }
 
+    fn object_size(&self, path: &str) -> Result<u64> {
+        let key = join(&self.prefix, path);
+
+        info!("Getting object size from S3: {}", key);
+
+        let result = self
+            .runtime
+            .block_on(self.client.head_object(HeadObjectRequest {
+                bucket: self.bucket.clone(),
+                key: key.clone(),
+                ..Default::default()
+            }))
+            .map_err(|e| self.s3_error("HeadObject", &key, e))?;
+
+        Ok(result.content_length.unwrap_or(0) as u64)
+    }
+
+    fn object_e_tag(&self, path: &str) -> Result<Option<String>> {
+        let key = join(&self.prefix, path);
+
+        info!("Getting object ETag from S3: {}", key);
+
+        let result = self
+            .runtime
+            .block_on(self.client.head_object(HeadObjectRequest {
+                bucket: self.bucket.clone(),
+                key: key.clone(),
+                ..Default::default()
+            }))
+            .map_err(|e| self.s3_error("HeadObject", &key, e))?;
+
+        Ok(result
+            .e_tag
+            .map(|e_tag| e_tag.trim_matches('"').to_string()))
+    }
+
     fn delete_object(&self, path: &str) -> Result<()> {
         let key = join(&self.prefix, path);
 
         info!("Deleting object in S3: {}", key);
 
         self.runtime
-            .block_on(self.s3_client.delete_object(DeleteObjectRequest {
+            .block_on(self.client.delete_object(DeleteObjectRequest {
+                bucket: self.bucket.clone(),
+                key: key.clone(),
+                ..Default::default()
+            }))
+            .map_err(|e| self.s3_error("DeleteObject", &key, e))?;
+
+        Ok(())
+    }
+
+    /// Copies an object to a new key in the same bucket without downloading it. The copy is
+    /// written with the storage class and tags in `write_options`. The object must be no larger
+    /// than 5 GiB.
+    fn copy_object(
+        &self,
+        src_path: &str,
+        dst_path: &str,
+        write_options: &S3WriteOptions,
+    ) -> Result<()> {
+        let src_key = join(&self.prefix, src_path);
+        let dst_key = join(&self.prefix, dst_path);
+
+        info!("Copying object in S3 from {} to {}", src_key, dst_key);
+
+        self.runtime
+            .block_on(self.client.copy_object(CopyObjectRequest {
                 bucket: self.bucket.clone(),
-                key,
+                key: dst_key.clone(),
+                copy_source: format!(
+                    "{}/{}",
+                    self.bucket,
+                    utf8_percent_encode(&src_key, COPY_SOURCE_ENCODE_SET)
+                ),
+                storage_class: write_options.storage_class.clone(),
+                tagging: encode_tags(&write_options.tags),
+                tagging_directive: Some("REPLACE".to_string()),
                 ..Default::default()
-            }))?;
+            }))
+            .map_err(|e| self.s3_error("CopyObject", &dst_key, e))?;
 
         Ok(())
     }
@@ -895,8 +2725,8 @@ impl S3Client {
         info!("Putting object in S3: {}", key);
 
         self.runtime
-            .block_on(self.s3_client.put_object(PutObjectRequest {
-                key,
+            .block_on(self.client.put_object(PutObjectRequest {
+                key: key.clone(),
                 bucket: self.bucket.clone(),
                 content_length: Some(content.len() as i64),
                 body: Some(ByteStream::new(futures::stream::once(async move {
@@ -904,158 +2734,204 @@ impl S3Client {
                 }))),
                 content_type: content_type.map(|s| s.to_string()),
                 ..Default::default()
-            }))?;
+            }))
+            .map_err(|e| self.s3_error("PutObject", &key, e))?;
 
         Ok(())
     }
 
+    /// Uploads the file to S3. The MD5 of every request body is sent to S3 so that corrupt
+    /// requests are rejected, and the ETag of the resulting object is compared to the expected
+    /// ETag. If a `digest_algorithm` is specified, then the digest of the uploaded bytes is
+    /// computed using it and returned. The object is written with the storage class and tags in
+    /// `write_options`.
     fn put_object_file(
         &self,
         path: &str,
-        file_path: impl AsRef<Path>,
+        file_path: &Path,
         content_type: Option<&str>,
-    ) -> Result<()> {
-        let content_length = std::fs::metadata(&file_path)?.len();
+        digest_algorithm: Option<DigestAlgorithm>,
+        write_options: &S3WriteOptions,
+    ) -> Result<Option<HexDigest>> {
+        let content_length = std::fs::metadata(file_path)?.len();
 
         if content_length > PART_SIZE {
-            self.multipart_put_file(path, file_path, content_length, content_type)?;
-        } else {
-            let key = join(&self.prefix, path);
-            info!("Putting {} in S3 at {}", file_path.as_ref().display(), key);
+            return self.multipart_put_file(
+                path,
+                file_path,
+                content_length,
+                content_type,
+                digest_algorithm,
+                write_options,
+            );
+        }
 
-            #[allow(clippy::unnecessary_to_owned)]
-            let stream = tokio::fs::read(file_path.as_ref().to_path_buf())
-                .into_stream()
-                .map_ok(Bytes::from);
+        let key = join(&self.prefix, path);
+        info!("Putting {} in S3 at {}", file_path.display(), key);
 
-            self.runtime
-                .block_on(self.s3_client.put_object(PutObjectRequest {
-                    key,
-                    bucket: self.bucket.clone(),
-                    content_length: Some(content_length as i64),
-                    body: Some(StreamingBody::new(stream)),
-                    content_type: content_type.map(|s| s.to_string()),
-                    ..Default::default()
-                }))?;
-        }
+        let content = std::fs::read(file_path)?;
+        let md5 = Md5::digest(&content);
+        let digest = match digest_algorithm {
+            Some(algorithm) => Some(algorithm.hash_hex(&mut content.as_slice())?),
+            None => None,
+        };
+        let content = Bytes::from(content);
 
-        Ok(())
+        let result = self
+            .runtime
+            .block_on(self.client.put_object(PutObjectRequest {
+                key: key.clone(),
+                bucket: self.bucket.clone(),
+                content_length: Some(content_length as i64),
+                content_md5: Some(base64::encode(md5)),
+                body: Some(ByteStream::new(futures::stream::once(async move {
+                    Ok(content)
+                }))),
+                content_type: content_type.map(|s| s.to_string()),
+                storage_class: write_options.storage_class.clone(),
+                tagging: encode_tags(&write_options.tags),
+                ..Default::default()
+            }))
+            .map_err(|e| self.s3_error("PutObject", &key, e))?;
+
+        verify_e_tag(
+            &key,
+            result.e_tag.as_deref(),
+            result.server_side_encryption.as_deref(),
+            &hex::encode(md5),
+        )?;
+
+        Ok(digest)
     }
+}
 
-    fn multipart_put_file(
+impl DispatchSignedRequest for InstrumentedDispatcher {
+    fn dispatch(
         &self,
-        path: &str,
-        file_path: impl AsRef<Path>,
-        content_length: u64,
-        content_type: Option<&str>,
-    ) -> Result<()> {
-        let key = join(&self.prefix, path);
+        request: SignedRequest,
+        timeout: Option<Duration>,
+    ) -> DispatchSignedRequestFuture {
+        let operation = operation_name(&request);
+        let bytes_sent = request_body_size(&request);
+
+        let response = self
+            .http_client
+            .dispatch(request, timeout.or(self.request_timeout));
+
+        let metrics = match &self.metrics {
+            Some(metrics) => metrics.clone(),
+            None => return response,
+        };
 
-        info!(
-            "Initiating S3 multipart upload of {} to {}",
-            file_path.as_ref().to_string_lossy(),
-            key
-        );
+        Box::pin(async move {
+            let start = Instant::now();
+            let result = response.await;
+            let latency = start.elapsed();
 
-        let mut i = 1;
-        let mut reader = File::open(file_path)?;
-        let mut buffer = [b'a'; PART_SIZE as usize];
-        let mut parts = Vec::with_capacity(((content_length / PART_SIZE) + 1) as usize);
+            let mut metrics = metrics.lock().unwrap();
+            let operation = metrics.operations.entry(operation.to_string()).or_default();
 
-        let upload_id = self
-            .runtime
-            .block_on(
-                self.s3_client
-                    .create_multipart_upload(CreateMultipartUploadRequest {
-                        bucket: self.bucket.clone(),
-                        content_type: content_type.map(|s| s.to_string()),
-                        key: key.clone(),
-                        ..Default::default()
-                    }),
-            )?
-            .upload_id
-            .unwrap();
+            operation.requests += 1;
+            operation.latency += latency;
+            operation.bytes_sent += bytes_sent;
 
-        let create_upload_part = |content: Vec<u8>, part_number: i64| -> UploadPartRequest {
-            UploadPartRequest {
-                upload_id: upload_id.clone(),
-                part_number,
-                bucket: self.bucket.clone(),
-                key: key.clone(),
-                body: Some(content.into()),
-                ..Default::default()
+            match &result {
+                Ok(response) => {
+                    if !response.status.is_success() {
+                        operation.failures += 1;
+                    }
+                    operation.bytes_received += response
+                        .headers
+                        .get("content-length")
+                        .and_then(|length| length.parse::<u64>().ok())
+                        .unwrap_or(0);
+                }
+                Err(_) => operation.failures += 1,
             }
-        };
 
-        loop {
-            let read = match reader.read(&mut buffer) {
-                Ok(read) => read,
-                Err(e) => {
-                    self.abort_multipart(&key, &upload_id);
-                    return Err(e.into());
-                }
-            };
+            result
+        })
+    }
+}
 
-            if read == 0 {
-                break;
-            }
+impl ListResult {
+    fn is_empty(&self) -> bool {
+        self.objects.is_empty() && self.directories.is_empty()
+    }
+}
 
-            debug!("Upload part {} for {}", i, read);
+impl<'a> ListPages<'a> {
+    fn request_page(&mut self) -> Result<ListResult> {
+        let (page, continuation) = self.client.list_page(
+            &self.path,
+            self.delimiter.as_deref(),
+            self.continuation.take(),
+        )?;
 
-            let e_tag = match self.runtime.block_on(
-                self.s3_client
-                    .upload_part(create_upload_part(buffer[..read].to_vec(), i)),
-            ) {
-                Ok(result) => result.e_tag,
-                Err(e) => {
-                    self.abort_multipart(&key, &upload_id);
-                    return Err(e.into());
-                }
-            };
+        self.continuation = continuation;
+        self.done = self.continuation.is_none();
 
-            parts.push(CompletedPart {
-                e_tag,
-                part_number: Some(i),
-            });
+        Ok(page)
+    }
+}
 
-            i += 1;
+impl<'a> Iterator for ListPages<'a> {
+    type Item = Result<ListResult>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
         }
 
-        debug!("Finish multipart upload for {}", key);
+        let page = self.request_page();
 
-        self.runtime
-            .block_on(
-                self.s3_client
-                    .complete_multipart_upload(CompleteMultipartUploadRequest {
-                        bucket: self.bucket.clone(),
-                        key: key.clone(),
-                        multipart_upload: Some(CompletedMultipartUpload { parts: Some(parts) }),
-                        upload_id: upload_id.clone(),
-                        ..Default::default()
-                    }),
-            )?;
+        if page.is_err() {
+            self.done = true;
+        }
 
-        Ok(())
+        Some(page)
     }
+}
 
-    fn abort_multipart(&self, key: &str, upload_id: &str) {
-        info!("Aborting multipart upload to {}", key);
-        if let Err(e) = self.runtime.block_on(self.s3_client.abort_multipart_upload(
-            AbortMultipartUploadRequest {
-                bucket: self.bucket.clone(),
-                key: key.to_string(),
-                upload_id: upload_id.to_string(),
-                ..Default::default()
-            },
-        )) {
-            error!("Failed to abort multipart upload to {}: {}", key, e);
-        };
+impl<'a> DirIter<'a> {
+    /// Creates an iterator over the directories within the directory at the path. Nothing is
+    /// listed until the iterator is advanced.
+    fn new(store: &'a S3OcflStore, path: String, depth: usize) -> Self {
+        let pages = store.client.list_pages(&path, Some("/".to_string()));
+        Self::with_first_page(path, depth, ListResult::default(), pages)
+    }
+
+    /// Creates an iterator over the directories within a directory, starting with the
+    /// directories in the page that has already been listed
+    fn with_first_page(
+        path: String,
+        depth: usize,
+        first_page: ListResult,
+        pages: ListPages<'a>,
+    ) -> Self {
+        Self {
+            path,
+            depth,
+            directories: first_page.directories.into_iter(),
+            pages,
+        }
     }
 }
 
-impl ListResult {
-    fn is_empty(&self) -> bool {
-        self.objects.is_empty() && self.directories.is_empty()
+impl<'a> Iterator for DirIter<'a> {
+    type Item = Result<String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(directory) = self.directories.next() {
+                return Some(Ok(directory));
+            }
+
+            match self.pages.next()? {
+                Ok(page) => self.directories = page.directories.into_iter(),
+                Err(e) => return Some(Err(e)),
+            }
+        }
     }
 }
 
@@ -1086,17 +2962,36 @@ impl<'a> InventoryIter<'a> {
 
     /// Creates a new iterator that returns all objects if no `id_matcher` is provided, or only
     /// the objects the `id_matcher` returns `true` for if one is provided.
+    ///
+    /// If the storage layout dictates the depth of object roots, then only directories that the
+    /// layout could have created are descended into, and directories at the depth of object roots
+    /// are read as objects without listing them first.
     fn new(store: &'a S3OcflStore, id_matcher: Option<IdMatcher>, closed: Arc<AtomicBool>) -> Self {
+        let shape = store
+            .storage_layout
+            .as_ref()
+            .and_then(StorageLayout::hierarchy_shape);
+
+        let indexed_roots = match (shape, store.object_index.read()) {
+            (Some(shape), Ok(index)) => index
+                .object_roots()
+                .filter(|object_root| !shape.contains_root(object_root))
+                .cloned()
+                .collect(),
+            _ => Vec::new(),
+        };
+
         Self {
             store,
-            dir_iters: Vec::new(),
-            current: RefCell::new(Some(vec!["".to_string()].into_iter())),
+            shape,
+            dirs: vec![DirIter::new(store, "".to_string(), 0)],
+            indexed_roots: indexed_roots.into_iter(),
             id_matcher,
             closed,
         }
     }
 
-    fn create_if_matches(&self, object_root: &str) -> Option<Result<Inventory>> {
+    fn create_if_matches(&self, object_root: &str) -> Option<Result<Inventory, ObjectError>> {
         match self.store.parse_inventory(object_root) {
             Ok(Some(inventory)) => {
                 if let Some(id_matcher) = &self.id_matcher {
@@ -1109,17 +3004,20 @@ impl<'a> InventoryIter<'a> {
                     Some(Ok(inventory))
                 }
             }
-            Ok(None) => Some(Err(RocflError::NotFound(format!(
-                "Expected object to exist at {}, but none found.",
-                object_root
-            )))),
-            Err(e) => Some(Err(e)),
+            Ok(None) => Some(Err(ObjectError::at_path(
+                object_root,
+                RocflError::NotFound(format!(
+                    "Expected object to exist at {}, but none found.",
+                    object_root
+                )),
+            ))),
+            Err(e) => Some(Err(ObjectError::at_path(object_root, e))),
         }
     }
 }
 
 impl<'a> Iterator for InventoryIter<'a> {
-    type Item = Result<Inventory>;
+    type Item = Result<Inventory, ObjectError>;
 
     fn next(&mut self) -> Option<Self::Item> {
         loop {
@@ -1128,37 +3026,73 @@ impl<'a> Iterator for InventoryIter<'a> {
                 return None;
             }
 
-            if self.current.borrow().is_none() && self.dir_iters.is_empty() {
-                return None;
-            } else if self.current.borrow().is_none() {
-                self.current.replace(self.dir_iters.pop());
-            }
+            let dir = match self.dirs.last_mut() {
+                Some(dir) => dir,
+                None => {
+                    let object_root = self.indexed_roots.next()?;
+                    match self.create_if_matches(&object_root) {
+                        Some(result) => return Some(result),
+                        None => continue,
+                    }
+                }
+            };
 
-            let entry = self.current.borrow_mut().as_mut().unwrap().next();
+            let depth = dir.depth;
 
-            match entry {
+            let entry = match dir.next() {
+                Some(Ok(entry)) => entry,
+                Some(Err(e)) => {
+                    let path = dir.path.clone();
+                    self.dirs.pop();
+                    return Some(Err(ObjectError::at_path(path, e)));
+                }
                 None => {
-                    self.current.replace(None);
+                    self.dirs.pop();
+                    continue;
                 }
-                Some(entry) => {
-                    if entry.ends_with(EXTENSIONS_DIR_SUFFIX) {
-                        continue;
+            };
+
+            if entry.ends_with(EXTENSIONS_DIR_SUFFIX) {
+                continue;
+            }
+
+            match self.shape {
+                Some(shape) if depth < shape.object_depth() => {
+                    let name = entry.rsplit('/').next().unwrap_or_default();
+                    if shape.is_tuple(name) {
+                        self.dirs.push(DirIter::new(self.store, entry, depth + 1));
+                    } else {
+                        debug!(
+                            "Skipping {} because it is not part of the storage layout",
+                            entry
+                        );
+                    }
+                }
+                Some(_) => {
+                    if let Some(result) = self.create_if_matches(&entry) {
+                        return Some(result);
                     }
+                }
+                None => {
+                    let mut pages = self.store.client.list_pages(&entry, Some("/".to_string()));
 
-                    let listing = match self.store.list_dir(&entry) {
-                        Ok(listing) => listing,
-                        Err(e) => return Some(Err(e)),
+                    let first_page = match pages.next() {
+                        Some(Ok(page)) => page,
+                        Some(Err(e)) => return Some(Err(ObjectError::at_path(entry, e))),
+                        None => ListResult::default(),
                     };
 
-                    if is_object_dir(&listing.objects) {
-                        match self.create_if_matches(&entry) {
-                            Some(Ok(inventory)) => return Some(Ok(inventory)),
-                            Some(Err(e)) => return Some(Err(e)),
-                            _ => (),
+                    if is_object_dir(&first_page.objects) {
+                        if let Some(result) = self.create_if_matches(&entry) {
+                            return Some(result);
                         }
                     } else {
-                        self.dir_iters.push(self.current.replace(None).unwrap());
-                        self.current.replace(Some(listing.directories.into_iter()));
+                        self.dirs.push(DirIter::with_first_page(
+                            entry,
+                            depth + 1,
+                            first_page,
+                            pages,
+                        ));
                     }
                 }
             }
@@ -1167,21 +3101,21 @@ impl<'a> Iterator for InventoryIter<'a> {
 }
 
 impl S3Storage {
-    fn new(s3_client: Arc<S3Client>) -> Self {
-        Self { s3_client }
+    fn new(client: Arc<dyn BlobClient>) -> Self {
+        Self { client }
     }
 }
 
 impl Storage for S3Storage {
     /// Reads the file at the specified path and writes its contents to the provided sink.
     fn read<W: Write>(&self, path: &str, sink: &mut W) -> Result<()> {
-        self.s3_client.stream_object(path, sink)
+        self.client.stream_object(path, sink)
     }
 
     /// Lists the contents of the specified directory. If `recursive` is `true`, then all leaf-nodes
     /// are returned. If the directory does not exist, or is empty, then an empty vector is returned.
     /// The returned paths are all relative the directory that was listed.
-    fn list(&self, path: &str, recursive: bool) -> Result<Vec<Listing>> {
+    fn list(&self, path: &str, recursive: bool) -> Result<Vec<Listing<'_>>> {
         let prefix_len = if path.is_empty() || path.ends_with('/') {
             path.len()
         } else {
@@ -1189,13 +3123,13 @@ impl Storage for S3Storage {
         };
 
         if recursive {
-            let key_parts = self.s3_client.list_objects(path)?;
+            let key_parts = self.client.list_objects(path)?;
             Ok(key_parts
                 .iter()
                 .map(|entry| Listing::file_owned(entry[prefix_len..].to_string()))
                 .collect::<Vec<Listing>>())
         } else {
-            let s3_result = self.s3_client.list_dir(path)?;
+            let s3_result = self.client.list_dir(path)?;
             let mut result =
                 Vec::with_capacity(s3_result.directories.len() + s3_result.objects.len());
 
@@ -1220,8 +3154,8 @@ impl Storage for S3Storage {
     }
 }
 
-fn check_extensions(s3_client: &S3Client) {
-    match s3_client.list_dir(EXTENSIONS_DIR) {
+fn check_extensions(client: &dyn BlobClient) {
+    match client.list_dir(EXTENSIONS_DIR) {
         Ok(result) => {
             for entry in result.directories {
                 let ext_name = &entry[EXTENSIONS_DIR.len() + 1..];
@@ -1237,30 +3171,86 @@ fn check_extensions(s3_client: &S3Client) {
     }
 }
 
-fn create_rusoto_client(region: Region, profile: Option<&str>) -> RusotoS3Client {
-    match profile {
+fn create_rusoto_client(
+    region: Region,
+    profile: Option<&str>,
+    client_options: &S3ClientOptions,
+    metrics: Option<Arc<Mutex<S3RequestMetrics>>>,
+) -> RusotoS3Client {
+    let mut http_client = HttpClient::new().expect("failed to create request dispatcher");
+    if let Some(user_agent) = &client_options.user_agent {
+        http_client.local_agent_prepend(user_agent.clone());
+    }
+
+    let dispatcher = InstrumentedDispatcher {
+        http_client,
+        request_timeout: client_options.request_timeout,
+        metrics,
+    };
+
+    // Client setup code copied from Rusoto -- they don't make it easy to set the profile
+    let client = match profile {
         Some(profile) => {
-            // Client setup code copied from Rusoto -- they don't make it easy to set the profile
             let credentials_provider =
                 AutoRefreshingProvider::new(ChainProvider::with_profile_provider(
                     ProfileProvider::with_default_credentials(profile)
                         .expect("failed to create profile provider"),
                 ))
                 .expect("failed to create credentials provider");
-            let dispatcher = HttpClient::new().expect("failed to create request dispatcher");
-            let client = Client::new_with(credentials_provider, dispatcher);
-            RusotoS3Client::new_with_client(client, region)
+            Client::new_with(credentials_provider, dispatcher)
         }
-        None => RusotoS3Client::new(region),
+        None => {
+            let credentials_provider =
+                DefaultCredentialsProvider::new().expect("failed to create credentials provider");
+            Client::new_with(credentials_provider, dispatcher)
+        }
+    };
+
+    RusotoS3Client::new_with_client(client, region)
+}
+
+/// Returns the name of the S3 operation that the request invokes. Only the operations that
+/// rocfl uses are identified.
+fn operation_name(request: &SignedRequest) -> &'static str {
+    let has_param = |param: &str| request.params.contains_key(param);
+
+    match request.method.as_str() {
+        "GET" if has_param("list-type") => "ListObjectsV2",
+        "GET" => "GetObject",
+        "HEAD" => "HeadObject",
+        "PUT" if has_param("partNumber") => "UploadPart",
+        "PUT" => "PutObject",
+        "POST" if has_param("uploads") => "CreateMultipartUpload",
+        "POST" if has_param("uploadId") => "CompleteMultipartUpload",
+        "DELETE" if has_param("uploadId") => "AbortMultipartUpload",
+        "DELETE" => "DeleteObject",
+        _ => "Other",
+    }
+}
+
+/// Returns the size of the request's body, in bytes. The size of streamed bodies is taken from
+/// their `Content-Length` header.
+fn request_body_size(request: &SignedRequest) -> u64 {
+    match &request.payload {
+        Some(SignedRequestPayload::Buffer(bytes)) => bytes.len() as u64,
+        Some(SignedRequestPayload::Stream(_)) => request
+            .headers
+            .get("content-length")
+            .and_then(|values| values.first())
+            .and_then(|value| std::str::from_utf8(value).ok())
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(0),
+        None => 0,
     }
 }
 
 fn init_new_repo(
-    s3_client: &S3Client,
+    client: &dyn BlobClient,
     version: SpecVersion,
     layout: Option<&StorageLayout>,
+    spec_copy: bool,
 ) -> Result<()> {
-    if !s3_client.list_dir("")?.is_empty() {
+    if !client.list_dir("")?.is_empty() {
         return Err(RocflError::IllegalState(
             "Cannot create new repository. Storage root must be empty".to_string(),
         ));
@@ -1268,33 +3258,39 @@ fn init_new_repo(
 
     info!(
         "Initializing OCFL storage root in bucket {} under prefix {}",
-        s3_client.bucket, s3_client.prefix
+        client.bucket(),
+        client.prefix()
     );
 
-    write_namaste_and_spec(s3_client, version)?;
+    write_namaste_and_spec(client, version, spec_copy)?;
 
     if let Some(layout) = layout {
-        write_layout_config(s3_client, layout)?;
+        write_layout_config(client, layout)?;
     }
 
     Ok(())
 }
 
-fn write_namaste_and_spec(s3_client: &S3Client, version: SpecVersion) -> Result<()> {
+fn write_namaste_and_spec(
+    client: &dyn BlobClient,
+    version: SpecVersion,
+    spec_copy: bool,
+) -> Result<()> {
     let root_namaste = version.root_namaste();
 
-    s3_client.put_object_bytes(
+    client.put_object_bytes(
         root_namaste.filename,
         Bytes::from(root_namaste.content.as_bytes()),
         Some(TYPE_PLAIN),
     )?;
 
-    let spec = match version {
-        SpecVersion::Ocfl1_0 => specs::OCFL_1_0_SPEC,
-        SpecVersion::Ocfl1_1 => specs::OCFL_1_1_SPEC,
-    };
+    if !spec_copy {
+        return Ok(());
+    }
+
+    let spec = specs::ocfl_spec(version);
 
-    s3_client.put_object_bytes(
+    client.put_object_bytes(
         version.spec_filename(),
         Bytes::from(spec.as_bytes()),
         Some(TYPE_PLAIN),
@@ -1303,7 +3299,7 @@ fn write_namaste_and_spec(s3_client: &S3Client, version: SpecVersion) -> Result<
     Ok(())
 }
 
-fn write_layout_config(s3_client: &S3Client, layout: &StorageLayout) -> Result<()> {
+fn write_layout_config(client: &dyn BlobClient, layout: &StorageLayout) -> Result<()> {
     let extension_name = layout.extension_name().to_string();
 
     let ocfl_layout = OcflLayout {
@@ -1315,13 +3311,13 @@ fn write_layout_config(s3_client: &S3Client, layout: &StorageLayout) -> Result<(
 
     serde_json::to_writer_pretty(&mut ocfl_layout_bytes, &ocfl_layout)?;
 
-    s3_client.put_object_bytes(
+    client.put_object_bytes(
         OCFL_LAYOUT_FILE,
         Bytes::from(ocfl_layout_bytes),
         Some(TYPE_JSON),
     )?;
 
-    s3_client.put_object_bytes(
+    client.put_object_bytes(
         &format!(
             "{}/{}/{}",
             EXTENSIONS_DIR, extension_name, EXTENSIONS_CONFIG_FILE
@@ -1330,15 +3326,9 @@ fn write_layout_config(s3_client: &S3Client, layout: &StorageLayout) -> Result<(
         Some(TYPE_JSON),
     )?;
 
-    let extension_spec = match layout.extension_name() {
-        LayoutExtensionName::FlatDirectLayout => specs::EXT_0002_SPEC,
-        LayoutExtensionName::HashedNTupleObjectIdLayout => specs::EXT_0003_SPEC,
-        LayoutExtensionName::HashedNTupleLayout => specs::EXT_0004_SPEC,
-        LayoutExtensionName::FlatOmitPrefixLayout => specs::EXT_0006_SPEC,
-        LayoutExtensionName::NTupleOmitPrefixLayout => specs::EXT_0007_SPEC,
-    };
+    let extension_spec = specs::layout_extension_spec(layout.extension_name());
 
-    s3_client.put_object_bytes(
+    client.put_object_bytes(
         &format!("{}.md", extension_name),
         Bytes::from(extension_spec),
         Some(TYPE_MARKDOWN),
@@ -1348,8 +3338,8 @@ fn write_layout_config(s3_client: &S3Client, layout: &StorageLayout) -> Result<(
 }
 
 /// Attempts to read `ocfl_layout.json` and returns it if able
-fn load_ocfl_layout<T: DeserializeOwned>(s3_client: &S3Client) -> Option<T> {
-    match s3_client.get_object(OCFL_LAYOUT_FILE) {
+fn load_ocfl_layout<T: DeserializeOwned>(client: &dyn BlobClient) -> Option<T> {
+    match client.get_object(OCFL_LAYOUT_FILE) {
         Ok(Some(layout)) => match serde_json::from_slice::<T>(layout.as_slice()) {
             Ok(layout) => Some(layout),
             Err(e) => {
@@ -1360,7 +3350,8 @@ fn load_ocfl_layout<T: DeserializeOwned>(s3_client: &S3Client) -> Option<T> {
         Ok(None) => {
             info!(
                 "The OCFL repository at {}/{} does not contain an ocfl_layout.json file.",
-                s3_client.bucket, s3_client.prefix
+                client.bucket(),
+                client.prefix()
             );
             None
         }
@@ -1371,33 +3362,133 @@ fn load_ocfl_layout<T: DeserializeOwned>(s3_client: &S3Client) -> Option<T> {
     }
 }
 
+/// Loads an index, such as the object index, from the specified path, if it exists. An empty
+/// index is returned if it does not exist or cannot be read.
+fn load_index<T: DeserializeOwned + Default>(
+    client: &dyn BlobClient,
+    index_path: &str,
+    name: &str,
+) -> T {
+    match client.get_object(index_path) {
+        Ok(Some(bytes)) => match serde_json::from_slice::<T>(&bytes) {
+            Ok(index) => index,
+            Err(e) => {
+                error!("Failed to load {} at {}: {:#}", name, index_path, e);
+                T::default()
+            }
+        },
+        Ok(None) => T::default(),
+        Err(e) => {
+            error!("Failed to load {} at {}: {:#}", name, index_path, e);
+            T::default()
+        }
+    }
+}
+
+fn write_index<T: Serialize>(client: &dyn BlobClient, index_path: &str, index: &T) -> Result<()> {
+    let mut bytes = serde_json::to_vec_pretty(index)?;
+    bytes.push(b'\n');
+
+    client.put_object_bytes(index_path, Bytes::from(bytes), Some(TYPE_JSON))
+}
+
+fn object_index_path() -> String {
+    join(
+        &join(EXTENSIONS_DIR, ROCFL_OBJECT_INDEX_EXTENSION),
+        OBJECT_INDEX_FILE,
+    )
+}
+
+fn object_index_config_path() -> String {
+    join(
+        &join(EXTENSIONS_DIR, ROCFL_OBJECT_INDEX_EXTENSION),
+        EXTENSIONS_CONFIG_FILE,
+    )
+}
+
+fn alias_index_path() -> String {
+    join(
+        &join(EXTENSIONS_DIR, ROCFL_ALIASES_EXTENSION),
+        ALIAS_INDEX_FILE,
+    )
+}
+
+fn repo_defaults_path() -> String {
+    join(
+        &join(EXTENSIONS_DIR, ROCFL_REPO_DEFAULTS_EXTENSION),
+        EXTENSIONS_CONFIG_FILE,
+    )
+}
+
+fn frozen_marker_path(object_root: &str) -> String {
+    join(
+        &join(&join(object_root, EXTENSIONS_DIR), ROCFL_FROZEN_EXTENSION),
+        FROZEN_FILE,
+    )
+}
+
+fn validation_history_path() -> String {
+    join(
+        &join(EXTENSIONS_DIR, ROCFL_VALIDATION_HISTORY_EXTENSION),
+        VALIDATION_HISTORY_FILE,
+    )
+}
+
 /// Reads `ocfl_layout.json` and attempts to load the specified storage layout extension
-fn load_storage_layout(s3_client: &S3Client) -> Option<StorageLayout> {
-    load_ocfl_layout::<OcflLayout>(s3_client)
-        .and_then(|layout| load_layout_extension(layout, s3_client))
+fn load_storage_layout(client: &dyn BlobClient) -> Option<StorageLayout> {
+    load_ocfl_layout::<OcflLayout>(client).and_then(|layout| load_layout_extension(layout, client))
+}
+
+/// Reads and verifies the storage root's `ocfl_layout.json` file and its extension config, if
+/// they exist. Unlike `load_storage_layout()`, problems are returned as errors.
+fn verify_existing_layout(client: &dyn BlobClient) -> Result<Option<StorageLayout>> {
+    let layout = match client.get_object(OCFL_LAYOUT_FILE)? {
+        Some(bytes) => serde_json::from_slice::<OcflLayoutLenient>(&bytes).map_err(|e| {
+            RocflError::IllegalState(format!("Failed to parse OCFL layout file: {:#}", e))
+        })?,
+        None => return Ok(None),
+    };
+
+    let extension = LayoutExtensionName::from_str(&layout.extension).map_err(|_| {
+        RocflError::IllegalState(format!(
+            "Storage layout extension {} is not supported",
+            layout.extension
+        ))
+    })?;
+
+    let config_path = join(
+        &join(EXTENSIONS_DIR, &layout.extension),
+        EXTENSIONS_CONFIG_FILE,
+    );
+    let config = client.get_object(&config_path)?;
+
+    let storage_layout = StorageLayout::new(extension, config.as_deref()).map_err(|e| {
+        RocflError::IllegalState(format!(
+            "Invalid storage layout extension {} config: {:#}",
+            extension, e
+        ))
+    })?;
+
+    Ok(Some(storage_layout))
 }
 
 /// Attempts to read a storage layout extension config and return configured `StorageLayout`
-fn load_layout_extension(layout: OcflLayout, s3_client: &S3Client) -> Option<StorageLayout> {
+fn load_layout_extension(layout: OcflLayout, client: &dyn BlobClient) -> Option<StorageLayout> {
     let config_path = join(
         &join(EXTENSIONS_DIR, &layout.extension.to_string()),
         EXTENSIONS_CONFIG_FILE,
     );
 
-    match s3_client.get_object(&config_path) {
+    match client.get_object(&config_path) {
         Ok(config) => match StorageLayout::new(layout.extension, config.as_deref()) {
             Ok(storage_layout) => {
-                info!(
-                    "Loaded storage layout extension {}",
-                    layout.extension.to_string()
-                );
+                info!("Loaded storage layout extension {}", layout.extension);
                 Some(storage_layout)
             }
             Err(e) => {
                 error!(
                     "Failed to load storage layout extension {}: {:#}",
-                    layout.extension.to_string(),
-                    e
+                    layout.extension, e
                 );
                 None
             }
@@ -1405,14 +3496,75 @@ fn load_layout_extension(layout: OcflLayout, s3_client: &S3Client) -> Option<Sto
         Err(e) => {
             error!(
                 "Failed to load storage layout extension {}: {:#}",
-                layout.extension.to_string(),
-                e
+                layout.extension, e
             );
             None
         }
     }
 }
 
+/// Returns the ETag that S3 assigns to an object that was uploaded in parts with the specified
+/// MD5 digests. This is the MD5 of the concatenated part digests, followed by the part count.
+fn multipart_e_tag(part_md5s: &[Vec<u8>]) -> String {
+    format!(
+        "{}-{}",
+        hex::encode(Md5::digest(part_md5s.concat())),
+        part_md5s.len()
+    )
+}
+
+/// Encodes tags as URL query parameters, as S3 expects in the tagging header
+fn encode_tags(tags: &[(String, String)]) -> Option<String> {
+    if tags.is_empty() {
+        return None;
+    }
+
+    Some(
+        tags.iter()
+            .map(|(key, value)| {
+                format!(
+                    "{}={}",
+                    utf8_percent_encode(key, NON_ALPHANUMERIC),
+                    utf8_percent_encode(value, NON_ALPHANUMERIC)
+                )
+            })
+            .collect::<Vec<String>>()
+            .join("&"),
+    )
+}
+
+/// Compares the ETag S3 assigned to an uploaded object to the expected ETag. The ETags of objects
+/// that are encrypted using SSE-KMS are not derived from their content, and are not compared.
+fn verify_e_tag(
+    key: &str,
+    e_tag: Option<&str>,
+    encryption: Option<&str>,
+    expected: &str,
+) -> Result<()> {
+    if encryption.is_some_and(|encryption| encryption.starts_with("aws:kms")) {
+        debug!(
+            "Skipping ETag verification of {} because it uses SSE-KMS",
+            key
+        );
+        return Ok(());
+    }
+
+    match e_tag.map(|e_tag| e_tag.trim_matches('"')) {
+        Some(e_tag) if e_tag.eq_ignore_ascii_case(expected) => Ok(()),
+        Some(e_tag) => Err(RocflError::General(format!(
+            "The upload of {} is corrupt. Expected ETag {}; Found: {}",
+            key, expected, e_tag
+        ))),
+        None => {
+            warn!(
+                "S3 did not return an ETag for {}. It cannot be verified.",
+                key
+            );
+            Ok(())
+        }
+    }
+}
+
 fn is_object_dir(objects: &[String]) -> bool {
     for object in objects {
         if object.ends_with(OBJECT_NAMASTE_FILE_1_0) || object.ends_with(OBJECT_NAMASTE_FILE_1_1) {
@@ -1424,7 +3576,14 @@ fn is_object_dir(objects: &[String]) -> bool {
 
 #[cfg(test)]
 mod tests {
-    use super::{is_object_dir, join, join_with_trailing_slash};
+    use md5::{Digest, Md5};
+    use rusoto_core::signature::SignedRequest;
+    use rusoto_core::{ByteStream, Region};
+
+    use super::{
+        is_object_dir, join, join_with_trailing_slash, multipart_e_tag, operation_name,
+        request_body_size, verify_e_tag,
+    };
 
     #[test]
     fn join_path_when_both_empty() {
@@ -1482,4 +3641,106 @@ mod tests {
         let objects = vec!["foo/bar.txt".to_string()];
         assert!(!is_object_dir(&objects));
     }
+
+    #[test]
+    fn identify_operations_from_requests() {
+        let request = |method: &str, params: &[&str]| {
+            let mut request = SignedRequest::new(method, "s3", &Region::UsEast2, "/bucket/key");
+            for param in params {
+                request.add_param(*param, "1");
+            }
+            request
+        };
+
+        assert_eq!(
+            "ListObjectsV2",
+            operation_name(&request("GET", &["list-type"]))
+        );
+        assert_eq!("GetObject", operation_name(&request("GET", &[])));
+        assert_eq!("HeadObject", operation_name(&request("HEAD", &[])));
+        assert_eq!(
+            "UploadPart",
+            operation_name(&request("PUT", &["partNumber", "uploadId"]))
+        );
+        assert_eq!("PutObject", operation_name(&request("PUT", &[])));
+        assert_eq!(
+            "CreateMultipartUpload",
+            operation_name(&request("POST", &["uploads"]))
+        );
+        assert_eq!(
+            "CompleteMultipartUpload",
+            operation_name(&request("POST", &["uploadId"]))
+        );
+        assert_eq!(
+            "AbortMultipartUpload",
+            operation_name(&request("DELETE", &["uploadId"]))
+        );
+        assert_eq!("DeleteObject", operation_name(&request("DELETE", &[])));
+    }
+
+    #[test]
+    fn request_body_size_of_buffered_and_streamed_bodies() {
+        let mut request = SignedRequest::new("PUT", "s3", &Region::UsEast2, "/bucket/key");
+        assert_eq!(0, request_body_size(&request));
+
+        request.set_payload(Some(b"content".to_vec()));
+        assert_eq!(7, request_body_size(&request));
+
+        request.set_payload_stream(ByteStream::from(b"streamed".to_vec()));
+        request.add_header("Content-Length", "8");
+        assert_eq!(8, request_body_size(&request));
+    }
+
+    #[test]
+    fn multipart_e_tag_is_digest_of_part_digests() {
+        let parts = vec![
+            Md5::digest(b"part one").to_vec(),
+            Md5::digest(b"part two").to_vec(),
+        ];
+        assert_eq!(
+            multipart_e_tag(&parts),
+            "0732917abc3288784e318ac0aab1757a-2"
+        );
+    }
+
+    #[test]
+    fn verify_e_tag_when_matches() {
+        let expected = "3303e12af474ca11d85ed2966a932992";
+        assert!(verify_e_tag(
+            "key",
+            Some("\"3303e12af474ca11d85ed2966a932992\""),
+            None,
+            expected
+        )
+        .is_ok());
+        assert!(verify_e_tag(
+            "key",
+            Some("3303E12AF474CA11D85ED2966A932992"),
+            None,
+            expected
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn verify_e_tag_fails_when_does_not_match() {
+        let result = verify_e_tag(
+            "key",
+            Some("\"0732917abc3288784e318ac0aab1757a-2\""),
+            Some("AES256"),
+            "3303e12af474ca11d85ed2966a932992",
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn verify_e_tag_skipped_when_kms_encrypted() {
+        assert!(verify_e_tag(
+            "key",
+            Some("\"not-an-md5\""),
+            Some("aws:kms"),
+            "3303e12af474ca11d85ed2966a932992"
+        )
+        .is_ok());
+    }
 }
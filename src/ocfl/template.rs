@@ -0,0 +1,252 @@
+//! Object templates describe the initial contents of new objects, so that objects that share a
+//! common structure can be created consistently.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::ocfl::consts::DEFAULT_CONTENT_DIR;
+use crate::ocfl::error::{Result, RocflError};
+use crate::ocfl::DigestAlgorithm;
+
+/// A definition of the first version of a new object. Templates may be written in TOML or JSON.
+///
+/// ```toml
+/// digest_algorithm = "sha512"
+///
+/// [metadata]
+/// type = "mets"
+///
+/// [[files]]
+/// path = "mets.xml"
+/// source = "skeletons/mets.xml"
+///
+/// [[files]]
+/// path = "README.txt"
+/// content = "Describe the object here\n"
+/// ```
+#[derive(Deserialize, Debug, Default, Eq, PartialEq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct ObjectTemplate {
+    /// The digest algorithm to create the object with
+    pub digest_algorithm: Option<DigestAlgorithm>,
+    /// The name of the object's content directory
+    pub content_directory: Option<String>,
+    /// The width to zero-pad version numbers to
+    pub zero_padding: Option<u32>,
+    /// The key/value metadata to stage in the object's metadata file
+    #[serde(default)]
+    pub metadata: BTreeMap<String, String>,
+    /// The files to stage in the object
+    #[serde(default)]
+    pub files: Vec<TemplateFile>,
+}
+
+/// A file in an `ObjectTemplate`. Each file must either be copied from a `source` file or
+/// contain the literal `content`, but not both.
+#[derive(Deserialize, Debug, Eq, PartialEq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct TemplateFile {
+    /// The logical path of the file in the object
+    pub path: String,
+    /// The file to copy the file's content from. Relative paths are resolved against the
+    /// directory the template is in.
+    pub source: Option<PathBuf>,
+    /// The literal content of the file
+    pub content: Option<String>,
+}
+
+impl ObjectTemplate {
+    /// Reads a template from a file. Files with a `.json` extension are parsed as JSON, and all
+    /// other files are parsed as TOML. Relative file sources are resolved against the directory
+    /// the template is in.
+    pub fn read(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let contents =
+            fs::read_to_string(path).map_err(|e| RocflError::io_path("read template", path, e))?;
+
+        let is_json = path
+            .extension()
+            .map(|ext| ext.eq_ignore_ascii_case("json"))
+            .unwrap_or(false);
+
+        let mut template = if is_json {
+            Self::from_json(&contents)
+        } else {
+            Self::from_toml(&contents)
+        }
+        .map_err(|e| {
+            RocflError::InvalidValue(format!(
+                "Invalid template {}: {}",
+                path.to_string_lossy(),
+                e
+            ))
+        })?;
+
+        if let Some(parent) = path.parent() {
+            for file in &mut template.files {
+                if let Some(source) = &file.source {
+                    if source.is_relative() {
+                        file.source = Some(parent.join(source));
+                    }
+                }
+            }
+        }
+
+        Ok(template)
+    }
+
+    /// Parses a TOML template. Relative file sources are not resolved.
+    pub fn from_toml(toml: &str) -> Result<Self> {
+        let template: Self =
+            toml::from_str(toml).map_err(|e| RocflError::InvalidValue(e.to_string()))?;
+        template.validate()?;
+        Ok(template)
+    }
+
+    /// Parses a JSON template. Relative file sources are not resolved.
+    pub fn from_json(json: &str) -> Result<Self> {
+        let template: Self =
+            serde_json::from_str(json).map_err(|e| RocflError::InvalidValue(e.to_string()))?;
+        template.validate()?;
+        Ok(template)
+    }
+
+    /// Returns an error if any of the template's files do not define exactly one of `source`
+    /// or `content`
+    pub fn validate(&self) -> Result<()> {
+        for file in &self.files {
+            match (&file.source, &file.content) {
+                (Some(_), Some(_)) => {
+                    return Err(RocflError::InvalidValue(format!(
+                        "Template file {} must define either a source or content, but not both",
+                        file.path
+                    )));
+                }
+                (None, None) => {
+                    return Err(RocflError::InvalidValue(format!(
+                        "Template file {} must define either a source or content",
+                        file.path
+                    )));
+                }
+                _ => (),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The template's digest algorithm, or sha512 if it does not define one
+    pub fn digest_algorithm(&self) -> DigestAlgorithm {
+        self.digest_algorithm.unwrap_or(DigestAlgorithm::Sha512)
+    }
+
+    /// The template's content directory, or `content` if it does not define one
+    pub fn content_directory(&self) -> &str {
+        self.content_directory
+            .as_deref()
+            .unwrap_or(DEFAULT_CONTENT_DIR)
+    }
+
+    /// The template's zero-padding width, or 0 if it does not define one
+    pub fn zero_padding(&self) -> u32 {
+        self.zero_padding.unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::fs;
+    use std::path::PathBuf;
+
+    use assert_fs::TempDir;
+
+    use super::{ObjectTemplate, TemplateFile};
+    use crate::ocfl::DigestAlgorithm;
+
+    #[test]
+    fn parse_toml_template() {
+        let template = ObjectTemplate::from_toml(
+            r#"
+            digest_algorithm = "sha256"
+            zero_padding = 3
+
+            [metadata]
+            type = "mets"
+
+            [[files]]
+            path = "mets.xml"
+            source = "skeletons/mets.xml"
+
+            [[files]]
+            path = "README.txt"
+            content = "readme"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            ObjectTemplate {
+                digest_algorithm: Some(DigestAlgorithm::Sha256),
+                content_directory: None,
+                zero_padding: Some(3),
+                metadata: BTreeMap::from([("type".to_string(), "mets".to_string())]),
+                files: vec![
+                    TemplateFile {
+                        path: "mets.xml".to_string(),
+                        source: Some(PathBuf::from("skeletons/mets.xml")),
+                        content: None,
+                    },
+                    TemplateFile {
+                        path: "README.txt".to_string(),
+                        source: None,
+                        content: Some("readme".to_string()),
+                    },
+                ],
+            },
+            template
+        );
+        assert_eq!("content", template.content_directory());
+    }
+
+    #[test]
+    fn parse_json_template_and_resolve_sources() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("template.json");
+        fs::write(
+            &path,
+            r#"{"content_directory": "data", "files": [{"path": "a.txt", "source": "a.txt"}]}"#,
+        )
+        .unwrap();
+
+        let template = ObjectTemplate::read(&path).unwrap();
+
+        assert_eq!("data", template.content_directory());
+        assert_eq!(DigestAlgorithm::Sha512, template.digest_algorithm());
+        assert_eq!(Some(temp.path().join("a.txt")), template.files[0].source);
+    }
+
+    #[test]
+    fn reject_files_without_exactly_one_source() {
+        assert!(ObjectTemplate::from_toml(
+            r#"
+            [[files]]
+            path = "a.txt"
+            "#
+        )
+        .is_err());
+        assert!(ObjectTemplate::from_toml(
+            r#"
+            [[files]]
+            path = "a.txt"
+            source = "a.txt"
+            content = "a"
+            "#
+        )
+        .is_err());
+        assert!(ObjectTemplate::from_toml("unknown = true").is_err());
+    }
+}
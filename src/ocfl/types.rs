@@ -1,14 +1,14 @@
 use core::fmt;
 use std::borrow::Cow;
 use std::cmp::Ordering;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::convert::{TryFrom, TryInto};
 use std::fmt::{Display, Formatter, Write};
 use std::hash::{Hash, Hasher};
-use std::path;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
 use std::str::{FromStr, Split};
+use std::time::{Duration, SystemTime};
 
 use chrono::{DateTime, Local};
 use once_cell::sync::Lazy;
@@ -18,11 +18,12 @@ use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use strum_macros::EnumIter;
 use VersionRef::Head;
 
-use crate::ocfl::bimap::PathBiMap;
+use crate::config::Config;
 use crate::ocfl::consts::*;
 use crate::ocfl::digest::HexDigest;
 use crate::ocfl::error::{Result, RocflError};
 use crate::ocfl::inventory::{Inventory, Version};
+use crate::ocfl::validate::{FixitySampleRecord, ObjectValidationResult, ValidationResult};
 use crate::ocfl::Knowable::{Known, Unknown};
 use crate::ocfl::VersionRef::Number;
 use crate::ocfl::{util, DigestAlgorithm};
@@ -48,11 +49,33 @@ pub struct VersionNum {
 }
 
 /// Represents either a specific version number or whatever the current head version is
+#[derive(Debug, Copy, Clone)]
 pub enum VersionRef {
     Number(VersionNum),
     Head,
 }
 
+/// A logical path that is optionally qualified with the version to resolve it in, written as
+/// `PATH@VERSION`, for example `dir/file.txt@v3`. A path that itself ends in `@VERSION` may be
+/// referred to by appending an additional `@`, for example `file@v3@`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct PathRef {
+    /// The logical path
+    pub path: LogicalPath,
+    /// The version the path is in, if specified
+    pub version: Option<VersionNum>,
+}
+
+/// A range of object versions, written as `START..END`, for example `v1..v5`. The range covers
+/// the changes that were made after the start version, up to and including the end version.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct VersionRange {
+    /// The version the changes are relative to
+    pub start: VersionNum,
+    /// The last version to include changes from
+    pub end: VersionNum,
+}
+
 /// OCFL spec version
 #[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, EnumIter)]
 pub enum SpecVersion {
@@ -71,6 +94,94 @@ pub struct RepoInfo {
     pub extensions: Vec<String>,
 }
 
+/// Describes a repository's storage layout configuration, and any problems with it
+#[derive(Debug, Default)]
+pub struct LayoutInfo {
+    /// The extension name declared in `ocfl_layout.json`, if it exists
+    pub extension: Option<String>,
+    /// The description declared in `ocfl_layout.json`, if it exists
+    pub description: Option<String>,
+    /// The effective layout configuration, serialized as JSON, if it could be loaded
+    pub config: Option<String>,
+    /// True if the layout extension has a `config.json` file. When it does not, the extension's
+    /// defaults are used.
+    pub has_config_file: bool,
+    /// Discrepancies between `ocfl_layout.json` and the repository's extension configs
+    pub problems: Vec<String>,
+}
+
+/// The kinds of problems that `OcflRepo::check_health()` looks for
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum HealthCheck {
+    /// The storage root's namaste file is missing or invalid
+    StorageRoot,
+    /// The storage layout declaration is inconsistent with the layout extension configs
+    Layout,
+    /// An object could not be read
+    UnreadableObject,
+    /// An object root contains a version directory that is not in its inventory
+    InterruptedCommit,
+    /// An object root does not have a sidecar for its root inventory
+    InventorySidecar,
+    /// A staged version is unreadable, or no longer follows its object's HEAD version
+    Staging,
+    /// An object lock file was left behind by a process that is no longer running, or has
+    /// existed for longer than expected
+    StaleLock,
+}
+
+/// A problem identified by `OcflRepo::check_health()`
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct HealthProblem {
+    /// The check that identified the problem
+    pub check: HealthCheck,
+    /// The ID of the object the problem is in, if it is specific to a known object
+    pub object_id: Option<String>,
+    /// The path the problem is at, relative the storage root, or the staging root for staging
+    /// problems and lock files
+    pub path: Option<String>,
+    /// Description of the problem
+    pub message: String,
+    /// Description of how the problem may be fixed
+    pub remedy: String,
+}
+
+/// The results of `OcflRepo::check_health()`
+#[derive(Debug, Default)]
+pub struct HealthReport {
+    /// The number of objects in the repository that were checked
+    pub objects_checked: usize,
+    /// The number of staged objects that were checked
+    pub staged_objects_checked: usize,
+    /// The problems that were identified, in the order they were found
+    pub problems: Vec<HealthProblem>,
+}
+
+/// The kinds of leftovers that `OcflRepo::find_orphans()` looks for
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum OrphanKind {
+    /// A lock file that was left behind by a process that is no longer running
+    StaleLock,
+    /// A staged object directory that does not contain an inventory
+    StagingDir,
+}
+
+/// A lock file or staging directory that was left behind by a rocfl process that stopped
+/// unexpectedly
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Orphan {
+    pub kind: OrphanKind,
+    /// The ID of the object the orphan belongs to, if it is known
+    pub object_id: Option<String>,
+    /// The absolute path to the lock file or directory
+    pub path: PathBuf,
+    /// Why the lock file or directory is believed to be orphaned
+    pub reason: String,
+    /// When the lock file or directory was last modified when it was found. It is not removed if
+    /// it has been modified since.
+    pub(crate) modified: SystemTime,
+}
+
 /// Encapsulates OCFL metadata about an object
 #[derive(Debug)]
 pub struct ObjectInfo {
@@ -80,6 +191,14 @@ pub struct ObjectInfo {
     pub digest_algorithm: Option<String>,
     /// The list of extension names configured on the object
     pub extensions: Vec<String>,
+    /// The content directory declared in the object's inventory, or the default
+    pub content_directory: Option<String>,
+    /// Descriptions of the object's content paths that are not within its content directory
+    pub content_dir_anomalies: Vec<String>,
+    /// The digest algorithms used in the inventory's fixity block, sorted
+    pub fixity_algorithms: Vec<String>,
+    /// The object's freeze marker, if it is frozen
+    pub frozen: Option<FreezeMarker>,
 }
 
 /// Encapsulates a namaste file name and content; used for version conformance declarations
@@ -91,7 +210,7 @@ pub(crate) struct Namaste {
 
 pub trait InventoryPath {
     /// Returns an iterable containing each segment of the path split on the `/` separator
-    fn parts(&self) -> Split<char>;
+    fn parts(&self) -> Split<'_, char>;
 
     /// Returns the parent path of this path.
     fn parent(&self) -> Self;
@@ -160,6 +279,23 @@ pub struct ObjectVersion {
     pub state: HashMap<Rc<LogicalPath>, FileDetails>,
 }
 
+/// A logical file or directory in an object version, and its descendants. Directories are
+/// implied by the `/` separated parts of the version's logical paths.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct StateTree {
+    /// The last part of the node's logical path. The root's name is empty.
+    pub name: String,
+    /// The node's logical path. The root's path is empty.
+    pub path: String,
+    /// The details of a file. This is `None` for directories.
+    pub details: Option<FileDetails>,
+    /// The size, in bytes, of a file, or the total size of the files within a directory. This
+    /// is only set when sizes are requested.
+    pub size: Option<u64>,
+    /// The node's children, sorted on name. Files do not have children.
+    pub children: Vec<StateTree>,
+}
+
 /// Details about a file in an OCFL object
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub struct FileDetails {
@@ -190,6 +326,131 @@ pub struct VersionDetails {
     pub message: Option<String>,
 }
 
+/// The content that was added to an object in a version
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct VersionContentSize {
+    /// The version number of the version
+    pub version_num: VersionNum,
+    /// The number of content files that were introduced in the version
+    pub file_count: u64,
+    /// The total size, in bytes, of the content files that were introduced in the version
+    pub byte_count: u64,
+}
+
+/// Restricts the objects, or the files in an object, that are listed to those that were created
+/// within a range of dates, or that are within a range of sizes. Objects are matched on the date
+/// their HEAD version was created, and the total size of their content files. Files are matched on
+/// the date of the version they were last updated in, and the size of their content.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ListFilter {
+    /// Only match entries created before this date
+    created_before: Option<DateTime<Local>>,
+    /// Only match entries created after this date
+    created_after: Option<DateTime<Local>>,
+    /// Only match entries that are at least this many bytes
+    min_size: Option<u64>,
+    /// Only match entries that are at most this many bytes
+    max_size: Option<u64>,
+    /// Only match objects that are frozen
+    frozen: bool,
+}
+
+/// Selects a range of an object's versions, so that callers that only display a few versions of
+/// objects with long histories do not need to process all of them
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct VersionPage {
+    /// Order the versions from newest to oldest, rather than oldest to newest
+    reverse: bool,
+    /// The number of versions to skip
+    offset: usize,
+    /// The maximum number of versions to include
+    limit: Option<usize>,
+}
+
+/// A secondary identifier, such as a DOI or handle, that resolves to an object
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct ObjectAlias {
+    /// The alias
+    pub alias: String,
+    /// The ID of the object the alias identifies
+    pub object_id: String,
+}
+
+/// A record of a rocfl operation that created an object version. When the audit trail is
+/// enabled, entries are appended to a file in the object's `logs` directory.
+#[derive(Deserialize, Serialize, Debug, Eq, PartialEq, Clone)]
+pub struct AuditTrailEntry {
+    /// The operation, such as `commit` or `upgrade`
+    pub operation: String,
+    /// The version the operation created
+    pub version: VersionNum,
+    /// When the operation was performed
+    pub timestamp: DateTime<Local>,
+    /// The name of the user the version is attributed to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_name: Option<String>,
+    /// The address of the user the version is attributed to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_address: Option<String>,
+    /// The logical paths that were added, modified, deleted, or renamed
+    pub paths: Vec<String>,
+    /// The name and version of the tool that performed the operation
+    pub tool_version: String,
+}
+
+/// Marks an object as frozen. rocfl refuses to create new versions of, or purge, frozen objects
+/// unless the operation is forced. The marker is stored in the object's `rocfl-frozen` extension.
+#[derive(Deserialize, Serialize, Debug, Eq, PartialEq, Clone)]
+pub struct FreezeMarker {
+    /// The ID of the frozen object
+    pub object_id: String,
+    /// When the object was frozen
+    pub timestamp: DateTime<Local>,
+    /// Why the object was frozen
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// A summary of the most recent validation of an object. When validation history is enabled,
+/// a record is kept for each object in the repository's `rocfl-validation-history` extension.
+#[derive(Deserialize, Serialize, Debug, Eq, PartialEq, Clone)]
+pub struct ValidationRecord {
+    /// The ID of the object that was validated
+    pub object_id: String,
+    /// When the object was validated
+    pub timestamp: DateTime<Local>,
+    /// The name and version of the tool that validated the object
+    pub tool_version: String,
+    /// True if no errors were identified
+    pub valid: bool,
+    /// The number of errors that were identified
+    pub errors: usize,
+    /// The number of warnings that were identified
+    pub warnings: usize,
+    /// The number of content files that had their fixity checked
+    pub fixity_checked: usize,
+    /// The state of the object's fixity sampling, if its content files were sampled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fixity_sample: Option<FixitySampleRecord>,
+}
+
+impl ValidationRecord {
+    /// Creates a record of a validation result that was produced now. `None` is returned if the
+    /// ID of the object that was validated is unknown.
+    pub fn from_result(result: &ObjectValidationResult) -> Option<Self> {
+        result.object_id.as_ref().map(|object_id| Self {
+            object_id: object_id.clone(),
+            timestamp: Local::now(),
+            tool_version: format!("rocfl {}", env!("CARGO_PKG_VERSION")),
+            valid: !result.has_errors(),
+            errors: result.errors().len(),
+            warnings: result.warnings().len(),
+            fixity_checked: result.fixity_checked().len(),
+            fixity_sample: result.fixity_sample().cloned(),
+        })
+    }
+}
+
 /// Similar to `ObjectVersion`, except it does not contain the state map.
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub struct ObjectVersionDetails {
@@ -203,6 +464,22 @@ pub struct ObjectVersionDetails {
     pub version_details: VersionDetails,
 }
 
+/// Identifies the current state of an object's root inventory, so that changes to the object can
+/// be detected without reading its inventory. Two fingerprints of the same object are only equal
+/// if the object's root inventory did not change between them.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct ObjectFingerprint {
+    /// The object's ID
+    pub object_id: String,
+    /// The algorithm that the inventory's digest was calculated with
+    pub digest_algorithm: DigestAlgorithm,
+    /// The digest of the object's root inventory, as recorded in its sidecar
+    pub inventory_digest: HexDigest,
+    /// The ETag of the object's root inventory, when the repository is in S3 or another cloud
+    /// storage service
+    pub e_tag: Option<String>,
+}
+
 /// Optional meta that may be associated with a commit
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub struct CommitMeta {
@@ -216,6 +493,204 @@ pub struct CommitMeta {
     pub(super) created: Option<DateTime<Local>>,
 }
 
+/// Builds `CommitMeta`, defaulting the user from a `Config` and the created timestamp to the
+/// time the meta is built
+#[derive(Debug, Default, Clone)]
+pub struct CommitMetaBuilder {
+    user_name: Option<String>,
+    user_address: Option<String>,
+    message: Option<String>,
+    created: Option<DateTime<Local>>,
+}
+
+/// The S3 storage class and object tags to apply to files when they are written to S3
+#[derive(Debug, Default, Eq, PartialEq, Clone)]
+pub struct S3WriteOptions {
+    /// The storage class to write files to. The bucket's default is used when not set.
+    pub storage_class: Option<String>,
+    /// The tags to apply to each file
+    pub tags: Vec<(String, String)>,
+}
+
+/// Options that control how a new repository is initialized
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub struct RepoInitOptions {
+    /// Whether a copy of the OCFL spec is written to the storage root, as the spec recommends.
+    /// Defaults to true.
+    pub spec_copy: bool,
+}
+
+impl Default for RepoInitOptions {
+    fn default() -> Self {
+        Self { spec_copy: true }
+    }
+}
+
+/// Configures the HTTP client that S3 requests are sent with
+#[derive(Debug, Default, Eq, PartialEq, Clone)]
+pub struct S3ClientOptions {
+    /// Prepended to the default user agent of every request, to identify the application that
+    /// made it
+    pub user_agent: Option<String>,
+    /// The maximum amount of time to wait for a response to each request. By default, requests
+    /// do not time out.
+    pub request_timeout: Option<Duration>,
+    /// Whether the number, latency, and size of requests is recorded. The recorded metrics are
+    /// retrieved with `OcflRepo::s3_request_metrics()`.
+    pub request_metrics: bool,
+}
+
+/// A cloud storage service, other than S3, that a repository may be stored in. Repositories are
+/// stored under a prefix within a bucket, or container, of the service in the same way that they
+/// are in S3.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub enum CloudStorage {
+    /// Azure Blob Storage. Credentials are read from the environment when `access_key` is not
+    /// set.
+    Azure {
+        /// The name of the storage account that the container is in
+        account: String,
+        /// The storage account's access key
+        access_key: Option<String>,
+    },
+    /// Google Cloud Storage. Credentials are read from the environment when `service_account`
+    /// is not set.
+    Gcs {
+        /// The path to a service account JSON key file
+        service_account: Option<String>,
+    },
+}
+
+/// The requests that have been sent to S3, grouped by the S3 operation they invoked, such as
+/// `GetObject` or `ListObjectsV2`
+#[derive(Debug, Default, Eq, PartialEq, Clone)]
+pub struct S3RequestMetrics {
+    pub operations: BTreeMap<String, S3OperationMetrics>,
+}
+
+/// Aggregate metrics about the requests that invoked a single S3 operation
+#[derive(Debug, Default, Eq, PartialEq, Copy, Clone)]
+pub struct S3OperationMetrics {
+    /// The number of requests sent
+    pub requests: u64,
+    /// The number of requests that failed to receive a response, or received an error response
+    pub failures: u64,
+    /// The total time spent waiting for responses. The time spent reading response bodies is
+    /// not included.
+    pub latency: Duration,
+    /// The total size of request bodies, in bytes
+    pub bytes_sent: u64,
+    /// The total size of response bodies, in bytes, as declared by their `Content-Length`
+    pub bytes_received: u64,
+}
+
+/// The changes that a mutating operation makes to a repository. In dry-run mode, operations
+/// record their plans instead of making the changes.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct ChangePlan {
+    /// The name of the operation, such as `commit` or `copy`
+    pub operation: String,
+    /// The ID of the object that the operation changes, if it changes an object
+    pub object_id: Option<String>,
+    /// The individual changes, in the order they are made
+    pub changes: Vec<PlannedChange>,
+}
+
+/// A single change within a `ChangePlan`
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub enum PlannedChange {
+    /// The logical path is added to, or updated in, the object's staged or committed version
+    AddPath(String),
+    /// The logical path is removed from the object's staged or committed version
+    RemovePath(String),
+    /// The object's staged version is committed as a new version
+    CreateVersion(VersionNum),
+    /// The object's staged version is discarded
+    DiscardStagedVersion(VersionNum),
+    /// The object is upgraded to the spec version
+    UpgradeObject(SpecVersion),
+    /// The content file, relative the object's root, is written to the object's staged version
+    StageFile(String),
+    /// The content file, relative the object's root, is deleted from the object's staged version
+    UnstageFile(String),
+    /// The file, relative the storage root, is written
+    WriteFile(String),
+    /// The file, relative the storage root, is deleted
+    DeleteFile(String),
+    /// The object's version is rewritten as a different version, when versions are squashed
+    RewriteVersion(VersionNum, VersionNum),
+}
+
+/// The outcome of squashing an object's versions
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct SquashReport {
+    /// The ID of the object
+    pub object_id: String,
+    /// Maps each of the object's original versions to the version it is part of after the
+    /// squash, in order. The versions that were collapsed all map to `v1`.
+    pub versions: Vec<(VersionNum, VersionNum)>,
+    /// The content paths, relative the object root, of the files that were removed because the
+    /// remaining versions do not reference their content
+    pub removed_files: Vec<String>,
+}
+
+/// The content of an object's staged version that is staged within the repository's S3 bucket
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct BucketStagedObject {
+    /// The path to the object's root within the staging repository
+    pub object_root: String,
+    /// The number of staged content files
+    pub files: usize,
+    /// The total size of the staged content files, in bytes
+    pub bytes: u64,
+    /// When the most recently staged content file was written
+    pub last_modified: Option<DateTime<Local>>,
+}
+
+/// Limits that are enforced when objects are committed, so that runaway jobs are caught before
+/// they write to the repository. Limits that are not set are not enforced.
+#[derive(Debug, Default, Eq, PartialEq, Copy, Clone)]
+pub struct CommitQuotas {
+    /// The maximum size, in bytes, of all of an object's content files, including the files
+    /// committed in earlier versions
+    pub max_object_size: Option<u64>,
+    /// The maximum number of logical paths in a version's state
+    pub max_version_files: Option<u64>,
+    /// The maximum size, in bytes, of the content files that a commit adds to an object
+    pub max_staged_bytes: Option<u64>,
+}
+
+/// How symbolic links are treated when copying or moving files from outside the repository into
+/// an object
+#[derive(Debug, Default, Eq, PartialEq, Copy, Clone)]
+pub enum SymlinkPolicy {
+    /// Links are followed, and the files they point to are added to the object. Directory links
+    /// are walked into, unless they form a cycle.
+    #[default]
+    Follow,
+    /// Links are not added to the object, and a warning is logged for each link that is skipped
+    Skip,
+    /// Links are not added to the object, and each link that is encountered is reported as an
+    /// error
+    Error,
+}
+
+/// How paths that are deleted and added with the same content are reported in diffs. Renames
+/// are only detected when the content's digest is unchanged.
+#[derive(Debug, Default, Eq, PartialEq, Copy, Clone)]
+pub enum RenameDetection {
+    /// All of the deleted and added paths that share a digest are reported as a single rename,
+    /// even when several paths are renamed to several others
+    #[default]
+    All,
+    /// Only a single deleted path and a single added path that share a digest are reported as a
+    /// rename. When more than one path on either side shares the digest, the paths are reported
+    /// as adds and deletes.
+    OneToOne,
+    /// Renames are not detected, and all paths are reported as adds and deletes
+    Off,
+}
+
 /// Represents a change to a file
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub enum Diff {
@@ -228,6 +703,50 @@ pub enum Diff {
     },
 }
 
+/// A difference between the files in versions of two different objects, A and B
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub enum ObjectDiff {
+    /// The path only exists in object A
+    OnlyInA(Rc<LogicalPath>),
+    /// The path only exists in object B
+    OnlyInB(Rc<LogicalPath>),
+    /// The path exists in both objects, but its content differs
+    ContentDiffers(Rc<LogicalPath>),
+}
+
+/// The changes that were made to the files in a version, relative the version before it
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct VersionDiff {
+    /// The version the changes were made in
+    pub version_num: VersionNum,
+    /// The changes made in the version
+    pub diffs: Vec<Diff>,
+}
+
+/// A line within a file that matched a search pattern
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct ContentMatch {
+    /// The logical path of the file
+    pub logical_path: Rc<LogicalPath>,
+    /// The 1-based line number of the line
+    pub line_number: u64,
+    /// The content of the line, without its line terminator
+    pub line: String,
+}
+
+/// A logical path in an object version that matched a path glob
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct PathMatch {
+    /// The ID of the object the path is in
+    pub object_id: String,
+    /// The version the path is in
+    pub version_num: VersionNum,
+    /// The logical path that matched
+    pub logical_path: Rc<LogicalPath>,
+    /// The digest of the file's content
+    pub digest: Rc<HexDigest>,
+}
+
 pub(crate) struct PrettyPrintSet<'a, T: Display>(pub(crate) &'a HashSet<T>);
 
 impl<K, U> Knowable<K, U> {
@@ -300,7 +819,7 @@ impl VersionNum {
             _ => u32::pow(10, self.width - 1) - 1,
         };
 
-        if self.number + 1 > max as u32 {
+        if self.number + 1 > max {
             return Err(RocflError::IllegalState(format!(
                 "Version cannot be greater than {}",
                 max
@@ -467,6 +986,158 @@ impl TryFrom<&str> for VersionRef {
     }
 }
 
+impl From<&PathRef> for VersionRef {
+    fn from(path_ref: &PathRef) -> Self {
+        path_ref.version.into()
+    }
+}
+
+impl FromStr for PathRef {
+    type Err = RocflError;
+
+    /// Parses a string in the format `PATH` or `PATH@VERSION`, where the version is formatted
+    /// like `v3`. If the text following the final `@` is not a version, then the entire string
+    /// is the path.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (path, version) = match s.rsplit_once('@') {
+            Some((path, "")) => (path, None),
+            Some((path, version)) => match VersionNum::try_from(version) {
+                Ok(version) => (path, Some(version)),
+                Err(_) => (s, None),
+            },
+            None => (s, None),
+        };
+
+        Ok(Self {
+            path: path.try_into()?,
+            version,
+        })
+    }
+}
+
+impl Display for PathRef {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.version {
+            Some(version) => write!(f, "{}@{}", self.path, version),
+            None => write!(f, "{}", self.path),
+        }
+    }
+}
+
+impl VersionRange {
+    /// Creates a new range, or returns an error if the start version is not before the end version
+    pub fn new(start: VersionNum, end: VersionNum) -> Result<Self> {
+        if start >= end {
+            return Err(RocflError::InvalidValue(format!(
+                "Invalid version range {}..{}. The start version must be before the end version",
+                start, end
+            )));
+        }
+
+        Ok(Self { start, end })
+    }
+
+    /// Returns the versions whose changes are covered by the range, in ascending order
+    pub fn versions(&self) -> impl Iterator<Item = VersionNum> {
+        let width = self.end.width;
+        (self.start.number + 1..=self.end.number).map(move |number| VersionNum { number, width })
+    }
+}
+
+impl FromStr for VersionRange {
+    type Err = RocflError;
+
+    /// Parses a string in the format `START..END`, where each version is formatted like `v3` or
+    /// `3`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once("..") {
+            Some((start, end)) => {
+                VersionRange::new(VersionNum::from_str(start)?, VersionNum::from_str(end)?)
+            }
+            None => Err(RocflError::InvalidValue(format!(
+                "Invalid version range {}. Ranges must be formatted as START..END",
+                s
+            ))),
+        }
+    }
+}
+
+impl HealthProblem {
+    pub fn new(check: HealthCheck, message: impl Into<String>, remedy: impl Into<String>) -> Self {
+        Self {
+            check,
+            object_id: None,
+            path: None,
+            message: message.into(),
+            remedy: remedy.into(),
+        }
+    }
+
+    pub fn with_object_id(mut self, object_id: impl Into<String>) -> Self {
+        self.object_id = Some(object_id.into());
+        self
+    }
+
+    pub fn with_path(mut self, path: impl Into<String>) -> Self {
+        self.path = Some(path.into());
+        self
+    }
+}
+
+impl Display for HealthCheck {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            HealthCheck::StorageRoot => "storage-root",
+            HealthCheck::Layout => "layout",
+            HealthCheck::UnreadableObject => "unreadable-object",
+            HealthCheck::InterruptedCommit => "interrupted-commit",
+            HealthCheck::InventorySidecar => "inventory-sidecar",
+            HealthCheck::Staging => "staging",
+            HealthCheck::StaleLock => "stale-lock",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl ChangePlan {
+    pub fn new(operation: &str, object_id: Option<&str>) -> Self {
+        Self {
+            operation: operation.to_string(),
+            object_id: object_id.map(|id| id.to_string()),
+            changes: Vec::new(),
+        }
+    }
+}
+
+impl Display for PlannedChange {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            PlannedChange::AddPath(path) => write!(f, "add {}", path),
+            PlannedChange::RemovePath(path) => write!(f, "remove {}", path),
+            PlannedChange::CreateVersion(version) => write!(f, "create version {}", version),
+            PlannedChange::DiscardStagedVersion(version) => {
+                write!(f, "discard staged version {}", version)
+            }
+            PlannedChange::UpgradeObject(version) => {
+                write!(f, "upgrade to OCFL {}", version.version())
+            }
+            PlannedChange::StageFile(path) => write!(f, "stage {}", path),
+            PlannedChange::UnstageFile(path) => write!(f, "unstage {}", path),
+            PlannedChange::WriteFile(path) => write!(f, "write {}", path),
+            PlannedChange::DeleteFile(path) => write!(f, "delete {}", path),
+            PlannedChange::RewriteVersion(original, squashed) => {
+                write!(f, "rewrite version {} as {}", original, squashed)
+            }
+        }
+    }
+}
+
+impl Display for VersionRange {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}..{}", self.start, self.end)
+    }
+}
+
 impl SpecVersion {
     /// Return the OCFL spec version based on the version number string
     pub fn try_from_num(version: &str) -> Result<SpecVersion> {
@@ -555,15 +1226,31 @@ impl RepoInfo {
 }
 
 impl ObjectInfo {
+    /// Creates a new `ObjectInfo`. The inventory is `None` when the object adheres to an
+    /// unsupported spec version.
     pub fn new(
         spec_version: String,
-        digest_algorithm: Option<String>,
+        inventory: Option<&Inventory>,
         extensions: Vec<String>,
+        frozen: Option<FreezeMarker>,
     ) -> Self {
         Self {
             spec_version,
-            digest_algorithm,
+            digest_algorithm: inventory.map(|inv| inv.digest_algorithm.to_string()),
             extensions,
+            content_directory: inventory.map(|inv| inv.defaulted_content_dir().to_string()),
+            content_dir_anomalies: inventory
+                .map(Inventory::content_dir_anomalies)
+                .unwrap_or_default(),
+            fixity_algorithms: inventory
+                .map(|inv| {
+                    inv.fixity_algorithms()
+                        .into_iter()
+                        .map(String::from)
+                        .collect()
+                })
+                .unwrap_or_default(),
+            frozen,
         }
     }
 }
@@ -576,7 +1263,7 @@ impl Namaste {
 
 impl InventoryPath for InventoryPathInner {
     /// Returns an iterable containing each segment of the path split on the `/` separator
-    fn parts(&self) -> Split<char> {
+    fn parts(&self) -> Split<'_, char> {
         self.0.split('/')
     }
 
@@ -634,7 +1321,7 @@ impl InventoryPath for InventoryPathInner {
 
 impl InventoryPath for LogicalPath {
     /// Returns an iterable containing each segment of the path split on the `/` separator
-    fn parts(&self) -> Split<char> {
+    fn parts(&self) -> Split<'_, char> {
         self.inner.parts()
     }
 
@@ -686,7 +1373,7 @@ impl InventoryPath for LogicalPath {
 
 impl InventoryPath for ContentPath {
     /// Returns an iterable containing each segment of the path split on the `/` separator
-    fn parts(&self) -> Split<char> {
+    fn parts(&self) -> Split<'_, char> {
         self.inner.parts()
     }
 
@@ -1052,11 +1739,31 @@ impl<'de> Deserialize<'de> for LogicalPath {
 impl ObjectVersion {
     /// Creates an `ObjectVersion` by consuming the supplied `Inventory`.
     pub fn from_inventory<S: AsRef<str> + Copy>(
+        inventory: Inventory,
+        version_num: VersionRef,
+        object_storage_path: S,
+        object_staging_path: Option<S>,
+        use_backslashes: bool,
+    ) -> Result<Self> {
+        ObjectVersion::from_inventory_subset(
+            inventory,
+            version_num,
+            object_storage_path,
+            object_staging_path,
+            use_backslashes,
+            |_| true,
+        )
+    }
+
+    /// Same as `from_inventory()`, except the version's state only contains the logical paths
+    /// that are accepted by `matches`. The details of the other paths are never resolved.
+    pub fn from_inventory_subset<S: AsRef<str> + Copy>(
         mut inventory: Inventory,
         version_num: VersionRef,
         object_storage_path: S,
         object_staging_path: Option<S>,
         use_backslashes: bool,
+        matches: impl Fn(&LogicalPath) -> bool,
     ) -> Result<Self> {
         let version_num = version_num.resolve(inventory.head);
 
@@ -1069,29 +1776,43 @@ impl ObjectVersion {
             object_storage_path,
             object_staging_path,
             use_backslashes,
+            matches,
         )?;
 
         Ok(Self {
             id: inventory.id,
-            object_root: inventory.storage_path,
+            object_root: util::strip_extended_length_prefix(&inventory.storage_path).into(),
             digest_algorithm: inventory.digest_algorithm,
             version_details,
             state,
         })
     }
 
+    /// Computes the state of the target version along with the version that each file was last
+    /// updated in. Versions are walked backwards from the target, once, and a file's last update
+    /// is the earliest version in the unbroken run of versions that map its logical path to its
+    /// current digest. Each path is resolved as soon as the run ends, and is not looked up in any
+    /// earlier versions, so the cost is proportional to the number of state entries in the runs,
+    /// which is bounded by the size of the inventory. Only the paths that are accepted by
+    /// `matches` are resolved.
     fn construct_state<S: AsRef<str> + Copy>(
         target: VersionNum,
         inventory: &mut Inventory,
         object_storage_path: S,
         object_staging_path: Option<S>,
         use_backslashes: bool,
+        matches: impl Fn(&LogicalPath) -> bool,
     ) -> Result<HashMap<Rc<LogicalPath>, FileDetails>> {
-        let mut state = HashMap::new();
-
         let mut current_version_num = target;
         let mut current_version = inventory.remove_version(target)?;
-        let mut target_path_map = current_version.remove_state();
+        let mut unresolved: Vec<_> = current_version
+            .remove_state()
+            .into_iter()
+            .filter(|(path, _)| matches(path))
+            .collect();
+
+        let mut state = HashMap::with_capacity(unresolved.len());
+        let mut updated = Vec::new();
 
         // This nonsense is needed to differentiate the storage paths for staged files
         let staging_version_prefix = if object_staging_path.is_some() {
@@ -1100,86 +1821,70 @@ impl ObjectVersion {
             None
         };
 
-        while !target_path_map.is_empty() {
-            let mut not_found = PathBiMap::new();
+        while !unresolved.is_empty() {
             let version_details = Rc::new(VersionDetails::from_version(
                 current_version_num,
                 current_version,
             ));
 
-            // No versions left to compare to; any remaining files were last updated here
-            if version_details.version_num.number == 1 {
-                for (target_path, target_digest) in target_path_map {
-                    let content_path = inventory.content_path_for_digest(
-                        &target_digest,
-                        current_version_num.into(),
-                        Some(&target_path),
-                    )?;
-
-                    let storage_path = ObjectVersion::storage_path(
-                        content_path.as_str(),
-                        object_storage_path,
-                        use_backslashes,
-                        &staging_version_prefix,
-                        &object_staging_path,
-                    );
-
-                    state.insert(
-                        target_path,
-                        FileDetails::new(
-                            content_path.clone(),
-                            storage_path,
-                            target_digest,
-                            inventory.digest_algorithm,
-                            version_details.clone(),
-                        ),
-                    );
+            let previous_version = if current_version_num.number == 1 {
+                // No versions left to compare to; any remaining files were last updated here
+                None
+            } else {
+                let previous_version_num = current_version_num.previous()?;
+                Some((
+                    previous_version_num,
+                    inventory.remove_version(previous_version_num)?,
+                ))
+            };
+
+            // The files that did not have the same digest in the previous version were last
+            // updated in this version, and are moved out of the unresolved files in place
+            unresolved.retain(|(path, digest)| {
+                let unchanged = previous_version
+                    .as_ref()
+                    .and_then(|(_, version)| version.lookup_digest(path))
+                    == Some(digest);
+                if !unchanged {
+                    updated.push((path.clone(), digest.clone()));
                 }
-
-                break;
+                unchanged
+            });
+
+            for (target_path, target_digest) in updated.drain(..) {
+                let content_path = inventory.content_path_for_digest(
+                    &target_digest,
+                    current_version_num.into(),
+                    Some(&target_path),
+                )?;
+
+                let storage_path = ObjectVersion::storage_path(
+                    content_path.as_str(),
+                    object_storage_path,
+                    use_backslashes,
+                    &staging_version_prefix,
+                    &object_staging_path,
+                );
+
+                state.insert(
+                    target_path,
+                    FileDetails::new(
+                        content_path.clone(),
+                        storage_path,
+                        target_digest,
+                        inventory.digest_algorithm,
+                        version_details.clone(),
+                    ),
+                );
             }
 
-            let previous_version_num = version_details.version_num.previous()?;
-            let mut previous_version = inventory.remove_version(previous_version_num)?;
-            let mut previous_path_map = previous_version.remove_state();
-
-            for (target_path, target_digest) in target_path_map {
-                let entry = previous_path_map.remove_path(&target_path);
-
-                if entry.is_none() || entry.unwrap().1 != target_digest {
-                    let content_path = inventory.content_path_for_digest(
-                        &target_digest,
-                        current_version_num.into(),
-                        Some(&target_path),
-                    )?;
-
-                    let storage_path = ObjectVersion::storage_path(
-                        content_path.as_str(),
-                        object_storage_path,
-                        use_backslashes,
-                        &staging_version_prefix,
-                        &object_staging_path,
-                    );
-
-                    state.insert(
-                        target_path,
-                        FileDetails::new(
-                            content_path.clone(),
-                            storage_path,
-                            target_digest,
-                            inventory.digest_algorithm,
-                            version_details.clone(),
-                        ),
-                    );
-                } else {
-                    not_found.insert_rc(target_digest, target_path);
+            match previous_version {
+                Some((previous_version_num, previous_version)) => {
+                    current_version_num = previous_version_num;
+                    current_version = previous_version;
                 }
+                None => break,
             }
-
-            current_version_num = previous_version_num;
-            current_version = previous_version;
-
-            target_path_map = not_found;
         }
 
         Ok(state)
@@ -1196,19 +1901,23 @@ impl ObjectVersion {
             && content_path.starts_with(staging_version_prefix.as_ref().unwrap())
         {
             // The content path resides in staging
-            convert_path_separator(
+            util::convert_path_separator(
                 util::BACKSLASH_SEPARATOR,
-                join(
+                util::join_with_separator(
                     util::BACKSLASH_SEPARATOR,
-                    staging_path.unwrap().as_ref(),
+                    &util::strip_extended_length_prefix(staging_path.unwrap().as_ref()),
                     content_path,
                 ),
             )
         } else {
             // The content path resides in the main repo
-            convert_path_separator(
+            util::convert_path_separator(
                 use_backslashes,
-                join(use_backslashes, storage_path.as_ref(), content_path),
+                util::join_with_separator(
+                    use_backslashes,
+                    &util::strip_extended_length_prefix(storage_path.as_ref()),
+                    content_path,
+                ),
             )
         }
     }
@@ -1276,13 +1985,93 @@ impl ObjectVersionDetails {
 
         Ok(Self {
             id: inventory.id,
-            object_root: inventory.storage_path,
+            object_root: util::strip_extended_length_prefix(&inventory.storage_path).into(),
             digest_algorithm: inventory.digest_algorithm,
             version_details,
         })
     }
 }
 
+impl StateTree {
+    /// Builds the tree of the logical paths in an object version's state
+    pub fn from_state(state: HashMap<Rc<LogicalPath>, FileDetails>) -> Self {
+        #[derive(Default)]
+        struct Node {
+            details: Option<FileDetails>,
+            children: BTreeMap<String, Node>,
+        }
+
+        fn build(name: String, path: String, node: Node) -> StateTree {
+            let children = node
+                .children
+                .into_iter()
+                .map(|(child_name, child)| {
+                    let child_path = if path.is_empty() {
+                        child_name.clone()
+                    } else {
+                        format!("{}/{}", path, child_name)
+                    };
+                    build(child_name, child_path, child)
+                })
+                .collect();
+
+            StateTree {
+                name,
+                path,
+                details: node.details,
+                size: None,
+                children,
+            }
+        }
+
+        let mut root = Node::default();
+
+        for (path, details) in state {
+            let mut current = &mut root;
+            for part in path.as_str().split('/') {
+                current = current.children.entry(part.to_string()).or_default();
+            }
+            current.details = Some(details);
+        }
+
+        build(String::new(), String::new(), root)
+    }
+
+    /// Returns true if the node is a directory
+    pub fn is_dir(&self) -> bool {
+        self.details.is_none()
+    }
+
+    /// Returns the node at the logical path, relative this node, or `None` if it does not exist
+    pub fn find(&self, path: &str) -> Option<&StateTree> {
+        let path = util::trim_slashes(path);
+        if path.is_empty() {
+            return Some(self);
+        }
+
+        let mut current = self;
+        for part in path.split('/') {
+            current = current.children.iter().find(|child| child.name == part)?;
+        }
+        Some(current)
+    }
+
+    /// Sets the size of every file using the sizes of the content with each digest, and the size
+    /// of every directory to the total size of its files
+    pub(crate) fn set_sizes(&mut self, sizes: &HashMap<Rc<HexDigest>, u64>) -> u64 {
+        let size = match &self.details {
+            Some(details) => sizes.get(&details.digest).copied().unwrap_or_default(),
+            None => self
+                .children
+                .iter_mut()
+                .map(|child| child.set_sizes(sizes))
+                .sum(),
+        };
+        self.size = Some(size);
+        size
+    }
+}
+
 impl Default for CommitMeta {
     fn default() -> Self {
         Self::new()
@@ -1323,6 +2112,139 @@ impl CommitMeta {
         self.created = created;
         self
     }
+
+    /// Returns a builder for constructing commit meta
+    pub fn builder() -> CommitMetaBuilder {
+        CommitMetaBuilder::default()
+    }
+}
+
+impl CommitMetaBuilder {
+    /// Sets the name of the user who created the commit
+    pub fn user_name(mut self, name: impl Into<Option<String>>) -> Self {
+        self.user_name = name.into();
+        self
+    }
+
+    /// Sets the URI address of the user who created the commit, such as `mailto:me@example.com`.
+    /// A user name must also be set.
+    pub fn user_address(mut self, address: impl Into<Option<String>>) -> Self {
+        self.user_address = address.into();
+        self
+    }
+
+    /// Sets the message describing the changes
+    pub fn message(mut self, message: impl Into<Option<String>>) -> Self {
+        self.message = message.into();
+        self
+    }
+
+    /// Sets when the commit was created. Default: the time `build()` is called
+    pub fn created(mut self, created: impl Into<Option<DateTime<Local>>>) -> Self {
+        self.created = created.into();
+        self
+    }
+
+    /// Uses the `author_name` and `author_address` in the config as the commit user, unless a
+    /// user has already been set. The config's address is only used with the config's name.
+    pub fn defaults_from_config(mut self, config: &Config) -> Self {
+        if self.user_name.is_none() && self.user_address.is_none() {
+            self.user_name = config.author_name.clone();
+            self.user_address = config.author_address.clone();
+        }
+        self
+    }
+
+    /// Builds the commit meta. An error is returned if a user address is set without a
+    /// user name.
+    pub fn build(self) -> Result<CommitMeta> {
+        Ok(CommitMeta::new()
+            .with_user(self.user_name, self.user_address)?
+            .with_message(self.message)
+            .with_created(Some(self.created.unwrap_or_else(Local::now))))
+    }
+}
+
+impl S3WriteOptions {
+    /// Creates write options from a storage class name, such as `STANDARD_IA`, and a comma
+    /// separated list of `KEY=VALUE` tags. An error is returned if the storage class is unknown
+    /// or the tags are malformed.
+    pub fn parse(storage_class: Option<&str>, tags: Option<&str>) -> Result<Self> {
+        let storage_class = match storage_class {
+            Some(class) => Some(parse_storage_class(class)?),
+            None => None,
+        };
+
+        let tags = match tags {
+            Some(tags) => parse_tags(tags)?,
+            None => Vec::new(),
+        };
+
+        Ok(Self {
+            storage_class,
+            tags,
+        })
+    }
+
+    /// Adds a tag to the options. An error is returned if the key is empty or the maximum
+    /// number of tags is exceeded.
+    pub fn with_tag(mut self, key: &str, value: &str) -> Result<Self> {
+        if key.trim().is_empty() {
+            return Err(RocflError::InvalidValue(format!(
+                "S3 tag keys cannot be blank. Found: '{}={}'",
+                key, value
+            )));
+        }
+
+        self.tags.retain(|(k, _)| k != key.trim());
+        self.tags
+            .push((key.trim().to_string(), value.trim().to_string()));
+
+        if self.tags.len() > S3_MAX_TAGS {
+            return Err(RocflError::InvalidValue(format!(
+                "S3 objects may have at most {} tags",
+                S3_MAX_TAGS
+            )));
+        }
+
+        Ok(self)
+    }
+
+    /// Returns true if neither a storage class nor tags are set
+    pub fn is_empty(&self) -> bool {
+        self.storage_class.is_none() && self.tags.is_empty()
+    }
+}
+
+fn parse_storage_class(class: &str) -> Result<String> {
+    let normalized = class.trim().to_uppercase();
+    if S3_STORAGE_CLASSES.contains(&normalized.as_str()) {
+        Ok(normalized)
+    } else {
+        Err(RocflError::InvalidValue(format!(
+            "Unknown S3 storage class '{}'. Must be one of: {}",
+            class,
+            S3_STORAGE_CLASSES.join(", ")
+        )))
+    }
+}
+
+fn parse_tags(tags: &str) -> Result<Vec<(String, String)>> {
+    let mut options = S3WriteOptions::default();
+
+    for tag in tags.split(',').filter(|tag| !tag.trim().is_empty()) {
+        match tag.split_once('=') {
+            Some((key, value)) => options = options.with_tag(key, value)?,
+            None => {
+                return Err(RocflError::InvalidValue(format!(
+                    "S3 tags must be formatted as KEY=VALUE. Found: '{}'",
+                    tag
+                )))
+            }
+        }
+    }
+
+    Ok(options.tags)
 }
 
 impl Diff {
@@ -1340,6 +2262,114 @@ impl Diff {
     }
 }
 
+impl ListFilter {
+    /// Creates a filter that matches everything
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only match entries that were created before the date
+    pub fn with_created_before(mut self, date: DateTime<Local>) -> Self {
+        self.created_before = Some(date);
+        self
+    }
+
+    /// Only match entries that were created after the date
+    pub fn with_created_after(mut self, date: DateTime<Local>) -> Self {
+        self.created_after = Some(date);
+        self
+    }
+
+    /// Only match entries that are at least `bytes` in size
+    pub fn with_min_size(mut self, bytes: u64) -> Self {
+        self.min_size = Some(bytes);
+        self
+    }
+
+    /// Only match entries that are at most `bytes` in size
+    pub fn with_max_size(mut self, bytes: u64) -> Self {
+        self.max_size = Some(bytes);
+        self
+    }
+
+    /// Only match objects that are frozen. This does not apply to files within objects.
+    pub fn with_frozen(mut self) -> Self {
+        self.frozen = true;
+        self
+    }
+
+    /// Returns true if the filter only matches frozen objects, which requires each object's
+    /// freeze marker to be read from storage
+    pub(crate) fn filters_frozen(&self) -> bool {
+        self.frozen
+    }
+
+    /// Returns true if the filter restricts entries by size, which requires the size of content
+    /// files to be read from storage
+    pub(crate) fn filters_size(&self) -> bool {
+        self.min_size.is_some() || self.max_size.is_some()
+    }
+
+    /// Returns true if the creation date is within the filter's date range
+    pub(crate) fn matches_created(&self, created: &DateTime<Local>) -> bool {
+        self.created_before.is_none_or(|before| *created < before)
+            && self.created_after.is_none_or(|after| *created > after)
+    }
+
+    /// Returns true if the size is within the filter's size range
+    pub(crate) fn matches_size(&self, size: u64) -> bool {
+        self.min_size.is_none_or(|min| size >= min) && self.max_size.is_none_or(|max| size <= max)
+    }
+}
+
+impl VersionPage {
+    /// Creates a page that contains every version, in ascending order
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Order the versions from newest to oldest
+    pub fn with_reverse(mut self, reverse: bool) -> Self {
+        self.reverse = reverse;
+        self
+    }
+
+    /// Skip the first `offset` versions, in the page's order
+    pub fn with_offset(mut self, offset: usize) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Include at most `limit` versions
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Collects the items that are on the page from an iterator that is in ascending version
+    /// order
+    pub(crate) fn select<T>(&self, iter: impl DoubleEndedIterator<Item = T>) -> Vec<T> {
+        let limit = self.limit.unwrap_or(usize::MAX);
+
+        if self.reverse {
+            iter.rev().skip(self.offset).take(limit).collect()
+        } else {
+            iter.skip(self.offset).take(limit).collect()
+        }
+    }
+}
+
+impl ObjectDiff {
+    /// Returns the path that differs
+    pub fn path(&self) -> &Rc<LogicalPath> {
+        match self {
+            ObjectDiff::OnlyInA(path) => path,
+            ObjectDiff::OnlyInB(path) => path,
+            ObjectDiff::ContentDiffers(path) => path,
+        }
+    }
+}
+
 impl<'a, T: Display> Display for PrettyPrintSet<'a, T> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         f.write_char('[')?;
@@ -1354,23 +2384,6 @@ impl<'a, T: Display> Display for PrettyPrintSet<'a, T> {
     }
 }
 
-/// Joins to strings using the file system separator
-fn join(use_backslashes: bool, parent: &str, child: &str) -> String {
-    if use_backslashes {
-        format!("{}\\{}", parent, child)
-    } else {
-        format!("{}/{}", parent, child)
-    }
-}
-
-/// Changes `/` to `\` on Windows
-fn convert_path_separator(use_backslashes: bool, path: String) -> String {
-    if use_backslashes && path::MAIN_SEPARATOR == '\\' {
-        return path.replace('/', "\\");
-    }
-    path
-}
-
 #[cfg(test)]
 mod tests {
     use std::convert::{TryFrom, TryInto};
@@ -1,8 +1,10 @@
 use std::borrow::Cow;
 use std::io::ErrorKind;
-use std::path::Path;
-use std::{fs, io, path};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::{fs, io, path, thread};
 
+use log::info;
 use walkdir::WalkDir;
 
 use crate::ocfl::error::Result;
@@ -10,6 +12,11 @@ use crate::ocfl::error::Result;
 /// Indicates if the system path separator is `\`
 pub const BACKSLASH_SEPARATOR: bool = path::MAIN_SEPARATOR == '\\';
 
+/// The prefix of Windows extended-length paths
+const EXTENDED_LENGTH_PREFIX: &str = r"\\?\";
+/// The prefix of Windows extended-length UNC paths
+const EXTENDED_LENGTH_UNC_PREFIX: &str = r"\\?\UNC\";
+
 /// Walks up the directory hierarchy deleting directories until it finds a non-empty directory.
 pub fn clean_dirs_up(start_dir: impl AsRef<Path>) -> Result<()> {
     let mut current = start_dir.as_ref();
@@ -45,13 +52,79 @@ pub fn remove_file_ignore_not_found(path: impl AsRef<Path>) -> io::Result<()> {
     Ok(())
 }
 
+/// Moves the file or directory at `src` to `dst`. `fs::rename()` cannot move paths across
+/// filesystems, so when it fails for that reason, `src` is copied to `dst` and then removed.
+pub fn move_path(src: impl AsRef<Path>, dst: impl AsRef<Path>) -> io::Result<()> {
+    move_path_with(src.as_ref(), dst.as_ref(), |src, dst| fs::rename(src, dst))
+}
+
+fn move_path_with(
+    src: &Path,
+    dst: &Path,
+    rename: impl FnOnce(&Path, &Path) -> io::Result<()>,
+) -> io::Result<()> {
+    match rename(src, dst) {
+        Err(e) if e.kind() == ErrorKind::CrossesDevices => {
+            info!(
+                "Copying {} to {} because it cannot be renamed across filesystems",
+                src.to_string_lossy(),
+                dst.to_string_lossy()
+            );
+
+            let dst_existed = dst.symlink_metadata().is_ok();
+
+            if let Err(e) = copy_path(src, dst) {
+                if !dst_existed {
+                    let _ = remove_path(dst);
+                }
+                return Err(e);
+            }
+
+            remove_path(src)
+        }
+        result => result,
+    }
+}
+
+/// Recursively copies the file or directory at `src` to `dst`
+fn copy_path(src: &Path, dst: &Path) -> io::Result<()> {
+    if !src.is_dir() {
+        return fs::copy(src, dst).map(|_| ());
+    }
+
+    for entry in WalkDir::new(src) {
+        let entry = entry?;
+        let target = match entry.path().strip_prefix(src) {
+            Ok(relative) if relative.as_os_str().is_empty() => dst.to_path_buf(),
+            Ok(relative) => dst.join(relative),
+            Err(_) => continue,
+        };
+
+        if entry.file_type().is_dir() {
+            fs::create_dir_all(&target)?;
+        } else {
+            fs::copy(entry.path(), &target)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn remove_path(path: &Path) -> io::Result<()> {
+    if path.symlink_metadata()?.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
 /// Returns true if the specified directory does not contain any files
 pub fn dir_is_empty(dir: impl AsRef<Path>) -> Result<bool> {
     Ok(fs::read_dir(dir)?.next().is_none())
 }
 
 /// Changes `/` to `\` on Windows
-pub fn convert_forwardslash_to_back(path: &str) -> Cow<str> {
+pub fn convert_forwardslash_to_back(path: &str) -> Cow<'_, str> {
     if BACKSLASH_SEPARATOR && path.contains('/') {
         return Cow::Owned(path.replace('/', "\\"));
     }
@@ -59,13 +132,86 @@ pub fn convert_forwardslash_to_back(path: &str) -> Cow<str> {
 }
 
 /// Changes `\\` to `/` on Windows
-pub fn convert_backslash_to_forward(path: &str) -> Cow<str> {
+pub fn convert_backslash_to_forward(path: &str) -> Cow<'_, str> {
     if BACKSLASH_SEPARATOR && path.contains('\\') {
         return Cow::Owned(path.replace('\\', "/"));
     }
     path.into()
 }
 
+/// Converts a `/` separated path, such as an object root or content path, into a native path.
+/// Paths must be converted before they are joined to an extended-length path on Windows
+/// because Windows does not normalize the separators in extended-length paths.
+pub fn native_path(path: &str) -> PathBuf {
+    PathBuf::from(convert_forwardslash_to_back(path).as_ref())
+}
+
+/// Joins two path strings with `\` if `use_backslashes` is `true`, and `/` otherwise
+pub fn join_with_separator(use_backslashes: bool, parent: &str, child: &str) -> String {
+    if use_backslashes {
+        format!("{}\\{}", parent, child)
+    } else {
+        format!("{}/{}", parent, child)
+    }
+}
+
+/// Changes `/` to `\` on Windows, if `use_backslashes` is `true`
+pub fn convert_path_separator(use_backslashes: bool, path: String) -> String {
+    if use_backslashes {
+        return convert_forwardslash_to_back(&path).into_owned();
+    }
+    path
+}
+
+/// Converts the path to a Windows extended-length path, `\\?\C:\...`, so that it may exceed
+/// `MAX_PATH`. Extended-length paths must be absolute and may not contain `.` or `..`, so the
+/// path is resolved against the current directory and normalized. On other platforms, the path
+/// is returned unchanged.
+pub fn extended_length_path(path: impl AsRef<Path>) -> PathBuf {
+    let path = path.as_ref();
+
+    if !BACKSLASH_SEPARATOR || path.to_string_lossy().starts_with(EXTENDED_LENGTH_PREFIX) {
+        return path.to_path_buf();
+    }
+
+    let absolute = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        match std::env::current_dir() {
+            Ok(current) => current.join(path),
+            Err(_) => return path.to_path_buf(),
+        }
+    };
+
+    let mut normalized = PathBuf::new();
+    for component in absolute.components() {
+        match component {
+            path::Component::CurDir => (),
+            path::Component::ParentDir => {
+                normalized.pop();
+            }
+            component => normalized.push(component.as_os_str()),
+        }
+    }
+
+    let normalized = normalized.to_string_lossy();
+    match normalized.strip_prefix(r"\\") {
+        Some(unc) => PathBuf::from(format!("{}{}", EXTENDED_LENGTH_UNC_PREFIX, unc)),
+        None => PathBuf::from(format!("{}{}", EXTENDED_LENGTH_PREFIX, normalized)),
+    }
+}
+
+/// Removes the Windows extended-length prefix from a path so that it may be displayed
+pub fn strip_extended_length_prefix(path: &str) -> Cow<'_, str> {
+    if let Some(unc) = path.strip_prefix(EXTENDED_LENGTH_UNC_PREFIX) {
+        Cow::Owned(format!(r"\\{}", unc))
+    } else if let Some(stripped) = path.strip_prefix(EXTENDED_LENGTH_PREFIX) {
+        stripped.into()
+    } else {
+        path.into()
+    }
+}
+
 /// Trims all trailing slashes from the path
 pub fn trim_trailing_slashes(path: &str) -> &str {
     path.trim_end_matches('/')
@@ -80,3 +226,196 @@ pub fn trim_leading_slashes(path: &str) -> &str {
 pub fn trim_slashes(path: &str) -> &str {
     trim_trailing_slashes(trim_leading_slashes(path))
 }
+
+/// A bounded pool of worker threads that parallelizable work, such as fixity checking or
+/// validating many objects, is distributed across. The workers are scoped threads that only live
+/// for the duration of a single call to `map()`, so the work may borrow from the caller.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct ThreadPool {
+    threads: usize,
+}
+
+impl ThreadPool {
+    /// Creates a pool that uses up to `threads` workers. A pool always has at least one worker.
+    pub fn new(threads: usize) -> Self {
+        Self {
+            threads: threads.max(1),
+        }
+    }
+
+    /// The maximum number of workers the pool uses
+    pub fn threads(&self) -> usize {
+        self.threads
+    }
+
+    /// Returns a pool that uses no more than `threads` workers
+    pub fn limit(self, threads: usize) -> Self {
+        Self::new(self.threads.min(threads))
+    }
+
+    /// Applies `f` to every item, using up to `threads` workers, and returns the outcomes in the
+    /// same order as the items. An item's outcome is `None` if `stop` returned `true` before
+    /// the item was started.
+    pub fn map<T, R, S, F>(&self, items: &[T], stop: S, f: F) -> Vec<Option<R>>
+    where
+        T: Sync,
+        R: Send,
+        S: Fn() -> bool + Sync,
+        F: Fn(&T) -> R + Sync,
+    {
+        let workers = self.threads.min(items.len());
+        let next = AtomicUsize::new(0);
+        let mut outcomes: Vec<Option<R>> = (0..items.len()).map(|_| None).collect();
+
+        let worker = || {
+            let mut completed = Vec::new();
+
+            while !stop() {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                let item = match items.get(index) {
+                    Some(item) => item,
+                    None => break,
+                };
+
+                completed.push((index, f(item)));
+            }
+
+            completed
+        };
+
+        if workers == 1 {
+            for (index, outcome) in worker() {
+                outcomes[index] = Some(outcome);
+            }
+            return outcomes;
+        }
+
+        thread::scope(|scope| {
+            let handles: Vec<_> = (0..workers).map(|_| scope.spawn(worker)).collect();
+
+            for handle in handles {
+                let completed = handle
+                    .join()
+                    .unwrap_or_else(|e| std::panic::resume_unwind(e));
+                for (index, outcome) in completed {
+                    outcomes[index] = Some(outcome);
+                }
+            }
+        });
+
+        outcomes
+    }
+}
+
+impl Default for ThreadPool {
+    /// Creates a pool with a worker for each of the machine's available cores
+    fn default() -> Self {
+        Self::new(
+            thread::available_parallelism()
+                .map(|n| n.get())
+                .unwrap_or(1),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::io::{self, ErrorKind};
+    use std::path::Path;
+
+    use assert_fs::prelude::*;
+    use assert_fs::TempDir;
+
+    use super::{move_path_with, ThreadPool};
+
+    fn cross_device(_src: &Path, _dst: &Path) -> io::Result<()> {
+        Err(io::Error::from(ErrorKind::CrossesDevices))
+    }
+
+    #[test]
+    fn copy_and_delete_file_when_rename_crosses_devices() {
+        let temp = TempDir::new().unwrap();
+        temp.child("src.txt").write_str("content").unwrap();
+        let dst = temp.child("nested").child("dst.txt");
+        fs::create_dir_all(dst.path().parent().unwrap()).unwrap();
+
+        move_path_with(temp.child("src.txt").path(), dst.path(), cross_device).unwrap();
+
+        temp.child("src.txt").assert(predicates::path::missing());
+        dst.assert("content");
+    }
+
+    #[test]
+    fn copy_and_delete_dir_when_rename_crosses_devices() {
+        let temp = TempDir::new().unwrap();
+        temp.child("v2/inventory.json").write_str("{}").unwrap();
+        temp.child("v2/content/a/b.txt").write_str("b").unwrap();
+        temp.child("v2/content/empty").create_dir_all().unwrap();
+        let dst = temp.child("object/v2");
+        temp.child("object").create_dir_all().unwrap();
+
+        move_path_with(temp.child("v2").path(), dst.path(), cross_device).unwrap();
+
+        temp.child("v2").assert(predicates::path::missing());
+        dst.child("inventory.json").assert("{}");
+        dst.child("content/a/b.txt").assert("b");
+        dst.child("content/empty")
+            .assert(predicates::path::is_dir());
+    }
+
+    #[test]
+    fn remove_partial_copy_when_fallback_fails() {
+        let temp = TempDir::new().unwrap();
+        let dst = temp.child("dst");
+
+        let result = move_path_with(temp.child("missing").path(), dst.path(), cross_device);
+
+        assert!(result.is_err());
+        dst.assert(predicates::path::missing());
+    }
+
+    #[test]
+    fn return_other_rename_errors_without_copying() {
+        let temp = TempDir::new().unwrap();
+        temp.child("src.txt").write_str("content").unwrap();
+        let dst = temp.child("dst.txt");
+
+        let result = move_path_with(temp.child("src.txt").path(), dst.path(), |_, _| {
+            Err(io::Error::from(ErrorKind::PermissionDenied))
+        });
+
+        assert_eq!(ErrorKind::PermissionDenied, result.unwrap_err().kind());
+        temp.child("src.txt").assert("content");
+        dst.assert(predicates::path::missing());
+    }
+
+    #[test]
+    fn thread_pool_returns_outcomes_in_item_order() {
+        let items: Vec<usize> = (0..100).collect();
+
+        let outcomes = ThreadPool::new(4).map(&items, || false, |item| item * 2);
+
+        let expected: Vec<Option<usize>> = items.iter().map(|item| Some(item * 2)).collect();
+        assert_eq!(expected, outcomes);
+    }
+
+    #[test]
+    fn thread_pool_does_not_start_items_after_stopping() {
+        let items = vec![1, 2, 3];
+
+        let outcomes = ThreadPool::new(2).map(&items, || true, |item| *item);
+
+        assert_eq!(vec![None, None, None], outcomes);
+    }
+
+    #[test]
+    fn thread_pool_has_at_least_one_worker() {
+        assert_eq!(1, ThreadPool::new(0).threads());
+        assert_eq!(2, ThreadPool::new(8).limit(2).threads());
+        assert_eq!(
+            1,
+            ThreadPool::new(1).map(&[5], || false, |item| *item).len()
+        );
+    }
+}
@@ -1,32 +1,96 @@
 use std::cell::RefCell;
-use std::collections::{HashMap, HashSet};
+use std::cmp;
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::convert::TryFrom;
+use std::io;
+use std::mem;
+use std::rc::Rc;
 use std::slice::Iter;
 use std::str::FromStr;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::vec::IntoIter;
 
+use ::serde::{Deserialize, Serialize};
+use globset::{GlobBuilder, GlobMatcher};
 use log::info;
 use once_cell::sync::Lazy;
 use regex::Regex;
 use strum_macros::{Display as EnumDisplay, EnumString};
+use unicode_normalization::is_nfc;
 
 use crate::ocfl::consts::*;
 use crate::ocfl::digest::{HexDigest, MultiDigestWriter};
+use crate::ocfl::encryption::{DecryptingWriter, EncryptionKey, EncryptionMarker};
 use crate::ocfl::error::{Result, RocflError};
-use crate::ocfl::inventory::Inventory;
-use crate::ocfl::store::{Listing, OcflLayout, Storage};
+use crate::ocfl::inventory::{Inventory, Version};
+use crate::ocfl::store::{Listing, OcflLayoutLenient, Storage};
 use crate::ocfl::{
-    paths, util, ContentPath, ContentPathVersion, DigestAlgorithm, InventoryPath, PrettyPrintSet,
-    SpecVersion, VersionNum,
+    paths, specs, util, ContentPath, ContentPathVersion, DigestAlgorithm, InventoryPath,
+    LayoutExtensionName, PrettyPrintSet, SpecVersion, ThreadPool, VersionNum,
 };
 
 mod serde;
 
+/// The URL of the OCFL spec that validation codes link to
+const OCFL_1_1_SPEC_URL: &str = "https://ocfl.io/1.1/spec/";
+
+/// The spec section and text of each validation code, keyed on the code
+static SPEC_REFERENCES: Lazy<HashMap<&'static str, (&'static str, &'static str)>> =
+    Lazy::new(|| {
+        specs::OCFL_1_1_VALIDATION_CODES
+            .lines()
+            .skip(1)
+            .filter_map(|line| {
+                let mut parts = line.splitn(3, '\t');
+                match (parts.next(), parts.next(), parts.next()) {
+                    (Some(code), Some(section), Some(description)) => {
+                        Some((code, (section, description)))
+                    }
+                    _ => None,
+                }
+            })
+            .collect()
+    });
+
 static SIDECAR_SPLIT: Lazy<Regex> = Lazy::new(|| Regex::new(r#"[\t ]+"#).unwrap());
 static EMPTY_PATHS: Vec<ContentPath> = vec![];
 
+/// The maximum number of threads that are used to fixity check a single object's content files
+const MAX_FIXITY_WORKERS: usize = 8;
+
+/// The maximum number of paths that are listed in a single path portability or storage
+/// hierarchy problem
+const MAX_REPORTED_PATHS: usize = 10;
+
+/// The widest zero-padded version number whose maximum version, 999999999, fits in a u32
+const MAX_PADDING_WIDTH: u32 = 10;
+
+/// File names that Windows reserves for devices, regardless of extension
+const WINDOWS_RESERVED_NAMES: [&str; 22] = [
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Returns true if the path is a non-empty path that is relative the storage root and does not
+/// traverse outside of it
+fn is_relative_object_root(path: &str) -> bool {
+    !path.is_empty()
+        && !path.starts_with('/')
+        && path
+            .split('/')
+            .all(|part| !part.is_empty() && part != "." && part != "..")
+}
+
+/// Returns true if the path segment is a name that Windows reserves for devices. Windows ignores
+/// everything after the first period, so `con.txt` is also reserved.
+fn is_windows_reserved_name(segment: &str) -> bool {
+    let stem = segment.split('.').next().unwrap_or_default().trim_end();
+    WINDOWS_RESERVED_NAMES
+        .iter()
+        .any(|name| name.eq_ignore_ascii_case(stem))
+}
+
 /// If `object_id` is empty, then an `InvalidValue` error is returned. This does not enforce that
 /// the id is a URI.
 pub fn validate_object_id(object_id: &str) -> Result<()> {
@@ -49,6 +113,19 @@ pub fn validate_digest_algorithm(digest_algorithm: DigestAlgorithm) -> Result<()
     Ok(())
 }
 
+/// If `padding_width` is not 0, to disable zero-padding, or between 2 and 10, then an
+/// `InvalidValue` error is returned. A width of 1 could not fit the leading zero, and wider
+/// versions would exceed the maximum version number.
+pub fn validate_padding_width(padding_width: u32) -> Result<()> {
+    if padding_width == 1 || padding_width > MAX_PADDING_WIDTH {
+        return Err(RocflError::InvalidValue(format!(
+            "The zero-padding width must be 0 or between 2 and {}. Found: {}",
+            MAX_PADDING_WIDTH, padding_width
+        )));
+    }
+    Ok(())
+}
+
 /// If `content_dir` contains `.`, `..`, or `/`, then an `InvalidValue` error is returned.
 pub fn validate_content_dir(content_dir: &str) -> Result<()> {
     if content_dir.eq(".") || content_dir.eq("..") || content_dir.contains('/') {
@@ -71,6 +148,48 @@ pub fn validate_spec_version(object_version: SpecVersion, repo_version: SpecVers
     }
 }
 
+/// Parses and validates the bytes of an `inventory.json` file without a repository. The
+/// inventory is only returned if no errors were identified, and the diagnostics contain every
+/// error and warning that was. Only the inventory itself is validated; its sidecar and the
+/// files it references are not.
+pub fn parse_inventory(bytes: &[u8]) -> (Result<Inventory>, ParseDiagnostics) {
+    match serde::parse(bytes) {
+        ParseResult::Ok(parse_result, inventory) => (Ok(inventory), parse_result.into()),
+        ParseResult::Error(parse_result) => {
+            let diagnostics = ParseDiagnostics::from(parse_result);
+            let message = match diagnostics.errors.first() {
+                Some(error) => format!("Invalid inventory: [{}] {}", error.code, error.text),
+                None => "Invalid inventory".to_string(),
+            };
+            (Err(RocflError::InvalidValue(message)), diagnostics)
+        }
+    }
+}
+
+/// Verifies that the bytes of an `inventory.json` file match the digest that is expected by its
+/// sidecar. An E060 error is added to the diagnostics if they do not.
+pub fn validate_inventory_digest(
+    bytes: &[u8],
+    algorithm: DigestAlgorithm,
+    expected: &HexDigest,
+    diagnostics: &mut ParseDiagnostics,
+) -> Result<()> {
+    let actual = algorithm.hash_hex(&mut &*bytes)?;
+
+    if actual != *expected {
+        diagnostics.error(
+            ProblemLocation::ObjectRoot,
+            ErrorCode::E060,
+            format!(
+                "Inventory does not match expected digest. Expected: {}; Found: {}",
+                expected, actual
+            ),
+        );
+    }
+
+    Ok(())
+}
+
 /// OCFL validation codes for errors: https://ocfl.io/1.0/spec/validation-codes.html
 #[allow(dead_code)]
 #[derive(Debug, EnumDisplay, EnumString, Copy, Clone, Eq, PartialEq)]
@@ -186,6 +305,15 @@ pub enum ErrorCode {
     E110,
     E111,
     E112,
+    /// Not defined by the OCFL spec. Reported in place of `RW005` when strict path validation
+    /// is enabled.
+    RE001,
+    /// Not defined by the OCFL spec. Reported in place of `RW006` when strict path validation
+    /// is enabled.
+    RE002,
+    /// Not defined by the OCFL spec. Reported in place of `RW007` when strict path validation
+    /// is enabled.
+    RE003,
 }
 
 /// OCFL validation codes for warnings: https://ocfl.io/1.0/spec/validation-codes.html
@@ -208,6 +336,70 @@ pub enum WarnCode {
     W014,
     W015,
     W016,
+    /// Not defined by the OCFL spec. A storage path is too long to be accessed on Windows
+    /// by software that does not use extended-length paths.
+    RW001,
+    /// Not defined by the OCFL spec. A content file could not be fixity checked because it is
+    /// in an archival S3 storage class.
+    RW002,
+    /// Not defined by the OCFL spec. A version was created before the version that precedes it.
+    RW003,
+    /// Not defined by the OCFL spec. The storage root does not contain a copy of the OCFL spec,
+    /// or its copy does not match the spec it declares.
+    RW004,
+    /// Not defined by the OCFL spec. A logical or content path contains control characters.
+    RW005,
+    /// Not defined by the OCFL spec. A logical or content path is not in Unicode Normalization
+    /// Form C, so it may not match the same path written on a platform that normalizes paths.
+    RW006,
+    /// Not defined by the OCFL spec. A logical or content path contains a file name that Windows
+    /// reserves for devices, such as `CON` or `LPT1`.
+    RW007,
+    /// Not defined by the OCFL spec. The content files of an encrypted object could not be
+    /// fixity checked because its encryption key is not configured.
+    RW008,
+    /// Not defined by the OCFL spec. An inventory's fixity block uses a digest algorithm that is
+    /// not defined by the OCFL spec, and the object does not declare the
+    /// `0009-digest-algorithms` extension that registers it.
+    RW009,
+}
+
+/// The text of the OCFL spec that defines a validation code
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SpecReference {
+    /// The number and title of the spec section the code is defined in
+    pub section: &'static str,
+    /// The spec sentence that defines the code
+    pub description: &'static str,
+    /// A link to the code's definition in the spec
+    pub url: String,
+}
+
+impl SpecReference {
+    fn lookup(code: &str) -> Option<Self> {
+        SPEC_REFERENCES
+            .get(code)
+            .map(|(section, description)| SpecReference {
+                section,
+                description,
+                url: format!("{}#{}", OCFL_1_1_SPEC_URL, code),
+            })
+    }
+}
+
+impl ErrorCode {
+    /// Returns the OCFL 1.1 spec text that defines the code, if the spec defines it
+    pub fn spec_reference(&self) -> Option<SpecReference> {
+        SpecReference::lookup(&self.to_string())
+    }
+}
+
+impl WarnCode {
+    /// Returns the OCFL 1.1 spec text that defines the code. rocfl's own warnings, which are
+    /// prefixed with `RW`, are not defined by the spec.
+    pub fn spec_reference(&self) -> Option<SpecReference> {
+        SpecReference::lookup(&self.to_string())
+    }
 }
 
 // OCFL validation results for an object or structural element
@@ -262,6 +454,102 @@ pub struct ObjectValidationResult {
     errors: Vec<ValidationError>,
     /// Any warning identified in the object
     warnings: Vec<ValidationWarning>,
+    /// The content paths of the files that had their fixity checked
+    fixity_checked: Vec<String>,
+    /// The object's sampling state after its content files were sampled
+    fixity_sample: Option<FixitySampleRecord>,
+}
+
+/// Describes which of an object's content files should have their fixity checked
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum FixityCheck {
+    /// No content files are fixity checked
+    None,
+    /// Every content file is fixity checked
+    All,
+    /// A subset of the content files are fixity checked
+    Sample(FixitySample),
+}
+
+/// Describes a subset of an object's content files to fixity check.
+///
+/// The content files are ordered by a hash of the seed and their content paths, and then
+/// partitioned into windows that contain `percent` of the files. `round` selects the window to
+/// check, so that successive rounds with the same seed cover different files.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub struct FixitySample {
+    /// The percentage of content files to check, between 1 and 100
+    pub percent: u8,
+    /// The seed used to order the content files
+    pub seed: u64,
+    /// The window of ordered content files to check
+    pub round: u32,
+    /// Whether an object that has a recorded sample with the same percentage continues from where
+    /// that sample left off, using its seed, rather than checking the window that `seed` and
+    /// `round` select. Recorded samples are loaded with `OcflRepo::continue_fixity_samples()`.
+    pub continue_recorded: bool,
+}
+
+/// The state of an object's fixity sampling, which is kept in its validation record so that the
+/// next sampled validation of the object checks files that have not been checked yet
+#[derive(Deserialize, Serialize, Debug, Eq, PartialEq, Clone)]
+pub struct FixitySampleRecord {
+    /// The percentage of content files that were checked
+    pub percent: u8,
+    /// The seed used to order the content files
+    pub seed: u64,
+    /// The round of the most recent sample, which restarts at 0 once every file was checked
+    pub round: u32,
+    /// The sort key of the last file that was checked. The files after it in the seed's order
+    /// have not been checked since every file was last checked.
+    #[serde(default)]
+    pub position: Option<String>,
+}
+
+/// Restricts repository validation to a subset of the repository's objects. The storage root is
+/// always validated, but only the parts of the storage hierarchy that may contain matching
+/// objects are descended into.
+#[derive(Debug, Clone, Default)]
+pub struct ValidationFilter {
+    /// Only objects with storage paths that start with this prefix are validated
+    prefix: Option<String>,
+    /// Only objects with IDs that match this glob are validated
+    id_matcher: Option<GlobMatcher>,
+    /// Objects with these IDs are not validated
+    excluded_ids: HashSet<String>,
+}
+
+/// The progress of a repository validation, from which an interrupted validation may be resumed
+/// with `IncrementalValidator::resume()`. Objects are validated in order of their storage paths,
+/// so only the path of the last object that was validated needs to be recorded, along with the
+/// results that are accumulated across objects.
+#[derive(Deserialize, Serialize, Debug, Default, Clone, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidationCheckpoint {
+    /// The storage root relative path of the last object that was validated
+    last_object_root: Option<String>,
+    /// The IDs of the objects that were validated, so that duplicate objects are still identified
+    object_ids: BTreeSet<String>,
+    /// The codes and descriptions of the errors identified in the storage hierarchy
+    hierarchy_errors: Vec<(String, String)>,
+    /// The codes and descriptions of the warnings identified in the storage hierarchy
+    hierarchy_warnings: Vec<(String, String)>,
+    /// The objects that were found directly under the storage root
+    #[serde(default)]
+    top_level_objects: ObjectPlacement,
+    /// The objects that were found within a directory hierarchy
+    #[serde(default)]
+    nested_objects: ObjectPlacement,
+}
+
+/// The number of objects that were found in one kind of placement in the storage hierarchy, and
+/// the storage paths of the first few of them. Used to identify storage roots that mix top-level
+/// objects and objects in a directory hierarchy (W015).
+#[derive(Deserialize, Serialize, Debug, Default, Clone, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct ObjectPlacement {
+    count: usize,
+    paths: Vec<String>,
 }
 
 #[derive(Debug, Eq, PartialEq, Copy, Clone)]
@@ -272,7 +560,7 @@ pub enum ProblemLocation {
     ObjectVersion(VersionNum),
 }
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug, Eq, PartialEq, Clone)]
 pub struct ValidationError {
     /// Indicates where the problem occurred
     pub location: ProblemLocation,
@@ -292,10 +580,29 @@ pub struct ValidationWarning {
     pub text: String,
 }
 
+/// The errors and warnings identified while parsing an inventory with `parse_inventory()`
+#[derive(Debug, Default)]
+pub struct ParseDiagnostics {
+    /// The id of the object, if it could be parsed
+    pub object_id: Option<String>,
+    /// Any errors identified in the inventory
+    errors: Vec<ValidationError>,
+    /// Any warnings identified in the inventory
+    warnings: Vec<ValidationWarning>,
+}
+
 /// A validator that's able to validate OCFL objects and repositories against the OCFL spec
 pub struct Validator<S: Storage> {
     /// Storage abstraction used to access files in any backend
     storage: S,
+    /// The pool that an object's content files are fixity checked on
+    pool: ThreadPool,
+    /// Indicates if unportable paths are reported as errors rather than warnings
+    strict_paths: AtomicBool,
+    /// The key that the content of encrypted objects is decrypted with before it is fixity checked
+    encryption_key: Option<EncryptionKey>,
+    /// The previous fixity sampling state of objects, keyed on object ID
+    sample_history: Mutex<HashMap<String, FixitySampleRecord>>,
     closed: Arc<AtomicBool>,
 }
 
@@ -314,6 +621,16 @@ pub trait IncrementalValidator: Iterator<Item = Result<ObjectValidationResult>>
     /// The validation results for the repository's hierarchy. This is available _after_ every
     /// object has been validated.
     fn storage_hierarchy_result_mut(&mut self) -> &mut StorageValidationResult;
+
+    /// Returns the validator's progress, from which validation may be resumed if it is
+    /// interrupted. It includes every object that has been returned by `next()`.
+    fn checkpoint(&self) -> ValidationCheckpoint;
+
+    /// Resumes validation from a checkpoint of a previous validation of the same repository. The
+    /// objects that were validated before the checkpoint was taken are skipped, and the storage
+    /// hierarchy results that were accumulated up to the checkpoint are restored. This must be
+    /// called before `next()`.
+    fn resume(&mut self, checkpoint: ValidationCheckpoint);
 }
 
 /// Lazily validates every object in the repository. Each call to `next()` validates another object.
@@ -323,13 +640,174 @@ pub struct IncrementalValidatorImpl<'a, S: Storage> {
     validator: &'a Validator<S>,
     storage: &'a S,
     root_version: Option<SpecVersion>,
-    fixity_check: bool,
+    fixity_check: FixityCheck,
+    filter: ValidationFilter,
     dir_iters: Vec<Dir<'a>>,
     current_iter: Option<Dir<'a>>,
     seen_ids: HashSet<String>,
+    /// The storage path of the last object that was validated
+    last_object_root: Option<String>,
+    /// The storage path of the last object that was validated before validation was resumed
+    resumed_after: Option<String>,
+    /// The extension name declared in the storage root's `ocfl_layout.json`, if there is one
+    layout_extension: Option<String>,
+    top_level_objects: ObjectPlacement,
+    nested_objects: ObjectPlacement,
     closed: Arc<AtomicBool>,
 }
 
+impl From<bool> for FixityCheck {
+    fn from(fixity_check: bool) -> Self {
+        if fixity_check {
+            FixityCheck::All
+        } else {
+            FixityCheck::None
+        }
+    }
+}
+
+impl FixitySample {
+    /// Selects the subset of the paths that should be checked, and returns it with the object's
+    /// sampling state once they are checked. If `continue_recorded` is set and the object was
+    /// previously sampled with the same percentage, then the previous seed is used and the files
+    /// that follow the last file it checked are selected, starting over once every file was
+    /// checked. Otherwise, the window selected by the seed and round is.
+    fn select<'p, T: Clone>(
+        &self,
+        paths: Vec<(&'p ContentPath, T)>,
+        previous: Option<&FixitySampleRecord>,
+    ) -> Result<(Vec<(&'p ContentPath, T)>, FixitySampleRecord)> {
+        let previous =
+            previous.filter(|previous| self.continue_recorded && previous.percent == self.percent);
+        let seed = previous.map_or(self.seed, |previous| previous.seed);
+
+        let mut keyed = Vec::with_capacity(paths.len());
+        for (path, value) in paths {
+            let key =
+                DigestAlgorithm::Sha256.hash_hex(&mut format!("{}:{}", seed, path).as_bytes())?;
+            keyed.push((key, path, value));
+        }
+        keyed.sort_unstable_by(|a, b| a.0.cmp(&b.0).then_with(|| a.1.cmp(b.1)));
+
+        let total = keyed.len();
+        let count = (total * self.percent.clamp(1, 100) as usize).div_ceil(100);
+
+        let (selected, round): (Vec<_>, u32) = match previous {
+            Some(previous) => {
+                let start = previous.position.as_deref().map_or(0, |position| {
+                    keyed.partition_point(|(key, _, _)| key.as_ref() <= position)
+                });
+
+                if start == total {
+                    // Every file was checked, so start over
+                    (keyed.into_iter().take(count).collect(), 0)
+                } else {
+                    (
+                        keyed.into_iter().skip(start).take(count).collect(),
+                        previous.round + 1,
+                    )
+                }
+            }
+            None if total == 0 => (Vec::new(), self.round),
+            None => {
+                let start = (self.round as usize * count) % total;
+                let selected = keyed.into_iter().cycle().skip(start).take(count);
+                (selected.collect(), self.round)
+            }
+        };
+
+        let record = FixitySampleRecord {
+            percent: self.percent,
+            seed,
+            round,
+            position: selected.last().map(|(key, _, _)| key.to_string()),
+        };
+
+        Ok((
+            selected
+                .into_iter()
+                .map(|(_, path, value)| (path, value))
+                .collect(),
+            record,
+        ))
+    }
+}
+
+impl ValidationFilter {
+    /// Creates a filter that matches every object
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only validate objects with storage paths, relative the storage root, that start with
+    /// the prefix
+    pub fn with_prefix(mut self, prefix: &str) -> Self {
+        let prefix = util::convert_backslash_to_forward(prefix);
+        let prefix = prefix.trim_start_matches('/');
+        self.prefix = if prefix.is_empty() {
+            None
+        } else {
+            Some(prefix.to_string())
+        };
+        self
+    }
+
+    /// Only validate objects with IDs that match the glob
+    pub fn with_id_glob(mut self, glob: &str) -> Result<Self> {
+        self.id_matcher = Some(
+            GlobBuilder::new(glob)
+                .backslash_escape(true)
+                .build()?
+                .compile_matcher(),
+        );
+        Ok(self)
+    }
+
+    /// Do not validate objects with these IDs
+    pub fn with_excluded_ids(mut self, object_ids: impl IntoIterator<Item = String>) -> Self {
+        self.excluded_ids.extend(object_ids);
+        self
+    }
+
+    /// Returns true if objects' IDs must be read to determine if they match the filter
+    fn matches_ids(&self) -> bool {
+        self.id_matcher.is_some() || !self.excluded_ids.is_empty()
+    }
+
+    /// Returns true if the directory at the storage path may contain matching objects
+    fn may_contain(&self, path: &str) -> bool {
+        match &self.prefix {
+            Some(prefix) => {
+                path.starts_with(prefix.as_str()) || prefix.starts_with(&format!("{}/", path))
+            }
+            None => true,
+        }
+    }
+
+    /// Returns true if an object at the storage path is within the prefix
+    fn includes_path(&self, path: &str) -> bool {
+        match &self.prefix {
+            Some(prefix) => path.starts_with(prefix.as_str()),
+            None => true,
+        }
+    }
+
+    /// Returns true if the object ID matches the glob and is not excluded. If the ID is unknown,
+    /// then the object is included so that objects with unreadable inventories are not hidden.
+    fn includes_id(&self, id: Option<&str>) -> bool {
+        match id {
+            Some(id) => {
+                !self.excluded_ids.contains(id)
+                    && self
+                        .id_matcher
+                        .as_ref()
+                        .is_none_or(|matcher| matcher.is_match(id))
+            }
+            None => true,
+        }
+    }
+}
+
 /// Wraps a directory iterator with the path to the directory
 struct Dir<'a> {
     /// Path to the directory that was listed
@@ -338,6 +816,12 @@ struct Dir<'a> {
     iter: IntoIter<Listing<'a>>,
 }
 
+/// The ID of an object, as read from its inventory without validation
+#[derive(Deserialize)]
+struct InventoryId {
+    id: String,
+}
+
 /// The result of deserializing an inventory
 #[derive(Debug)]
 #[allow(clippy::large_enum_variant)]
@@ -368,6 +852,21 @@ struct ContentPathsIter<'a> {
     path_map: &'a HashMap<VersionNum, Vec<ContentPath>>,
 }
 
+/// A content file to fixity check, along with every digest it is expected to have
+struct FixityJob {
+    path: String,
+    full_path: String,
+    expectations: HashMap<DigestAlgorithm, HexDigest>,
+}
+
+enum FixityOutcome {
+    Digests(HashMap<DigestAlgorithm, HexDigest>),
+    Archived,
+    /// The content of an encrypted object could not be decrypted and authenticated
+    Undecryptable(String),
+    Failed(RocflError),
+}
+
 impl StorageValidationResult {
     pub fn new() -> Self {
         Self {
@@ -421,6 +920,54 @@ impl Default for StorageValidationResult {
     }
 }
 
+impl ValidationResult for ParseDiagnostics {
+    /// True if any errors were identified
+    fn has_errors(&self) -> bool {
+        !self.errors.is_empty()
+    }
+
+    /// True if any warnings were identified
+    fn has_warnings(&self) -> bool {
+        !self.warnings.is_empty()
+    }
+
+    fn errors(&self) -> &[ValidationError] {
+        &self.errors
+    }
+
+    fn warnings(&self) -> &[ValidationWarning] {
+        &self.warnings
+    }
+
+    fn errors_mut(&mut self) -> &mut Vec<ValidationError> {
+        &mut self.errors
+    }
+
+    fn warnings_mut(&mut self) -> &mut Vec<ValidationWarning> {
+        &mut self.warnings
+    }
+
+    fn error(&mut self, location: ProblemLocation, code: ErrorCode, message: String) {
+        self.errors
+            .push(ValidationError::new(location, code, message));
+    }
+
+    fn warn(&mut self, location: ProblemLocation, code: WarnCode, message: String) {
+        self.warnings
+            .push(ValidationWarning::new(location, code, message));
+    }
+}
+
+impl From<ParseValidationResult> for ParseDiagnostics {
+    fn from(result: ParseValidationResult) -> Self {
+        Self {
+            object_id: result.object_id.take(),
+            errors: result.errors.take(),
+            warnings: result.warnings.take(),
+        }
+    }
+}
+
 impl ObjectValidationResult {
     pub fn new(object_id: Option<&str>, storage_path: String) -> Self {
         Self {
@@ -428,9 +975,22 @@ impl ObjectValidationResult {
             storage_path,
             errors: Vec::new(),
             warnings: Vec::new(),
+            fixity_checked: Vec::new(),
+            fixity_sample: None,
         }
     }
 
+    /// The content paths of the files that had their fixity checked, in the order they were
+    /// checked
+    pub fn fixity_checked(&self) -> &[String] {
+        &self.fixity_checked
+    }
+
+    /// The object's sampling state, if a sample of its content files was fixity checked
+    pub fn fixity_sample(&self) -> Option<&FixitySampleRecord> {
+        self.fixity_sample.as_ref()
+    }
+
     fn object_id(&mut self, object_id: &str) {
         if self.object_id.is_none() {
             self.object_id = Some(object_id.to_string());
@@ -529,18 +1089,47 @@ impl<S: Storage> Validator<S> {
     pub fn new(storage: S) -> Self {
         Self {
             storage,
+            pool: ThreadPool::default().limit(MAX_FIXITY_WORKERS),
+            strict_paths: AtomicBool::new(false),
+            encryption_key: None,
+            sample_history: Mutex::new(HashMap::new()),
             closed: Arc::new(AtomicBool::new(false)),
         }
     }
 
-    /// Validates an object at a specific location relative the repository root. if `fixity_check`
-    /// is false, then the digests of the object's content files will not be validated.
+    /// Sets whether paths that contain control characters, are not NFC normalized, or contain
+    /// Windows reserved names are reported as errors rather than warnings
+    pub fn set_strict_paths(&self, strict: bool) {
+        self.strict_paths.store(strict, Ordering::Release);
+    }
+
+    /// Sets the fixity sampling state of objects, keyed on object ID. When an object with a state
+    /// is sampled with the same percentage, the files that its previous samples did not check
+    /// are checked, rather than the files selected by the sample's seed and round.
+    pub fn set_sample_history(&self, history: HashMap<String, FixitySampleRecord>) {
+        *self.sample_history.lock().unwrap() = history;
+    }
+
+    /// Sets the pool that content files are fixity checked on. By default, up to
+    /// `MAX_FIXITY_WORKERS` threads are used.
+    pub fn set_thread_pool(&mut self, pool: ThreadPool) {
+        self.pool = pool;
+    }
+
+    /// Sets the key that the content files of encrypted objects are decrypted with before their
+    /// digests are checked. Without a key, the content of encrypted objects is not fixity checked.
+    pub fn set_encryption_key(&mut self, key: EncryptionKey) {
+        self.encryption_key = Some(key);
+    }
+
+    /// Validates an object at a specific location relative the repository root. `fixity_check`
+    /// determines which of the object's content files have their digests validated.
     pub fn validate_object(
         &self,
         object_id: Option<&str>,
         object_root: &str,
         root_version: Option<SpecVersion>,
-        fixity_check: bool,
+        fixity_check: FixityCheck,
     ) -> Result<ObjectValidationResult> {
         info!("Validating object at {}", object_root);
 
@@ -580,7 +1169,11 @@ impl<S: Storage> Validator<S> {
 
         // If the root inventory is not valid, then we don't have a fixed point to use to validate
         // anything else in the object.
-        if !result.has_errors() {
+        let root_inventory_valid = !result.has_errors();
+
+        self.validate_version_dir_padding(&root_files, &inventory, &mut result);
+
+        if root_inventory_valid {
             self.validate_object_root_contents(
                 object_root,
                 &root_files,
@@ -596,6 +1189,9 @@ impl<S: Storage> Validator<S> {
 
                 let content_files =
                     self.find_all_content_files(object_root, &inventory, &mut result)?;
+                self.validate_storage_path_lengths(object_root, &content_files, &mut result);
+                self.validate_path_portability(&inventory, &mut result);
+                self.validate_version_timeline(&inventory, &mut result);
                 self.validate_manifest(
                     &inventory,
                     &content_files,
@@ -640,12 +1236,13 @@ impl<S: Storage> Validator<S> {
                     }
                 }
 
-                if fixity_check {
+                if fixity_check != FixityCheck::None {
                     self.fixity_check(
                         object_root,
                         &content_files,
                         &inventory,
                         &inventories,
+                        fixity_check,
                         &mut result,
                     )?;
                 }
@@ -655,17 +1252,151 @@ impl<S: Storage> Validator<S> {
         Ok(result)
     }
 
+    /// Validates an object's staged version before it is committed. `committed_head` is the
+    /// object's current HEAD version in the main repository, or `None` if the object is new.
+    ///
+    /// The staged inventory must be schema-valid, its HEAD must be the next version in sequence,
+    /// and every content file added in the staged version must exist and match its manifest
+    /// digest. Content files from previous versions are not checked because they are not in
+    /// staging.
+    pub fn validate_staged_version(
+        &self,
+        inventory: &Inventory,
+        committed_head: Option<VersionNum>,
+    ) -> Result<ObjectValidationResult> {
+        info!("Validating staged version of object {}", inventory.id);
+
+        let mut result =
+            ObjectValidationResult::new(Some(&inventory.id), inventory.object_root.clone());
+
+        let bytes = serde_json::to_vec(inventory)?;
+
+        match serde::parse(&bytes) {
+            ParseResult::Ok(parse_result, _) => result.add_parse_result(None, parse_result),
+            ParseResult::Error(parse_result) => result.add_parse_result(None, parse_result),
+        }
+
+        let head = inventory.head;
+
+        match committed_head {
+            Some(committed_head) => {
+                let expected = committed_head.next()?;
+                if head != expected {
+                    result.error(
+                        ProblemLocation::ObjectVersion(head),
+                        ErrorCode::E010,
+                        format!(
+                            "Staged version must be the next version after {}. Expected: {}; Found: {}",
+                            committed_head, expected, head
+                        ),
+                    );
+                } else if head.width != expected.width {
+                    result.error(
+                        ProblemLocation::ObjectVersion(head),
+                        ErrorCode::E013,
+                        format!(
+                            "Staged version must use the same zero-padding as existing versions. Expected: {}; Found: {}",
+                            expected, head
+                        ),
+                    );
+                }
+            }
+            None => {
+                if head.number != 1 {
+                    result.error(
+                        ProblemLocation::ObjectVersion(head),
+                        ErrorCode::E010,
+                        format!(
+                            "Staged version of a new object must be version 1. Found: {}",
+                            head
+                        ),
+                    );
+                }
+            }
+        }
+
+        let prefix = paths::join(&head.to_string(), inventory.defaulted_content_dir());
+        let content_root = paths::join(&inventory.object_root, &prefix);
+
+        let content_files: HashSet<String> = self
+            .storage
+            .list(&content_root, true)?
+            .iter()
+            .filter(|listing| matches!(listing, Listing::File(_)))
+            .map(|listing| paths::join(&prefix, listing.path()))
+            .collect();
+
+        let mut staged_paths: Vec<(&Rc<ContentPath>, &Rc<HexDigest>)> = inventory
+            .manifest()
+            .iter()
+            .filter(|(path, _)| path.version == ContentPathVersion::VersionNum(head))
+            .collect();
+
+        // Sort the paths so that the errors are reported in a deterministic order
+        staged_paths.sort_by(|a, b| a.0.cmp(b.0));
+
+        for (path, expected) in staged_paths {
+            if self.is_closed() {
+                info!("Terminating validation of object {}", inventory.id);
+                break;
+            }
+
+            if !content_files.contains(path.as_str()) {
+                result.error(
+                    ProblemLocation::ObjectVersion(head),
+                    ErrorCode::E092,
+                    format!(
+                        "Inventory manifest references a file that does not exist in staging: {}",
+                        path
+                    ),
+                );
+                continue;
+            }
+
+            info!("Checking fixity of {}", path);
+            result.fixity_checked.push(path.to_string());
+
+            let mut digester = inventory.digest_algorithm.writer(std::io::sink());
+            self.storage.read(
+                &paths::join(&inventory.object_root, path.as_str()),
+                &mut digester,
+            )?;
+            let actual = digester.finalize_hex();
+
+            if actual != **expected {
+                result.error(
+                    ProblemLocation::ObjectVersion(head),
+                    ErrorCode::E092,
+                    format!(
+                        "Staged content file {} failed {} fixity check. Expected: {}; Found: {}",
+                        path, inventory.digest_algorithm, expected, actual
+                    ),
+                );
+            }
+        }
+
+        Ok(result)
+    }
+
     /// Validates the structure of an OCFL repository as well as all of the objects in the repository
-    /// When `fixity_check` is `false`, then the digests of object content files are not validated.
+    /// `fixity_check` determines which object content files have their digests validated.
     ///
     /// The storage root is validated immediately, and an incremental validator is returned that
     /// is used to lazily validate the rest of the repository.
-    pub fn validate_repo(&self, fixity_check: bool) -> Result<IncrementalValidatorImpl<S>> {
+    pub fn validate_repo(
+        &self,
+        fixity_check: FixityCheck,
+        filter: ValidationFilter,
+    ) -> Result<IncrementalValidatorImpl<'_, S>> {
         let mut root_result = StorageValidationResult::new();
         let files = self.storage.list("", false)?;
 
         let root_version = self.validate_root_namaste(&files, &mut root_result);
 
+        if let Some(root_version) = root_version {
+            self.validate_spec_copy(&files, root_version, &mut root_result);
+        }
+
         if files.contains(&Listing::dir(EXTENSIONS_DIR)) {
             let ext_files = self.storage.list(EXTENSIONS_DIR, false)?;
             self.validate_extension_contents(
@@ -675,7 +1406,7 @@ impl<S: Storage> Validator<S> {
             )?;
         }
 
-        self.validate_ocfl_layout(&files, &mut root_result);
+        let layout_extension = self.validate_ocfl_layout(&files, &mut root_result);
 
         // remove all files in the root as they are allowed
         let files: Vec<Listing> = files
@@ -683,16 +1414,15 @@ impl<S: Storage> Validator<S> {
             .filter(|file| !matches!(file, Listing::File(_)))
             .collect();
 
-        // TODO W015 should either be direct children or hierarchy; not both
-
         Ok(IncrementalValidatorImpl::new(
             root_result,
             self,
             &self.storage,
             root_version,
             fixity_check,
+            filter,
+            layout_extension,
             files,
-            self.closed.clone(),
         ))
     }
 
@@ -750,14 +1480,68 @@ impl<S: Storage> Validator<S> {
         version
     }
 
-    fn validate_ocfl_layout(&self, files: &[Listing], result: &mut StorageValidationResult) {
-        if files.contains(&Listing::dir(OCFL_LAYOUT_FILE)) {
+    fn validate_spec_copy(
+        &self,
+        files: &[Listing],
+        version: SpecVersion,
+        result: &mut StorageValidationResult,
+    ) {
+        let declarations = files
+            .iter()
+            .filter(|file| {
+                matches!(file, Listing::File(path)
+                    if SpecVersion::try_from_root_namaste_name(path.as_ref()).is_ok())
+            })
+            .count();
+
+        // Which spec should be copied is ambiguous when there are multiple declarations (E076)
+        if declarations > 1 {
+            return;
+        }
+
+        let filename = version.spec_filename();
+
+        if !files.contains(&Listing::file(filename)) {
+            result.warn(
+                ProblemLocation::StorageRoot,
+                WarnCode::RW004,
+                format!(
+                    "Storage root does not contain a copy of the OCFL spec: {}",
+                    filename
+                ),
+            );
+            return;
+        }
+
+        let mut bytes: Vec<u8> = Vec::new();
+        if self.storage.read(filename, &mut bytes).is_err()
+            || bytes != specs::ocfl_spec(version).as_bytes()
+        {
+            result.warn(
+                ProblemLocation::StorageRoot,
+                WarnCode::RW004,
+                format!(
+                    "Storage root copy of the OCFL spec does not match OCFL {}: {}",
+                    version.version(),
+                    filename
+                ),
+            );
+        }
+    }
+
+    /// Validates the storage root's `ocfl_layout.json`, if it has one, and returns the name of the
+    /// extension that it declares
+    fn validate_ocfl_layout(
+        &self,
+        files: &[Listing],
+        result: &mut StorageValidationResult,
+    ) -> Option<String> {
+        if files.contains(&Listing::file(OCFL_LAYOUT_FILE)) {
             let mut bytes: Vec<u8> = Vec::new();
             if self.storage.read(OCFL_LAYOUT_FILE, &mut bytes).is_ok() {
-                match serde_json::from_slice::<OcflLayout>(&bytes) {
-                    Ok(_layout) => {
-                        // TODO https://github.com/OCFL/spec/issues/565
-                    }
+                match serde_json::from_slice::<OcflLayoutLenient>(&bytes) {
+                    // TODO https://github.com/OCFL/spec/issues/565
+                    Ok(layout) => return Some(layout.extension().to_string()),
                     Err(_) => {
                         result.error(
                             ProblemLocation::StorageRoot,
@@ -773,6 +1557,8 @@ impl<S: Storage> Validator<S> {
                 );
             }
         }
+
+        None
     }
 
     fn validate_object_namaste(
@@ -1194,26 +1980,161 @@ impl<S: Storage> Validator<S> {
             }
         }
 
-        if let Some(expected_versions) = expected_versions {
-            expected_versions.iter().for_each(|v| {
+        if let Some(expected_versions) = expected_versions {
+            expected_versions.iter().for_each(|v| {
+                result.error(
+                    ProblemLocation::ObjectRoot,
+                    ErrorCode::E010,
+                    format!(
+                        "Object root does not contain version directory '{}'",
+                        v.path()
+                    ),
+                );
+            });
+        }
+
+        let mut ext_files = Vec::new();
+
+        if files.contains(&Listing::dir(EXTENSIONS_DIR)) {
+            let extensions = paths::join(object_root, EXTENSIONS_DIR);
+            ext_files = self.storage.list(&extensions, false)?;
+            self.validate_extension_contents(&ext_files, ProblemLocation::ObjectRoot, result)?;
+        }
+
+        if let Some(inventory) = inventory {
+            self.validate_fixity_algorithms(
+                inventory,
+                ext_files.contains(&Listing::dir(DIGEST_ALGORITHMS_EXTENSION)),
+                result,
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Warns about fixity algorithms that are not defined by the OCFL spec. Algorithms that are
+    /// registered by the `0009-digest-algorithms` extension are permitted when the object
+    /// declares the extension.
+    fn validate_fixity_algorithms(
+        &self,
+        inventory: &Inventory,
+        extension_declared: bool,
+        result: &mut ObjectValidationResult,
+    ) {
+        for algorithm in inventory.fixity_algorithms() {
+            match DigestAlgorithm::from_str(algorithm) {
+                Ok(known) if !known.is_extension_algorithm() => (),
+                Ok(_) if extension_declared => (),
+                Ok(_) => result.warn(
+                    ProblemLocation::ObjectRoot,
+                    WarnCode::RW009,
+                    format!(
+                        "Inventory fixity block uses digest algorithm '{}', which is registered \
+                        by the {} extension, but the object does not declare the extension",
+                        algorithm, DIGEST_ALGORITHMS_EXTENSION
+                    ),
+                ),
+                Err(_) => result.warn(
+                    ProblemLocation::ObjectRoot,
+                    WarnCode::RW009,
+                    format!(
+                        "Inventory fixity block uses digest algorithm '{}', which is not defined \
+                        by the OCFL spec or the {} extension",
+                        algorithm, DIGEST_ALGORITHMS_EXTENSION
+                    ),
+                ),
+            }
+        }
+    }
+
+    /// Validates that the object's version directories use a single zero-padding scheme, and
+    /// that it is the same scheme as the root inventory's version keys. This does not depend on
+    /// the root inventory being valid, so that objects assembled by other tools are checked even
+    /// when their inventories cannot be used.
+    fn validate_version_dir_padding(
+        &self,
+        files: &[Listing],
+        inventory: &Option<Inventory>,
+        result: &mut ObjectValidationResult,
+    ) {
+        let version_dirs: Vec<(&str, VersionNum)> = files
+            .iter()
+            .filter_map(|entry| match entry {
+                Listing::Directory(path) => VersionNum::try_from(path.as_ref())
+                    .ok()
+                    .map(|version_num| (path.as_ref(), version_num)),
+                _ => None,
+            })
+            .collect();
+
+        let width = match version_dirs.iter().map(|(_, num)| num.width).max() {
+            Some(width) => width,
+            None => return,
+        };
+
+        let mut inconsistent = Vec::new();
+
+        for (name, version_num) in &version_dirs {
+            if version_num.width == width {
+                continue;
+            }
+            if version_num.width == 0 && name.len() - 1 == width as usize {
+                // The padded numbers ran out of room, so the directory does not start with a zero
                 result.error(
                     ProblemLocation::ObjectRoot,
-                    ErrorCode::E010,
+                    ErrorCode::E011,
                     format!(
-                        "Object root does not contain version directory '{}'",
-                        v.path()
+                        "Zero-padded version directory '{}' does not start with a zero",
+                        name
                     ),
                 );
-            });
+            } else {
+                inconsistent.push(*name);
+            }
         }
 
-        if files.contains(&Listing::dir(EXTENSIONS_DIR)) {
-            let extensions = paths::join(object_root, EXTENSIONS_DIR);
-            let ext_files = self.storage.list(&extensions, false)?;
-            self.validate_extension_contents(&ext_files, ProblemLocation::ObjectRoot, result)?;
+        if !inconsistent.is_empty() {
+            result.error(
+                ProblemLocation::ObjectRoot,
+                ErrorCode::E012,
+                format!(
+                    "Object root contains version directories that are not padded to {} \
+                    characters: {}",
+                    width + 1,
+                    inconsistent.join(", ")
+                ),
+            );
+        } else if let Some(inventory) = inventory {
+            if inventory.head.width != width {
+                result.error(
+                    ProblemLocation::ObjectRoot,
+                    ErrorCode::E012,
+                    format!(
+                        "Version directories are not padded the same as the inventory's version \
+                        keys. Expected: {}; Found: {}",
+                        inventory.head,
+                        version_dirs
+                            .iter()
+                            .find(|(_, num)| num.number == inventory.head.number)
+                            .unwrap_or(&version_dirs[0])
+                            .0
+                    ),
+                );
+            }
         }
 
-        Ok(())
+        if width > 0
+            && !result
+                .warnings()
+                .iter()
+                .any(|warning| warning.code == WarnCode::W001)
+        {
+            result.warn(
+                ProblemLocation::ObjectRoot,
+                WarnCode::W001,
+                "Contains zero-padded version directories".to_string(),
+            );
+        }
     }
 
     fn validate_extension_contents<V: ValidationResult>(
@@ -1252,6 +2173,153 @@ impl<S: Storage> Validator<S> {
         Ok(())
     }
 
+    /// Warns if any version was created before the version that precedes it. The spec does not
+    /// require created timestamps to increase, but when they don't it usually indicates a clock
+    /// or migration problem.
+    fn validate_version_timeline(
+        &self,
+        inventory: &Inventory,
+        result: &mut ObjectValidationResult,
+    ) {
+        let mut previous: Option<(&VersionNum, &Version)> = None;
+
+        for (version_num, version) in &inventory.versions {
+            if let Some((previous_num, previous_version)) = previous {
+                if version.created < previous_version.created {
+                    result.warn(
+                        ProblemLocation::ObjectRoot,
+                        WarnCode::RW003,
+                        format!(
+                            "Inventory version {} was created at {}, which is before version {} \
+                            was created at {}",
+                            version_num,
+                            version.created.to_rfc3339(),
+                            previous_num,
+                            previous_version.created.to_rfc3339()
+                        ),
+                    );
+                }
+            }
+            previous = Some((version_num, version));
+        }
+    }
+
+    /// Warns if any of the object's content files have storage root relative paths that exceed
+    /// the Windows `MAX_PATH` limit. The absolute paths of these files are necessarily longer,
+    /// so the object cannot be read on Windows by software that does not use extended-length
+    /// paths.
+    fn validate_storage_path_lengths(
+        &self,
+        object_root: &str,
+        content_files: &ContentPaths,
+        result: &mut ObjectValidationResult,
+    ) {
+        let object_root = util::convert_backslash_to_forward(object_root);
+
+        let long_paths = content_files
+            .path_map
+            .values()
+            .flatten()
+            .map(|content_path| paths::join(&object_root, content_path.as_ref()))
+            .filter(|path| path.chars().count() > WINDOWS_MAX_PATH)
+            .collect::<Vec<String>>();
+
+        if let Some(longest) = long_paths.iter().max_by_key(|path| path.chars().count()) {
+            result.warn(
+                ProblemLocation::ObjectRoot,
+                WarnCode::RW001,
+                format!(
+                    "Object contains {} storage paths longer than the Windows MAX_PATH limit of \
+                    {} characters. The longest is {} characters: {}",
+                    long_paths.len(),
+                    WINDOWS_MAX_PATH,
+                    longest.chars().count(),
+                    longest
+                ),
+            );
+        }
+    }
+
+    /// Reports logical and content paths that are likely to break when the object is moved
+    /// between platforms: paths that contain control characters, paths that are not in Unicode
+    /// Normalization Form C, and paths that contain names Windows reserves for devices.
+    fn validate_path_portability(
+        &self,
+        inventory: &Inventory,
+        result: &mut ObjectValidationResult,
+    ) {
+        let mut all_paths = BTreeSet::new();
+
+        for (path, _) in inventory.manifest().iter() {
+            all_paths.insert(path.as_str());
+        }
+        for version in inventory.versions.values() {
+            for (path, _) in version.state_iter() {
+                all_paths.insert(path.as_str());
+            }
+        }
+
+        self.report_unportable_paths(
+            all_paths
+                .iter()
+                .filter(|path| path.chars().any(char::is_control)),
+            "contain control characters",
+            (WarnCode::RW005, ErrorCode::RE001),
+            result,
+        );
+        self.report_unportable_paths(
+            all_paths.iter().filter(|path| !is_nfc(path)),
+            "are not in Unicode Normalization Form C",
+            (WarnCode::RW006, ErrorCode::RE002),
+            result,
+        );
+        self.report_unportable_paths(
+            all_paths
+                .iter()
+                .filter(|path| path.split('/').any(is_windows_reserved_name)),
+            "contain names that are reserved on Windows",
+            (WarnCode::RW007, ErrorCode::RE003),
+            result,
+        );
+    }
+
+    fn report_unportable_paths<'a>(
+        &self,
+        paths: impl Iterator<Item = &'a &'a str>,
+        problem: &str,
+        codes: (WarnCode, ErrorCode),
+        result: &mut ObjectValidationResult,
+    ) {
+        let paths: Vec<String> = paths.map(|path| format!("{:?}", path)).collect();
+
+        if paths.is_empty() {
+            return;
+        }
+
+        let mut listed = paths
+            .iter()
+            .take(MAX_REPORTED_PATHS)
+            .map(String::as_str)
+            .collect::<Vec<&str>>()
+            .join(", ");
+        if paths.len() > MAX_REPORTED_PATHS {
+            listed.push_str(", ...");
+        }
+
+        let message = format!(
+            "Object contains {} paths that {}: {}",
+            paths.len(),
+            problem,
+            listed
+        );
+
+        if self.strict_paths.load(Ordering::Acquire) {
+            result.error(ProblemLocation::ObjectRoot, codes.1, message);
+        } else {
+            result.warn(ProblemLocation::ObjectRoot, codes.0, message);
+        }
+    }
+
     fn find_all_content_files(
         &self,
         object_root: &str,
@@ -1767,68 +2835,138 @@ impl<S: Storage> Validator<S> {
         Ok(())
     }
 
+    /// Checks the digests of the object's content files. Files are read and hashed by a bounded
+    /// pool of worker threads, and their outcomes are recorded in the order the files were
+    /// selected, so that the results are the same regardless of which worker checked each file.
     fn fixity_check(
         &self,
         object_root: &str,
         content_files: &ContentPaths,
         root_inventory: &Inventory,
         inventories: &HashMap<DigestAlgorithm, Inventory>,
+        fixity_check: FixityCheck,
         result: &mut ObjectValidationResult,
     ) -> Result<()> {
         let root_algorithm = root_inventory.digest_algorithm;
         let mut fixity = root_inventory.invert_fixity();
 
-        for path in content_files.iter(root_inventory.head) {
-            if self.is_closed() {
-                info!("Terminating validation of object {}", root_inventory.id);
-                break;
+        let mut paths: Vec<(&ContentPath, &Rc<HexDigest>)> = content_files
+            .iter(root_inventory.head)
+            .filter_map(|path| {
+                root_inventory
+                    .digest_for_content_path(path)
+                    .map(|digest| (path, digest))
+            })
+            .collect();
+
+        if let FixityCheck::Sample(sample) = fixity_check {
+            let previous = self
+                .sample_history
+                .lock()
+                .unwrap()
+                .get(&root_inventory.id)
+                .cloned();
+            let (selected, record) = sample.select(paths, previous.as_ref())?;
+            paths = selected;
+            result.fixity_sample = Some(record);
+        }
+
+        let key = match self.content_encryption(object_root, root_inventory) {
+            Ok(key) => key,
+            Err(reason) => {
+                if !paths.is_empty() {
+                    result.warn(
+                        ProblemLocation::ObjectRoot,
+                        WarnCode::RW008,
+                        format!(
+                            "Content files could not be fixity checked because {}",
+                            reason
+                        ),
+                    );
+                }
+                return Ok(());
             }
+        };
 
-            if let Some(digest) = root_inventory.digest_for_content_path(path) {
+        let jobs: Vec<FixityJob> = paths
+            .into_iter()
+            .map(|(path, digest)| {
                 let mut expectations = HashMap::new();
-                expectations.insert(root_algorithm, digest);
+                expectations.insert(root_algorithm, (**digest).clone());
 
                 if let Some(fixity) = &mut fixity {
                     if let Some(fixity_expectations) = fixity.get(path) {
                         for (algorithm, alt_digest) in fixity_expectations {
-                            expectations.insert(*algorithm, alt_digest);
+                            expectations.insert(*algorithm, (**alt_digest).clone());
                         }
                     }
                 }
                 for (algorithm, inventory) in inventories {
                     if let Some(alt_digest) = inventory.digest_for_content_path(path) {
-                        expectations.insert(*algorithm, alt_digest);
+                        expectations.insert(*algorithm, (**alt_digest).clone());
                     }
                 }
 
-                let algorithms: Vec<DigestAlgorithm> = expectations.keys().copied().collect();
-                let mut digester = MultiDigestWriter::new(&algorithms, std::io::sink());
+                FixityJob {
+                    path: path.to_string(),
+                    full_path: paths::join(object_root, path.as_str()),
+                    expectations,
+                }
+            })
+            .collect();
 
-                let full_path = paths::join(object_root, path.as_str());
+        for (job, outcome) in jobs.iter().zip(self.run_fixity_jobs(&jobs, key)) {
+            let digests = match outcome {
+                Some(FixityOutcome::Digests(digests)) => digests,
+                Some(FixityOutcome::Archived) => {
+                    result.warn(
+                        ProblemLocation::ObjectRoot,
+                        WarnCode::RW002,
+                        format!(
+                            "Content file {} could not be fixity checked because it is in an \
+                            archival storage class and must be restored before it can be read",
+                            job.path
+                        ),
+                    );
+                    continue;
+                }
+                Some(FixityOutcome::Undecryptable(e)) => {
+                    result.error(
+                        ProblemLocation::ObjectRoot,
+                        ErrorCode::E092,
+                        format!("Content file {} failed fixity check. {}", job.path, e),
+                    );
+                    continue;
+                }
+                Some(FixityOutcome::Failed(e)) => return Err(e),
+                None => {
+                    info!("Terminating validation of object {}", root_inventory.id);
+                    break;
+                }
+            };
 
-                self.storage.read(&full_path, &mut digester)?;
+            result.fixity_checked.push(job.path.clone());
 
-                for (algorithm, actual) in digester.finalize_hex() {
-                    let expected = expectations.get(&algorithm).unwrap();
-                    if actual != ***expected {
-                        // TODO technically, one of these digests could be in the fixity block...
-                        let code = if algorithm == DigestAlgorithm::Sha512
-                            || algorithm == DigestAlgorithm::Sha256
-                        {
-                            ErrorCode::E092
-                        } else {
-                            ErrorCode::E093
-                        };
+            for (algorithm, actual) in digests {
+                let expected = job.expectations.get(&algorithm).unwrap();
+                if actual != *expected {
+                    // TODO technically, one of these digests could be in the fixity block...
+                    let code = if algorithm == DigestAlgorithm::Sha512
+                        || algorithm == DigestAlgorithm::Sha256
+                    {
+                        ErrorCode::E092
+                    } else {
+                        ErrorCode::E093
+                    };
 
-                        result.error(
-                            ProblemLocation::ObjectRoot,
-                            code,
-                            format!(
-                                "Content file {} failed {} fixity check. Expected: {}; Found: {}",
-                                path, algorithm, expected, actual
-                            ),
-                        );
-                    }
+                    result.error(
+                        ProblemLocation::ObjectRoot,
+                        code,
+                        format!(
+                            "Content file {} failed {} fixity check. Expected: {}; Found: {}",
+                            job.path, algorithm, expected, actual
+                        ),
+                    );
                 }
             }
         }
@@ -1836,6 +2974,139 @@ impl<S: Storage> Validator<S> {
         Ok(())
     }
 
+    /// Digests the files described by the jobs on the validator's thread pool. The returned
+    /// outcomes are in the same order as the jobs. A job's outcome is `None` if the validator
+    /// was closed before the job was started.
+    fn run_fixity_jobs(
+        &self,
+        jobs: &[FixityJob],
+        key: Option<&EncryptionKey>,
+    ) -> Vec<Option<FixityOutcome>> {
+        self.pool.map(
+            jobs,
+            || self.is_closed(),
+            |job| {
+                info!("Checking fixity of {}", job.path);
+                self.digest_fixity_job(job, key)
+            },
+        )
+    }
+
+    fn digest_fixity_job(&self, job: &FixityJob, key: Option<&EncryptionKey>) -> FixityOutcome {
+        let algorithms: Vec<DigestAlgorithm> = job.expectations.keys().copied().collect();
+        let mut digester = MultiDigestWriter::new(&algorithms, std::io::sink());
+
+        let result = match key {
+            Some(key) => {
+                let mut writer = DecryptingWriter::new(key, &mut digester);
+                self.storage
+                    .read(&job.full_path, &mut writer)
+                    .and_then(|_| writer.finish().map(|_| ()))
+            }
+            None => self.storage.read(&job.full_path, &mut digester),
+        };
+
+        match result {
+            Ok(_) => FixityOutcome::Digests(digester.finalize_hex()),
+            Err(RocflError::ArchivedContent(_)) => FixityOutcome::Archived,
+            Err(RocflError::Io(e)) if key.is_some() && e.kind() == io::ErrorKind::InvalidData => {
+                FixityOutcome::Undecryptable(e.to_string())
+            }
+            Err(e) => FixityOutcome::Failed(e),
+        }
+    }
+
+    /// Returns the key that the object's content must be decrypted with, or `None` if the object
+    /// is not encrypted. An error describing why the content cannot be decrypted is returned if
+    /// the object is encrypted and the validator does not have its key.
+    fn content_encryption(
+        &self,
+        object_root: &str,
+        inventory: &Inventory,
+    ) -> std::result::Result<Option<&EncryptionKey>, String> {
+        let extensions = paths::join(object_root, EXTENSIONS_DIR);
+        let encrypted = self
+            .storage
+            .list(&extensions, false)
+            .map_err(|e| e.to_string())?
+            .iter()
+            .any(|listing| {
+                matches!(listing, Listing::Directory(path) if path == ROCFL_ENCRYPTION_EXTENSION)
+            });
+
+        if !encrypted {
+            return Ok(None);
+        }
+
+        let key = self
+            .encryption_key
+            .as_ref()
+            .ok_or("the object is encrypted and an encryption key is not configured")?;
+
+        let mut bytes = Vec::new();
+        let marker_path = paths::join(
+            &extensions,
+            &paths::join(ROCFL_ENCRYPTION_EXTENSION, EXTENSIONS_CONFIG_FILE),
+        );
+        self.storage
+            .read(&marker_path, &mut bytes)
+            .map_err(|e| format!("its encryption marker could not be read: {}", e))?;
+        let marker: EncryptionMarker = serde_json::from_slice(&bytes)
+            .map_err(|e| format!("its encryption marker is invalid: {}", e))?;
+
+        key.ensure_matches(&inventory.id, &marker)
+            .map_err(|e| e.to_string())?;
+
+        Ok(Some(key))
+    }
+
+    /// Checks the other locations that an object may have been stored at, such as the paths it
+    /// maps to under different storage layouts, and reports an `E037` error for each one that
+    /// contains an object with the same ID as the object at `object_root`.
+    pub fn validate_no_duplicate_object(
+        &self,
+        object_id: &str,
+        object_root: &str,
+        candidate_roots: &[String],
+        result: &mut ObjectValidationResult,
+    ) -> Result<()> {
+        let mut checked = HashSet::new();
+        checked.insert(object_root);
+
+        for candidate in candidate_roots {
+            if !checked.insert(candidate.as_str()) || !is_relative_object_root(candidate) {
+                continue;
+            }
+
+            let is_object_root = self
+                .storage
+                .list(candidate, false)?
+                .iter()
+                .any(|entry| matches!(entry, Listing::File(name) if name.starts_with(OBJECT_NAMASTE_FILE_PREFIX)));
+
+            if is_object_root && self.read_object_id(candidate).as_deref() == Some(object_id) {
+                result.error(
+                    ProblemLocation::ObjectRoot,
+                    ErrorCode::E037,
+                    format!("Found duplicate object {} at {}", object_id, candidate),
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Reads the ID of the object at the path from its inventory, without validating it
+    fn read_object_id(&self, object_root: &str) -> Option<String> {
+        let mut bytes = Vec::new();
+        self.storage
+            .read(&paths::join(object_root, INVENTORY_FILE), &mut bytes)
+            .ok()?;
+        serde_json::from_slice::<InventoryId>(&bytes)
+            .ok()
+            .map(|inventory| inventory.id)
+    }
+
     /// Instructs the store to gracefully stop any in-flight work and not accept any additional
     /// requests.
     pub fn close(&self) {
@@ -1848,14 +3119,16 @@ impl<S: Storage> Validator<S> {
 }
 
 impl<'a, S: Storage> IncrementalValidatorImpl<'a, S> {
+    #[allow(clippy::too_many_arguments)]
     fn new(
         storage_root_result: StorageValidationResult,
         validator: &'a Validator<S>,
         storage: &'a S,
         root_version: Option<SpecVersion>,
-        fixity_check: bool,
+        fixity_check: FixityCheck,
+        filter: ValidationFilter,
+        layout_extension: Option<String>,
         root_files: Vec<Listing<'a>>,
-        closed: Arc<AtomicBool>,
     ) -> Self {
         Self {
             storage_root_result,
@@ -1864,10 +3137,16 @@ impl<'a, S: Storage> IncrementalValidatorImpl<'a, S> {
             storage,
             root_version,
             fixity_check,
-            dir_iters: vec![Dir::new("".to_string(), root_files.into_iter())],
+            filter,
+            dir_iters: vec![Dir::new("".to_string(), root_files)],
             current_iter: None,
             seen_ids: HashSet::new(),
-            closed,
+            last_object_root: None,
+            resumed_after: None,
+            layout_extension,
+            top_level_objects: ObjectPlacement::default(),
+            nested_objects: ObjectPlacement::default(),
+            closed: validator.closed.clone(),
         }
     }
 
@@ -1879,9 +3158,97 @@ impl<'a, S: Storage> IncrementalValidatorImpl<'a, S> {
         }
     }
 
+    /// Records whether the object at the storage path is directly under the storage root or
+    /// within a directory hierarchy
+    fn record_object_placement(&mut self, object_root: &str) {
+        let placement = if object_root.contains('/') {
+            &mut self.nested_objects
+        } else {
+            &mut self.top_level_objects
+        };
+
+        placement.count += 1;
+        if placement.paths.len() < MAX_REPORTED_PATHS {
+            placement.paths.push(object_root.to_string());
+        }
+    }
+
+    /// Warns, once every object has been found, if the storage root contains both top-level
+    /// objects and objects within a directory hierarchy (W015). The objects that are reported
+    /// are the ones that do not fit the layout declared in `ocfl_layout.json`, or, when a layout
+    /// is not declared, the ones in the less common placement.
+    fn validate_object_placement(&mut self) {
+        if self.top_level_objects.count == 0 || self.nested_objects.count == 0 {
+            return;
+        }
+
+        let top_level = mem::take(&mut self.top_level_objects);
+        let nested = mem::take(&mut self.nested_objects);
+        let counts = format!(
+            "Storage hierarchy mixes top-level objects ({}) and objects within a directory hierarchy ({})",
+            top_level.count, nested.count
+        );
+
+        let flat_layout = self.layout_extension.as_deref().map(|extension| {
+            extension == LayoutExtensionName::FlatDirectLayout.to_string()
+                || extension == LayoutExtensionName::FlatOmitPrefixLayout.to_string()
+        });
+
+        let report_nested = match flat_layout {
+            Some(flat) => flat,
+            None => nested.count < top_level.count,
+        };
+
+        let (description, offending) = if report_nested {
+            ("within a directory hierarchy", nested)
+        } else {
+            ("directly under the storage root", top_level)
+        };
+
+        let mut listed = offending.paths.join(", ");
+        if offending.count > offending.paths.len() {
+            listed.push_str(", ...");
+        }
+
+        let reason = match &self.layout_extension {
+            Some(extension) => format!("that do not follow the declared layout {}", extension),
+            None => "(the less common placement)".to_string(),
+        };
+
+        self.storage_hierarchy_result.warn(
+            ProblemLocation::StorageHierarchy,
+            WarnCode::W015,
+            format!("{}. Objects {} {}: {}", counts, description, reason, listed),
+        );
+    }
+
     fn full_path(&self, name: &str) -> String {
         paths::join(&self.current_iter.as_ref().unwrap().path, name)
     }
+
+    /// Returns true if everything at the path was already validated before validation was
+    /// resumed. Storage paths are compared component by component, which is the order that the
+    /// storage hierarchy is traversed in.
+    fn validated_before_resume(&self, path: &str) -> bool {
+        match &self.resumed_after {
+            Some(last) => {
+                let is_ancestor = last
+                    .strip_prefix(path)
+                    .is_some_and(|rest| rest.starts_with('/'));
+                !is_ancestor && path.split('/').cmp(last.split('/')) != cmp::Ordering::Greater
+            }
+            None => false,
+        }
+    }
+
+    /// Returns true if the object at the path matches the validation filter
+    fn matches_filter(&self, object_root: &str) -> bool {
+        self.filter.includes_path(object_root)
+            && (!self.filter.matches_ids()
+                || self
+                    .filter
+                    .includes_id(self.validator.read_object_id(object_root).as_deref()))
+    }
 }
 
 /// Lazily validates every object in the repository. Each call to `next()` validates another object.
@@ -1907,6 +3274,61 @@ impl<'a, S: Storage> IncrementalValidator for IncrementalValidatorImpl<'a, S> {
     fn storage_hierarchy_result_mut(&mut self) -> &mut StorageValidationResult {
         &mut self.storage_hierarchy_result
     }
+
+    /// Returns the validator's progress, from which validation may be resumed if it is
+    /// interrupted. It includes every object that has been returned by `next()`.
+    fn checkpoint(&self) -> ValidationCheckpoint {
+        ValidationCheckpoint {
+            last_object_root: self.last_object_root.clone(),
+            object_ids: self.seen_ids.iter().cloned().collect(),
+            hierarchy_errors: self
+                .storage_hierarchy_result
+                .errors()
+                .iter()
+                .map(|error| (error.code.to_string(), error.text.clone()))
+                .collect(),
+            hierarchy_warnings: self
+                .storage_hierarchy_result
+                .warnings()
+                .iter()
+                .map(|warning| (warning.code.to_string(), warning.text.clone()))
+                .collect(),
+            top_level_objects: self.top_level_objects.clone(),
+            nested_objects: self.nested_objects.clone(),
+        }
+    }
+
+    /// Resumes validation from a checkpoint of a previous validation of the same repository. The
+    /// objects that were validated before the checkpoint was taken are skipped, and the storage
+    /// hierarchy results that were accumulated up to the checkpoint are restored.
+    fn resume(&mut self, checkpoint: ValidationCheckpoint) {
+        for (code, text) in checkpoint.hierarchy_errors {
+            match ErrorCode::from_str(&code) {
+                Ok(code) => self.storage_hierarchy_result.error(
+                    ProblemLocation::StorageHierarchy,
+                    code,
+                    text,
+                ),
+                Err(_) => info!("Skipping checkpointed error with unknown code {}", code),
+            }
+        }
+        for (code, text) in checkpoint.hierarchy_warnings {
+            match WarnCode::from_str(&code) {
+                Ok(code) => self.storage_hierarchy_result.warn(
+                    ProblemLocation::StorageHierarchy,
+                    code,
+                    text,
+                ),
+                Err(_) => info!("Skipping checkpointed warning with unknown code {}", code),
+            }
+        }
+
+        self.seen_ids.extend(checkpoint.object_ids);
+        self.top_level_objects = checkpoint.top_level_objects;
+        self.nested_objects = checkpoint.nested_objects;
+        self.last_object_root = checkpoint.last_object_root.clone();
+        self.resumed_after = checkpoint.last_object_root;
+    }
 }
 
 impl<'a, S: Storage> Iterator for IncrementalValidatorImpl<'a, S> {
@@ -1921,6 +3343,7 @@ impl<'a, S: Storage> Iterator for IncrementalValidatorImpl<'a, S> {
             }
 
             if self.current_iter.is_none() && self.dir_iters.is_empty() {
+                self.validate_object_placement();
                 return None;
             } else if self.current_iter.is_none() {
                 self.current_iter = self.dir_iters.pop();
@@ -1936,6 +3359,12 @@ impl<'a, S: Storage> Iterator for IncrementalValidatorImpl<'a, S> {
 
                             let path = self.full_path(&name);
 
+                            if !self.filter.may_contain(&path)
+                                || self.validated_before_resume(&path)
+                            {
+                                continue;
+                            }
+
                             match self.storage.list(&path, false) {
                                 Ok(listing) => {
                                     if listing.is_empty() {
@@ -1946,8 +3375,18 @@ impl<'a, S: Storage> Iterator for IncrementalValidatorImpl<'a, S> {
                                         )
                                     }
 
+                                    if listing.iter().any(|entry| self.is_object_root(entry))
+                                        && !self.matches_filter(&path)
+                                    {
+                                        info!("Skipping object at {} because it does not match the filter", path);
+                                        continue;
+                                    }
+
                                     for entry in &listing {
                                         if self.is_object_root(entry) {
+                                            self.last_object_root = Some(path.clone());
+                                            self.record_object_placement(&path);
+
                                             return match self.validator.validate_object(
                                                 None,
                                                 &path,
@@ -1973,7 +3412,7 @@ impl<'a, S: Storage> Iterator for IncrementalValidatorImpl<'a, S> {
                                     }
 
                                     // no object found -- advance to next directory
-                                    let dir = Dir::new(path, listing.into_iter());
+                                    let dir = Dir::new(path, listing);
                                     self.dir_iters.push(self.current_iter.replace(dir).unwrap());
                                 }
                                 Err(e) => return Some(Err(e)),
@@ -1982,6 +3421,10 @@ impl<'a, S: Storage> Iterator for IncrementalValidatorImpl<'a, S> {
                         Listing::File(name) => {
                             let path = self.full_path(&name);
 
+                            if self.validated_before_resume(&path) {
+                                continue;
+                            }
+
                             self.storage_hierarchy_result.error(
                                 ProblemLocation::StorageHierarchy,
                                 ErrorCode::E072,
@@ -1991,6 +3434,10 @@ impl<'a, S: Storage> Iterator for IncrementalValidatorImpl<'a, S> {
                         Listing::Other(name) => {
                             let path = self.full_path(&name);
 
+                            if self.validated_before_resume(&path) {
+                                continue;
+                            }
+
                             self.storage_hierarchy_result.error(
                                 ProblemLocation::StorageHierarchy,
                                 ErrorCode::E090,
@@ -2008,8 +3455,14 @@ impl<'a, S: Storage> Iterator for IncrementalValidatorImpl<'a, S> {
 }
 
 impl<'a> Dir<'a> {
-    fn new(path: String, iter: IntoIter<Listing<'a>>) -> Self {
-        Self { path, iter }
+    /// Directory contents are sorted so that the storage hierarchy is always traversed in the
+    /// same order, which is what allows validation to be resumed
+    fn new(path: String, mut listing: Vec<Listing<'a>>) -> Self {
+        listing.sort_unstable_by(|a, b| a.path().cmp(b.path()));
+        Self {
+            path,
+            iter: listing.into_iter(),
+        }
     }
 }
 
@@ -2051,7 +3504,7 @@ impl ParseValidationResult {
     }
 
     pub fn has_errors(&self) -> bool {
-        self.errors.borrow().len() > 0
+        !self.errors.borrow().is_empty()
     }
 }
 
@@ -2070,13 +3523,13 @@ impl ContentPaths {
 
     fn add_path(&mut self, path: ContentPath) {
         if let ContentPathVersion::VersionNum(num) = path.version {
-            self.path_map.entry(num).or_insert_with(Vec::new).push(path);
+            self.path_map.entry(num).or_default().push(path);
         }
     }
 
     /// Iterates over all of the content files that _should_ appear in the manifest of an inventory
     /// at the specified `version_num`. Paths from later versions are not included.
-    fn iter(&self, version_num: VersionNum) -> ContentPathsIter {
+    fn iter(&self, version_num: VersionNum) -> ContentPathsIter<'_> {
         ContentPathsIter {
             current_version: version_num,
             current_iter: self
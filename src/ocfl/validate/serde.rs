@@ -1,3 +1,4 @@
+use std::borrow::Cow;
 use std::cell::RefCell;
 use std::collections::hash_map::Entry;
 use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
@@ -18,9 +19,9 @@ use crate::ocfl::bimap::PathBiMap;
 use crate::ocfl::digest::HexDigest;
 use crate::ocfl::inventory::{Inventory, User, Version};
 use crate::ocfl::serde::{
-    ADDRESS_FIELD, CONTENT_DIRECTORY_FIELD, CREATED_FIELD, DIGEST_ALGORITHM_FIELD, FIXITY_FIELD,
-    HEAD_FIELD, ID_FIELD, INVENTORY_FIELDS, MANIFEST_FIELD, MESSAGE_FIELD, NAME_FIELD, STATE_FIELD,
-    TYPE_FIELD, USER_FIELD, USER_FIELDS, VERSIONS_FIELD, VERSION_FIELDS,
+    CowStr, ADDRESS_FIELD, CONTENT_DIRECTORY_FIELD, CREATED_FIELD, DIGEST_ALGORITHM_FIELD,
+    FIXITY_FIELD, HEAD_FIELD, ID_FIELD, INVENTORY_FIELDS, MANIFEST_FIELD, MESSAGE_FIELD,
+    NAME_FIELD, STATE_FIELD, TYPE_FIELD, USER_FIELD, USER_FIELDS, VERSIONS_FIELD, VERSION_FIELDS,
 };
 use crate::ocfl::validate::{ErrorCode, ParseResult, ParseValidationResult, WarnCode};
 use crate::ocfl::{ContentPath, DigestAlgorithm, LogicalPath, VersionNum};
@@ -419,7 +420,7 @@ impl<'de> Deserialize<'de> for OptionWrapper<Inventory> {
                         )
                     }
 
-                    if let Some(highest_version) = versions.nums.iter().rev().next() {
+                    if let Some(highest_version) = versions.nums.iter().next_back() {
                         if head != highest_version {
                             self.result.error(
                                 ErrorCode::E040,
@@ -898,29 +899,21 @@ impl<'de: 'b, 'a, 'b> DeserializeSeed<'de> for ManifestSeed<'a, 'b> {
                         None => break,
                         Some(digest) => {
                             digests.insert(digest);
-                            match map.next_value::<Vec<&str>>() {
+                            match map.next_value::<Vec<CowStr>>() {
                                 Ok(paths) => {
                                     let mut content_paths = Vec::with_capacity(paths.len());
 
-                                    for path in paths {
-                                        if path.starts_with('/') || path.ends_with('/') {
-                                            self.result.error(ErrorCode::E100,
-                                                              format!("Inventory manifest key '{}' contains a path with a leading/trailing '/'. Found: {}",
-                                                                      digest, path));
-                                        } else {
-                                            match ContentPath::try_from(path) {
-                                                Ok(content_path) => {
-                                                    content_paths.push(content_path)
-                                                }
-                                                Err(_) => {
-                                                    self.result.error(ErrorCode::E099,
-                                                                      format!("Inventory manifest key '{}' contains a path containing an illegal path part. Found: {}",
-                                                                              digest, path));
-                                                }
+                                    for CowStr(path) in paths {
+                                        match parse_content_path(path.as_ref()) {
+                                            Ok(content_path) => content_paths.push(content_path),
+                                            Err((code, problem)) => {
+                                                self.result.error(code,
+                                                                  format!("Inventory manifest key '{}' contains {}. Found: {}",
+                                                                          digest, problem, path));
                                             }
                                         }
 
-                                        if all_paths.contains(path) {
+                                        if all_paths.contains(&path) {
                                             self.result.error(ErrorCode::E101,
                                                           format!("Inventory manifest contains duplicate path '{}'",
                                                                   path));
@@ -1009,18 +1002,18 @@ impl<'de: 'b, 'a, 'b, 'c> DeserializeSeed<'de> for StateSeed<'a, 'b, 'c> {
                 loop {
                     match map.next_key()? {
                         None => break,
-                        Some(digest) => match map.next_value::<Vec<&str>>() {
+                        Some(digest) => match map.next_value::<Vec<CowStr>>() {
                             Ok(paths) => {
                                 let digest_ref = self.data.insert_digest(digest);
                                 let mut path_refs = Vec::with_capacity(paths.len());
 
-                                for path in paths {
+                                for CowStr(path) in paths {
                                     if path.starts_with('/') || path.ends_with('/') {
                                         self.result.error(ErrorCode::E053,
                                                               format!("In inventory version {}, state key '{}' contains a path with a leading/trailing '/'. Found: {}",
                                                                       self.version, digest, path));
                                     } else {
-                                        match self.data.insert_path::<A::Error>(path) {
+                                        match self.data.insert_path::<A::Error>(path.clone()) {
                                             Ok(logical_path) => path_refs.push(logical_path),
                                             Err(_) => {
                                                 self.result.error(ErrorCode::E052,
@@ -1030,7 +1023,7 @@ impl<'de: 'b, 'a, 'b, 'c> DeserializeSeed<'de> for StateSeed<'a, 'b, 'c> {
                                         }
                                     }
 
-                                    if all_paths.contains(path) {
+                                    if all_paths.contains(&path) {
                                         self.result.error(ErrorCode::E095,
                                                           format!("In inventory version {}, state contains duplicate path '{}'",
                                                                   self.version, path));
@@ -1252,7 +1245,7 @@ impl<'de, 'a, 'b> DeserializeSeed<'de> for UserSeed<'a, 'b> {
 #[derive(Debug)]
 struct DigestsAndPaths<'a> {
     digests: HashMap<&'a str, Rc<HexDigest>>,
-    paths: HashMap<&'a str, Rc<LogicalPath>>,
+    paths: HashMap<Cow<'a, str>, Rc<LogicalPath>>,
 }
 
 impl<'a> DigestsAndPaths<'a> {
@@ -1270,15 +1263,15 @@ impl<'a> DigestsAndPaths<'a> {
             .clone()
     }
 
-    fn insert_path<E>(&mut self, path: &'a str) -> Result<Rc<LogicalPath>, E>
+    fn insert_path<E>(&mut self, path: Cow<'a, str>) -> Result<Rc<LogicalPath>, E>
     where
         E: SerdeError,
     {
         match self.paths.entry(path) {
             Entry::Occupied(entry) => Ok(entry.get().clone()),
             Entry::Vacant(vacant) => {
-                let path =
-                    LogicalPath::try_from(path).map_err(|e| SerdeError::custom(e.to_string()))?;
+                let path = LogicalPath::try_from(vacant.key().as_ref())
+                    .map_err(|e| SerdeError::custom(e.to_string()))?;
                 let path_rc = Rc::new(path);
                 let clone = path_rc.clone();
                 vacant.insert(path_rc);
@@ -1357,54 +1350,57 @@ fn validate_fixity(
             let mut all_paths = HashSet::with_capacity(fixity_manifest.len());
             let mut all_digests = HashSet::with_capacity(fixity_manifest.len());
 
-            if let Some(manifest) = manifest {
-                for (digest, paths) in fixity_manifest {
-                    let digest = digest.to_ascii_lowercase();
-                    if all_digests.contains(&digest) {
+            for (digest, paths) in fixity_manifest {
+                let digest = digest.to_ascii_lowercase();
+                if all_digests.contains(&digest) {
+                    result.error(
+                        ErrorCode::E097,
+                        format!(
+                            "Inventory fixity block '{}' contains duplicate digest '{}'",
+                            algorithm, digest
+                        ),
+                    );
+                } else {
+                    all_digests.insert(digest);
+                }
+
+                for path in paths {
+                    if all_paths.contains(&path) {
                         result.error(
-                            ErrorCode::E097,
+                            ErrorCode::E101,
                             format!(
-                                "Inventory fixity block '{}' contains duplicate digest '{}'",
-                                algorithm, digest
+                                "Inventory fixity block '{}' contains duplicate path '{}'",
+                                algorithm, path
                             ),
                         );
-                    } else {
-                        all_digests.insert(digest);
+
+                        continue;
                     }
 
-                    for path in paths {
-                        if all_paths.contains(&path) {
+                    all_paths.insert(path);
+
+                    // Malformed paths are reported even when the manifest is invalid, but whether
+                    // a path is in the manifest can only be checked when it could be parsed
+                    match parse_content_path(path) {
+                        Ok(content_path) => {
+                            if let Some(manifest) = manifest {
+                                if !manifest.manifest.contains_path(&content_path) {
+                                    result.error(
+                                        ErrorCode::E057,
+                                        format!("Inventory fixity block '{}' contains a path not present in the manifest. Found: {}",
+                                                algorithm, path),
+                                    );
+                                }
+                            }
+                        }
+                        Err((code, problem)) => {
                             result.error(
-                                ErrorCode::E101,
+                                code,
                                 format!(
-                                    "Inventory fixity block '{}' contains duplicate path '{}'",
-                                    algorithm, path
+                                    "Inventory fixity block '{}' contains {}. Found: {}",
+                                    algorithm, problem, path
                                 ),
                             );
-
-                            continue;
-                        }
-
-                        all_paths.insert(path);
-
-                        if path.starts_with('/') || path.ends_with('/') {
-                            result.error(ErrorCode::E100,
-                                              format!("Inventory fixity block '{}' contains a path with a leading/trailing '/'. Found: {}",
-                                                      algorithm, path));
-                        } else if let Ok(content_path) = ContentPath::try_from(path) {
-                            if !manifest.manifest.contains_path(&content_path) {
-                                result.error(
-                                    ErrorCode::E057,
-                                    format!("Inventory fixity block '{}' contains a path not present in the manifest. Found: {}",
-                                            algorithm, path),
-                                );
-                            }
-                        } else {
-                            result.error(
-                                ErrorCode::E099,
-                                format!("Inventory fixity block '{}' contains a path containing an illegal path part. Found: {}",
-                                        algorithm, path),
-                            );
                         }
                     }
                 }
@@ -1413,12 +1409,23 @@ fn validate_fixity(
     }
 }
 
-fn validate_non_conflicting<F>(paths: &HashSet<&str>, error: F)
+/// Parses a manifest or fixity content path. If the path is malformed, the error code and a
+/// description of the problem are returned.
+fn parse_content_path(path: &str) -> Result<ContentPath, (ErrorCode, &'static str)> {
+    if path.starts_with('/') || path.ends_with('/') {
+        Err((ErrorCode::E100, "a path with a leading/trailing '/'"))
+    } else {
+        ContentPath::try_from(path)
+            .map_err(|_| (ErrorCode::E099, "a path containing an illegal path part"))
+    }
+}
+
+fn validate_non_conflicting<F>(paths: &HashSet<Cow<str>>, error: F)
 where
     F: Fn(&str, &str),
 {
     for path in paths {
-        let mut part = *path;
+        let mut part = path.as_ref();
         while let Some(index) = part.rfind('/') {
             part = &part[0..index];
             if paths.contains(part) {
@@ -2075,6 +2082,70 @@ mod tests {
         }
     }
 
+    #[test]
+    fn fixity_invalid_paths_when_manifest_invalid() {
+        let json = r###"{
+            "id": "urn:example:test",
+            "type": "https://ocfl.io/1.0/spec/#inventory",
+            "digestAlgorithm": "sha512",
+            "head": "v1",
+            "contentDirectory": "content",
+            "manifest": false,
+            "versions": {
+                "v1": {
+                    "created": "2021-09-05T20:36:50.923505656-05:00",
+                    "state": {
+                        "fb0d38126bb990e2fd0edae87bf58e7a69e85a652b67cb9db30b32c138750377f6c3e1bb2f45588aeb0db1509f3562107f896b47d5b2c8972809e42e6bb68455": [
+                            "file1.txt"
+                        ]
+                    },
+                    "message": "initial commit",
+                    "user": {
+                        "name": "Peter Winckles",
+                        "address": "mailto:me@example.com"
+                    }
+                }
+            },
+            "fixity": {
+                "md5": {
+                    "184f84e28cbe75e050e9c25ea7f2e939": [
+                        "/v1/content/file1.txt",
+                        "v1/content/../../file2.txt",
+                        "file3.txt"
+                    ]
+                }
+            }
+        }"###;
+
+        match parse(json.as_bytes()) {
+            ParseResult::Ok(_, _) => panic!("Expected parse failure"),
+            ParseResult::Error(result) => {
+                has_error(
+                    ErrorCode::E106,
+                    "Inventory 'manifest' must be an object",
+                    &result,
+                );
+                has_error(
+                    ErrorCode::E100,
+                    "Inventory fixity block 'md5' contains a path with a leading/trailing '/'. Found: /v1/content/file1.txt",
+                    &result,
+                );
+                has_error(
+                    ErrorCode::E099,
+                    "Inventory fixity block 'md5' contains a path containing an illegal path part. Found: v1/content/../../file2.txt",
+                    &result,
+                );
+                has_error(
+                    ErrorCode::E099,
+                    "Inventory fixity block 'md5' contains a path containing an illegal path part. Found: file3.txt",
+                    &result,
+                );
+                error_count(4, &result);
+                warning_count(0, &result);
+            }
+        }
+    }
+
     #[test]
     fn user_address_invalid_type() {
         let json = r###"{
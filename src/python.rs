@@ -0,0 +1,385 @@
+//! Python bindings for the `ocfl` module, built with [pyo3](https://pyo3.rs/) when the `python`
+//! feature is enabled. The bindings are packaged with [maturin](https://www.maturin.rs/), which
+//! reads its configuration from `pyproject.toml`.
+//!
+//! Objects, versions, and validation results are returned as `dict`s so that they are easy to
+//! inspect and serialize from Python. Errors are raised as `rocfl.RocflError`, or its subclass
+//! `rocfl.NotFoundError` when an object, version, or file does not exist.
+
+use std::mem;
+use std::path::PathBuf;
+
+use pyo3::create_exception;
+use pyo3::exceptions::PyException;
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyDict, PyList};
+
+use crate::ocfl::{
+    self, CommitMeta, DigestAlgorithm, InventoryPath, LayoutExtensionName, LogicalPath,
+    ObjectValidationResult, ObjectVersion, ObjectVersionDetails, OcflRepo, ProblemLocation,
+    SpecVersion, StorageLayout, StorageValidationResult, ValidationResult, VersionDetails,
+    VersionRef,
+};
+
+create_exception!(
+    rocfl,
+    RocflError,
+    PyException,
+    "Raised when an OCFL operation fails"
+);
+create_exception!(
+    rocfl,
+    NotFoundError,
+    RocflError,
+    "Raised when an object, version, or file does not exist"
+);
+
+impl From<ocfl::RocflError> for PyErr {
+    fn from(e: ocfl::RocflError) -> Self {
+        match e {
+            ocfl::RocflError::NotFound(_) => NotFoundError::new_err(format!("{:#}", e)),
+            e => RocflError::new_err(format!("{:#}", e)),
+        }
+    }
+}
+
+/// The `rocfl` Python module
+#[pymodule]
+fn rocfl(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyOcflRepo>()?;
+    m.add("RocflError", m.py().get_type::<RocflError>())?;
+    m.add("NotFoundError", m.py().get_type::<NotFoundError>())?;
+    Ok(())
+}
+
+/// An OCFL repository on the local filesystem
+#[pyclass(name = "OcflRepo", module = "rocfl", frozen)]
+struct PyOcflRepo {
+    repo: OcflRepo,
+}
+
+#[pymethods]
+impl PyOcflRepo {
+    /// Opens an existing OCFL repository. The staging directory defaults to the repository's
+    /// staging extension directory.
+    #[new]
+    #[pyo3(signature = (root, staging_root = None))]
+    fn new(root: PathBuf, staging_root: Option<PathBuf>) -> PyResult<Self> {
+        Ok(Self {
+            repo: OcflRepo::fs_repo(root, staging_root.as_deref())?,
+        })
+    }
+
+    /// Initializes a new OCFL repository. `layout` is the name of a storage layout extension,
+    /// such as `0004-hashed-n-tuple-storage-layout`, that is configured with its defaults.
+    #[staticmethod]
+    #[pyo3(signature = (root, staging_root = None, layout = None, spec_version = "1.1"))]
+    fn init(
+        root: PathBuf,
+        staging_root: Option<PathBuf>,
+        layout: Option<&str>,
+        spec_version: &str,
+    ) -> PyResult<Self> {
+        let layout = match layout {
+            Some(name) => Some(StorageLayout::new(parse_layout_name(name)?, None)?),
+            None => None,
+        };
+
+        Ok(Self {
+            repo: OcflRepo::init_fs_repo(
+                root,
+                staging_root.as_deref(),
+                SpecVersion::try_from_num(spec_version)?,
+                layout,
+            )?,
+        })
+    }
+
+    /// Returns the head versions of every object in the repository, optionally restricted to
+    /// the objects with IDs that match a glob.
+    #[pyo3(signature = (glob = None))]
+    fn list_objects<'py>(
+        &self,
+        py: Python<'py>,
+        glob: Option<&str>,
+    ) -> PyResult<Vec<Bound<'py, PyDict>>> {
+        let mut objects = Vec::new();
+
+        for object in self.repo.list_objects(glob)? {
+            let object = object.map_err(ocfl::RocflError::from)?;
+            objects.push(object_details_dict(py, &object)?);
+        }
+
+        Ok(objects)
+    }
+
+    /// Returns a version of an object, including its files. The head version is returned when
+    /// `version` is not specified.
+    #[pyo3(signature = (object_id, version = None))]
+    fn show<'py>(
+        &self,
+        py: Python<'py>,
+        object_id: &str,
+        version: Option<&str>,
+    ) -> PyResult<Bound<'py, PyDict>> {
+        let object = self.repo.get_object(object_id, version_ref(version)?)?;
+        object_version_dict(py, &object)
+    }
+
+    /// Returns the content of a file in a version of an object. The head version is used when
+    /// `version` is not specified.
+    #[pyo3(signature = (object_id, path, version = None))]
+    fn cat<'py>(
+        &self,
+        py: Python<'py>,
+        object_id: &str,
+        path: &str,
+        version: Option<&str>,
+    ) -> PyResult<Bound<'py, PyBytes>> {
+        let path = LogicalPath::try_from(path)?;
+        let version = version_ref(version)?;
+        let mut content = Vec::new();
+
+        py.allow_threads(|| {
+            self.repo
+                .get_object_file(object_id, &path, version, &mut content)
+        })?;
+
+        Ok(PyBytes::new(py, &content))
+    }
+
+    /// Validates an object. Content fixity is checked unless `fixity_check` is false.
+    #[pyo3(signature = (object_id, fixity_check = true))]
+    fn validate_object<'py>(
+        &self,
+        py: Python<'py>,
+        object_id: &str,
+        fixity_check: bool,
+    ) -> PyResult<Bound<'py, PyDict>> {
+        let result = py.allow_threads(|| self.repo.validate_object(object_id, fixity_check))?;
+        object_result_dict(py, &result)
+    }
+
+    /// Validates the entire repository. Content fixity is checked unless `fixity_check` is
+    /// false.
+    #[pyo3(signature = (fixity_check = true))]
+    fn validate<'py>(&self, py: Python<'py>, fixity_check: bool) -> PyResult<Bound<'py, PyDict>> {
+        let (root, objects, hierarchy) = py.allow_threads(|| -> ocfl::Result<_> {
+            let mut validator = self.repo.validate_repo(fixity_check)?;
+            let objects = (&mut validator).collect::<ocfl::Result<Vec<_>>>()?;
+            Ok((
+                mem::take(validator.storage_root_result_mut()),
+                objects,
+                mem::take(validator.storage_hierarchy_result_mut()),
+            ))
+        })?;
+
+        let valid = !root.has_errors()
+            && !hierarchy.has_errors()
+            && objects.iter().all(|object| !object.has_errors());
+
+        let dict = PyDict::new(py);
+        dict.set_item("valid", valid)?;
+        dict.set_item("storage_root", storage_result_dict(py, &root)?)?;
+        dict.set_item(
+            "objects",
+            objects
+                .iter()
+                .map(|object| object_result_dict(py, object))
+                .collect::<PyResult<Vec<_>>>()?,
+        )?;
+        dict.set_item("storage_hierarchy", storage_result_dict(py, &hierarchy)?)?;
+        Ok(dict)
+    }
+
+    /// Stages a new object. The object is not added to the repository until it is committed.
+    #[pyo3(signature = (object_id, digest_algorithm = "sha512", content_directory = "content", zero_padding = 0))]
+    fn create_object(
+        &self,
+        object_id: &str,
+        digest_algorithm: &str,
+        content_directory: &str,
+        zero_padding: u32,
+    ) -> PyResult<()> {
+        let digest_algorithm = digest_algorithm.parse::<DigestAlgorithm>().map_err(|_| {
+            ocfl::RocflError::InvalidValue(format!(
+                "Unknown digest algorithm: {}",
+                digest_algorithm
+            ))
+        })?;
+
+        Ok(self.repo.create_object(
+            object_id,
+            None,
+            digest_algorithm,
+            content_directory,
+            zero_padding,
+        )?)
+    }
+
+    /// Copies files from the local filesystem into an object's staged version. A destination
+    /// of `/` is the object's root.
+    #[pyo3(signature = (object_id, src, dst, recursive = false))]
+    fn copy(
+        &self,
+        py: Python<'_>,
+        object_id: &str,
+        src: Vec<PathBuf>,
+        dst: &str,
+        recursive: bool,
+    ) -> PyResult<()> {
+        Ok(py.allow_threads(|| {
+            self.repo
+                .copy_files_external(object_id, &src, dst, recursive)
+        })?)
+    }
+
+    /// Removes files from an object's staged version. The files still exist in prior versions.
+    #[pyo3(signature = (object_id, paths, recursive = false))]
+    fn remove(&self, object_id: &str, paths: Vec<String>, recursive: bool) -> PyResult<()> {
+        Ok(self.repo.remove_files(object_id, &paths, recursive)?)
+    }
+
+    /// Commits an object's staged changes as a new version. `user_name` must be specified if
+    /// `user_address` is.
+    #[pyo3(signature = (object_id, message = None, user_name = None, user_address = None, pretty_print = false))]
+    fn commit(
+        &self,
+        py: Python<'_>,
+        object_id: &str,
+        message: Option<String>,
+        user_name: Option<String>,
+        user_address: Option<String>,
+        pretty_print: bool,
+    ) -> PyResult<()> {
+        let meta = CommitMeta::new()
+            .with_user(user_name, user_address)?
+            .with_message(message);
+
+        Ok(py.allow_threads(|| self.repo.commit(object_id, meta, None, pretty_print))?)
+    }
+
+    /// Gracefully stops in-flight work and rejects any further requests
+    fn close(&self) {
+        self.repo.close();
+    }
+}
+
+fn parse_layout_name(name: &str) -> ocfl::Result<LayoutExtensionName> {
+    name.parse::<LayoutExtensionName>()
+        .map_err(|_| ocfl::RocflError::InvalidValue(format!("Unknown storage layout: {}", name)))
+}
+
+fn version_ref(version: Option<&str>) -> ocfl::Result<VersionRef> {
+    match version {
+        Some(version) => VersionRef::try_from(version),
+        None => Ok(VersionRef::Head),
+    }
+}
+
+fn object_details_dict<'py>(
+    py: Python<'py>,
+    object: &ObjectVersionDetails,
+) -> PyResult<Bound<'py, PyDict>> {
+    let dict = PyDict::new(py);
+    dict.set_item("id", &object.id)?;
+    dict.set_item("object_root", &object.object_root)?;
+    dict.set_item("digest_algorithm", object.digest_algorithm.to_string())?;
+    dict.set_item("version", version_dict(py, &object.version_details)?)?;
+    Ok(dict)
+}
+
+fn object_version_dict<'py>(
+    py: Python<'py>,
+    object: &ObjectVersion,
+) -> PyResult<Bound<'py, PyDict>> {
+    let mut files: Vec<_> = object.state.iter().collect();
+    files.sort_by_key(|(path, _)| *path);
+
+    let file_list = PyList::empty(py);
+    for (path, details) in files {
+        let file = PyDict::new(py);
+        file.set_item("path", path.as_str())?;
+        file.set_item("digest", details.digest.as_ref().as_ref())?;
+        file.set_item("content_path", details.content_path.as_str())?;
+        file.set_item("storage_path", &details.storage_path)?;
+        file.set_item("last_update", version_dict(py, &details.last_update)?)?;
+        file_list.append(file)?;
+    }
+
+    let dict = PyDict::new(py);
+    dict.set_item("id", &object.id)?;
+    dict.set_item("object_root", &object.object_root)?;
+    dict.set_item("digest_algorithm", object.digest_algorithm.to_string())?;
+    dict.set_item("version", version_dict(py, &object.version_details)?)?;
+    dict.set_item("files", file_list)?;
+    Ok(dict)
+}
+
+fn version_dict<'py>(py: Python<'py>, version: &VersionDetails) -> PyResult<Bound<'py, PyDict>> {
+    let dict = PyDict::new(py);
+    dict.set_item("version_num", version.version_num.to_string())?;
+    dict.set_item("created", version.created.to_rfc3339())?;
+    dict.set_item("user_name", &version.user_name)?;
+    dict.set_item("user_address", &version.user_address)?;
+    dict.set_item("message", &version.message)?;
+    Ok(dict)
+}
+
+fn object_result_dict<'py>(
+    py: Python<'py>,
+    result: &ObjectValidationResult,
+) -> PyResult<Bound<'py, PyDict>> {
+    let dict = problems_dict(py, result)?;
+    dict.set_item("object_id", &result.object_id)?;
+    dict.set_item("storage_path", &result.storage_path)?;
+    dict.set_item("fixity_checked", result.fixity_checked().len())?;
+    Ok(dict)
+}
+
+fn storage_result_dict<'py>(
+    py: Python<'py>,
+    result: &StorageValidationResult,
+) -> PyResult<Bound<'py, PyDict>> {
+    problems_dict(py, result)
+}
+
+/// Returns a dict containing the validity of a result and its errors and warnings
+fn problems_dict<'py>(
+    py: Python<'py>,
+    result: &impl ValidationResult,
+) -> PyResult<Bound<'py, PyDict>> {
+    let problem_dict = |location: &ProblemLocation, code: String, text: &str| {
+        let dict = PyDict::new(py);
+        dict.set_item("code", code)?;
+        dict.set_item("location", location_name(location))?;
+        dict.set_item("text", text)?;
+        Ok::<_, PyErr>(dict)
+    };
+
+    let errors = result
+        .errors()
+        .iter()
+        .map(|e| problem_dict(&e.location, e.code.to_string(), &e.text))
+        .collect::<PyResult<Vec<_>>>()?;
+    let warnings = result
+        .warnings()
+        .iter()
+        .map(|w| problem_dict(&w.location, w.code.to_string(), &w.text))
+        .collect::<PyResult<Vec<_>>>()?;
+
+    let dict = PyDict::new(py);
+    dict.set_item("valid", !result.has_errors())?;
+    dict.set_item("errors", errors)?;
+    dict.set_item("warnings", warnings)?;
+    Ok(dict)
+}
+
+fn location_name(location: &ProblemLocation) -> String {
+    match location {
+        ProblemLocation::ObjectRoot => "root".to_string(),
+        ProblemLocation::ObjectVersion(num) => num.to_string(),
+        ProblemLocation::StorageRoot => "storage-root".to_string(),
+        ProblemLocation::StorageHierarchy => "hierarchy".to_string(),
+    }
+}
@@ -1,10 +1,15 @@
+#![cfg(feature = "cli")]
+
 use std::path::{Path, PathBuf};
 
 use assert_cmd::Command;
 use assert_fs::TempDir;
+use chrono::{Local, TimeZone};
 use common::*;
+use flate2::write::GzEncoder;
+use flate2::Compression;
 use predicates::prelude::*;
-use predicates::str::{ContainsPredicate, IsEmptyPredicate};
+use predicates::str::{ContainsPredicate, IsEmptyPredicate, RegexPredicate};
 
 mod common;
 
@@ -98,6 +103,139 @@ fn list_multiple_objects() {
         .stdout(contains_str(object_id_3));
 }
 
+#[test]
+fn list_objects_and_contents_as_ndjson() {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let _ = init(root.path()).assert().success();
+
+    for object_id in ["obj-2", "obj-1"] {
+        let _ = new(root.path()).arg(object_id).assert().success();
+        let _ = copy(root.path())
+            .arg(object_id)
+            .arg(create_file(&temp, "file.txt", object_id).path())
+            .arg("--")
+            .arg("dir/file.txt")
+            .assert()
+            .success();
+        let _ = commit(root.path()).arg(object_id).assert().success();
+    }
+
+    let output = list(root.path())
+        .arg("--format")
+        .arg("ndjson")
+        .arg("-s")
+        .arg("name")
+        .output()
+        .unwrap();
+    let lines = json_lines(output.stdout);
+
+    assert_eq!(2, lines.len());
+    assert_eq!("obj-1", lines[0]["object_id"]);
+    assert_eq!("v1", lines[0]["version"]);
+    assert_eq!("sha512", lines[0]["digest_algorithm"]);
+    assert!(lines[0]["object_root"].is_string());
+    assert_eq!("obj-2", lines[1]["object_id"]);
+
+    let output = list(root.path())
+        .arg("-f")
+        .arg("ndjson")
+        .arg("obj-1")
+        .output()
+        .unwrap();
+    let lines = json_lines(output.stdout);
+
+    assert_eq!(1, lines.len());
+    assert_eq!("file", lines[0]["type"]);
+    assert_eq!("dir/file.txt", lines[0]["logical_path"]);
+    assert_eq!("v1", lines[0]["version"]);
+    assert!(lines[0]["digest"].is_string());
+
+    let output = list(root.path())
+        .arg("-f")
+        .arg("ndjson")
+        .arg("-D")
+        .arg("obj-1")
+        .output()
+        .unwrap();
+    let lines = json_lines(output.stdout);
+
+    assert_eq!(1, lines.len());
+    assert_eq!("dir", lines[0]["type"]);
+    assert_eq!("dir/", lines[0]["logical_path"]);
+}
+
+#[test]
+fn list_contents_with_origin_content_path_and_digest_prefix() {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let _ = init(root.path()).assert().success();
+    let _ = new(root.path()).arg("obj").assert().success();
+    let _ = copy(root.path())
+        .arg("obj")
+        .arg(create_file(&temp, "a.txt", "a").path())
+        .arg("--")
+        .arg("/")
+        .assert()
+        .success();
+    let _ = commit(root.path()).arg("obj").assert().success();
+
+    let _ = mv(root.path())
+        .arg("-i")
+        .arg("obj")
+        .arg("a.txt")
+        .arg("--")
+        .arg("b.txt")
+        .assert()
+        .success();
+    let _ = copy(root.path())
+        .arg("obj")
+        .arg(create_file(&temp, "c.txt", "c").path())
+        .arg("--")
+        .arg("/")
+        .assert()
+        .success();
+    let _ = commit(root.path()).arg("obj").assert().success();
+
+    let _ = list(root.path())
+        .arg("-Hcd")
+        .arg("--origin")
+        .arg("--digest-length")
+        .arg("8")
+        .arg("obj")
+        .assert()
+        .success()
+        .stdout(
+            "Origin Logical Path Content Path     Digest\n    \
+             v1 b.txt        v1/content/a.txt sha512:1f40fc92\n    \
+             v2 c.txt        v2/content/c.txt sha512:acc28db2\n",
+        );
+
+    let _ = list(root.path())
+        .arg("-l")
+        .arg("--origin")
+        .arg("obj")
+        .arg("b.txt")
+        .assert()
+        .success()
+        .stdout(predicates::str::is_match("^v2 .+ v1 b.txt\n$").unwrap());
+
+    let output = list(root.path())
+        .arg("-f")
+        .arg("ndjson")
+        .arg("obj")
+        .arg("b.txt")
+        .output()
+        .unwrap();
+    let lines = json_lines(output.stdout);
+
+    assert_eq!("v2", lines[0]["version"]);
+    assert_eq!("v1", lines[0]["origin"]);
+    assert_eq!("v1/content/a.txt", lines[0]["content_path"]);
+}
+
 #[test]
 fn logical_directory_listing() {
     let root = TempDir::new().unwrap();
@@ -232,6 +370,102 @@ fn logical_directory_listing() {
         .stdout(contains_str("a/c/different.txt").not());
 }
 
+#[test]
+fn list_repo_with_unreadable_objects() {
+    let root = repo_root("invalid");
+
+    let _ = list(&root)
+        .assert()
+        .failure()
+        .stdout(contains_str("o2"))
+        .stderr(contains_str("Skipped 2 objects that could not be read"))
+        .stderr(contains_str(
+            "2352da7280f1decc3acf1ba84eb945c9fc2b7b541094e1d0992dbffd1b6664cc",
+        ));
+
+    let _ = list(&root)
+        .arg("--skip-errors")
+        .assert()
+        .success()
+        .stdout(contains_str("o2"))
+        .stderr(contains_str("Skipped 2 objects that could not be read"));
+}
+
+#[test]
+fn list_staged_object_with_diff_markers() {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let object_id = "obj-1";
+
+    let _ = init(root.path()).assert().success();
+    let _ = new(root.path()).arg(object_id).assert().success();
+
+    let _ = copy(root.path())
+        .arg(object_id)
+        .arg(create_file(&temp, "a.txt", "a").path())
+        .arg(create_file(&temp, "b.txt", "b").path())
+        .arg(create_file(&temp, "c.txt", "c").path())
+        .arg("--")
+        .arg("/")
+        .assert()
+        .success();
+
+    let _ = list(root.path())
+        .arg("-St")
+        .arg("--diff-markers")
+        .arg(object_id)
+        .assert()
+        .success()
+        .stdout(marked("Added", "a.txt"))
+        .stdout(marked("Added", "b.txt"))
+        .stdout(marked("Added", "c.txt"));
+
+    let _ = commit(root.path()).arg(object_id).assert().success();
+
+    let _ = copy(root.path())
+        .arg(object_id)
+        .arg(create_file(&temp, "b.txt", "modified").path())
+        .arg(create_file(&temp, "d.txt", "d").path())
+        .arg("--")
+        .arg("/")
+        .assert()
+        .success();
+
+    let _ = rocfl(root.path(), "rm")
+        .arg(object_id)
+        .arg("c.txt")
+        .assert()
+        .success();
+
+    let _ = list(root.path())
+        .arg("-St")
+        .arg("--diff-markers")
+        .arg(object_id)
+        .assert()
+        .success()
+        .stdout(marked("Unchanged", "a.txt"))
+        .stdout(marked("Modified", "b.txt"))
+        .stdout(marked("Deleted", "c.txt"))
+        .stdout(marked("Added", "d.txt"));
+
+    let _ = list(root.path())
+        .arg("-St")
+        .arg("--diff-markers")
+        .arg(object_id)
+        .arg("c*")
+        .assert()
+        .success()
+        .stdout(marked("Deleted", "c.txt"))
+        .stdout(contains_str("a.txt").not());
+
+    let _ = list(root.path())
+        .arg("--diff-markers")
+        .arg(object_id)
+        .assert()
+        .failure();
+}
+
 #[test]
 fn validate_repo_sanity() {
     let root = validate_repo_root("invalid");
@@ -264,63 +498,2314 @@ fn validate_repo_quiet() {
         .stdout(contains_str("Storage issues:  10"));
 }
 
-fn init(path: impl AsRef<Path>) -> Command {
-    rocfl(path, "init")
-}
+#[test]
+fn validate_repo_resumes_from_state_file() {
+    let root = validate_repo_root("invalid");
+    let temp = TempDir::new().unwrap();
+    let state = temp.path().join("state.json");
 
-fn new(path: impl AsRef<Path>) -> Command {
-    rocfl(path, "new")
-}
+    std::fs::write(
+        &state,
+        r#"{
+  "checkpoint": {
+    "lastObjectRoot": "b01/0ba/c95/b010bac9560b3cf4050f0585e9aee1732ce4fe380a331a800ad9f1525e201fff",
+    "objectIds": ["urn:example:rocfl:obj-2"],
+    "hierarchyErrors": [],
+    "hierarchyWarnings": []
+  },
+  "objects": 1,
+  "invalidObjects": 1,
+  "filesChecked": 0,
+  "errorValidating": false
+}"#,
+    )
+    .unwrap();
 
-fn copy(path: impl AsRef<Path>) -> Command {
-    rocfl(path, "cp")
-}
+    let _ = validate(&root)
+        .arg("--resume")
+        .arg(&state)
+        .assert()
+        .stdout(contains_str("urn:example:rocfl:obj-2").not())
+        .stdout(contains_str("Object urn:example:rocfl:obj-1 is valid"))
+        .stdout(contains_str("Storage hierarchy is invalid"))
+        .stdout(contains_str("Total objects:   2"))
+        .stdout(contains_str("Invalid objects: 1"))
+        .stdout(contains_str("Storage issues:  10"));
 
-fn mv(path: impl AsRef<Path>) -> Command {
-    rocfl(path, "mv")
+    assert!(!state.exists());
 }
 
-fn commit(path: impl AsRef<Path>) -> Command {
-    rocfl(path, "commit")
-}
+#[test]
+fn validate_verbose_and_json_include_spec_references() {
+    let root = validate_repo_root("invalid");
 
-fn list(path: impl AsRef<Path>) -> Command {
-    rocfl(path, "ls")
-}
+    let _ = rocfl(&root, "-v")
+        .arg("validate")
+        .assert()
+        .stdout(contains_str(
+            "[E069] Root version declaration does not exist",
+        ))
+        .stdout(contains_str(
+            "4.1 Root Structure: https://ocfl.io/1.1/spec/#E069",
+        ))
+        .stdout(contains_str(
+            "An OCFL Storage Root MUST contain a Root Conformance Declaration",
+        ))
+        .stdout(contains_str("https://ocfl.io/1.1/spec/#W016"));
 
-fn status(path: impl AsRef<Path>) -> Command {
-    rocfl(path, "status")
-}
+    let output = validate(&root).arg("--json").output().unwrap();
+    let lines: Vec<serde_json::Value> = String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
 
-fn validate(path: impl AsRef<Path>) -> Command {
-    rocfl(path, "validate")
+    assert_eq!(5, lines.len());
+    assert_eq!("storage", lines[0]["type"]);
+    assert_eq!("root", lines[0]["location"]);
+    assert_eq!(false, lines[0]["valid"]);
+    assert_eq!("E069", lines[0]["errors"][0]["code"]);
+    assert_eq!(
+        "https://ocfl.io/1.1/spec/#E069",
+        lines[0]["errors"][0]["spec"]["url"]
+    );
+    assert_eq!(
+        "4.1 Root Structure",
+        lines[0]["errors"][0]["spec"]["section"]
+    );
+    assert_eq!("W016", lines[0]["warnings"][0]["code"]);
+    assert_eq!("summary", lines[4]["type"]);
+    assert_eq!(1, lines[4]["invalid_objects"]);
+    assert_eq!(10, lines[4]["storage_issues"]);
 }
 
-fn rocfl(path: impl AsRef<Path>, command: &str) -> Command {
-    let mut rocfl = Command::cargo_bin("rocfl").unwrap();
-    rocfl
-        .arg("-S")
-        .arg("-r")
-        .arg(path.as_ref().to_string_lossy().as_ref())
-        .arg(command);
-    rocfl
+#[test]
+fn validate_objects_on_multiple_threads_in_order() {
+    let root = TempDir::new().unwrap();
+
+    let object_ids = ["urn:d", "urn:a", "urn:c", "urn:e", "urn:b"];
+
+    let _ = init(root.path()).assert().success();
+    for object_id in object_ids {
+        let _ = new(root.path()).arg(object_id).assert().success();
+        let _ = commit(root.path()).arg(object_id).assert().success();
+    }
+
+    let output = rocfl(root.path(), "--threads")
+        .arg("3")
+        .arg("validate")
+        .arg("--json")
+        .args(object_ids)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let lines: Vec<serde_json::Value> = String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+
+    assert_eq!(6, lines.len());
+    for (line, object_id) in lines.iter().zip(object_ids) {
+        assert_eq!(object_id, line["object_id"]);
+        assert_eq!(true, line["valid"]);
+    }
+    assert_eq!(5, lines[5]["total_objects"]);
+
+    let _ = rocfl(root.path(), "--threads")
+        .arg("0")
+        .arg("validate")
+        .assert()
+        .failure()
+        .stderr(contains_str("Threads must be greater than 0"));
 }
 
-fn contains_str(string: &str) -> ContainsPredicate {
-    predicates::str::contains(string)
+#[test]
+fn log_repository_changes_to_json_log_file() {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+    let log_file = temp.path().join("rocfl.log");
+
+    let object_id = "obj-1";
+
+    let _ = init(root.path()).assert().success();
+
+    let logged = |command: &str| {
+        let mut rocfl = Command::cargo_bin("rocfl").unwrap();
+        rocfl
+            .arg("-S")
+            .arg("-r")
+            .arg(root.path().to_string_lossy().as_ref())
+            .arg("--log-file")
+            .arg(log_file.to_string_lossy().as_ref())
+            .arg("--log-format")
+            .arg("json")
+            .arg(command);
+        rocfl
+    };
+
+    let _ = logged("new").arg(object_id).assert().success();
+    let _ = logged("cp")
+        .arg(object_id)
+        .arg(create_file(&temp, "file.txt", "blah").path())
+        .arg("--")
+        .arg("/")
+        .assert()
+        .success()
+        .stderr(empty());
+    let _ = logged("commit").arg(object_id).assert().success();
+
+    let events = std::fs::read_to_string(&log_file)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+        .filter(|entry| entry["target"] == "rocfl::audit")
+        .map(|entry| entry["event"].clone())
+        .collect::<Vec<serde_json::Value>>();
+
+    assert_eq!(3, events.len());
+
+    assert_eq!("create", events[0]["operation"]);
+    assert_eq!("copy", events[1]["operation"]);
+    assert_eq!("commit", events[2]["operation"]);
+
+    for event in &events {
+        assert_eq!(object_id, event["object_id"]);
+        assert_eq!("v1", event["version"]);
+        assert_eq!("success", event["outcome"]);
+        assert!(event["duration_ms"].is_u64());
+    }
+
+    assert!(events[1]["paths"][0]
+        .as_str()
+        .unwrap()
+        .ends_with("file.txt"));
+    assert_eq!("/", events[1]["destination"]);
 }
 
-fn empty() -> IsEmptyPredicate {
-    predicates::str::is_empty()
+#[test]
+fn log_marks_versions_created_before_previous_version() {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let object_id = "obj-1";
+
+    let _ = init(root.path()).assert().success();
+    let _ = new(root.path()).arg(object_id).assert().success();
+    let _ = commit(root.path())
+        .arg(object_id)
+        .arg("-c")
+        .arg("2020-01-01T00:00:00Z")
+        .assert()
+        .success();
+
+    let _ = copy(root.path())
+        .arg(object_id)
+        .arg(create_file(&temp, "file.txt", "blah").path())
+        .arg("--")
+        .arg("/")
+        .assert()
+        .success();
+    let _ = commit(root.path())
+        .arg(object_id)
+        .arg("-c")
+        .arg("2019-01-01T00:00:00Z")
+        .assert()
+        .success();
+
+    let _ = log(root.path())
+        .arg(object_id)
+        .assert()
+        .success()
+        .stdout(contains_str("(!) Created before the previous version").count(1));
+
+    let _ = log(root.path())
+        .arg("-c")
+        .arg(object_id)
+        .arg("file.txt")
+        .assert()
+        .success()
+        .stdout(contains_str("(!)").count(1));
+
+    let _ = log(root.path())
+        .arg("-ctn1")
+        .arg("--reverse")
+        .arg(object_id)
+        .assert()
+        .success()
+        .stdout(contains_str("v2").and(contains_str("(!)").count(1)));
+
+    let _ = log(root.path())
+        .arg("-ctn1")
+        .arg("--skip")
+        .arg("1")
+        .arg(object_id)
+        .assert()
+        .success()
+        .stdout(contains_str("v2").and(contains_str("(!)").count(1)));
+
+    let _ = log(root.path())
+        .arg("-ctn1")
+        .arg(object_id)
+        .assert()
+        .success()
+        .stdout(contains_str("v1").and(contains_str("(!)").count(0)));
 }
 
-fn validate_repo_root(name: &str) -> PathBuf {
-    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
-    path.push("resources");
-    path.push("test");
-    path.push("validate");
-    path.push("custom");
-    path.push("repos");
-    path.push(name);
+#[test]
+fn log_with_diff_displays_change_counts_of_each_version() {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let object_id = "obj-1";
+
+    let _ = init(root.path()).assert().success();
+    let _ = new(root.path()).arg(object_id).assert().success();
+    let _ = copy(root.path())
+        .arg(object_id)
+        .arg(create_file(&temp, "a.txt", "a").path())
+        .arg(create_file(&temp, "b.txt", "b").path())
+        .arg("--")
+        .arg("/")
+        .assert()
+        .success();
+    let _ = commit(root.path()).arg(object_id).assert().success();
+
+    let _ = mv(root.path())
+        .arg("-i")
+        .arg(object_id)
+        .arg("a.txt")
+        .arg("--")
+        .arg("c.txt")
+        .assert()
+        .success();
+    let _ = copy(root.path())
+        .arg(object_id)
+        .arg(create_file(&temp, "b.txt", "changed").path())
+        .arg("--")
+        .arg("/")
+        .assert()
+        .success();
+    let _ = commit(root.path()).arg(object_id).assert().success();
+
+    let _ = log(root.path())
+        .arg("--diff")
+        .arg(object_id)
+        .assert()
+        .success()
+        .stdout(
+            contains_str("Changes: 0 added, 1 modified, 0 deleted, 1 renamed").and(contains_str(
+                "Changes: 2 added, 0 modified, 0 deleted, 0 renamed",
+            )),
+        );
+
+    let output = log(root.path())
+        .arg("-cthd")
+        .arg(object_id)
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<Vec<&str>> = stdout
+        .lines()
+        .map(|line| line.split('\t').map(str::trim).collect())
+        .collect();
+
+    assert_eq!(
+        vec!["Added", "Modified", "Deleted", "Renamed"],
+        lines[0][4..8].to_vec()
+    );
+    assert_eq!(vec!["2", "0", "0", "0"], lines[1][4..8].to_vec());
+    assert_eq!(vec!["0", "1", "0", "1"], lines[2][4..8].to_vec());
+}
+
+#[test]
+fn path_refs_select_versions_in_cat_log_and_show() {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let object_id = "obj-1";
+
+    let _ = init(root.path()).assert().success();
+    let _ = new(root.path()).arg(object_id).assert().success();
+    let _ = copy(root.path())
+        .arg(object_id)
+        .arg(create_file(&temp, "file.txt", "one").path())
+        .arg(create_file(&temp, "other@v1", "other").path())
+        .arg("--")
+        .arg("/")
+        .assert()
+        .success();
+    let _ = commit(root.path()).arg(object_id).assert().success();
+
+    let _ = copy(root.path())
+        .arg(object_id)
+        .arg(create_file(&temp, "file.txt", "two").path())
+        .arg("--")
+        .arg("/")
+        .assert()
+        .success();
+    let _ = commit(root.path()).arg(object_id).assert().success();
+
+    let _ = cat(root.path())
+        .arg(object_id)
+        .arg("file.txt@v1")
+        .assert()
+        .success()
+        .stdout("one");
+    let _ = cat(root.path())
+        .arg(object_id)
+        .arg("file.txt")
+        .assert()
+        .success()
+        .stdout("two");
+    let _ = cat(root.path())
+        .arg(object_id)
+        .arg("other@v1@")
+        .assert()
+        .success()
+        .stdout("other");
+    let _ = cat(root.path())
+        .arg("-v")
+        .arg("2")
+        .arg(object_id)
+        .arg("file.txt@v1")
+        .assert()
+        .failure();
+
+    let _ = log(root.path())
+        .arg("-c")
+        .arg(object_id)
+        .arg("file.txt@v1")
+        .assert()
+        .success()
+        .stdout(contains_str("v1").and(contains_str("v2").not()));
+
+    let _ = show(root.path())
+        .arg("-m")
+        .arg(object_id)
+        .arg("file.txt@v1")
+        .assert()
+        .success()
+        .stdout(contains_str("file.txt").and(contains_str("other@v1").not()));
+    let _ = show(root.path())
+        .arg("-m")
+        .arg(object_id)
+        .arg("v1")
+        .assert()
+        .success()
+        .stdout(contains_str("file.txt").and(contains_str("other@v1")));
+}
+
+#[test]
+fn diff_version_ranges() {
+    let root = repo_root("multiple-objects");
+
+    let _ = rocfl(&root, "diff")
+        .arg("--versions")
+        .arg("v1..v3")
+        .arg("o2")
+        .assert()
+        .success()
+        .stdout(
+            "Operation Logical Path
+Added     dir1/file3
+Deleted   file1
+",
+        );
+
+    let _ = rocfl(&root, "diff")
+        .arg("--versions")
+        .arg("v1..v3")
+        .arg("--steps")
+        .arg("o2")
+        .assert()
+        .success()
+        .stdout(
+            "Version 2
+Operation Logical Path
+Added     dir1/file3
+Renamed   file1 -> dir3/file1
+
+Version 3
+Operation Logical Path
+Modified  dir1/file3
+Deleted   dir3/file1
+",
+        );
+
+    let _ = rocfl(&root, "diff")
+        .arg("--versions")
+        .arg("v1..v3")
+        .arg("--steps")
+        .arg("--stat")
+        .arg("o2")
+        .assert()
+        .success()
+        .stdout(
+            "Version Added Modified Deleted Renamed
+v2          1        0       0       1
+v3          0        1       1       0
+",
+        );
+
+    let _ = rocfl(&root, "diff")
+        .arg("--stat")
+        .arg("o2")
+        .arg("v1")
+        .arg("v3")
+        .assert()
+        .success()
+        .stdout(
+            "Version Added Modified Deleted Renamed
+v1..v3      1        0       1       0
+",
+        );
+
+    let _ = rocfl(&root, "diff")
+        .arg("--versions")
+        .arg("v3..v1")
+        .arg("o2")
+        .assert()
+        .failure()
+        .stderr(contains_str(
+            "The start version must be before the end version",
+        ));
+}
+
+#[test]
+fn emit_patch_and_apply_to_another_object() {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let a = create_file(&temp, "a.txt", "moved");
+    let b = create_file(&temp, "b.txt", "added");
+
+    let _ = init(root.path()).assert().success();
+
+    for object_id in ["obj-1", "obj-2"] {
+        let _ = new(root.path()).arg(object_id).assert().success();
+        let _ = copy(root.path())
+            .arg(object_id)
+            .arg(a.path())
+            .arg("--")
+            .arg("/")
+            .assert()
+            .success();
+        let _ = commit(root.path()).arg(object_id).assert().success();
+    }
+
+    let _ = copy(root.path())
+        .arg("obj-1")
+        .arg(b.path())
+        .arg("--")
+        .arg("/")
+        .assert()
+        .success();
+    let _ = mv(root.path())
+        .arg("-i")
+        .arg("obj-1")
+        .arg("a.txt")
+        .arg("--")
+        .arg("dir/a.txt")
+        .assert()
+        .success();
+    let _ = commit(root.path()).arg("obj-1").assert().success();
+
+    let output = rocfl(root.path(), "diff")
+        .arg("--emit-patch")
+        .arg("--inline")
+        .arg("obj-1")
+        .arg("v1")
+        .arg("v2")
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    let patch: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!("rocfl-patch/1", patch["format"]);
+    assert_eq!(2, patch["changes"].as_array().unwrap().len());
+
+    let _ = rocfl(root.path(), "apply")
+        .arg("obj-2")
+        .arg("-")
+        .write_stdin(output)
+        .assert()
+        .success();
+    let _ = commit(root.path()).arg("obj-2").assert().success();
+
+    let _ = list(root.path())
+        .arg("obj-2")
+        .assert()
+        .success()
+        .stdout("b.txt\ndir/a.txt\n");
+    let _ = rocfl(root.path(), "cmp")
+        .arg("obj-1")
+        .arg("obj-2")
+        .assert()
+        .success()
+        .stdout(empty());
+}
+
+#[test]
+fn cmp_lists_differences_between_objects() {
+    let root = repo_root("multiple-objects");
+
+    let _ = rocfl(&root, "cmp")
+        .arg("o2")
+        .arg("o2")
+        .assert()
+        .success()
+        .stdout(empty());
+
+    let _ = rocfl(&root, "cmp")
+        .arg("--version-a")
+        .arg("v2")
+        .arg("o2")
+        .arg("o2")
+        .assert()
+        .code(1)
+        .stdout(
+            "Difference Logical Path
+Differs    dir1/file3
+Only in A  dir3/file1
+",
+        );
+
+    let _ = rocfl(&root, "cmp")
+        .arg("o1")
+        .arg("o2")
+        .assert()
+        .code(1)
+        .stdout(
+            "Difference Logical Path
+Only in B  dir1/dir2/file2
+Only in B  dir1/file3
+Only in A  file1
+Only in A  file2
+",
+        );
+}
+
+#[test]
+fn inspect_inventory_without_repo() {
+    let mut valid = validate_root();
+    valid.push("official-1.0/valid/spec-ex-full/inventory.json");
+
+    let _ = Command::cargo_bin("rocfl")
+        .unwrap()
+        .arg("-S")
+        .arg("inspect-inventory")
+        .arg(valid)
+        .assert()
+        .success()
+        .stdout(
+            "Inventory of object ark:/12345/bcd987 is valid
+  Type:              https://ocfl.io/1.0/spec/#inventory
+  Digest algorithm:  sha512
+  Head:              v3
+  Content directory: content
+  Versions:          3
+  Content files:     4
+",
+        );
+
+    let mut invalid = validate_root();
+    invalid.push("official-1.0/error/E040_wrong_head_format/inventory.json");
+
+    let _ = Command::cargo_bin("rocfl")
+        .unwrap()
+        .arg("-S")
+        .arg("inspect-inventory")
+        .arg(invalid)
+        .assert()
+        .code(2)
+        .stdout(contains_str("is invalid"))
+        .stdout(contains_str("[E040] Inventory 'head' must be a string"));
+}
+
+#[test]
+fn validate_inventory_file_with_digest() {
+    let mut inventory = validate_root();
+    inventory.push("official-1.0/valid/spec-ex-full/inventory.json");
+
+    let digest = "9c79b29388b177feead576b156376e9180fcab81710fcdd3cac95b79d822c3b09b30d115619a861eb3d74c2933ef3689ba4660a6bc31001da1e87db281d09f66";
+
+    let _ = Command::cargo_bin("rocfl")
+        .unwrap()
+        .arg("-S")
+        .arg("validate")
+        .arg("--inventory")
+        .arg(&inventory)
+        .arg("--digest")
+        .arg(format!("sha512:{}", digest))
+        .assert()
+        .success()
+        .stdout(contains_str(
+            "Inventory of object ark:/12345/bcd987 is valid",
+        ));
+
+    let _ = Command::cargo_bin("rocfl")
+        .unwrap()
+        .arg("-S")
+        .arg("validate")
+        .arg("--inventory")
+        .arg(&inventory)
+        .arg("--digest")
+        .arg(format!("sha512:{}", digest.replace('9', "0")))
+        .assert()
+        .code(2)
+        .stdout(contains_str("is invalid"))
+        .stdout(contains_str(
+            "[E060] Inventory does not match expected digest.",
+        ));
+
+    let _ = Command::cargo_bin("rocfl")
+        .unwrap()
+        .arg("-S")
+        .arg("validate")
+        .arg("--inventory")
+        .arg(&inventory)
+        .arg("--digest")
+        .arg(format!("sha512:{}", digest.replace('9', "0")))
+        .arg("-e")
+        .arg("E060")
+        .assert()
+        .success();
+
+    let _ = Command::cargo_bin("rocfl")
+        .unwrap()
+        .arg("-S")
+        .arg("validate")
+        .arg("--inventory")
+        .arg(&inventory)
+        .arg("--digest")
+        .arg("bogus:abc")
+        .assert()
+        .failure()
+        .stderr(contains_str("Invalid digest 'bogus:abc'"));
+}
+
+#[test]
+fn manifest_export_and_verify() {
+    let root = repo_root("multiple-objects");
+    let temp = TempDir::new().unwrap();
+
+    let file2 = "4cf0ff5673ec65d9900df95502ed92b2605fc602ca20b6901652c7561b302668026095813af6adb0e663bdcdbe1f276d18bf0de254992a78573ad6574e7ae1f6";
+    let file3 = "6e027f3dc89e0bfd97e4c2ec6919a8fb793bdc7b5c513bea618f174beec32a66d2fc0ce19439751e2f01ae49f78c56dcfc7b49c167a751c823d09da8419a4331";
+    let checksums = format!("{}  dir1/dir2/file2\n{}  dir1/file3\n", file2, file3);
+
+    let _ = rocfl(&root, "manifest")
+        .arg("export")
+        .arg("o2")
+        .assert()
+        .success()
+        .stdout(checksums.clone());
+
+    let _ = rocfl(&root, "manifest")
+        .arg("export")
+        .arg("-f")
+        .arg("csv")
+        .arg("-v")
+        .arg("v2")
+        .arg("o2")
+        .assert()
+        .success()
+        .stdout(contains_str("digest,algorithm,logical_path,version\n"))
+        .stdout(contains_str(&format!(
+            "{},sha512,dir1/dir2/file2,v1\n",
+            file2
+        )))
+        .stdout(contains_str(",sha512,dir3/file1,v2\n"));
+
+    let manifest = create_file(&temp, "manifest.txt", &checksums);
+
+    let _ = rocfl(&root, "manifest")
+        .arg("import")
+        .arg("--verify")
+        .arg("o2")
+        .arg(manifest.path())
+        .assert()
+        .success()
+        .stdout("Object o2 version v3 matches the manifest\n");
+
+    let _ = rocfl(&root, "manifest")
+        .arg("import")
+        .arg("--verify")
+        .arg("-f")
+        .arg("csv")
+        .arg("o2")
+        .arg("-")
+        .write_stdin(format!(
+            "digest,algorithm,logical_path,version\n{},sha512,dir1/dir2/file2,v1\n\
+             2a86b5bc6f7c51a24a41a114b65875bd,md5,dir1/file3,v3\n",
+            file2
+        ))
+        .assert()
+        .success();
+
+    let _ = rocfl(&root, "manifest")
+        .arg("import")
+        .arg("--verify")
+        .arg("-v")
+        .arg("v2")
+        .arg("o2")
+        .arg(manifest.path())
+        .assert()
+        .code(1)
+        .stdout(contains_str(&format!(
+            "[Problem] dir1/file3 has sha512 digest 7b866cfcfe06bf2bcaea7086f2a059854afe8de12a6e21e4286bec4828d3da36bdef28599be8c9be49da3e45ede3ddbc049f99ee197e5244c33e294748b1a986 but the manifest has {}",
+            file3
+        )))
+        .stdout(contains_str("[Problem] dir3/file1 is not in the manifest"));
+
+    let _ = rocfl(&root, "manifest")
+        .arg("import")
+        .arg("--verify")
+        .arg("o2")
+        .arg("-")
+        .write_stdin(format!("{}  dir1/bogus\n", file2))
+        .assert()
+        .code(1)
+        .stdout(contains_str("[Problem] dir1/bogus is not in the object"))
+        .stdout(contains_str("[Problem] dir1/file3 is not in the manifest"));
+}
+
+#[test]
+fn tree_renders_object_state() {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let object_id = "obj-1";
+
+    let _ = init(root.path()).assert().success();
+    let _ = new(root.path()).arg(object_id).assert().success();
+    create_file(&temp, "a/b/one.txt", "one");
+    create_file(&temp, "a/two.txt", "two!");
+    create_file(&temp, "top.txt", "top");
+    let _ = copy(root.path())
+        .arg("-r")
+        .arg(object_id)
+        .arg(temp.path().join("a"))
+        .arg(temp.path().join("top.txt"))
+        .arg("--")
+        .arg("/")
+        .assert()
+        .success();
+    let _ = commit(root.path()).arg(object_id).assert().success();
+
+    let _ = tree(root.path()).arg(object_id).assert().success().stdout(
+        "obj-1
+├── a
+│   ├── b
+│   │   └── one.txt
+│   └── two.txt
+└── top.txt
+
+2 directories, 3 files
+",
+    );
+
+    let _ = tree(root.path())
+        .arg("-s")
+        .arg(object_id)
+        .arg("a")
+        .assert()
+        .success()
+        .stdout(
+            "[         7] a
+├── [         3] b
+│   └── [         3] one.txt
+└── [         4] two.txt
+
+1 directory, 2 files
+",
+        );
+
+    let _ = tree(root.path())
+        .arg("-D")
+        .arg("-L")
+        .arg("1")
+        .arg(object_id)
+        .assert()
+        .success()
+        .stdout(
+            "obj-1
+└── a
+
+1 directory
+",
+        );
+
+    let _ = tree(root.path())
+        .arg(object_id)
+        .arg("missing")
+        .assert()
+        .failure()
+        .stderr(contains_str("Not found: Path missing in object obj-1"));
+}
+
+#[test]
+fn set_and_get_object_metadata() {
+    let root = TempDir::new().unwrap();
+
+    let object_id = "obj-1";
+
+    let _ = init(root.path()).assert().success();
+    let _ = new(root.path()).arg(object_id).assert().success();
+    let _ = commit(root.path()).arg(object_id).assert().success();
+
+    let _ = meta(root.path())
+        .arg("set")
+        .arg(object_id)
+        .arg("title=An Example")
+        .arg("creator=Someone")
+        .assert()
+        .success();
+
+    let _ = meta(root.path())
+        .arg("get")
+        .arg(object_id)
+        .assert()
+        .success()
+        .stdout("creator=Someone\ntitle=An Example\n");
+
+    let _ = meta(root.path())
+        .arg("set")
+        .arg(object_id)
+        .arg("-u")
+        .arg("creator")
+        .assert()
+        .success();
+
+    let _ = meta(root.path())
+        .arg("get")
+        .arg(object_id)
+        .arg("title")
+        .assert()
+        .success()
+        .stdout("An Example\n");
+    let _ = meta(root.path())
+        .arg("get")
+        .arg(object_id)
+        .arg("creator")
+        .assert()
+        .failure();
+    let _ = meta(root.path())
+        .arg("get")
+        .arg("-v2")
+        .arg(object_id)
+        .arg("creator")
+        .assert()
+        .success()
+        .stdout("Someone\n");
+
+    let _ = list(root.path())
+        .arg(object_id)
+        .assert()
+        .success()
+        .stdout(contains_str("metadata/rocfl.json"));
+    let _ = status(root.path()).assert().success().stdout(empty());
+}
+
+#[test]
+fn copy_files_from_tar_gz_archive() {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let object_id = "obj-1";
+
+    let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+    for (path, content) in [("a/file.txt", "file"), ("a/b/nested.txt", "nested")] {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(content.len() as u64);
+        header.set_mode(0o644);
+        builder
+            .append_data(&mut header, path, content.as_bytes())
+            .unwrap();
+    }
+    let archive = temp.path().join("archive.tar.gz");
+    std::fs::write(&archive, builder.into_inner().unwrap().finish().unwrap()).unwrap();
+
+    let _ = init(root.path()).assert().success();
+    let _ = new(root.path()).arg(object_id).assert().success();
+    let _ = copy(root.path())
+        .arg("--from-archive")
+        .arg(&archive)
+        .arg(object_id)
+        .arg("--")
+        .arg("dst")
+        .assert()
+        .success();
+
+    let _ = list(root.path())
+        .arg("-S")
+        .arg(object_id)
+        .assert()
+        .success()
+        .stdout("dst/a/b/nested.txt\ndst/a/file.txt\n");
+}
+
+#[test]
+fn copy_and_move_files_listed_in_files_from() {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let object_id = "obj-1";
+
+    for name in ["a.txt", "b c.txt", "d.txt", "e.txt"] {
+        std::fs::write(temp.path().join(name), name).unwrap();
+    }
+
+    let _ = init(root.path()).assert().success();
+    let _ = new(root.path()).arg(object_id).assert().success();
+
+    let stdin = format!(
+        "{}\0{}\0{}\0",
+        temp.path().join("a.txt").to_string_lossy(),
+        temp.path().join("b c.txt").to_string_lossy(),
+        temp.path().join("missing.txt").to_string_lossy()
+    );
+
+    let _ = copy(root.path())
+        .arg("--files-from")
+        .arg("-")
+        .arg(object_id)
+        .arg("--")
+        .arg("copied/")
+        .write_stdin(stdin)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("missing.txt: Does not exist"));
+
+    let list_file = temp.path().join("list.txt");
+    std::fs::write(
+        &list_file,
+        format!(
+            "{}\r\n\n{}\n",
+            temp.path().join("d.txt").to_string_lossy(),
+            temp.path().join("e.txt").to_string_lossy()
+        ),
+    )
+    .unwrap();
+
+    let _ = mv(root.path())
+        .arg("--files-from")
+        .arg(&list_file)
+        .arg(object_id)
+        .arg("--")
+        .arg("moved")
+        .assert()
+        .success();
+
+    assert!(!temp.path().join("d.txt").exists());
+    assert!(!temp.path().join("e.txt").exists());
+
+    let _ = list(root.path())
+        .arg("-S")
+        .arg(object_id)
+        .assert()
+        .success()
+        .stdout("copied/a.txt\ncopied/b c.txt\nmoved/d.txt\nmoved/e.txt\n");
+}
+
+#[test]
+fn gc_lists_and_deletes_orphaned_content() {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let object_id = "obj-1";
+
+    let _ = init(root.path())
+        .arg("-l")
+        .arg("0002-flat-direct-storage-layout")
+        .assert()
+        .success();
+    let _ = new(root.path()).arg(object_id).assert().success();
+    let _ = copy(root.path())
+        .arg(object_id)
+        .arg(create_file(&temp, "file.txt", "blah").path())
+        .arg("--")
+        .arg("/")
+        .assert()
+        .success();
+    let _ = commit(root.path()).arg(object_id).assert().success();
+
+    let orphan = root.path().join(object_id).join("v1/content/orphan.txt");
+    std::fs::write(&orphan, "orphan").unwrap();
+
+    let _ = gc(root.path())
+        .arg("--dry-run")
+        .arg(object_id)
+        .assert()
+        .success()
+        .stdout("v1/content/orphan.txt\n");
+    assert!(orphan.exists());
+
+    let _ = gc(root.path())
+        .arg(object_id)
+        .write_stdin("n\n")
+        .assert()
+        .success()
+        .stdout(contains_str("Aborted"));
+    assert!(orphan.exists());
+
+    let _ = gc(root.path())
+        .arg(object_id)
+        .write_stdin("y\n")
+        .assert()
+        .success()
+        .stdout(contains_str("Deleted 1 orphaned file(s)"));
+    assert!(!orphan.exists());
+    assert!(root
+        .path()
+        .join(object_id)
+        .join("v1/content/file.txt")
+        .exists());
+
+    let _ = gc(root.path())
+        .arg(object_id)
+        .assert()
+        .success()
+        .stdout(contains_str("No orphaned files found"));
+}
+
+#[test]
+fn init_without_spec_copy_warns_on_validate() {
+    let root = TempDir::new().unwrap();
+
+    let _ = init(root.path()).arg("--no-spec-copy").assert().success();
+    assert!(!root.path().join("ocfl_1.1.md").exists());
+    assert!(root.path().join("0=ocfl_1.1").exists());
+
+    let _ = validate(root.path())
+        .assert()
+        .success()
+        .stdout(contains_str(
+            "[RW004] Storage root does not contain a copy of the OCFL spec: ocfl_1.1.md",
+        ));
+}
+
+#[test]
+fn init_digest_algorithm_is_default_for_new_objects() {
+    let root = TempDir::new().unwrap();
+
+    let _ = init(root.path())
+        .arg("--digest")
+        .arg("sha256")
+        .assert()
+        .success()
+        .stderr(contains_str("recommends sha512 (W004)"));
+    assert_eq!(
+        "{\n  \"digestAlgorithm\": \"sha256\"\n}\n",
+        std::fs::read_to_string(
+            root.path()
+                .join("extensions/rocfl-repo-defaults/config.json")
+        )
+        .unwrap()
+    );
+
+    let _ = new(root.path())
+        .arg("urn:a")
+        .assert()
+        .success()
+        .stderr(contains_str("recommends sha512 (W004)"));
+    let _ = commit(root.path()).arg("urn:a").assert().success();
+    let _ = validate(root.path())
+        .arg("urn:a")
+        .assert()
+        .success()
+        .stdout(contains_str("[W004]"));
+
+    let _ = new(root.path())
+        .arg("urn:b")
+        .arg("--digest-algorithm")
+        .arg("sha512")
+        .assert()
+        .success()
+        .stderr("");
+    let _ = commit(root.path()).arg("urn:b").assert().success();
+    let _ = validate(root.path())
+        .arg("urn:b")
+        .assert()
+        .success()
+        .stdout(contains_str("[W004]").not());
+}
+
+#[test]
+fn verify_layout_reports_mismatched_extension_configs() {
+    let root = TempDir::new().unwrap();
+
+    let _ = init(root.path())
+        .arg("-l")
+        .arg("0004-hashed-n-tuple-storage-layout")
+        .assert()
+        .success();
+
+    let _ = layout(root.path())
+        .arg("verify")
+        .assert()
+        .success()
+        .stdout("Storage layout is consistent\n");
+
+    let _ = layout(root.path())
+        .arg("set-description")
+        .arg("Hashed n-tuple layout")
+        .assert()
+        .success();
+    let _ = layout(root.path())
+        .arg("show")
+        .assert()
+        .success()
+        .stdout(contains_str(
+            "Extension:   0004-hashed-n-tuple-storage-layout",
+        ))
+        .stdout(contains_str("Description: Hashed n-tuple layout"))
+        .stdout(contains_str("\"tupleSize\": 3"));
+
+    let extensions = root.path().join("extensions");
+    std::fs::write(
+        extensions.join("0004-hashed-n-tuple-storage-layout/config.json"),
+        r#"{"extensionName": "0003-hash-and-id-n-tuple-storage-layout"}"#,
+    )
+    .unwrap();
+    std::fs::create_dir_all(extensions.join("0002-flat-direct-storage-layout")).unwrap();
+
+    let _ = layout(root.path())
+        .arg("verify")
+        .assert()
+        .code(1)
+        .stdout(contains_str(
+            "extensions/0004-hashed-n-tuple-storage-layout/config.json is not a valid \
+            0004-hashed-n-tuple-storage-layout config",
+        ))
+        .stdout(contains_str(
+            "extensions contains layout extension 0002-flat-direct-storage-layout, but \
+            ocfl_layout.json declares 0004-hashed-n-tuple-storage-layout",
+        ));
+}
+
+#[test]
+fn enable_object_registry() {
+    let root = TempDir::new().unwrap();
+
+    let _ = init(root.path()).assert().success();
+    for object_id in ["obj-1", "obj-2"] {
+        let _ = new(root.path()).arg(object_id).assert().success();
+        let _ = commit(root.path()).arg(object_id).assert().success();
+    }
+
+    let _ = layout(root.path())
+        .arg("registry")
+        .assert()
+        .success()
+        .stdout("Enabled the object registry with 2 objects\n");
+    let _ = layout(root.path())
+        .arg("show")
+        .assert()
+        .success()
+        .stdout(contains_str("Registry:    enabled"));
+
+    let _ = new(root.path()).arg("obj-3").assert().success();
+    let _ = commit(root.path()).arg("obj-3").assert().success();
+
+    let index =
+        std::fs::read_to_string(root.path().join("extensions/rocfl-object-index/index.json"))
+            .unwrap();
+    assert!(index.contains("\"obj-3\""));
+
+    let _ = rocfl(root.path(), "ls")
+        .assert()
+        .success()
+        .stdout(contains_str("obj-1"))
+        .stdout(contains_str("obj-3"));
+
+    let _ = layout(root.path())
+        .arg("registry")
+        .arg("--disable")
+        .assert()
+        .success()
+        .stdout("Disabled the object registry\n");
+    let _ = layout(root.path())
+        .arg("show")
+        .assert()
+        .success()
+        .stdout(contains_str("Registry:    disabled"));
+}
+
+#[test]
+fn validate_only_objects_that_were_not_validated_recently() {
+    let root = TempDir::new().unwrap();
+
+    let _ = init(root.path()).assert().success();
+    for object_id in ["obj-1", "obj-2", "obj-3"] {
+        let _ = new(root.path()).arg(object_id).assert().success();
+        let _ = commit(root.path()).arg(object_id).assert().success();
+    }
+
+    let history = root
+        .path()
+        .join("extensions/rocfl-validation-history/history.json");
+    std::fs::create_dir_all(history.parent().unwrap()).unwrap();
+    let record = |object_id: &str, timestamp: String| {
+        serde_json::json!({
+            "object_id": object_id,
+            "timestamp": timestamp,
+            "tool_version": "rocfl 1.0.0",
+            "valid": true,
+            "errors": 0,
+            "warnings": 0,
+            "fixity_checked": 0,
+        })
+    };
+    std::fs::write(
+        &history,
+        serde_json::json!({
+            "obj-1": record("obj-1", chrono::Local::now().to_rfc3339()),
+            "obj-2": record("obj-2", "2000-01-01T00:00:00Z".to_string()),
+        })
+        .to_string(),
+    )
+    .unwrap();
+
+    let _ = validate(root.path())
+        .arg("--stale-than")
+        .arg("90d")
+        .assert()
+        .success()
+        .stdout(contains_str("Object obj-1").not())
+        .stdout(contains_str("Object obj-2 is valid"))
+        .stdout(contains_str("Object obj-3 is valid"))
+        .stdout(contains_str("Total objects:   2"));
+
+    let _ = validate(root.path())
+        .arg("--stale-than")
+        .arg("1w")
+        .arg("obj-1")
+        .arg("obj-2")
+        .assert()
+        .success()
+        .stdout(contains_str("Object obj-1").not())
+        .stdout(contains_str("Object obj-2 is valid"))
+        .stdout(contains_str("Total objects:   1"));
+
+    let _ = validate(root.path())
+        .arg("--stale-than")
+        .arg("90 days")
+        .assert()
+        .failure()
+        .stderr(contains_str("Invalid age '90 days'"));
+}
+
+#[test]
+fn map_object_id_to_each_layout_without_a_repo() {
+    let root = TempDir::new().unwrap();
+
+    let _ = layout(root.path())
+        .arg("map")
+        .arg("info:example/test-123")
+        .assert()
+        .success()
+        .stdout(contains_str(
+            "0002-flat-direct-storage-layout          info:example/test-123\n",
+        ))
+        .stdout(contains_str(
+            "0003-hash-and-id-n-tuple-storage-layout  1e4/d16/d89/info%3aexample%2ftest-123\n",
+        ))
+        .stdout(contains_str(
+            "0004-hashed-n-tuple-storage-layout       1e4/d16/d89/\
+            1e4d16d8940c54e7a88a8562fa5a55bafc0902128abb163f39fae3bda53425ae\n",
+        ))
+        .stdout(contains_str(
+            "0006-flat-omit-prefix-storage-layout     requires --delimiter\n",
+        ));
+
+    let _ = layout(root.path())
+        .arg("map")
+        .arg("-d")
+        .arg(":")
+        .arg("namespace:12887296")
+        .assert()
+        .success()
+        .stdout(contains_str(
+            "0006-flat-omit-prefix-storage-layout     12887296\n",
+        ))
+        .stdout(contains_str(
+            "0007-n-tuple-omit-prefix-storage-layout  012/887/296/12887296\n",
+        ));
+}
+
+#[test]
+fn completions_list_object_ids_and_generate_scripts() {
+    let root = TempDir::new().unwrap();
+
+    let _ = init(root.path()).assert().success();
+    for object_id in ["urn:a:1", "urn:a:2", "urn:b:1"] {
+        let _ = new(root.path()).arg(object_id).assert().success();
+        let _ = commit(root.path()).arg(object_id).assert().success();
+    }
+
+    let _ = completions(root.path())
+        .arg("--object-ids")
+        .arg("urn:a")
+        .assert()
+        .success()
+        .stdout("urn:a:1\nurn:a:2\n");
+    let _ = completions(root.path())
+        .arg("--object-ids")
+        .arg("--limit")
+        .arg("1")
+        .assert()
+        .success()
+        .stdout(predicates::str::is_match(r"^urn:[ab]:\d\n$").unwrap());
+
+    let _ = completions(root.path())
+        .arg("bash")
+        .assert()
+        .success()
+        .stdout(contains_str("complete -F _rocfl_object_ids"))
+        .stdout(contains_str("rocfl completions --object-ids"));
+    let _ = completions(root.path())
+        .arg("zsh")
+        .assert()
+        .success()
+        .stdout(predicates::str::starts_with("#compdef rocfl\n"))
+        .stdout(contains_str(
+            "':object_id -- ID of the object:_rocfl_object_ids'",
+        ));
+    let _ = completions(root.path())
+        .arg("fish")
+        .assert()
+        .success()
+        .stdout(contains_str("__fish_seen_subcommand_from ls tree log show"));
+}
+
+#[test]
+fn find_logical_paths_across_objects() {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let _ = init(root.path()).assert().success();
+
+    for object_id in ["obj-1", "obj-2"] {
+        let _ = new(root.path()).arg(object_id).assert().success();
+        let _ = copy(root.path())
+            .arg(object_id)
+            .arg(create_file(&temp, "image.tiff", object_id).path())
+            .arg("--")
+            .arg("images/image.tiff")
+            .assert()
+            .success();
+        let _ = commit(root.path()).arg(object_id).assert().success();
+    }
+
+    let _ = copy(root.path())
+        .arg("obj-1")
+        .arg(create_file(&temp, "notes.txt", "notes").path())
+        .arg("--")
+        .arg("/")
+        .assert()
+        .success();
+    let _ = commit(root.path()).arg("obj-1").assert().success();
+
+    let _ = find(root.path())
+        .arg("--path")
+        .arg("*.tiff")
+        .arg("obj-1")
+        .assert()
+        .success()
+        .stdout("obj-1\tv1\timages/image.tiff\nobj-1\tv2\timages/image.tiff\n");
+    let _ = find(root.path())
+        .arg("--head")
+        .arg("-p")
+        .arg("*.tiff")
+        .assert()
+        .success()
+        .stdout(contains_str("obj-1\tv2\timages/image.tiff\n"))
+        .stdout(contains_str("obj-2\tv1\timages/image.tiff\n"))
+        .stdout(contains_str("v1\timages").count(1));
+    let _ = find(root.path())
+        .arg("--head")
+        .arg("-p")
+        .arg("*.txt")
+        .assert()
+        .success()
+        .stdout("obj-1\tv2\tnotes.txt\n");
+}
+
+#[test]
+fn frozen_objects_require_force_to_commit() {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let object_id = "frozen";
+
+    let _ = init(root.path()).assert().success();
+    let _ = new(root.path()).arg(object_id).assert().success();
+    let _ = commit(root.path()).arg(object_id).assert().success();
+
+    let _ = rocfl(root.path(), "freeze")
+        .arg("-r")
+        .arg("legal hold")
+        .arg(object_id)
+        .assert()
+        .success();
+
+    let _ = rocfl(root.path(), "info")
+        .arg(object_id)
+        .assert()
+        .success()
+        .stdout(contains_str("Frozen:"))
+        .stdout(contains_str("legal hold"));
+    let _ = list(root.path())
+        .arg("--frozen")
+        .assert()
+        .success()
+        .stdout(format!("{}\n", object_id));
+
+    let _ = copy(root.path())
+        .arg(object_id)
+        .arg(create_file(&temp, "file.txt", "blah").path())
+        .arg("--")
+        .arg("/")
+        .assert()
+        .success();
+
+    let _ = commit(root.path())
+        .arg(object_id)
+        .assert()
+        .failure()
+        .stderr(contains_str(
+            "Cannot commit because object frozen is frozen",
+        ));
+    let _ = commit(root.path())
+        .arg("--force")
+        .arg(object_id)
+        .assert()
+        .success();
+
+    let _ = rocfl(root.path(), "unfreeze")
+        .arg(object_id)
+        .assert()
+        .success();
+    let _ = list(root.path())
+        .arg("--frozen")
+        .assert()
+        .success()
+        .stdout("");
+    let _ = log(root.path())
+        .arg(object_id)
+        .assert()
+        .success()
+        .stdout(contains_str("Version 2"));
+}
+
+#[test]
+fn list_objects_with_fingerprints() {
+    let root = TempDir::new().unwrap();
+
+    let object_id = "fingerprint";
+
+    let _ = init(root.path()).assert().success();
+    let _ = new(root.path()).arg(object_id).assert().success();
+    let _ = commit(root.path()).arg(object_id).assert().success();
+
+    let output = list(root.path())
+        .arg("-t")
+        .arg("--fingerprint")
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let line = String::from_utf8(output).unwrap();
+    let (id, fingerprint) = line.trim_end().split_once('\t').unwrap();
+
+    assert_eq!(object_id, id);
+    assert!(fingerprint.starts_with("sha512:"));
+
+    let _ = list(root.path())
+        .args(["--format", "ndjson", "--fingerprint"])
+        .assert()
+        .success()
+        .stdout(contains_str(&format!(
+            "\"fingerprint\":\"{}\"",
+            fingerprint
+        )))
+        .stdout(contains_str("\"e_tag\":null"));
+}
+
+#[test]
+fn list_original_timestamps_of_copied_files() {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let object_id = "timestamps";
+
+    let dated = create_file(&temp, "dated.txt", "dated");
+    std::fs::File::options()
+        .write(true)
+        .open(dated.path())
+        .unwrap()
+        .set_modified(Local.with_ymd_and_hms(2001, 2, 3, 4, 5, 6).unwrap().into())
+        .unwrap();
+
+    let _ = init(root.path()).assert().success();
+    let _ = new(root.path()).arg(object_id).assert().success();
+    let _ = copy(root.path())
+        .arg(object_id)
+        .arg(create_file(&temp, "undated.txt", "undated").path())
+        .arg("--")
+        .arg("/")
+        .assert()
+        .success();
+    let _ = copy(root.path())
+        .arg("--preserve-timestamps")
+        .arg(object_id)
+        .arg(dated.path())
+        .arg("--")
+        .arg("/")
+        .assert()
+        .success();
+    let _ = commit(root.path()).arg(object_id).assert().success();
+
+    let _ = list(root.path())
+        .arg("-lt")
+        .arg("--timestamps")
+        .arg("original")
+        .arg(object_id)
+        .arg("*.txt")
+        .assert()
+        .success()
+        .stdout(contains_str("v1\t2001-02-03 04:05\tdated.txt\n"))
+        .stdout(contains_str("2001").count(1));
+    let _ = list(root.path())
+        .arg("--timestamps")
+        .arg("original")
+        .arg(object_id)
+        .assert()
+        .failure();
+}
+
+#[test]
+fn doctor_reports_problems_with_fixes() {
+    let root = TempDir::new().unwrap();
+
+    let _ = init(root.path()).assert().success();
+    let _ = new(root.path()).arg("obj").assert().success();
+    let _ = commit(root.path()).arg("obj").assert().success();
+
+    let _ = rocfl(root.path(), "doctor")
+        .assert()
+        .success()
+        .stdout("Checked 1 object(s) and 0 staged object(s). No problems found\n");
+
+    let lock = root
+        .path()
+        .join("extensions/rocfl-staging/extensions/rocfl-locks/stale.lock");
+    std::fs::File::create(&lock)
+        .unwrap()
+        .set_modified(Local.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap().into())
+        .unwrap();
+
+    let _ = rocfl(root.path(), "doctor")
+        .arg("--lock-age")
+        .arg("30m")
+        .assert()
+        .code(1)
+        .stdout(contains_str("[stale-lock]"))
+        .stdout(contains_str("Fix: If rocfl is not running"))
+        .stdout(contains_str("Found 1 problem(s)"));
+}
+
+#[test]
+fn report_and_auto_clean_orphaned_locks() {
+    let root = TempDir::new().unwrap();
+
+    let _ = init(root.path()).assert().success();
+    let _ = new(root.path()).arg("obj").assert().success();
+
+    let lock = root
+        .path()
+        .join("extensions/rocfl-staging/extensions/rocfl-locks/stale.lock");
+    std::fs::File::create(&lock)
+        .unwrap()
+        .set_modified(Local.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap().into())
+        .unwrap();
+
+    // Orphans are only looked for before commands that write to the repository
+    let _ = list(root.path()).assert().success().stderr("");
+
+    let _ = new(root.path())
+        .arg("obj2")
+        .assert()
+        .success()
+        .stderr(contains_str("Found orphaned lock file at"))
+        .stderr(contains_str("Run with --auto-clean"));
+
+    assert!(lock.exists());
+
+    let _ = rocfl(root.path(), "--auto-clean")
+        .arg("ls")
+        .assert()
+        .success()
+        .stderr(contains_str(
+            "Removed 1 orphaned locks and staging directories",
+        ));
+
+    assert!(!lock.exists());
+
+    let _ = list(root.path()).assert().success().stderr("");
+}
+
+#[test]
+fn negating_flags_override_config_defaults() {
+    let root = TempDir::new().unwrap();
+    let config_home = TempDir::new().unwrap();
+
+    let _ = init(root.path())
+        .arg("-l")
+        .arg("0002-flat-direct-storage-layout")
+        .assert()
+        .success();
+
+    std::fs::create_dir_all(config_home.path().join("rocfl")).unwrap();
+    std::fs::write(
+        config_home.path().join("rocfl").join("config.toml"),
+        "[global]\npretty_print = true\n",
+    )
+    .unwrap();
+
+    for (object_id, flag, pretty) in [
+        ("pretty", None, true),
+        ("compact", Some("--no-pretty-print"), false),
+    ] {
+        let _ = new(root.path()).arg(object_id).assert().success();
+
+        let mut commit = rocfl(root.path(), "commit");
+        commit.env("XDG_CONFIG_HOME", config_home.path());
+        if let Some(flag) = flag {
+            commit.arg(flag);
+        }
+        let _ = commit.arg(object_id).assert().success();
+
+        let inventory =
+            std::fs::read_to_string(root.path().join(object_id).join("inventory.json")).unwrap();
+        assert_eq!(pretty, inventory.contains("\n  "), "{}", object_id);
+    }
+}
+
+#[test]
+fn sign_commits_and_verify_signatures() {
+    let root = TempDir::new().unwrap();
+    let config_home = TempDir::new().unwrap();
+    let keys = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("resources/test/keys");
+
+    let _ = init(root.path()).assert().success();
+    let _ = new(root.path()).arg("obj").assert().success();
+    let _ = commit(root.path()).arg("obj").assert().success();
+
+    std::fs::create_dir_all(config_home.path().join("rocfl")).unwrap();
+    std::fs::write(
+        config_home.path().join("rocfl").join("config.toml"),
+        format!(
+            "[global]\nsigning_key = {:?}\n",
+            keys.join("signing-key.pem").to_string_lossy()
+        ),
+    )
+    .unwrap();
+
+    let _ = rocfl(root.path(), "new")
+        .env("XDG_CONFIG_HOME", config_home.path())
+        .arg("signed")
+        .assert()
+        .success();
+    let _ = rocfl(root.path(), "commit")
+        .env("XDG_CONFIG_HOME", config_home.path())
+        .arg("signed")
+        .assert()
+        .success();
+
+    let _ = rocfl(root.path(), "verify-signatures")
+        .env("XDG_CONFIG_HOME", config_home.path())
+        .arg("signed")
+        .assert()
+        .success()
+        .stdout(contains_str("Object signed version v1: valid, signed by"))
+        .stdout(contains_str(
+            "Verified 1 version(s): 1 valid, 0 unsigned, 0 failed",
+        ));
+
+    let _ = rocfl(root.path(), "verify-signatures")
+        .arg("-k")
+        .arg(keys.join("signing-key.pub.pem"))
+        .assert()
+        .success()
+        .stdout(contains_str("Object obj version v1: unsigned"))
+        .stdout(contains_str(
+            "Verified 2 version(s): 1 valid, 1 unsigned, 0 failed",
+        ));
+
+    let _ = rocfl(root.path(), "verify-signatures")
+        .arg("-k")
+        .arg(keys.join("other-key.pub.pem"))
+        .arg("signed")
+        .assert()
+        .code(1)
+        .stdout(contains_str(
+            "Object signed version v1: untrusted, signed by",
+        ));
+
+    let _ = rocfl(root.path(), "verify-signatures")
+        .arg("obj")
+        .assert()
+        .failure()
+        .stderr(contains_str("--key"));
+}
+
+#[test]
+fn rebase_staged_changes_onto_current_version() {
+    let root = TempDir::new().unwrap();
+    let other_staging = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let other = |command: &str| {
+        let mut rocfl = Command::cargo_bin("rocfl").unwrap();
+        rocfl
+            .arg("-S")
+            .arg("-r")
+            .arg(root.path())
+            .arg("-s")
+            .arg(other_staging.path())
+            .arg(command);
+        rocfl
+    };
+
+    let _ = init(root.path()).assert().success();
+    let _ = new(root.path()).arg("obj").assert().success();
+    let _ = commit(root.path()).arg("obj").assert().success();
+
+    let _ = copy(root.path())
+        .arg("obj")
+        .arg(create_file(&temp, "a.txt", "a").path())
+        .arg("--")
+        .arg("/")
+        .assert()
+        .success();
+
+    let _ = other("cp")
+        .arg("obj")
+        .arg(create_file(&temp, "b.txt", "b").path())
+        .arg("--")
+        .arg("/")
+        .assert()
+        .success();
+
+    let _ = rocfl(root.path(), "rebase")
+        .arg("obj")
+        .assert()
+        .success()
+        .stdout("The staged changes to obj already follow its current version\n");
+
+    let _ = other("commit").arg("obj").assert().success();
+
+    let _ = commit(root.path())
+        .arg("obj")
+        .assert()
+        .failure()
+        .stderr(contains_str("the current version is at v2"));
+
+    let _ = rocfl(root.path(), "rebase")
+        .arg("obj")
+        .assert()
+        .success()
+        .stdout("Rebased the staged changes to obj from version v2 to version v3\n");
+
+    let _ = commit(root.path()).arg("obj").assert().success();
+
+    let _ = list(root.path())
+        .arg("obj")
+        .assert()
+        .success()
+        .stdout("a.txt\nb.txt\n");
+}
+
+#[test]
+fn watch_once_mirrors_directory_into_staged_version() {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let _ = init(root.path()).assert().success();
+    let _ = new(root.path()).arg("obj").assert().success();
+    let _ = commit(root.path()).arg("obj").assert().success();
+
+    create_file(&temp, "a.txt", "a");
+    create_file(&temp, "sub/b.txt", "b");
+
+    let _ = watch(root.path())
+        .arg("--once")
+        .arg(temp.path())
+        .arg("obj")
+        .assert()
+        .success()
+        .stdout("Staged 2 and removed 0 file(s) in object obj\n");
+
+    let _ = commit(root.path()).arg("obj").assert().success();
+
+    let _ = watch(root.path())
+        .arg("--once")
+        .arg(temp.path())
+        .arg("obj")
+        .assert()
+        .success()
+        .stdout(empty());
+
+    create_file(&temp, "a.txt", "updated");
+    create_file(&temp, "c[1].txt", "c");
+    std::fs::remove_dir_all(temp.path().join("sub")).unwrap();
+
+    let _ = watch(root.path())
+        .arg("--once")
+        .arg(temp.path())
+        .arg("obj")
+        .assert()
+        .success()
+        .stdout("Staged 2 and removed 1 file(s) in object obj\n");
+
+    let _ = commit(root.path()).arg("obj").assert().success();
+
+    let _ = list(root.path())
+        .arg("obj")
+        .assert()
+        .success()
+        .stdout("a.txt\nc[1].txt\n");
+
+    let _ = cat(root.path())
+        .arg("obj")
+        .arg("a.txt")
+        .assert()
+        .success()
+        .stdout("updated");
+}
+
+#[test]
+fn dry_run_reports_changes_without_making_them() {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let _ = dry_run(root.path(), "init")
+        .assert()
+        .success()
+        .stdout(contains_str("Dry run: init"))
+        .stdout(contains_str("write 0=ocfl_1.1"));
+    assert!(!root.path().join("0=ocfl_1.1").exists());
+
+    let _ = init(root.path()).assert().success();
+    let _ = new(root.path()).arg("obj").assert().success();
+    let _ = commit(root.path()).arg("obj").assert().success();
+
+    let _ = dry_run(root.path(), "cp")
+        .arg("obj")
+        .arg(create_file(&temp, "file.txt", "content").path())
+        .arg("--")
+        .arg("/")
+        .assert()
+        .success()
+        .stdout("Dry run: copy obj\n  add file.txt\n  stage v2/content/file.txt\n");
+
+    let _ = status(root.path()).assert().success().stdout(empty());
+
+    let _ = copy(root.path())
+        .arg("obj")
+        .arg(temp.path().join("file.txt"))
+        .arg("--")
+        .arg("/")
+        .assert()
+        .success();
+
+    let _ = dry_run(root.path(), "commit")
+        .arg("obj")
+        .assert()
+        .success()
+        .stdout(contains_str(
+            "Dry run: commit obj\n  create version v2\n  add file.txt\n  write ",
+        ))
+        .stdout(contains_str("/v2/content/file.txt\n"))
+        .stdout(contains_str("/inventory.json\n"));
+
+    let _ = log(root.path())
+        .arg("obj")
+        .assert()
+        .success()
+        .stdout(contains_str("v2").not());
+
+    let _ = dry_run(root.path(), "new")
+        .arg("other")
+        .assert()
+        .failure()
+        .stderr(contains_str("dry-run mode"));
+}
+
+fn init(path: impl AsRef<Path>) -> Command {
+    rocfl(path, "init")
+}
+
+fn new(path: impl AsRef<Path>) -> Command {
+    rocfl(path, "new")
+}
+
+fn copy(path: impl AsRef<Path>) -> Command {
+    rocfl(path, "cp")
+}
+
+fn mv(path: impl AsRef<Path>) -> Command {
+    rocfl(path, "mv")
+}
+
+fn commit(path: impl AsRef<Path>) -> Command {
+    rocfl(path, "commit")
+}
+
+fn log(path: impl AsRef<Path>) -> Command {
+    rocfl(path, "log")
+}
+
+fn cat(path: impl AsRef<Path>) -> Command {
+    rocfl(path, "cat")
+}
+
+fn show(path: impl AsRef<Path>) -> Command {
+    rocfl(path, "show")
+}
+
+fn tree(path: impl AsRef<Path>) -> Command {
+    rocfl(path, "tree")
+}
+
+fn watch(path: impl AsRef<Path>) -> Command {
+    rocfl(path, "watch")
+}
+
+fn gc(path: impl AsRef<Path>) -> Command {
+    rocfl(path, "gc")
+}
+
+fn find(path: impl AsRef<Path>) -> Command {
+    rocfl(path, "find")
+}
+
+fn completions(path: impl AsRef<Path>) -> Command {
+    rocfl(path, "completions")
+}
+
+fn layout(path: impl AsRef<Path>) -> Command {
+    rocfl(path, "layout")
+}
+
+fn meta(path: impl AsRef<Path>) -> Command {
+    rocfl(path, "meta")
+}
+
+fn list(path: impl AsRef<Path>) -> Command {
+    rocfl(path, "ls")
+}
+
+fn status(path: impl AsRef<Path>) -> Command {
+    rocfl(path, "status")
+}
+
+fn validate(path: impl AsRef<Path>) -> Command {
+    rocfl(path, "validate")
+}
+
+#[test]
+fn run_command_against_multiple_named_repos() {
+    let root_a = TempDir::new().unwrap();
+    let root_b = TempDir::new().unwrap();
+    let config_home = TempDir::new().unwrap();
+
+    let _ = init(root_a.path()).assert().success();
+    let _ = init(root_b.path()).assert().success();
+    let _ = new(root_a.path()).arg("obj-a").assert().success();
+    let _ = commit(root_a.path()).arg("obj-a").assert().success();
+    let _ = new(root_b.path()).arg("obj-b").assert().success();
+    let _ = commit(root_b.path()).arg("obj-b").assert().success();
+
+    std::fs::create_dir_all(config_home.path().join("rocfl")).unwrap();
+    std::fs::write(
+        config_home.path().join("rocfl").join("config.toml"),
+        format!(
+            "[a]\nroot = {:?}\n\n[b]\nroot = {:?}\n",
+            root_a.path().to_string_lossy(),
+            root_b.path().to_string_lossy()
+        ),
+    )
+    .unwrap();
+
+    let _ = Command::cargo_bin("rocfl")
+        .unwrap()
+        .env("XDG_CONFIG_HOME", config_home.path())
+        .args(["-S", "-n", "a,b", "ls"])
+        .assert()
+        .success()
+        .stdout(contains_str("==> a <==\nobj-a\n\n==> b <==\nobj-b"));
+
+    let _ = Command::cargo_bin("rocfl")
+        .unwrap()
+        .env("XDG_CONFIG_HOME", config_home.path())
+        .args(["-S", "-n", "a,missing,b", "ls"])
+        .assert()
+        .code(1)
+        .stdout(contains_str("==> a <==\nobj-a"))
+        .stdout(contains_str("==> b <==\nobj-b"))
+        .stderr(contains_str("[missing]"))
+        .stderr(contains_str(
+            "Command failed in 1 of 3 repositories: missing",
+        ));
+
+    let _ = Command::cargo_bin("rocfl")
+        .unwrap()
+        .env("XDG_CONFIG_HOME", config_home.path())
+        .args(["-S", "-n", "a,b", "-r"])
+        .arg(root_a.path())
+        .arg("ls")
+        .assert()
+        .failure()
+        .stderr(contains_str(
+            "may not be used with multiple repository names",
+        ));
+}
+
+#[test]
+fn export_objects_to_content_addressed_directory() {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+    let dst = TempDir::new().unwrap();
+
+    let _ = init(root.path()).assert().success();
+
+    std::fs::write(temp.path().join("a.txt"), "shared").unwrap();
+
+    for object_id in ["obj-1", "obj-2"] {
+        let _ = new(root.path()).arg(object_id).assert().success();
+        let _ = copy(root.path())
+            .arg(object_id)
+            .arg(temp.path().join("a.txt"))
+            .arg("--")
+            .arg("a.txt")
+            .assert()
+            .success();
+        let _ = commit(root.path()).arg(object_id).assert().success();
+    }
+
+    let _ = rocfl(root.path(), "export")
+        .arg("--cas")
+        .arg(dst.path())
+        .assert()
+        .success()
+        .stdout(contains_str("Wrote 1 files (6 bytes) and skipped 1 files"));
+
+    assert!(dst.path().join("objects").join("obj-1.json").exists());
+    assert!(dst.path().join("objects").join("obj-2.json").exists());
+
+    let _ = rocfl(root.path(), "export")
+        .arg("--cas")
+        .arg(dst.path())
+        .arg("obj-1")
+        .assert()
+        .success()
+        .stdout(contains_str("Wrote 0 files (0 bytes) and skipped 1 files"));
+
+    let _ = rocfl(root.path(), "export")
+        .arg(dst.path())
+        .assert()
+        .failure();
+}
+
+#[test]
+fn commit_retry_rebases_stale_staged_changes() {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+    let other_staging = TempDir::new().unwrap();
+
+    let _ = init(root.path()).assert().success();
+    let _ = new(root.path()).arg("obj").assert().success();
+    let _ = commit(root.path()).arg("obj").assert().success();
+
+    std::fs::write(temp.path().join("a.txt"), "a").unwrap();
+    std::fs::write(temp.path().join("b.txt"), "b").unwrap();
+
+    let _ = copy(root.path())
+        .arg("obj")
+        .arg(temp.path().join("a.txt"))
+        .arg("--")
+        .arg("a.txt")
+        .assert()
+        .success();
+
+    let other = |command: &str| {
+        let mut rocfl = Command::cargo_bin("rocfl").unwrap();
+        rocfl
+            .arg("-S")
+            .arg("-r")
+            .arg(root.path())
+            .arg("-s")
+            .arg(other_staging.path())
+            .arg(command);
+        rocfl
+    };
+
+    let _ = other("cp")
+        .arg("obj")
+        .arg(temp.path().join("b.txt"))
+        .arg("--")
+        .arg("b.txt")
+        .assert()
+        .success();
+    let _ = other("commit").arg("obj").assert().success();
+
+    let _ = commit(root.path())
+        .arg("obj")
+        .assert()
+        .failure()
+        .stderr(contains_str(
+            "Cannot create version v2 in object obj because the current version is at v2",
+        ));
+
+    let _ = commit(root.path())
+        .arg("--retry")
+        .arg("obj")
+        .assert()
+        .success()
+        .stdout(contains_str(
+            "Rebased the staged changes onto version v2 as version v3",
+        ));
+
+    let _ = rocfl(root.path(), "ls")
+        .arg("obj")
+        .assert()
+        .success()
+        .stdout(contains_str("a.txt"))
+        .stdout(contains_str("b.txt"));
+}
+
+#[test]
+fn squash_object_versions() {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let _ = init(root.path()).assert().success();
+    let _ = new(root.path()).arg("obj").assert().success();
+
+    for content in ["1", "2", "3"] {
+        std::fs::write(temp.path().join("a.txt"), content).unwrap();
+        let _ = copy(root.path())
+            .arg("obj")
+            .arg(temp.path().join("a.txt"))
+            .arg("--")
+            .arg("a.txt")
+            .assert()
+            .success();
+        let _ = commit(root.path()).arg("obj").assert().success();
+    }
+
+    let _ = rocfl(root.path(), "squash")
+        .args(["--through", "v2", "obj"])
+        .write_stdin("n\n")
+        .assert()
+        .success()
+        .stdout(contains_str("Aborted"));
+
+    let _ = dry_run(root.path(), "squash")
+        .args(["--through", "v2", "obj"])
+        .assert()
+        .success()
+        .stdout(contains_str("rewrite version v3 as v2"))
+        .stdout(contains_str("/v1/content/a.txt"));
+
+    let _ = rocfl(root.path(), "squash")
+        .args(["-f", "--through", "v2", "obj"])
+        .assert()
+        .success()
+        .stdout(contains_str("v1 -> v1\nv2 -> v1\nv3 -> v2"))
+        .stdout(contains_str(
+            "Squashed object obj through v2, deleting 1 content file(s)",
+        ));
+
+    let _ = log(root.path())
+        .arg("obj")
+        .assert()
+        .success()
+        .stdout(contains_str("Version 2"))
+        .stdout(contains_str("Version 3").not());
+
+    let _ = rocfl(root.path(), "validate").arg("obj").assert().success();
+}
+
+fn rocfl(path: impl AsRef<Path>, command: &str) -> Command {
+    let mut rocfl = Command::cargo_bin("rocfl").unwrap();
+    rocfl
+        .arg("-S")
+        .arg("-r")
+        .arg(path.as_ref().to_string_lossy().as_ref())
+        .arg(command);
+    rocfl
+}
+
+fn dry_run(path: impl AsRef<Path>, command: &str) -> Command {
+    let mut rocfl = Command::cargo_bin("rocfl").unwrap();
+    rocfl
+        .arg("-S")
+        .arg("-r")
+        .arg(path.as_ref().to_string_lossy().as_ref())
+        .arg("--dry-run")
+        .arg(command);
+    rocfl
+}
+
+fn json_lines(stdout: Vec<u8>) -> Vec<serde_json::Value> {
+    String::from_utf8(stdout)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+}
+
+fn contains_str(string: &str) -> ContainsPredicate {
+    predicates::str::contains(string)
+}
+
+fn marked(marker: &str, path: &str) -> RegexPredicate {
+    predicates::str::is_match(format!(r"(?m)^{}\s*\t{}$", marker, regex::escape(path))).unwrap()
+}
+
+fn empty() -> IsEmptyPredicate {
+    predicates::str::is_empty()
+}
+
+fn repo_root(name: &str) -> PathBuf {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    path.push("resources");
+    path.push("test");
+    path.push("repos");
+    path.push(name);
+    path
+}
+
+fn validate_repo_root(name: &str) -> PathBuf {
+    let mut path = validate_root();
+    path.push("custom");
+    path.push("repos");
+    path.push(name);
+    path
+}
+
+fn validate_root() -> PathBuf {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    path.push("resources");
+    path.push("test");
+    path.push("validate");
     path
 }
@@ -1,19 +1,31 @@
 use std::convert::{TryFrom, TryInto};
 use std::fs;
 use std::fs::File;
+use std::io::{Cursor, Write};
 use std::path::{Path, PathBuf};
 use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
 
 use assert_fs::prelude::*;
 use assert_fs::TempDir;
 use chrono::{DateTime, Local, TimeZone};
 use common::*;
+use flate2::write::GzEncoder;
+use flate2::Compression;
 use fs_extra::dir::CopyOptions;
-use maplit::hashmap;
+use maplit::{btreemap, hashmap};
+use rocfl::config::Config;
 use rocfl::ocfl::{
-    CommitMeta, ContentPath, Diff, DigestAlgorithm, FileDetails, InventoryPath,
-    LayoutExtensionName, ObjectVersion, ObjectVersionDetails, OcflRepo, Result, RocflError,
-    SpecVersion, StorageLayout, ValidationResult, VersionDetails, VersionNum, VersionRef,
+    CalloutFile, CalloutVerdict, CasManifest, CommitMeta, CommitQuotas, ContentPath, Diff,
+    DigestAlgorithm, EncryptionKey, ErrorCode, FileDetails, FixityCheck, FixitySample, HealthCheck,
+    InventoryPath, LayoutExtensionName, ListFilter, LogicalPath, ObjectAlias, ObjectDiff,
+    ObjectError, ObjectTemplate, ObjectValidationResult, ObjectVersion, ObjectVersionDetails,
+    OcflRepo, OrphanKind, PatchChange, PlannedChange, RenameDetection, Result, RocflError,
+    SignatureStatus, SigningKey, SpecVersion, StorageLayout, SymlinkPolicy, ValidationRecord,
+    ValidationResult, VerifyingKey, VersionContentSize, VersionDetails, VersionDiff, VersionNum,
+    VersionPage, VersionRange, VersionRef, WarnCode,
 };
 
 mod common;
@@ -132,6 +144,47 @@ fn list_single_object_from_glob() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn list_repo_with_invalid_objects_identifies_unreadable_objects() -> Result<()> {
+    let repo_root = create_repo_root("invalid");
+    let repo = OcflRepo::fs_repo(&repo_root, None)?;
+
+    let mut errors: Vec<ObjectError> = repo
+        .list_objects(None)?
+        .filter_map(|object| object.err())
+        .collect();
+
+    errors.sort_unstable_by(|a, b| a.path.cmp(&b.path));
+
+    assert_eq!(2, errors.len());
+    assert_eq!(
+        Some(
+            repo_root
+                .join("235")
+                .join("2da")
+                .join("728")
+                .join("2352da7280f1decc3acf1ba84eb945c9fc2b7b541094e1d0992dbffd1b6664cc")
+                .to_string_lossy()
+                .to_string()
+        ),
+        errors[0].path
+    );
+    assert_eq!(
+        Some(
+            repo_root
+                .join("de2")
+                .join("d91")
+                .join("dc0")
+                .join("de2d91dc0a2580414e9a70f7dfc76af727b69cac0838f2cbe0a88d12642efcbf")
+                .to_string_lossy()
+                .to_string()
+        ),
+        errors[1].path
+    );
+
+    Ok(())
+}
+
 #[test]
 fn list_empty_repo() -> Result<()> {
     let repo_root = create_repo_root("empty");
@@ -172,6 +225,32 @@ fn list_repo_with_invalid_objects() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn list_object_ids_with_prefix() -> Result<()> {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let repo = default_repo(root.path());
+
+    for object_id in ["urn:a:2", "urn:a:1", "urn:b:1", "other"] {
+        create_simple_object(object_id, &repo, &temp);
+    }
+    repo.create_object("urn:a:staged", None, DigestAlgorithm::Sha512, "content", 0)?;
+
+    assert_eq!(
+        vec!["other", "urn:a:1", "urn:a:2", "urn:b:1"],
+        repo.list_object_ids("", 10)?
+    );
+    assert_eq!(
+        vec!["urn:a:1", "urn:a:2"],
+        repo.list_object_ids("urn:a", 10)?
+    );
+    assert_eq!(1, repo.list_object_ids("urn:", 1)?.len());
+    assert!(repo.list_object_ids("missing", 10)?.is_empty());
+
+    Ok(())
+}
+
 #[test]
 fn get_object_when_exists() -> Result<()> {
     let repo_root = create_repo_root("multiple-objects");
@@ -407,6 +486,29 @@ fn list_versions_when_multiple() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn list_versions_page() -> Result<()> {
+    let repo_root = create_repo_root("multiple-objects");
+    let repo = OcflRepo::fs_repo(&repo_root, None)?;
+
+    let versions =
+        repo.list_object_versions_page("o2", VersionPage::new().with_reverse(true).with_limit(2))?;
+    assert_eq!(vec![o2_v3_details(), o2_v2_details()], versions);
+
+    let versions =
+        repo.list_object_versions_page("o2", VersionPage::new().with_offset(1).with_limit(1))?;
+    assert_eq!(vec![o2_v2_details()], versions);
+
+    let versions =
+        repo.list_object_versions_page("o2", VersionPage::new().with_reverse(true).with_offset(2))?;
+    assert_eq!(vec![o2_v1_details()], versions);
+
+    let versions = repo.list_object_versions_page("o2", VersionPage::new().with_offset(3))?;
+    assert!(versions.is_empty());
+
+    Ok(())
+}
+
 #[test]
 fn list_file_versions_when_multiple() -> Result<()> {
     let repo_root = create_repo_root("multiple-objects");
@@ -518,6 +620,319 @@ fn diff_version_not_exists() {
     repo.diff("o1", None, 2.try_into().unwrap()).unwrap();
 }
 
+#[test]
+fn diff_reports_renames_as_configured() -> Result<()> {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let repo = default_repo(root.path());
+
+    let object_id = "renames";
+
+    repo.create_object(object_id, None, DigestAlgorithm::Sha512, "content", 0)?;
+    create_file(&temp, "a.txt", "single");
+    create_file(&temp, "b.txt", "double");
+    create_file(&temp, "c.txt", "double");
+    repo.copy_files_external(
+        object_id,
+        &[
+            temp.child("a.txt").path(),
+            temp.child("b.txt").path(),
+            temp.child("c.txt").path(),
+        ],
+        "/",
+        false,
+    )?;
+    commit(object_id, &repo);
+
+    repo.move_files_internal(object_id, &["a.txt"], "a2.txt")?;
+    repo.move_files_internal(object_id, &["b.txt", "c.txt"], "dir/")?;
+    commit(object_id, &repo);
+
+    let v2 = VersionNum::try_from(2)?;
+
+    let mut diff = repo.diff_with_renames(object_id, None, v2, RenameDetection::All)?;
+    sort_diffs(&mut diff);
+    assert_eq!(
+        vec![
+            Diff::Renamed {
+                original: vec![lpath_rc("a.txt")],
+                renamed: vec![lpath_rc("a2.txt")],
+            },
+            Diff::Renamed {
+                original: vec![lpath_rc("b.txt"), lpath_rc("c.txt")],
+                renamed: vec![lpath_rc("dir/b.txt"), lpath_rc("dir/c.txt")],
+            },
+        ],
+        diff
+    );
+
+    let mut diff = repo.diff_with_renames(object_id, None, v2, RenameDetection::OneToOne)?;
+    sort_diffs(&mut diff);
+    assert_eq!(
+        vec![
+            Diff::Renamed {
+                original: vec![lpath_rc("a.txt")],
+                renamed: vec![lpath_rc("a2.txt")],
+            },
+            Diff::Deleted(lpath_rc("b.txt")),
+            Diff::Deleted(lpath_rc("c.txt")),
+            Diff::Added(lpath_rc("dir/b.txt")),
+            Diff::Added(lpath_rc("dir/c.txt")),
+        ],
+        diff
+    );
+
+    let mut diff = repo.diff_with_renames(object_id, None, v2, RenameDetection::Off)?;
+    sort_diffs(&mut diff);
+    assert_eq!(
+        vec![
+            Diff::Deleted(lpath_rc("a.txt")),
+            Diff::Added(lpath_rc("a2.txt")),
+            Diff::Deleted(lpath_rc("b.txt")),
+            Diff::Deleted(lpath_rc("c.txt")),
+            Diff::Added(lpath_rc("dir/b.txt")),
+            Diff::Added(lpath_rc("dir/c.txt")),
+        ],
+        diff
+    );
+
+    Ok(())
+}
+
+#[test]
+fn diff_steps_returns_changes_in_each_version_in_range() -> Result<()> {
+    let repo_root = create_repo_root("multiple-objects");
+    let repo = OcflRepo::fs_repo(&repo_root, None)?;
+
+    let mut steps = repo
+        .diff_steps("o2", "v1..v3".parse()?, RenameDetection::All)?
+        .collect::<Result<Vec<VersionDiff>>>()?;
+
+    assert_eq!(2, steps.len());
+
+    let mut step = steps.remove(0);
+    sort_diffs(&mut step.diffs);
+    assert_eq!(VersionNum::try_from(2)?, step.version_num);
+    assert_eq!(
+        vec![
+            Diff::Added(lpath_rc("dir1/file3")),
+            Diff::Renamed {
+                original: vec![lpath_rc("file1")],
+                renamed: vec![lpath_rc("dir3/file1")],
+            },
+        ],
+        step.diffs
+    );
+
+    let mut step = steps.remove(0);
+    sort_diffs(&mut step.diffs);
+    assert_eq!(VersionNum::try_from(3)?, step.version_num);
+    assert_eq!(
+        vec![
+            Diff::Modified(lpath_rc("dir1/file3")),
+            Diff::Deleted(lpath_rc("dir3/file1")),
+        ],
+        step.diffs
+    );
+
+    Ok(())
+}
+
+#[test]
+fn diff_steps_fails_when_range_is_invalid() -> Result<()> {
+    let repo_root = create_repo_root("multiple-objects");
+    let repo = OcflRepo::fs_repo(&repo_root, None)?;
+
+    assert!(matches!(
+        "v2..v2".parse::<VersionRange>(),
+        Err(RocflError::InvalidValue(_))
+    ));
+    assert!(matches!(
+        repo.diff_steps("o2", "v1..v4".parse()?, RenameDetection::All),
+        Err(RocflError::NotFound(_))
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn apply_patch_replays_version_changes_onto_another_object() -> Result<()> {
+    let root = TempDir::new().unwrap();
+    let mirror_root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let repo = default_repo(root.path());
+    let mirror_repo = default_repo(mirror_root.path());
+
+    let source_id = "source";
+    let mirror_id = "mirror";
+
+    let a = create_file(&temp, "a.txt", "moved");
+    let b = create_file(&temp, "b.txt", "deleted");
+    let c = create_file(&temp, "c.txt", "original");
+    let v1_files = [a.path(), b.path(), c.path()];
+
+    for (repo, object_id) in [
+        (&repo, source_id),
+        (&repo, mirror_id),
+        (&mirror_repo, mirror_id),
+    ] {
+        repo.create_object(object_id, None, DigestAlgorithm::Sha512, "content", 0)?;
+        repo.copy_files_external(object_id, &v1_files, "/", false)?;
+        commit(object_id, repo);
+    }
+
+    create_file(&temp, "c.txt", "modified");
+    let d = create_file(&temp, "d.txt", "added");
+    repo.move_files_internal(source_id, &["a.txt"], "dir/a.txt")?;
+    repo.remove_files(source_id, &["b.txt"], false)?;
+    repo.copy_files_external(source_id, &[c.path(), d.path()], "/", false)?;
+    commit(source_id, &repo);
+
+    let v2 = VersionNum::try_from(2)?;
+
+    let patch = repo.create_patch(source_id, None, v2, RenameDetection::All, false)?;
+    assert_eq!(Some(VersionNum::v1()), patch.from_version);
+    assert_eq!(4, patch.changes.len());
+
+    repo.apply_patch(mirror_id, &patch)?;
+    commit(mirror_id, &repo);
+
+    assert!(repo
+        .compare_objects(source_id, VersionRef::Head, mirror_id, VersionRef::Head)?
+        .is_empty());
+
+    match mirror_repo.apply_patch(mirror_id, &patch) {
+        Err(RocflError::CopyMoveError(e)) => assert_eq!(2, e.0.len()),
+        other => panic!("Expected a copy/move error; got: {:?}", other),
+    }
+    mirror_repo.reset_all(mirror_id)?;
+
+    let patch = repo.create_patch(source_id, None, v2, RenameDetection::All, true)?;
+    mirror_repo.apply_patch(mirror_id, &patch)?;
+    commit(mirror_id, &mirror_repo);
+
+    let mirror = mirror_repo.get_object(mirror_id, VersionRef::Head)?;
+    let mut paths: Vec<String> = mirror.state.keys().map(|path| path.to_string()).collect();
+    paths.sort();
+    assert_eq!(vec!["c.txt", "d.txt", "dir/a.txt"], paths);
+
+    let mut out: Vec<u8> = Vec::new();
+    mirror_repo.get_object_file(mirror_id, &lpath("c.txt"), VersionRef::Head, &mut out)?;
+    assert_eq!("modified", String::from_utf8(out).unwrap());
+
+    Ok(())
+}
+
+#[test]
+fn apply_patch_does_not_stage_referenced_content_that_does_not_match_digest() -> Result<()> {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let repo = default_repo(root.path());
+
+    let source_id = "source";
+    let mirror_id = "mirror";
+
+    let a = create_file(&temp, "a.txt", "original");
+
+    for object_id in [source_id, mirror_id] {
+        repo.create_object(object_id, None, DigestAlgorithm::Sha512, "content", 0)?;
+        repo.copy_files_external(object_id, &[a.path()], "/", false)?;
+        commit(object_id, &repo);
+    }
+
+    create_file(&temp, "a.txt", "modified");
+    repo.copy_files_external(source_id, &[a.path()], "/", false)?;
+    commit(source_id, &repo);
+
+    let mut patch = repo.create_patch(
+        source_id,
+        None,
+        VersionNum::try_from(2)?,
+        RenameDetection::All,
+        false,
+    )?;
+    for change in &mut patch.changes {
+        if let PatchChange::Modify(file) = change {
+            file.digest = DigestAlgorithm::Sha512
+                .hash_hex(&mut "bogus".as_bytes())?
+                .to_string();
+        }
+    }
+
+    match repo.apply_patch(mirror_id, &patch) {
+        Err(RocflError::CopyMoveError(e)) => {
+            assert_eq!(1, e.0.len());
+            assert!(e.0[0].contains("Expected content to have"), "{}", e.0[0]);
+        }
+        other => panic!("Expected a copy/move error; got: {:?}", other),
+    }
+
+    let mut out: Vec<u8> = Vec::new();
+    repo.get_staged_object_file(mirror_id, &lpath("a.txt"), &mut out)?;
+    assert_eq!("original", String::from_utf8(out).unwrap());
+
+    let staged = repo.get_staged_object(mirror_id)?;
+    let content_dir = Path::new(&staged.object_root).join("v2/content");
+    assert!(!content_dir.join("a.txt").exists());
+    assert!(!content_dir.join("a.txt.part").exists());
+
+    Ok(())
+}
+
+#[test]
+fn apply_patch_rejects_object_that_diverged_from_patch() -> Result<()> {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let repo = default_repo(root.path());
+
+    let source_id = "source";
+    let mirror_id = "mirror";
+
+    let a = create_file(&temp, "a.txt", "original");
+    let b = create_file(&temp, "b.txt", "deleted");
+
+    for object_id in [source_id, mirror_id] {
+        repo.create_object(object_id, None, DigestAlgorithm::Sha512, "content", 0)?;
+        repo.copy_files_external(object_id, &[a.path(), b.path()], "/", false)?;
+        commit(object_id, &repo);
+    }
+
+    create_file(&temp, "a.txt", "modified in source");
+    let c = create_file(&temp, "c.txt", "added");
+    repo.copy_files_external(source_id, &[a.path(), c.path()], "/", false)?;
+    repo.remove_files(source_id, &["b.txt"], false)?;
+    commit(source_id, &repo);
+
+    create_file(&temp, "a.txt", "modified in mirror");
+    repo.copy_files_external(mirror_id, &[a.path(), c.path()], "/", false)?;
+    commit(mirror_id, &repo);
+
+    let patch = repo.create_patch(
+        source_id,
+        None,
+        VersionNum::try_from(2)?,
+        RenameDetection::All,
+        true,
+    )?;
+
+    match repo.apply_patch(mirror_id, &patch) {
+        Err(RocflError::IllegalState(message)) => {
+            assert!(message.contains("a.txt has digest"), "{}", message);
+            assert!(message.contains("c.txt already exists"), "{}", message);
+            assert!(!message.contains("b.txt"), "{}", message);
+        }
+        other => panic!("Expected an illegal state error; got: {:?}", other),
+    }
+
+    assert!(repo.diff_staged(mirror_id)?.is_empty());
+
+    Ok(())
+}
+
 #[test]
 fn get_object_file_when_exists() -> Result<()> {
     let repo_root = create_repo_root("multiple-objects");
@@ -748,6 +1163,63 @@ fn create_1_1_object() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn describe_object_reports_content_directory_anomalies() -> Result<()> {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let repo = default_repo(root.path());
+
+    let object_id = "data-dir";
+
+    repo.create_object(
+        object_id,
+        Some(SpecVersion::Ocfl1_0),
+        DigestAlgorithm::Sha512,
+        "data",
+        0,
+    )?;
+
+    temp.child("test.txt").write_str("testing").unwrap();
+    repo.copy_files_external(
+        object_id,
+        &[temp.child("test.txt").path()],
+        "test.txt",
+        false,
+    )?;
+
+    commit(object_id, &repo);
+
+    let info = repo.describe_object(object_id)?;
+
+    assert_eq!(Some("data".to_string()), info.content_directory);
+    assert!(info.content_dir_anomalies.is_empty());
+
+    // Simulate a third-party object that does not declare the content directory it uses
+    let object_root = PathBuf::from(repo.get_object(object_id, VersionRef::Head)?.object_root);
+    let mut inventory: serde_json::Value =
+        serde_json::from_slice(&fs::read(object_root.join("inventory.json"))?).unwrap();
+    inventory
+        .as_object_mut()
+        .unwrap()
+        .remove("contentDirectory");
+    fs::write(object_root.join("inventory.json"), inventory.to_string())?;
+
+    let info = repo.describe_object(object_id)?;
+
+    assert_eq!(Some("content".to_string()), info.content_directory);
+    assert_eq!(
+        vec!["1 content path(s) are in 'data' directories rather than 'content'"],
+        info.content_dir_anomalies
+    );
+
+    let mut out: Vec<u8> = Vec::new();
+    repo.get_object_file(object_id, &lpath("test.txt"), VersionRef::Head, &mut out)?;
+    assert_eq!("testing", String::from_utf8(out).unwrap());
+
+    Ok(())
+}
+
 #[test]
 #[should_panic(expected = "must be empty")]
 fn fail_new_repo_creation_when_non_empty_root() {
@@ -1368,7 +1840,7 @@ fn copy_should_partially_succeed_when_multiple_src_and_some_fail() {
     match result {
         Err(RocflError::CopyMoveError(e)) => {
             assert_eq!(1, e.0.len());
-            assert!(e.0.get(0).unwrap().contains("bogus: Does not exist"));
+            assert!(e.0.first().unwrap().contains("bogus: Does not exist"));
         }
         _ => panic!("Expected copy to return an error"),
     }
@@ -1390,47 +1862,131 @@ fn copy_should_partially_succeed_when_multiple_src_and_some_fail() {
 }
 
 #[test]
-fn copy_multiple_sources() -> Result<()> {
+fn copy_and_move_many_files_on_multiple_threads() -> Result<()> {
     let root = TempDir::new().unwrap();
     let temp = TempDir::new().unwrap();
 
-    let object_id = "missing";
+    let object_id = "threaded";
 
-    let repo = default_repo(root.path());
+    let repo = default_repo(root.path()).with_threads(4);
 
     repo.create_object(
         object_id,
         Some(SpecVersion::Ocfl1_0),
-        DigestAlgorithm::Sha256,
+        DigestAlgorithm::Sha512,
         "content",
         0,
     )?;
 
-    create_dirs(&temp, "a/b/c");
-    create_dirs(&temp, "a/d/e");
-    create_dirs(&temp, "a/f");
-    create_file(&temp, "a/file1.txt", "File One");
-    create_file(&temp, "a/b/file2.txt", "File Two");
-    create_file(&temp, "a/b/file3.txt", "File Three");
-    create_file(&temp, "a/b/c/file4.txt", "File Four");
-    create_file(&temp, "a/d/e/file5.txt", "File Five");
-    create_file(&temp, "a/f/file6.txt", "File Six");
+    for i in 0..30 {
+        create_file(
+            &temp,
+            &format!("many/file{}.txt", i),
+            &format!("File {}", i),
+        );
+        create_file(
+            &temp,
+            &format!("moved/file{}.txt", i),
+            &format!("Moved {}", i),
+        );
+    }
+    create_file(&temp, "p/x.txt", "first");
+    create_file(&temp, "q/x.txt", "second");
+    create_file(&temp, "r/c", "file c");
+    create_file(&temp, "s/c/y.txt", "conflict");
 
-    repo.copy_files_external(
+    // The later files with the same, or a conflicting, logical path are applied in order
+    let result = repo.copy_files_external(
         object_id,
         &[
-            resolve_child(&temp, "a/b").path(),
-            resolve_child(&temp, "a/d").path(),
-            resolve_child(&temp, "a/file1.txt").path(),
+            resolve_child(&temp, "many").path(),
+            resolve_child(&temp, "p/x.txt").path(),
+            resolve_child(&temp, "q/x.txt").path(),
+            resolve_child(&temp, "r/c").path(),
+            resolve_child(&temp, "s/c").path(),
         ],
-        "dst",
+        "/",
         true,
-    )?;
-
-    let staged_obj = repo.get_staged_object(object_id)?;
-    let staged_root = PathBuf::from(&staged_obj.object_root);
-
-    assert_eq!(5, staged_obj.state.len());
+    );
+
+    match result {
+        Err(RocflError::CopyMoveError(e)) => {
+            assert_eq!(1, e.0.len());
+            assert!(e.0[0].contains("y.txt"));
+        }
+        _ => panic!("Expected copy to return an error"),
+    }
+
+    repo.move_files_external(object_id, &[resolve_child(&temp, "moved").path()], "/")?;
+
+    let staged_obj = repo.get_staged_object(object_id)?;
+    assert_eq!(62, staged_obj.state.len());
+
+    let mut out = Vec::new();
+    repo.get_staged_object_file_verified(object_id, &lpath("x.txt"), &mut out)?;
+    assert_eq!("second", String::from_utf8(out).unwrap());
+
+    for i in 0..30 {
+        let mut out = Vec::new();
+        repo.get_staged_object_file_verified(
+            object_id,
+            &lpath(&format!("moved/file{}.txt", i)),
+            &mut out,
+        )?;
+        assert_eq!(format!("Moved {}", i), String::from_utf8(out).unwrap());
+    }
+
+    assert!(!resolve_child(&temp, "moved").path().exists());
+
+    commit(object_id, &repo);
+
+    no_errors(&repo.validate_object(object_id, true)?);
+
+    Ok(())
+}
+
+#[test]
+fn copy_multiple_sources() -> Result<()> {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let object_id = "missing";
+
+    let repo = default_repo(root.path());
+
+    repo.create_object(
+        object_id,
+        Some(SpecVersion::Ocfl1_0),
+        DigestAlgorithm::Sha256,
+        "content",
+        0,
+    )?;
+
+    create_dirs(&temp, "a/b/c");
+    create_dirs(&temp, "a/d/e");
+    create_dirs(&temp, "a/f");
+    create_file(&temp, "a/file1.txt", "File One");
+    create_file(&temp, "a/b/file2.txt", "File Two");
+    create_file(&temp, "a/b/file3.txt", "File Three");
+    create_file(&temp, "a/b/c/file4.txt", "File Four");
+    create_file(&temp, "a/d/e/file5.txt", "File Five");
+    create_file(&temp, "a/f/file6.txt", "File Six");
+
+    repo.copy_files_external(
+        object_id,
+        &[
+            resolve_child(&temp, "a/b").path(),
+            resolve_child(&temp, "a/d").path(),
+            resolve_child(&temp, "a/file1.txt").path(),
+        ],
+        "dst",
+        true,
+    )?;
+
+    let staged_obj = repo.get_staged_object(object_id)?;
+    let staged_root = PathBuf::from(&staged_obj.object_root);
+
+    assert_eq!(5, staged_obj.state.len());
 
     assert_file_details(
         staged_obj.state.get(&lpath("dst/file1.txt")).unwrap(),
@@ -1467,6 +2023,79 @@ fn copy_multiple_sources() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn copy_files_from_tar_archives() -> Result<()> {
+    let root = TempDir::new().unwrap();
+
+    let repo = default_repo(root.path());
+
+    let object_id = "archive";
+
+    repo.create_object(
+        object_id,
+        Some(SpecVersion::Ocfl1_0),
+        DigestAlgorithm::Sha512,
+        "content",
+        0,
+    )?;
+
+    let tar = tar_archive(&[
+        ("./test.txt", "testing"),
+        ("./nested/dir/", ""),
+        ("./nested/1.txt", "File 1"),
+    ]);
+    repo.copy_archive_external(object_id, tar.as_slice(), "another")?;
+
+    let mut gz = GzEncoder::new(Vec::new(), Compression::default());
+    gz.write_all(&tar_archive(&[("test.txt", "testing")]))?;
+    repo.copy_archive_external(object_id, gz.finish()?.as_slice(), "/")?;
+
+    let staged_obj = repo.get_staged_object(object_id)?;
+    let obj_root = PathBuf::from(&staged_obj.object_root);
+
+    assert_eq!(3, staged_obj.state.len());
+
+    assert_file_details(
+        staged_obj.state.get(&lpath("test.txt")).unwrap(),
+        &obj_root,
+        "v1/content/test.txt",
+        "521b9ccefbcd14d179e7a1bb877752870a6d620938b28a66a107eac6e6805b9d0989f45b57\
+                        30508041aa5e710847d439ea74cd312c9355f1f2dae08d40e41d50",
+    );
+    assert_file_details(
+        staged_obj.state.get(&lpath("another/test.txt")).unwrap(),
+        &obj_root,
+        "v1/content/another/test.txt",
+        "521b9ccefbcd14d179e7a1bb877752870a6d620938b28a66a107eac6e6805b9d0989f45b57\
+                        30508041aa5e710847d439ea74cd312c9355f1f2dae08d40e41d50",
+    );
+    assert_file_details(
+        staged_obj
+            .state
+            .get(&lpath("another/nested/1.txt"))
+            .unwrap(),
+        &obj_root,
+        "v1/content/another/nested/1.txt",
+        "9c614ba0d58c976d0b39f8f5536eb8af89fae745cbe3783ac2ca3e3055bb0b1e3687417a1d\
+                        1104288d2883a4368d3dacb9931460c6e523117ff3eaa28810481a",
+    );
+
+    match repo.copy_archive_external(
+        object_id,
+        tar_archive(&[("test.txt/conflict", "again")]).as_slice(),
+        "/",
+    ) {
+        Err(RocflError::CopyMoveError(_)) => (),
+        result => panic!("Expected a conflict error; found: {:?}", result),
+    }
+
+    commit(object_id, &repo);
+
+    no_errors(&repo.validate_object(object_id, true)?);
+
+    Ok(())
+}
+
 #[test]
 fn create_object_with_non_standard_config() {
     let root = TempDir::new().unwrap();
@@ -1512,6 +2141,113 @@ fn create_object_with_non_standard_config() {
     validate_repo(&repo);
 }
 
+#[test]
+fn create_object_from_template() -> Result<()> {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let repo = default_repo(root.path());
+
+    let object_id = "templated";
+    let metadata_path: LogicalPath = "metadata/rocfl.json".try_into()?;
+
+    create_file(&temp, "skeleton.xml", "<mets/>");
+    create_file(
+        &temp,
+        "template.toml",
+        r#"
+        digest_algorithm = "sha256"
+        content_directory = "data"
+
+        [metadata]
+        type = "mets"
+
+        [[files]]
+        path = "mets.xml"
+        source = "skeleton.xml"
+
+        [[files]]
+        path = "docs/README.txt"
+        content = "readme"
+        "#,
+    );
+
+    let template = ObjectTemplate::read(temp.child("template.toml").path())?;
+
+    repo.create_object_from_template(
+        object_id,
+        Some(SpecVersion::Ocfl1_0),
+        &template,
+        &metadata_path,
+    )?;
+
+    let object = repo.get_staged_object(object_id)?;
+
+    assert_eq!(DigestAlgorithm::Sha256, object.digest_algorithm);
+    assert_eq!(3, object.state.len());
+    assert!(object
+        .state
+        .get(&lpath("mets.xml"))
+        .unwrap()
+        .content_path
+        .as_str()
+        .contains("/data/"));
+
+    let mut out: Vec<u8> = Vec::new();
+    repo.get_staged_object_file(object_id, &lpath("mets.xml"), &mut out)?;
+    assert_eq!("<mets/>", String::from_utf8(out).unwrap());
+
+    let mut out: Vec<u8> = Vec::new();
+    repo.get_staged_object_file(object_id, &lpath("docs/README.txt"), &mut out)?;
+    assert_eq!("readme", String::from_utf8(out).unwrap());
+
+    assert_eq!(
+        btreemap! {"type".to_string() => "mets".to_string()},
+        repo.get_staged_object_metadata(object_id, &metadata_path)?
+    );
+
+    repo.commit(object_id, CommitMeta::new(), None, false)?;
+
+    validate_repo(&repo);
+    Ok(())
+}
+
+#[test]
+fn drop_staged_object_when_template_cannot_be_applied() -> Result<()> {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let repo = default_repo(root.path());
+
+    let template = ObjectTemplate::from_toml(&format!(
+        r#"
+        [[files]]
+        path = "a.txt"
+        content = "a"
+
+        [[files]]
+        path = "b.txt"
+        source = "{}"
+        "#,
+        temp.child("missing.txt").path().to_string_lossy()
+    ))?;
+
+    let result = repo.create_object_from_template(
+        "templated",
+        None,
+        &template,
+        &"metadata/rocfl.json".try_into()?,
+    );
+
+    assert!(result
+        .unwrap_err()
+        .to_string()
+        .contains("Failed to copy template file"));
+    assert_staged_obj_count(&repo, 0);
+
+    Ok(())
+}
+
 #[test]
 #[should_panic(expected = "Object IDs may not be blank")]
 fn reject_object_creation_with_empty_id() {
@@ -2200,7 +2936,7 @@ fn internal_copy_should_continue_on_partial_success() -> Result<()> {
             assert_eq!(1, e.0.len());
             assert!(e
                 .0
-                .get(0)
+                .first()
                 .unwrap()
                 .contains("does not contain any files at bogus.txt"));
         }
@@ -2225,8 +2961,8 @@ fn internal_copy_should_continue_on_partial_success() -> Result<()> {
         "4ccdbf78d368aed12d806efaf67fbce3300bca8e62a6f32716af2f447de1821e",
     );
 
-    assert!(staged_obj.state.get(&lpath("a/file1.txt")).is_some());
-    assert!(staged_obj.state.get(&lpath("a/file5.txt")).is_some());
+    assert!(staged_obj.state.contains_key(&lpath("a/file1.txt")));
+    assert!(staged_obj.state.contains_key(&lpath("a/file5.txt")));
 
     validate_repo(&repo);
     Ok(())
@@ -2751,7 +3487,7 @@ fn move_should_partially_succeed_when_multiple_src_and_some_fail() {
     match result {
         Err(RocflError::CopyMoveError(e)) => {
             assert_eq!(1, e.0.len());
-            assert!(e.0.get(0).unwrap().contains("bogus: Does not exist"));
+            assert!(e.0.first().unwrap().contains("bogus: Does not exist"));
         }
         _ => panic!("Expected copy to return an error"),
     }
@@ -2773,99 +3509,332 @@ fn move_should_partially_succeed_when_multiple_src_and_some_fail() {
 }
 
 #[test]
-fn fail_copy_when_conflicting_src() {
+#[cfg(unix)]
+fn copy_follows_symlinks_and_skips_cycles() -> Result<()> {
+    use std::os::unix::fs::symlink;
+
     let root = TempDir::new().unwrap();
     let temp = TempDir::new().unwrap();
 
-    let object_id = "conflicting source";
+    let object_id = "symlinks";
 
     let repo = default_repo(root.path());
 
     repo.create_object(
         object_id,
         Some(SpecVersion::Ocfl1_0),
-        DigestAlgorithm::Sha256,
+        DigestAlgorithm::Sha512,
         "content",
         0,
     )
     .unwrap();
 
-    create_file(&temp, "a/test", "1");
-    create_file(&temp, "b/test/testing.txt", "2");
-
-    match repo.copy_files_external(
-        object_id,
-        &[
-            resolve_child(&temp, "a/test").path(),
-            resolve_child(&temp, "b/test").path(),
-        ],
-        "/",
-        true,
-    ) {
-        Err(e) => {
-            assert!(e.to_string().ends_with(
-                "Illegal state: Conflicting logical path \
-            test/testing.txt: The path part test is an existing logical file"
-            ));
-        }
-        Ok(_) => panic!("Should have failed"),
-    }
+    create_file(&temp, "a/file1.txt", "File One");
+    create_file(&temp, "other/file2.txt", "File Two");
+    symlink(
+        resolve_child(&temp, "a/file1.txt").path(),
+        temp.child("a/link.txt").path(),
+    )?;
+    symlink(temp.child("other").path(), temp.child("a/dir-link").path())?;
+    symlink(temp.child("a").path(), temp.child("a/cycle").path())?;
 
-    let object = repo.get_staged_object(object_id).unwrap();
+    repo.copy_files_external(object_id, &[temp.child("a").path()], "/", true)?;
 
-    assert_eq!(1, object.state.len());
+    let staged_obj = repo.get_staged_object(object_id)?;
 
-    assert_file_details(
-        object.state.get(&lpath("test")).unwrap(),
-        &object.object_root,
-        "v1/content/test",
-        "6b86b273ff34fce19d6b804eff5a3f5747ada4eaa22f1d49c01e52ddb7875b4b",
-    );
+    assert_eq!(3, staged_obj.state.len());
+    assert!(staged_obj.state.contains_key(&lpath("a/file1.txt")));
+    assert!(staged_obj.state.contains_key(&lpath("a/link.txt")));
+    assert!(staged_obj
+        .state
+        .contains_key(&lpath("a/dir-link/file2.txt")));
 
-    validate_repo(&repo);
+    Ok(())
 }
 
 #[test]
-fn internal_move_single_existing_file() -> Result<()> {
+#[cfg(unix)]
+fn copy_fails_on_dangling_symlinks_when_following() {
+    use std::os::unix::fs::symlink;
+
     let root = TempDir::new().unwrap();
     let temp = TempDir::new().unwrap();
 
-    let object_id = "InternalMove";
+    let object_id = "symlinks";
 
     let repo = default_repo(root.path());
 
-    create_example_object(object_id, &repo, &temp);
+    repo.create_object(
+        object_id,
+        Some(SpecVersion::Ocfl1_0),
+        DigestAlgorithm::Sha512,
+        "content",
+        0,
+    )
+    .unwrap();
 
-    repo.move_files_internal(object_id, &["a/file1.txt"], "new/blah.txt")?;
+    create_file(&temp, "a/file1.txt", "File One");
+    symlink(temp.child("bogus").path(), temp.child("a/dangling").path()).unwrap();
+    symlink(temp.child("bogus").path(), temp.child("dangling").path()).unwrap();
 
-    let committed_obj = repo.get_object(object_id, VersionRef::Head)?;
-    let staged_obj = repo.get_staged_object(object_id)?;
+    let result = repo.copy_files_external(
+        object_id,
+        &[temp.child("a").path(), temp.child("dangling").path()],
+        "/",
+        true,
+    );
 
-    assert_eq!(7, staged_obj.state.len());
+    match result {
+        Err(RocflError::CopyMoveError(e)) => {
+            assert_eq!(2, e.0.len());
+            assert!(e.0.iter().any(|e| e.contains("a/dangling")));
+            assert!(e
+                .0
+                .iter()
+                .any(|e| e.contains("dangling: Symbolic link target does not exist")));
+        }
+        _ => panic!("Expected copy to return an error"),
+    }
 
-    assert_file_details(
-        staged_obj.state.get(&lpath("new/blah.txt")).unwrap(),
-        Path::new(&committed_obj.object_root),
-        "v1/content/a/file1.txt",
-        "7d9fe7396f8f5f9862bfbfff4d98877bf36cf4a44447078c8d887dcc2dab0497",
-    );
+    let staged_obj = repo.get_staged_object(object_id).unwrap();
 
-    assert!(staged_obj.state.get(&lpath("a/file1.txt")).is_none());
+    assert_eq!(1, staged_obj.state.len());
+    assert!(staged_obj.state.contains_key(&lpath("a/file1.txt")));
+}
 
-    commit(object_id, &repo);
+#[test]
+#[cfg(unix)]
+fn copy_skips_symlinks_when_not_following() -> Result<()> {
+    use std::os::unix::fs::symlink;
 
-    let committed_obj = repo.get_object(object_id, VersionRef::Head)?;
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
 
-    assert_eq!(7, committed_obj.state.len());
+    let object_id = "symlinks";
 
-    assert_file_details(
-        committed_obj.state.get(&lpath("new/blah.txt")).unwrap(),
+    let repo = default_repo(root.path());
+
+    repo.create_object(
+        object_id,
+        Some(SpecVersion::Ocfl1_0),
+        DigestAlgorithm::Sha512,
+        "content",
+        0,
+    )
+    .unwrap();
+    repo.set_symlink_policy(SymlinkPolicy::Skip);
+
+    create_file(&temp, "a/file1.txt", "File One");
+    symlink(
+        resolve_child(&temp, "a/file1.txt").path(),
+        temp.child("a/link.txt").path(),
+    )?;
+    symlink(temp.child("bogus").path(), temp.child("a/dangling").path())?;
+    symlink(temp.child("a").path(), temp.child("a/cycle").path())?;
+    symlink(
+        resolve_child(&temp, "a/file1.txt").path(),
+        temp.child("link.txt").path(),
+    )?;
+
+    repo.copy_files_external(
+        object_id,
+        &[temp.child("a").path(), temp.child("link.txt").path()],
+        "/",
+        true,
+    )?;
+
+    let staged_obj = repo.get_staged_object(object_id)?;
+
+    assert_eq!(1, staged_obj.state.len());
+    assert!(staged_obj.state.contains_key(&lpath("a/file1.txt")));
+
+    Ok(())
+}
+
+#[test]
+#[cfg(unix)]
+fn copy_rejects_symlinks_when_policy_is_error() {
+    use std::os::unix::fs::symlink;
+
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let object_id = "symlinks";
+
+    let repo = default_repo(root.path());
+
+    repo.create_object(
+        object_id,
+        Some(SpecVersion::Ocfl1_0),
+        DigestAlgorithm::Sha512,
+        "content",
+        0,
+    )
+    .unwrap();
+    repo.set_symlink_policy(SymlinkPolicy::Error);
+
+    create_file(&temp, "a/file1.txt", "File One");
+    symlink(
+        resolve_child(&temp, "a/file1.txt").path(),
+        temp.child("a/link.txt").path(),
+    )
+    .unwrap();
+    symlink(temp.child("bogus").path(), temp.child("a/dangling").path()).unwrap();
+
+    let result = repo.copy_files_external(object_id, &[temp.child("a").path()], "/", true);
+
+    match result {
+        Err(RocflError::CopyMoveError(e)) => {
+            assert_eq!(2, e.0.len());
+            assert!(e.0.iter().all(|e| e.ends_with("Is a symbolic link")));
+        }
+        _ => panic!("Expected copy to return an error"),
+    }
+
+    let staged_obj = repo.get_staged_object(object_id).unwrap();
+
+    assert_eq!(1, staged_obj.state.len());
+    assert!(staged_obj.state.contains_key(&lpath("a/file1.txt")));
+}
+
+#[test]
+#[cfg(unix)]
+fn move_copies_symlink_targets_and_removes_links() -> Result<()> {
+    use std::os::unix::fs::symlink;
+
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+    let outside = TempDir::new().unwrap();
+
+    let object_id = "symlinks";
+
+    let repo = default_repo(root.path());
+
+    repo.create_object(
+        object_id,
+        Some(SpecVersion::Ocfl1_0),
+        DigestAlgorithm::Sha512,
+        "content",
+        0,
+    )
+    .unwrap();
+
+    create_file(&outside, "target.txt", "Target");
+    create_file(&temp, "a/file1.txt", "File One");
+    symlink(
+        outside.child("target.txt").path(),
+        temp.child("a/link.txt").path(),
+    )?;
+
+    repo.move_files_external(object_id, &[temp.child("a").path()], "/")?;
+
+    let staged_obj = repo.get_staged_object(object_id)?;
+
+    assert_eq!(2, staged_obj.state.len());
+    assert!(staged_obj.state.contains_key(&lpath("a/file1.txt")));
+    assert!(fs::symlink_metadata(&staged_obj.state[&lpath("a/link.txt")].storage_path)?.is_file());
+
+    outside.child("target.txt").assert("Target");
+    assert!(temp.child("a/link.txt").path().symlink_metadata().is_err());
+    temp.child("a").assert(predicates::path::missing());
+
+    Ok(())
+}
+
+#[test]
+fn fail_copy_when_conflicting_src() {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let object_id = "conflicting source";
+
+    let repo = default_repo(root.path());
+
+    repo.create_object(
+        object_id,
+        Some(SpecVersion::Ocfl1_0),
+        DigestAlgorithm::Sha256,
+        "content",
+        0,
+    )
+    .unwrap();
+
+    create_file(&temp, "a/test", "1");
+    create_file(&temp, "b/test/testing.txt", "2");
+
+    match repo.copy_files_external(
+        object_id,
+        &[
+            resolve_child(&temp, "a/test").path(),
+            resolve_child(&temp, "b/test").path(),
+        ],
+        "/",
+        true,
+    ) {
+        Err(e) => {
+            assert!(e.to_string().ends_with(
+                "Illegal state: Conflicting logical path \
+            test/testing.txt: The path part test is an existing logical file"
+            ));
+        }
+        Ok(_) => panic!("Should have failed"),
+    }
+
+    let object = repo.get_staged_object(object_id).unwrap();
+
+    assert_eq!(1, object.state.len());
+
+    assert_file_details(
+        object.state.get(&lpath("test")).unwrap(),
+        &object.object_root,
+        "v1/content/test",
+        "6b86b273ff34fce19d6b804eff5a3f5747ada4eaa22f1d49c01e52ddb7875b4b",
+    );
+
+    validate_repo(&repo);
+}
+
+#[test]
+fn internal_move_single_existing_file() -> Result<()> {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let object_id = "InternalMove";
+
+    let repo = default_repo(root.path());
+
+    create_example_object(object_id, &repo, &temp);
+
+    repo.move_files_internal(object_id, &["a/file1.txt"], "new/blah.txt")?;
+
+    let committed_obj = repo.get_object(object_id, VersionRef::Head)?;
+    let staged_obj = repo.get_staged_object(object_id)?;
+
+    assert_eq!(7, staged_obj.state.len());
+
+    assert_file_details(
+        staged_obj.state.get(&lpath("new/blah.txt")).unwrap(),
+        Path::new(&committed_obj.object_root),
+        "v1/content/a/file1.txt",
+        "7d9fe7396f8f5f9862bfbfff4d98877bf36cf4a44447078c8d887dcc2dab0497",
+    );
+
+    assert!(!staged_obj.state.contains_key(&lpath("a/file1.txt")));
+
+    commit(object_id, &repo);
+
+    let committed_obj = repo.get_object(object_id, VersionRef::Head)?;
+
+    assert_eq!(7, committed_obj.state.len());
+
+    assert_file_details(
+        committed_obj.state.get(&lpath("new/blah.txt")).unwrap(),
         Path::new(&committed_obj.object_root),
         "v1/content/a/file1.txt",
         "7d9fe7396f8f5f9862bfbfff4d98877bf36cf4a44447078c8d887dcc2dab0497",
     );
 
-    assert!(committed_obj.state.get(&lpath("a/file1.txt")).is_none());
+    assert!(!committed_obj.state.contains_key(&lpath("a/file1.txt")));
 
     validate_repo(&repo);
     Ok(())
@@ -2908,9 +3877,9 @@ fn internal_move_multiple_existing_file() -> Result<()> {
         "b47592b10bc3e5c8ca8703d0862df10a6e409f43478804f93a08dd1844ae81b6",
     );
 
-    assert!(staged_obj.state.get(&lpath("a/file1.txt")).is_none());
-    assert!(staged_obj.state.get(&lpath("a/file5.txt")).is_none());
-    assert!(staged_obj.state.get(&lpath("a/b/file2.txt")).is_none());
+    assert!(!staged_obj.state.contains_key(&lpath("a/file1.txt")));
+    assert!(!staged_obj.state.contains_key(&lpath("a/file5.txt")));
+    assert!(!staged_obj.state.contains_key(&lpath("a/b/file2.txt")));
 
     commit(object_id, &repo);
 
@@ -2946,9 +3915,9 @@ fn internal_move_multiple_existing_file() -> Result<()> {
         "b47592b10bc3e5c8ca8703d0862df10a6e409f43478804f93a08dd1844ae81b6",
     );
 
-    assert!(committed_obj.state.get(&lpath("a/file1.txt")).is_none());
-    assert!(committed_obj.state.get(&lpath("a/file5.txt")).is_none());
-    assert!(committed_obj.state.get(&lpath("a/b/file2.txt")).is_none());
+    assert!(!committed_obj.state.contains_key(&lpath("a/file1.txt")));
+    assert!(!committed_obj.state.contains_key(&lpath("a/file5.txt")));
+    assert!(!committed_obj.state.contains_key(&lpath("a/b/file2.txt")));
 
     validate_repo(&repo);
     Ok(())
@@ -2976,7 +3945,7 @@ fn internal_move_should_continue_on_partial_success() -> Result<()> {
             assert_eq!(1, e.0.len());
             assert!(e
                 .0
-                .get(0)
+                .first()
                 .unwrap()
                 .contains("does not contain any files at bogus.txt"));
         }
@@ -3001,8 +3970,8 @@ fn internal_move_should_continue_on_partial_success() -> Result<()> {
         "4ccdbf78d368aed12d806efaf67fbce3300bca8e62a6f32716af2f447de1821e",
     );
 
-    assert!(staged_obj.state.get(&lpath("a/file1.txt")).is_none());
-    assert!(staged_obj.state.get(&lpath("a/file5.txt")).is_none());
+    assert!(!staged_obj.state.contains_key(&lpath("a/file1.txt")));
+    assert!(!staged_obj.state.contains_key(&lpath("a/file5.txt")));
 
     validate_repo(&repo);
     Ok(())
@@ -3040,7 +4009,7 @@ fn internal_move_files_added_in_staged_version() {
         "b37d2cbfd875891e9ed073fcbe61f35a990bee8eecbdd07f9efc51339d5ffd66",
     );
 
-    assert!(staged_obj.state.get(&lpath("just in.txt")).is_none());
+    assert!(!staged_obj.state.contains_key(&lpath("just in.txt")));
 
     commit(object_id, &repo);
 
@@ -3132,18 +4101,18 @@ fn remove_existing_file() -> Result<()> {
     let staged_obj = repo.get_staged_object(object_id)?;
 
     assert_eq!(6, staged_obj.state.len());
-    assert!(staged_obj.state.get(&lpath("a/file5.txt")).is_none());
+    assert!(!staged_obj.state.contains_key(&lpath("a/file5.txt")));
 
     commit(object_id, &repo);
 
     let committed_obj = repo.get_object(object_id, VersionRef::Head)?;
 
     assert_eq!(6, committed_obj.state.len());
-    assert!(committed_obj.state.get(&lpath("a/file5.txt")).is_none());
+    assert!(!committed_obj.state.contains_key(&lpath("a/file5.txt")));
 
     let previous_version = repo.get_object(object_id, 4.try_into()?)?;
 
-    assert!(previous_version.state.get(&lpath("a/file5.txt")).is_some());
+    assert!(previous_version.state.contains_key(&lpath("a/file5.txt")));
 
     validate_repo(&repo);
     Ok(())
@@ -3165,27 +4134,25 @@ fn remove_multiple_existing_files() -> Result<()> {
     let staged_obj = repo.get_staged_object(object_id)?;
 
     assert_eq!(5, staged_obj.state.len());
-    assert!(staged_obj.state.get(&lpath("a/file5.txt")).is_none());
-    assert!(staged_obj.state.get(&lpath("something/new.txt")).is_none());
+    assert!(!staged_obj.state.contains_key(&lpath("a/file5.txt")));
+    assert!(!staged_obj.state.contains_key(&lpath("something/new.txt")));
 
     commit(object_id, &repo);
 
     let committed_obj = repo.get_object(object_id, VersionRef::Head)?;
 
     assert_eq!(5, committed_obj.state.len());
-    assert!(committed_obj.state.get(&lpath("a/file5.txt")).is_none());
-    assert!(committed_obj
+    assert!(!committed_obj.state.contains_key(&lpath("a/file5.txt")));
+    assert!(!committed_obj
         .state
-        .get(&lpath("something/new.txt"))
-        .is_none());
+        .contains_key(&lpath("something/new.txt")));
 
     let previous_version = repo.get_object(object_id, 4.try_into()?)?;
 
-    assert!(previous_version.state.get(&lpath("a/file5.txt")).is_some());
+    assert!(previous_version.state.contains_key(&lpath("a/file5.txt")));
     assert!(previous_version
         .state
-        .get(&lpath("something/new.txt"))
-        .is_some());
+        .contains_key(&lpath("something/new.txt")));
 
     validate_repo(&repo);
     Ok(())
@@ -3207,22 +4174,22 @@ fn remove_globs() -> Result<()> {
     let staged_obj = repo.get_staged_object(object_id)?;
 
     assert_eq!(5, staged_obj.state.len());
-    assert!(staged_obj.state.get(&lpath("a/file5.txt")).is_none());
-    assert!(staged_obj.state.get(&lpath("a/file1.txt")).is_none());
-    assert!(staged_obj.state.get(&lpath("a/f/file6.txt")).is_some());
+    assert!(!staged_obj.state.contains_key(&lpath("a/file5.txt")));
+    assert!(!staged_obj.state.contains_key(&lpath("a/file1.txt")));
+    assert!(staged_obj.state.contains_key(&lpath("a/f/file6.txt")));
 
     commit(object_id, &repo);
 
     let committed_obj = repo.get_object(object_id, VersionRef::Head)?;
 
     assert_eq!(5, committed_obj.state.len());
-    assert!(committed_obj.state.get(&lpath("a/file5.txt")).is_none());
-    assert!(committed_obj.state.get(&lpath("a/file1.txt")).is_none());
+    assert!(!committed_obj.state.contains_key(&lpath("a/file5.txt")));
+    assert!(!committed_obj.state.contains_key(&lpath("a/file1.txt")));
 
     let previous_version = repo.get_object(object_id, 4.try_into()?)?;
 
-    assert!(previous_version.state.get(&lpath("a/file5.txt")).is_some());
-    assert!(previous_version.state.get(&lpath("a/file1.txt")).is_some());
+    assert!(previous_version.state.contains_key(&lpath("a/file5.txt")));
+    assert!(previous_version.state.contains_key(&lpath("a/file1.txt")));
 
     validate_repo(&repo);
     Ok(())
@@ -3244,14 +4211,14 @@ fn remove_recursive() -> Result<()> {
     let staged_obj = repo.get_staged_object(object_id)?;
 
     assert_eq!(1, staged_obj.state.len());
-    assert!(staged_obj.state.get(&lpath("file3.txt")).is_some());
+    assert!(staged_obj.state.contains_key(&lpath("file3.txt")));
 
     commit(object_id, &repo);
 
     let committed_obj = repo.get_object(object_id, VersionRef::Head)?;
 
     assert_eq!(1, committed_obj.state.len());
-    assert!(committed_obj.state.get(&lpath("file3.txt")).is_some());
+    assert!(committed_obj.state.contains_key(&lpath("file3.txt")));
 
     validate_repo(&repo);
     Ok(())
@@ -3273,7 +4240,7 @@ fn remove_files_that_do_not_exist_should_do_nothing() -> Result<()> {
     let staged_obj = repo.get_staged_object(object_id)?;
 
     assert_eq!(6, staged_obj.state.len());
-    assert!(staged_obj.state.get(&lpath("file3.txt")).is_none());
+    assert!(!staged_obj.state.contains_key(&lpath("file3.txt")));
 
     validate_repo(&repo);
     Ok(())
@@ -3323,8 +4290,8 @@ fn reset_newly_added_files() -> Result<()> {
 
     assert_eq!(8, staged_obj.state.len());
 
-    assert!(staged_obj.state.get(&lpath("new.txt")).is_none());
-    assert!(staged_obj.state.get(&lpath("new2.txt")).is_some());
+    assert!(!staged_obj.state.contains_key(&lpath("new.txt")));
+    assert!(staged_obj.state.contains_key(&lpath("new2.txt")));
 
     commit(object_id, &repo);
 
@@ -3333,7 +4300,7 @@ fn reset_newly_added_files() -> Result<()> {
 
     assert_eq!(8, obj.state.len());
 
-    assert!(obj.state.get(&lpath("new.txt")).is_none());
+    assert!(!obj.state.contains_key(&lpath("new.txt")));
     assert!(!object_root
         .join("v5")
         .join("content")
@@ -3400,8 +4367,8 @@ fn reset_copied_file() -> Result<()> {
 
     assert_eq!(8, staged_obj.state.len());
 
-    assert!(staged_obj.state.get(&lpath("new.txt")).is_none());
-    assert!(staged_obj.state.get(&lpath("new (copy).txt")).is_some());
+    assert!(!staged_obj.state.contains_key(&lpath("new.txt")));
+    assert!(staged_obj.state.contains_key(&lpath("new (copy).txt")));
 
     commit(object_id, &repo);
 
@@ -3410,7 +4377,7 @@ fn reset_copied_file() -> Result<()> {
 
     assert_eq!(8, obj.state.len());
 
-    assert!(obj.state.get(&lpath("new.txt")).is_none());
+    assert!(!obj.state.contains_key(&lpath("new.txt")));
 
     assert_file_details(
         obj.state.get(&lpath("new (copy).txt")).unwrap(),
@@ -3522,10 +4489,10 @@ fn reset_removed_file() -> Result<()> {
 
     assert_eq!(3, staged_obj.state.len());
 
-    assert!(staged_obj.state.get(&lpath("a/file1.txt")).is_none());
-    assert!(staged_obj.state.get(&lpath("a/file5.txt")).is_none());
-    assert!(staged_obj.state.get(&lpath("a/b/file2.txt")).is_none());
-    assert!(staged_obj.state.get(&lpath("a/f/file6.txt")).is_none());
+    assert!(!staged_obj.state.contains_key(&lpath("a/file1.txt")));
+    assert!(!staged_obj.state.contains_key(&lpath("a/file5.txt")));
+    assert!(!staged_obj.state.contains_key(&lpath("a/b/file2.txt")));
+    assert!(!staged_obj.state.contains_key(&lpath("a/f/file6.txt")));
 
     repo.reset(object_id, &["a/f"], true)?;
 
@@ -3533,8 +4500,8 @@ fn reset_removed_file() -> Result<()> {
 
     assert_eq!(4, staged_obj.state.len());
 
-    assert!(staged_obj.state.get(&lpath("a/file1.txt")).is_none());
-    assert!(staged_obj.state.get(&lpath("a/file5.txt")).is_none());
+    assert!(!staged_obj.state.contains_key(&lpath("a/file1.txt")));
+    assert!(!staged_obj.state.contains_key(&lpath("a/file5.txt")));
 
     let object_root = PathBuf::from(repo.get_object(object_id, VersionRef::Head)?.object_root);
 
@@ -3551,8 +4518,8 @@ fn reset_removed_file() -> Result<()> {
 
     assert_eq!(4, obj.state.len());
 
-    assert!(staged_obj.state.get(&lpath("a/file1.txt")).is_none());
-    assert!(staged_obj.state.get(&lpath("a/file5.txt")).is_none());
+    assert!(!staged_obj.state.contains_key(&lpath("a/file1.txt")));
+    assert!(!staged_obj.state.contains_key(&lpath("a/file5.txt")));
 
     assert_file_details(
         obj.state.get(&lpath("a/f/file6.txt")).unwrap(),
@@ -3588,6 +4555,58 @@ fn reset_all() {
     repo.get_staged_object(object_id).unwrap();
 }
 
+#[test]
+fn stage_subset_of_previous_version() -> Result<()> {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let repo = default_repo(root.path());
+
+    let object_id = "stage from";
+
+    create_example_object(object_id, &repo, &temp);
+
+    match repo.stage_from_version(object_id, VersionRef::Head, &["missing"], false) {
+        Err(RocflError::NotFound(_)) => (),
+        result => panic!("Expected a not found error but was: {:?}", result),
+    }
+
+    repo.stage_from_version(object_id, VersionNum::try_from(1)?.into(), &["a/b"], true)?;
+
+    let staged_obj = repo.get_staged_object(object_id)?;
+
+    assert_eq!(3, staged_obj.state.len());
+    assert!(staged_obj.state.contains_key(&lpath("a/b/file2.txt")));
+    assert!(staged_obj.state.contains_key(&lpath("a/b/file3.txt")));
+    assert_eq!(
+        cpath_rc("v1/content/a/b/c/file4.txt"),
+        staged_obj.state[&lpath("a/b/c/file4.txt")].content_path
+    );
+
+    match repo.stage_from_version(object_id, VersionRef::Head, &["*"], true) {
+        Err(RocflError::IllegalState(_)) => (),
+        result => panic!("Expected an illegal state error but was: {:?}", result),
+    }
+
+    commit(object_id, &repo);
+
+    let object = repo.get_object(object_id, VersionRef::Head)?;
+
+    assert_eq!(VersionNum::try_from(5)?, object.version_details.version_num);
+    assert_eq!(3, object.state.len());
+
+    let mut out: Vec<u8> = Vec::new();
+    repo.get_object_file(
+        object_id,
+        &lpath("a/b/c/file4.txt"),
+        VersionRef::Head,
+        &mut out,
+    )?;
+    assert_eq!("File Four", String::from_utf8(out).unwrap());
+
+    Ok(())
+}
+
 #[test]
 fn reset_complex_changes_without_conflict() -> Result<()> {
     let root = TempDir::new().unwrap();
@@ -3609,16 +4628,15 @@ fn reset_complex_changes_without_conflict() -> Result<()> {
 
     assert_eq!(4, staged_obj.state.len());
 
-    assert!(staged_obj.state.get(&lpath("a/file1.txt")).is_none());
-    assert!(staged_obj.state.get(&lpath("a/file5.txt")).is_none());
-    assert!(staged_obj.state.get(&lpath("a/b/file2.txt")).is_none());
-    assert!(staged_obj.state.get(&lpath("a/f/file6.txt")).is_none());
+    assert!(!staged_obj.state.contains_key(&lpath("a/file1.txt")));
+    assert!(!staged_obj.state.contains_key(&lpath("a/file5.txt")));
+    assert!(!staged_obj.state.contains_key(&lpath("a/b/file2.txt")));
+    assert!(!staged_obj.state.contains_key(&lpath("a/f/file6.txt")));
 
-    assert!(staged_obj.state.get(&lpath("a/b")).is_some());
+    assert!(staged_obj.state.contains_key(&lpath("a/b")));
     assert!(staged_obj
         .state
-        .get(&lpath("a/file1.txt/file3.txt"))
-        .is_some());
+        .contains_key(&lpath("a/file1.txt/file3.txt")));
 
     repo.reset(object_id, &["*"], true)?;
 
@@ -3626,11 +4644,10 @@ fn reset_complex_changes_without_conflict() -> Result<()> {
 
     assert_eq!(7, staged_obj.state.len());
 
-    assert!(staged_obj.state.get(&lpath("a/b")).is_none());
-    assert!(staged_obj
+    assert!(!staged_obj.state.contains_key(&lpath("a/b")));
+    assert!(!staged_obj
         .state
-        .get(&lpath("a/file1.txt/file3.txt"))
-        .is_none());
+        .contains_key(&lpath("a/file1.txt/file3.txt")));
 
     validate_repo(&repo);
     Ok(())
@@ -3662,16 +4679,15 @@ fn fail_reset_when_conflicted() {
 
     assert_eq!(4, staged_obj.state.len());
 
-    assert!(staged_obj.state.get(&lpath("a/file1.txt")).is_none());
-    assert!(staged_obj.state.get(&lpath("a/file5.txt")).is_none());
-    assert!(staged_obj.state.get(&lpath("a/b/file2.txt")).is_none());
-    assert!(staged_obj.state.get(&lpath("a/f/file6.txt")).is_none());
+    assert!(!staged_obj.state.contains_key(&lpath("a/file1.txt")));
+    assert!(!staged_obj.state.contains_key(&lpath("a/file5.txt")));
+    assert!(!staged_obj.state.contains_key(&lpath("a/b/file2.txt")));
+    assert!(!staged_obj.state.contains_key(&lpath("a/f/file6.txt")));
 
-    assert!(staged_obj.state.get(&lpath("a/b")).is_some());
+    assert!(staged_obj.state.contains_key(&lpath("a/b")));
     assert!(staged_obj
         .state
-        .get(&lpath("a/file1.txt/file3.txt"))
-        .is_some());
+        .contains_key(&lpath("a/file1.txt/file3.txt")));
 
     repo.reset(object_id, &["a/file1.txt"], false).unwrap();
 }
@@ -3782,6 +4798,69 @@ fn purge_should_remove_object_from_repo_and_staging() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn purge_should_finish_partially_purged_objects() -> Result<()> {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let repo = default_repo(root.path());
+
+    let object_id = "partial";
+    create_simple_object(object_id, &repo, &temp);
+    let object_path = PathBuf::from(
+        repo.get_object_details(object_id, VersionRef::Head)?
+            .object_root,
+    );
+
+    // An interrupted purge deletes the namaste file last
+    fs::remove_dir_all(object_path.join("v1"))?;
+    fs::remove_file(object_path.join("0=ocfl_object_1.0"))?;
+
+    repo.purge_object(object_id)?;
+
+    assert!(!object_path.exists());
+    assert_obj_not_exists(&repo, object_id);
+
+    let object_id = "resume";
+    create_simple_object(object_id, &repo, &temp);
+    let object_path = PathBuf::from(
+        repo.get_object_details(object_id, VersionRef::Head)?
+            .object_root,
+    );
+    let object_root = object_path
+        .strip_prefix(root.path())
+        .unwrap()
+        .to_string_lossy()
+        .replace('\\', "/");
+
+    match repo.purge_object_root(&object_root) {
+        Err(RocflError::IllegalState(_)) => (),
+        result => panic!("Expected an illegal state error but was: {:?}", result),
+    }
+
+    fs::remove_dir_all(object_path.join("v1"))?;
+    fs::remove_file(object_path.join("inventory.json"))?;
+    fs::remove_file(object_path.join("inventory.json.sha512"))?;
+
+    let deleted = repo.purge_object_root(&format!("/{}/", object_root))?;
+
+    assert_eq!(vec!["0=ocfl_object_1.0"], deleted);
+    assert!(!object_path.exists());
+
+    match repo.purge_object_root(&object_root) {
+        Err(RocflError::NotFound(_)) => (),
+        result => panic!("Expected a not found error but was: {:?}", result),
+    }
+
+    match repo.purge_object_root("../outside") {
+        Err(RocflError::InvalidValue(_)) => (),
+        result => panic!("Expected an invalid value error but was: {:?}", result),
+    }
+
+    validate_repo(&repo);
+    Ok(())
+}
+
 #[test]
 fn purge_should_do_nothing_when_obj_does_not_exist() -> Result<()> {
     let root = TempDir::new().unwrap();
@@ -3799,21 +4878,71 @@ fn purge_should_do_nothing_when_obj_does_not_exist() -> Result<()> {
 }
 
 #[test]
-fn commit_should_use_custom_meta_when_provided() -> Result<()> {
+fn rename_object_rewrites_every_inventory() -> Result<()> {
     let root = TempDir::new().unwrap();
     let temp = TempDir::new().unwrap();
 
     let repo = default_repo(root.path());
 
-    let object_id = "commit meta";
+    create_simple_object("original", &repo, &temp);
+    create_simple_object("other", &repo, &temp);
 
-    repo.create_object(
-        object_id,
-        Some(SpecVersion::Ocfl1_0),
-        DigestAlgorithm::Sha256,
-        "content",
-        0,
-    )?;
+    temp.child("b.txt").write_str("b").unwrap();
+    repo.copy_files_external("original", &[temp.child("b.txt").path()], "b.txt", false)?;
+    commit("original", &repo);
+
+    repo.add_object_alias("original", "nickname")?;
+
+    match repo.rename_object("original", "other", None, false) {
+        Err(RocflError::IllegalState(message)) => assert!(message.contains("already exists")),
+        result => panic!("Expected an illegal state error; found: {:?}", result),
+    }
+
+    repo.rename_object("original", "renamed", None, true)?;
+
+    assert!(!repo.object_exists("original")?);
+    assert!(repo.object_exists("renamed")?);
+
+    no_errors(&repo.validate_object("renamed", true)?);
+
+    let object = repo.get_object("renamed", VersionRef::Head)?;
+    assert_eq!("renamed", object.id);
+    assert_eq!(
+        VersionNum::try_from(2).unwrap(),
+        object.version_details.version_num
+    );
+
+    let v1 = repo.get_object_details("renamed", VersionRef::Number(VersionNum::v1()))?;
+    assert_eq!("renamed", v1.id);
+
+    let mut content = Vec::new();
+    repo.get_object_file("renamed", &lpath("b.txt"), VersionRef::Head, &mut content)?;
+    assert_eq!(b"b", content.as_slice());
+
+    let aliases = repo.list_object_aliases(None)?;
+    assert_eq!(1, aliases.len());
+    assert_eq!("renamed", aliases[0].object_id);
+    assert_eq!("nickname", aliases[0].alias);
+
+    Ok(())
+}
+
+#[test]
+fn commit_should_use_custom_meta_when_provided() -> Result<()> {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let repo = default_repo(root.path());
+
+    let object_id = "commit meta";
+
+    repo.create_object(
+        object_id,
+        Some(SpecVersion::Ocfl1_0),
+        DigestAlgorithm::Sha256,
+        "content",
+        0,
+    )?;
 
     repo.move_files_external(
         object_id,
@@ -3887,6 +5016,72 @@ fn commit_should_use_custom_meta_when_mixture_provided() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn commit_should_use_meta_built_with_config_defaults() -> Result<()> {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let repo = default_repo(root.path());
+
+    let object_id = "commit meta builder";
+
+    create_simple_object(object_id, &repo, &temp);
+
+    let mut config = Config::new();
+    config.author_name = Some("Config Name".to_string());
+    config.author_address = Some("mailto:config@example.com".to_string());
+
+    let before = Local::now();
+
+    repo.move_files_external(
+        object_id,
+        &[create_file(&temp, "blah", "blah").path()],
+        "blah",
+    )?;
+    let meta = CommitMeta::builder()
+        .message("built".to_string())
+        .defaults_from_config(&config)
+        .build()?;
+    repo.commit(object_id, meta, None, false)?;
+
+    let obj = repo.get_object(object_id, VersionRef::Head)?;
+
+    assert_eq!("Config Name", obj.version_details.user_name.unwrap());
+    assert_eq!(
+        "mailto:config@example.com",
+        obj.version_details.user_address.unwrap()
+    );
+    assert_eq!("built", obj.version_details.message.unwrap());
+    assert!(obj.version_details.created >= before);
+
+    repo.move_files_external(
+        object_id,
+        &[create_file(&temp, "blah2", "blah2").path()],
+        "blah2",
+    )?;
+    let meta = CommitMeta::builder()
+        .user_name("Explicit".to_string())
+        .defaults_from_config(&config)
+        .build()?;
+    repo.commit(object_id, meta, None, false)?;
+
+    let obj = repo.get_object(object_id, VersionRef::Head)?;
+
+    assert_eq!("Explicit", obj.version_details.user_name.unwrap());
+    assert!(obj.version_details.user_address.is_none());
+
+    match CommitMeta::builder()
+        .user_address("mailto:me@example.com".to_string())
+        .build()
+    {
+        Err(RocflError::InvalidValue(_)) => (),
+        result => panic!("Expected an invalid value error; found: {:?}", result),
+    }
+
+    validate_repo(&repo);
+    Ok(())
+}
+
 #[test]
 fn commit_should_pretty_print_inventory() {
     let root = TempDir::new().unwrap();
@@ -4141,6 +5336,59 @@ fn fail_get_staged_object_file_when_does_not_exist() {
         .unwrap();
 }
 
+#[test]
+fn get_object_file_verified_detects_corrupt_content() -> Result<()> {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let repo = default_repo(root.path());
+
+    let object_id = "verify";
+
+    create_simple_object(object_id, &repo, &temp);
+
+    let mut out: Vec<u8> = Vec::new();
+    repo.get_object_file_verified(object_id, &lpath("test.txt"), VersionRef::Head, &mut out)?;
+    assert_eq!("testing", String::from_utf8(out).unwrap());
+
+    repo.copy_files_external(
+        object_id,
+        &[create_file(&temp, "staged.txt", "staged").path()],
+        "staged.txt",
+        false,
+    )?;
+
+    let mut out: Vec<u8> = Vec::new();
+    repo.get_staged_object_file_verified(object_id, &lpath("staged.txt"), &mut out)?;
+    assert_eq!("staged", String::from_utf8(out).unwrap());
+
+    let object = repo.get_object(object_id, VersionRef::Head)?;
+    fs::write(
+        &object.state.get(&lpath("test.txt")).unwrap().storage_path,
+        "corrupt",
+    )?;
+
+    let mut out: Vec<u8> = Vec::new();
+    repo.get_object_file(object_id, &lpath("test.txt"), VersionRef::Head, &mut out)?;
+    assert_eq!("corrupt", String::from_utf8(out).unwrap());
+
+    let mut out: Vec<u8> = Vec::new();
+    match repo.get_object_file_verified(object_id, &lpath("test.txt"), VersionRef::Head, &mut out) {
+        Err(RocflError::CorruptObject { message, .. }) => {
+            assert!(message.contains("File test.txt has sha512 digest"))
+        }
+        result => panic!("Expected corrupt object error, found {:?}", result),
+    }
+
+    let mut out: Vec<u8> = Vec::new();
+    match repo.get_staged_object_file_verified(object_id, &lpath("test.txt"), &mut out) {
+        Err(RocflError::CorruptObject { .. }) => (),
+        result => panic!("Expected corrupt object error, found {:?}", result),
+    }
+
+    Ok(())
+}
+
 #[test]
 fn diff_should_detect_simple_rename() -> Result<()> {
     let root = TempDir::new().unwrap();
@@ -4708,6 +5956,81 @@ fn fail_commit_when_staged_version_out_of_sync_with_main() {
     validate_repo(&repo);
 }
 
+#[test]
+fn commit_rejects_stale_staged_versions_until_rebased() -> Result<()> {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+    let other_staging = TempDir::new().unwrap();
+
+    let repo = default_repo(root.path());
+    let other = OcflRepo::fs_repo(root.path(), Some(other_staging.path()))?;
+
+    let object_id = "obj";
+    create_simple_object(object_id, &repo, &temp);
+
+    temp.child("a.txt").write_str("a").unwrap();
+    temp.child("b.txt").write_str("b").unwrap();
+    repo.copy_files_external(object_id, &[temp.child("a.txt").path()], "a.txt", false)?;
+    other.copy_files_external(object_id, &[temp.child("b.txt").path()], "b.txt", false)?;
+    commit(object_id, &other);
+
+    match repo.commit(object_id, CommitMeta::new(), None, false) {
+        Err(RocflError::VersionConflict {
+            staged, current, ..
+        }) => {
+            assert_eq!(VersionNum::try_from(2).unwrap(), staged);
+            assert_eq!(VersionNum::try_from(2).unwrap(), current);
+        }
+        result => panic!("Expected a version conflict; found: {:?}", result),
+    }
+
+    assert_eq!(
+        VersionNum::try_from(3).unwrap(),
+        repo.rebase_staged(object_id)?
+    );
+    commit(object_id, &repo);
+
+    let object = repo.get_object(object_id, VersionRef::Head)?;
+    assert_eq!(
+        VersionNum::try_from(3).unwrap(),
+        object.version_details.version_num
+    );
+    assert_eq!(3, object.state.len());
+    assert!(object.state.contains_key(&lpath_rc("b.txt")));
+
+    let mut content = Vec::new();
+    repo.get_object_file(object_id, &lpath("a.txt"), VersionRef::Head, &mut content)?;
+    assert_eq!(b"a", content.as_slice());
+    no_errors(&repo.validate_object(object_id, true)?);
+
+    temp.child("test.txt").write_str("mine").unwrap();
+    repo.copy_files_external(
+        object_id,
+        &[temp.child("test.txt").path()],
+        "test.txt",
+        false,
+    )?;
+    temp.child("test.txt").write_str("theirs").unwrap();
+    other.copy_files_external(
+        object_id,
+        &[temp.child("test.txt").path()],
+        "test.txt",
+        false,
+    )?;
+    commit(object_id, &other);
+
+    assert!(matches!(
+        repo.commit(object_id, CommitMeta::new(), None, false),
+        Err(RocflError::VersionConflict { .. })
+    ));
+    match repo.rebase_staged(object_id) {
+        Err(RocflError::IllegalState(message)) => assert!(message.contains("test.txt")),
+        result => panic!("Expected a rebase conflict; found: {:?}", result),
+    }
+
+    Ok(())
+}
+
 #[test]
 #[should_panic(expected = "Cannot stage changes for object because it has an active mutable HEAD.")]
 fn do_not_stage_changes_for_objects_with_mutable_heads() {
@@ -4850,105 +6173,3221 @@ fn fail_when_incorrect_object_in_root() {
     repo.get_object(object_id_2, VersionRef::Head).unwrap();
 }
 
-// TODO When version rewrite is implemented it is no longer safe to assume that logical paths
-//      were mapped directly to content paths. This means that all move/copy operations must
-//      verify that they are not unintentionally overwriting an existing file.
+#[test]
+fn commit_object_to_custom_root_in_repo_with_layout() -> Result<()> {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
 
-fn validate_repo(repo: &OcflRepo) {
-    let mut validator = repo.validate_repo(true).unwrap();
+    let repo = default_repo(root.path());
 
-    if validator.storage_root_result().has_errors() {
-        panic!(
-            "Found validation errors: {:?}",
-            validator.storage_root_result()
-        );
-    }
+    let object_id = "custom root";
+    let object_root = "custom/path/to/obj";
 
-    for result in &mut validator {
-        let result = result.unwrap();
-        if result.has_errors() {
-            panic!("Found validation errors: {:?}", result);
-        }
-    }
+    repo.create_object(
+        object_id,
+        Some(SpecVersion::Ocfl1_0),
+        DigestAlgorithm::Sha256,
+        "content",
+        0,
+    )?;
+    repo.move_files_external(
+        object_id,
+        &[create_file(&temp, "test.txt", "testing").path()],
+        "test.txt",
+    )?;
+    repo.commit(object_id, CommitMeta::new(), Some(object_root), false)?;
 
-    if validator.storage_hierarchy_result().has_errors() {
-        panic!(
-            "Found validation errors: {:?}",
-            validator.storage_hierarchy_result()
-        );
-    }
-}
+    root.child(object_root)
+        .child("0=ocfl_object_1.0")
+        .assert(predicates::path::exists());
+    root.child("extensions/rocfl-object-index/index.json")
+        .assert(predicates::str::contains(
+            r#""custom root": "custom/path/to/obj""#,
+        ));
 
-fn assert_staged_obj_count(repo: &OcflRepo, count: usize) {
-    assert_eq!(count, repo.list_staged_objects(None).unwrap().count());
-}
+    let repo = OcflRepo::fs_repo(root.path(), None)?;
 
-fn assert_obj_count(repo: &OcflRepo, count: usize) {
-    assert_eq!(count, repo.list_objects(None).unwrap().count());
-}
+    repo.move_files_external(
+        object_id,
+        &[create_file(&temp, "test2.txt", "testing2").path()],
+        "test2.txt",
+    )?;
+    commit(object_id, &repo);
 
-fn assert_obj_not_exists(repo: &OcflRepo, object_id: &str) {
-    match repo.get_object(object_id, VersionRef::Head) {
-        Err(RocflError::NotFound(_)) => (),
-        _ => panic!("Expected object '{}' to not be found", object_id),
-    }
-}
+    let obj = repo.get_object(object_id, VersionRef::Head)?;
 
-fn assert_staged_obj_not_exists(repo: &OcflRepo, object_id: &str) {
-    match repo.get_staged_object(object_id) {
-        Err(RocflError::NotFound(_)) => (),
-        _ => panic!("Expected staged object '{}' to not be found", object_id),
-    }
-}
+    assert_eq!(2, obj.state.len());
+    assert_file_details(
+        obj.state.get(&lpath("test2.txt")).unwrap(),
+        root.child(object_root).path(),
+        "v2/content/test2.txt",
+        "431111472993bf4d9b8b347476b79321fea8a337f3c1cb2fedaa185b54185540",
+    );
 
-fn assert_file_not_exists(obj: &ObjectVersion, logical_path: &str, content_path: &str) {
-    assert!(obj.state.get(&lpath(logical_path)).is_none());
-    assert!(!Path::new(&obj.object_root).join(content_path).exists());
-}
+    assert!(!repo.validate_object(object_id, true)?.has_errors());
+    validate_repo(&repo);
 
-fn assert_file_details(
-    actual: &FileDetails,
-    object_root: impl AsRef<Path>,
-    content_path: &str,
-    digest: &str,
-) {
-    assert_eq!(cpath_rc(content_path), actual.content_path);
-    assert_eq!(
-        join(object_root, &content_path.split('/').collect::<Vec<&str>>())
-            .to_string_lossy()
-            .to_string(),
-        actual.storage_path
-    );
+    repo.purge_object(object_id)?;
 
-    assert!(
-        Path::new(&actual.storage_path).is_file(),
-        "Expected {} to exist and be a file",
-        actual.storage_path
-    );
-    if digest.len() == 64 {
-        assert_eq!(
-            digest,
-            DigestAlgorithm::Sha256
-                .hash_hex(&mut File::open(&actual.storage_path).unwrap())
-                .unwrap()
-                .as_ref()
-        )
-    } else {
-        assert_eq!(
-            digest,
-            DigestAlgorithm::Sha512
-                .hash_hex(&mut File::open(&actual.storage_path).unwrap())
-                .unwrap()
-                .as_ref()
-        )
-    }
-    assert_eq!(Rc::new(digest.into()), actual.digest);
+    assert_obj_not_exists(&repo, object_id);
+    assert!(!fs::read_to_string(
+        root.child("extensions/rocfl-object-index/index.json")
+            .path()
+    )?
+    .contains(object_id));
+
+    Ok(())
 }
 
-fn assert_deduped_path(
-    object_root: impl AsRef<Path>,
-    details: &FileDetails,
-    possible_paths: &[&str],
+#[test]
+fn adopt_object_at_nonstandard_path() -> Result<()> {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let repo = OcflRepo::init_fs_repo(
+        root.path(),
+        None,
+        SpecVersion::Ocfl1_0,
+        Some(StorageLayout::new(LayoutExtensionName::FlatDirectLayout, None).unwrap()),
+    )?;
+
+    let object_id = "adopted";
+    let object_root = "elsewhere/adopted";
+
+    create_simple_object(object_id, &repo, &temp);
+
+    fs::create_dir_all(root.child("elsewhere").path())?;
+    fs::rename(root.child(object_id).path(), root.child(object_root).path())?;
+
+    let repo = OcflRepo::fs_repo(root.path(), None)?;
+
+    assert_obj_not_exists(&repo, object_id);
+
+    assert_eq!(object_id, repo.adopt_object(object_root)?);
+
+    let repo = OcflRepo::fs_repo(root.path(), None)?;
+
+    repo.move_files_external(
+        object_id,
+        &[create_file(&temp, "test2.txt", "testing2").path()],
+        "test2.txt",
+    )?;
+    commit(object_id, &repo);
+
+    let obj = repo.get_object(object_id, VersionRef::Head)?;
+
+    assert_eq!(VersionNum::try_from(2)?, obj.version_details.version_num);
+    assert_eq!(2, obj.state.len());
+    assert!(!repo.validate_object(object_id, true)?.has_errors());
+
+    repo.purge_object(object_id)?;
+
+    assert_obj_not_exists(&repo, object_id);
+    root.child(object_root).assert(predicates::path::missing());
+
+    Ok(())
+}
+
+#[test]
+fn locate_objects_using_object_registry() -> Result<()> {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let repo = OcflRepo::init_fs_repo(root.path(), None, SpecVersion::Ocfl1_0, None)?;
+
+    let create = |repo: &OcflRepo, object_id: &str, object_root: &str| {
+        repo.create_object(
+            object_id,
+            Some(SpecVersion::Ocfl1_0),
+            DigestAlgorithm::Sha512,
+            "content",
+            0,
+        )
+        .unwrap();
+        repo.move_files_external(
+            object_id,
+            &[create_file(&temp, "test.txt", object_id).path()],
+            "test.txt",
+        )
+        .unwrap();
+        repo.commit(object_id, CommitMeta::new(), Some(object_root), false)
+            .unwrap();
+    };
+
+    create(&repo, "obj-1", "a/obj-1");
+    create(&repo, "obj-2", "b/obj-2");
+
+    let index_file = root.child("extensions/rocfl-object-index/index.json");
+    fs::remove_file(index_file.path())?;
+
+    let repo = OcflRepo::fs_repo(root.path(), None)?;
+    assert!(!repo.is_object_registry());
+    assert_eq!(2, repo.set_object_registry(true)?);
+    assert!(repo.is_object_registry());
+
+    create(&repo, "obj-3", "c/obj-3");
+    repo.purge_object("obj-1")?;
+
+    let index: serde_json::Value = serde_json::from_slice(&fs::read(index_file.path())?)?;
+    assert_eq!(
+        serde_json::json!({"obj-2": "b/obj-2", "obj-3": "c/obj-3"}),
+        index
+    );
+
+    // Objects that are not in the registry are not found by scanning
+    fs::write(index_file.path(), r#"{"obj-3": "c/obj-3"}"#)?;
+    let repo = OcflRepo::fs_repo(root.path(), None)?;
+
+    assert!(repo.is_object_registry());
+    let ids: Vec<String> = repo
+        .list_objects(None)?
+        .map(|object| object.unwrap().id)
+        .collect();
+    assert_eq!(vec!["obj-3".to_string()], ids);
+    assert_obj_not_exists(&repo, "obj-2");
+
+    assert_eq!(1, repo.set_object_registry(false)?);
+    assert!(!repo.is_object_registry());
+    assert_eq!("obj-2", repo.get_object("obj-2", VersionRef::Head)?.id);
+
+    Ok(())
+}
+
+#[test]
+fn fail_adopt_object_when_no_object_at_path() {
+    let root = TempDir::new().unwrap();
+
+    let repo = default_repo(root.path());
+
+    match repo.adopt_object("does/not/exist") {
+        Err(RocflError::NotFound(_)) => (),
+        _ => panic!("Expected adopt to fail with not found"),
+    }
+}
+
+#[test]
+#[should_panic(expected = "because it already exists at")]
+fn fail_adopt_object_when_object_exists_at_layout_path() {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let repo = OcflRepo::init_fs_repo(
+        root.path(),
+        None,
+        SpecVersion::Ocfl1_0,
+        Some(StorageLayout::new(LayoutExtensionName::FlatDirectLayout, None).unwrap()),
+    )
+    .unwrap();
+
+    let object_id = "duplicate";
+
+    create_simple_object(object_id, &repo, &temp);
+
+    fs::create_dir_all(root.child("copy").path()).unwrap();
+    fs_extra::dir::copy(
+        root.child(object_id).path(),
+        root.child("copy").path(),
+        &CopyOptions::new(),
+    )
+    .unwrap();
+
+    repo.adopt_object(&format!("copy/{}", object_id)).unwrap();
+}
+
+#[test]
+fn object_aliases_resolve_to_objects() -> Result<()> {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let repo = default_repo(root.path());
+
+    let object_id = "aliased";
+    let alias = "doi:10.1234/aliased";
+
+    create_simple_object(object_id, &repo, &temp);
+    create_simple_object("other", &repo, &temp);
+
+    repo.add_object_alias(object_id, alias)?;
+    repo.add_object_alias(object_id, "hdl:1234/1")?;
+    repo.add_object_alias("other", "hdl:1234/2")?;
+
+    let repo = OcflRepo::fs_repo(root.path(), None)?;
+
+    let obj = repo.get_object(alias, VersionRef::Head)?;
+    assert_eq!(object_id, obj.id);
+    assert_eq!(
+        object_id,
+        repo.get_object_details(alias, VersionRef::Head)?.id
+    );
+    assert_eq!(1, repo.list_object_versions(alias)?.len());
+
+    let mut out: Vec<u8> = Vec::new();
+    repo.get_object_file(alias, &lpath("test.txt"), VersionRef::Head, &mut out)?;
+    assert_eq!("testing", String::from_utf8(out).unwrap());
+
+    assert_eq!(
+        vec![
+            ObjectAlias {
+                alias: alias.to_string(),
+                object_id: object_id.to_string(),
+            },
+            ObjectAlias {
+                alias: "hdl:1234/1".to_string(),
+                object_id: object_id.to_string(),
+            },
+        ],
+        repo.list_object_aliases(Some(object_id))?
+    );
+    assert_eq!(3, repo.list_object_aliases(None)?.len());
+
+    repo.remove_object_alias(alias)?;
+
+    assert_obj_not_exists(&repo, alias);
+    assert_eq!(2, repo.list_object_aliases(None)?.len());
+
+    validate_repo(&repo);
+
+    Ok(())
+}
+
+#[test]
+fn aliases_added_through_different_repos_are_not_lost() -> Result<()> {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let repo = default_repo(root.path());
+
+    create_simple_object("obj-1", &repo, &temp);
+    create_simple_object("obj-2", &repo, &temp);
+
+    let other = OcflRepo::fs_repo(root.path(), None)?;
+
+    repo.add_object_alias("obj-1", "alias-1")?;
+    other.add_object_alias("obj-2", "alias-2")?;
+    repo.add_object_alias("obj-1", "alias-3")?;
+
+    let repo = OcflRepo::fs_repo(root.path(), None)?;
+
+    assert_eq!(3, repo.list_object_aliases(None)?.len());
+    assert!(!root
+        .child("extensions/rocfl-object-aliases/aliases.json.part")
+        .path()
+        .exists());
+    assert!(root
+        .child("extensions/rocfl-locks")
+        .path()
+        .read_dir()?
+        .next()
+        .is_none());
+
+    Ok(())
+}
+
+#[test]
+fn purge_object_removes_its_aliases() -> Result<()> {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let repo = default_repo(root.path());
+
+    create_simple_object("purged", &repo, &temp);
+    create_simple_object("kept", &repo, &temp);
+
+    repo.add_object_alias("purged", "alias-1")?;
+    repo.add_object_alias("kept", "alias-2")?;
+
+    repo.purge_object("purged")?;
+
+    let repo = OcflRepo::fs_repo(root.path(), None)?;
+
+    assert_eq!(
+        vec![ObjectAlias {
+            alias: "alias-2".to_string(),
+            object_id: "kept".to_string(),
+        }],
+        repo.list_object_aliases(None)?
+    );
+    assert_obj_not_exists(&repo, "alias-1");
+
+    Ok(())
+}
+
+#[test]
+fn fail_add_object_alias_when_invalid() -> Result<()> {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let repo = default_repo(root.path());
+
+    create_simple_object("obj-1", &repo, &temp);
+    create_simple_object("obj-2", &repo, &temp);
+
+    repo.add_object_alias("obj-1", "alias")?;
+
+    match repo.add_object_alias("missing", "other-alias") {
+        Err(RocflError::NotFound(_)) => (),
+        _ => panic!("Expected alias add to fail with not found"),
+    }
+
+    match repo.add_object_alias("obj-1", "obj-2") {
+        Err(RocflError::IllegalOperation(_)) => (),
+        _ => panic!("Expected alias add to fail because the alias is an object ID"),
+    }
+
+    match repo.add_object_alias("obj-2", "alias") {
+        Err(RocflError::IllegalOperation(_)) => (),
+        _ => panic!("Expected alias add to fail because the alias is in use"),
+    }
+
+    match repo.remove_object_alias("missing") {
+        Err(RocflError::NotFound(_)) => (),
+        _ => panic!("Expected alias remove to fail with not found"),
+    }
+
+    assert_eq!(1, repo.list_object_aliases(None)?.len());
+
+    Ok(())
+}
+
+#[test]
+fn object_exists_and_head_version_do_not_read_inventory() -> Result<()> {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let repo = default_repo(root.path());
+
+    let object_id = "head-version";
+
+    repo.create_object(
+        object_id,
+        Some(SpecVersion::Ocfl1_0),
+        DigestAlgorithm::Sha512,
+        "content",
+        3,
+    )?;
+    repo.copy_files_external(
+        object_id,
+        &[create_file(&temp, "a", "a").path()],
+        "a",
+        false,
+    )?;
+    commit(object_id, &repo);
+    repo.copy_files_external(
+        object_id,
+        &[create_file(&temp, "b", "b").path()],
+        "b",
+        false,
+    )?;
+    commit(object_id, &repo);
+
+    repo.add_object_alias(object_id, "alias")?;
+
+    let object_root = root
+        .path()
+        .join(repo.get_object(object_id, VersionRef::Head)?.object_root);
+
+    // Neither an unreadable inventory nor an incomplete version should affect the result
+    fs::write(object_root.join("inventory.json"), "corrupt")?;
+    fs::create_dir(object_root.join("v003"))?;
+
+    assert!(repo.object_exists(object_id)?);
+    assert!(repo.object_exists("alias")?);
+    assert!(!repo.object_exists("missing")?);
+
+    assert_eq!(VersionNum::try_from("v002")?, repo.head_version(object_id)?);
+    assert_eq!(VersionNum::try_from("v002")?, repo.head_version("alias")?);
+
+    match repo.head_version("missing") {
+        Err(RocflError::NotFound(_)) => (),
+        _ => panic!("Expected head version to fail with not found"),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn head_version_of_object_with_mutable_head() -> Result<()> {
+    let repo_root = create_repo_root("mutable");
+    let repo = OcflRepo::fs_repo(&repo_root, None)?;
+
+    let object = repo.get_object("o1", VersionRef::Head)?;
+
+    assert!(repo.object_exists("o1")?);
+    assert_eq!(object.version_details.version_num, repo.head_version("o1")?);
+
+    Ok(())
+}
+
+#[test]
+fn validate_staged_version_before_commit() -> Result<()> {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let repo = default_repo(root.path());
+
+    let object_id = "staged";
+
+    match repo.validate_staged(object_id) {
+        Err(RocflError::NotFound(_)) => (),
+        _ => panic!("Expected validation to fail with not found"),
+    }
+
+    create_simple_object(object_id, &repo, &temp);
+
+    temp.child("a.txt").write_str("a").unwrap();
+    temp.child("b.txt").write_str("b").unwrap();
+    repo.copy_files_external(
+        object_id,
+        &[temp.child("a.txt").path(), temp.child("b.txt").path()],
+        "/",
+        false,
+    )?;
+
+    let result = repo.validate_staged(object_id)?;
+    no_errors(&result);
+    assert_eq!(2, result.fixity_checked().len());
+
+    let staged = repo.get_staged_object(object_id)?;
+    let staged_file = &staged.state.get(&lpath("b.txt")).unwrap().storage_path;
+    fs::write(staged_file, "corrupt")?;
+
+    let result = repo.validate_staged(object_id)?;
+    error_count(1, &result);
+    assert_eq!(ErrorCode::E092, result.errors()[0].code);
+    assert!(result.errors()[0].text.contains("v2/content/b.txt"));
+
+    fs::remove_file(staged_file)?;
+
+    let result = repo.validate_staged(object_id)?;
+    has_errors(
+        &result,
+        &[version_error(
+            "v2",
+            ErrorCode::E092,
+            "Inventory manifest references a file that does not exist in staging: v2/content/b.txt",
+        )],
+    );
+
+    Ok(())
+}
+
+#[test]
+fn validate_warns_when_storage_paths_exceed_windows_max_path() -> Result<()> {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let repo = default_repo(root.path());
+
+    let object_id = "long-paths";
+
+    create_simple_object(object_id, &repo, &temp);
+
+    let long_path_warnings = |result: &ObjectValidationResult| {
+        result
+            .warnings()
+            .iter()
+            .filter(|warning| warning.code == WarnCode::RW001)
+            .map(|warning| warning.text.clone())
+            .collect::<Vec<String>>()
+    };
+
+    assert!(long_path_warnings(&repo.validate_object(object_id, true)?).is_empty());
+
+    let long_dir = format!("{}/{}", "a".repeat(100), "b".repeat(100));
+    temp.child("long.txt").write_str("long").unwrap();
+    repo.copy_files_external(
+        object_id,
+        &[temp.child("long.txt").path()],
+        &format!("{}/long.txt", long_dir),
+        false,
+    )?;
+    commit(object_id, &repo);
+
+    let result = repo.validate_object(object_id, true)?;
+    no_errors(&result);
+
+    let warnings = long_path_warnings(&result);
+    assert_eq!(1, warnings.len());
+    assert!(warnings[0]
+        .contains("1 storage paths longer than the Windows MAX_PATH limit of 260 characters"));
+    assert!(warnings[0].ends_with(&format!("v2/content/{}/long.txt", long_dir)));
+
+    Ok(())
+}
+
+#[test]
+fn validate_warns_when_version_created_before_previous_version() -> Result<()> {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let repo = default_repo(root.path());
+
+    let object_id = "out-of-order";
+
+    create_simple_object(object_id, &repo, &temp);
+
+    let timeline_warnings = |result: &ObjectValidationResult| {
+        result
+            .warnings()
+            .iter()
+            .filter(|warning| warning.code == WarnCode::RW003)
+            .map(|warning| warning.text.clone())
+            .collect::<Vec<String>>()
+    };
+
+    assert!(timeline_warnings(&repo.validate_object(object_id, true)?).is_empty());
+
+    temp.child("second.txt").write_str("second").unwrap();
+    repo.copy_files_external(object_id, &[temp.child("second.txt").path()], "/", false)?;
+    repo.commit(
+        object_id,
+        CommitMeta::new().with_created(Some(Local.with_ymd_and_hms(2000, 1, 1, 0, 0, 0).unwrap())),
+        None,
+        false,
+    )?;
+
+    let result = repo.validate_object(object_id, true)?;
+    no_errors(&result);
+
+    let warnings = timeline_warnings(&result);
+    assert_eq!(1, warnings.len());
+    assert!(warnings[0].starts_with("Inventory version v2 was created at 2000-01-01"));
+    assert!(warnings[0].contains("which is before version v1"));
+
+    Ok(())
+}
+
+#[test]
+fn validate_reports_unportable_paths_as_warnings_or_strict_errors() -> Result<()> {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let repo = default_repo(root.path());
+
+    let object_id = "unportable-paths";
+
+    create_simple_object(object_id, &repo, &temp);
+
+    let path_codes = [WarnCode::RW005, WarnCode::RW006, WarnCode::RW007];
+    let path_warnings = |result: &ObjectValidationResult| {
+        result
+            .warnings()
+            .iter()
+            .filter(|warning| path_codes.contains(&warning.code))
+            .count()
+    };
+
+    assert_eq!(0, path_warnings(&repo.validate_object(object_id, true)?));
+
+    for dst in ["tab\there.txt", "cafe\u{301}.txt", "dir/con.txt"] {
+        temp.child("file.txt").write_str(dst).unwrap();
+        repo.copy_files_external(object_id, &[temp.child("file.txt").path()], dst, false)?;
+    }
+    commit(object_id, &repo);
+
+    let warning_text = |result: &ObjectValidationResult, code: WarnCode| {
+        result
+            .warnings()
+            .iter()
+            .find(|warning| warning.code == code)
+            .map(|warning| warning.text.clone())
+            .unwrap_or_default()
+    };
+
+    let result = repo.validate_object(object_id, true)?;
+    no_errors(&result);
+    assert_eq!(3, path_warnings(&result));
+    assert_eq!(
+        "Object contains 2 paths that contain control characters: \"tab\\there.txt\", \
+        \"v2/content/tab\\there.txt\"",
+        warning_text(&result, WarnCode::RW005)
+    );
+    assert!(warning_text(&result, WarnCode::RW006)
+        .starts_with("Object contains 2 paths that are not in Unicode Normalization Form C"));
+    assert!(warning_text(&result, WarnCode::RW007)
+        .starts_with("Object contains 2 paths that contain names that are reserved on Windows"));
+
+    repo.set_strict_paths(true);
+
+    let result = repo.validate_object(object_id, true)?;
+    assert_eq!(0, path_warnings(&result));
+    assert_eq!(
+        vec![ErrorCode::RE001, ErrorCode::RE002, ErrorCode::RE003],
+        result
+            .errors()
+            .iter()
+            .map(|error| error.code)
+            .collect::<Vec<ErrorCode>>()
+    );
+
+    Ok(())
+}
+
+#[test]
+fn validate_permits_extension_fixity_algorithms_when_extension_declared() -> Result<()> {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let repo = default_repo(root.path());
+
+    let object_id = "fixity";
+
+    create_simple_object(object_id, &repo, &temp);
+
+    let object_root = PathBuf::from(
+        repo.get_object_details(object_id, VersionRef::Head)?
+            .object_root,
+    );
+
+    let sha512_256 = DigestAlgorithm::Sha512_256.hash_hex(&mut "testing".as_bytes())?;
+    let mut inventory: serde_json::Value =
+        serde_json::from_slice(&fs::read(object_root.join("inventory.json"))?)?;
+    inventory["fixity"] = serde_json::json!({
+        "sha512/256": { sha512_256.as_ref(): ["v1/content/test.txt"] },
+        "crc32": { "d87f7e0c": ["v1/content/test.txt"] },
+    });
+    let bytes = serde_json::to_vec(&inventory)?;
+    let digest = DigestAlgorithm::Sha512.hash_hex(&mut bytes.as_slice())?;
+    for dir in [object_root.clone(), object_root.join("v1")] {
+        fs::write(dir.join("inventory.json"), &bytes)?;
+        fs::write(
+            dir.join("inventory.json.sha512"),
+            format!("{}  inventory.json\n", digest),
+        )?;
+    }
+
+    let fixity_warnings = |result: &ObjectValidationResult| {
+        result
+            .warnings()
+            .iter()
+            .filter(|warning| warning.code == WarnCode::RW009)
+            .map(|warning| warning.text.clone())
+            .collect::<Vec<String>>()
+    };
+
+    let result = repo.validate_object(object_id, true)?;
+    no_errors(&result);
+    assert_eq!(
+        vec![
+            "Inventory fixity block uses digest algorithm 'crc32', which is not defined by the \
+            OCFL spec or the 0009-digest-algorithms extension",
+            "Inventory fixity block uses digest algorithm 'sha512/256', which is registered by \
+            the 0009-digest-algorithms extension, but the object does not declare the extension",
+        ],
+        fixity_warnings(&result)
+    );
+
+    fs::create_dir_all(object_root.join("extensions/0009-digest-algorithms"))?;
+
+    let result = repo.validate_object(object_id, true)?;
+    no_errors(&result);
+    assert!(result
+        .warnings()
+        .iter()
+        .all(|warning| warning.code != WarnCode::W013));
+    assert_eq!(1, fixity_warnings(&result).len());
+    assert!(fixity_warnings(&result)[0].contains("'crc32'"));
+
+    let info = repo.describe_object(object_id)?;
+    assert_eq!(vec!["crc32", "sha512/256"], info.fixity_algorithms);
+    assert_eq!(vec!["0009-digest-algorithms"], info.extensions);
+
+    Ok(())
+}
+
+#[test]
+fn zero_padded_versions_keep_their_width_on_commit() -> Result<()> {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let repo = default_repo(root.path());
+
+    let object_id = "padded";
+
+    match repo.create_object(object_id, None, DigestAlgorithm::Sha512, "content", 1) {
+        Err(RocflError::InvalidValue(message)) => {
+            assert!(message.contains("The zero-padding width must be 0 or between 2 and 10"))
+        }
+        _ => panic!("Expected object creation to fail with an invalid padding width"),
+    }
+
+    repo.create_object(object_id, None, DigestAlgorithm::Sha512, "content", 4)?;
+
+    temp.child("a.txt").write_str("a").unwrap();
+    repo.copy_files_external(object_id, &[temp.child("a.txt").path()], "/", false)?;
+    commit(object_id, &repo);
+
+    temp.child("b.txt").write_str("b").unwrap();
+    repo.copy_files_external(object_id, &[temp.child("b.txt").path()], "/", false)?;
+
+    let staged = repo.get_staged_object(object_id)?;
+    assert_eq!("v0002", staged.version_details.version_num.to_string());
+
+    // Rewrite the staged inventory so that the staged version is no longer zero-padded
+    let inventory_path = PathBuf::from(&staged.object_root).join("inventory.json");
+    let inventory = fs::read_to_string(&inventory_path)?;
+    fs::write(&inventory_path, inventory.replace("v0002", "v2"))?;
+
+    match repo.commit(object_id, CommitMeta::new(), None, false) {
+        Err(RocflError::IllegalState(message)) => assert!(message.contains(
+            "Cannot commit version v2 of object padded because its zero-padding does not match the object's existing versions, such as v0001"
+        )),
+        _ => panic!("Expected commit to fail because of mismatched zero-padding"),
+    }
+
+    fs::write(&inventory_path, inventory)?;
+    commit(object_id, &repo);
+
+    let object = repo.get_object(object_id, VersionRef::Head)?;
+    assert_eq!("v0002", object.version_details.version_num.to_string());
+
+    Ok(())
+}
+
+#[test]
+fn squash_object_versions_into_baseline_version() -> Result<()> {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let repo = default_repo(root.path());
+    let object_id = "squash";
+
+    repo.create_object(object_id, None, DigestAlgorithm::Sha512, "content", 0)?;
+    repo.copy_files_external(
+        object_id,
+        &[
+            create_file(&temp, "a.txt", "a").path(),
+            create_file(&temp, "b.txt", "b").path(),
+        ],
+        "/",
+        false,
+    )?;
+    commit(object_id, &repo);
+
+    repo.remove_files(object_id, &["b.txt"], false)?;
+    repo.copy_files_external(
+        object_id,
+        &[create_file(&temp, "c.txt", "c").path()],
+        "/",
+        false,
+    )?;
+    commit(object_id, &repo);
+
+    repo.copy_files_external(
+        object_id,
+        &[
+            create_file(&temp, "a.txt", "a2").path(),
+            create_file(&temp, "d.txt", "d").path(),
+        ],
+        "/",
+        false,
+    )?;
+    repo.commit(
+        object_id,
+        CommitMeta::new().with_message(Some("third".to_string())),
+        None,
+        false,
+    )?;
+
+    repo.copy_files_external(
+        object_id,
+        &[create_file(&temp, "a.txt", "a3").path()],
+        "/",
+        false,
+    )?;
+    commit(object_id, &repo);
+
+    repo.add_object_alias(object_id, "doi:squash")?;
+
+    repo.copy_files_external(
+        object_id,
+        &[create_file(&temp, "e.txt", "e").path()],
+        "/",
+        false,
+    )?;
+    assert!(matches!(
+        repo.squash_object(object_id, 3.try_into()?, false),
+        Err(RocflError::IllegalState(_))
+    ));
+    repo.reset_all(object_id)?;
+
+    let report = repo.squash_object(object_id, 3.try_into()?, false)?;
+
+    let v = |num: u32| VersionNum::try_from(num).unwrap();
+    assert_eq!(
+        vec![(v(1), v(1)), (v(2), v(1)), (v(3), v(1)), (v(4), v(2))],
+        report.versions
+    );
+    assert_eq!(
+        vec![
+            "v1/content/a.txt".to_string(),
+            "v1/content/b.txt".to_string()
+        ],
+        report.removed_files
+    );
+
+    no_errors(&repo.validate_object(object_id, true)?);
+
+    let versions = repo.list_object_versions(object_id)?;
+    assert_eq!(2, versions.len());
+    assert_eq!(Some("third".to_string()), versions[0].message);
+
+    let v1 = repo.get_object(object_id, VersionRef::Number(v(1)))?;
+    assert_eq!(3, v1.state.len());
+    assert!(!v1.state.contains_key(&lpath_rc("b.txt")));
+
+    let mut content = Vec::new();
+    repo.get_object_file(
+        object_id,
+        &lpath("a.txt"),
+        VersionRef::Number(v(1)),
+        &mut content,
+    )?;
+    assert_eq!(b"a2", content.as_slice());
+
+    let mut content = Vec::new();
+    repo.get_object_file(object_id, &lpath("a.txt"), VersionRef::Head, &mut content)?;
+    assert_eq!(b"a3", content.as_slice());
+
+    assert!(repo.object_exists("doi:squash")?);
+
+    Ok(())
+}
+
+#[test]
+fn read_only_repo_rejects_modifications() -> Result<()> {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let object_id = "read-only";
+
+    {
+        let repo = default_repo(root.path());
+        create_simple_object(object_id, &repo, &temp);
+    }
+
+    let repo = OcflRepo::fs_repo_read_only(root.path(), None)?;
+    assert!(repo.is_read_only());
+
+    let object = repo.get_object(object_id, VersionRef::Head)?;
+    assert_eq!(1, object.state.len());
+
+    temp.child("a.txt").write_str("a").unwrap();
+
+    fn assert_read_only<T>(result: Result<T>, operation: &str) {
+        match result {
+            Err(RocflError::ReadOnly(message)) => assert_eq!(operation, message),
+            Err(e) => panic!("Expected a read-only error but was: {}", e),
+            Ok(_) => panic!("Expected a read-only error"),
+        }
+    }
+
+    assert_read_only(
+        repo.create_object("new", None, DigestAlgorithm::Sha512, "content", 0),
+        "create an object",
+    );
+    assert_read_only(
+        repo.copy_files_external(object_id, &[temp.child("a.txt").path()], "/", false),
+        "copy files",
+    );
+    assert_read_only(
+        repo.move_files_internal(object_id, &["test.txt"], "moved.txt"),
+        "move files",
+    );
+    assert_read_only(
+        repo.remove_files(object_id, &["test.txt"], false),
+        "remove files",
+    );
+    assert_read_only(
+        repo.commit(object_id, CommitMeta::new(), None, false),
+        "commit",
+    );
+    assert_read_only(repo.purge_object(object_id), "purge an object");
+
+    assert_eq!(0, repo.list_staged_objects(None)?.count());
+    assert_eq!(1, repo.list_objects(None)?.count());
+
+    Ok(())
+}
+
+#[test]
+fn dry_run_plans_changes_without_making_them() -> Result<()> {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let repo = default_repo(root.path());
+
+    let object_id = "dry-run";
+
+    create_simple_object(object_id, &repo, &temp);
+    temp.child("new.txt").write_str("new").unwrap();
+
+    repo.set_dry_run(true);
+
+    repo.copy_files_external(object_id, &[temp.child("new.txt").path()], "/", false)?;
+    repo.move_files_internal(object_id, &["test.txt"], "moved.txt")?;
+    repo.remove_files(object_id, &["test.txt"], false)?;
+    repo.purge_object(object_id)?;
+
+    assert!(matches!(
+        repo.commit(object_id, CommitMeta::new(), None, false),
+        Err(RocflError::General(_))
+    ));
+    assert!(matches!(
+        repo.reset_all(object_id),
+        Err(RocflError::IllegalOperation(_))
+    ));
+
+    let plans = repo.take_change_plans();
+
+    assert_eq!(
+        vec![
+            vec![
+                PlannedChange::AddPath("new.txt".to_string()),
+                PlannedChange::StageFile("v2/content/new.txt".to_string()),
+            ],
+            vec![
+                PlannedChange::AddPath("moved.txt".to_string()),
+                PlannedChange::RemovePath("test.txt".to_string()),
+            ],
+            vec![PlannedChange::RemovePath("test.txt".to_string())],
+        ],
+        plans[..3]
+            .iter()
+            .map(|plan| plan.changes.clone())
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(
+        vec!["copy", "move", "remove", "purge"],
+        plans
+            .iter()
+            .map(|p| p.operation.as_str())
+            .collect::<Vec<_>>()
+    );
+    assert!(plans[3].changes.iter().any(|change| matches!(
+        change,
+        PlannedChange::DeleteFile(path) if path.ends_with("/v1/content/test.txt")
+    )));
+    assert!(repo.take_change_plans().is_empty());
+
+    assert_staged_obj_not_exists(&repo, object_id);
+    let object = repo.get_object(object_id, VersionRef::Head)?;
+    assert_eq!(VersionNum::try_from(1)?, object.version_details.version_num);
+    assert!(object
+        .state
+        .contains_key(&LogicalPath::try_from("test.txt")?));
+
+    repo.set_dry_run(false);
+    repo.copy_files_external(object_id, &[temp.child("new.txt").path()], "/", false)?;
+    repo.set_dry_run(true);
+
+    repo.move_files_internal(object_id, &["new.txt"], "renamed.txt")?;
+
+    assert_eq!(
+        vec![
+            PlannedChange::RemovePath("new.txt".to_string()),
+            PlannedChange::AddPath("renamed.txt".to_string()),
+            PlannedChange::StageFile("v2/content/renamed.txt".to_string()),
+            PlannedChange::UnstageFile("v2/content/new.txt".to_string()),
+        ],
+        repo.take_change_plans()[0].changes
+    );
+    assert!(repo
+        .get_staged_object(object_id)?
+        .state
+        .contains_key(&LogicalPath::try_from("new.txt")?));
+
+    repo.commit(object_id, CommitMeta::new(), None, false)?;
+
+    let object_root =
+        "25d/331/83d/25d33183d01ca7827df2eac040cbc6b605e44890bb878b56993f3d08e432c551";
+    let write = |path: &str| PlannedChange::WriteFile(format!("{}/{}", object_root, path));
+
+    assert_eq!(
+        vec![
+            PlannedChange::CreateVersion(VersionNum::try_from(2)?),
+            PlannedChange::AddPath("new.txt".to_string()),
+            write("v2/content/new.txt"),
+            write("v2/inventory.json"),
+            write("v2/inventory.json.sha512"),
+            write("inventory.json"),
+            write("inventory.json.sha512"),
+        ],
+        repo.take_change_plans()[0].changes
+    );
+    assert_eq!(
+        VersionNum::try_from(1)?,
+        repo.get_object(object_id, VersionRef::Head)?
+            .version_details
+            .version_num
+    );
+    assert!(repo.get_staged_object(object_id).is_ok());
+
+    Ok(())
+}
+
+#[test]
+fn frozen_objects_cannot_be_modified_unless_overridden() -> Result<()> {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let repo = default_repo(root.path());
+
+    let object_id = "frozen";
+
+    create_simple_object(object_id, &repo, &temp);
+    create_simple_object("thawed", &repo, &temp);
+
+    assert_eq!(None, repo.get_freeze_marker(object_id)?);
+
+    repo.freeze_object(object_id, Some("legal hold"))?;
+
+    let marker = repo.get_freeze_marker(object_id)?.unwrap();
+    assert_eq!(object_id, marker.object_id);
+    assert_eq!(Some("legal hold".to_string()), marker.reason);
+    assert_eq!(Some(marker), repo.describe_object(object_id)?.frozen);
+
+    let frozen: Vec<String> = repo
+        .list_objects_filtered(None, ListFilter::new().with_frozen())?
+        .map(|object| object.unwrap().id)
+        .collect();
+    assert_eq!(vec![object_id.to_string()], frozen);
+
+    fn assert_frozen<T>(result: Result<T>, operation: &str) {
+        match result {
+            Err(RocflError::Frozen(message, object_id)) => {
+                assert_eq!(operation, message);
+                assert_eq!("frozen", object_id);
+            }
+            Err(e) => panic!("Expected a frozen error but was: {}", e),
+            Ok(_) => panic!("Expected a frozen error"),
+        }
+    }
+
+    repo.copy_files_external(
+        object_id,
+        &[create_file(&temp, "a.txt", "a").path()],
+        "/",
+        false,
+    )?;
+
+    assert_frozen(
+        repo.commit(object_id, CommitMeta::new(), None, false),
+        "commit",
+    );
+    assert_frozen(
+        repo.upgrade_object(object_id, SpecVersion::Ocfl1_1, CommitMeta::new(), false),
+        "upgrade an object",
+    );
+    assert_frozen(repo.purge_object(object_id), "purge an object");
+
+    assert_eq!(VersionNum::try_from(1)?, repo.head_version(object_id)?);
+
+    repo.set_override_frozen(true);
+    repo.commit(object_id, CommitMeta::new(), None, false)?;
+    repo.set_override_frozen(false);
+
+    assert_eq!(VersionNum::try_from(2)?, repo.head_version(object_id)?);
+    no_errors(&repo.validate_object(object_id, true)?);
+
+    repo.unfreeze_object(object_id)?;
+
+    assert_eq!(None, repo.get_freeze_marker(object_id)?);
+    assert!(repo.describe_object(object_id)?.extensions.is_empty());
+
+    repo.purge_object(object_id)?;
+    assert_obj_not_exists(&repo, object_id);
+
+    Ok(())
+}
+
+#[test]
+fn repo_operations_fail_after_timeout_elapses() {
+    let repo_root = create_repo_root("multiple-objects");
+    let repo = Arc::new(OcflRepo::fs_repo(&repo_root, None).unwrap());
+
+    repo.set_timeout(Duration::from_secs(60));
+    assert!(repo.get_object("o1", VersionRef::Head).is_ok());
+    assert!(!repo.is_timed_out());
+
+    repo.set_timeout(Duration::from_millis(10));
+    thread::sleep(Duration::from_millis(500));
+
+    assert!(repo.is_timed_out());
+    match repo.get_object("o1", VersionRef::Head) {
+        Err(RocflError::TimedOut(timeout)) => assert_eq!(Duration::from_millis(10), timeout),
+        result => panic!("Expected a timeout error; found: {:?}", result.map(|_| ())),
+    }
+}
+
+#[test]
+fn preserve_original_timestamps_of_external_files() -> Result<()> {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let repo = default_repo(root.path());
+
+    let object_id = "timestamps";
+
+    let copied_time = Local.with_ymd_and_hms(2001, 2, 3, 4, 5, 6).unwrap();
+    let moved_time = Local.with_ymd_and_hms(2010, 11, 12, 13, 14, 15).unwrap();
+
+    let copied = create_file(&temp, "dir/copied.txt", "copied");
+    let moved = create_file(&temp, "moved.txt", "moved");
+    File::options()
+        .write(true)
+        .open(copied.path())?
+        .set_modified(copied_time.into())?;
+    File::options()
+        .write(true)
+        .open(moved.path())?
+        .set_modified(moved_time.into())?;
+
+    repo.create_object(object_id, None, DigestAlgorithm::Sha512, "content", 0)?;
+
+    repo.copy_files_external(
+        object_id,
+        &[create_file(&temp, "untracked.txt", "untracked").path()],
+        "/",
+        false,
+    )?;
+
+    repo.set_preserve_timestamps(true);
+    repo.copy_files_external(object_id, &[resolve_child(&temp, "dir").path()], "/", true)?;
+    repo.move_files_external(object_id, &[moved.path()], "a/")?;
+
+    let expected = btreemap! {
+        "dir/copied.txt".to_string() => copied_time,
+        "a/moved.txt".to_string() => moved_time,
+    };
+
+    assert_eq!(expected, repo.get_staged_original_timestamps(object_id)?);
+
+    commit(object_id, &repo);
+
+    assert_eq!(
+        expected,
+        repo.get_original_timestamps(object_id, VersionRef::Head)?
+    );
+    assert!(repo
+        .get_object(object_id, VersionRef::Head)?
+        .state
+        .contains_key(&lpath("metadata/rocfl-timestamps.json")));
+
+    Ok(())
+}
+
+#[test]
+fn check_health_reports_structural_problems() -> Result<()> {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let repo = default_repo(root.path());
+
+    create_simple_object("interrupted", &repo, &temp);
+    create_simple_object("no-sidecar", &repo, &temp);
+    repo.create_object("staged", None, DigestAlgorithm::Sha512, "content", 0)?;
+
+    let report = repo.check_health(Duration::from_secs(60))?;
+    assert_eq!(2, report.objects_checked);
+    assert_eq!(1, report.staged_objects_checked);
+    assert!(report.problems.is_empty());
+
+    let interrupted_root = repo
+        .get_object_details("interrupted", VersionRef::Head)?
+        .object_root;
+    fs::create_dir_all(Path::new(&interrupted_root).join("v2").join("content"))?;
+
+    let no_sidecar_root = repo
+        .get_object_details("no-sidecar", VersionRef::Head)?
+        .object_root;
+    fs::remove_file(Path::new(&no_sidecar_root).join("inventory.json.sha512"))?;
+
+    let lock = root
+        .path()
+        .join("extensions/rocfl-staging/extensions/rocfl-locks/stale.lock");
+    File::create(&lock)?
+        .set_modified(Local.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap().into())?;
+
+    let mut problems = repo.check_health(Duration::from_secs(60))?.problems;
+    problems.sort_by_key(|problem| problem.check);
+
+    let checks: Vec<(HealthCheck, Option<&str>)> = problems
+        .iter()
+        .map(|problem| (problem.check, problem.object_id.as_deref()))
+        .collect();
+    assert_eq!(
+        vec![
+            (HealthCheck::InterruptedCommit, Some("interrupted")),
+            (HealthCheck::InventorySidecar, Some("no-sidecar")),
+            (HealthCheck::StaleLock, None),
+        ],
+        checks
+    );
+    assert!(problems[0].path.as_ref().unwrap().ends_with("v2"));
+    assert_eq!(
+        Some(lock.to_string_lossy().as_ref()),
+        problems[2].path.as_deref()
+    );
+
+    Ok(())
+}
+
+#[test]
+fn find_and_clean_orphaned_locks_and_staging_dirs() -> Result<()> {
+    let root = TempDir::new().unwrap();
+
+    let repo = default_repo(root.path());
+
+    repo.create_object("staged", None, DigestAlgorithm::Sha512, "content", 0)?;
+
+    assert!(repo.find_orphans(Duration::from_secs(60))?.is_empty());
+
+    let staging = root.path().join("extensions/rocfl-staging");
+
+    let lock = staging.join("extensions/rocfl-locks/stale.lock");
+    File::create(&lock)?
+        .set_modified(Local.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap().into())?;
+
+    let interrupted = staging.join("aaa/bbb/ccc/interrupted");
+    fs::create_dir_all(&interrupted)?;
+    fs::write(interrupted.join("0=ocfl_object_1.0"), "ocfl_object_1.0\n")?;
+
+    let mut orphans = repo.find_orphans(Duration::from_secs(60))?;
+    orphans.sort_by_key(|orphan| orphan.path.clone());
+
+    assert_eq!(2, orphans.len());
+    assert_eq!(OrphanKind::StagingDir, orphans[0].kind);
+    assert_eq!(interrupted, orphans[0].path);
+    assert_eq!(OrphanKind::StaleLock, orphans[1].kind);
+    assert_eq!(lock, orphans[1].path);
+    assert_eq!(None, orphans[1].object_id);
+
+    repo.clean_orphans(&orphans)?;
+
+    assert!(!lock.exists());
+    assert!(!staging.join("aaa").exists());
+    assert!(repo.find_orphans(Duration::from_secs(60))?.is_empty());
+    assert_eq!(1, repo.list_staged_objects(None)?.count());
+
+    Ok(())
+}
+
+#[test]
+fn clean_orphans_skips_orphans_that_changed_after_they_were_found() -> Result<()> {
+    let root = TempDir::new().unwrap();
+
+    let repo = default_repo(root.path());
+
+    repo.create_object("staged", None, DigestAlgorithm::Sha512, "content", 0)?;
+
+    let staging = root.path().join("extensions/rocfl-staging");
+
+    let lock = staging.join("extensions/rocfl-locks/stale.lock");
+    File::create(&lock)?
+        .set_modified(Local.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap().into())?;
+
+    let interrupted = staging.join("aaa/bbb/ccc/interrupted");
+    fs::create_dir_all(&interrupted)?;
+    fs::write(interrupted.join("0=ocfl_object_1.0"), "ocfl_object_1.0\n")?;
+
+    let orphans = repo.find_orphans(Duration::from_secs(60))?;
+    assert_eq!(2, orphans.len());
+
+    // Another process takes over the lock and finishes staging the object
+    File::create(&lock)?
+        .set_modified(Local.with_ymd_and_hms(2021, 1, 1, 0, 0, 0).unwrap().into())?;
+    fs::write(interrupted.join("inventory.json"), "{}")?;
+
+    repo.clean_orphans(&orphans)?;
+
+    assert!(lock.exists());
+    assert!(interrupted.join("inventory.json").exists());
+    assert_eq!(1, fs::read_dir(staging.join("aaa/bbb/ccc"))?.count());
+
+    Ok(())
+}
+
+#[test]
+fn remove_orphaned_content_files() -> Result<()> {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let repo = default_repo(root.path());
+
+    let object_id = "orphans";
+
+    create_simple_object(object_id, &repo, &temp);
+
+    assert!(repo.list_orphaned_content(object_id)?.is_empty());
+
+    let object_root = PathBuf::from(repo.get_object(object_id, VersionRef::Head)?.object_root);
+    let content_dir = object_root.join("v1").join("content");
+    fs::write(content_dir.join("orphan.txt"), "orphan")?;
+    fs::create_dir_all(content_dir.join("interrupted").join("dir"))?;
+    fs::write(
+        content_dir.join("interrupted").join("dir").join("file.txt"),
+        "orphan",
+    )?;
+
+    assert!(repo
+        .validate_object(object_id, true)?
+        .errors()
+        .iter()
+        .any(|error| error.code == ErrorCode::E023));
+
+    let expected = vec![
+        "v1/content/interrupted/dir/file.txt".to_string(),
+        "v1/content/orphan.txt".to_string(),
+    ];
+
+    assert_eq!(expected, repo.list_orphaned_content(object_id)?);
+    assert_eq!(expected, repo.rm_orphaned_content(object_id)?);
+
+    assert!(!content_dir.join("orphan.txt").exists());
+    assert!(!content_dir.join("interrupted").exists());
+    assert!(repo.list_orphaned_content(object_id)?.is_empty());
+
+    let result = repo.validate_object(object_id, true)?;
+    no_errors(&result);
+
+    Ok(())
+}
+
+#[test]
+fn object_fingerprint_changes_when_object_is_committed() -> Result<()> {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let repo = default_repo(root.path());
+
+    let object_id = "fingerprint";
+
+    create_simple_object(object_id, &repo, &temp);
+
+    let first = repo.object_fingerprint(object_id)?;
+    let object_root = repo
+        .get_object_details(object_id, VersionRef::Head)?
+        .object_root;
+    let sidecar = fs::read_to_string(PathBuf::from(object_root).join("inventory.json.sha512"))?;
+
+    assert_eq!(object_id, first.object_id);
+    assert_eq!(DigestAlgorithm::Sha512, first.digest_algorithm);
+    assert_eq!(
+        sidecar.split_whitespace().next().unwrap(),
+        first.inventory_digest.as_ref()
+    );
+    assert_eq!(None, first.e_tag);
+    assert_eq!(first, repo.object_fingerprint(object_id)?);
+
+    repo.copy_files_external(
+        object_id,
+        &[create_file(&temp, "b.txt", "b").path()],
+        "/",
+        false,
+    )?;
+    repo.commit(object_id, CommitMeta::new(), None, false)?;
+
+    assert_ne!(
+        first.inventory_digest,
+        repo.object_fingerprint(object_id)?.inventory_digest
+    );
+
+    match repo.object_fingerprint("missing") {
+        Err(RocflError::NotFound(_)) => (),
+        result => panic!("Expected a not found error; found {:?}", result),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn commit_callouts_inspect_new_files_and_may_veto_commit() -> Result<()> {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let inspected = Arc::new(Mutex::new(Vec::new()));
+    let inspected_clone = inspected.clone();
+
+    let repo = default_repo(root.path()).with_commit_callout(
+        move |file: &CalloutFile| -> Result<CalloutVerdict> {
+            let content = fs::read_to_string(&file.path)?;
+            inspected_clone.lock().unwrap().push((
+                file.object_id.clone(),
+                file.logical_paths
+                    .iter()
+                    .map(|path| path.to_string())
+                    .collect::<Vec<String>>(),
+            ));
+            if content.contains("EICAR") {
+                Ok(CalloutVerdict::Reject("malware detected".to_string()))
+            } else {
+                Ok(CalloutVerdict::Accept)
+            }
+        },
+    );
+
+    let object_id = "callout";
+
+    create_simple_object(object_id, &repo, &temp);
+    assert_eq!(
+        vec![(object_id.to_string(), vec!["test.txt".to_string()])],
+        *inspected.lock().unwrap()
+    );
+    inspected.lock().unwrap().clear();
+
+    repo.copy_files_external(
+        object_id,
+        &[
+            create_file(&temp, "bad.txt", "EICAR test").path(),
+            create_file(&temp, "good.txt", "fine").path(),
+        ],
+        "/",
+        false,
+    )?;
+    repo.copy_files_internal(
+        object_id,
+        VersionRef::Head,
+        &["test.txt"],
+        "copy.txt",
+        false,
+    )?;
+
+    match repo.commit(object_id, CommitMeta::new(), None, false) {
+        Err(RocflError::CommitRejected { path, reason, .. }) => {
+            assert_eq!("bad.txt", path);
+            assert_eq!("malware detected", reason);
+        }
+        Err(e) => panic!("Expected the commit to be rejected but was: {}", e),
+        Ok(_) => panic!("Expected the commit to be rejected"),
+    }
+
+    assert_eq!(VersionNum::v1(), repo.head_version(object_id)?);
+    assert!(repo.get_staged_object(object_id).is_ok());
+
+    // Files committed in earlier versions are not inspected again
+    assert!(inspected
+        .lock()
+        .unwrap()
+        .iter()
+        .all(|(_, paths)| !paths.contains(&"copy.txt".to_string())));
+
+    repo.remove_files(object_id, &["bad.txt"], false)?;
+    repo.commit(object_id, CommitMeta::new(), None, false)?;
+
+    assert_eq!(VersionNum::try_from(2)?, repo.head_version(object_id)?);
+
+    Ok(())
+}
+
+#[test]
+fn encrypted_objects_are_decrypted_when_read_and_fixity_checked() -> Result<()> {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let key = EncryptionKey::from_base64("q6/5kQmVrm0gCMkt1WPyEz3CFLOUbT5wSXfvY7Qh8Zs=")?;
+    let other_key = EncryptionKey::from_base64("hY0GJ0BzLSwUKkF4mG7VN2u7cZj/Mg8T5DxzQ8m7XqA=")?;
+
+    create_simple_object("plain", &default_repo(root.path()), &temp);
+
+    let repo = OcflRepo::fs_repo(root.path(), None)?.with_encryption_key(key);
+    let object_id = "encrypted";
+
+    create_simple_object(object_id, &repo, &temp);
+    repo.copy_files_external(
+        object_id,
+        &[create_file(&temp, "second.txt", "more secrets").path()],
+        "/",
+        false,
+    )?;
+    repo.commit(object_id, CommitMeta::new(), None, false)?;
+
+    assert!(repo
+        .describe_object(object_id)?
+        .extensions
+        .contains(&"rocfl-encryption".to_string()));
+
+    let object = repo.get_object(object_id, VersionRef::Head)?;
+    for (path, expected) in [("test.txt", "testing"), ("second.txt", "more secrets")] {
+        let stored = fs::read(&object.state[&lpath(path)].storage_path)?;
+        assert!(!stored
+            .windows(expected.len())
+            .any(|w| w == expected.as_bytes()));
+
+        let mut content = Vec::new();
+        repo.get_object_file_verified(object_id, &lpath(path), VersionRef::Head, &mut content)?;
+        assert_eq!(expected.as_bytes(), content.as_slice());
+    }
+
+    let result = repo.validate_object(object_id, true)?;
+    no_errors(&result);
+    assert!(result
+        .warnings()
+        .iter()
+        .all(|warning| warning.code != WarnCode::RW008));
+    assert_eq!(2, result.fixity_checked().len());
+
+    // Objects that were created before encryption was enabled are not encrypted
+    let mut content = Vec::new();
+    repo.get_object_file("plain", &lpath("test.txt"), VersionRef::Head, &mut content)?;
+    assert_eq!(b"testing", content.as_slice());
+    no_errors(&repo.validate_object("plain", true)?);
+
+    let without_key = OcflRepo::fs_repo(root.path(), None)?;
+    let with_other_key = OcflRepo::fs_repo(root.path(), None)?.with_encryption_key(other_key);
+
+    for other in [&without_key, &with_other_key] {
+        match other.get_object_file(
+            object_id,
+            &lpath("test.txt"),
+            VersionRef::Head,
+            &mut Vec::new(),
+        ) {
+            Err(RocflError::InvalidConfiguration(_)) => (),
+            result => panic!("Expected a configuration error but was: {:?}", result),
+        }
+    }
+
+    let result = without_key.validate_object(object_id, true)?;
+    no_errors(&result);
+    assert!(result.fixity_checked().is_empty());
+    assert!(result.warnings().iter().any(|warning| {
+        warning.code == WarnCode::RW008
+            && warning.text
+                == "Content files could not be fixity checked because the object is encrypted \
+                and an encryption key is not configured"
+    }));
+
+    Ok(())
+}
+
+#[test]
+fn encrypted_object_sizes_are_plaintext_sizes() -> Result<()> {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let key = EncryptionKey::from_base64("q6/5kQmVrm0gCMkt1WPyEz3CFLOUbT5wSXfvY7Qh8Zs=")?;
+    default_repo(root.path());
+    let repo = OcflRepo::fs_repo(root.path(), None)?.with_encryption_key(key);
+    let object_id = "encrypted";
+
+    // Spans several encryption chunks
+    let large = "x".repeat(2 * 64 * 1024 + 10);
+
+    create_simple_object(object_id, &repo, &temp);
+    repo.copy_files_external(
+        object_id,
+        &[create_file(&temp, "large.txt", &large).path()],
+        "/",
+        false,
+    )?;
+    commit(object_id, &repo);
+
+    let plaintext_size = (large.len() + "testing".len()) as u64;
+
+    let object = repo.get_object(object_id, VersionRef::Head)?;
+    let stored_size = fs::metadata(&object.state[&lpath("large.txt")].storage_path)?.len();
+    assert!(stored_size > large.len() as u64);
+
+    let sizes = repo.list_object_version_sizes(object_id)?;
+    assert_eq!(
+        vec![7, large.len() as u64],
+        sizes
+            .iter()
+            .map(|size| size.byte_count)
+            .collect::<Vec<u64>>()
+    );
+
+    let tree = repo.get_object_tree(object_id, VersionRef::Head, true)?;
+    assert_eq!(Some(plaintext_size), tree.size);
+    assert_eq!(
+        Some(large.len() as u64),
+        tree.find("large.txt").unwrap().size
+    );
+
+    let list = |filter: ListFilter| -> Result<usize> {
+        Ok(repo.list_objects_filtered(None, filter)?.count())
+    };
+    assert_eq!(1, list(ListFilter::new().with_min_size(plaintext_size))?);
+    assert_eq!(1, list(ListFilter::new().with_max_size(plaintext_size))?);
+
+    let repo = repo.with_commit_quotas(CommitQuotas {
+        max_object_size: Some(plaintext_size + 1),
+        max_version_files: None,
+        max_staged_bytes: None,
+    });
+    repo.copy_files_external(
+        object_id,
+        &[create_file(&temp, "b.txt", "b").path()],
+        "/",
+        false,
+    )?;
+    repo.commit(object_id, CommitMeta::new(), None, false)?;
+
+    Ok(())
+}
+
+#[test]
+fn commits_that_exceed_quotas_fail_unless_overridden() -> Result<()> {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let repo = default_repo(root.path()).with_commit_quotas(CommitQuotas {
+        max_object_size: Some(15),
+        max_version_files: Some(2),
+        max_staged_bytes: Some(5),
+    });
+
+    let object_id = "quotas";
+
+    repo.set_override_quotas(true);
+    create_simple_object(object_id, &repo, &temp);
+    repo.set_override_quotas(false);
+
+    fn assert_exceeded(result: Result<()>, expected_quota: &str, expected_actual: u64) {
+        match result {
+            Err(RocflError::QuotaExceeded { quota, actual, .. }) => {
+                assert_eq!(expected_quota, quota);
+                assert_eq!(expected_actual, actual);
+            }
+            Err(e) => panic!("Expected a quota error but was: {}", e),
+            Ok(_) => panic!("Expected a quota error"),
+        }
+    }
+
+    // Content that duplicates committed content is not counted as staged bytes
+    repo.copy_files_external(
+        object_id,
+        &[create_file(&temp, "c.txt", "testing").path()],
+        "/",
+        false,
+    )?;
+    repo.commit(object_id, CommitMeta::new(), None, false)?;
+
+    repo.remove_files(object_id, &["c.txt"], false)?;
+    repo.copy_files_external(
+        object_id,
+        &[create_file(&temp, "a.txt", "abcdefghijk").path()],
+        "/",
+        false,
+    )?;
+
+    assert_exceeded(
+        repo.commit(object_id, CommitMeta::new(), None, false),
+        "max_staged_bytes",
+        11,
+    );
+    assert_eq!(VersionNum::try_from(2)?, repo.head_version(object_id)?);
+
+    repo.set_override_quotas(true);
+    repo.commit(object_id, CommitMeta::new(), None, false)?;
+    repo.set_override_quotas(false);
+
+    assert_eq!(VersionNum::try_from(3)?, repo.head_version(object_id)?);
+
+    repo.copy_files_external(
+        object_id,
+        &[create_file(&temp, "b.txt", "b").path()],
+        "/",
+        false,
+    )?;
+
+    assert_exceeded(
+        repo.commit(object_id, CommitMeta::new(), None, false),
+        "max_version_files",
+        3,
+    );
+
+    repo.remove_files(object_id, &["test.txt"], false)?;
+
+    // Content committed in earlier versions counts towards the object's size
+    assert_exceeded(
+        repo.commit(object_id, CommitMeta::new(), None, false),
+        "max_object_size",
+        19,
+    );
+    assert_eq!(VersionNum::try_from(3)?, repo.head_version(object_id)?);
+
+    repo.set_override_quotas(true);
+    repo.commit(object_id, CommitMeta::new(), None, false)?;
+
+    assert_eq!(VersionNum::try_from(4)?, repo.head_version(object_id)?);
+    no_errors(&repo.validate_object(object_id, true)?);
+
+    Ok(())
+}
+
+#[test]
+fn compare_objects_with_different_digest_algorithms() -> Result<()> {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let repo = default_repo(root.path());
+
+    let object_a = "object-a";
+    let object_b = "object-b";
+
+    repo.create_object(object_a, None, DigestAlgorithm::Sha512, "content", 0)?;
+    repo.create_object(object_b, None, DigestAlgorithm::Sha256, "content", 0)?;
+
+    temp.child("a/same.txt").write_str("same").unwrap();
+    temp.child("a/changed.txt").write_str("before").unwrap();
+    temp.child("a/only-a.txt").write_str("a").unwrap();
+    temp.child("b/same.txt").write_str("same").unwrap();
+    temp.child("b/changed.txt").write_str("after").unwrap();
+    temp.child("b/only-b.txt").write_str("b").unwrap();
+
+    for (object_id, dir, only) in [(object_a, "a", "only-a.txt"), (object_b, "b", "only-b.txt")] {
+        let files = ["same.txt", "changed.txt", only].map(|name| temp.child(dir).child(name));
+        repo.copy_files_external(object_id, &files.map(|file| file.to_path_buf()), "/", false)?;
+    }
+    commit(object_a, &repo);
+    commit(object_b, &repo);
+
+    let diffs = repo.compare_objects(object_a, VersionRef::Head, object_b, VersionRef::Head)?;
+
+    assert_eq!(
+        vec![
+            ObjectDiff::ContentDiffers(lpath_rc("changed.txt")),
+            ObjectDiff::OnlyInA(lpath_rc("only-a.txt")),
+            ObjectDiff::OnlyInB(lpath_rc("only-b.txt")),
+        ],
+        diffs
+    );
+
+    assert!(repo
+        .compare_objects(object_a, VersionRef::Head, object_a, VersionRef::Head)?
+        .is_empty());
+
+    match repo.compare_objects(object_a, VersionRef::Head, "missing", VersionRef::Head) {
+        Err(RocflError::NotFound(_)) => (),
+        _ => panic!("Expected the comparison to fail with not found"),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn list_objects_and_files_filtered_by_date_and_size() -> Result<()> {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let repo = default_repo(root.path());
+
+    let create = |object_id: &str, files: &[(&str, &str)], year: i32| -> Result<()> {
+        repo.create_object(object_id, None, DigestAlgorithm::Sha512, "content", 0)?;
+        for (name, content) in files {
+            temp.child(name).write_str(content).unwrap();
+            repo.copy_files_external(object_id, &[temp.child(name).path()], name, false)?;
+        }
+        repo.commit(
+            object_id,
+            CommitMeta::new()
+                .with_created(Some(Local.with_ymd_and_hms(year, 1, 1, 0, 0, 0).unwrap())),
+            None,
+            false,
+        )
+    };
+
+    create("old", &[("a.txt", "a")], 2000)?;
+    create("new", &[("b.txt", "bbbbbbbbbb"), ("c.txt", "cc")], 2020)?;
+
+    let list = |filter: ListFilter| -> Result<Vec<String>> {
+        let mut ids = repo
+            .list_objects_filtered(None, filter)?
+            .map(|object| object.unwrap().id)
+            .collect::<Vec<String>>();
+        ids.sort();
+        Ok(ids)
+    };
+
+    let date = Local.with_ymd_and_hms(2010, 1, 1, 0, 0, 0).unwrap();
+
+    assert_eq!(vec!["new", "old"], list(ListFilter::new())?);
+    assert_eq!(
+        vec!["old"],
+        list(ListFilter::new().with_created_before(date))?
+    );
+    assert_eq!(
+        vec!["new"],
+        list(ListFilter::new().with_created_after(date))?
+    );
+    assert_eq!(vec!["new"], list(ListFilter::new().with_min_size(12))?);
+    assert_eq!(vec!["old"], list(ListFilter::new().with_max_size(11))?);
+    assert!(list(ListFilter::new().with_created_before(date).with_min_size(2))?.is_empty());
+
+    let object =
+        repo.get_object_filtered("new", VersionRef::Head, ListFilter::new().with_min_size(3))?;
+    assert_eq!(1, object.state.len());
+    assert!(object.state.contains_key(&lpath_rc("b.txt")));
+
+    let object = repo.get_object_filtered(
+        "new",
+        VersionRef::Head,
+        ListFilter::new().with_min_size(2).with_max_size(2),
+    )?;
+    assert_eq!(1, object.state.len());
+    assert!(object.state.contains_key(&lpath_rc("c.txt")));
+
+    let object = repo.get_object_filtered(
+        "new",
+        VersionRef::Head,
+        ListFilter::new().with_created_before(date),
+    )?;
+    assert!(object.state.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn get_object_subset_only_resolves_matching_paths() -> Result<()> {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let repo = default_repo(root.path());
+    let object_id = "subset";
+
+    repo.create_object(
+        object_id,
+        Some(SpecVersion::Ocfl1_0),
+        DigestAlgorithm::Sha512,
+        "content",
+        0,
+    )?;
+
+    for (path, content) in [
+        ("a/b/one.txt", "one"),
+        ("a/b/c/two.txt", "two"),
+        ("a/three.txt", "three"),
+        ("other.txt", "other"),
+    ] {
+        temp.child(path).write_str(content).unwrap();
+        repo.copy_files_external(object_id, &[temp.child(path).path()], path, false)?;
+    }
+    commit(object_id, &repo);
+
+    temp.child("two.txt").write_str("two updated").unwrap();
+    repo.copy_files_external(
+        object_id,
+        &[temp.child("two.txt").path()],
+        "a/b/c/two.txt",
+        false,
+    )?;
+    commit(object_id, &repo);
+
+    let full = repo.get_object(object_id, VersionRef::Head)?;
+
+    let subset = repo.get_object_subset(object_id, VersionRef::Head, "a/b")?;
+    assert_eq!(2, subset.state.len());
+    for path in ["a/b/one.txt", "a/b/c/two.txt"] {
+        assert_eq!(
+            full.state.get(&lpath_rc(path)),
+            subset.state.get(&lpath_rc(path))
+        );
+    }
+    assert_eq!(
+        VersionNum::try_from(2)?,
+        subset.state[&lpath_rc("a/b/c/two.txt")]
+            .last_update
+            .version_num
+    );
+    assert_eq!(full.version_details, subset.version_details);
+
+    let subset = repo.get_object_subset(object_id, VersionRef::Head, "/a/*.txt")?;
+    assert_eq!(1, subset.state.len());
+    assert!(subset.state.contains_key(&lpath_rc("a/three.txt")));
+
+    let subset = repo.get_object_subset(object_id, VersionNum::v1().into(), "a/b/c")?;
+    assert_eq!(
+        VersionNum::v1(),
+        subset.state[&lpath_rc("a/b/c/two.txt")]
+            .last_update
+            .version_num
+    );
+
+    assert!(repo
+        .get_object_subset(object_id, VersionRef::Head, "missing")?
+        .state
+        .is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn list_and_get_object_log_files() -> Result<()> {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let repo = default_repo(root.path());
+
+    let object_id = "logged";
+
+    create_simple_object(object_id, &repo, &temp);
+
+    assert!(repo.list_object_logs(object_id)?.is_empty());
+
+    let object_root = PathBuf::from(repo.get_object(object_id, VersionRef::Head)?.object_root);
+    let logs = object_root.join("logs");
+    fs::create_dir_all(logs.join("events"))?;
+    fs::write(logs.join("fixity.log"), "fixity ok")?;
+    fs::write(logs.join("events").join("2022.log"), "ingested")?;
+
+    assert_eq!(
+        vec!["events/2022.log".to_string(), "fixity.log".to_string()],
+        repo.list_object_logs(object_id)?
+    );
+
+    let mut out: Vec<u8> = Vec::new();
+    repo.get_object_log_file(object_id, "events/2022.log", &mut out)?;
+    assert_eq!("ingested", String::from_utf8(out).unwrap());
+
+    repo.add_object_alias(object_id, "hdl:1234/logged")?;
+    let mut out: Vec<u8> = Vec::new();
+    repo.get_object_log_file("hdl:1234/logged", "fixity.log", &mut out)?;
+    assert_eq!("fixity ok", String::from_utf8(out).unwrap());
+
+    match repo.get_object_log_file(object_id, "missing.log", &mut Vec::new()) {
+        Err(RocflError::NotFound(_)) => (),
+        _ => panic!("Expected log file get to fail with not found"),
+    }
+
+    match repo.get_object_log_file(object_id, "../inventory.json", &mut Vec::new()) {
+        Err(RocflError::InvalidValue(_)) => (),
+        _ => panic!("Expected log file get to fail with invalid value"),
+    }
+
+    match repo.list_object_logs("missing") {
+        Err(RocflError::NotFound(_)) => (),
+        _ => panic!("Expected log listing to fail with not found"),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn list_and_get_object_extension_files() -> Result<()> {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let repo = default_repo(root.path());
+
+    let object_id = "extended";
+
+    create_simple_object(object_id, &repo, &temp);
+
+    assert!(repo.list_object_extension_files(object_id)?.is_empty());
+
+    let object_root = PathBuf::from(repo.get_object(object_id, VersionRef::Head)?.object_root);
+    let extensions = object_root.join("extensions");
+    fs::create_dir_all(extensions.join("another-ext").join("state"))?;
+    fs::create_dir_all(extensions.join("custom-ext"))?;
+    fs::write(
+        extensions
+            .join("another-ext")
+            .join("state")
+            .join("state.json"),
+        "{}",
+    )?;
+    fs::write(extensions.join("custom-ext").join("payload.txt"), "custom")?;
+
+    assert_eq!(
+        vec![
+            "another-ext/state/state.json".to_string(),
+            "custom-ext/payload.txt".to_string()
+        ],
+        repo.list_object_extension_files(object_id)?
+    );
+
+    let mut out: Vec<u8> = Vec::new();
+    repo.get_object_extension_file(object_id, "custom-ext/payload.txt", &mut out)?;
+    assert_eq!("custom", String::from_utf8(out).unwrap());
+
+    repo.add_object_alias(object_id, "hdl:1234/extended")?;
+    let mut out: Vec<u8> = Vec::new();
+    repo.get_object_extension_file(
+        "hdl:1234/extended",
+        "another-ext/state/state.json",
+        &mut out,
+    )?;
+    assert_eq!("{}", String::from_utf8(out).unwrap());
+
+    match repo.get_object_extension_file(object_id, "custom-ext/missing.txt", &mut Vec::new()) {
+        Err(RocflError::NotFound(_)) => (),
+        _ => panic!("Expected extension file get to fail with not found"),
+    }
+
+    match repo.get_object_extension_file(object_id, "../inventory.json", &mut Vec::new()) {
+        Err(RocflError::InvalidValue(_)) => (),
+        _ => panic!("Expected extension file get to fail with invalid value"),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn stage_and_commit_object_metadata() -> Result<()> {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let repo = default_repo(root.path());
+
+    let object_id = "metadata";
+    let metadata_path: LogicalPath = "metadata/rocfl.json".try_into()?;
+
+    create_simple_object(object_id, &repo, &temp);
+
+    assert!(repo
+        .get_object_metadata(object_id, VersionRef::Head, &metadata_path)?
+        .is_empty());
+
+    repo.stage_object_metadata(
+        object_id,
+        &metadata_path,
+        &btreemap! {
+            "title".to_string() => Some("Example".to_string()),
+            "creator".to_string() => Some("Someone".to_string()),
+        },
+    )?;
+
+    assert_eq!(
+        btreemap! {
+            "title".to_string() => "Example".to_string(),
+            "creator".to_string() => "Someone".to_string(),
+        },
+        repo.get_staged_object_metadata(object_id, &metadata_path)?
+    );
+
+    commit(object_id, &repo);
+
+    repo.stage_object_metadata(
+        object_id,
+        &metadata_path,
+        &btreemap! {
+            "title".to_string() => Some("Updated".to_string()),
+            "creator".to_string() => None,
+        },
+    )?;
+    commit(object_id, &repo);
+
+    assert_eq!(
+        btreemap! {
+            "title".to_string() => "Updated".to_string(),
+        },
+        repo.get_object_metadata(object_id, VersionRef::Head, &metadata_path)?
+    );
+    assert_eq!(
+        btreemap! {
+            "title".to_string() => "Example".to_string(),
+            "creator".to_string() => "Someone".to_string(),
+        },
+        repo.get_object_metadata(object_id, VersionNum::try_from(2)?.into(), &metadata_path)?
+    );
+
+    let object = repo.get_object(object_id, VersionRef::Head)?;
+    assert!(object.state.contains_key(&metadata_path));
+    assert!(object
+        .state
+        .contains_key(&LogicalPath::try_from("test.txt")?));
+
+    no_errors(&repo.validate_object(object_id, true)?);
+
+    Ok(())
+}
+
+#[test]
+fn get_object_tree_nests_logical_paths() -> Result<()> {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let repo = default_repo(root.path());
+
+    let object_id = "tree";
+
+    create_simple_object(object_id, &repo, &temp);
+
+    temp.child("nested.txt").write_str("nested").unwrap();
+    repo.copy_files_external(
+        object_id,
+        &[temp.child("nested.txt").path()],
+        "a/b/nested.txt",
+        false,
+    )?;
+    commit(object_id, &repo);
+
+    let tree = repo.get_object_tree(object_id, VersionRef::Head, true)?;
+
+    assert!(tree.is_dir());
+    assert_eq!(Some(13), tree.size);
+    assert_eq!(
+        vec!["a", "test.txt"],
+        tree.children
+            .iter()
+            .map(|child| child.name.as_str())
+            .collect::<Vec<&str>>()
+    );
+
+    let dir = tree.find("a/b").unwrap();
+    assert!(dir.is_dir());
+    assert_eq!("a/b", dir.path);
+    assert_eq!(Some(6), dir.size);
+
+    let file = tree.find("a/b/nested.txt").unwrap();
+    assert!(!file.is_dir());
+    assert_eq!(Some(6), file.size);
+    assert_eq!(
+        cpath_rc("v2/content/a/b/nested.txt"),
+        file.details.as_ref().unwrap().content_path
+    );
+
+    assert!(tree.find("a/missing").is_none());
+
+    let v1 = repo.get_object_tree(object_id, VersionNum::v1().into(), false)?;
+    assert_eq!(1, v1.children.len());
+    assert_eq!(None, v1.size);
+    assert!(v1.find("a").is_none());
+
+    Ok(())
+}
+
+#[test]
+fn adopt_version_installs_pre_hashed_version_directory() -> Result<()> {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let repo = default_repo(root.path());
+
+    let object_id = "adopted-version";
+
+    create_simple_object(object_id, &repo, &temp);
+
+    let object_root = PathBuf::from(repo.get_object(object_id, VersionRef::Head)?.object_root);
+    let mut inventory: serde_json::Value =
+        serde_json::from_slice(&fs::read(object_root.join("inventory.json"))?).unwrap();
+
+    let digest = DigestAlgorithm::Sha512
+        .hash_hex(&mut "migrated".as_bytes())?
+        .to_string();
+    let existing_digest = inventory["versions"]["v1"]["state"]
+        .as_object()
+        .unwrap()
+        .keys()
+        .next()
+        .unwrap()
+        .clone();
+
+    inventory["head"] = "v2".into();
+    inventory["manifest"][&digest] = serde_json::json!(["v2/content/dir/migrated.txt"]);
+    inventory["versions"]["v2"] = serde_json::json!({
+        "created": "2022-10-01T12:00:00Z",
+        "message": "migrated",
+        "user": {"name": "Migrator", "address": "mailto:migrator@example.com"},
+        "state": {
+            existing_digest: ["test.txt"],
+            digest.clone(): ["dir/migrated.txt"],
+        },
+    });
+
+    let write_version_dir = |name: &str, content: &str, extra: bool| {
+        let version_dir = temp.child(name);
+        version_dir
+            .child("inventory.json")
+            .write_str(&inventory.to_string())
+            .unwrap();
+        version_dir
+            .child("content/dir/migrated.txt")
+            .write_str(content)
+            .unwrap();
+        if extra {
+            version_dir
+                .child("content/unlisted.txt")
+                .write_str("unlisted")
+                .unwrap();
+        }
+        version_dir
+    };
+
+    let corrupt = write_version_dir("corrupt", "tampered", false);
+    match repo.adopt_version(object_id, corrupt.path(), false) {
+        Err(RocflError::CorruptObject { .. }) => (),
+        _ => panic!("Expected adopting a version with a bad digest to fail"),
+    }
+
+    let unlisted = write_version_dir("unlisted", "migrated", true);
+    match repo.adopt_version(object_id, unlisted.path(), false) {
+        Err(RocflError::InvalidValue(_)) => (),
+        _ => panic!("Expected adopting a version with an unlisted file to fail"),
+    }
+
+    let version_dir = write_version_dir("v2", "migrated", false);
+
+    temp.child("staged.txt").write_str("staged").unwrap();
+    repo.copy_files_external(object_id, &[temp.child("staged.txt").path()], "/", false)?;
+    match repo.adopt_version(object_id, version_dir.path(), false) {
+        Err(RocflError::IllegalState(_)) => (),
+        _ => panic!("Expected adopting a version into an object with staged changes to fail"),
+    }
+    repo.reset_all(object_id)?;
+
+    assert_eq!(
+        VersionNum::try_from(2)?,
+        repo.adopt_version(object_id, version_dir.path(), false)?
+    );
+
+    let object = repo.get_object(object_id, VersionRef::Head)?;
+    assert_eq!(VersionNum::try_from(2)?, object.version_details.version_num);
+    assert_eq!(Some("migrated".to_string()), object.version_details.message);
+    assert_eq!(
+        Some("Migrator".to_string()),
+        object.version_details.user_name
+    );
+    assert_eq!(2, object.state.len());
+
+    let mut out: Vec<u8> = Vec::new();
+    repo.get_object_file(
+        object_id,
+        &lpath("dir/migrated.txt"),
+        VersionRef::Head,
+        &mut out,
+    )?;
+    assert_eq!("migrated", String::from_utf8(out).unwrap());
+
+    let result = repo.validate_object(object_id, FixityCheck::All)?;
+    no_errors(&result);
+
+    match repo.adopt_version(object_id, version_dir.path(), false) {
+        Err(RocflError::NotFound(_)) | Err(RocflError::InvalidValue(_)) => (),
+        _ => panic!("Expected adopting the same version twice to fail"),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn export_objects_to_content_addressed_layout() -> Result<()> {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+    let dst = TempDir::new().unwrap();
+
+    let repo = default_repo(root.path());
+
+    create_simple_object("obj-1", &repo, &temp);
+    create_simple_object("obj:2", &repo, &temp);
+
+    let export = repo.export_cas("obj-1", VersionRef::Head, dst.path())?;
+    assert_eq!(1, export.files_written);
+    assert_eq!(0, export.files_skipped);
+    assert_eq!(7, export.bytes_written);
+
+    let export = repo.export_cas("obj:2", VersionRef::Head, dst.path())?;
+    assert_eq!(0, export.files_written);
+    assert_eq!(1, export.files_skipped);
+
+    let digest = DigestAlgorithm::Sha512
+        .hash_hex(&mut "testing".as_bytes())?
+        .to_string();
+    dst.child(format!("content/sha512/{}/{}", &digest[..2], digest))
+        .assert("testing");
+
+    let manifest: CasManifest = serde_json::from_str(&fs::read_to_string(
+        dst.child("objects/obj%3A2.json").path(),
+    )?)?;
+    assert_eq!(
+        CasManifest {
+            id: "obj:2".to_string(),
+            version: VersionNum::v1(),
+            digest_algorithm: DigestAlgorithm::Sha512,
+            files: btreemap! {"test.txt".to_string() => digest},
+        },
+        manifest
+    );
+
+    temp.child("test.txt").write_str("updated").unwrap();
+    repo.copy_files_external("obj-1", &[temp.child("test.txt").path()], "test.txt", false)?;
+    commit("obj-1", &repo);
+
+    let export = repo.export_cas("obj:2", VersionRef::Head, dst.path())?;
+    assert_eq!(0, export.files_written);
+    assert_eq!(1, export.files_skipped);
+
+    let export = repo.export_cas("obj-1", VersionRef::Head, dst.path())?;
+    assert_eq!(VersionNum::try_from(2).unwrap(), export.version_num);
+    assert_eq!(1, export.files_written);
+    assert_eq!(0, export.files_skipped);
+
+    Ok(())
+}
+
+#[test]
+fn export_and_import_object_zip() -> Result<()> {
+    let root = TempDir::new().unwrap();
+    let other_root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let repo = default_repo(root.path());
+    let other = default_repo(other_root.path());
+
+    let object_id = "obj-1";
+    create_simple_object(object_id, &repo, &temp);
+
+    temp.child("b.txt").write_str("b").unwrap();
+    repo.copy_files_external(object_id, &[temp.child("b.txt").path()], "b.txt", false)?;
+    commit(object_id, &repo);
+
+    let mut archive = Cursor::new(Vec::new());
+    let export = repo.export_object_zip(object_id, &mut archive)?;
+    assert_eq!(object_id, export.object_id);
+    assert_eq!(VersionNum::try_from(2).unwrap(), export.head);
+    // namaste, plus an inventory, sidecar, and content file in the root and each version
+    assert_eq!(9, export.files);
+
+    archive.set_position(0);
+    let import = other.import_object_zip(&mut archive)?;
+    assert_eq!(object_id, import.object_id);
+    assert_eq!(VersionNum::try_from(2).unwrap(), import.head);
+    assert_eq!(9, import.files);
+
+    no_errors(&other.validate_object(object_id, true)?);
+
+    let mut content = Vec::new();
+    other.get_object_file(object_id, &lpath("b.txt"), VersionRef::Head, &mut content)?;
+    assert_eq!(b"b", content.as_slice());
+
+    archive.set_position(0);
+    match other.import_object_zip(&mut archive) {
+        Err(RocflError::IllegalState(message)) => {
+            assert!(message.contains("already exists"))
+        }
+        result => panic!("Expected an illegal state error; found: {:?}", result),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn import_object_zip_rejects_invalid_objects() -> Result<()> {
+    let root = TempDir::new().unwrap();
+    let other_root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let repo = default_repo(root.path());
+    let other = default_repo(other_root.path());
+
+    let object_id = "obj-1";
+    create_simple_object(object_id, &repo, &temp);
+
+    let mut archive = Cursor::new(Vec::new());
+    repo.export_object_zip(object_id, &mut archive)?;
+
+    let mut original = zip::ZipArchive::new(archive).unwrap();
+    let mut tampered = zip::ZipWriter::new(Cursor::new(Vec::new()));
+
+    for i in 0..original.len() {
+        let entry = original.by_index_raw(i).unwrap();
+        if entry.name() == "v1/content/test.txt" {
+            tampered
+                .start_file(entry.name(), zip::write::SimpleFileOptions::default())
+                .unwrap();
+            tampered.write_all(b"tampered")?;
+        } else {
+            tampered.raw_copy_file(entry).unwrap();
+        }
+    }
+
+    let tampered = tampered.finish().unwrap();
+
+    match other.import_object_zip(tampered) {
+        Err(RocflError::InvalidObject { object_id, errors }) => {
+            assert_eq!("obj-1", object_id);
+            assert!(errors.iter().any(|e| e.code == ErrorCode::E092));
+        }
+        result => panic!("Expected an invalid object error; found: {:?}", result),
+    }
+
+    assert!(!other.object_exists(object_id)?);
+    assert!(other.get_staged_object(object_id).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn validation_history_records_most_recent_validation_of_each_object() -> Result<()> {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let repo = OcflRepo::init_fs_repo(
+        root.path(),
+        None,
+        SpecVersion::Ocfl1_1,
+        Some(StorageLayout::new(LayoutExtensionName::HashedNTupleLayout, None).unwrap()),
+    )?
+    .with_validation_history(true);
+
+    create_simple_object("obj-1", &repo, &temp);
+    create_simple_object("obj-2", &repo, &temp);
+
+    let first = repo.validate_object("obj-1", FixityCheck::All)?;
+    let second = repo.validate_object("obj-2", FixityCheck::None)?;
+    repo.record_validations(&[
+        ValidationRecord::from_result(&first).unwrap(),
+        ValidationRecord::from_result(&second).unwrap(),
+    ])?;
+
+    let history = repo.validation_history()?;
+    assert_eq!(2, history.len());
+    assert_eq!("obj-1", history[0].object_id);
+    assert!(history[0].valid);
+    assert_eq!(0, history[0].errors);
+    assert_eq!(1, history[0].fixity_checked);
+    assert_eq!(
+        format!("rocfl {}", env!("CARGO_PKG_VERSION")),
+        history[0].tool_version
+    );
+    assert_eq!("obj-2", history[1].object_id);
+    assert_eq!(0, history[1].fixity_checked);
+
+    root.child("extensions/rocfl-validation-history/history.json")
+        .assert(predicates::path::is_file());
+
+    let revalidated =
+        ValidationRecord::from_result(&repo.validate_object("obj-2", FixityCheck::All)?).unwrap();
+    repo.record_validations(std::slice::from_ref(&revalidated))?;
+
+    assert_eq!(2, repo.validation_history()?.len());
+    assert_eq!(Some(revalidated), repo.validation_record("obj-2")?);
+    assert_eq!(None, repo.validation_record("obj-3")?);
+
+    let unrecorded = OcflRepo::fs_repo(root.path(), None)?;
+    let result = unrecorded.validate_object("obj-1", FixityCheck::All)?;
+    unrecorded.record_validations(&[ValidationRecord::from_result(&result).unwrap()])?;
+
+    assert_eq!(history[0], repo.validation_record("obj-1")?.unwrap());
+
+    Ok(())
+}
+
+#[test]
+fn sampled_validations_continue_from_recorded_sample() -> Result<()> {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let repo = OcflRepo::init_fs_repo(
+        root.path(),
+        None,
+        SpecVersion::Ocfl1_1,
+        Some(StorageLayout::new(LayoutExtensionName::HashedNTupleLayout, None).unwrap()),
+    )?
+    .with_validation_history(true);
+
+    let object_id = "sampled";
+
+    let files: Vec<_> = (0..4)
+        .map(|i| create_file(&temp, &format!("file-{}.txt", i), &format!("content {}", i)))
+        .collect();
+    repo.create_object(object_id, None, DigestAlgorithm::Sha512, "content", 0)?;
+    repo.copy_files_external(
+        object_id,
+        &files.iter().map(|file| file.path()).collect::<Vec<_>>(),
+        "/",
+        false,
+    )?;
+    commit(object_id, &repo);
+
+    // Each run uses a different seed and the same round, which would select overlapping files
+    // if the previous sample was not continued
+    let sample = |seed, continue_recorded| {
+        repo.continue_fixity_samples()?;
+        let result = repo.validate_object(
+            object_id,
+            FixityCheck::Sample(FixitySample {
+                percent: 50,
+                seed,
+                round: 0,
+                continue_recorded,
+            }),
+        )?;
+        no_errors(&result);
+        repo.record_validations(&[ValidationRecord::from_result(&result).unwrap()])?;
+
+        let mut checked = result.fixity_checked().to_vec();
+        checked.sort();
+        Ok::<Vec<String>, RocflError>(checked)
+    };
+
+    let first = sample(1, true)?;
+    let second = sample(2, true)?;
+
+    assert_eq!(2, first.len());
+    assert_eq!(2, second.len());
+    assert!(first.iter().all(|path| !second.contains(path)));
+
+    let record = repo.validation_record(object_id)?.unwrap();
+    let state = record.fixity_sample.unwrap();
+    assert_eq!(1, state.seed);
+    assert_eq!(1, state.round);
+    assert!(state.position.is_some());
+
+    // Every file was checked, so the next sample starts over
+    let third = sample(3, true)?;
+    assert_eq!(first, third);
+    let state = repo
+        .validation_record(object_id)?
+        .unwrap()
+        .fixity_sample
+        .unwrap();
+    assert_eq!(0, state.round);
+
+    // An explicit seed and round are used even though the object has a recorded sample
+    let explicit = sample(2, false)?;
+    let state = repo
+        .validation_record(object_id)?
+        .unwrap()
+        .fixity_sample
+        .unwrap();
+    assert_eq!(2, state.seed);
+    assert_eq!(0, state.round);
+    assert_eq!(explicit, sample(2, false)?);
+
+    Ok(())
+}
+
+#[test]
+fn audit_trail_records_commits_and_upgrades_in_object_logs() -> Result<()> {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let repo = OcflRepo::init_fs_repo(
+        root.path(),
+        None,
+        SpecVersion::Ocfl1_1,
+        Some(StorageLayout::new(LayoutExtensionName::HashedNTupleLayout, None).unwrap()),
+    )?
+    .with_audit_trail(true);
+
+    let object_id = "audited";
+
+    create_simple_object(object_id, &repo, &temp);
+
+    temp.child("b.txt").write_str("b").unwrap();
+    repo.copy_files_external(object_id, &[temp.child("b.txt").path()], "b.txt", false)?;
+    repo.move_files_internal(object_id, &["test.txt"], "a.txt")?;
+    repo.commit(
+        object_id,
+        CommitMeta::new().with_user(
+            Some("Peter".to_string()),
+            Some("mailto:peter@example.com".to_string()),
+        )?,
+        None,
+        false,
+    )?;
+
+    repo.upgrade_object(object_id, SpecVersion::Ocfl1_1, CommitMeta::new(), false)?;
+
+    assert_eq!(
+        vec!["rocfl-audit-trail.jsonl".to_string()],
+        repo.list_object_logs(object_id)?
+    );
+
+    let entries = repo.list_audit_trail(object_id)?;
+    assert_eq!(3, entries.len());
+
+    assert_eq!("commit", entries[0].operation);
+    assert_eq!(VersionNum::v1(), entries[0].version);
+    assert_eq!(vec!["test.txt".to_string()], entries[0].paths);
+    assert_eq!(None, entries[0].user_name);
+
+    assert_eq!("commit", entries[1].operation);
+    assert_eq!(VersionNum::try_from(2)?, entries[1].version);
+    assert_eq!(
+        vec![
+            "b.txt".to_string(),
+            "test.txt".to_string(),
+            "a.txt".to_string()
+        ],
+        entries[1].paths
+    );
+    assert_eq!(Some("Peter".to_string()), entries[1].user_name);
+    assert_eq!(
+        Some("mailto:peter@example.com".to_string()),
+        entries[1].user_address
+    );
+
+    assert_eq!("upgrade", entries[2].operation);
+    assert_eq!(VersionNum::try_from(3)?, entries[2].version);
+    assert!(entries[2].paths.is_empty());
+
+    for entry in &entries {
+        assert_eq!(
+            format!("rocfl {}", env!("CARGO_PKG_VERSION")),
+            entry.tool_version
+        );
+    }
+
+    let unaudited = default_repo(TempDir::new().unwrap().path());
+    create_simple_object(object_id, &unaudited, &temp);
+    assert!(unaudited.list_audit_trail(object_id)?.is_empty());
+    assert!(unaudited.list_object_logs(object_id)?.is_empty());
+
+    match repo.list_audit_trail("missing") {
+        Err(RocflError::NotFound(_)) => (),
+        _ => panic!("Expected audit trail listing to fail with not found"),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn sign_and_verify_inventory_signatures() -> Result<()> {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+    let keys = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("resources/test/keys");
+
+    let object_id = "signed";
+
+    create_simple_object(object_id, &default_repo(root.path()), &temp);
+
+    let repo = OcflRepo::fs_repo(root.path(), None)?
+        .with_signing_key(SigningKey::from_pem_file(keys.join("signing-key.pem"))?);
+
+    for file in &["b.txt", "c.txt"] {
+        temp.child(file).write_str(file).unwrap();
+        repo.copy_files_external(object_id, &[temp.child(file).path()], file, false)?;
+        commit(object_id, &repo);
+    }
+
+    assert_eq!(2, repo.list_inventory_signatures(object_id)?.len());
+
+    let trusted = VerifyingKey::from_pem_file(keys.join("signing-key.pub.pem"))?;
+    let other = VerifyingKey::from_pem_file(keys.join("other-key.pub.pem"))?;
+
+    let report = repo.verify_signatures(object_id, std::slice::from_ref(&trusted))?;
+    assert_eq!(object_id, report.object_id);
+    assert_eq!(3, report.versions.len());
+    assert_eq!(SignatureStatus::Unsigned, report.versions[0].status);
+    assert_eq!(
+        SignatureStatus::Valid {
+            public_key: trusted.to_base64()
+        },
+        report.versions[1].status
+    );
+    assert_eq!(
+        SignatureStatus::Valid {
+            public_key: trusted.to_base64()
+        },
+        report.versions[2].status
+    );
+    assert!(!report.is_verified());
+    assert!(!report.has_failures());
+
+    let report = repo.verify_signatures(object_id, &[other])?;
+    assert_eq!(
+        SignatureStatus::Untrusted {
+            public_key: trusted.to_base64()
+        },
+        report.versions[2].status
+    );
+    assert!(report.has_failures());
+
+    let object_root = PathBuf::from(
+        repo.get_object_details(object_id, VersionRef::Head)?
+            .object_root,
+    );
+    let v2_inventory = object_root.join("v2").join("inventory.json");
+    let tampered = fs::read_to_string(&v2_inventory)?.replace("b.txt", "x.txt");
+    fs::write(&v2_inventory, tampered)?;
+
+    let report = repo.verify_signatures(object_id, &[trusted])?;
+    assert_eq!(
+        SignatureStatus::Invalid("The inventory does not match its signature".to_string()),
+        report.versions[1].status
+    );
+    assert!(report.has_failures());
+
+    match repo.verify_signatures("missing", &[]) {
+        Err(RocflError::NotFound(_)) => (),
+        _ => panic!("Expected signature verification to fail with not found"),
+    }
+
+    Ok(())
+}
+
+#[test]
+#[cfg(unix)]
+fn commit_hard_links_content_that_duplicates_other_objects() -> Result<()> {
+    use std::os::unix::fs::MetadataExt;
+
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let repo = default_repo(root.path()).with_hard_links(true);
+
+    create_simple_object("original", &repo, &temp);
+    create_object_with_duplicate_content("linked", &repo, &temp);
+
+    commit("linked", &repo);
+
+    let original = repo.get_object("original", VersionRef::Head)?;
+    let linked = repo.get_object("linked", VersionRef::Head)?;
+
+    let existing = fs::metadata(&original.state[&lpath("test.txt")].storage_path)?;
+    let duplicate = fs::metadata(&linked.state[&lpath("dup.txt")].storage_path)?;
+    let unique = fs::metadata(&linked.state[&lpath("unique.txt")].storage_path)?;
+
+    assert_eq!(existing.ino(), duplicate.ino());
+    assert_eq!(2, duplicate.nlink());
+    assert_eq!(1, unique.nlink());
+
+    no_errors(&repo.validate_object("original", true)?);
+    no_errors(&repo.validate_object("linked", true)?);
+
+    Ok(())
+}
+
+#[test]
+#[cfg(unix)]
+fn commit_copies_content_when_hard_link_fails() -> Result<()> {
+    use std::os::unix::fs::MetadataExt;
+
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let repo = default_repo(root.path()).with_hard_links(true);
+
+    create_simple_object("original", &repo, &temp);
+    create_object_with_duplicate_content("copied", &repo, &temp);
+
+    // Linking fails, as it would across filesystems, when the existing file is unavailable
+    let original = repo.get_object("original", VersionRef::Head)?;
+    fs::remove_file(&original.state[&lpath("test.txt")].storage_path)?;
+
+    commit("copied", &repo);
+
+    let copied = repo.get_object("copied", VersionRef::Head)?;
+    let duplicate = &copied.state[&lpath("dup.txt")].storage_path;
+
+    assert_eq!(1, fs::metadata(duplicate)?.nlink());
+    assert_eq!("testing", fs::read_to_string(duplicate)?);
+
+    no_errors(&repo.validate_object("copied", true)?);
+
+    Ok(())
+}
+
+#[test]
+#[cfg(unix)]
+fn commit_does_not_link_content_that_does_not_match_its_digest() -> Result<()> {
+    use std::os::unix::fs::MetadataExt;
+
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let repo = default_repo(root.path()).with_hard_links(true);
+
+    create_simple_object("original", &repo, &temp);
+    create_object_with_duplicate_content("copied", &repo, &temp);
+
+    // Same size, different content
+    let original = repo.get_object("original", VersionRef::Head)?;
+    let existing = &original.state[&lpath("test.txt")].storage_path;
+    fs::write(existing, "TESTING")?;
+
+    commit("copied", &repo);
+
+    let copied = repo.get_object("copied", VersionRef::Head)?;
+    let duplicate = &copied.state[&lpath("dup.txt")].storage_path;
+
+    assert_eq!(1, fs::metadata(duplicate)?.nlink());
+    assert_eq!("testing", fs::read_to_string(duplicate)?);
+    assert_eq!("TESTING", fs::read_to_string(existing)?);
+
+    no_errors(&repo.validate_object("copied", true)?);
+
+    Ok(())
+}
+
+#[test]
+#[cfg(unix)]
+fn commit_hard_links_content_committed_before_links_were_enabled() -> Result<()> {
+    use std::os::unix::fs::MetadataExt;
+
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    create_simple_object("original", &default_repo(root.path()), &temp);
+
+    let repo = OcflRepo::fs_repo(root.path(), None)?.with_hard_links(true);
+
+    create_object_with_duplicate_content("linked", &repo, &temp);
+    commit("linked", &repo);
+
+    let original = repo.get_object("original", VersionRef::Head)?;
+    let linked = repo.get_object("linked", VersionRef::Head)?;
+
+    let existing = fs::metadata(&original.state[&lpath("test.txt")].storage_path)?;
+    let duplicate = fs::metadata(&linked.state[&lpath("dup.txt")].storage_path)?;
+
+    assert_eq!(existing.ino(), duplicate.ino());
+
+    no_errors(&repo.validate_object("linked", true)?);
+
+    Ok(())
+}
+
+#[test]
+fn search_object_content_returns_matching_lines() -> Result<()> {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let repo = default_repo(root.path());
+
+    let object_id = "grep";
+
+    create_example_object(object_id, &repo, &temp);
+
+    let search = |version_num: VersionRef, pattern: &str, path_glob: Option<&str>| {
+        let mut found = Vec::new();
+        repo.search_object_content(object_id, version_num, pattern, path_glob, 1024, |m| {
+            found.push(format!("{}:{}:{}", m.logical_path, m.line_number, m.line));
+            true
+        })?;
+        Ok::<_, RocflError>(found)
+    };
+
+    assert_eq!(
+        vec!["a/b/file2.txt:1:File Two", "file3.txt:1:File Three"],
+        search(VersionRef::Head, "F.+ T", None)?
+    );
+    assert_eq!(
+        vec!["a/b/file2.txt:1:File Two"],
+        search(VersionNum::try_from(2)?.into(), "F.+ T", None)?
+    );
+    assert_eq!(
+        vec![
+            "a/b/c/file4.txt:1:File Four",
+            "a/b/file2.txt:1:File Two",
+            "a/b/file3.txt:1:File Three",
+        ],
+        search(VersionNum::try_from(1)?.into(), "File", Some("a/b/**"))?
+    );
+
+    Ok(())
+}
+
+#[test]
+fn find_paths_across_objects_and_versions() -> Result<()> {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let repo = default_repo(root.path());
+
+    create_example_object("example", &repo, &temp);
+    create_simple_object("simple", &repo, &temp);
+
+    let found = |glob: &str, object_glob: Option<&str>, head_only: bool| -> Result<Vec<String>> {
+        let mut found = Vec::new();
+        for matches in repo.find_paths(glob, object_glob, head_only)? {
+            for path_match in matches? {
+                found.push(format!(
+                    "{} {} {}",
+                    path_match.object_id, path_match.version_num, path_match.logical_path
+                ));
+            }
+        }
+        found.sort();
+        Ok(found)
+    };
+
+    assert_eq!(
+        vec![
+            "example v1 a/b/file3.txt",
+            "example v3 file3.txt",
+            "example v4 file3.txt",
+        ],
+        found("*file3.txt", None, false)?
+    );
+    assert_eq!(
+        vec!["example v4 file3.txt"],
+        found("/*file3.txt", None, true)?
+    );
+    assert_eq!(vec!["simple v1 test.txt"], found("test.txt", None, true)?);
+    assert_eq!(
+        vec![
+            "example v4 something/file1.txt",
+            "example v4 something/new.txt",
+        ],
+        found("something/*", Some("ex*"), true)?
+    );
+    assert!(found("*.tiff", None, false)?.is_empty());
+    assert!(found("*.txt", Some("missing"), false)?.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn search_object_content_skips_binary_and_large_files() -> Result<()> {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let repo = default_repo(root.path());
+
+    let object_id = "grep";
+
+    repo.create_object(object_id, None, DigestAlgorithm::Sha512, "content", 0)?;
+
+    repo.move_files_external(
+        object_id,
+        &[
+            create_file(&temp, "text.txt", "line one\nneedle here\nline three\n").path(),
+            create_file(&temp, "twice.txt", "needle one\nneedle two\n").path(),
+            create_file(&temp, "binary.bin", "needle\0binary").path(),
+            create_file(&temp, "large.txt", &"needle\n".repeat(100)).path(),
+        ],
+        "/",
+    )?;
+    commit(object_id, &repo);
+
+    let search = |first_only: bool| {
+        let mut found = Vec::new();
+        repo.search_object_content(object_id, VersionRef::Head, "needle", None, 100, |m| {
+            found.push(format!("{}:{}:{}", m.logical_path, m.line_number, m.line));
+            !first_only
+        })?;
+        Ok::<_, RocflError>(found)
+    };
+
+    assert_eq!(
+        vec![
+            "text.txt:2:needle here",
+            "twice.txt:1:needle one",
+            "twice.txt:2:needle two",
+        ],
+        search(false)?
+    );
+    assert_eq!(
+        vec!["text.txt:2:needle here", "twice.txt:1:needle one"],
+        search(true)?
+    );
+
+    Ok(())
+}
+
+#[test]
+#[should_panic(expected = "Invalid search pattern")]
+fn fail_search_object_content_when_pattern_invalid() {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let repo = default_repo(root.path());
+
+    let object_id = "grep";
+
+    create_simple_object(object_id, &repo, &temp);
+
+    repo.search_object_content(object_id, VersionRef::Head, "(unclosed", None, 1024, |_| {
+        true
+    })
+    .unwrap();
+}
+
+#[test]
+fn list_object_version_sizes() -> Result<()> {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let repo = default_repo(root.path());
+
+    let object_id = "sizes";
+
+    create_example_object(object_id, &repo, &temp);
+
+    let sizes = repo.list_object_version_sizes(object_id)?;
+
+    assert_eq!(
+        vec![
+            VersionContentSize {
+                version_num: VersionNum::try_from(1)?,
+                file_count: 6,
+                byte_count: 52,
+            },
+            VersionContentSize {
+                version_num: VersionNum::try_from(2)?,
+                file_count: 0,
+                byte_count: 0,
+            },
+        ],
+        sizes[0..2]
+    );
+    assert_eq!(repo.list_object_versions(object_id)?.len(), sizes.len());
+
+    Ok(())
+}
+
+#[test]
+fn init_from_existing_repo_writes_missing_files() -> Result<()> {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+
+    let repo = OcflRepo::init_fs_repo(
+        root.path(),
+        None,
+        SpecVersion::Ocfl1_1,
+        Some(StorageLayout::new(
+            LayoutExtensionName::FlatDirectLayout,
+            None,
+        )?),
+    )?;
+
+    let object_id = "existing";
+    create_simple_object(object_id, &repo, &temp);
+
+    let layout_name = "0002-flat-direct-storage-layout";
+    fs::remove_file(root.child("ocfl_1.1.md").path())?;
+    fs::remove_file(root.child(format!("{}.md", layout_name)).path())?;
+    fs::remove_dir_all(root.child("extensions").child(layout_name).path())?;
+
+    let repo = OcflRepo::init_fs_repo_from_existing(root.path(), None)?;
+
+    root.child("ocfl_1.1.md")
+        .assert(predicates::path::is_file());
+    root.child(format!("{}.md", layout_name))
+        .assert(predicates::path::is_file());
+    assert_layout_extension(
+        &root,
+        layout_name,
+        r#"{
+  "extensionName": "0002-flat-direct-storage-layout"
+}"#,
+    );
+
+    let object = repo.get_object(object_id, VersionRef::Head)?;
+    assert_eq!(1, object.state.len());
+
+    validate_repo(&repo);
+
+    Ok(())
+}
+
+#[test]
+#[should_panic(expected = "does not contain an OCFL version declaration")]
+fn fail_init_from_existing_when_not_ocfl_repo() {
+    let root = TempDir::new().unwrap();
+    root.child("file.txt").write_str("not ocfl").unwrap();
+
+    OcflRepo::init_fs_repo_from_existing(root.path(), None).unwrap();
+}
+
+#[test]
+#[should_panic(expected = "Storage layout extension bogus-layout is not supported")]
+fn fail_init_from_existing_when_layout_unsupported() {
+    let root = TempDir::new().unwrap();
+
+    OcflRepo::init_fs_repo(root.path(), None, SpecVersion::Ocfl1_0, None).unwrap();
+
+    root.child("ocfl_layout.json")
+        .write_str(r#"{"extension": "bogus-layout", "description": "bogus"}"#)
+        .unwrap();
+
+    OcflRepo::init_fs_repo_from_existing(root.path(), None).unwrap();
+}
+
+// TODO When version rewrite is implemented it is no longer safe to assume that logical paths
+//      were mapped directly to content paths. This means that all move/copy operations must
+//      verify that they are not unintentionally overwriting an existing file.
+
+fn validate_repo(repo: &OcflRepo) {
+    let mut validator = repo.validate_repo(true).unwrap();
+
+    if validator.storage_root_result().has_errors() {
+        panic!(
+            "Found validation errors: {:?}",
+            validator.storage_root_result()
+        );
+    }
+
+    for result in &mut validator {
+        let result = result.unwrap();
+        if result.has_errors() {
+            panic!("Found validation errors: {:?}", result);
+        }
+    }
+
+    if validator.storage_hierarchy_result().has_errors() {
+        panic!(
+            "Found validation errors: {:?}",
+            validator.storage_hierarchy_result()
+        );
+    }
+}
+
+fn assert_staged_obj_count(repo: &OcflRepo, count: usize) {
+    assert_eq!(count, repo.list_staged_objects(None).unwrap().count());
+}
+
+fn assert_obj_count(repo: &OcflRepo, count: usize) {
+    assert_eq!(count, repo.list_objects(None).unwrap().count());
+}
+
+fn assert_obj_not_exists(repo: &OcflRepo, object_id: &str) {
+    match repo.get_object(object_id, VersionRef::Head) {
+        Err(RocflError::NotFound(_)) => (),
+        _ => panic!("Expected object '{}' to not be found", object_id),
+    }
+}
+
+fn assert_staged_obj_not_exists(repo: &OcflRepo, object_id: &str) {
+    match repo.get_staged_object(object_id) {
+        Err(RocflError::NotFound(_)) => (),
+        _ => panic!("Expected staged object '{}' to not be found", object_id),
+    }
+}
+
+fn assert_file_not_exists(obj: &ObjectVersion, logical_path: &str, content_path: &str) {
+    assert!(!obj.state.contains_key(&lpath(logical_path)));
+    assert!(!Path::new(&obj.object_root).join(content_path).exists());
+}
+
+fn assert_file_details(
+    actual: &FileDetails,
+    object_root: impl AsRef<Path>,
+    content_path: &str,
+    digest: &str,
+) {
+    assert_eq!(cpath_rc(content_path), actual.content_path);
+    assert_eq!(
+        join(object_root, &content_path.split('/').collect::<Vec<&str>>())
+            .to_string_lossy()
+            .to_string(),
+        actual.storage_path
+    );
+
+    assert!(
+        Path::new(&actual.storage_path).is_file(),
+        "Expected {} to exist and be a file",
+        actual.storage_path
+    );
+    if digest.len() == 64 {
+        assert_eq!(
+            digest,
+            DigestAlgorithm::Sha256
+                .hash_hex(&mut File::open(&actual.storage_path).unwrap())
+                .unwrap()
+                .as_ref()
+        )
+    } else {
+        assert_eq!(
+            digest,
+            DigestAlgorithm::Sha512
+                .hash_hex(&mut File::open(&actual.storage_path).unwrap())
+                .unwrap()
+                .as_ref()
+        )
+    }
+    assert_eq!(Rc::new(digest.into()), actual.digest);
+}
+
+fn assert_deduped_path(
+    object_root: impl AsRef<Path>,
+    details: &FileDetails,
+    possible_paths: &[&str],
 ) -> Rc<ContentPath> {
     assert!(possible_paths.contains(&(*details.content_path).as_ref()));
 
@@ -5002,6 +9441,25 @@ fn assert_layout_extension(root: &TempDir, layout_name: &str, config: &str) {
         .assert(config);
 }
 
+/// Creates an in-memory tar archive. Entries with paths that end in `/` are directories.
+fn tar_archive(entries: &[(&str, &str)]) -> Vec<u8> {
+    let mut builder = tar::Builder::new(Vec::new());
+
+    for (path, content) in entries {
+        let mut header = tar::Header::new_gnu();
+        if path.ends_with('/') {
+            header.set_entry_type(tar::EntryType::Directory);
+        }
+        header.set_size(content.len() as u64);
+        header.set_mode(0o644);
+        builder
+            .append_data(&mut header, path, content.as_bytes())
+            .unwrap();
+    }
+
+    builder.into_inner().unwrap()
+}
+
 fn create_simple_object(object_id: &str, repo: &OcflRepo, temp: &TempDir) {
     repo.create_object(
         object_id,
@@ -5024,6 +9482,31 @@ fn create_simple_object(object_id: &str, repo: &OcflRepo, temp: &TempDir) {
     commit(object_id, repo);
 }
 
+#[cfg(unix)]
+fn create_object_with_duplicate_content(object_id: &str, repo: &OcflRepo, temp: &TempDir) {
+    repo.create_object(
+        object_id,
+        Some(SpecVersion::Ocfl1_0),
+        DigestAlgorithm::Sha512,
+        "content",
+        0,
+    )
+    .unwrap();
+
+    temp.child("dup.txt").write_str("testing").unwrap();
+    temp.child("unique.txt").write_str("unique").unwrap();
+    repo.copy_files_external(
+        object_id,
+        &[
+            temp.child("dup.txt").path(),
+            temp.child("unique.txt").path(),
+        ],
+        "/",
+        false,
+    )
+    .unwrap();
+}
+
 /// # v1
 ///
 /// - a/file1.txt
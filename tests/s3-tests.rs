@@ -11,6 +11,7 @@
 use std::panic::UnwindSafe;
 use std::path::{Path, PathBuf};
 use std::rc::Rc;
+use std::time::Duration;
 use std::{env, fs, panic};
 
 use assert_fs::prelude::*;
@@ -20,8 +21,8 @@ use fs_extra::dir::CopyOptions;
 use rand::Rng;
 use rocfl::ocfl::{
     CommitMeta, DigestAlgorithm, ErrorCode, FileDetails, LayoutExtensionName, OcflRepo,
-    ProblemLocation, RocflError, SpecVersion, StorageLayout, ValidationError, VersionNum,
-    VersionRef, WarnCode,
+    ProblemLocation, RocflError, S3ClientOptions, SpecVersion, StorageLayout, ValidationError,
+    VersionNum, VersionRef, WarnCode,
 };
 use rusoto_core::Region;
 use rusoto_s3::{
@@ -616,6 +617,142 @@ fn purge_object_when_not_exists() {
     );
 }
 
+#[test]
+fn stage_content_in_bucket() {
+    skip_or_run_s3_test(
+        "stage_content_in_bucket",
+        |_s3_client: S3Client, prefix: String, staging: TempDir, temp: TempDir| {
+            let repo = default_repo(&prefix, staging.path()).with_bucket_staging(true);
+            let object_id = "bucket-staging";
+
+            repo.create_object(
+                object_id,
+                Some(SpecVersion::Ocfl1_0),
+                DigestAlgorithm::Sha256,
+                "content",
+                0,
+            )
+            .unwrap();
+            repo.copy_files_external(
+                object_id,
+                &[
+                    create_file(&temp, "a.txt", "file a").path(),
+                    create_file(&temp, "b.txt", "file b").path(),
+                ],
+                "/",
+                false,
+            )
+            .unwrap();
+            repo.copy_files_internal(object_id, VersionRef::Head, &["a.txt"], "c.txt", false)
+                .unwrap();
+            repo.move_files_internal(object_id, &["b.txt"], "d.txt")
+                .unwrap();
+
+            let staged_root = PathBuf::from(repo.get_staged_object(object_id).unwrap().object_root);
+            assert!(!staged_root.join("v1/content/a.txt").exists());
+
+            let mut out: Vec<u8> = Vec::new();
+            repo.get_staged_object_file(object_id, &"d.txt".try_into().unwrap(), &mut out)
+                .unwrap();
+            assert_eq!("file b", String::from_utf8(out).unwrap());
+
+            no_errors(&repo.validate_staged(object_id).unwrap());
+            assert!(!staged_root.join("v1/content/a.txt").exists());
+
+            repo.commit(object_id, CommitMeta::new(), None, false)
+                .unwrap();
+
+            no_errors(&repo.validate_object(object_id, true).unwrap());
+            assert_eq!(
+                0,
+                repo.list_abandoned_staging(Duration::ZERO).unwrap().len()
+            );
+
+            let mut out: Vec<u8> = Vec::new();
+            repo.get_object_file(
+                object_id,
+                &"c.txt".try_into().unwrap(),
+                VersionRef::Head,
+                &mut out,
+            )
+            .unwrap();
+            assert_eq!("file a", String::from_utf8(out).unwrap());
+
+            repo.copy_files_external(
+                object_id,
+                &[create_file(&temp, "e.txt", "file e").path()],
+                "/",
+                false,
+            )
+            .unwrap();
+            let staged_root = PathBuf::from(repo.get_staged_object(object_id).unwrap().object_root);
+            fs::remove_dir_all(staged_root).unwrap();
+
+            assert_eq!(1, repo.rm_abandoned_staging(Duration::ZERO).unwrap().len());
+            assert_eq!(
+                0,
+                repo.list_abandoned_staging(Duration::ZERO).unwrap().len()
+            );
+        },
+    );
+}
+
+#[test]
+fn abandoned_staging_only_includes_content_staged_by_the_staging_root() {
+    skip_or_run_s3_test(
+        "abandoned_staging_only_includes_content_staged_by_the_staging_root",
+        |_s3_client: S3Client, prefix: String, staging: TempDir, temp: TempDir| {
+            let other_staging = TempDir::new().unwrap();
+
+            let repo = default_repo(&prefix, staging.path()).with_bucket_staging(true);
+            let other = OcflRepo::s3_repo(
+                REGION,
+                &bucket(),
+                Some(&prefix),
+                other_staging.path(),
+                None,
+                &S3ClientOptions::default(),
+            )
+            .unwrap()
+            .with_bucket_staging(true);
+
+            let object_id = "bucket-staging-owner";
+
+            for (repo, content) in [(&repo, "file a"), (&other, "other file a")] {
+                repo.create_object(
+                    object_id,
+                    Some(SpecVersion::Ocfl1_0),
+                    DigestAlgorithm::Sha256,
+                    "content",
+                    0,
+                )
+                .unwrap();
+                repo.copy_files_external(
+                    object_id,
+                    &[create_file(&temp, "a.txt", content).path()],
+                    "/",
+                    false,
+                )
+                .unwrap();
+            }
+
+            let other_root = PathBuf::from(other.get_staged_object(object_id).unwrap().object_root);
+            fs::remove_dir_all(other_root).unwrap();
+
+            assert_eq!(
+                0,
+                repo.list_abandoned_staging(Duration::ZERO).unwrap().len()
+            );
+            assert_eq!(1, other.rm_abandoned_staging(Duration::ZERO).unwrap().len());
+
+            let mut out: Vec<u8> = Vec::new();
+            repo.get_staged_object_file(object_id, &"a.txt".try_into().unwrap(), &mut out)
+                .unwrap();
+            assert_eq!("file a", String::from_utf8(out).unwrap());
+        },
+    );
+}
+
 #[test]
 #[should_panic(
     expected = "Cannot create version v2 in object out-of-sync because the current version is at v2"
@@ -916,6 +1053,7 @@ fn init_repo(prefix: &str, staging: impl AsRef<Path>, layout: Option<StorageLayo
         staging,
         SpecVersion::Ocfl1_0,
         layout,
+        &S3ClientOptions::default(),
     )
     .unwrap()
 }
@@ -1,9 +1,14 @@
+use std::fs;
 use std::path::{Path, PathBuf};
 
+use assert_fs::TempDir;
 use common::*;
+use fs_extra::dir::CopyOptions;
 use rocfl::ocfl::{
-    ErrorCode, ObjectValidationResult, OcflRepo, ProblemLocation, ValidationError,
-    ValidationResult, ValidationWarning, WarnCode,
+    parse_inventory, CommitMeta, DigestAlgorithm, ErrorCode, FixityCheck, FixitySample,
+    LayoutExtensionName, ObjectValidationResult, OcflRepo, ProblemLocation, RepoInitOptions,
+    SpecVersion, StorageLayout, ValidationError, ValidationFilter, ValidationResult,
+    ValidationWarning, WarnCode,
 };
 
 mod common;
@@ -209,10 +214,16 @@ fn invalid_padded_head_version() {
 
     has_errors(
         &result,
-        &[root_error(
-            ErrorCode::E013,
-            "Inventory 'versions' contains inconsistently padded version numbers",
-        )],
+        &[
+            root_error(
+                ErrorCode::E013,
+                "Inventory 'versions' contains inconsistently padded version numbers",
+            ),
+            root_error(
+                ErrorCode::E011,
+                "Zero-padded version directory 'v10' does not start with a zero",
+            ),
+        ],
     );
     has_warnings(
         &result,
@@ -223,6 +234,76 @@ fn invalid_padded_head_version() {
     )
 }
 
+#[test]
+fn version_dirs_with_inconsistent_padding() {
+    let root = TempDir::new().unwrap();
+    copy_official_valid_object("spec-ex-full", root.path());
+    let object_root = root.path().join("spec-ex-full");
+    fs::rename(object_root.join("v3"), object_root.join("v003")).unwrap();
+
+    let result = new_repo(root.path())
+        .validate_object_at("spec-ex-full", true)
+        .unwrap();
+
+    has_errors(
+        &result,
+        &[
+            root_error(
+                ErrorCode::E012,
+                "Object root contains version directories that are not padded to 4 characters: v1, v2",
+            ),
+            root_error(
+                ErrorCode::E001,
+                "Unexpected file in object root: v003",
+            ),
+            root_error(
+                ErrorCode::E010,
+                "Object root does not contain version directory 'v3'",
+            ),
+        ],
+    );
+    has_warnings(
+        &result,
+        &[
+            root_warning(WarnCode::W001, "Contains zero-padded version directories"),
+            version_warning("v3", WarnCode::W010, "Inventory file does not exist"),
+        ],
+    );
+}
+
+#[test]
+fn version_dirs_padded_differently_than_inventory() {
+    let root = TempDir::new().unwrap();
+    copy_official_valid_object("minimal_one_version_one_file", root.path());
+    let object_root = root.path().join("minimal_one_version_one_file");
+    fs::rename(object_root.join("v1"), object_root.join("v01")).unwrap();
+
+    let result = new_repo(root.path())
+        .validate_object_at("minimal_one_version_one_file", true)
+        .unwrap();
+
+    has_errors(
+        &result,
+        &[
+            root_error(
+                ErrorCode::E012,
+                "Version directories are not padded the same as the inventory's version keys. \
+                Expected: v1; Found: v01",
+            ),
+            root_error(ErrorCode::E001, "Unexpected file in object root: v01"),
+            root_error(
+                ErrorCode::E010,
+                "Object root does not contain version directory 'v1'",
+            ),
+            root_error(
+                ErrorCode::E092,
+                "Inventory manifest references a file that does not exist in a content directory: \
+                v1/content/a_file.txt",
+            ),
+        ],
+    );
+}
+
 #[test]
 fn content_not_in_content_dir() {
     let result = official_error_test("E015_content_not_in_content_dir");
@@ -1384,7 +1465,7 @@ fn validate_object_does_not_exist() {
 
 #[test]
 fn validate_valid_repo() {
-    let repo = new_repo(&repo_test_path("valid"));
+    let repo = new_repo(repo_test_path("valid"));
     let mut validator = repo.validate_repo(true).unwrap();
 
     no_errors_storage(validator.storage_root_result());
@@ -1400,9 +1481,88 @@ fn validate_valid_repo() {
     no_warnings_storage(validator.storage_hierarchy_result());
 }
 
+#[test]
+fn validate_repo_filtered_by_prefix_and_id_glob() {
+    let repo = new_repo(repo_test_path("valid"));
+
+    let validated_ids = |filter: ValidationFilter| {
+        let mut validator = repo.validate_repo_filtered(true, filter).unwrap();
+        no_errors_storage(validator.storage_root_result());
+
+        let mut ids: Vec<String> = (&mut validator)
+            .map(|result| result.unwrap().object_id.unwrap())
+            .collect();
+        ids.sort();
+
+        no_errors_storage(validator.storage_hierarchy_result());
+        ids
+    };
+
+    assert_eq!(
+        vec!["urn:example:rocfl:obj-2", "urn:example:rocfl:obj-3"],
+        validated_ids(ValidationFilter::new().with_prefix("b"))
+    );
+    assert_eq!(
+        vec!["urn:example:rocfl:obj-1"],
+        validated_ids(ValidationFilter::new().with_prefix("/e84/b88/"))
+    );
+    assert_eq!(
+        vec!["urn:example:rocfl:obj-1", "urn:example:rocfl:obj-2"],
+        validated_ids(ValidationFilter::new().with_id_glob("*obj-[12]").unwrap())
+    );
+    assert_eq!(
+        vec!["urn:example:rocfl:obj-2"],
+        validated_ids(
+            ValidationFilter::new()
+                .with_prefix("b")
+                .with_id_glob("*obj-[12]")
+                .unwrap()
+        )
+    );
+    assert!(validated_ids(ValidationFilter::new().with_prefix("c")).is_empty());
+    assert_eq!(
+        vec!["urn:example:rocfl:obj-3"],
+        validated_ids(ValidationFilter::new().with_excluded_ids([
+            "urn:example:rocfl:obj-1".to_string(),
+            "urn:example:rocfl:obj-2".to_string()
+        ]))
+    );
+}
+
+#[test]
+fn validate_invalid_repo_filtered_by_prefix_skips_other_branches() {
+    let repo = new_repo(repo_test_path("invalid"));
+    let mut validator = repo
+        .validate_repo_filtered(true, ValidationFilter::new().with_prefix("e84"))
+        .unwrap();
+
+    has_errors_storage(
+        validator.storage_root_result(),
+        &[
+            ValidationError::new(
+                ProblemLocation::StorageRoot,
+                ErrorCode::E069,
+                "Root version declaration does not exist".to_string(),
+            ),
+            ValidationError::new(
+                ProblemLocation::StorageRoot,
+                ErrorCode::E112,
+                "Extensions directory contains an illegal file: file.txt".to_string(),
+            ),
+        ],
+    );
+
+    let ids: Vec<String> = (&mut validator)
+        .map(|result| result.unwrap().object_id.unwrap())
+        .collect();
+    assert_eq!(vec!["urn:example:rocfl:obj-1"], ids);
+
+    no_errors_storage(validator.storage_hierarchy_result());
+}
+
 #[test]
 fn validate_invalid_repo() {
-    let repo = new_repo(&repo_test_path("invalid"));
+    let repo = new_repo(repo_test_path("invalid"));
     let mut validator = repo.validate_repo(true).unwrap();
 
     has_errors_storage(
@@ -1474,9 +1634,53 @@ fn validate_invalid_repo() {
     no_warnings_storage(validator.storage_hierarchy_result());
 }
 
+#[test]
+fn resume_repo_validation_from_checkpoint() {
+    let repo = new_repo(repo_test_path("invalid"));
+
+    let mut validator = repo.validate_repo(false).unwrap();
+    let mut expected_ids: Vec<String> = (&mut validator)
+        .map(|result| result.unwrap().object_id.unwrap())
+        .collect();
+    expected_ids.sort();
+    let expected_errors = validator.storage_hierarchy_result().errors().len();
+
+    let mut validator = repo.validate_repo(false).unwrap();
+    let mut ids = vec![validator.next().unwrap().unwrap().object_id.unwrap()];
+    let checkpoint = validator.checkpoint();
+    drop(validator);
+
+    let mut validator = repo.validate_repo(false).unwrap();
+    validator.resume(checkpoint);
+    for result in &mut validator {
+        ids.push(result.unwrap().object_id.unwrap());
+    }
+    ids.sort();
+
+    assert_eq!(expected_ids, ids);
+    assert_eq!(
+        expected_errors,
+        validator.storage_hierarchy_result().errors().len()
+    );
+    let stray = ValidationError::new(
+        ProblemLocation::StorageHierarchy,
+        ErrorCode::E072,
+        "Found a file in the storage hierarchy: b01/0ba/world.txt".to_string(),
+    );
+    assert_eq!(
+        1,
+        validator
+            .storage_hierarchy_result()
+            .errors()
+            .iter()
+            .filter(|error| **error == stray)
+            .count()
+    );
+}
+
 #[test]
 fn multiple_root_version_declarations() {
-    let repo = new_repo(&repo_test_path("multiple-root-decls"));
+    let repo = new_repo(repo_test_path("multiple-root-decls"));
     let mut validator = repo.validate_repo(true).unwrap();
 
     has_errors_storage(
@@ -1499,6 +1703,283 @@ fn multiple_root_version_declarations() {
     no_warnings_storage(validator.storage_hierarchy_result());
 }
 
+#[test]
+fn fixity_sample_checks_subset_of_content_files() {
+    let repo = new_repo(official_valid_root());
+    let name = "updates_all_actions";
+
+    let full = repo.validate_object_at(name, true).unwrap();
+    let total = full.fixity_checked().len();
+    assert!(total > 1);
+
+    let sample = |seed, round| {
+        FixityCheck::Sample(FixitySample {
+            percent: 50,
+            seed,
+            round,
+            continue_recorded: false,
+        })
+    };
+
+    let round_0 = repo.validate_object_at(name, sample(7, 0)).unwrap();
+    let round_1 = repo.validate_object_at(name, sample(7, 1)).unwrap();
+    let repeated = repo.validate_object_at(name, sample(7, 0)).unwrap();
+
+    no_errors(&round_0);
+    no_errors(&round_1);
+
+    let expected_count = total.div_ceil(2);
+    assert_eq!(expected_count, round_0.fixity_checked().len());
+    assert_eq!(expected_count, round_1.fixity_checked().len());
+    assert_eq!(round_0.fixity_checked(), repeated.fixity_checked());
+
+    let mut covered: Vec<&String> = round_0
+        .fixity_checked()
+        .iter()
+        .chain(round_1.fixity_checked())
+        .collect();
+    covered.sort();
+    covered.dedup();
+
+    let mut all: Vec<&String> = full.fixity_checked().iter().collect();
+    all.sort();
+
+    assert_eq!(all, covered);
+}
+
+#[test]
+fn no_fixity_check_checks_no_files() {
+    let repo = new_repo(official_valid_root());
+
+    let result = repo
+        .validate_object_at("updates_all_actions", FixityCheck::None)
+        .unwrap();
+
+    no_errors(&result);
+    assert!(result.fixity_checked().is_empty());
+}
+
+#[test]
+fn parallel_fixity_check_reports_results_in_content_order() {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+    let object_id = "many-files";
+
+    let repo = OcflRepo::init_fs_repo(
+        root.path(),
+        None,
+        SpecVersion::Ocfl1_1,
+        Some(StorageLayout::new(LayoutExtensionName::FlatDirectLayout, None).unwrap()),
+    )
+    .unwrap();
+
+    let files: Vec<_> = (0..64)
+        .map(|i| create_file(&temp, &format!("file-{:02}.txt", i), &format!("file {}", i)))
+        .collect();
+    let paths: Vec<&Path> = files.iter().map(|file| file.path()).collect();
+
+    repo.create_object(object_id, None, DigestAlgorithm::Sha512, "content", 0)
+        .unwrap();
+    repo.copy_files_external(object_id, &paths, "/", false)
+        .unwrap();
+    repo.commit(object_id, CommitMeta::new(), None, false)
+        .unwrap();
+
+    for i in [3, 17, 42, 63] {
+        std::fs::write(
+            root.path()
+                .join(object_id)
+                .join(format!("v1/content/file-{:02}.txt", i)),
+            "corrupt",
+        )
+        .unwrap();
+    }
+
+    let first = repo.validate_object(object_id, true).unwrap();
+    let second = repo.validate_object(object_id, true).unwrap();
+
+    assert_eq!(64, first.fixity_checked().len());
+    assert_eq!(first.fixity_checked(), second.fixity_checked());
+    assert_eq!(first.errors(), second.errors());
+    error_count(4, &first);
+
+    // Errors are reported in the order the files were selected, not the order they finished
+    let corrupt: Vec<&str> = first
+        .fixity_checked()
+        .iter()
+        .map(String::as_str)
+        .filter(|path| ["03", "17", "42", "63"].iter().any(|i| path.contains(i)))
+        .collect();
+    let reported: Vec<&str> = first
+        .errors()
+        .iter()
+        .map(|error| error.text.split(' ').nth(2).unwrap())
+        .collect();
+
+    assert_eq!(corrupt, reported);
+}
+
+#[test]
+fn validate_object_detects_duplicate_at_other_layout_path() {
+    let root = TempDir::new().unwrap();
+    let temp = TempDir::new().unwrap();
+    let object_id = "duplicate-object";
+
+    let repo = OcflRepo::init_fs_repo(
+        root.path(),
+        None,
+        SpecVersion::Ocfl1_1,
+        Some(StorageLayout::new(LayoutExtensionName::HashedNTupleLayout, None).unwrap()),
+    )
+    .unwrap();
+
+    let file = create_file(&temp, "file.txt", "contents");
+    repo.create_object(object_id, None, DigestAlgorithm::Sha512, "content", 0)
+        .unwrap();
+    repo.copy_files_external(object_id, &[file.path()], "/", false)
+        .unwrap();
+    repo.commit(object_id, CommitMeta::new(), None, false)
+        .unwrap();
+
+    let result = repo.validate_object(object_id, true).unwrap();
+    no_errors(&result);
+
+    // Simulates a copy left behind by migrating from a flat layout
+    let object_root = StorageLayout::new(LayoutExtensionName::HashedNTupleLayout, None)
+        .unwrap()
+        .map_object_id(object_id);
+    let mut options = CopyOptions::new();
+    options.copy_inside = true;
+    fs_extra::dir::copy(
+        root.path().join(object_root),
+        root.path().join(object_id),
+        &options,
+    )
+    .unwrap();
+
+    let result = repo.validate_object(object_id, true).unwrap();
+
+    has_errors(
+        &result,
+        &[root_error(
+            ErrorCode::E037,
+            "Found duplicate object duplicate-object at duplicate-object",
+        )],
+    );
+}
+
+#[test]
+fn validate_repo_warns_when_spec_copy_missing_or_mismatched() {
+    let root = TempDir::new().unwrap();
+
+    let repo = OcflRepo::init_fs_repo_with_options(
+        root.path(),
+        None,
+        SpecVersion::Ocfl1_1,
+        Some(StorageLayout::new(LayoutExtensionName::HashedNTupleLayout, None).unwrap()),
+        RepoInitOptions { spec_copy: false },
+    )
+    .unwrap();
+
+    assert!(!root.path().join("ocfl_1.1.md").exists());
+
+    let validator = repo.validate_repo(true).unwrap();
+
+    no_errors_storage(validator.storage_root_result());
+    has_warnings_storage(
+        validator.storage_root_result(),
+        &[ValidationWarning::new(
+            ProblemLocation::StorageRoot,
+            WarnCode::RW004,
+            "Storage root does not contain a copy of the OCFL spec: ocfl_1.1.md".to_string(),
+        )],
+    );
+
+    std::fs::write(root.path().join("ocfl_1.1.md"), "not the spec").unwrap();
+
+    let validator = repo.validate_repo(true).unwrap();
+
+    no_errors_storage(validator.storage_root_result());
+    has_warnings_storage(
+        validator.storage_root_result(),
+        &[ValidationWarning::new(
+            ProblemLocation::StorageRoot,
+            WarnCode::RW004,
+            "Storage root copy of the OCFL spec does not match OCFL 1.1: ocfl_1.1.md".to_string(),
+        )],
+    );
+}
+
+#[test]
+fn validate_repo_warns_when_objects_are_top_level_and_nested() {
+    let root = TempDir::new().unwrap();
+    let mut options = CopyOptions::new();
+    options.content_only = true;
+    fs_extra::dir::copy(repo_test_path("valid"), root.path(), &options).unwrap();
+
+    let object_root = "e84b88ab161ae3c37e622100a59f287fab8383882219691ec921e506eb125a4f";
+    std::fs::rename(
+        root.path().join("e84/b88/ab1").join(object_root),
+        root.path().join(object_root),
+    )
+    .unwrap();
+    std::fs::remove_dir_all(root.path().join("e84")).unwrap();
+
+    let repo = new_repo(root.path());
+    let mut validator = repo.validate_repo(false).unwrap();
+
+    for result in &mut validator {
+        no_errors(&result.unwrap());
+    }
+
+    no_errors_storage(validator.storage_hierarchy_result());
+    has_warnings_storage(
+        validator.storage_hierarchy_result(),
+        &[ValidationWarning::new(
+            ProblemLocation::StorageHierarchy,
+            WarnCode::W015,
+            format!("Storage hierarchy mixes top-level objects (1) and objects within a directory hierarchy (2). \
+            Objects directly under the storage root that do not follow the declared layout 0004-hashed-n-tuple-storage-layout: {}", object_root),
+        )],
+    );
+}
+
+#[test]
+fn parse_inventory_without_repo() {
+    let bytes = std::fs::read(official_valid_root().join("spec-ex-full/inventory.json")).unwrap();
+    let (inventory, diagnostics) = parse_inventory(&bytes);
+    let inventory = inventory.unwrap();
+
+    assert_eq!("ark:/12345/bcd987", inventory.id);
+    assert_eq!(3, inventory.versions.len());
+    assert_eq!(Some("ark:/12345/bcd987"), diagnostics.object_id.as_deref());
+    assert!(!diagnostics.has_errors_or_warnings());
+
+    let bytes =
+        std::fs::read(official_warn_root().join("W007_no_message_or_user/inventory.json")).unwrap();
+    let (inventory, diagnostics) = parse_inventory(&bytes);
+
+    assert!(inventory.is_ok());
+    assert!(!diagnostics.has_errors());
+    assert!(diagnostics.warnings().contains(&root_warning(
+        WarnCode::W007,
+        "Inventory version 'v1' is missing recommended key 'message'"
+    )));
+
+    let bytes =
+        std::fs::read(official_error_root().join("E040_wrong_head_format/inventory.json")).unwrap();
+    let (inventory, diagnostics) = parse_inventory(&bytes);
+
+    assert!(inventory.is_err());
+    assert_eq!(
+        &[root_error(
+            ErrorCode::E040,
+            "Inventory 'head' must be a string"
+        )],
+        diagnostics.errors()
+    );
+}
+
 fn official_valid_test(name: &str) -> ObjectValidationResult {
     let repo = new_repo(official_valid_root());
     repo.validate_object_at(name, true).unwrap()
@@ -1526,6 +2007,10 @@ fn new_repo(root: impl AsRef<Path>) -> OcflRepo {
     OcflRepo::fs_repo(root, None).unwrap()
 }
 
+fn copy_official_valid_object(name: &str, dst: impl AsRef<Path>) {
+    fs_extra::dir::copy(official_valid_root().join(name), dst, &CopyOptions::new()).unwrap();
+}
+
 fn official_valid_root() -> PathBuf {
     let mut path = validate_repo_root();
     path.push("official-1.0");